@@ -0,0 +1,313 @@
+//! Layered settings shared by every blizz tool
+//!
+//! Each tool declares the settings it reads as [`Key`]s. A key's value comes from its
+//! environment variable when that is set, otherwise from `$BLIZZ_HOME/config.yaml`,
+//! otherwise the reading code falls back to its built-in default. The file nests keys
+//! by their dotted names:
+//!
+//! ```yaml
+//! insights:
+//!   server_url: http://localhost:4000
+//!   rerank:
+//!     final_limit: 12
+//! ```
+//!
+//! [`var`] and [`get`] read a key the way `std::env::var` used to, so a tool moving a
+//! setting here keeps its own parsing, validation and default.
+
+use anyhow::{anyhow, Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Name of the settings file inside the blizz home
+pub const FILE_NAME: &str = "config.yaml";
+
+// Keys
+// ====
+
+/// A setting a tool reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+  /// Dotted name in the settings file, e.g. `insights.server_url`
+  pub name: &'static str,
+  /// Environment variable that overrides the file
+  pub env: &'static str,
+  /// The built-in default, as shown to users
+  pub default: &'static str,
+  pub description: &'static str,
+}
+
+impl Key {
+  pub const fn new(
+    name: &'static str,
+    env: &'static str,
+    default: &'static str,
+    description: &'static str,
+  ) -> Self {
+    Self { name, env, default, description }
+  }
+}
+
+/// Where a key's value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layer {
+  Default,
+  File(PathBuf),
+  Env(&'static str),
+}
+
+impl std::fmt::Display for Layer {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Layer::Default => write!(f, "default"),
+      Layer::File(path) => write!(f, "{}", path.display()),
+      Layer::Env(var) => write!(f, "${var}"),
+    }
+  }
+}
+
+// Settings File
+// =============
+
+/// The blizz home: `BLIZZ_HOME`, or `~/.blizz`
+pub fn home() -> PathBuf {
+  match std::env::var("BLIZZ_HOME") {
+    Ok(home) if !home.trim().is_empty() => PathBuf::from(home),
+    _ => dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".blizz"),
+  }
+}
+
+/// Values stored in a settings file, by dotted name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigFile {
+  path: PathBuf,
+  values: BTreeMap<String, String>,
+}
+
+impl ConfigFile {
+  /// The settings file in the blizz home
+  pub fn load() -> Result<Self> {
+    Self::at(home().join(FILE_NAME))
+  }
+
+  /// The settings file at `path`, empty if it doesn't exist yet
+  pub fn at(path: impl Into<PathBuf>) -> Result<Self> {
+    let path = path.into();
+    let mut values = BTreeMap::new();
+    if path.exists() {
+      let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read settings file: {}", path.display()))?;
+      if !content.trim().is_empty() {
+        let root: Value = serde_yaml::from_str(&content)
+          .with_context(|| format!("Failed to parse settings file: {}", path.display()))?;
+        flatten("", &root, &mut values);
+      }
+    }
+    Ok(Self { path, values })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  pub fn get(&self, name: &str) -> Option<&str> {
+    self.values.get(name).map(String::as_str)
+  }
+
+  /// Every stored value, sorted by name
+  pub fn values(&self) -> &BTreeMap<String, String> {
+    &self.values
+  }
+
+  pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+    if name.split('.').any(|part| part.trim().is_empty()) {
+      return Err(anyhow!("'{name}' is not a valid setting name"));
+    }
+    let nested = format!("{name}.");
+    if let Some(existing) = self
+      .values
+      .keys()
+      .find(|existing| existing.starts_with(&nested) || name.starts_with(&format!("{existing}.")))
+    {
+      return Err(anyhow!("'{name}' conflicts with the existing setting '{existing}'"));
+    }
+    self.values.insert(name.to_string(), value.to_string());
+    Ok(())
+  }
+
+  /// Remove a value, returning whether it was set
+  pub fn unset(&mut self, name: &str) -> bool {
+    self.values.remove(name).is_some()
+  }
+
+  /// Write the values back, nested by their dotted names
+  pub fn save(&self) -> Result<()> {
+    let mut root = Mapping::new();
+    for (name, value) in &self.values {
+      insert(&mut root, name, scalar(value));
+    }
+    if let Some(dir) = self.path.parent() {
+      fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    }
+    let content = serde_yaml::to_string(&Value::Mapping(root))?;
+    fs::write(&self.path, content)
+      .with_context(|| format!("Failed to write settings file: {}", self.path.display()))
+  }
+
+  /// A key's value and the layer it came from, `None` when only the default applies
+  pub fn resolve(&self, key: &Key) -> (Option<String>, Layer) {
+    if let Ok(value) = std::env::var(key.env) {
+      return (Some(value), Layer::Env(key.env));
+    }
+    match self.get(key.name) {
+      Some(value) => (Some(value.to_string()), Layer::File(self.path.clone())),
+      None => (None, Layer::Default),
+    }
+  }
+}
+
+fn flatten(prefix: &str, value: &Value, values: &mut BTreeMap<String, String>) {
+  let text = match value {
+    Value::Mapping(mapping) => {
+      for (name, value) in mapping {
+        let name = match name {
+          Value::String(name) => name.clone(),
+          other => scalar_text(other).unwrap_or_default(),
+        };
+        let name = if prefix.is_empty() { name } else { format!("{prefix}.{name}") };
+        flatten(&name, value, values);
+      }
+      return;
+    }
+    Value::Sequence(items) => {
+      Some(items.iter().filter_map(scalar_text).collect::<Vec<_>>().join(","))
+    }
+    other => scalar_text(other),
+  };
+  if let Some(text) = text {
+    values.insert(prefix.to_string(), text);
+  }
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+  match value {
+    Value::String(text) => Some(text.clone()),
+    Value::Number(number) => Some(number.to_string()),
+    Value::Bool(flag) => Some(flag.to_string()),
+    _ => None,
+  }
+}
+
+/// A stored value as YAML, keeping numbers and booleans unquoted
+fn scalar(value: &str) -> Value {
+  match serde_yaml::from_str::<Value>(value) {
+    Ok(parsed @ (Value::Number(_) | Value::Bool(_))) => parsed,
+    _ => Value::String(value.to_string()),
+  }
+}
+
+fn insert(mapping: &mut Mapping, name: &str, value: Value) {
+  match name.split_once('.') {
+    None => {
+      mapping.insert(Value::String(name.to_string()), value);
+    }
+    Some((head, rest)) => {
+      let child = mapping
+        .entry(Value::String(head.to_string()))
+        .or_insert_with(|| Value::Mapping(Mapping::new()));
+      if let Value::Mapping(child) = child {
+        insert(child, rest, value);
+      }
+    }
+  }
+}
+
+// Reading Settings
+// ================
+
+/// A key's value from the environment or the settings file
+///
+/// A settings file that can't be read is treated as empty.
+pub fn var(key: &Key) -> Option<String> {
+  if let Ok(value) = std::env::var(key.env) {
+    return Some(value);
+  }
+  ConfigFile::load().ok()?.get(key.name).map(str::to_string)
+}
+
+/// A key's value parsed as `T`, `None` when unset or unparsable
+pub fn get<T: FromStr>(key: &Key) -> Option<T> {
+  var(key).and_then(|value| value.trim().parse().ok())
+}
+
+// Tests
+// =====
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const LIMIT: Key = Key::new("tool.search.limit", "BENTLEY_TEST_LIMIT", "8", "Results per page");
+
+  #[test]
+  fn test_file_round_trips_nested_values() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("home").join(FILE_NAME);
+
+    let mut file = ConfigFile::at(&path).unwrap();
+    assert!(file.values().is_empty());
+    file.set("tool.search.limit", "12").unwrap();
+    file.set("tool.url", "http://localhost:4000").unwrap();
+    file.set("tool.strict", "true").unwrap();
+    file.save().unwrap();
+
+    let written = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+      written,
+      "tool:\n  search:\n    limit: 12\n  strict: true\n  url: http://localhost:4000\n"
+    );
+    let reloaded = ConfigFile::at(&path).unwrap();
+    assert_eq!(reloaded, file);
+    assert_eq!(reloaded.get("tool.search.limit"), Some("12"));
+  }
+
+  #[test]
+  fn test_file_flattens_sequences_and_rejects_conflicts() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join(FILE_NAME);
+    fs::write(&path, "lint:\n  source_topics: [auth, billing]\n  empty: ~\n").unwrap();
+
+    let mut file = ConfigFile::at(&path).unwrap();
+    assert_eq!(file.get("lint.source_topics"), Some("auth,billing"));
+    assert_eq!(file.get("lint.empty"), None);
+    assert!(file.set("lint.source_topics.extra", "x").is_err());
+    assert!(file.set("lint", "x").is_err());
+    assert!(file.set("lint..x", "x").is_err());
+    assert!(file.unset("lint.source_topics"));
+    assert!(!file.unset("lint.source_topics"));
+
+    fs::write(&path, "lint: [unclosed").unwrap();
+    assert!(ConfigFile::at(&path).is_err());
+  }
+
+  #[test]
+  fn test_environment_overrides_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let mut file = ConfigFile::at(dir.path().join(FILE_NAME)).unwrap();
+    assert_eq!(file.resolve(&LIMIT), (None, Layer::Default));
+
+    file.set(LIMIT.name, "12").unwrap();
+    assert_eq!(file.resolve(&LIMIT), (Some("12".to_string()), Layer::File(file.path().into())));
+
+    std::env::set_var(LIMIT.env, "20");
+    assert_eq!(file.resolve(&LIMIT), (Some("20".to_string()), Layer::Env(LIMIT.env)));
+    assert_eq!(get::<usize>(&LIMIT), Some(20));
+    std::env::set_var(LIMIT.env, "many");
+    assert_eq!(get::<usize>(&LIMIT), None);
+    std::env::remove_var(LIMIT.env);
+  }
+}
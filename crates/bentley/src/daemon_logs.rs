@@ -1,13 +1,24 @@
 //! Daemon logging infrastructure for bentley
 //!
 //! This module provides persistent, structured logging for daemons with:
-//! - JSONL disk storage with unlimited capacity
+//! - JSONL disk storage, rotated by size and age into numbered (optionally gzipped) files
+//! - Compaction that drops entries older than a cutoff
 //! - Thread-safe async operations with internal locking
 //! - Optional console output (silent mode support)
 //! - Full bentley macro integration for unified logging
+//! - Optional per-level collapsing of repeated entries
+//! - Entries below the `BLIZZ_LOG` threshold for their component are dropped
 
 use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::dedup::{self, Deduplicator, Verdict};
 
 #[cfg(feature = "schemars")]
 use schemars::JsonSchema;
@@ -82,10 +93,47 @@ pub struct ErrorInfo {
   pub tag: String,
 }
 
+/// When the log file is rotated and how many old files are kept
+///
+/// A full log file is renamed to `<file>.1` (or compressed to `<file>.1.gz`), older
+/// rotations move up one number, and anything past `keep` is deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rotation {
+  /// Rotate once the file reaches this many bytes
+  pub max_bytes: Option<u64>,
+  /// Rotate once the file's first entry is this old
+  pub max_age: Option<Duration>,
+  /// Rotated files to keep; 0 discards the file instead
+  pub keep: usize,
+  /// Gzip rotated files
+  pub compress: bool,
+}
+
+impl Default for Rotation {
+  fn default() -> Self {
+    Self { max_bytes: Some(10 * 1024 * 1024), max_age: None, keep: 5, compress: false }
+  }
+}
+
+impl Rotation {
+  /// Never rotate, letting the file grow without bound
+  pub fn disabled() -> Self {
+    Self { max_bytes: None, max_age: None, ..Self::default() }
+  }
+}
+
 /// Internal log storage implementation
 struct DaemonLogsInner {
   log_file_path: std::path::PathBuf,
   silent: bool,
+  dedup: Deduplicator,
+  /// Component of the last entry written at each level, for its repeat summary
+  components: HashMap<String, String>,
+  rotation: Rotation,
+  /// Timestamp of the first entry in the current file
+  started: Option<DateTime<Utc>>,
+  /// Time dedup windows are measured against, replaced in tests
+  clock: fn() -> std::time::Instant,
 }
 
 /// Thread-safe disk-based log storage for daemons using JSONL format
@@ -127,7 +175,16 @@ impl DaemonLogsInner {
       std::fs::File::create(&log_file_path)?;
     }
 
-    Ok(Self { log_file_path, silent })
+    let started = first_timestamp(&log_file_path);
+    Ok(Self {
+      log_file_path,
+      silent,
+      dedup: Deduplicator::new(),
+      components: HashMap::new(),
+      rotation: Rotation::default(),
+      started,
+      clock: std::time::Instant::now,
+    })
   }
 
   /// Add a log entry to storage (appends to JSONL file)
@@ -142,6 +199,52 @@ impl DaemonLogsInner {
     message: &str,
     component: &str,
     context: Option<LogContext>,
+  ) -> std::io::Result<()> {
+    if !crate::level::enabled(level, component) {
+      return Ok(());
+    }
+
+    let now = (self.clock)();
+    let lapsed = self.dedup.expire(now);
+    self.write_summaries(lapsed)?;
+
+    let key = format!("{component}: {message}");
+    match self.dedup.check(level, &key, now) {
+      Verdict::Suppress => return Ok(()),
+      Verdict::Emit { repeated: Some(count) } => self.write_summary(level, count)?,
+      Verdict::Emit { repeated: None } => {}
+    }
+
+    self.components.insert(level.to_string(), component.to_string());
+    self.write_entry(level, message, component, context)
+  }
+
+  /// Write repeat summaries whose dedup window has lapsed
+  fn flush_expired(&mut self) -> std::io::Result<()> {
+    let pending = self.dedup.expire((self.clock)());
+    self.write_summaries(pending)
+  }
+
+  fn write_summaries(&mut self, pending: Vec<(String, usize)>) -> std::io::Result<()> {
+    for (level, count) in pending {
+      self.write_summary(&level, count)?;
+    }
+    Ok(())
+  }
+
+  /// Summarize suppressed repeats under the component that logged them
+  fn write_summary(&mut self, level: &str, count: usize) -> std::io::Result<()> {
+    let component = self.components.get(level).cloned().unwrap_or_default();
+    self.write_entry(level, &dedup::repeated_message(count), &component, None)
+  }
+
+  /// Append a single entry to the JSONL file
+  fn write_entry(
+    &mut self,
+    level: &str,
+    message: &str,
+    component: &str,
+    context: Option<LogContext>,
   ) -> std::io::Result<()> {
     let entry = LogEntry {
       timestamp: Utc::now(),
@@ -158,13 +261,183 @@ impl DaemonLogsInner {
     use std::fs::OpenOptions;
     use std::io::Write;
 
+    self.rotate_if_due()?;
     let mut file = OpenOptions::new().create(true).append(true).open(&self.log_file_path)?;
 
     writeln!(file, "{json_line}")?;
     file.flush()?;
+    self.started.get_or_insert(entry.timestamp);
+
+    Ok(())
+  }
+}
+
+impl Drop for DaemonLogsInner {
+  /// Don't lose the count of repeats still inside their window
+  fn drop(&mut self) {
+    let pending = self.dedup.flush();
+    let _ = self.write_summaries(pending);
+  }
+}
+
+// Rotation and Compaction
+// =======================
+
+impl DaemonLogsInner {
+  /// Rotate the log file if it has outgrown the size or age limit
+  fn rotate_if_due(&mut self) -> std::io::Result<()> {
+    let size = std::fs::metadata(&self.log_file_path).map(|m| m.len()).unwrap_or(0);
+    if size == 0 {
+      return Ok(());
+    }
+
+    let too_big = self.rotation.max_bytes.is_some_and(|max| size >= max);
+    let too_old = match (self.rotation.max_age, self.started) {
+      (Some(max_age), Some(started)) => {
+        Utc::now().signed_duration_since(started).to_std().is_ok_and(|age| age >= max_age)
+      }
+      _ => false,
+    };
+    if too_big || too_old {
+      self.rotate()?;
+    }
+    Ok(())
+  }
+
+  /// Move the current file to `<file>.1`, shifting older rotations up
+  fn rotate(&mut self) -> std::io::Result<()> {
+    let keep = self.rotation.keep;
+    if keep > 0 {
+      for compressed in [false, true] {
+        remove_if_exists(&self.rotated_path(keep, compressed))?;
+      }
+      for index in (1..keep).rev() {
+        for compressed in [false, true] {
+          let from = self.rotated_path(index, compressed);
+          if from.exists() {
+            std::fs::rename(&from, self.rotated_path(index + 1, compressed))?;
+          }
+        }
+      }
+
+      if self.rotation.compress {
+        let mut input = std::fs::File::open(&self.log_file_path)?;
+        let output = std::fs::File::create(self.rotated_path(1, true))?;
+        let mut encoder = GzEncoder::new(output, Compression::default());
+        std::io::copy(&mut input, &mut encoder)?;
+        encoder.finish()?;
+      } else {
+        std::fs::rename(&self.log_file_path, self.rotated_path(1, false))?;
+      }
+    }
 
+    std::fs::File::create(&self.log_file_path)?;
+    self.started = None;
     Ok(())
   }
+
+  /// Path of the `index`th rotated file
+  fn rotated_path(&self, index: usize, compressed: bool) -> PathBuf {
+    let mut name = self.log_file_path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    if compressed {
+      name.push(".gz");
+    }
+    PathBuf::from(name)
+  }
+
+  /// Drop entries older than `cutoff`, returning how many were dropped
+  ///
+  /// Rotated files last written before the cutoff are deleted outright, so their
+  /// entries are not counted. Lines that don't parse are kept.
+  fn compact(&mut self, cutoff: DateTime<Utc>) -> std::io::Result<usize> {
+    for index in 1..=self.rotation.keep.max(1) {
+      for compressed in [false, true] {
+        let path = self.rotated_path(index, compressed);
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified());
+        if modified.is_ok_and(|modified| DateTime::<Utc>::from(modified) < cutoff) {
+          std::fs::remove_file(&path)?;
+        }
+      }
+    }
+
+    if !self.log_file_path.exists() {
+      return Ok(0);
+    }
+    let content = std::fs::read_to_string(&self.log_file_path)?;
+    let mut kept = String::with_capacity(content.len());
+    let mut dropped = 0;
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+      match serde_json::from_str::<LogEntry>(line) {
+        Ok(entry) if entry.timestamp < cutoff => dropped += 1,
+        _ => {
+          kept.push_str(line);
+          kept.push('\n');
+        }
+      }
+    }
+
+    if dropped > 0 {
+      let mut temp_path = self.log_file_path.as_os_str().to_owned();
+      temp_path.push(".compact");
+      std::fs::write(&temp_path, kept)?;
+      std::fs::rename(&temp_path, &self.log_file_path)?;
+      self.started = first_timestamp(&self.log_file_path);
+    }
+    Ok(dropped)
+  }
+}
+
+/// Timestamp of the first parseable entry in a log file
+fn first_timestamp(path: &Path) -> Option<DateTime<Utc>> {
+  use std::io::{BufRead, BufReader};
+
+  let file = std::fs::File::open(path).ok()?;
+  BufReader::new(file)
+    .lines()
+    .map_while(Result::ok)
+    .find_map(|line| serde_json::from_str::<LogEntry>(&line).ok())
+    .map(|entry| entry.timestamp)
+}
+
+fn read_entries(
+  path: &Path,
+  compressed: bool,
+  level_filter: Option<&str>,
+) -> std::io::Result<Vec<LogEntry>> {
+  use std::io::{BufRead, BufReader};
+
+  let file = match std::fs::File::open(path) {
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+    file => file?,
+  };
+  let reader: Box<dyn BufRead> = if compressed {
+    Box::new(BufReader::new(GzDecoder::new(file)))
+  } else {
+    Box::new(BufReader::new(file))
+  };
+
+  let mut logs = Vec::new();
+  for line in reader.lines() {
+    let line = line?;
+    if line.trim().is_empty() {
+      continue;
+    }
+    // Malformed lines are skipped
+    if let Ok(entry) = serde_json::from_str::<LogEntry>(&line) {
+      if level_filter.is_none_or(|filter| filter == "all" || entry.level == filter) {
+        logs.push(entry);
+      }
+    }
+  }
+  Ok(logs)
+}
+
+fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+  match std::fs::remove_file(path) {
+    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+    _ => Ok(()),
+  }
 }
 
 // Log Operations
@@ -172,47 +445,31 @@ impl DaemonLogsInner {
 
 impl DaemonLogsInner {
   /// Retrieve logs with optional filtering and limiting (reads from JSONL file)
+  ///
+  /// Rotated files are read too, newest first, until there are enough entries
+  /// for `limit`.
   #[cfg(not(tarpaulin_include))]
   fn get_logs(
     &self,
     limit: Option<usize>,
     level_filter: Option<&str>,
   ) -> std::io::Result<Vec<LogEntry>> {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
-
-    if !self.log_file_path.exists() {
-      return Ok(Vec::new());
-    }
-
-    let file = File::open(&self.log_file_path)?;
-    let reader = BufReader::new(file);
-
-    let mut logs = Vec::new();
-
-    for line_result in reader.lines() {
-      let line = line_result?;
-      if line.trim().is_empty() {
-        continue;
+    let mut files = vec![read_entries(&self.log_file_path, false, level_filter)?];
+    let mut count = files[0].len();
+    for index in 1..=self.rotation.keep {
+      if limit.is_some_and(|limit| count >= limit) {
+        break;
       }
-
-      match serde_json::from_str::<LogEntry>(&line) {
-        Ok(entry) => {
-          // Apply level filter
-          let matches_level =
-            level_filter.is_none_or(|filter| filter == "all" || entry.level == filter);
-
-          if matches_level {
-            logs.push(entry);
-          }
-        }
-        Err(_) => {
-          // Skip malformed lines
-          continue;
-        }
+      for compressed in [false, true] {
+        let entries =
+          read_entries(&self.rotated_path(index, compressed), compressed, level_filter)?;
+        count += entries.len();
+        files.push(entries);
       }
     }
 
+    let mut logs: Vec<LogEntry> = files.into_iter().flatten().collect();
+
     // Sort by timestamp (newest first) to get most recent entries first
     logs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
 
@@ -285,6 +542,37 @@ impl DaemonLogs {
     guard.add_log_with_context(level, message, component, context)
   }
 
+  /// Collapse repeated entries at every level within `window` (`None` disables)
+  pub async fn set_default_dedup_window(&self, window: Option<std::time::Duration>) {
+    let mut guard = self.inner.lock().await;
+    guard.dedup.set_default_window(window);
+  }
+
+  /// Collapse repeated entries at one level within `window` (`None` disables)
+  pub async fn set_dedup_window(&self, level: &str, window: Option<std::time::Duration>) {
+    let mut guard = self.inner.lock().await;
+    guard.dedup.set_window(level, window);
+  }
+
+  /// Write any pending "repeated N times" summaries now
+  pub async fn flush(&self) -> std::io::Result<()> {
+    let mut guard = self.inner.lock().await;
+    let pending = guard.dedup.flush();
+    guard.write_summaries(pending)
+  }
+
+  /// Change when the log file is rotated
+  pub async fn set_rotation(&self, rotation: Rotation) {
+    let mut guard = self.inner.lock().await;
+    guard.rotation = rotation;
+  }
+
+  /// Drop entries older than `cutoff`, returning how many were dropped
+  pub async fn compact(&self, cutoff: DateTime<Utc>) -> std::io::Result<usize> {
+    let mut guard = self.inner.lock().await;
+    guard.compact(cutoff)
+  }
+
   /// Add a log entry (fire-and-forget, ignores errors)
   pub async fn log(&self, level: &str, message: &str, component: &str) {
     let _ = self.add_log(level, message, component).await;
@@ -307,7 +595,8 @@ impl DaemonLogs {
     limit: Option<usize>,
     level_filter: Option<&str>,
   ) -> std::io::Result<Vec<LogEntry>> {
-    let guard = self.inner.lock().await;
+    let mut guard = self.inner.lock().await;
+    guard.flush_expired()?;
     guard.get_logs(limit, level_filter)
   }
 
@@ -568,6 +857,72 @@ mod tests {
     assert_eq!(entry.component, "test_component");
   }
 
+  #[tokio::test]
+  async fn test_dedup_collapses_repeated_entries() {
+    let (_temp_dir, log_path) = temp_log_path();
+    let logs = DaemonLogs::new_with_silent(&log_path, true).unwrap();
+    logs.set_dedup_window("warn", Some(std::time::Duration::from_secs(60))).await;
+
+    for _ in 0..5 {
+      logs.add_log("warn", "Malformed line", "reader").await.unwrap();
+    }
+    logs.add_log("warn", "Different", "reader").await.unwrap();
+
+    let entries = logs.get_logs(None, None).await.unwrap();
+    let messages: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec!["Malformed line", "last message repeated 4 times", "Different"]);
+  }
+
+  #[tokio::test]
+  async fn test_dedup_summary_keeps_repeated_component() {
+    let (_temp_dir, log_path) = temp_log_path();
+    let logs = DaemonLogs::new_with_silent(&log_path, true).unwrap();
+    logs.set_dedup_window("warn", Some(std::time::Duration::from_secs(60))).await;
+
+    logs.add_log("warn", "Malformed line", "reader").await.unwrap();
+    logs.add_log("warn", "Malformed line", "reader").await.unwrap();
+    logs.add_log("warn", "Disk full", "writer").await.unwrap();
+
+    let entries = logs.get_logs(None, None).await.unwrap();
+    let summary = &entries[1];
+    assert_eq!(summary.message, "last message repeated 1 time");
+    assert_eq!(summary.component, "reader");
+  }
+
+  #[tokio::test]
+  async fn test_dedup_summary_written_when_window_lapses() {
+    let (_temp_dir, log_path) = temp_log_path();
+    let logs = DaemonLogs::new_with_silent(&log_path, true).unwrap();
+    logs.set_dedup_window("warn", Some(std::time::Duration::from_secs(1))).await;
+
+    logs.add_log("warn", "Malformed line", "reader").await.unwrap();
+    logs.add_log("warn", "Malformed line", "reader").await.unwrap();
+    logs.inner.lock().await.clock =
+      || std::time::Instant::now() + std::time::Duration::from_secs(60);
+
+    let entries = logs.get_logs(None, None).await.unwrap();
+    let messages: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec!["Malformed line", "last message repeated 1 time"]);
+    assert_eq!(entries[1].component, "reader");
+  }
+
+  #[tokio::test]
+  async fn test_dedup_summary_written_on_drop() {
+    let (_temp_dir, log_path) = temp_log_path();
+    let logs = DaemonLogs::new_with_silent(&log_path, true).unwrap();
+    logs.set_dedup_window("warn", Some(std::time::Duration::from_secs(60))).await;
+
+    for _ in 0..3 {
+      logs.add_log("warn", "Malformed line", "reader").await.unwrap();
+    }
+    drop(logs);
+
+    let content = fs::read_to_string(&log_path).unwrap();
+    let last: LogEntry = serde_json::from_str(content.lines().last().unwrap()).unwrap();
+    assert_eq!(last.message, "last message repeated 2 times");
+    assert_eq!(last.component, "reader");
+  }
+
   #[tokio::test]
   async fn test_log_fire_and_forget() {
     let (_temp_dir, log_path) = temp_log_path();
@@ -932,4 +1287,114 @@ another bad line
     assert_eq!(error.message, "Test error");
     assert_eq!(error.tag, "test_tag");
   }
+
+  // Rotation and Compaction Tests
+  // =============================
+
+  fn write_entries(logs: &mut DaemonLogsInner, count: usize) {
+    for i in 0..count {
+      logs.add_log("info", &format!("Message {i}"), "comp").unwrap();
+    }
+  }
+
+  #[test]
+  fn test_rotates_by_size_and_keeps_n_files() {
+    let (_temp_dir, log_path) = temp_log_path();
+    let mut logs = DaemonLogsInner::new(&log_path, true).unwrap();
+    logs.rotation = Rotation { max_bytes: Some(300), keep: 2, ..Rotation::default() };
+
+    write_entries(&mut logs, 30);
+
+    assert!(fs::metadata(&log_path).unwrap().len() < 400);
+    assert!(logs.rotated_path(1, false).exists());
+    assert!(logs.rotated_path(2, false).exists());
+    assert!(!logs.rotated_path(3, false).exists());
+
+    let newest = fs::read_to_string(&log_path).unwrap();
+    assert!(newest.contains("Message 29"));
+    let previous = fs::read_to_string(logs.rotated_path(1, false)).unwrap();
+    assert!(!previous.contains("Message 29"));
+  }
+
+  #[test]
+  fn test_rotation_can_gzip_or_discard() {
+    use std::io::Read;
+
+    let (_temp_dir, log_path) = temp_log_path();
+    let mut logs = DaemonLogsInner::new(&log_path, true).unwrap();
+    logs.rotation = Rotation { max_bytes: Some(1), keep: 3, compress: true, ..Rotation::default() };
+    write_entries(&mut logs, 3);
+
+    assert!(!logs.rotated_path(1, false).exists());
+    let mut rotated = String::new();
+    let gz = fs::File::open(logs.rotated_path(2, true)).unwrap();
+    flate2::read::GzDecoder::new(gz).read_to_string(&mut rotated).unwrap();
+    let entry: LogEntry = serde_json::from_str(rotated.trim()).unwrap();
+    assert_eq!(entry.message, "Message 0");
+
+    logs.rotation = Rotation { max_bytes: Some(1), keep: 0, ..Rotation::default() };
+    write_entries(&mut logs, 2);
+    assert_eq!(fs::read_to_string(&log_path).unwrap().lines().count(), 1);
+  }
+
+  #[test]
+  fn test_get_logs_reads_rotated_files_when_the_limit_needs_them() {
+    let (_temp_dir, log_path) = temp_log_path();
+    let mut logs = DaemonLogsInner::new(&log_path, true).unwrap();
+    logs.rotation = Rotation { max_bytes: Some(1), keep: 3, ..Rotation::default() };
+    write_entries(&mut logs, 2);
+    logs.rotation.compress = true;
+    write_entries(&mut logs, 2);
+
+    let messages = |limit| -> Vec<String> {
+      logs.get_logs(limit, None).unwrap().into_iter().map(|entry| entry.message).collect()
+    };
+    assert_eq!(messages(Some(1)), ["Message 1"]);
+    assert_eq!(messages(Some(3)), ["Message 1", "Message 0", "Message 1"]);
+    assert_eq!(messages(None), ["Message 0", "Message 1", "Message 0", "Message 1"]);
+  }
+
+  #[test]
+  fn test_rotates_by_age_of_first_entry() {
+    let (_temp_dir, log_path) = temp_log_path();
+    let mut logs = DaemonLogsInner::new(&log_path, true).unwrap();
+    logs.rotation = Rotation::disabled();
+    write_entries(&mut logs, 2);
+
+    logs.rotation.max_age = Some(Duration::from_secs(3600));
+    write_entries(&mut logs, 1);
+    assert!(!logs.rotated_path(1, false).exists());
+
+    logs.started = Some(Utc::now() - chrono::Duration::hours(2));
+    write_entries(&mut logs, 1);
+    assert_eq!(fs::read_to_string(logs.rotated_path(1, false)).unwrap().lines().count(), 3);
+    assert_eq!(fs::read_to_string(&log_path).unwrap().lines().count(), 1);
+  }
+
+  #[tokio::test]
+  async fn test_compact_drops_old_entries() {
+    let (_temp_dir, log_path) = temp_log_path();
+    let now = Utc::now();
+    let line = |age_hours: i64, message: &str| {
+      let entry = LogEntry {
+        timestamp: now - chrono::Duration::hours(age_hours),
+        level: "info".to_string(),
+        message: message.to_string(),
+        component: "comp".to_string(),
+        context: None,
+      };
+      serde_json::to_string(&entry).unwrap()
+    };
+    let content = [line(48, "old"), "not json".to_string(), line(1, "recent")].join("\n");
+    fs::write(&log_path, content + "\n").unwrap();
+
+    let logs = DaemonLogs::new_with_silent(&log_path, true).unwrap();
+    assert_eq!(logs.compact(now - chrono::Duration::hours(24)).await.unwrap(), 1);
+    assert_eq!(logs.compact(now - chrono::Duration::hours(24)).await.unwrap(), 0);
+
+    let remaining = logs.get_logs(None, None).await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].message, "recent");
+    assert!(fs::read_to_string(&log_path).unwrap().contains("not json"));
+  }
 }
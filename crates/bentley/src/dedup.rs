@@ -0,0 +1,294 @@
+//! Message deduplication for flood-prone logging
+//!
+//! Identical messages logged at the same level within a configurable window are
+//! collapsed: the first is emitted, the rest are counted, and a single
+//! "last message repeated N times" line is emitted once a different message
+//! arrives, the next message is logged after the window lapsed, or [`flush`] is
+//! called.
+//!
+//! Deduplication is off by default and is configured per level. A program that
+//! turns it on for terminal output should call [`flush`] before exiting, or the
+//! count of repeats still inside their window is lost.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+// Types and Data Structures
+// =========================
+
+/// What the caller should do with a message after deduplication
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+  /// Emit the message, preceded by a repeat summary when `repeated` is set
+  Emit { repeated: Option<usize> },
+  /// Drop the message; it repeats the previous one within the window
+  Suppress,
+}
+
+/// Last message seen at a level
+#[derive(Debug)]
+struct Recent {
+  message: String,
+  since: Instant,
+  repeated: usize,
+}
+
+/// Per-level deduplication state
+#[derive(Debug, Default)]
+pub struct Deduplicator {
+  default_window: Option<Duration>,
+  windows: HashMap<String, Option<Duration>>,
+  recent: HashMap<String, Recent>,
+}
+
+// Core API
+// ========
+
+impl Deduplicator {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Set the window applied to levels without their own setting (`None` disables)
+  pub fn set_default_window(&mut self, window: Option<Duration>) {
+    self.default_window = window;
+  }
+
+  /// Set the window for a single level (`None` disables deduplication for it)
+  pub fn set_window(&mut self, level: &str, window: Option<Duration>) {
+    self.windows.insert(level.to_string(), window);
+    if window.is_none() {
+      self.recent.remove(level);
+    }
+  }
+
+  /// The window in effect for a level
+  pub fn window(&self, level: &str) -> Option<Duration> {
+    self.windows.get(level).copied().unwrap_or(self.default_window)
+  }
+
+  /// Decide whether a message should be emitted at `now`
+  pub fn check(&mut self, level: &str, message: &str, now: Instant) -> Verdict {
+    let Some(window) = self.window(level) else {
+      return Verdict::Emit { repeated: None };
+    };
+
+    if let Some(recent) = self.recent.get_mut(level) {
+      if recent.message == message && now.saturating_duration_since(recent.since) < window {
+        recent.repeated += 1;
+        return Verdict::Suppress;
+      }
+    }
+
+    let previous = self
+      .recent
+      .insert(level.to_string(), Recent { message: message.to_string(), since: now, repeated: 0 });
+
+    Verdict::Emit { repeated: previous.map(|p| p.repeated).filter(|n| *n > 0) }
+  }
+
+  /// Drain repeat counts whose window has lapsed at `now`, returning
+  /// `(level, repeated)` pairs
+  pub fn expire(&mut self, now: Instant) -> Vec<(String, usize)> {
+    let lapsed: Vec<String> = self
+      .recent
+      .iter()
+      .filter(|(level, recent)| {
+        self
+          .window(level)
+          .is_none_or(|window| now.saturating_duration_since(recent.since) >= window)
+      })
+      .map(|(level, _)| level.clone())
+      .collect();
+
+    let mut pending: Vec<(String, usize)> = lapsed
+      .into_iter()
+      .filter_map(|level| self.recent.remove(&level).map(|recent| (level, recent.repeated)))
+      .filter(|(_, repeated)| *repeated > 0)
+      .collect();
+    pending.sort();
+    pending
+  }
+
+  /// Drain pending repeat counts, returning `(level, repeated)` pairs
+  pub fn flush(&mut self) -> Vec<(String, usize)> {
+    let mut pending: Vec<(String, usize)> = self
+      .recent
+      .drain()
+      .filter(|(_, recent)| recent.repeated > 0)
+      .map(|(level, recent)| (level, recent.repeated))
+      .collect();
+    pending.sort();
+    pending
+  }
+}
+
+/// Summary line emitted in place of suppressed repeats
+pub fn repeated_message(count: usize) -> String {
+  if count == 1 {
+    "last message repeated 1 time".to_string()
+  } else {
+    format!("last message repeated {count} times")
+  }
+}
+
+// Global Terminal Deduplication
+// =============================
+
+static GLOBAL: OnceLock<Mutex<Deduplicator>> = OnceLock::new();
+
+fn global() -> &'static Mutex<Deduplicator> {
+  GLOBAL.get_or_init(|| Mutex::new(Deduplicator::new()))
+}
+
+/// Set the dedup window for every level of the terminal logging functions
+pub fn set_default_window(window: Option<Duration>) {
+  if let Ok(mut dedup) = global().lock() {
+    dedup.set_default_window(window);
+  }
+}
+
+/// Set the dedup window for one level (`"info"`, `"warn"`, `"error"`, `"debug"`,
+/// `"success"`, `"verbose"`, `"fail"`)
+pub fn set_window(level: &str, window: Option<Duration>) {
+  if let Ok(mut dedup) = global().lock() {
+    dedup.set_window(level, window);
+  }
+}
+
+/// Check a message against the global terminal deduplicator, first writing the
+/// summaries of repeats at any level whose window has lapsed
+pub(crate) fn check(level: &str, message: &str) -> Verdict {
+  let (lapsed, verdict) = match global().lock() {
+    Ok(mut dedup) => {
+      let now = Instant::now();
+      (dedup.expire(now), dedup.check(level, message, now))
+    }
+    Err(_) => return Verdict::Emit { repeated: None },
+  };
+
+  for (level, count) in lapsed {
+    crate::write_level(&level, &repeated_message(count));
+  }
+  verdict
+}
+
+/// Emit any pending "repeated N times" summaries (e.g. before exiting)
+#[cfg(not(tarpaulin_include))]
+pub fn flush() {
+  let pending = match global().lock() {
+    Ok(mut dedup) => dedup.flush(),
+    Err(_) => return,
+  };
+
+  for (level, count) in pending {
+    crate::write_level(&level, &repeated_message(count));
+  }
+}
+
+// Tests
+// =====
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn enabled(window_ms: u64) -> Deduplicator {
+    let mut dedup = Deduplicator::new();
+    dedup.set_default_window(Some(Duration::from_millis(window_ms)));
+    dedup
+  }
+
+  #[test]
+  fn test_disabled_by_default() {
+    let mut dedup = Deduplicator::new();
+    let now = Instant::now();
+
+    assert_eq!(dedup.check("warn", "same", now), Verdict::Emit { repeated: None });
+    assert_eq!(dedup.check("warn", "same", now), Verdict::Emit { repeated: None });
+  }
+
+  #[test]
+  fn test_collapses_repeats_until_message_changes() {
+    let mut dedup = enabled(1000);
+    let now = Instant::now();
+
+    assert_eq!(dedup.check("warn", "bad line", now), Verdict::Emit { repeated: None });
+    assert_eq!(dedup.check("warn", "bad line", now), Verdict::Suppress);
+    assert_eq!(dedup.check("warn", "bad line", now), Verdict::Suppress);
+    assert_eq!(dedup.check("warn", "other", now), Verdict::Emit { repeated: Some(2) });
+  }
+
+  #[test]
+  fn test_window_lapse_re_emits() {
+    let mut dedup = enabled(100);
+    let start = Instant::now();
+
+    dedup.check("info", "tick", start);
+    assert_eq!(dedup.check("info", "tick", start + Duration::from_millis(50)), Verdict::Suppress);
+    assert_eq!(
+      dedup.check("info", "tick", start + Duration::from_millis(150)),
+      Verdict::Emit { repeated: Some(1) }
+    );
+  }
+
+  #[test]
+  fn test_levels_are_independent() {
+    let mut dedup = Deduplicator::new();
+    dedup.set_window("warn", Some(Duration::from_secs(1)));
+    let now = Instant::now();
+
+    dedup.check("warn", "x", now);
+    assert_eq!(dedup.check("warn", "x", now), Verdict::Suppress);
+    dedup.check("error", "x", now);
+    assert_eq!(dedup.check("error", "x", now), Verdict::Emit { repeated: None });
+  }
+
+  #[test]
+  fn test_level_override_disables_default() {
+    let mut dedup = enabled(1000);
+    dedup.set_window("error", None);
+    let now = Instant::now();
+
+    dedup.check("error", "x", now);
+    assert_eq!(dedup.check("error", "x", now), Verdict::Emit { repeated: None });
+    assert_eq!(dedup.window("warn"), Some(Duration::from_millis(1000)));
+  }
+
+  #[test]
+  fn test_flush_reports_pending_repeats() {
+    let mut dedup = enabled(1000);
+    let now = Instant::now();
+
+    dedup.check("warn", "x", now);
+    dedup.check("warn", "x", now);
+    dedup.check("info", "y", now);
+
+    assert_eq!(dedup.flush(), vec![("warn".to_string(), 1)]);
+    assert!(dedup.flush().is_empty());
+  }
+
+  #[test]
+  fn test_expire_reports_lapsed_repeats_only() {
+    let mut dedup = enabled(100);
+    let start = Instant::now();
+
+    dedup.check("warn", "x", start);
+    dedup.check("warn", "x", start);
+    dedup.check("info", "y", start + Duration::from_millis(80));
+    dedup.check("info", "y", start + Duration::from_millis(80));
+
+    let later = start + Duration::from_millis(120);
+    assert_eq!(dedup.expire(later), vec![("warn".to_string(), 1)]);
+    assert!(dedup.expire(later).is_empty());
+    assert_eq!(dedup.check("warn", "x", later), Verdict::Emit { repeated: None });
+    assert_eq!(dedup.flush(), vec![("info".to_string(), 1)]);
+  }
+
+  #[test]
+  fn test_repeated_message_wording() {
+    assert_eq!(repeated_message(1), "last message repeated 1 time");
+    assert_eq!(repeated_message(3), "last message repeated 3 times");
+  }
+}
@@ -0,0 +1,515 @@
+//! Width-aware text layout for terminal output
+//!
+//! Widths are measured in terminal columns, so wide characters and ANSI color
+//! codes are accounted for: colored text can be wrapped, padded and truncated
+//! without breaking alignment.
+
+use console::{measure_text_width, truncate_str, Term};
+
+// Constants
+// =========
+
+/// Width assumed when the terminal size cannot be detected
+pub const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// Marker appended (or prepended) to truncated text
+pub const ELLIPSIS: &str = "...";
+
+/// Gap between table columns
+const DEFAULT_GAP: usize = 1;
+
+/// Narrowest a column is shrunk to when fitting a table into a width
+const MIN_COLUMN_WIDTH: usize = 4;
+
+// Measuring
+// =========
+
+/// Width of the terminal attached to stdout
+///
+/// Falls back to the `COLUMNS` environment variable, then to
+/// [`DEFAULT_TERMINAL_WIDTH`] when output is not a terminal.
+pub fn terminal_width() -> usize {
+  Term::stdout()
+    .size_checked()
+    .map(|(_, columns)| columns as usize)
+    .or_else(|| std::env::var("COLUMNS").ok().and_then(|s| s.parse().ok()))
+    .filter(|width| *width > 0)
+    .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+}
+
+/// Display width of text in terminal columns, ignoring color codes
+pub fn display_width(text: &str) -> usize {
+  measure_text_width(text)
+}
+
+// Truncation
+// ==========
+
+/// Shorten text to at most `width` columns, ending it with [`ELLIPSIS`]
+pub fn truncate(text: &str, width: usize) -> String {
+  truncate_str(text, width, ELLIPSIS).into_owned()
+}
+
+/// Shorten text to at most `width` columns, keeping its end
+///
+/// Suited to file paths, where the end is the informative part. Intended for
+/// plain text; color codes in the dropped part are lost.
+pub fn truncate_start(text: &str, width: usize) -> String {
+  if display_width(text) <= width {
+    return text.to_string();
+  }
+
+  let budget = width.saturating_sub(ELLIPSIS.len());
+  let mut kept = Vec::new();
+  let mut used = 0;
+  for c in text.chars().rev() {
+    let char_width = display_width(c.encode_utf8(&mut [0; 4]));
+    if used + char_width > budget {
+      break;
+    }
+    used += char_width;
+    kept.push(c);
+  }
+
+  let tail: String = kept.into_iter().rev().collect();
+  format!("{}{tail}", &ELLIPSIS[..ELLIPSIS.len().min(width)])
+}
+
+// Padding
+// =======
+
+/// Horizontal alignment of text within a column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+  #[default]
+  Left,
+  Right,
+  Center,
+}
+
+/// Pad text with spaces to `width` columns (text already wider is left as is)
+pub fn pad(text: &str, width: usize, align: Align) -> String {
+  let padding = width.saturating_sub(display_width(text));
+  let (left, right) = match align {
+    Align::Left => (0, padding),
+    Align::Right => (padding, 0),
+    Align::Center => (padding / 2, padding - padding / 2),
+  };
+  format!("{}{text}{}", " ".repeat(left), " ".repeat(right))
+}
+
+// Wrapping
+// ========
+
+/// Word-wrap text to `width` columns
+///
+/// Existing line breaks are kept. Words longer than a whole line are split.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+  wrap_indented(text, width, "", "")
+}
+
+/// Word-wrap text with a hanging indent
+///
+/// The first line starts with `initial` and every following line with
+/// `subsequent`, e.g. a bullet followed by spaces of the same width.
+pub fn wrap_indented(text: &str, width: usize, initial: &str, subsequent: &str) -> Vec<String> {
+  let mut lines = Vec::new();
+  let mut indent = initial;
+
+  let available = |indent: &str| width.saturating_sub(display_width(indent)).max(1);
+
+  for paragraph in text.lines() {
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in paragraph.split_whitespace() {
+      let word_width = display_width(word);
+      if line_width > 0 && line_width + 1 + word_width <= available(indent) {
+        line.push(' ');
+        line.push_str(word);
+        line_width += 1 + word_width;
+        continue;
+      }
+
+      if line_width > 0 {
+        lines.push(format!("{indent}{line}"));
+        indent = subsequent;
+        line.clear();
+      }
+
+      let mut pieces = split_word(word, available(indent));
+      let last = pieces.pop().unwrap_or_default();
+      for piece in pieces {
+        lines.push(format!("{indent}{piece}"));
+        indent = subsequent;
+      }
+      line_width = display_width(&last);
+      line = last;
+    }
+
+    lines.push(format!("{indent}{line}").trim_end().to_string());
+    indent = subsequent;
+  }
+
+  lines
+}
+
+/// Split a word into pieces no wider than `width` columns
+fn split_word(word: &str, width: usize) -> Vec<String> {
+  let mut pieces = vec![String::new()];
+  let mut used = 0;
+  for c in word.chars() {
+    let char_width = display_width(c.encode_utf8(&mut [0; 4]));
+    if used + char_width > width && used > 0 {
+      pieces.push(String::new());
+      used = 0;
+    }
+    pieces.last_mut().expect("pieces is never empty").push(c);
+    used += char_width;
+  }
+  pieces
+}
+
+// Sizes
+// =====
+
+/// Byte count in binary units, e.g. `1.5 KiB`
+pub fn format_size(bytes: u64) -> String {
+  match bytes {
+    b if b >= 1 << 30 => format!("{:.1} GiB", b as f64 / (1u64 << 30) as f64),
+    b if b >= 1 << 20 => format!("{:.1} MiB", b as f64 / (1u64 << 20) as f64),
+    b if b >= 1 << 10 => format!("{:.1} KiB", b as f64 / (1u64 << 10) as f64),
+    b => format!("{b} B"),
+  }
+}
+
+// Tables
+// ======
+
+/// Which end of an over-long cell is cut off
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Truncate {
+  /// Keep the start, e.g. for descriptions
+  #[default]
+  End,
+  /// Keep the end, e.g. for file paths
+  Start,
+}
+
+/// A table column
+#[derive(Debug, Clone)]
+pub struct Column {
+  header: String,
+  align: Align,
+  min_width: usize,
+  max_width: Option<usize>,
+  truncate: Truncate,
+}
+
+impl Column {
+  pub fn new(header: &str) -> Self {
+    Self {
+      header: header.to_string(),
+      align: Align::Left,
+      min_width: 0,
+      max_width: None,
+      truncate: Truncate::End,
+    }
+  }
+
+  pub fn align(mut self, align: Align) -> Self {
+    self.align = align;
+    self
+  }
+
+  /// Pad the column to at least this many columns
+  pub fn min_width(mut self, width: usize) -> Self {
+    self.min_width = width;
+    self
+  }
+
+  /// Truncate cells wider than this many columns
+  pub fn max_width(mut self, width: usize) -> Self {
+    self.max_width = Some(width);
+    self
+  }
+
+  pub fn truncate(mut self, truncate: Truncate) -> Self {
+    self.truncate = truncate;
+    self
+  }
+}
+
+/// A simple aligned table, rendered as plain lines
+///
+/// ```
+/// use bentley::layout::{Align, Column, Table};
+///
+/// let table = Table::new()
+///   .column(Column::new("language"))
+///   .column(Column::new("threshold").align(Align::Right))
+///   .row(["rust", "7.00"]);
+/// assert_eq!(table.render(), vec!["language threshold", "rust          7.00"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Table {
+  columns: Vec<Column>,
+  rows: Vec<Vec<String>>,
+  gap: usize,
+  rule: Option<char>,
+  max_width: Option<usize>,
+  show_header: bool,
+}
+
+impl Default for Table {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Table {
+  pub fn new() -> Self {
+    Self {
+      columns: Vec::new(),
+      rows: Vec::new(),
+      gap: DEFAULT_GAP,
+      rule: None,
+      max_width: None,
+      show_header: true,
+    }
+  }
+
+  pub fn column(mut self, column: Column) -> Self {
+    self.columns.push(column);
+    self
+  }
+
+  /// Add a row; missing cells are left blank and extra cells are ignored
+  pub fn row<I, S>(mut self, cells: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    self.push_row(cells);
+    self
+  }
+
+  /// Add a row to a table that is built up in a loop
+  pub fn push_row<I, S>(&mut self, cells: I)
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    self.rows.push(cells.into_iter().map(Into::into).collect());
+  }
+
+  /// Spaces between columns
+  pub fn gap(mut self, gap: usize) -> Self {
+    self.gap = gap;
+    self
+  }
+
+  /// Draw a rule of this character under the header
+  pub fn rule(mut self, rule: char) -> Self {
+    self.rule = Some(rule);
+    self
+  }
+
+  /// Shrink the widest columns until the table fits in this many columns
+  pub fn max_width(mut self, width: usize) -> Self {
+    self.max_width = Some(width);
+    self
+  }
+
+  pub fn without_header(mut self) -> Self {
+    self.show_header = false;
+    self
+  }
+
+  /// Width of every column after applying limits
+  fn widths(&self) -> Vec<usize> {
+    let mut widths: Vec<usize> = self
+      .columns
+      .iter()
+      .enumerate()
+      .map(|(index, column)| {
+        let header = if self.show_header { display_width(&column.header) } else { 0 };
+        let cells = self.rows.iter().filter_map(|row| row.get(index)).map(|c| display_width(c));
+        let natural = cells.fold(header, usize::max).max(column.min_width);
+        column.max_width.map_or(natural, |max| natural.min(max))
+      })
+      .collect();
+
+    if let Some(limit) = self.max_width {
+      let gaps = self.gap * widths.len().saturating_sub(1);
+      while widths.iter().sum::<usize>() + gaps > limit {
+        let Some(widest) = widths.iter_mut().filter(|w| **w > MIN_COLUMN_WIDTH).max() else {
+          break;
+        };
+        *widest -= 1;
+      }
+    }
+
+    widths
+  }
+
+  fn render_cells(&self, cells: &[String], widths: &[usize]) -> String {
+    let gap = " ".repeat(self.gap);
+    let rendered: Vec<String> = self
+      .columns
+      .iter()
+      .zip(widths)
+      .enumerate()
+      .map(|(index, (column, width))| {
+        let cell = cells.get(index).map(String::as_str).unwrap_or_default();
+        let fitted = match column.truncate {
+          Truncate::End => truncate(cell, *width),
+          Truncate::Start => truncate_start(cell, *width),
+        };
+        pad(&fitted, *width, column.align)
+      })
+      .collect();
+    rendered.join(&gap).trim_end().to_string()
+  }
+
+  /// Render the table, one string per line
+  pub fn render(&self) -> Vec<String> {
+    let widths = self.widths();
+    let mut lines = Vec::new();
+
+    if self.show_header {
+      let headers: Vec<String> = self.columns.iter().map(|c| c.header.clone()).collect();
+      lines.push(self.render_cells(&headers, &widths));
+      if let Some(rule) = self.rule {
+        let total = widths.iter().sum::<usize>() + self.gap * widths.len().saturating_sub(1);
+        lines.push(rule.to_string().repeat(total));
+      }
+    }
+
+    for row in &self.rows {
+      lines.push(self.render_cells(row, &widths));
+    }
+
+    lines
+  }
+}
+
+/// A table with one plain column per header, fitted to the terminal
+///
+/// Shorthand for the common case; build a [`Table`] directly to align or cap
+/// individual columns.
+///
+/// ```
+/// let table = bentley::table(&["name", "status"], [["keeper", "running"], ["insights", "stopped"]]);
+/// assert_eq!(table.render()[0], "name     status");
+/// ```
+pub fn table<R, I, S>(headers: &[&str], rows: R) -> Table
+where
+  R: IntoIterator<Item = I>,
+  I: IntoIterator<Item = S>,
+  S: Into<String>,
+{
+  let mut table = headers
+    .iter()
+    .fold(Table::new(), |table, header| table.column(Column::new(header)))
+    .max_width(terminal_width());
+  for row in rows {
+    table.push_row(row);
+  }
+  table
+}
+
+impl std::fmt::Display for Table {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for line in self.render() {
+      writeln!(f, "{line}")?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use colored::Colorize;
+
+  #[test]
+  fn test_display_width_ignores_color_and_counts_wide_chars() {
+    assert_eq!(display_width("plain"), 5);
+    assert_eq!(display_width(&"red".red().to_string()), 3);
+    assert_eq!(display_width("日本"), 4);
+  }
+
+  #[test]
+  fn test_truncate_keeps_start_or_end() {
+    assert_eq!(truncate("short", 10), "short");
+    assert_eq!(truncate("a long description", 10), "a long ...");
+    assert_eq!(truncate_start("src/deeply/nested/file.rs", 12), "...d/file.rs");
+    assert_eq!(truncate_start("←←←←←", 4), "...←");
+  }
+
+  #[test]
+  fn test_format_size_picks_the_largest_unit() {
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(1536), "1.5 KiB");
+    assert_eq!(format_size(3 << 20), "3.0 MiB");
+    assert_eq!(format_size(5 << 30), "5.0 GiB");
+  }
+
+  #[test]
+  fn test_pad_aligns() {
+    assert_eq!(pad("ab", 5, Align::Left), "ab   ");
+    assert_eq!(pad("ab", 5, Align::Right), "   ab");
+    assert_eq!(pad("ab", 5, Align::Center), " ab  ");
+    assert_eq!(pad("toolong", 3, Align::Left), "toolong");
+  }
+
+  #[test]
+  fn test_wrap_breaks_on_words_and_keeps_line_breaks() {
+    assert_eq!(wrap("the quick brown fox jumps", 10), vec!["the quick", "brown fox", "jumps"]);
+    assert_eq!(wrap("one\n\ntwo", 10), vec!["one", "", "two"]);
+    assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+  }
+
+  #[test]
+  fn test_wrap_indented_hangs_after_first_line() {
+    let lines = wrap_indented("alpha beta gamma delta", 14, "- ", "  ");
+    assert_eq!(lines, vec!["- alpha beta", "  gamma delta"]);
+  }
+
+  #[test]
+  fn test_table_aligns_truncates_and_rules() {
+    let table = Table::new()
+      .column(Column::new("file").max_width(10).truncate(Truncate::Start))
+      .column(Column::new("score").align(Align::Right))
+      .rule('=')
+      .row(["src/main.rs", "12.50"])
+      .row(["lib.rs", "3.00"]);
+
+    assert_eq!(
+      table.render(),
+      vec!["file       score", "================", "...main.rs 12.50", "lib.rs      3.00"]
+    );
+  }
+
+  #[test]
+  fn test_table_shorthand_sizes_columns_to_content() {
+    let rows = vec![vec!["keeper".to_string(), "running".to_string()], vec!["insights".into()]];
+    let lines = table(&["name", "status"], rows).render();
+    assert_eq!(lines, vec!["name     status", "keeper   running", "insights"]);
+  }
+
+  #[test]
+  fn test_table_fits_max_width_by_shrinking_widest_column() {
+    let table = Table::new()
+      .column(Column::new("name"))
+      .column(Column::new("description"))
+      .max_width(20)
+      .without_header()
+      .row(["id", "a very long description of it"]);
+
+    let lines = table.render();
+    assert_eq!(lines, vec!["id a very long de..."]);
+    assert!(lines.iter().all(|line| display_width(line) <= 20));
+  }
+}
@@ -0,0 +1,246 @@
+//! Level thresholds for logging, globally and per component
+//!
+//! Messages below the threshold in effect are dropped. The threshold comes from
+//! `BLIZZ_LOG` on first use, in the form `debug`, `insights=debug,secrets=warn`
+//! or a mix of both (`info,insights=debug`), and can be changed at runtime with
+//! [`set_level`] and [`set_component_level`].
+//!
+//! A component override applies to the component and to everything nested under
+//! it, so `insights=debug` also covers `insights-api` and `insights::server`. The
+//! terminal logging functions use the running program's name as their component.
+//!
+//! Everything is shown by default.
+
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+
+// Types and Data Structures
+// =========================
+
+/// Severity thresholds, from most to least chatty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+  Verbose,
+  Debug,
+  Info,
+  Warn,
+  Error,
+}
+
+impl Level {
+  /// The threshold a named logging level is measured against
+  ///
+  /// `success` and the theatrical levels count as info, `fail` and `showstopper`
+  /// as error. Unknown names are treated as info.
+  pub fn of(name: &str) -> Self {
+    match name {
+      "verbose" => Level::Verbose,
+      "debug" => Level::Debug,
+      "warn" => Level::Warn,
+      "error" | "fail" | "showstopper" => Level::Error,
+      _ => Level::Info,
+    }
+  }
+}
+
+impl FromStr for Level {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value.trim().to_lowercase().as_str() {
+      "verbose" | "trace" => Ok(Level::Verbose),
+      "debug" => Ok(Level::Debug),
+      "info" => Ok(Level::Info),
+      "warn" | "warning" => Ok(Level::Warn),
+      "error" => Ok(Level::Error),
+      other => Err(format!("unknown log level '{other}'")),
+    }
+  }
+}
+
+/// A default threshold plus per-component overrides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+  default: Level,
+  components: Vec<(String, Level)>,
+}
+
+impl Default for Filter {
+  fn default() -> Self {
+    Self { default: Level::Verbose, components: Vec::new() }
+  }
+}
+
+// Core API
+// ========
+
+impl Filter {
+  /// Parse a `BLIZZ_LOG` value; malformed entries are skipped
+  pub fn parse(spec: &str) -> Self {
+    let mut filter = Self::default();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+      match entry.split_once('=') {
+        Some((component, level)) => {
+          if let Ok(level) = level.parse() {
+            filter.set_component(component.trim(), level);
+          }
+        }
+        None => {
+          if let Ok(level) = entry.parse() {
+            filter.default = level;
+          }
+        }
+      }
+    }
+    filter
+  }
+
+  /// Set the threshold for components without an override
+  pub fn set_default(&mut self, level: Level) {
+    self.default = level;
+  }
+
+  /// Set the threshold for a component and everything nested under it
+  pub fn set_component(&mut self, component: &str, level: Level) {
+    match self.components.iter_mut().find(|(name, _)| name == component) {
+      Some(entry) => entry.1 = level,
+      None => self.components.push((component.to_string(), level)),
+    }
+  }
+
+  /// The threshold in effect for a component; the most specific override wins
+  pub fn threshold(&self, component: Option<&str>) -> Level {
+    let Some(component) = component else { return self.default };
+    self
+      .components
+      .iter()
+      .filter(|(name, _)| covers(name, component))
+      .max_by_key(|(name, _)| name.len())
+      .map_or(self.default, |(_, level)| *level)
+  }
+
+  /// Whether a message at a named level should be logged for a component
+  pub fn allows(&self, level: &str, component: Option<&str>) -> bool {
+    Level::of(level) >= self.threshold(component)
+  }
+}
+
+/// Whether an override for `name` applies to `component`
+fn covers(name: &str, component: &str) -> bool {
+  match component.strip_prefix(name) {
+    Some("") => true,
+    Some(rest) => rest.starts_with(['-', ':', '.', '/']),
+    None => false,
+  }
+}
+
+// Global Filter
+// =============
+
+static GLOBAL: OnceLock<RwLock<Filter>> = OnceLock::new();
+static PROGRAM: OnceLock<Option<String>> = OnceLock::new();
+
+fn global() -> &'static RwLock<Filter> {
+  GLOBAL.get_or_init(|| {
+    RwLock::new(std::env::var("BLIZZ_LOG").map(|spec| Filter::parse(&spec)).unwrap_or_default())
+  })
+}
+
+/// Name of the running program, the component of terminal log messages
+fn program() -> Option<&'static str> {
+  PROGRAM
+    .get_or_init(|| {
+      let exe = std::env::current_exe().ok()?;
+      Some(exe.file_stem()?.to_string_lossy().into_owned())
+    })
+    .as_deref()
+}
+
+/// Set the threshold for every component without its own override
+pub fn set_level(level: Level) {
+  if let Ok(mut filter) = global().write() {
+    filter.set_default(level);
+  }
+}
+
+/// Set the threshold for one component (e.g. `"insights"`) and those nested under it
+pub fn set_component_level(component: &str, level: Level) {
+  if let Ok(mut filter) = global().write() {
+    filter.set_component(component, level);
+  }
+}
+
+/// Whether a message at a named level passes the global filter for a component
+pub fn enabled(level: &str, component: &str) -> bool {
+  global().read().map_or(true, |filter| filter.allows(level, Some(component)))
+}
+
+/// Whether a terminal message at a named level passes the global filter
+pub(crate) fn enabled_here(level: &str) -> bool {
+  global().read().map_or(true, |filter| filter.allows(level, program()))
+}
+
+// Tests
+// =====
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_everything_is_shown_by_default() {
+    let filter = Filter::default();
+    assert!(filter.allows("verbose", None));
+    assert!(filter.allows("debug", Some("insights")));
+  }
+
+  #[test]
+  fn test_named_levels_map_to_thresholds() {
+    assert_eq!(Level::of("success"), Level::Info);
+    assert_eq!(Level::of("announce"), Level::Info);
+    assert_eq!(Level::of("fail"), Level::Error);
+    assert!(Level::Verbose < Level::Debug && Level::Debug < Level::Info);
+    assert_eq!("WARNING".parse(), Ok(Level::Warn));
+    assert!("loud".parse::<Level>().is_err());
+  }
+
+  #[test]
+  fn test_parse_global_threshold() {
+    let filter = Filter::parse("info");
+    assert!(!filter.allows("debug", None));
+    assert!(!filter.allows("verbose", Some("secrets")));
+    assert!(filter.allows("success", None));
+    assert!(filter.allows("error", None));
+  }
+
+  #[test]
+  fn test_parse_component_overrides() {
+    let filter = Filter::parse("warn, insights=debug ,secrets=error,bogus,violet=loud");
+    assert_eq!(filter.threshold(None), Level::Warn);
+    assert_eq!(filter.threshold(Some("insights")), Level::Debug);
+    assert_eq!(filter.threshold(Some("secrets")), Level::Error);
+    assert_eq!(filter.threshold(Some("violet")), Level::Warn);
+  }
+
+  #[test]
+  fn test_overrides_cover_nested_components_only() {
+    let mut filter = Filter::parse("error,insights=debug");
+    filter.set_component("insights-import", Level::Warn);
+
+    assert_eq!(filter.threshold(Some("insights-api")), Level::Debug);
+    assert_eq!(filter.threshold(Some("insights::server")), Level::Debug);
+    assert_eq!(filter.threshold(Some("insights-import")), Level::Warn);
+    assert_eq!(filter.threshold(Some("insightsful")), Level::Error);
+  }
+
+  #[test]
+  fn test_runtime_changes_replace_settings() {
+    let mut filter = Filter::parse("insights=debug");
+    filter.set_default(Level::Error);
+    filter.set_component("insights", Level::Info);
+
+    assert!(!filter.allows("warn", Some("secrets")));
+    assert!(!filter.allows("debug", Some("insights")));
+    assert!(filter.allows("info", Some("insights")));
+  }
+}
@@ -7,15 +7,25 @@
 //! - Multi-line message support with consistent formatting
 //! - Theatrical enhancements (announce, spotlight, flourish, showstopper)
 //! - Banner displays for important messages
+//! - Level thresholds, globally and per component (`BLIZZ_LOG=info,insights=debug`)
+//! - Optional per-level deduplication of repeated messages
+//! - Width-aware word wrapping, truncation and table rendering
+//! - Indented sections that scope everything logged inside them
+//! - Pluggable sinks, with capture for tests and plain-text output for files
 //! - Daemon logging infrastructure (with "daemon-logs" feature)
+//! - Layered settings shared across tools (with "config" feature)
+//! - The `--output` formats shared by every command line (clap support with "cli" feature)
 //! - All output to stderr (compatible with bash logging.sh)
 //!
 //! ## Usage
 //!
 //! Standard logging functions: `info()`, `warn()`, `error()`, `debug()`, `success()`
 //! Theatrical functions: `announce()`, `spotlight()`, `flourish()`, `showstopper()`
+//! Structure: `section()` for indented scopes, `table()` for aligned columns
+//! Output: `set_sink()` to redirect everything, `capture()` to record it in tests
 
 use colored::*;
+use std::cell::{Cell, RefCell};
 
 // Constants
 // ========
@@ -26,16 +36,96 @@ const DEFAULT_BANNER_WIDTH: usize = 50;
 /// Default prefix width for standard logging
 const PREFIX_WIDTH: usize = 7;
 
+/// Indentation added by each enclosing section
+const SECTION_INDENT: &str = "  ";
+
 // Core Functions
 // ==============
 
-/// Core logging function that handles the actual output
+/// Core logging function that hands a message to the current sink
 pub fn log(message: &str) {
-  for line in message.lines() {
-    eprintln!("{line}");
+  sink::emit("log", message);
+}
+
+/// Lines an entry is shown as on the terminal, before section indentation
+pub(crate) fn render(entry: &sink::Entry) -> Vec<String> {
+  let message = entry.message.as_str();
+  match entry.level.as_str() {
+    "log" => message.lines().map(str::to_string).collect(),
+    "section" => message.lines().map(|line| line.bold().to_string()).collect(),
+    "announce" => banner(message, 50, '-', |line| line.blue().bold()),
+    "spotlight" => banner(message, 40, '*', |line| line.yellow().bold()),
+    "flourish" => banner(message, 45, '~', |line| line.green().bold()),
+    "showstopper" => banner(message, 60, '*', |line| line.bright_red().bold()),
+    level => {
+      let (color, tag) = level_style(level);
+      let prefix = format_prefix(color, tag);
+      message.lines().map(|line| format!("{prefix} {line}")).collect()
+    }
   }
 }
 
+/// Banner lines around a message, each painted the same way
+fn banner(
+  message: &str,
+  width: usize,
+  border: char,
+  paint: fn(&str) -> ColoredString,
+) -> Vec<String> {
+  let lines = RefCell::new(Vec::new());
+  let push =
+    |text: &str| lines.borrow_mut().extend(text.lines().map(|line| paint(line).to_string()));
+  as_banner(push, message, Some(width), Some(border));
+  lines.into_inner()
+}
+
+// Sections
+// ========
+
+thread_local! {
+  /// Sections open on this thread
+  static SECTION_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Closes a section even if its body panics
+struct SectionGuard;
+
+impl Drop for SectionGuard {
+  fn drop(&mut self) {
+    SECTION_DEPTH.with(|depth| depth.set(depth.get().saturating_sub(1)));
+  }
+}
+
+/// Sections open on the current thread
+pub(crate) fn section_depth() -> usize {
+  SECTION_DEPTH.with(Cell::get)
+}
+
+/// Prefix for lines logged in the current section
+pub fn section_indent() -> String {
+  SECTION_INDENT.repeat(section_depth())
+}
+
+/// Log a title, then run `body` with everything it logs indented beneath it
+///
+/// Sections nest, and the indentation is per thread, so concurrent work does
+/// not pick up another thread's section.
+///
+/// ```
+/// let checks = bentley::section("vault", || {
+///   bentley::info("unlocked");
+///   bentley::section("groups", || bentley::info("github: 2 secrets"));
+///   3
+/// });
+/// assert_eq!(checks, 3);
+/// ```
+pub fn section<T>(title: &str, body: impl FnOnce() -> T) -> T {
+  sink::emit("section", title);
+  SECTION_DEPTH.with(|depth| depth.set(depth.get() + 1));
+  let _guard = SectionGuard;
+  body()
+}
+
 // Utility Functions
 // =================
 
@@ -44,6 +134,40 @@ fn format_prefix(color: Color, prefix: &str) -> String {
   format!("[{}]{:<width$}", prefix.color(color).bold(), "", width = PREFIX_WIDTH - prefix.len() - 2)
 }
 
+/// Color and tag used for a named log level
+fn level_style(level: &str) -> (Color, &'static str) {
+  match level {
+    "warn" => (Color::Yellow, "warn"),
+    "error" => (Color::Red, "error"),
+    "debug" => (Color::Magenta, "debug"),
+    "success" => (Color::Green, "sccs"),
+    "verbose" => (Color::Cyan, "verb"),
+    "fail" => (Color::BrightRed, "fail"),
+    _ => (Color::Blue, "info"),
+  }
+}
+
+/// Write a message with the prefix of a named level, bypassing deduplication
+pub(crate) fn write_level(level: &str, message: &str) {
+  sink::emit(level, message);
+}
+
+/// Log a message at a named level, collapsing repeats when deduplication is enabled
+fn log_level(level: &str, message: &str) {
+  if !level::enabled_here(level) {
+    return;
+  }
+  match dedup::check(level, message) {
+    dedup::Verdict::Suppress => {}
+    dedup::Verdict::Emit { repeated } => {
+      if let Some(count) = repeated {
+        write_level(level, &dedup::repeated_message(count));
+      }
+      write_level(level, message);
+    }
+  }
+}
+
 /// Create a banner line of the specified length and character
 #[cfg(not(tarpaulin_include))]
 pub fn banner_line(length: usize, char: char) -> String {
@@ -72,85 +196,76 @@ where
 /// Info level logging - general information
 #[cfg(not(tarpaulin_include))]
 pub fn info(message: &str) {
-  let prefix = format_prefix(Color::Blue, "info");
-  for line in message.lines() {
-    log(&format!("{prefix} {line}"));
-  }
+  log_level("info", message);
 }
 
 /// Warning level logging - something needs attention
 pub fn warn(message: &str) {
-  let prefix = format_prefix(Color::Yellow, "warn");
-  for line in message.lines() {
-    log(&format!("{prefix} {line}"));
-  }
+  log_level("warn", message);
 }
 
 /// Error level logging - something went wrong
 pub fn error(message: &str) {
-  let prefix = format_prefix(Color::Red, "error");
-  for line in message.lines() {
-    log(&format!("{prefix} {line}"));
-  }
+  log_level("error", message);
 }
 
 /// Debug level logging - detailed diagnostic information
 #[cfg(not(tarpaulin_include))]
 pub fn debug(message: &str) {
-  let prefix = format_prefix(Color::Magenta, "debug");
-  for line in message.lines() {
-    log(&format!("{prefix} {line}"));
-  }
+  log_level("debug", message);
 }
 
 /// Success level logging - something completed successfully
 #[cfg(not(tarpaulin_include))]
 pub fn success(message: &str) {
-  let prefix = format_prefix(Color::Green, "sccs");
-  for line in message.lines() {
-    log(&format!("{prefix} {line}"));
-  }
+  log_level("success", message);
 }
 
 /// Verbose level logging - detailed trace information
 pub fn verbose(message: &str) {
-  let prefix = format_prefix(Color::Cyan, "verb");
-  for line in message.lines() {
-    log(&format!("{prefix} {line}"));
-  }
+  log_level("verbose", message);
 }
 
 /// Fail level logging - critical failures
 #[cfg(not(tarpaulin_include))]
 pub fn fail(message: &str) {
-  let prefix = format_prefix(Color::BrightRed, "fail");
-  for line in message.lines() {
-    log(&format!("{prefix} {line}"));
-  }
+  log_level("fail", message);
 }
 
 /// Theatrical announcement - for important but not critical messages
 #[cfg(not(tarpaulin_include))]
 pub fn announce(message: &str) {
-  as_banner(|msg| log(&msg.blue().bold().to_string()), message, Some(50), Some('-'));
+  if !level::enabled_here("announce") {
+    return;
+  }
+  sink::emit("announce", message);
 }
 
 /// Spotlight - highlight important information
 #[cfg(not(tarpaulin_include))]
 pub fn spotlight(message: &str) {
-  as_banner(|msg| log(&msg.yellow().bold().to_string()), message, Some(40), Some('*'));
+  if !level::enabled_here("spotlight") {
+    return;
+  }
+  sink::emit("spotlight", message);
 }
 
 /// Flourish - celebrate successful completion
 #[cfg(not(tarpaulin_include))]
 pub fn flourish(message: &str) {
-  as_banner(|msg| log(&msg.green().bold().to_string()), message, Some(45), Some('~'));
+  if !level::enabled_here("flourish") {
+    return;
+  }
+  sink::emit("flourish", message);
 }
 
 /// Show stopper - for critical announcements
 #[cfg(not(tarpaulin_include))]
 pub fn showstopper(message: &str) {
-  as_banner(|msg| log(&msg.bright_red().bold().to_string()), message, Some(60), Some('*'));
+  if !level::enabled_here("showstopper") {
+    return;
+  }
+  sink::emit("showstopper", message);
 }
 
 // Exported Macros
@@ -227,6 +342,52 @@ macro_rules! showstopper {
   };
 }
 
+// Level Filtering
+// ===============
+
+/// Level thresholds, globally and per component
+pub mod level;
+
+pub use level::{set_component_level, set_level, Level};
+
+// Deduplication
+// =============
+
+/// Optional collapsing of repeated messages, configurable per level
+pub mod dedup;
+
+// Text Layout
+// ===========
+
+/// Terminal width detection, word wrapping and table rendering
+pub mod layout;
+
+pub use layout::{format_size, table};
+
+// Prompts
+// =======
+
+/// Yes/no questions on the terminal
+pub mod prompt;
+
+pub use prompt::confirm;
+
+// Output Formats
+// ==============
+
+/// `--output` values shared by every command line
+pub mod output;
+
+pub use output::OutputFormat;
+
+// Output Sinks
+// ============
+
+/// Pluggable destinations for log entries, including capture for tests
+pub mod sink;
+
+pub use sink::{capture, reset_sink, set_sink, CaptureSink, LogSink, WriterSink};
+
 // Daemon Logging
 // ==============
 
@@ -236,7 +397,14 @@ pub mod daemon_logs;
 
 // Re-export daemon_logs module contents for convenience
 #[cfg(feature = "daemon-logs")]
-pub use daemon_logs::{DaemonLogs, ErrorInfo, LogEntry, LogsRequest, LogsResponse};
+pub use daemon_logs::{DaemonLogs, ErrorInfo, LogEntry, LogsRequest, LogsResponse, Rotation};
+
+// Shared Settings
+// ===============
+
+/// Settings from `$BLIZZ_HOME/config.yaml` and the environment - available with "config" feature
+#[cfg(feature = "config")]
+pub mod config;
 
 // Tests
 // =====
@@ -396,6 +564,24 @@ mod tests {
     assert_eq!(captured[2], "@@@@@@@@@@@@@@@"); // 15 '@' characters
   }
 
+  // Section Tests
+  // =============
+
+  #[test]
+  fn test_section_indents_nested_output_and_restores() {
+    assert_eq!(section_indent(), "");
+    let inner = section("outer", || {
+      assert_eq!(section_indent(), "  ");
+      section("inner", section_indent)
+    });
+    assert_eq!(inner, "    ");
+    assert_eq!(section_indent(), "");
+
+    let panicked = std::panic::catch_unwind(|| section("boom", || panic!("body failed")));
+    assert!(panicked.is_err());
+    assert_eq!(section_indent(), "");
+  }
+
   // Constants Tests
   // ===============
 
@@ -0,0 +1,35 @@
+//! Output formats shared by the blizz command lines
+//!
+//! `blizz`, `secrets` and `violet` all take `--output` with these values. Each
+//! command supports the formats that make sense for it and refuses the rest.
+
+use std::fmt;
+
+/// How a command prints its results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+  /// Human-readable text
+  #[default]
+  Text,
+  /// Machine-readable JSON on stdout, without banners
+  Json,
+  /// Standalone HTML page (lint only)
+  Html,
+}
+
+impl OutputFormat {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      OutputFormat::Text => "text",
+      OutputFormat::Json => "json",
+      OutputFormat::Html => "html",
+    }
+  }
+}
+
+impl fmt::Display for OutputFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
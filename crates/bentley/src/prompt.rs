@@ -0,0 +1,33 @@
+//! Yes/no questions on the terminal
+
+use std::io::{self, BufRead, Write};
+
+/// Ask `question` on stdout with a `(y/N)` suffix and read the answer from stdin
+///
+/// Only `y` and `yes`, in any case, count as yes; anything else, including
+/// end of input, is no.
+pub fn confirm(question: &str) -> io::Result<bool> {
+  print!("{question} (y/N): ");
+  io::stdout().flush()?;
+  answer(io::stdin().lock())
+}
+
+fn answer(mut input: impl BufRead) -> io::Result<bool> {
+  let mut line = String::new();
+  input.read_line(&mut line)?;
+  Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_only_yes_confirms() {
+    for (input, expected) in
+      [("y\n", true), ("YES\n", true), ("n\n", false), ("\n", false), ("", false)]
+    {
+      assert_eq!(answer(input.as_bytes()).unwrap(), expected, "{input:?}");
+    }
+  }
+}
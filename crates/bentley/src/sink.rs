@@ -0,0 +1,280 @@
+//! Where log output goes
+//!
+//! Everything the logging functions emit reaches a [`LogSink`] as an [`Entry`]: the
+//! level, the message and how many sections deep it was logged. Without a sink set,
+//! entries are rendered to stderr as they always have been. [`set_sink`] replaces
+//! that for the whole process, e.g. with a [`WriterSink`] to send a CLI's output to
+//! a file, and [`capture`] records what a closure logs on the current thread, so a
+//! test can assert on the warnings it caused without picking up other tests' output.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+// Types and Data Structures
+// =========================
+
+/// One message as it was logged
+///
+/// `level` is the logging function's name (`"info"`, `"warn"`, `"announce"`, ...),
+/// `"section"` for a section title, or `"log"` for [`crate::log`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+  pub level: String,
+  pub message: String,
+  pub depth: usize,
+  pub timestamp: DateTime<Utc>,
+}
+
+/// Destination for log entries
+pub trait LogSink: Send + Sync {
+  fn write(&self, entry: &Entry);
+}
+
+/// The default sink: colored output on stderr
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stderr;
+
+impl LogSink for Stderr {
+  fn write(&self, entry: &Entry) {
+    let indent = crate::SECTION_INDENT.repeat(entry.depth);
+    for line in crate::render(entry) {
+      eprintln!("{indent}{line}");
+    }
+  }
+}
+
+/// Plain-text lines written to any writer, such as a log file
+#[derive(Debug)]
+pub struct WriterSink<W> {
+  writer: Mutex<W>,
+}
+
+impl<W: Write + Send> WriterSink<W> {
+  pub fn new(writer: W) -> Self {
+    Self { writer: Mutex::new(writer) }
+  }
+
+  /// The writer back, e.g. to inspect a buffer
+  pub fn into_inner(self) -> W {
+    self.writer.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+impl<W: Write + Send> LogSink for WriterSink<W> {
+  fn write(&self, entry: &Entry) {
+    let Ok(mut writer) = self.writer.lock() else {
+      return;
+    };
+    let indent = crate::SECTION_INDENT.repeat(entry.depth);
+    for line in entry.message.lines() {
+      let _ = match entry.level.as_str() {
+        "log" | "section" => writeln!(writer, "{indent}{line}"),
+        level => writeln!(writer, "{indent}[{level}] {line}"),
+      };
+    }
+    let _ = writer.flush();
+  }
+}
+
+/// Records entries for later inspection; clones share the same record
+#[derive(Debug, Clone, Default)]
+pub struct CaptureSink {
+  entries: Arc<Mutex<Vec<Entry>>>,
+}
+
+impl CaptureSink {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Everything recorded so far, oldest first
+  pub fn entries(&self) -> Vec<Entry> {
+    self.entries.lock().map(|entries| entries.clone()).unwrap_or_default()
+  }
+
+  /// Messages recorded at one level, oldest first
+  pub fn messages(&self, level: &str) -> Vec<String> {
+    messages(&self.entries(), level)
+  }
+
+  /// Forget everything recorded so far
+  pub fn clear(&self) {
+    if let Ok(mut entries) = self.entries.lock() {
+      entries.clear();
+    }
+  }
+}
+
+impl LogSink for CaptureSink {
+  fn write(&self, entry: &Entry) {
+    if let Ok(mut entries) = self.entries.lock() {
+      entries.push(entry.clone());
+    }
+  }
+}
+
+/// Messages logged at one level, oldest first
+pub fn messages(entries: &[Entry], level: &str) -> Vec<String> {
+  entries.iter().filter(|entry| entry.level == level).map(|entry| entry.message.clone()).collect()
+}
+
+// Process-wide Sink
+// =================
+
+static GLOBAL: OnceLock<RwLock<Option<Box<dyn LogSink>>>> = OnceLock::new();
+
+fn global() -> &'static RwLock<Option<Box<dyn LogSink>>> {
+  GLOBAL.get_or_init(|| RwLock::new(None))
+}
+
+/// Send everything logged from now on, on every thread, to `sink`
+pub fn set_sink(sink: Box<dyn LogSink>) {
+  if let Ok(mut global) = global().write() {
+    *global = Some(sink);
+  }
+}
+
+/// Go back to logging to stderr
+pub fn reset_sink() {
+  if let Ok(mut global) = global().write() {
+    *global = None;
+  }
+}
+
+// Per-thread Capture
+// ==================
+
+thread_local! {
+  /// Capture in progress on this thread, taking precedence over the process sink
+  static CAPTURE: RefCell<Option<CaptureSink>> = const { RefCell::new(None) };
+}
+
+/// Restores the enclosing capture even if the body panics
+struct CaptureGuard(Option<CaptureSink>);
+
+impl Drop for CaptureGuard {
+  fn drop(&mut self) {
+    let previous = self.0.take();
+    CAPTURE.with(|capture| *capture.borrow_mut() = previous);
+  }
+}
+
+/// Run `body`, returning its result and everything it logged on this thread
+///
+/// Captured entries are not shown on stderr or passed to the process sink.
+///
+/// ```
+/// let (_, entries) = bentley::capture(|| bentley::warn("disk almost full"));
+/// assert_eq!(bentley::sink::messages(&entries, "warn"), ["disk almost full"]);
+/// ```
+pub fn capture<T>(body: impl FnOnce() -> T) -> (T, Vec<Entry>) {
+  let sink = CaptureSink::new();
+  let previous = CAPTURE.with(|capture| capture.borrow_mut().replace(sink.clone()));
+  let guard = CaptureGuard(previous);
+  let result = body();
+  drop(guard);
+  (result, sink.entries())
+}
+
+/// Hand an entry to the capture in progress, the process sink, or stderr
+pub(crate) fn emit(level: &str, message: &str) {
+  let entry = Entry {
+    level: level.to_string(),
+    message: message.to_string(),
+    depth: crate::section_depth(),
+    timestamp: Utc::now(),
+  };
+
+  if let Some(capture) = CAPTURE.with(|capture| capture.borrow().clone()) {
+    capture.write(&entry);
+    return;
+  }
+  match global().read() {
+    Ok(global) => match global.as_ref() {
+      Some(sink) => sink.write(&entry),
+      None => Stderr.write(&entry),
+    },
+    Err(_) => Stderr.write(&entry),
+  }
+}
+
+// Tests
+// =====
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_capture_records_structured_entries() {
+    let (count, entries) = capture(|| {
+      crate::warn("low disk");
+      crate::section("vault", || crate::success("unlocked"));
+      crate::announce("ready");
+      2
+    });
+
+    assert_eq!(count, 2);
+    let levels: Vec<(&str, &str, usize)> = entries
+      .iter()
+      .map(|entry| (entry.level.as_str(), entry.message.as_str(), entry.depth))
+      .collect();
+    assert_eq!(
+      levels,
+      [
+        ("warn", "low disk", 0),
+        ("section", "vault", 0),
+        ("success", "unlocked", 1),
+        ("announce", "ready", 0)
+      ]
+    );
+    assert_eq!(messages(&entries, "warn"), ["low disk"]);
+  }
+
+  #[test]
+  fn test_nested_capture_restores_outer() {
+    let (inner, outer) = capture(|| {
+      crate::info("outer before");
+      let (_, inner) = capture(|| crate::info("inner"));
+      crate::info("outer after");
+      inner
+    });
+    assert_eq!(messages(&inner, "info"), ["inner"]);
+    assert_eq!(messages(&outer, "info"), ["outer before", "outer after"]);
+
+    let panicked = std::panic::catch_unwind(|| capture(|| panic!("body failed")));
+    assert!(panicked.is_err());
+    assert!(CAPTURE.with(|capture| capture.borrow().is_none()));
+  }
+
+  #[test]
+  fn test_writer_sink_writes_plain_lines() {
+    let sink = WriterSink::new(Vec::new());
+    let entry = |level: &str, message: &str, depth| Entry {
+      level: level.to_string(),
+      message: message.to_string(),
+      depth,
+      timestamp: Utc::now(),
+    };
+    sink.write(&entry("section", "vault", 0));
+    sink.write(&entry("warn", "first\nsecond", 1));
+
+    let written = String::from_utf8(sink.into_inner()).unwrap();
+    assert_eq!(written, "vault\n  [warn] first\n  [warn] second\n");
+  }
+
+  #[test]
+  fn test_capture_sink_clones_share_entries() {
+    let sink = CaptureSink::new();
+    let handle = sink.clone();
+    let (_, entries) = capture(|| crate::error("boom"));
+    sink.write(&entries[0]);
+
+    assert_eq!(handle.messages("error"), ["boom"]);
+    handle.clear();
+    assert!(sink.entries().is_empty());
+  }
+}
@@ -20,3 +20,13 @@ fn test_multiline_messages() {
   debug(multiline_msg);
   success(multiline_msg);
 }
+
+#[test]
+fn test_capture_from_downstream_crate() {
+  let (_, entries) = capture(|| {
+    warn("Test warning message");
+    success("Test success message");
+  });
+  assert_eq!(sink::messages(&entries, "warn"), ["Test warning message"]);
+  assert_eq!(sink::messages(&entries, "success"), ["Test success message"]);
+}
@@ -0,0 +1,531 @@
+//! Interactive terminal browser for insights
+//!
+//! `blizz browse` draws the knowledge base with ratatui in three panes: the topic
+//! filter on the left, the insights of the selected topic in the middle and a
+//! preview of the selected insight on the right. `/` searches the list as you
+//! type. Deleting and editing leave the browser and run the same handlers as
+//! `insights delete` and `insights update`, then come back with the list
+//! reloaded.
+
+use anyhow::{anyhow, bail, Context, Result};
+use console::{style, Term};
+use insights::cli::commands;
+use insights::cli::server_manager::ensure_server_running;
+use insights::client::types::InsightSummary;
+use insights::client::{get_client, InsightsClient};
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::terminal::{
+  disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::process::Command;
+
+const TOPICS_WIDTH: u16 = 22;
+const HELP: &str = "↑↓ move  ←→ pane  / search  e edit  d delete  r reload  q quit";
+
+/// Which pane the arrow keys move in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pane {
+  Topics,
+  Insights,
+}
+
+/// What the browser needs done outside of its own screen
+#[derive(Debug, PartialEq)]
+pub enum Action {
+  Quit,
+  Reload,
+  Edit { topic: String, name: String },
+  Delete { topic: String, name: String },
+}
+
+/// Everything on screen, kept apart from the terminal so it can be tested
+pub struct Browser {
+  insights: Vec<InsightSummary>,
+  topics: Vec<String>,
+  /// Selected row of the topics pane, where 0 is every topic
+  topic: usize,
+  /// Selected row of the visible insights
+  selected: usize,
+  query: String,
+  searching: bool,
+  pane: Pane,
+}
+
+impl Browser {
+  pub fn new(mut insights: Vec<InsightSummary>) -> Self {
+    insights.sort_by(|a, b| (&a.topic, &a.name).cmp(&(&b.topic, &b.name)));
+    let mut topics: Vec<String> = insights.iter().map(|i| i.topic.clone()).collect();
+    topics.dedup();
+
+    Self {
+      insights,
+      topics,
+      topic: 0,
+      selected: 0,
+      query: String::new(),
+      searching: false,
+      pane: Pane::Insights,
+    }
+  }
+
+  /// Replace the insights, keeping the topic, search and selection where possible
+  pub fn reload(&mut self, insights: Vec<InsightSummary>) {
+    let topic = self.topic.checked_sub(1).map(|i| self.topics[i].clone());
+    let current = self.current().map(|i| (i.topic.clone(), i.name.clone()));
+    let fresh = Self::new(insights);
+    self.insights = fresh.insights;
+    self.topics = fresh.topics;
+
+    self.topic =
+      topic.and_then(|topic| self.topics.iter().position(|t| *t == topic)).map_or(0, |i| i + 1);
+    if let Some((topic, name)) = current {
+      let visible = self.visible();
+      self.selected = visible
+        .iter()
+        .position(|i| i.topic == topic && i.name == name)
+        .unwrap_or(self.selected.min(visible.len().saturating_sub(1)));
+    }
+  }
+
+  /// Insights in the selected topic that match the search
+  pub fn visible(&self) -> Vec<&InsightSummary> {
+    let topic = self.topic.checked_sub(1).map(|i| self.topics[i].as_str());
+    let query = self.query.to_lowercase();
+
+    self
+      .insights
+      .iter()
+      .filter(|i| topic.is_none_or(|topic| i.topic == topic))
+      .filter(|i| {
+        query.is_empty()
+          || [&i.topic, &i.name, &i.overview].iter().any(|f| f.to_lowercase().contains(&query))
+          || i.tags.iter().any(|t| t.to_lowercase().contains(&query))
+      })
+      .collect()
+  }
+
+  /// The insight under the cursor
+  pub fn current(&self) -> Option<&InsightSummary> {
+    self.visible().get(self.selected).copied()
+  }
+
+  /// Apply a key press, returning anything the caller has to carry out
+  pub fn handle(&mut self, key: KeyEvent) -> Option<Action> {
+    let ctrl_c = key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+    if ctrl_c {
+      return Some(Action::Quit);
+    }
+
+    if self.searching {
+      match key.code {
+        KeyCode::Char(c) => self.query.push(c),
+        KeyCode::Backspace => {
+          self.query.pop();
+        }
+        KeyCode::Esc => {
+          self.query.clear();
+          self.searching = false;
+        }
+        KeyCode::Enter => self.searching = false,
+        _ => return None,
+      }
+      self.selected = 0;
+      return None;
+    }
+
+    match key.code {
+      KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+      KeyCode::Char('r') => return Some(Action::Reload),
+      KeyCode::Char('/') => self.searching = true,
+      KeyCode::Tab | KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l') => {
+        self.pane = match self.pane {
+          Pane::Topics => Pane::Insights,
+          Pane::Insights => Pane::Topics,
+        };
+      }
+      KeyCode::Up | KeyCode::Char('k') => self.step(-1),
+      KeyCode::Down | KeyCode::Char('j') => self.step(1),
+      KeyCode::Char(c @ ('e' | 'd')) => {
+        let insight = self.current()?;
+        let (topic, name) = (insight.topic.clone(), insight.name.clone());
+        return Some(match c {
+          'e' => Action::Edit { topic, name },
+          _ => Action::Delete { topic, name },
+        });
+      }
+      _ => {}
+    }
+    None
+  }
+
+  fn step(&mut self, delta: isize) {
+    match self.pane {
+      Pane::Topics => {
+        self.topic = self.topic.saturating_add_signed(delta).min(self.topics.len());
+        self.selected = 0;
+      }
+      Pane::Insights => {
+        let last = self.visible().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+      }
+    }
+  }
+
+  /// Draw the header, the three panes and the help or search line
+  pub fn draw(&self, frame: &mut Frame, details: Option<&str>) {
+    let visible = self.visible();
+    let [header, body, footer] =
+      Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .areas(frame.area());
+    let [topics_area, list_area, preview_area] = Layout::horizontal([
+      Constraint::Length(TOPICS_WIDTH),
+      Constraint::Percentage(30),
+      Constraint::Min(0),
+    ])
+    .areas(body);
+
+    frame.render_widget(
+      Line::from(vec![
+        Span::from("blizz browse").bold(),
+        Span::from(format!(" {} of {} insights", visible.len(), self.insights.len())),
+      ]),
+      header,
+    );
+
+    let mut topics = vec![format!("All ({})", self.insights.len())];
+    topics.extend(self.topics.iter().cloned());
+    frame.render_stateful_widget(
+      pane_list(topics, "Topics", self.pane == Pane::Topics),
+      topics_area,
+      &mut ListState::default().with_selected(Some(self.topic)),
+    );
+
+    let names: Vec<String> = visible.iter().map(|i| i.name.clone()).collect();
+    frame.render_stateful_widget(
+      pane_list(names, "Insights", self.pane == Pane::Insights),
+      list_area,
+      &mut ListState::default().with_selected(Some(self.selected)),
+    );
+
+    let preview = match visible.get(self.selected) {
+      Some(insight) => preview(insight, details),
+      None => Text::from("No insights match".dim()),
+    };
+    frame.render_widget(
+      Paragraph::new(preview).wrap(Wrap { trim: false }).block(Block::bordered().title("Preview")),
+      preview_area,
+    );
+
+    let status = if self.searching || !self.query.is_empty() {
+      Line::from(format!("/{}", self.query))
+    } else {
+      Line::from(HELP.dim())
+    };
+    frame.render_widget(status, footer);
+  }
+}
+
+/// A bordered list whose selected row stands out more while it has the focus
+fn pane_list(items: Vec<String>, title: &str, focused: bool) -> List<'static> {
+  let (border, highlight) = if focused {
+    (Style::new().fg(Color::Cyan), Style::new().add_modifier(Modifier::REVERSED))
+  } else {
+    (Style::new(), Style::new().add_modifier(Modifier::BOLD))
+  };
+  List::new(items)
+    .block(Block::bordered().title(title.to_string()).border_style(border))
+    .highlight_style(highlight)
+}
+
+fn preview<'a>(insight: &'a InsightSummary, details: Option<&'a str>) -> Text<'a> {
+  let mut lines = vec![Line::from(format!("{}/{}", insight.topic, insight.name).bold())];
+  if !insight.tags.is_empty() {
+    lines.push(Line::from(format!("#{}", insight.tags.join(" #")).cyan()));
+  }
+  lines.push(Line::default());
+  lines.extend(insight.overview.lines().map(Line::from));
+  lines.push(Line::default());
+  match details {
+    Some(details) => lines.extend(details.lines().map(Line::from)),
+    None => lines.push(Line::from("Loading…".dim())),
+  }
+  Text::from(lines)
+}
+
+// Terminal
+// ========
+
+/// Raw mode on the alternate screen, left again however the browser exits
+struct Screen {
+  terminal: Terminal<CrosstermBackend<std::io::Stdout>>,
+}
+
+impl Screen {
+  fn enter() -> Result<Self> {
+    let terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+    enable_raw_mode()?;
+    let screen = Self { terminal };
+    ratatui::crossterm::execute!(std::io::stdout(), EnterAlternateScreen)?;
+    Ok(screen)
+  }
+}
+
+impl Drop for Screen {
+  fn drop(&mut self) {
+    let _ = self.terminal.show_cursor();
+    let _ = disable_raw_mode();
+    let _ = ratatui::crossterm::execute!(std::io::stdout(), LeaveAlternateScreen);
+  }
+}
+
+pub async fn execute() -> Result<()> {
+  if !std::io::stdout().is_terminal() {
+    bail!("blizz browse needs an interactive terminal");
+  }
+
+  ensure_server_running().await?;
+  let client = get_client();
+  let mut browser = Browser::new(load(&client).await?);
+  let mut details = HashMap::new();
+
+  loop {
+    let action = browse(&client, &mut browser, &mut details).await?;
+    let result = match action {
+      Action::Quit => return Ok(()),
+      Action::Reload => Ok(()),
+      Action::Delete { ref topic, ref name } => commands::delete_insight(topic, name, false).await,
+      Action::Edit { ref topic, ref name } => edit(&client, topic, name).await,
+    };
+
+    if let Some(error) = result.err() {
+      eprintln!("{} {error}", style("✗").red());
+    }
+    if action != Action::Reload {
+      println!("{}", style("Press any key to return to the browser").dim());
+      Term::stdout().read_key_raw()?;
+    }
+
+    browser.reload(load(&client).await?);
+    details.clear();
+  }
+}
+
+async fn load(client: &InsightsClient) -> Result<Vec<InsightSummary>> {
+  Ok(client.list_insights(None, &[]).await?.insights)
+}
+
+/// Run the browser until it needs something done outside of it
+async fn browse(
+  client: &InsightsClient,
+  browser: &mut Browser,
+  details: &mut HashMap<(String, String), String>,
+) -> Result<Action> {
+  let mut screen = Screen::enter()?;
+  screen.terminal.clear()?;
+
+  loop {
+    if let Some(key) = browser.current().map(|i| (i.topic.clone(), i.name.clone())) {
+      if let Entry::Vacant(entry) = details.entry(key) {
+        let (topic, name) = entry.key();
+        screen.terminal.draw(|frame| browser.draw(frame, None))?;
+        let body = match client.get_insight(topic, name, false).await {
+          Ok(response) => response.insight.details,
+          Err(e) => format!("Could not load this insight: {e}"),
+        };
+        entry.insert(body);
+      }
+    }
+    let shown = browser.current().and_then(|i| details.get(&(i.topic.clone(), i.name.clone())));
+    screen.terminal.draw(|frame| browser.draw(frame, shown.map(String::as_str)))?;
+
+    // Windows also reports key releases; only presses count
+    if let Event::Key(key) = event::read()? {
+      if key.kind == KeyEventKind::Press {
+        if let Some(action) = browser.handle(key) {
+          return Ok(action);
+        }
+      }
+    }
+  }
+}
+
+/// Edit an insight in `$VISUAL` or `$EDITOR`, then save it through `insights update`
+async fn edit(client: &InsightsClient, topic: &str, name: &str) -> Result<()> {
+  let insight = client.get_insight(topic, name, false).await?.insight;
+  let original = format!("---\n{}\n---\n\n{}\n", insight.overview, insight.details);
+
+  let mut file = tempfile::Builder::new().suffix(".md").tempfile()?;
+  file.write_all(original.as_bytes())?;
+  file.flush()?;
+
+  let editor = std::env::var("VISUAL")
+    .or_else(|_| std::env::var("EDITOR"))
+    .unwrap_or_else(|_| "vi".to_string());
+  let mut words = editor.split_whitespace();
+  let program = words.next().ok_or_else(|| anyhow!("The editor command is empty"))?;
+  let status = Command::new(program)
+    .args(words)
+    .arg(file.path())
+    .status()
+    .with_context(|| format!("Failed to start editor '{editor}'"))?;
+  if !status.success() {
+    bail!("Editor exited with {status}; the insight was not changed");
+  }
+
+  let edited = std::fs::read_to_string(file.path())?;
+  if edited == original {
+    println!("No changes to {topic}/{name}");
+    return Ok(());
+  }
+  let (overview, details) = parse_edited(&edited)?;
+  commands::update_insight(topic, name, Some(&overview), Some(&details), None, false, false).await
+}
+
+/// Split an edited insight back into its overview and details
+pub fn parse_edited(content: &str) -> Result<(String, String)> {
+  let rest = content
+    .strip_prefix("---\n")
+    .ok_or_else(|| anyhow!("The edited insight must start with a --- line"))?;
+  let (overview, details) = rest
+    .split_once("\n---\n")
+    .ok_or_else(|| anyhow!("The overview must be closed by a --- line"))?;
+  Ok((overview.trim().to_string(), details.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::Utc;
+  use ratatui::backend::TestBackend;
+
+  fn insight(topic: &str, name: &str, overview: &str, tags: &[&str]) -> InsightSummary {
+    InsightSummary {
+      topic: topic.to_string(),
+      name: name.to_string(),
+      overview: overview.to_string(),
+      tags: tags.iter().map(|t| t.to_string()).collect(),
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      expires_at: None,
+    }
+  }
+
+  fn browser() -> Browser {
+    Browser::new(vec![
+      insight("rust", "errors", "Use anyhow in binaries", &["errors"]),
+      insight("git", "rebase", "Rebase before merging", &[]),
+      insight("rust", "async", "Prefer tokio", &["runtime"]),
+    ])
+  }
+
+  fn names(browser: &Browser) -> Vec<&str> {
+    browser.visible().iter().map(|i| i.name.as_str()).collect()
+  }
+
+  fn press(browser: &mut Browser, code: KeyCode) -> Option<Action> {
+    browser.handle(KeyEvent::new(code, KeyModifiers::NONE))
+  }
+
+  #[test]
+  fn test_topic_pane_filters_the_list() {
+    let mut browser = browser();
+    assert_eq!(names(&browser), ["rebase", "async", "errors"]);
+
+    press(&mut browser, KeyCode::Tab);
+    press(&mut browser, KeyCode::Down);
+    press(&mut browser, KeyCode::Down);
+    assert_eq!(names(&browser), ["async", "errors"]);
+
+    press(&mut browser, KeyCode::Down);
+    assert_eq!(names(&browser), ["async", "errors"], "stops at the last topic");
+  }
+
+  #[test]
+  fn test_search_matches_names_overviews_and_tags() {
+    let mut browser = browser();
+    press(&mut browser, KeyCode::Char('/'));
+    for c in "RUNTIME".chars() {
+      press(&mut browser, KeyCode::Char(c));
+    }
+    assert_eq!(names(&browser), ["async"]);
+
+    // Keys are typed into the search until it is closed
+    assert_eq!(press(&mut browser, KeyCode::Char('q')), None);
+    assert!(names(&browser).is_empty());
+    press(&mut browser, KeyCode::Esc);
+    assert_eq!(names(&browser).len(), 3);
+  }
+
+  #[test]
+  fn test_actions_target_the_selected_insight() {
+    let mut browser = browser();
+    press(&mut browser, KeyCode::Down);
+    assert_eq!(
+      press(&mut browser, KeyCode::Char('d')),
+      Some(Action::Delete { topic: "rust".to_string(), name: "async".to_string() })
+    );
+    assert_eq!(
+      press(&mut browser, KeyCode::Char('e')),
+      Some(Action::Edit { topic: "rust".to_string(), name: "async".to_string() })
+    );
+    assert_eq!(press(&mut browser, KeyCode::Char('q')), Some(Action::Quit));
+
+    press(&mut browser, KeyCode::Char('/'));
+    press(&mut browser, KeyCode::Char('x'));
+    let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    assert_eq!(browser.handle(ctrl_c), Some(Action::Quit), "even while searching");
+    press(&mut browser, KeyCode::Enter);
+    assert_eq!(press(&mut browser, KeyCode::Char('d')), None, "nothing is selected");
+  }
+
+  #[test]
+  fn test_reload_keeps_the_selection() {
+    let mut browser = browser();
+    press(&mut browser, KeyCode::Down);
+    press(&mut browser, KeyCode::Down);
+
+    browser.reload(vec![
+      insight("rust", "errors", "Use anyhow in binaries", &["errors"]),
+      insight("rust", "async", "Prefer tokio", &["runtime"]),
+    ]);
+    assert_eq!(browser.current().unwrap().name, "errors");
+  }
+
+  #[test]
+  fn test_draw_fills_the_panes() {
+    let browser = browser();
+    let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+    terminal.draw(|frame| browser.draw(frame, Some("Pin the runtime version"))).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let lines: Vec<String> = (0..buffer.area.height)
+      .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect())
+      .collect();
+    assert!(lines[0].contains("3 of 3 insights"));
+    assert!(lines[1].contains("Topics") && lines[1].contains("Insights"));
+    assert!(lines[1].contains("Preview"));
+    assert!(lines.iter().any(|l| l.contains("All (3)") && l.contains("rebase")));
+    assert!(lines.iter().any(|l| l.contains("git/rebase")));
+    assert!(lines.iter().any(|l| l.contains("Pin the runtime version")));
+    assert!(lines[11].contains("q quit"));
+  }
+
+  #[test]
+  fn test_parse_edited() {
+    let (overview, details) = parse_edited("---\nShort\n---\n\nLong\nbody\n").unwrap();
+    assert_eq!(overview, "Short");
+    assert_eq!(details, "Long\nbody");
+
+    assert!(parse_edited("no frontmatter").is_err());
+    assert!(parse_edited("---\nunterminated").is_err());
+  }
+}
@@ -0,0 +1,191 @@
+//! Shared settings
+//!
+//! `blizz config` reads and writes `$BLIZZ_HOME/config.yaml`, the settings file insights
+//! and secrets consult. Each setting's environment variable still overrides the file,
+//! and `list` shows which layer every value comes from.
+
+use anyhow::{anyhow, Result};
+use bentley::config::{ConfigFile, Key, Layer};
+use bentley::layout::{Column, Table};
+use clap::Subcommand;
+use serde::Serialize;
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+  /// Print a setting's effective value
+  Get {
+    /// Setting name, e.g. insights.server_url
+    key: String,
+  },
+  /// Store a setting in the settings file
+  Set {
+    /// Setting name, e.g. insights.server_url
+    key: String,
+    value: String,
+  },
+  /// Remove a setting from the settings file, going back to its default
+  Unset {
+    /// Setting name, e.g. insights.server_url
+    key: String,
+  },
+  /// List every setting with its value and where it comes from
+  List,
+}
+
+/// One setting as `list` and `get` report it
+#[derive(Debug, Serialize, PartialEq)]
+struct Resolved {
+  key: &'static str,
+  env: &'static str,
+  /// The value from the environment or the settings file, `None` when the default applies
+  value: Option<String>,
+  default: &'static str,
+  source: String,
+  description: &'static str,
+}
+
+pub fn execute(command: ConfigCommands, json: bool) -> Result<()> {
+  let mut file = ConfigFile::load()?;
+  match command {
+    ConfigCommands::Get { key } => {
+      let resolved = resolve(&file, find(&key)?);
+      if json {
+        println!("{}", serde_json::to_string_pretty(&resolved)?);
+      } else {
+        println!("{}", resolved.value.as_deref().unwrap_or(resolved.default));
+      }
+    }
+    ConfigCommands::Set { key, value } => {
+      let key = find(&key)?;
+      file.set(key.name, &value)?;
+      file.save()?;
+      println!("{} = {value}  ({})", key.name, file.path().display());
+      warn_if_overridden(key);
+    }
+    ConfigCommands::Unset { key } => {
+      let key = find(&key)?;
+      if file.unset(key.name) {
+        file.save()?;
+        println!("{} unset, default: {}", key.name, shown_default(key));
+      } else {
+        println!("{} is not set in {}", key.name, file.path().display());
+      }
+      warn_if_overridden(key);
+    }
+    ConfigCommands::List => {
+      let settings: Vec<Resolved> = keys().map(|key| resolve(&file, key)).collect();
+      if json {
+        println!("{}", serde_json::to_string_pretty(&settings)?);
+      } else {
+        print!("{}", settings_table(&settings));
+        println!("\nsettings file: {}", file.path().display());
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Every setting a blizz tool reads
+fn keys() -> impl Iterator<Item = &'static Key> {
+  insights::settings::KEYS.iter().chain(secrets::settings::KEYS)
+}
+
+fn find(name: &str) -> Result<&'static Key> {
+  keys().find(|key| key.name == name).ok_or_else(|| {
+    anyhow!("Unknown setting '{name}'; run `blizz config list` to see every setting")
+  })
+}
+
+fn resolve(file: &ConfigFile, key: &'static Key) -> Resolved {
+  let (value, layer) = file.resolve(key);
+  let source = match layer {
+    Layer::File(_) => "config file".to_string(),
+    layer => layer.to_string(),
+  };
+  Resolved {
+    key: key.name,
+    env: key.env,
+    value,
+    default: key.default,
+    source,
+    description: key.description,
+  }
+}
+
+fn shown_default(key: &Key) -> &'static str {
+  if key.default.is_empty() {
+    "(none)"
+  } else {
+    key.default
+  }
+}
+
+fn warn_if_overridden(key: &Key) {
+  if std::env::var_os(key.env).is_some() {
+    bentley::warn!(&format!("${} is set and overrides {} in this shell", key.env, key.name));
+  }
+}
+
+fn settings_table(settings: &[Resolved]) -> Table {
+  let mut table = Table::new()
+    .column(Column::new("setting"))
+    .column(Column::new("value").max_width(40))
+    .column(Column::new("from"))
+    .column(Column::new("description"))
+    .rule('=');
+  for setting in settings {
+    let value = match &setting.value {
+      Some(value) => value.clone(),
+      None if setting.default.is_empty() => "(none)".to_string(),
+      None => setting.default.to_string(),
+    };
+    table.push_row([
+      setting.key.to_string(),
+      value,
+      setting.source.clone(),
+      setting.description.to_string(),
+    ]);
+  }
+  table
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_every_setting_is_found_by_name() {
+    let names: Vec<&str> = keys().map(|key| key.name).collect();
+    let mut unique = names.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), names.len());
+    assert!(names.contains(&"insights.server_url"));
+    assert!(names.contains(&"secrets.backend"));
+
+    assert_eq!(find("insights.rerank.final_limit").unwrap().env, "INSIGHTS_RERANK_FINAL_LIMIT");
+    let error = find("insights.nope").unwrap_err().to_string();
+    assert!(error.contains("Unknown setting 'insights.nope'"));
+  }
+
+  #[test]
+  fn test_resolve_reports_layers() {
+    let dir = TempDir::new().unwrap();
+    let mut file = ConfigFile::at(dir.path().join("config.yaml")).unwrap();
+    let key = find("insights.webhooks.backoff_ms").unwrap();
+    std::env::remove_var(key.env);
+
+    let resolved = resolve(&file, key);
+    assert_eq!((resolved.value, resolved.source.as_str()), (None, "default"));
+    assert_eq!(resolved.default, "1000");
+
+    file.set(key.name, "250").unwrap();
+    let resolved = resolve(&file, key);
+    assert_eq!((resolved.value.as_deref(), resolved.source.as_str()), (Some("250"), "config file"));
+
+    let table = settings_table(&[resolve(&file, key)]).to_string();
+    assert!(table.contains("insights.webhooks.backoff_ms"));
+    assert!(table.contains("250"));
+  }
+}
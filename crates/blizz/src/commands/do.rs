@@ -1,16 +1,45 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Deserializer, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
 use std::fs;
 use std::path::Path;
 use std::process::Stdio;
 use tokio::process::Command;
+use tokio::task::JoinSet;
 
 #[derive(Debug, Clone, Serialize)]
 pub enum TaskCommand {
   String(String),
   Array(Vec<String>),
+  /// `run` (optional) once every task in `depends_on` has succeeded, with the
+  /// variables of the `secrets` groups in its environment
+  Configured {
+    run: Option<Box<TaskCommand>>,
+    depends_on: Vec<String>,
+    secrets: Vec<String>,
+  },
+}
+
+/// The mapping form of a task
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskSpec {
+  run: Option<TaskCommand>,
+  #[serde(default)]
+  depends_on: Vec<String>,
+  #[serde(default)]
+  secrets: Vec<String>,
+}
+
+impl From<TaskSpec> for TaskCommand {
+  fn from(spec: TaskSpec) -> Self {
+    TaskCommand::Configured {
+      run: spec.run.map(Box::new),
+      depends_on: spec.depends_on,
+      secrets: spec.secrets,
+    }
+  }
 }
 
 impl<'de> Deserialize<'de> for TaskCommand {
@@ -54,7 +83,10 @@ impl<'de> Deserialize<'de> for TaskCommand {
           .collect();
         Ok(TaskCommand::Array(strings?))
       }
-      _ => Err(D::Error::custom("Task command must be a string or array of strings")),
+      serde_yaml::Value::Mapping(map) => serde_yaml::from_value::<TaskSpec>(map.into())
+        .map(TaskCommand::from)
+        .map_err(D::Error::custom),
+      _ => Err(D::Error::custom("Task command must be a string, array of strings or mapping")),
     }
   }
 }
@@ -64,6 +96,25 @@ impl TaskCommand {
     match self {
       TaskCommand::String(s) => s.clone(),
       TaskCommand::Array(arr) => arr.join(" && "),
+      TaskCommand::Configured { run, .. } => {
+        run.as_ref().map(|run| run.to_command_string()).unwrap_or_default()
+      }
+    }
+  }
+
+  /// Tasks that must succeed before this one runs
+  pub fn depends_on(&self) -> &[String] {
+    match self {
+      TaskCommand::Configured { depends_on, .. } => depends_on,
+      _ => &[],
+    }
+  }
+
+  /// Secrets groups injected into the task's environment
+  pub fn secrets(&self) -> &[String] {
+    match self {
+      TaskCommand::Configured { secrets, .. } => secrets,
+      _ => &[],
     }
   }
 }
@@ -76,14 +127,32 @@ pub struct TaskRunnerOptions {
   pub tasks_file_path: Option<String>,
   pub force_color: bool,
   pub no_color: bool,
+  /// Most tasks run at once; defaults to the number of CPUs
+  pub jobs: Option<usize>,
+  /// Secrets groups injected into every task's environment
+  pub with_secrets: Vec<String>,
 }
 
+/// How a task's output reaches the terminal
+#[derive(Debug, Clone, Copy)]
+struct Output {
+  stream: bool,
+  preserve_colors: bool,
+}
+
+/// Environment variables added to each task, by task name
+type TaskEnv = HashMap<String, HashMap<String, String>>;
+
 #[derive(Debug)]
 pub struct TaskResult {
   pub success: bool,
   pub exit_code: Option<i32>,
 }
 
+/// Run task `alias` after the tasks it depends on, up to `options.jobs` at once
+///
+/// Unknown dependencies and cycles are refused before anything runs. Only `alias`
+/// itself gets `args`, and the first failure stops everything still running.
 pub async fn run_task(
   alias: &str,
   args: &[String],
@@ -94,12 +163,16 @@ pub async fn run_task(
     None => load_merged_tasks_file()?,
   };
 
-  let task_command = tasks.get(alias).ok_or_else(|| {
+  if !tasks.contains_key(alias) {
     let task_names: Vec<String> = tasks.keys().cloned().collect();
-    anyhow!("Task '{}' not found. Available tasks: {}", alias, task_names.join(", "))
+    return Err(anyhow!("Task '{}' not found. Available tasks: {}", alias, task_names.join(", ")));
+  }
+
+  let order = plan(&tasks, alias)?;
+  let env = secret_env(&tasks, &order, &options.with_secrets, |group| {
+    secrets::Secrets::new().get_group_env_vars(group)
   })?;
 
-  let command_string = task_command.to_command_string();
   let stream_output = !options.silent;
   let preserve_colors = if options.no_color {
     false
@@ -108,8 +181,15 @@ pub async fn run_task(
   } else {
     stream_output && !is_ci_environment()
   };
+  let output = Output { stream: stream_output, preserve_colors };
+
+  if order.len() == 1 {
+    let command_string = tasks[alias].to_command_string();
+    return execute_command(&command_string, args, &env[alias], output).await;
+  }
 
-  execute_command(&command_string, args, stream_output, preserve_colors).await
+  let jobs = options.jobs.unwrap_or_else(default_jobs).max(1);
+  run_graph(&tasks, alias, &order, args, &env, jobs, output).await
 }
 
 pub async fn list_tasks(tasks_file_path: Option<String>) -> Result<Vec<String>> {
@@ -178,9 +258,12 @@ fn load_tasks_file(path: &str) -> Result<TasksFile> {
           .collect();
         TaskCommand::Array(strings?)
       }
+      serde_yaml::Value::Mapping(_) => serde_yaml::from_value::<TaskSpec>(value.clone())
+        .map(TaskCommand::from)
+        .map_err(|e| anyhow!("Invalid task '{}' in file '{}': {}", key_str, path, e))?,
       _ => {
         return Err(anyhow!(
-          "Task '{}' in file '{}' must be a string or array of strings",
+          "Task '{}' in file '{}' must be a string, array of strings or mapping",
           key_str,
           path
         ))
@@ -227,8 +310,8 @@ fn load_merged_tasks_file() -> Result<TasksFile> {
 async fn execute_command(
   command: &str,
   args: &[String],
-  stream_output: bool,
-  preserve_colors: bool,
+  env: &HashMap<String, String>,
+  output: Output,
 ) -> Result<TaskResult> {
   let full_command =
     if args.is_empty() { command.to_string() } else { format!("{} {}", command, args.join(" ")) };
@@ -242,16 +325,19 @@ async fn execute_command(
     c.args(["-c", &full_command]);
     c
   };
+  // A task stopped because another one failed takes its process down with it
+  cmd.kill_on_drop(true);
+  cmd.envs(env);
 
   // Set up environment for color support
-  if preserve_colors {
+  if output.preserve_colors {
     cmd.env("FORCE_COLOR", "1");
     if env::var("TERM").is_err() {
       cmd.env("TERM", "xterm-256color");
     }
   }
 
-  if stream_output {
+  if output.stream {
     execute_with_streaming(&mut cmd).await
   } else {
     execute_with_capture(&mut cmd).await
@@ -295,6 +381,172 @@ async fn execute_with_capture(cmd: &mut Command) -> Result<TaskResult> {
   Ok(TaskResult { success: output.status.success(), exit_code: output.status.code() })
 }
 
+// Dependencies
+// ============
+
+fn plan(tasks: &TasksFile, alias: &str) -> Result<Vec<String>> {
+  fn visit(
+    tasks: &TasksFile,
+    name: &str,
+    stack: &mut Vec<String>,
+    done: &mut HashSet<String>,
+    order: &mut Vec<String>,
+  ) -> Result<()> {
+    if done.contains(name) {
+      return Ok(());
+    }
+    if let Some(start) = stack.iter().position(|task| task == name) {
+      let mut cycle = stack[start..].to_vec();
+      cycle.push(name.to_string());
+      return Err(anyhow!("Task dependencies form a cycle: {}", cycle.join(" -> ")));
+    }
+
+    stack.push(name.to_string());
+    for dependency in tasks[name].depends_on() {
+      if !tasks.contains_key(dependency) {
+        return Err(anyhow!(
+          "Task '{}' depends on unknown task '{}' ({})",
+          name,
+          dependency,
+          stack.join(" -> ")
+        ));
+      }
+      visit(tasks, dependency, stack, done, order)?;
+    }
+    stack.pop();
+
+    done.insert(name.to_string());
+    order.push(name.to_string());
+    Ok(())
+  }
+
+  let mut order = Vec::new();
+  visit(tasks, alias, &mut Vec::new(), &mut HashSet::new(), &mut order)?;
+  Ok(order)
+}
+
+fn dependency_chain(tasks: &TasksFile, from: &str, to: &str) -> Vec<String> {
+  if from == to {
+    return vec![to.to_string()];
+  }
+  for dependency in tasks[from].depends_on() {
+    let mut chain = dependency_chain(tasks, dependency, to);
+    if chain.last().is_some_and(|last| last == to) {
+      chain.insert(0, from.to_string());
+      return chain;
+    }
+  }
+  Vec::new()
+}
+
+fn secret_env(
+  tasks: &TasksFile,
+  order: &[String],
+  shared_groups: &[String],
+  read_group: impl Fn(&str) -> Result<HashMap<String, String>>,
+) -> Result<TaskEnv> {
+  let mut groups: HashMap<&str, HashMap<String, String>> = HashMap::new();
+  let mut env = TaskEnv::new();
+
+  for name in order {
+    let mut task_env = HashMap::new();
+    for group in shared_groups.iter().chain(tasks[name].secrets()) {
+      if !groups.contains_key(group.as_str()) {
+        let vars = read_group(group).map_err(|e| {
+          anyhow!("Failed to read secrets group '{}' for task '{}': {}", group, name, e)
+        })?;
+        if vars.is_empty() {
+          bentley::warn!(&format!("Secrets group '{group}' has no variables to inject"));
+        }
+        groups.insert(group, vars);
+      }
+      task_env.extend(groups[group.as_str()].clone());
+    }
+    env.insert(name.clone(), task_env);
+  }
+  Ok(env)
+}
+
+fn default_jobs() -> usize {
+  std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+async fn run_graph(
+  tasks: &TasksFile,
+  alias: &str,
+  order: &[String],
+  args: &[String],
+  env: &TaskEnv,
+  jobs: usize,
+  output: Output,
+) -> Result<TaskResult> {
+  let mut waiting_on: HashMap<&str, HashSet<&str>> = HashMap::new();
+  let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+  for name in order {
+    let dependencies: HashSet<&str> = tasks[name].depends_on().iter().map(String::as_str).collect();
+    for dependency in &dependencies {
+      dependents.entry(*dependency).or_default().push(name);
+    }
+    waiting_on.insert(name, dependencies);
+  }
+
+  let mut ready: VecDeque<&str> =
+    order.iter().map(String::as_str).filter(|name| waiting_on[name].is_empty()).collect();
+  let mut running = JoinSet::new();
+
+  loop {
+    while running.len() < jobs {
+      let Some(name) = ready.pop_front() else { break };
+      let command = tasks[name].to_command_string();
+      let task_args = if name == alias { args.to_vec() } else { Vec::new() };
+      let task_env = env[name].clone();
+      let name = name.to_string();
+
+      if output.stream {
+        eprintln!("→ {name}");
+      }
+      running.spawn(async move {
+        let result = if command.trim().is_empty() {
+          Ok(TaskResult { success: true, exit_code: Some(0) })
+        } else {
+          execute_command(&command, &task_args, &task_env, output).await
+        };
+        (name, result)
+      });
+    }
+
+    let Some(joined) = running.join_next().await else { break };
+    let (name, result) = joined?;
+    let chain = dependency_chain(tasks, alias, &name).join(" -> ");
+
+    match result {
+      Ok(result) if result.success => {
+        for dependent in dependents.get(name.as_str()).into_iter().flatten() {
+          let waiting = waiting_on.get_mut(dependent).expect("every planned task is tracked");
+          waiting.remove(name.as_str());
+          if waiting.is_empty() {
+            ready.push_back(dependent);
+          }
+        }
+      }
+      Ok(result) => {
+        running.shutdown().await;
+        let status = result
+          .exit_code
+          .map_or("was terminated".to_string(), |code| format!("exited with code {code}"));
+        eprintln!("✗ Task '{name}' {status}\n  {chain}");
+        return Ok(result);
+      }
+      Err(e) => {
+        running.shutdown().await;
+        return Err(e.context(format!("Task '{name}' could not run ({chain})")));
+      }
+    }
+  }
+
+  Ok(TaskResult { success: true, exit_code: Some(0) })
+}
+
 fn is_ci_environment() -> bool {
   env::var("CI").is_ok() || env::var("NO_COLOR").is_ok()
 }
@@ -354,6 +606,8 @@ mod tests {
       tasks_file_path: Some("nonexistent.tasks".to_string()),
       force_color: false,
       no_color: false,
+      jobs: None,
+      with_secrets: Vec::new(),
     };
 
     let result = run_task("nonexistent_task", &[], options).await;
@@ -715,4 +969,160 @@ invalid_task:
     let error_message = result.unwrap_err().to_string();
     assert!(error_message.contains("Invalid mapping in array"));
   }
+
+  fn graph_tasks() -> TasksFile {
+    let yaml_content = r#"
+build: "echo build"
+lint: "echo lint"
+test:
+  run: "echo test"
+  depends_on: [build, lint]
+ci:
+  depends_on: [test, lint]
+"#;
+    serde_yaml::from_str(yaml_content).unwrap()
+  }
+
+  #[test]
+  fn test_dependencies_in_mapping_syntax() {
+    use std::fs;
+    use tempfile::NamedTempFile;
+
+    let yaml_content = r#"
+build: "cargo build"
+test:
+  run:
+    - "cargo test"
+    - do: build
+  depends_on: [build]
+unknown_key:
+  command: "cargo test"
+"#;
+
+    let temp_file = NamedTempFile::new().unwrap();
+    fs::write(temp_file.path(), &yaml_content[..yaml_content.find("unknown_key").unwrap()])
+      .unwrap();
+    let tasks = load_tasks_file(temp_file.path().to_str().unwrap()).unwrap();
+
+    let test = tasks.get("test").unwrap();
+    assert_eq!(test.depends_on(), ["build"]);
+    assert_eq!(test.to_command_string(), "cargo test && blizz do build");
+    assert!(tasks.get("build").unwrap().depends_on().is_empty());
+
+    fs::write(temp_file.path(), yaml_content).unwrap();
+    let error = load_tasks_file(temp_file.path().to_str().unwrap()).unwrap_err().to_string();
+    assert!(error.contains("Invalid task 'unknown_key'"));
+  }
+
+  #[test]
+  fn test_plan_puts_dependencies_first() {
+    let tasks = graph_tasks();
+
+    let order = plan(&tasks, "ci").unwrap();
+    assert_eq!(order.len(), 4);
+    let position = |name: &str| order.iter().position(|task| task == name).unwrap();
+    assert!(position("build") < position("test"));
+    assert!(position("lint") < position("test"));
+    assert_eq!(position("ci"), 3);
+
+    assert_eq!(plan(&tasks, "build").unwrap(), ["build"]);
+    assert_eq!(dependency_chain(&tasks, "ci", "build"), ["ci", "test", "build"]);
+  }
+
+  #[test]
+  fn test_plan_reports_unknown_tasks_and_cycles() {
+    let tasks: TasksFile = serde_yaml::from_str(
+      r#"
+a: { run: "true", depends_on: [b] }
+b: { run: "true", depends_on: [c] }
+c: { run: "true", depends_on: [a] }
+d: { run: "true", depends_on: [missing] }
+"#,
+    )
+    .unwrap();
+
+    let error = plan(&tasks, "a").unwrap_err().to_string();
+    assert!(error.contains("a -> b -> c -> a"), "{error}");
+
+    let error = plan(&tasks, "d").unwrap_err().to_string();
+    assert!(error.contains("'d' depends on unknown task 'missing'"), "{error}");
+  }
+
+  #[tokio::test]
+  async fn test_run_graph_runs_dependencies_and_fails_fast() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = |name: &str| dir.path().join(name).display().to_string();
+    let yaml_content = format!(
+      r#"
+build: "touch {build}"
+broken: "exit 3"
+test:
+  run: "touch {test}"
+  depends_on: [build]
+release:
+  run: "touch {release}"
+  depends_on: [test, broken]
+"#,
+      build = marker("build"),
+      test = marker("test"),
+      release = marker("release"),
+    );
+    let tasks_path = dir.path().join("blizz.yaml");
+    std::fs::write(&tasks_path, yaml_content).unwrap();
+
+    let options = |jobs| TaskRunnerOptions {
+      silent: true,
+      tasks_file_path: Some(tasks_path.display().to_string()),
+      jobs: Some(jobs),
+      ..Default::default()
+    };
+
+    let result = run_task("test", &[], options(2)).await.unwrap();
+    assert!(result.success);
+    assert!(dir.path().join("build").exists() && dir.path().join("test").exists());
+
+    let result = run_task("release", &[], options(1)).await.unwrap();
+    assert!(!result.success);
+    assert_eq!(result.exit_code, Some(3));
+    assert!(!dir.path().join("release").exists());
+  }
+
+  #[tokio::test]
+  async fn test_secret_env_reads_each_group_once() {
+    let tasks: TasksFile = serde_yaml::from_str(
+      r#"
+fetch: { run: "echo $GITHUB_TOKEN", secrets: [github] }
+report: { run: "echo $JIRA_TOKEN", depends_on: [fetch], secrets: [jira, github] }
+"#,
+    )
+    .unwrap();
+    let order = plan(&tasks, "report").unwrap();
+
+    let reads = std::cell::RefCell::new(Vec::new());
+    let env = secret_env(&tasks, &order, &["ci".to_string()], |group| {
+      reads.borrow_mut().push(group.to_string());
+      Ok(HashMap::from([(format!("{}_TOKEN", group.to_uppercase()), format!("{group}-secret"))]))
+    })
+    .unwrap();
+
+    assert_eq!(reads.borrow().len(), 3);
+    assert_eq!(env["fetch"].len(), 2);
+    assert_eq!(env["fetch"]["GITHUB_TOKEN"], "github-secret");
+    assert_eq!(env["report"]["JIRA_TOKEN"], "jira-secret");
+    assert_eq!(env["report"]["CI_TOKEN"], "ci-secret");
+
+    let error = secret_env(&tasks, &order, &[], |_| Err(anyhow!("keeper is locked")))
+      .unwrap_err()
+      .to_string();
+    assert!(error.contains("secrets group 'github' for task 'fetch'"), "{error}");
+
+    // The variables reach the spawned process
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("token");
+    let output = Output { stream: false, preserve_colors: false };
+    let command = format!("printf %s \"$GITHUB_TOKEN\" > {}", out.display());
+    let result = execute_command(&command, &[], &env["fetch"], output).await.unwrap();
+    assert!(result.success);
+    assert_eq!(std::fs::read_to_string(out).unwrap(), "github-secret");
+  }
 }
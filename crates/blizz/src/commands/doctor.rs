@@ -0,0 +1,555 @@
+//! Health check across the whole toolchain
+//!
+//! `blizz doctor` looks at everything blizz leans on: the home directory layout,
+//! the keeper daemon, the secrets vault, the insights server and its vector data,
+//! and the versions of the other installed binaries. Every problem comes with a
+//! suggested fix, and a failed critical check makes the command exit non-zero.
+
+use anyhow::Result;
+use console::style;
+use insights::client::get_client;
+use insights::server::startup::get_vector_data_path;
+use secrets::keeper_client::{self, KeeperState};
+use secrets::PasswordBasedCredentialStore;
+use serde::Serialize;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Binaries that ship alongside blizz and should match its version
+const COMPANION_BINARIES: &[&str] = &["insights", "secrets", "violet"];
+
+/// How long a companion binary gets to answer `--version`
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How a single check came out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+  Ok,
+  /// Worth fixing, but nothing is broken
+  Warn,
+  /// Something blizz needs is broken
+  Fail,
+}
+
+/// One line of the report
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+  pub name: String,
+  pub status: Status,
+  pub detail: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub fix: Option<String>,
+}
+
+impl Check {
+  fn ok(name: &str, detail: impl Into<String>) -> Self {
+    Self { name: name.to_string(), status: Status::Ok, detail: detail.into(), fix: None }
+  }
+
+  fn warn(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+    Self {
+      name: name.to_string(),
+      status: Status::Warn,
+      detail: detail.into(),
+      fix: Some(fix.into()),
+    }
+  }
+
+  fn fail(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+    Self {
+      name: name.to_string(),
+      status: Status::Fail,
+      detail: detail.into(),
+      fix: Some(fix.into()),
+    }
+  }
+}
+
+/// Everything `blizz doctor` found
+#[derive(Debug, Serialize)]
+pub struct Report {
+  pub healthy: bool,
+  pub checks: Vec<Check>,
+}
+
+impl Report {
+  fn new(checks: Vec<Check>) -> Self {
+    Self { healthy: checks.iter().all(|check| check.status != Status::Fail), checks }
+  }
+}
+
+/// Where each part of the toolchain keeps its state
+#[derive(Debug, Clone)]
+pub struct Paths {
+  /// BLIZZ_HOME, used by blizz itself and the keeper daemon
+  pub home: PathBuf,
+  /// BLIZZ_DIR, used by the secrets vault commands
+  pub secrets_home: PathBuf,
+  /// Where the insights server keeps its vector database
+  pub vector_data: PathBuf,
+}
+
+impl Paths {
+  pub(crate) fn from_env() -> Result<Self> {
+    let home = super::plugins::get_blizz_home()?;
+    Ok(Self { home, secrets_home: secrets::base_path(), vector_data: get_vector_data_path() })
+  }
+
+  pub(crate) fn keeper_socket(&self) -> PathBuf {
+    self.secrets_home.join("persistent").join("keeper").join("keeper.sock")
+  }
+
+  fn vault(&self) -> PathBuf {
+    self.secrets_home.join("persistent").join("keeper").join("credentials.enc")
+  }
+}
+
+/// Run every check, print the report and say whether the toolchain is healthy
+pub async fn execute(json: bool) -> Result<bool> {
+  let report = diagnose(&Paths::from_env()?).await;
+  if json {
+    println!("{}", serde_json::to_string_pretty(&report)?);
+  } else {
+    print_report(&report);
+  }
+  Ok(report.healthy)
+}
+
+/// Run every check against `paths`
+pub async fn diagnose(paths: &Paths) -> Report {
+  let mut checks = check_layout(paths);
+
+  let keeper = keeper_client::probe(&paths.keeper_socket()).await;
+  checks.push(check_keeper(&keeper));
+  checks.push(check_vault(paths, &keeper).await);
+
+  let server_version = match get_client().status().await {
+    Ok(status) => {
+      checks.push(Check::ok("insights server", format!("{} (v{})", status.status, status.version)));
+      Some(status.version)
+    }
+    Err(e) => {
+      checks.push(Check::warn(
+        "insights server",
+        format!("not reachable: {e}"),
+        "insights commands start the server on demand; if it will not start, check `insights logs`",
+      ));
+      None
+    }
+  };
+  checks.push(check_vector_data(&paths.vector_data));
+
+  let path_var = std::env::var_os("PATH").unwrap_or_default();
+  checks.extend(check_versions(&path_var, server_version.as_deref()).await);
+
+  Report::new(checks)
+}
+
+fn check_layout(paths: &Paths) -> Vec<Check> {
+  const NAME: &str = "blizz home";
+  let home = &paths.home;
+  if !home.is_dir() {
+    return vec![Check::fail(
+      NAME,
+      format!("{} does not exist", home.display()),
+      "reinstall blizz, or point BLIZZ_HOME at an existing install",
+    )];
+  }
+
+  let mut checks = Vec::new();
+  let volatile = home.join("volatile");
+  if volatile.is_dir() {
+    checks.push(Check::ok(NAME, home.display().to_string()));
+  } else {
+    checks.push(Check::warn(
+      NAME,
+      format!("{} is missing, so there are no rules or workflows to link", volatile.display()),
+      "run `blizz update` to reinstall them",
+    ));
+  }
+
+  let persistent = home.join("persistent");
+  if persistent.exists() && !persistent.is_dir() {
+    checks.push(Check::fail(
+      NAME,
+      format!("{} is not a directory", persistent.display()),
+      format!("move {} aside so blizz can recreate it", persistent.display()),
+    ));
+  }
+
+  if paths.secrets_home != paths.home {
+    checks.push(Check::warn(
+      NAME,
+      format!(
+        "secrets use {} (BLIZZ_DIR) but blizz and the keeper use {} (BLIZZ_HOME)",
+        paths.secrets_home.display(),
+        home.display()
+      ),
+      "set BLIZZ_DIR and BLIZZ_HOME to the same directory",
+    ));
+  }
+  checks
+}
+
+fn check_keeper(state: &KeeperState) -> Check {
+  const NAME: &str = "keeper";
+  match state {
+    KeeperState::Unlocked(_) => Check::ok(NAME, "running and unlocked"),
+    KeeperState::Locked => {
+      Check::warn(NAME, "running but locked", "run `blizz secrets agent unlock`")
+    }
+    KeeperState::NotRunning => Check::warn(
+      NAME,
+      "not running, so every secrets command asks for the master password",
+      "run `blizz secrets agent start`",
+    ),
+    KeeperState::Unreachable(e) => Check::fail(
+      NAME,
+      format!("socket exists but the daemon does not answer: {e}"),
+      "run `blizz secrets agent restart`",
+    ),
+    KeeperState::Unknown(reply) => Check::fail(
+      NAME,
+      format!("unexpected reply to STATUS: {reply}"),
+      "run `blizz secrets agent restart`; if that does not help, reinstall blizz",
+    ),
+  }
+}
+
+async fn check_vault(paths: &Paths, keeper: &KeeperState) -> Check {
+  const NAME: &str = "secrets vault";
+  let vault = paths.vault();
+  if !vault.exists() {
+    return Check::ok(NAME, "no vault yet");
+  }
+
+  let store = match PasswordBasedCredentialStore::load_from_file(&vault) {
+    Ok(Some(store)) => store,
+    Ok(None) => return Check::ok(NAME, "no vault yet"),
+    Err(e) => {
+      return Check::fail(
+        NAME,
+        format!("{} cannot be read: {e}", vault.display()),
+        format!(
+          "restore it from {} or another backup",
+          secrets::vaultfile::backup_path(&vault).display()
+        ),
+      )
+    }
+  };
+
+  if read_json(&vault).is_err() {
+    return Check::warn(
+      NAME,
+      format!("{} is damaged and secrets are read from its backup", vault.display()),
+      "store or delete any secret to rewrite the vault from the backup",
+    );
+  }
+
+  let password = if let Ok(password) = std::env::var("SECRETS_AUTH") {
+    password.trim().to_string()
+  } else if matches!(keeper, KeeperState::Unlocked(_)) {
+    match keeper_client::get(&paths.secrets_home).await {
+      Ok(password) => password,
+      Err(e) => {
+        return Check::warn(
+          NAME,
+          format!("readable, but the keeper would not hand over the password: {e}"),
+          "run `blizz secrets agent unlock` and try again",
+        )
+      }
+    }
+  } else {
+    return Check::warn(
+      NAME,
+      "readable, but not test-decrypted without an unlocked keeper",
+      "run `blizz secrets agent unlock` (or set SECRETS_AUTH) and run `blizz doctor` again",
+    );
+  };
+
+  match store.decrypt_credentials(&password) {
+    Ok(groups) => Check::ok(NAME, format!("decrypts ({} groups)", groups.len())),
+    Err(_) => Check::fail(
+      NAME,
+      "the master password does not decrypt the vault",
+      "lock the keeper with `blizz secrets agent lock` and unlock it with the right password",
+    ),
+  }
+}
+
+fn check_vector_data(dir: &Path) -> Check {
+  const NAME: &str = "vector database";
+  let journal = dir.join("memory").join("vectors.jsonl");
+  let lancedb = dir.join("lancedb");
+
+  let result = if journal.exists() {
+    read_journal(&journal).map(|records| format!("{records} journal entries"))
+  } else if lancedb.exists() {
+    read_tree(&lancedb).map(|files| format!("{files} lancedb files"))
+  } else {
+    return Check::ok(NAME, "no vector data yet; the server creates it on first start");
+  };
+
+  match result {
+    Ok(detail) => Check::ok(NAME, detail),
+    Err(e) => Check::fail(
+      NAME,
+      e.to_string(),
+      format!(
+        "stop the insights server, move {} aside and run `insights index --force` to rebuild it",
+        dir.display()
+      ),
+    ),
+  }
+}
+
+fn read_json(path: &Path) -> Result<serde_json::Value> {
+  Ok(serde_json::from_str(std::fs::read_to_string(path)?.trim())?)
+}
+
+/// Count the entries in a memory backend journal, failing on the first unparseable one
+fn read_journal(journal: &Path) -> Result<usize> {
+  let content = std::fs::read_to_string(journal)
+    .map_err(|e| anyhow::anyhow!("cannot read {}: {e}", journal.display()))?;
+  let mut records = 0;
+  for (index, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+    serde_json::from_str::<serde_json::Value>(line).map_err(|e| {
+      anyhow::anyhow!("{} is corrupt at line {}: {e}", journal.display(), index + 1)
+    })?;
+    records += 1;
+  }
+  Ok(records)
+}
+
+/// Open every file under `dir` for reading and count them
+fn read_tree(dir: &Path) -> Result<usize> {
+  let mut files = 0;
+  let entries =
+    std::fs::read_dir(dir).map_err(|e| anyhow::anyhow!("cannot read {}: {e}", dir.display()))?;
+  for entry in entries {
+    let path = entry?.path();
+    if path.is_dir() {
+      files += read_tree(&path)?;
+    } else {
+      std::fs::File::open(&path)
+        .map_err(|e| anyhow::anyhow!("cannot read {}: {e}", path.display()))?;
+      files += 1;
+    }
+  }
+  Ok(files)
+}
+
+async fn check_versions(path_var: &OsStr, server_version: Option<&str>) -> Vec<Check> {
+  let expected = env!("CARGO_PKG_VERSION");
+  let mut checks = Vec::new();
+
+  for binary in COMPANION_BINARIES {
+    let name = format!("{binary} version");
+    let Some(path) = find_binary(binary, path_var) else {
+      checks.push(Check::warn(&name, "not found on PATH", "run `blizz update` to reinstall it"));
+      continue;
+    };
+    checks.push(match binary_version(&path).await {
+      Some(version) => version_check(&name, &version, expected),
+      None => Check::warn(
+        &name,
+        format!("{} did not report a version", path.display()),
+        "run `blizz update` to reinstall it",
+      ),
+    });
+  }
+
+  if let Some(version) = server_version {
+    let check = version_check("insights server version", version, expected);
+    checks.push(match check.status {
+      Status::Ok => check,
+      _ => Check {
+        fix: Some("restart the insights server so it picks up the new build".into()),
+        ..check
+      },
+    });
+  }
+  checks
+}
+
+fn version_check(name: &str, version: &str, expected: &str) -> Check {
+  if version == expected {
+    Check::ok(name, version)
+  } else {
+    Check::warn(
+      name,
+      format!("{version}, but blizz is {expected}"),
+      "run `blizz update` so every binary comes from the same release",
+    )
+  }
+}
+
+fn find_binary(name: &str, path_var: &OsStr) -> Option<PathBuf> {
+  std::env::split_paths(path_var)
+    .map(|dir| dir.join(format!("{name}{}", std::env::consts::EXE_SUFFIX)))
+    .find(|path| path.is_file())
+}
+
+/// The version a clap binary prints for `--version`, e.g. `secrets 0.3.1, courtesy of blizz`
+async fn binary_version(path: &Path) -> Option<String> {
+  let output = tokio::process::Command::new(path)
+    .arg("--version")
+    .stdin(Stdio::null())
+    .stderr(Stdio::null())
+    .kill_on_drop(true)
+    .output();
+  let output = tokio::time::timeout(VERSION_TIMEOUT, output).await.ok()?.ok()?;
+  parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_version(output: &str) -> Option<String> {
+  let version = output.split_whitespace().nth(1)?.trim_end_matches(',');
+  version.starts_with(|c: char| c.is_ascii_digit()).then(|| version.to_string())
+}
+
+fn print_report(report: &Report) {
+  let width = report.checks.iter().map(|check| check.name.len()).max().unwrap_or(0);
+  for check in &report.checks {
+    let mark = match check.status {
+      Status::Ok => style("✓").green(),
+      Status::Warn => style("!").yellow(),
+      Status::Fail => style("✗").red(),
+    };
+    println!("{mark} {:<width$}  {}", check.name, check.detail);
+    if let Some(fix) = &check.fix {
+      println!("  {:<width$}  {} {fix}", "", style("→").dim());
+    }
+  }
+
+  let count = |status| report.checks.iter().filter(|check| check.status == status).count();
+  let (warnings, failures) = (count(Status::Warn), count(Status::Fail));
+  println!();
+  if failures > 0 {
+    println!("{}", style(format!("{failures} critical problem(s), {warnings} warning(s)")).red());
+  } else if warnings > 0 {
+    println!("{}", style(format!("No critical problems, {warnings} warning(s)")).yellow());
+  } else {
+    println!("{}", style("Everything looks good").green());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashMap;
+  use std::fs;
+  use tempfile::TempDir;
+
+  fn paths(dir: &TempDir) -> Paths {
+    let home = dir.path().to_path_buf();
+    Paths { secrets_home: home.clone(), vector_data: home.join("volatile/insights"), home }
+  }
+
+  fn statuses(checks: &[Check]) -> Vec<Status> {
+    checks.iter().map(|check| check.status).collect()
+  }
+
+  #[test]
+  fn test_layout_flags_missing_home_and_split_directories() {
+    let dir = TempDir::new().unwrap();
+    let mut missing = paths(&dir);
+    missing.home = dir.path().join("nope");
+    assert_eq!(statuses(&check_layout(&missing)), [Status::Fail]);
+
+    assert_eq!(statuses(&check_layout(&paths(&dir))), [Status::Warn]);
+
+    fs::create_dir(dir.path().join("volatile")).unwrap();
+    assert_eq!(statuses(&check_layout(&paths(&dir))), [Status::Ok]);
+
+    let mut split = paths(&dir);
+    split.secrets_home = dir.path().join("elsewhere");
+    let checks = check_layout(&split);
+    assert_eq!(statuses(&checks), [Status::Ok, Status::Warn]);
+    assert!(checks[1].detail.contains("BLIZZ_DIR"));
+  }
+
+  #[test]
+  fn test_keeper_states_map_to_severity() {
+    assert_eq!(check_keeper(&KeeperState::Unlocked(None)).status, Status::Ok);
+    assert_eq!(check_keeper(&KeeperState::Locked).status, Status::Warn);
+    assert_eq!(check_keeper(&KeeperState::NotRunning).status, Status::Warn);
+    assert_eq!(check_keeper(&KeeperState::Unreachable("refused".into())).status, Status::Fail);
+    assert_eq!(check_keeper(&KeeperState::Unknown("HUH".into())).status, Status::Fail);
+  }
+
+  #[tokio::test]
+  async fn test_vault_check_reads_and_decrypts() {
+    let dir = TempDir::new().unwrap();
+    let paths = paths(&dir);
+    std::env::remove_var("SECRETS_AUTH");
+    assert_eq!(check_vault(&paths, &KeeperState::NotRunning).await.status, Status::Ok);
+
+    let group = HashMap::from([("token".to_string(), "abc".to_string())]);
+    let credentials = HashMap::from([("github".to_string(), group)]);
+    let store = PasswordBasedCredentialStore::new(&credentials, "hunter2").unwrap();
+    fs::create_dir_all(paths.vault().parent().unwrap()).unwrap();
+    store.save_to_file(&paths.vault()).unwrap();
+    assert_eq!(check_vault(&paths, &KeeperState::NotRunning).await.status, Status::Warn);
+
+    store.save_to_file(&paths.vault()).unwrap();
+    fs::write(paths.vault(), "{ not json").unwrap();
+    assert_eq!(check_vault(&paths, &KeeperState::NotRunning).await.status, Status::Warn);
+
+    fs::remove_file(secrets::vaultfile::backup_path(&paths.vault())).unwrap();
+    assert_eq!(check_vault(&paths, &KeeperState::NotRunning).await.status, Status::Fail);
+  }
+
+  #[test]
+  fn test_vector_data_check_catches_corrupt_journal() {
+    let dir = TempDir::new().unwrap();
+    assert_eq!(check_vector_data(dir.path()).status, Status::Ok);
+
+    fs::create_dir(dir.path().join("memory")).unwrap();
+    let journal = dir.path().join("memory/vectors.jsonl");
+    fs::write(&journal, "{\"op\":\"upsert\"}\n{\"op\":\"delete\"}\n").unwrap();
+    let check = check_vector_data(dir.path());
+    assert_eq!((check.status, check.detail.as_str()), (Status::Ok, "2 journal entries"));
+
+    fs::write(&journal, "{\"op\":\"upsert\"}\n{\"op\":").unwrap();
+    let check = check_vector_data(dir.path());
+    assert_eq!(check.status, Status::Fail);
+    assert!(check.detail.contains("line 2"));
+  }
+
+  #[test]
+  fn test_versions_are_parsed_and_compared() {
+    assert_eq!(parse_version("secrets 0.3.1, courtesy of blizz\n").as_deref(), Some("0.3.1"));
+    assert_eq!(parse_version("usage: secrets [OPTIONS]"), None);
+    assert_eq!(parse_version(""), None);
+
+    let expected = env!("CARGO_PKG_VERSION");
+    assert_eq!(version_check("secrets version", expected, expected).status, Status::Ok);
+    assert_eq!(version_check("secrets version", "0.0.1", expected).status, Status::Warn);
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn test_versions_check_installed_binaries() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let script = dir.path().join("secrets");
+    fs::write(&script, "#!/bin/sh\necho \"secrets 0.0.1, courtesy of blizz\"\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let checks = check_versions(dir.path().as_os_str(), Some(env!("CARGO_PKG_VERSION"))).await;
+    let by_name = |name: &str| checks.iter().find(|check| check.name == name).unwrap();
+    assert_eq!(by_name("insights version").detail, "not found on PATH");
+    assert!(by_name("secrets version").detail.starts_with("0.0.1, but blizz is"));
+    assert_eq!(by_name("insights server version").status, Status::Ok);
+  }
+
+  #[test]
+  fn test_report_is_unhealthy_only_on_failures() {
+    assert!(Report::new(vec![Check::ok("a", ""), Check::warn("b", "", "")]).healthy);
+    assert!(!Report::new(vec![Check::ok("a", ""), Check::fail("b", "", "")]).healthy);
+  }
+}
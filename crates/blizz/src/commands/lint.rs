@@ -0,0 +1,16 @@
+use anyhow::Result;
+use std::process;
+use violet::cli::run;
+
+pub use violet::cli::{LintArgs, LintOptions};
+
+/// Lint with the same flags and exit status as the standalone violet binary
+pub fn execute(args: &LintArgs, options: LintOptions) -> Result<()> {
+  let violations = run(args, options)?;
+
+  if violations > 0 {
+    process::exit(1);
+  }
+
+  Ok(())
+}
@@ -0,0 +1,35 @@
+use anyhow::{anyhow, Result};
+use bentley::config::ConfigFile;
+use insights::settings;
+use insights::store::{self, StoreBackend};
+
+/// Copy every insight into another store and make it the configured one
+///
+/// The source store is left as it was, so switching `insights.store` back undoes
+/// the move.
+pub fn execute(to: &str, from: Option<&str>, overwrite: bool) -> Result<()> {
+  let to = StoreBackend::parse(to)?;
+  let from = match from {
+    Some(from) => StoreBackend::parse(from)?,
+    None => store::get_store_backend()?,
+  };
+  if from == to {
+    return Err(anyhow!("Insights are already kept in the {to} store"));
+  }
+
+  let report = store::migrate(&*store::open(from)?, &*store::open(to)?, overwrite)?;
+  println!("Copied {} insights from the {from} store to the {to} store", report.copied);
+  for name in &report.skipped {
+    println!("  skipped: {name} (already in the {to} store; pass --overwrite to replace it)");
+  }
+
+  let mut config = ConfigFile::load()?;
+  config.set(settings::STORE.name, to.name())?;
+  config.save()?;
+  println!("Set {} to {to} in {}", settings::STORE.name, config.path().display());
+
+  if let Ok(value) = std::env::var(settings::STORE.env) {
+    println!("Note: ${} is set to '{value}' and still takes precedence.", settings::STORE.env);
+  }
+  Ok(())
+}
@@ -1,6 +1,14 @@
+pub mod browse;
+pub mod config;
 pub mod r#do;
+pub mod doctor;
 pub mod link;
+pub mod lint;
+pub mod migrate_store;
+pub mod plugins;
+pub mod purge;
 pub mod secrets;
+pub mod sync;
 pub mod unlink;
 pub mod update;
 pub mod version;
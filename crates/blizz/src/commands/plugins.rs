@@ -0,0 +1,245 @@
+//! Git-style plugins
+//!
+//! `blizz foo` falls back to running a `blizz-foo` executable found on PATH when
+//! `foo` is not a built-in command. Plugins get the remaining arguments, the
+//! caller's environment plus `BLIZZ_HOME`, and their exit status becomes blizz's.
+//! A plugin describes itself by printing one line when run with `--describe`.
+
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::Duration;
+
+/// Executable name prefix that marks a plugin
+pub const PREFIX: &str = "blizz-";
+
+/// How long a plugin gets to answer `--describe`
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Subcommand)]
+pub enum PluginsCommands {
+  /// List plugins found on PATH with their descriptions
+  List,
+}
+
+/// A `blizz-<name>` executable
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plugin {
+  pub name: String,
+  pub path: PathBuf,
+}
+
+pub async fn execute(command: PluginsCommands, builtins: &[String]) -> Result<()> {
+  match command {
+    PluginsCommands::List => list(builtins).await,
+  }
+}
+
+async fn list(builtins: &[String]) -> Result<()> {
+  let plugins = discover(&std::env::var_os("PATH").unwrap_or_default());
+  if plugins.is_empty() {
+    println!("No plugins found. Put a `{PREFIX}<name>` executable on your PATH to add one.");
+    return Ok(());
+  }
+
+  let width = plugins.iter().map(|plugin| plugin.name.len()).max().unwrap_or(0);
+  println!("Available plugins:");
+  for plugin in plugins {
+    let mut description =
+      describe(&plugin.path).await.unwrap_or_else(|| "(no description)".to_string());
+    if builtins.contains(&plugin.name) {
+      description.push_str(" [shadowed by the built-in command]");
+    }
+    println!("• {:<width$}  {description}", plugin.name);
+  }
+  Ok(())
+}
+
+/// Every plugin on `path_var`, by name; earlier PATH entries win like they do for the shell
+pub fn discover(path_var: &OsStr) -> Vec<Plugin> {
+  let mut plugins = BTreeMap::new();
+  for dir in std::env::split_paths(path_var) {
+    let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+    for entry in entries.flatten() {
+      let path = entry.path();
+      let Some(name) = plugin_name(&path) else { continue };
+      if is_executable(&path) {
+        plugins.entry(name.clone()).or_insert(Plugin { name, path });
+      }
+    }
+  }
+  plugins.into_values().collect()
+}
+
+/// The plugin that `blizz <name>` runs
+pub fn find(name: &str, path_var: &OsStr) -> Option<Plugin> {
+  std::env::split_paths(path_var)
+    .map(|dir| dir.join(format!("{PREFIX}{name}{}", std::env::consts::EXE_SUFFIX)))
+    .find(|path| is_executable(path))
+    .map(|path| Plugin { name: name.to_string(), path })
+}
+
+/// First line a plugin prints for `--describe`, if it answers in time
+pub async fn describe(path: &Path) -> Option<String> {
+  let output = tokio::process::Command::new(path)
+    .arg("--describe")
+    .stdin(Stdio::null())
+    .stderr(Stdio::null())
+    .kill_on_drop(true)
+    .output();
+  let output = tokio::time::timeout(DESCRIBE_TIMEOUT, output).await.ok()?.ok()?;
+  if !output.status.success() {
+    return None;
+  }
+
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  stdout.lines().map(str::trim).find(|line| !line.is_empty()).map(str::to_string)
+}
+
+/// Run `blizz-<name>` with `args` and return the exit code blizz should exit with
+pub fn run(name: &str, args: &[String], path_var: &OsStr, blizz_home: &Path) -> Result<i32> {
+  let plugin = find(name, path_var).ok_or_else(|| {
+    anyhow!(
+      "unrecognized subcommand '{name}' (no {PREFIX}{name} on PATH)\n\nRun `blizz --help` for \
+       built-in commands or `blizz plugins list` for plugins."
+    )
+  })?;
+
+  let status = Command::new(&plugin.path)
+    .args(args)
+    .env("BLIZZ_HOME", blizz_home)
+    .status()
+    .map_err(|e| anyhow!("failed to run {}: {e}", plugin.path.display()))?;
+  Ok(exit_code(status))
+}
+
+/// Exit code for a finished plugin; a plugin killed by a signal exits with 128 + the signal
+fn exit_code(status: ExitStatus) -> i32 {
+  #[cfg(unix)]
+  {
+    use std::os::unix::process::ExitStatusExt;
+    if let Some(signal) = status.signal() {
+      return 128 + signal;
+    }
+  }
+  status.code().unwrap_or(1)
+}
+
+fn plugin_name(path: &Path) -> Option<String> {
+  let file_name = path.file_name()?.to_str()?;
+  let file_name = file_name.strip_suffix(std::env::consts::EXE_SUFFIX).unwrap_or(file_name);
+  let name = file_name.strip_prefix(PREFIX)?;
+  (!name.is_empty()).then(|| name.to_string())
+}
+
+fn is_executable(path: &Path) -> bool {
+  let Ok(metadata) = path.metadata() else { return false };
+  if !metadata.is_file() {
+    return false;
+  }
+
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+  }
+  #[cfg(not(unix))]
+  true
+}
+
+pub fn get_blizz_home() -> Result<PathBuf> {
+  if let Ok(home) = std::env::var("BLIZZ_HOME") {
+    Ok(PathBuf::from(home))
+  } else if let Some(user_home) = dirs::home_dir() {
+    Ok(user_home.join(".blizz"))
+  } else {
+    anyhow::bail!("Could not determine home directory")
+  }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use super::*;
+  use std::ffi::OsString;
+  use std::fs;
+  use std::os::unix::fs::PermissionsExt;
+  use tempfile::TempDir;
+
+  fn script(dir: &Path, name: &str, body: &str, mode: u32) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+    path
+  }
+
+  fn path_of(dirs: &[&TempDir]) -> OsString {
+    std::env::join_paths(dirs.iter().map(|dir| dir.path())).unwrap()
+  }
+
+  #[test]
+  fn test_discover_finds_executables_and_first_on_path_wins() {
+    let (first, second) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let deploy = script(first.path(), "blizz-deploy", "exit 0", 0o755);
+    script(second.path(), "blizz-deploy", "exit 0", 0o755);
+    script(second.path(), "blizz-report", "exit 0", 0o755);
+    script(second.path(), "blizz-notes", "exit 0", 0o644);
+    script(second.path(), "blizz-", "exit 0", 0o755);
+    script(second.path(), "deploy", "exit 0", 0o755);
+    fs::create_dir(second.path().join("blizz-dir")).unwrap();
+
+    let plugins = discover(&path_of(&[&first, &second]));
+    let names: Vec<&str> = plugins.iter().map(|plugin| plugin.name.as_str()).collect();
+    assert_eq!(names, ["deploy", "report"]);
+    assert_eq!(plugins[0].path, deploy);
+
+    assert_eq!(find("deploy", &path_of(&[&first, &second])).unwrap().path, deploy);
+    assert!(find("notes", &path_of(&[&second])).is_none());
+  }
+
+  #[tokio::test]
+  async fn test_describe_uses_first_line_of_output() {
+    let dir = TempDir::new().unwrap();
+    let described = script(
+      dir.path(),
+      "blizz-deploy",
+      r#"[ "$1" = "--describe" ] && printf '\n  Ship the current branch  \nmore\n'"#,
+      0o755,
+    );
+    let failing = script(dir.path(), "blizz-broken", "exit 2", 0o755);
+
+    assert_eq!(describe(&described).await.as_deref(), Some("Ship the current branch"));
+    assert_eq!(describe(&failing).await, None);
+  }
+
+  #[test]
+  fn test_run_passes_args_env_and_exit_code() {
+    let dir = TempDir::new().unwrap();
+    let out = dir.path().join("out");
+    script(
+      dir.path(),
+      "blizz-deploy",
+      &format!(r#"echo "$BLIZZ_HOME|$PLUGIN_TEST_VALUE|$*" > {}; exit 7"#, out.display()),
+      0o755,
+    );
+
+    std::env::set_var("PLUGIN_TEST_VALUE", "inherited");
+    let args = vec!["--env".to_string(), "prod eu".to_string()];
+    let code = run("deploy", &args, &path_of(&[&dir]), Path::new("/opt/blizz")).unwrap();
+
+    assert_eq!(code, 7);
+    assert_eq!(fs::read_to_string(&out).unwrap(), "/opt/blizz|inherited|--env prod eu\n");
+  }
+
+  #[test]
+  fn test_run_reports_missing_plugins_and_signals() {
+    let dir = TempDir::new().unwrap();
+    let error = run("nope", &[], &path_of(&[&dir]), Path::new("/tmp")).unwrap_err();
+    assert!(error.to_string().contains("no blizz-nope on PATH"));
+
+    script(dir.path(), "blizz-crash", "kill -TERM $$", 0o755);
+    assert_eq!(run("crash", &[], &path_of(&[&dir]), Path::new("/tmp")).unwrap(), 128 + 15);
+  }
+}
@@ -0,0 +1,243 @@
+//! Remove everything blizz has put on this machine
+//!
+//! `blizz unlink` only takes the rules out of one directory. `blizz purge` goes after
+//! the rest: the blizz home and what lives in it (keeper vault, sockets and PID file,
+//! insights and their backups, vector data, rules and workflows), a separate secrets
+//! home when BLIZZ_DIR points elsewhere, and the keeper's systemd user units. The
+//! keeper is stopped first. With `--dry-run` nothing is touched; otherwise the list is
+//! shown with sizes and removed once confirmed.
+
+use anyhow::{anyhow, Context, Result};
+use bentley::{confirm, format_size};
+use console::style;
+use insights::client::get_client;
+use insights::server::startup::get_vector_base_path;
+use secrets::keeper_client::{self, KeeperState};
+use secrets::systemd;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::doctor::Paths;
+
+/// Something blizz created, with how much space it takes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+  pub label: String,
+  pub path: PathBuf,
+  pub bytes: u64,
+}
+
+/// Run `blizz purge`
+pub async fn execute(dry_run: bool, yes: bool) -> Result<()> {
+  let paths = Paths::from_env()?;
+  let unit_dir = systemd::user_unit_dir().ok();
+  let targets = collect(&paths, &get_vector_base_path(), unit_dir.as_deref())?;
+
+  if targets.is_empty() {
+    println!("Nothing to purge: blizz has left nothing behind");
+    return Ok(());
+  }
+
+  print_targets(&targets);
+  let total: u64 = targets.iter().map(|target| target.bytes).sum();
+  if dry_run {
+    println!("\n{} would be reclaimed (dry run, nothing removed)", format_size(total));
+    return Ok(());
+  }
+
+  if get_client().health_check().await.is_ok() {
+    return Err(anyhow!("the insights server is running; stop it before purging its data"));
+  }
+
+  if !yes && !confirm("\nRemove all of the above? This cannot be undone.")? {
+    println!("Purge cancelled");
+    return Ok(());
+  }
+
+  let socket = paths.keeper_socket();
+  if !matches!(keeper_client::probe(&socket).await, KeeperState::NotRunning) {
+    keeper_client::stop(&socket, &socket.with_file_name("keeper.pid")).await?;
+  }
+
+  let reclaimed = remove(&targets)?;
+  remove_if_empty(&paths.home);
+  println!("\n{} Purged blizz, reclaimed {}", style("✓").green(), format_size(reclaimed));
+  println!("Directories linked with `blizz link` keep a dangling .cursor/rules/blizz symlink;");
+  println!("run `blizz unlink` in them to tidy up.");
+  Ok(())
+}
+
+/// Everything to remove, in the order it is shown and removed
+pub fn collect(paths: &Paths, vector_base: &Path, unit_dir: Option<&Path>) -> Result<Vec<Target>> {
+  let home = &paths.home;
+  let mut known: Vec<(&str, PathBuf)> = vec![
+    ("keeper (vault, socket, PID file, logs)", home.join("persistent").join("keeper")),
+    ("insights (knowledge base, backups, logs)", home.join("persistent").join("insights")),
+    ("vector data", vector_base.to_path_buf()),
+    ("rules and workflows", home.join("volatile")),
+  ];
+  if paths.secrets_home != *home {
+    known.push(("secrets home (BLIZZ_DIR)", paths.secrets_home.clone()));
+  }
+  if let Some(unit_dir) = unit_dir {
+    for extension in ["socket", "service"] {
+      let unit = unit_dir.join(format!("{}.{extension}", systemd::UNIT_NAME));
+      known.push(("keeper systemd unit", unit));
+    }
+  }
+
+  let mut targets = Vec::new();
+  for (label, path) in known {
+    // Vector data normally sits inside volatile/, which is listed on its own
+    let covered = targets.iter().any(|target: &Target| path.starts_with(&target.path));
+    if !covered && path.symlink_metadata().is_ok() {
+      targets.retain(|target: &Target| !target.path.starts_with(&path));
+      targets.push(Target { label: label.to_string(), bytes: size(&path)?, path });
+    }
+  }
+
+  // Whatever else is in the home: session data, caches, files from older versions
+  if home.is_dir() {
+    let mut others: Vec<PathBuf> =
+      fs::read_dir(home)?.map(|entry| entry.map(|entry| entry.path())).collect::<Result<_, _>>()?;
+    others.sort();
+    for path in others {
+      if path.is_dir() && path.file_name().is_some_and(|name| name == "persistent") {
+        let mut rest: Vec<PathBuf> = fs::read_dir(&path)?
+          .map(|entry| entry.map(|entry| entry.path()))
+          .collect::<Result<_, _>>()?;
+        rest.sort();
+        push_other(&mut targets, rest)?;
+      } else {
+        push_other(&mut targets, vec![path])?;
+      }
+    }
+  }
+  Ok(targets)
+}
+
+fn push_other(targets: &mut Vec<Target>, paths: Vec<PathBuf>) -> Result<()> {
+  for path in paths {
+    if !targets.iter().any(|target| path.starts_with(&target.path)) {
+      targets.push(Target { label: "other".to_string(), bytes: size(&path)?, path });
+    }
+  }
+  Ok(())
+}
+
+/// Bytes used by `path` and everything below it; symlinks count as themselves
+pub fn size(path: &Path) -> Result<u64> {
+  let metadata =
+    path.symlink_metadata().with_context(|| format!("Failed to read {}", path.display()))?;
+  if !metadata.is_dir() {
+    return Ok(metadata.len());
+  }
+  let mut total = 0;
+  for entry in fs::read_dir(path)? {
+    total += size(&entry?.path())?;
+  }
+  Ok(total)
+}
+
+/// Remove every target, returning the bytes reclaimed
+pub fn remove(targets: &[Target]) -> Result<u64> {
+  let mut reclaimed = 0;
+  for target in targets {
+    let path = &target.path;
+    let removed = if path.symlink_metadata()?.is_dir() {
+      fs::remove_dir_all(path)
+    } else {
+      fs::remove_file(path)
+    };
+    removed.with_context(|| format!("Failed to remove {}", path.display()))?;
+    println!("  Removed: {}", path.display());
+    reclaimed += target.bytes;
+  }
+  Ok(reclaimed)
+}
+
+/// Remove the blizz home once purging has left it empty
+fn remove_if_empty(dir: &Path) {
+  let _ = fs::remove_dir(dir.join("persistent"));
+  let _ = fs::remove_dir(dir);
+}
+
+fn print_targets(targets: &[Target]) {
+  let width = targets.iter().map(|target| target.label.len()).max().unwrap_or(0);
+  for target in targets {
+    println!(
+      "{:<width$}  {:>10}  {}",
+      target.label,
+      format_size(target.bytes),
+      style(target.path.display()).dim()
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn write(path: &Path, bytes: usize) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, vec![0u8; bytes]).unwrap();
+  }
+
+  fn paths(home: &Path) -> Paths {
+    Paths {
+      home: home.to_path_buf(),
+      secrets_home: home.to_path_buf(),
+      vector_data: home.join("volatile/insights/default"),
+    }
+  }
+
+  #[test]
+  fn test_collect_lists_every_subsystem_once() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path().join(".blizz");
+    write(&home.join("persistent/keeper/credentials.enc"), 100);
+    write(&home.join("persistent/keeper/keeper.pid"), 4);
+    write(&home.join("persistent/insights/rust/async.md"), 50);
+    write(&home.join("persistent/sessions/last.json"), 7);
+    write(&home.join("volatile/insights/default/index.db"), 1000);
+    write(&home.join("volatile/.cursor/rules/blizz/rule.mdc"), 10);
+    write(&home.join("stray.log"), 3);
+    let units = dir.path().join("systemd");
+    write(&units.join("blizz-keeper.service"), 20);
+
+    let targets = collect(&paths(&home), &home.join("volatile/insights"), Some(&units)).unwrap();
+    let listed: Vec<(&str, u64)> =
+      targets.iter().map(|target| (target.label.as_str(), target.bytes)).collect();
+    assert_eq!(
+      listed,
+      [
+        ("keeper (vault, socket, PID file, logs)", 104),
+        ("insights (knowledge base, backups, logs)", 50),
+        ("rules and workflows", 1010),
+        ("keeper systemd unit", 20),
+        ("other", 7),
+        ("other", 3),
+      ]
+    );
+    assert_eq!(targets[4].path, home.join("persistent/sessions"));
+  }
+
+  #[test]
+  fn test_remove_reclaims_and_empties_home() {
+    let dir = TempDir::new().unwrap();
+    let home = dir.path().join(".blizz");
+    write(&home.join("persistent/keeper/credentials.enc"), 100);
+    write(&home.join("volatile/rule.mdc"), 28);
+    let vectors = dir.path().join("vectors");
+    write(&vectors.join("index.db"), 500);
+
+    let targets = collect(&paths(&home), &vectors, None).unwrap();
+    assert_eq!(remove(&targets).unwrap(), 628);
+    remove_if_empty(&home);
+    assert!(!home.exists());
+    assert!(!vectors.exists());
+
+    assert!(collect(&paths(&home), &vectors, None).unwrap().is_empty());
+  }
+}
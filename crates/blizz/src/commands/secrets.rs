@@ -1,11 +1,15 @@
 use anyhow::Result;
-use secrets::cli::{handle_command, Commands};
+use secrets::cli::{handle_command, Commands, OutputFormat};
 
 pub type SecretsCommands = Commands;
 
-pub async fn handle_secrets_command(command: SecretsCommands) -> Result<()> {
+pub async fn handle_secrets_command(
+  command: SecretsCommands,
+  output: OutputFormat,
+  quiet: bool,
+) -> Result<()> {
   // Set quiet mode when called from blizz
   std::env::set_var("SECRETS_QUIET", "1");
 
-  handle_command(command).await
+  handle_command(command, output, quiet).await
 }
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use clap::Subcommand;
+use insights::server::models::insight::get_insights_root;
+use insights::server::services::sync;
+
+#[derive(Subcommand)]
+pub enum SyncCommands {
+  /// Track the knowledge base in a git repository so it can be synced
+  Init {
+    /// Git remote shared by every machine, e.g. git@github.com:me/insights.git
+    #[arg(long)]
+    remote: Option<String>,
+  },
+  /// Commit local insight changes and push them to the remote
+  Push,
+  /// Fetch insights from the remote and merge them with local ones
+  Pull,
+}
+
+pub async fn execute(command: SyncCommands) -> Result<()> {
+  let root = get_insights_root()?;
+
+  match command {
+    SyncCommands::Init { remote } => {
+      sync::init(&root, remote.as_deref())?;
+      println!("Insight sync enabled in {}", root.display());
+      if remote.is_none() {
+        println!("Add a remote with `blizz sync init --remote <url>` to push and pull.");
+      }
+    }
+    SyncCommands::Push => {
+      sync::push(&root)?;
+      println!("Pushed insights to {}", sync::REMOTE);
+    }
+    SyncCommands::Pull => {
+      let outcome = sync::pull(&root)?;
+      if outcome.changed.is_empty() {
+        println!("Already up to date.");
+        return Ok(());
+      }
+
+      println!("Pulled {} changed insight files", outcome.changed.len());
+      for path in &outcome.conflicts {
+        println!("  conflict: {path} (edited on both machines; resolve the markers)");
+      }
+      println!("Run `insights index` to refresh search for the pulled insights.");
+    }
+  }
+
+  Ok(())
+}
@@ -1 +1,9 @@
 pub mod commands;
+
+/// Insight storage backends, shared with the insights server
+pub mod insight {
+  pub use insights::store::{
+    get_store_backend, migrate, open, store, FileStore, InsightStore, MigrationReport, SqliteStore,
+    StoreBackend,
+  };
+}
@@ -1,6 +1,12 @@
 use anyhow::Result;
-use clap::{command, Parser, Subcommand};
+use bentley::OutputFormat;
+use clap::error::ErrorKind;
+use clap::{command, CommandFactory, FromArgMatches, Parser, Subcommand};
+use commands::config::ConfigCommands;
+use commands::lint::{LintArgs, LintOptions};
+use commands::plugins::PluginsCommands;
 use commands::secrets::SecretsCommands;
+use commands::sync::SyncCommands;
 use std::process;
 
 mod commands;
@@ -14,6 +20,15 @@ Blizz is a tool for managing projects from a personal perspective, enabling you
 struct Cli {
   #[command(subcommand)]
   command: Commands,
+
+  /// Suppress banners and flourishes (useful when called from other tools);
+  /// lint only shows files with violations
+  #[arg(long, global = true)]
+  quiet: bool,
+
+  /// Output format for commands that produce reports
+  #[arg(long, global = true, value_enum, default_value = "text")]
+  output: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -49,6 +64,12 @@ enum Commands {
     /// Force disable colored output
     #[arg(long)]
     no_color: bool,
+    /// Most dependencies run at once (defaults to the number of CPUs)
+    #[arg(long, short = 'j')]
+    jobs: Option<usize>,
+    /// Secrets groups whose variables are injected into the task (e.g. github,jira)
+    #[arg(long, value_delimiter = ',')]
+    with_secrets: Vec<String>,
   },
   /// List available tasks
   Tasks {
@@ -75,21 +96,96 @@ enum Commands {
   Secrets {
     #[command(subcommand)]
     command: SecretsCommands,
-    /// Suppress banners and flourishes (useful when called from other tools)
-    #[arg(long, global = true)]
-    quiet: bool,
   },
+  /// Analyze code complexity
+  Lint {
+    #[command(flatten)]
+    args: LintArgs,
+    /// Only show files with violations (violet's -q)
+    #[arg(short = 'q', long)]
+    only_violations: bool,
+  },
+  /// Sync the insights knowledge base between machines through git
+  Sync {
+    #[command(subcommand)]
+    command: SyncCommands,
+  },
+  /// Move insights to another storage backend and switch to it
+  MigrateStore {
+    /// Store to move insights into: files or sqlite
+    #[arg(long)]
+    to: String,
+    /// Store to copy from (defaults to the configured one)
+    #[arg(long)]
+    from: Option<String>,
+    /// Replace insights the target store already holds
+    #[arg(long)]
+    overwrite: bool,
+  },
+  /// Browse, search and edit insights in an interactive terminal view
+  Browse,
+  /// Check the whole toolchain and suggest fixes for anything broken
+  Doctor,
+  /// Read and change settings shared by every blizz tool
+  Config {
+    #[command(subcommand)]
+    command: ConfigCommands,
+  },
+  /// Remove everything blizz has created on this machine
+  Purge {
+    /// List what would be removed and how much space it takes, without removing anything
+    #[arg(long)]
+    dry_run: bool,
+    /// Skip the confirmation prompt
+    #[arg(short, long)]
+    yes: bool,
+  },
+  /// Manage blizz-<name> plugins found on PATH
+  Plugins {
+    #[command(subcommand)]
+    command: PluginsCommands,
+  },
+  /// Any other command runs the matching blizz-<name> plugin
+  #[command(external_subcommand)]
+  External(Vec<String>),
+}
+
+impl Commands {
+  fn outputs(&self) -> &'static [OutputFormat] {
+    match self {
+      Commands::Lint { .. } => &[OutputFormat::Text, OutputFormat::Json, OutputFormat::Html],
+      Commands::Secrets { .. } | Commands::Doctor | Commands::Config { .. } => {
+        &[OutputFormat::Text, OutputFormat::Json]
+      }
+      _ => &[OutputFormat::Text],
+    }
+  }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-  let cli = Cli::parse();
+  let matches = Cli::command().get_matches();
+  let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+  if !cli.command.outputs().contains(&cli.output) {
+    let name = matches.subcommand_name().unwrap_or_default();
+    let message = format!("`blizz {name}` does not support --output {}", cli.output);
+    Cli::command().error(ErrorKind::ArgumentConflict, message).exit();
+  }
 
   match cli.command {
     Commands::Link { dir } => commands::link::execute(&dir).await,
     Commands::Unlink { dir } => commands::unlink::execute(&dir).await,
-    Commands::Do { name, args, silent, file, color, no_color } => {
-      execute_task(&name, &args, silent, file, color, no_color).await
+    Commands::Do { name, args, silent, file, color, no_color, jobs, with_secrets } => {
+      let options = commands::r#do::TaskRunnerOptions {
+        silent,
+        tasks_file_path: file,
+        force_color: color,
+        no_color,
+        jobs,
+        with_secrets,
+      };
+      execute_task(&name, &args, options).await
     }
     Commands::Tasks { file, verbose } => list_tasks(file, verbose).await,
     Commands::Version { list } => commands::version::execute(list).await,
@@ -109,8 +205,48 @@ async fn main() -> Result<()> {
       }
       Ok(())
     }
-    Commands::Secrets { command, quiet: _ } => {
-      commands::secrets::handle_secrets_command(command).await
+    Commands::Secrets { command } => {
+      commands::secrets::handle_secrets_command(command, cli.output, cli.quiet).await
+    }
+    Commands::Lint { args, only_violations } => {
+      let options = LintOptions { quiet: cli.quiet || only_violations, output: cli.output };
+      commands::lint::execute(&args, options)
+    }
+    Commands::Sync { command } => commands::sync::execute(command).await,
+    Commands::MigrateStore { to, from, overwrite } => {
+      commands::migrate_store::execute(&to, from.as_deref(), overwrite)
+    }
+    Commands::Browse => commands::browse::execute().await,
+    Commands::Doctor => {
+      let healthy = commands::doctor::execute(cli.output == OutputFormat::Json).await?;
+      if !healthy {
+        process::exit(1);
+      }
+      Ok(())
+    }
+    Commands::Config { command } => {
+      commands::config::execute(command, cli.output == OutputFormat::Json)
+    }
+    Commands::Purge { dry_run, yes } => commands::purge::execute(dry_run, yes).await,
+    Commands::Plugins { command } => {
+      let builtins: Vec<String> =
+        Cli::command().get_subcommands().map(|command| command.get_name().to_string()).collect();
+      commands::plugins::execute(command, &builtins).await
+    }
+    Commands::External(args) => run_plugin(&args),
+  }
+}
+
+fn run_plugin(args: &[String]) -> Result<()> {
+  let (name, args) = args.split_first().expect("clap always passes the subcommand name");
+  let path = std::env::var_os("PATH").unwrap_or_default();
+  let blizz_home = commands::plugins::get_blizz_home()?;
+
+  match commands::plugins::run(name, args, &path, &blizz_home) {
+    Ok(code) => process::exit(code),
+    Err(err) => {
+      eprintln!("error: {err}");
+      process::exit(2);
     }
   }
 }
@@ -118,18 +254,8 @@ async fn main() -> Result<()> {
 async fn execute_task(
   name: &str,
   args: &[String],
-  silent: bool,
-  file: Option<String>,
-  color: bool,
-  no_color: bool,
+  options: commands::r#do::TaskRunnerOptions,
 ) -> Result<()> {
-  let options = commands::r#do::TaskRunnerOptions {
-    silent,
-    tasks_file_path: file,
-    force_color: color,
-    no_color,
-  };
-
   let result = commands::r#do::run_task(name, args, options).await?;
 
   if !result.success {
@@ -158,8 +284,11 @@ async fn list_tasks(file: Option<String>, verbose: bool) -> Result<()> {
     for (name, command) in sorted_tasks {
       let dots_count = max_name_length - name.len() + 4; // +4 for some padding
       let dots = "·".repeat(dots_count);
-      let command_display = command.to_command_string();
-      println!("• {name} {dots} {command_display}");
+      let mut command_display = command.to_command_string();
+      if !command.depends_on().is_empty() {
+        command_display = format!("[after {}] {command_display}", command.depends_on().join(", "));
+      }
+      println!("• {name} {dots} {}", command_display.trim_end());
     }
   } else {
     let mut tasks = commands::r#do::list_tasks(file).await?;
@@ -172,3 +301,37 @@ async fn list_tasks(file: Option<String>, verbose: bool) -> Result<()> {
 
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_secrets_accepts_quiet_after_the_subcommand() {
+    let cli = Cli::try_parse_from(["blizz", "secrets", "--quiet", "list"]).unwrap();
+    assert!(cli.quiet);
+    assert!(matches!(cli.command, Commands::Secrets { .. }));
+  }
+
+  #[test]
+  fn test_global_output_reaches_lint() {
+    let cli = Cli::try_parse_from(["blizz", "--output", "html", "lint"]).unwrap();
+    assert_eq!(cli.output, OutputFormat::Html);
+    assert!(cli.command.outputs().contains(&cli.output));
+  }
+
+  #[test]
+  fn test_lint_short_quiet_only_shows_violations() {
+    let cli = Cli::try_parse_from(["blizz", "lint", "-q"]).unwrap();
+    assert!(!cli.quiet);
+    assert!(matches!(cli.command, Commands::Lint { only_violations: true, .. }));
+  }
+
+  #[test]
+  fn test_reports_only_offer_their_formats() {
+    let cli = Cli::try_parse_from(["blizz", "doctor", "--output", "html"]).unwrap();
+    assert!(!cli.command.outputs().contains(&cli.output));
+    let cli = Cli::try_parse_from(["blizz", "tasks", "--output", "json"]).unwrap();
+    assert!(!cli.command.outputs().contains(&cli.output));
+  }
+}
@@ -22,6 +22,14 @@ struct Args {
   /// Enable verbose logging
   #[arg(short, long)]
   verbose: bool,
+
+  /// Reindex insight files when they are edited on disk
+  #[arg(long)]
+  watch: bool,
+
+  /// Serve the read-only web UI at /ui
+  #[arg(long)]
+  ui: bool,
 }
 
 #[tokio::main]
@@ -43,7 +51,7 @@ async fn main() -> Result<()> {
   bentley::info!(&format!("Binding to address: {}", args.bind));
 
   // Start the server
-  start_server(args.bind).await?;
+  start_server(args.bind, args.watch, args.ui).await?;
 
   Ok(())
 }
@@ -4,15 +4,34 @@
 //! to seamlessly work with both local and remote insights servers.
 
 use anyhow::{anyhow, Result};
+use bentley::config;
 use reqwest::Client;
 
+use crate::server::services::search::SearchCommandOptions;
 use std::time::Duration;
 use tokio::time::timeout;
 
+use crate::server::models::webhook::WebhookEvent;
+use crate::server::services::webhooks::DeliveryRecord;
+use crate::server::services::workspace;
 use crate::server::types::{
-  AddInsightRequest, BaseResponse, GetInsightRequest, GetInsightResponse, InsightFilter,
-  ListInsightsResponse, ListTopicsResponse, RemoveInsightRequest, UpdateInsightRequest,
+  AddInsightRequest, AddWebhookRequest, ApiError, AskRequest, AskResponse, BaseResponse,
+  BatchRequest, BatchResponse, BootstrapRequest, BootstrapResponse, ConfigureShardsRequest,
+  ConflictPolicy, CreateBackupResponse, CreateWorkspaceRequest, DeleteWorkspaceRequest,
+  DiffVersionsRequest, DiffVersionsResponse, ErrorCode, ExportInsightsRequest,
+  FindDuplicatesRequest, FindDuplicatesResponse, GetInsightRequest, GetInsightResponse,
+  HistoryRequest, HistoryResponse, ImportEntry, ImportInsightsRequest, ImportInsightsResponse,
+  IndexingStatusResponse, InsightsArchive, LintResponse, ListBackupsResponse,
+  ListDeliveriesResponse, ListInsightsResponse, ListRetentionResponse, ListTagsResponse,
+  ListTopicsResponse, ListWebhooksResponse, ListWorkspacesResponse, RebalanceShardsRequest,
+  RebalanceShardsResponse, ReindexStatusResponse, RelatedInsightsRequest, RelatedInsightsResponse,
+  RemoveInsightRequest, RemoveRetentionRequest, RemoveWebhookRequest, RestoreBackupRequest,
+  RestoreBackupResponse, RetentionPolicyData, RetentionSweepResponse, RollbackRequest,
+  RollbackResponse, ScanResponse, ShardsResponse, StatsResponse, StatusResponse,
+  SummarizeTopicRequest, TopicSummaryResponse, UpdateInsightRequest, ValidateRequest,
+  ValidateResponse, WebhookData, WorkspaceData, WriteInsightResponse,
 };
+use crate::settings;
 
 /// HTTP method types for REST API calls
 #[derive(Debug, Copy, Clone)]
@@ -42,11 +61,20 @@ pub struct ClientConfig {
   pub base_url: String,
   /// Request timeout in seconds
   pub timeout_secs: u64,
+  /// Bearer token sent with every request, when the server requires one
+  pub token: Option<String>,
+  /// Workspace every request operates on; the server's default when unset
+  pub workspace: Option<String>,
 }
 
 impl Default for ClientConfig {
   fn default() -> Self {
-    Self { base_url: "http://localhost:3000".to_string(), timeout_secs: 30 }
+    Self {
+      base_url: "http://localhost:3000".to_string(),
+      timeout_secs: 30,
+      token: None,
+      workspace: None,
+    }
   }
 }
 
@@ -62,6 +90,45 @@ impl Default for InsightsClient {
   }
 }
 
+/// A request the server rejected with a structured error
+#[derive(Debug)]
+pub struct ApiFailure {
+  /// Stable error category reported by the server
+  pub code: ErrorCode,
+  /// Human readable description of the failure
+  pub message: String,
+}
+
+impl std::fmt::Display for ApiFailure {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} [{}]", self.message, self.code)
+  }
+}
+
+impl std::error::Error for ApiFailure {}
+
+/// Error body returned by the server on failure
+#[derive(serde::Deserialize)]
+struct ErrorBody {
+  #[serde(default)]
+  errors: Vec<ApiError>,
+}
+
+/// Turn a failed response body into a coded failure when the server provided one
+fn parse_failure(body: &str, method: HttpMethod, endpoint: &str) -> anyhow::Error {
+  let error =
+    serde_json::from_str::<ErrorBody>(body).ok().and_then(|b| b.errors.into_iter().next());
+
+  match error {
+    Some(error) => ApiFailure {
+      code: error.code,
+      message: format!("Failed {method} {endpoint}: {}", error.message),
+    }
+    .into(),
+    None => anyhow!("Failed {method} {endpoint}: {body}"),
+  }
+}
+
 /// Helpers to handle HTTP response parsing and error handling
 async fn parse_response<R>(
   response: reqwest::Response,
@@ -73,7 +140,7 @@ where
 {
   if !response.status().is_success() {
     let error_text = response.text().await?;
-    return Err(anyhow!("Failed {method} {endpoint}: {error_text}"));
+    return Err(parse_failure(&error_text, method, endpoint));
   }
 
   let result: BaseResponse<R> = response.json().await?;
@@ -92,6 +159,7 @@ impl InsightsClient {
   pub fn with_config(config: ClientConfig) -> Self {
     let client = Client::builder()
       .timeout(Duration::from_secs(config.timeout_secs))
+      .default_headers(default_headers(&config))
       .build()
       .expect("Failed to create HTTP client");
 
@@ -99,25 +167,27 @@ impl InsightsClient {
   }
 }
 
+/// Headers sent with every request
+fn default_headers(config: &ClientConfig) -> reqwest::header::HeaderMap {
+  use reqwest::header::HeaderValue;
+
+  let mut headers = reqwest::header::HeaderMap::new();
+  let bearer = config.token.as_ref().map(|token| format!("Bearer {}", token.trim()));
+  if let Some(value) = bearer.and_then(|b| HeaderValue::from_str(&b).ok()) {
+    headers.insert(reqwest::header::AUTHORIZATION, value);
+  }
+  if let Some(value) = config.workspace.as_deref().and_then(|w| HeaderValue::from_str(w).ok()) {
+    headers.insert(workspace::HEADER, value);
+  }
+  headers
+}
+
 // Client Methods
 // ==============
 impl InsightsClient {
   /// Add a new insight
-  pub async fn add_insight(
-    &self,
-    topic: &str,
-    name: &str,
-    overview: &str,
-    details: &str,
-  ) -> Result<()> {
-    let request = AddInsightRequest {
-      topic: topic.to_string(),
-      name: name.to_string(),
-      overview: overview.to_string(),
-      details: details.to_string(),
-    };
-
-    self.post_json::<AddInsightRequest, ()>("/insights/add", &request).await
+  pub async fn add_insight(&self, request: &AddInsightRequest) -> Result<WriteInsightResponse> {
+    self.post_json("/insights/add", request).await
   }
 
   /// Get a specific insight
@@ -136,19 +206,9 @@ impl InsightsClient {
   /// Update an existing insight
   pub async fn update_insight(
     &self,
-    topic: &str,
-    name: &str,
-    overview: Option<&str>,
-    details: Option<&str>,
-  ) -> Result<()> {
-    let request = UpdateInsightRequest {
-      topic: topic.to_string(),
-      name: name.to_string(),
-      overview: overview.map(|s| s.to_string()),
-      details: details.map(|s| s.to_string()),
-    };
-
-    self.put_json::<UpdateInsightRequest, ()>("/insights/update", &request).await
+    request: &UpdateInsightRequest,
+  ) -> Result<WriteInsightResponse> {
+    self.put_json("/insights/update", request).await
   }
 
   /// Remove an insight
@@ -158,24 +218,62 @@ impl InsightsClient {
     self.delete_json::<RemoveInsightRequest, ()>("/insights/remove", &request).await
   }
 
+  /// Audit stored insights for secrets and PII
+  pub async fn scan_insights(&self) -> Result<ScanResponse> {
+    self.get_json("/insights/scan").await
+  }
+
+  /// Check stored insights against the content lint rules
+  pub async fn lint_insights(&self, topic: Option<&str>) -> Result<LintResponse> {
+    let query: Vec<_> = topic.map(|topic| ("topic", topic.to_string())).into_iter().collect();
+    self.get_json_with_query("/insights/lint", &query).await
+  }
+
+  /// Check stored frontmatter against the insight schema, repairing it when `fix` is set
+  pub async fn validate_insights(
+    &self,
+    topic: Option<&str>,
+    fix: bool,
+  ) -> Result<ValidateResponse> {
+    let request = ValidateRequest { topic: topic.map(str::to_string), fix };
+    self.post_json("/insights/validate", &request).await
+  }
+
   /// List all topics
   pub async fn list_topics(&self) -> Result<Vec<String>> {
     let response: ListTopicsResponse = self.get_json("/insights/list/topics").await?;
     Ok(response.topics)
   }
 
-  /// List insights with optional filtering
-  pub async fn list_insights(&self, _filters: Vec<InsightFilter>) -> Result<ListInsightsResponse> {
-    // TODO: Add query parameter support
-    self.get_json("/insights/list/insights").await
+  /// List insights in a topic (and those nested under it) carrying every given tag
+  pub async fn list_insights(
+    &self,
+    topic: Option<&str>,
+    tags: &[String],
+  ) -> Result<ListInsightsResponse> {
+    let mut query = Vec::new();
+    if let Some(topic) = topic {
+      query.push(("topic", topic.to_string()));
+    }
+    if !tags.is_empty() {
+      query.push(("tags", tags.join(",")));
+    }
+    self.get_json_with_query("/insights/list/insights", &query).await
+  }
+
+  /// List every tag in use with how many insights carry it
+  pub async fn list_tags(&self) -> Result<ListTagsResponse> {
+    self.get_json("/insights/list/tags").await
   }
 
   /// Check if the server is reachable
   pub async fn health_check(&self) -> Result<()> {
     let url = format!("{}/status", self.config.base_url);
+    // Ask as the default workspace, so a missing one doesn't look like a stopped server
+    let request = self.client.get(&url).header(workspace::HEADER, workspace::DEFAULT);
     let response = timeout(
       Duration::from_secs(5), // Shorter timeout for health check
-      self.client.get(&url).send(),
+      request.send(),
     )
     .await??;
 
@@ -186,6 +284,11 @@ impl InsightsClient {
     }
   }
 
+  /// The server's health, version and insights root
+  pub async fn status(&self) -> Result<StatusResponse> {
+    self.get_json("/status").await
+  }
+
   /// Get server logs
   pub async fn get_logs(
     &self,
@@ -205,21 +308,285 @@ impl InsightsClient {
   pub async fn search_insights(
     &self,
     terms: Vec<String>,
-    topic: Option<String>,
-    case_sensitive: bool,
-    overview_only: bool,
-    exact: bool,
-    semantic: bool,
+    options: &SearchCommandOptions,
   ) -> Result<crate::server::types::SearchResponse> {
     use crate::server::types::SearchRequest;
 
-    let request = SearchRequest { terms, topic, case_sensitive, overview_only, exact, semantic };
+    let request = SearchRequest {
+      terms,
+      topic: options.topic.clone(),
+      tags: options.tags.clone(),
+      case_sensitive: options.case_sensitive,
+      overview_only: options.overview_only,
+      exact: options.exact,
+      semantic: options.semantic,
+      mode: options.mode,
+      hybrid_weight: options.hybrid_weight,
+      popularity_weight: options.popularity_weight,
+      recency_weight: options.recency_weight,
+      recency_half_life_days: options.recency_half_life,
+      topic_boosts: options.topic_boost_map(),
+      explain: options.explain,
+    };
     self.post_json("/insights/search", &request).await
   }
 
-  /// Re-index all insights (fire-and-forget)
-  pub async fn reindex_insights(&self) -> Result<()> {
-    self.delete_without_body::<()>("/insights/index").await
+  /// Ask a question answered from the knowledge base
+  pub async fn ask(
+    &self,
+    question: &str,
+    topic: Option<String>,
+    limit: Option<usize>,
+  ) -> Result<AskResponse> {
+    let request = AskRequest { question: question.to_string(), topic, limit };
+    self.post_json("/ask", &request).await
+  }
+
+  /// Import many insights in a single request
+  pub async fn import_insights(
+    &self,
+    insights: Vec<ImportEntry>,
+    on_conflict: ConflictPolicy,
+    allow_sensitive: bool,
+  ) -> Result<ImportInsightsResponse> {
+    let request = ImportInsightsRequest { insights, on_conflict, allow_sensitive };
+    self.post_json("/insights/import", &request).await
+  }
+
+  /// Apply creates, updates and deletes in one all-or-nothing request
+  pub async fn apply_batch(&self, request: &BatchRequest) -> Result<BatchResponse> {
+    self.post_json("/insights/batch", request).await
+  }
+
+  /// Fetch insights, and optionally their embeddings, as an archive
+  pub async fn export_insights(
+    &self,
+    topic: Option<String>,
+    include_embeddings: bool,
+  ) -> Result<InsightsArchive> {
+    let request = ExportInsightsRequest { topic, include_embeddings };
+    self.post_json("/insights/export", &request).await
+  }
+
+  /// Populate the knowledge base from a seed archive
+  pub async fn bootstrap_insights(
+    &self,
+    source: &str,
+    sha256: Option<String>,
+    force: bool,
+  ) -> Result<BootstrapResponse> {
+    let request = BootstrapRequest { source: source.to_string(), sha256, force };
+    self.post_json("/insights/bootstrap", &request).await
+  }
+
+  /// Cluster a topic's insights into an outline
+  pub async fn summarize_topic(
+    &self,
+    topic: &str,
+    max_clusters: Option<usize>,
+  ) -> Result<TopicSummaryResponse> {
+    let request = SummarizeTopicRequest { topic: topic.to_string(), max_clusters };
+    self.post_json("/insights/summary", &request).await
+  }
+
+  /// Find clusters of near-identical insights
+  pub async fn find_duplicates(
+    &self,
+    topic: Option<&str>,
+    threshold: Option<f32>,
+  ) -> Result<FindDuplicatesResponse> {
+    let request = FindDuplicatesRequest { topic: topic.map(str::to_string), threshold };
+    self.post_json("/insights/duplicates", &request).await
+  }
+
+  /// Find the insights nearest to a stored insight
+  pub async fn related_insights(
+    &self,
+    topic: &str,
+    name: &str,
+    limit: Option<usize>,
+  ) -> Result<RelatedInsightsResponse> {
+    let request =
+      RelatedInsightsRequest { topic: topic.to_string(), name: name.to_string(), limit };
+    self.post_json("/insights/related", &request).await
+  }
+
+  /// List the recorded versions of an insight
+  pub async fn history(&self, topic: &str, name: &str) -> Result<HistoryResponse> {
+    let request = HistoryRequest { topic: topic.to_string(), name: name.to_string() };
+    self.post_json("/insights/history", &request).await
+  }
+
+  /// Diff two versions of an insight
+  pub async fn diff_versions(
+    &self,
+    topic: &str,
+    name: &str,
+    from: Option<u32>,
+    to: Option<u32>,
+  ) -> Result<DiffVersionsResponse> {
+    let request =
+      DiffVersionsRequest { topic: topic.to_string(), name: name.to_string(), from, to };
+    self.post_json("/insights/history/diff", &request).await
+  }
+
+  /// Restore an earlier version of an insight
+  pub async fn rollback(&self, topic: &str, name: &str, to: u32) -> Result<RollbackResponse> {
+    let request = RollbackRequest { topic: topic.to_string(), name: name.to_string(), to };
+    self.post_json("/insights/history/rollback", &request).await
+  }
+
+  /// List retention policies
+  pub async fn list_retention(&self) -> Result<Vec<RetentionPolicyData>> {
+    let response: ListRetentionResponse = self.get_json("/insights/retention").await?;
+    Ok(response.policies)
+  }
+
+  /// Create or replace a topic's retention policy
+  pub async fn set_retention(&self, policy: &RetentionPolicyData) -> Result<()> {
+    self.put_json::<RetentionPolicyData, ()>("/insights/retention", policy).await
+  }
+
+  /// Remove a topic's retention policy
+  pub async fn remove_retention(&self, topic: &str) -> Result<()> {
+    let request = RemoveRetentionRequest { topic: topic.to_string() };
+    self.delete_json::<RemoveRetentionRequest, ()>("/insights/retention", &request).await
+  }
+
+  /// Archive expired insights immediately
+  pub async fn sweep_retention(&self) -> Result<RetentionSweepResponse> {
+    self.post_json("/insights/retention/sweep", &()).await
+  }
+
+  /// List backups, newest first
+  pub async fn list_backups(&self) -> Result<ListBackupsResponse> {
+    self.get_json("/insights/backups").await
+  }
+
+  /// Back up insights and the vector database now
+  pub async fn create_backup(&self) -> Result<CreateBackupResponse> {
+    self.post_json("/insights/backups", &()).await
+  }
+
+  /// Replace the insights with a backup
+  pub async fn restore_backup(&self, id: &str) -> Result<RestoreBackupResponse> {
+    let request = RestoreBackupRequest { id: id.to_string() };
+    self.post_json("/insights/backups/restore", &request).await
+  }
+
+  /// List workspaces, the default first
+  pub async fn list_workspaces(&self) -> Result<ListWorkspacesResponse> {
+    self.get_json("/workspaces").await
+  }
+
+  /// Create a workspace
+  pub async fn create_workspace(&self, name: &str) -> Result<WorkspaceData> {
+    let request = CreateWorkspaceRequest { name: name.to_string() };
+    self.post_json("/workspaces", &request).await
+  }
+
+  /// Delete a workspace with its insights and embeddings
+  pub async fn delete_workspace(&self, name: &str) -> Result<()> {
+    let request = DeleteWorkspaceRequest { name: name.to_string() };
+    self.delete_json::<DeleteWorkspaceRequest, ()>("/workspaces", &request).await
+  }
+
+  /// Resource limits and state of background indexing
+  pub async fn indexing_status(&self) -> Result<IndexingStatusResponse> {
+    self.get_json("/insights/indexing").await
+  }
+
+  /// Hold background indexing until resumed
+  pub async fn pause_indexing(&self) -> Result<IndexingStatusResponse> {
+    self.post_json("/insights/indexing/pause", &()).await
+  }
+
+  /// Let paused background indexing continue
+  pub async fn resume_indexing(&self) -> Result<IndexingStatusResponse> {
+    self.post_json("/insights/indexing/resume", &()).await
+  }
+
+  /// List registered webhooks
+  pub async fn list_webhooks(&self) -> Result<Vec<WebhookData>> {
+    let response: ListWebhooksResponse = self.get_json("/insights/webhooks").await?;
+    Ok(response.webhooks)
+  }
+
+  /// Register a webhook for insight changes
+  pub async fn add_webhook(
+    &self,
+    url: &str,
+    secret: &str,
+    topics: &[String],
+    events: &[WebhookEvent],
+  ) -> Result<WebhookData> {
+    let request = AddWebhookRequest {
+      url: url.to_string(),
+      secret: secret.to_string(),
+      topics: topics.to_vec(),
+      events: events.to_vec(),
+    };
+    self.post_json("/insights/webhooks", &request).await
+  }
+
+  /// Remove a webhook
+  pub async fn remove_webhook(&self, id: &str) -> Result<()> {
+    let request = RemoveWebhookRequest { id: id.to_string() };
+    self.delete_json::<RemoveWebhookRequest, ()>("/insights/webhooks", &request).await
+  }
+
+  /// Recent webhook deliveries, newest first
+  pub async fn list_deliveries(
+    &self,
+    webhook: Option<&str>,
+    limit: usize,
+  ) -> Result<Vec<DeliveryRecord>> {
+    let mut endpoint = format!("/insights/webhooks/deliveries?limit={limit}");
+    if let Some(id) = webhook {
+      endpoint.push_str(&format!("&webhook={id}"));
+    }
+    let response: ListDeliveriesResponse = self.get_json(&endpoint).await?;
+    Ok(response.deliveries)
+  }
+
+  /// The most used insights, most read first
+  pub async fn stats(&self, top: usize) -> Result<StatsResponse> {
+    self.get_json(&format!("/insights/stats?top={top}")).await
+  }
+
+  /// Show the shard layout and shard sizes
+  pub async fn list_shards(&self) -> Result<ShardsResponse> {
+    self.get_json("/insights/shards").await
+  }
+
+  /// Switch to a new shard layout
+  pub async fn configure_shards(
+    &self,
+    request: &ConfigureShardsRequest,
+  ) -> Result<RebalanceShardsResponse> {
+    self.put_json("/insights/shards", request).await
+  }
+
+  /// Redistribute embeddings across shards
+  pub async fn rebalance_shards(&self, force: bool) -> Result<RebalanceShardsResponse> {
+    self.post_json("/insights/shards/rebalance", &RebalanceShardsRequest { force }).await
+  }
+
+  /// Start re-indexing all insights in the background
+  pub async fn reindex_insights(
+    &self,
+    concurrency: Option<usize>,
+  ) -> Result<ReindexStatusResponse> {
+    let endpoint = match concurrency {
+      Some(concurrency) => format!("/insights/index?concurrency={concurrency}"),
+      None => "/insights/index".to_string(),
+    };
+    self.delete_without_body(&endpoint).await
+  }
+
+  /// Progress of the current or last re-index
+  pub async fn reindex_status(&self) -> Result<ReindexStatusResponse> {
+    self.get_json("/insights/index/status").await
   }
 }
 
@@ -275,11 +642,19 @@ impl InsightsClient {
 
   /// Helper to make a GET request and return parsed response data
   async fn get_json<R>(&self, endpoint: &str) -> Result<R>
+  where
+    R: serde::de::DeserializeOwned,
+  {
+    self.get_json_with_query(endpoint, &[]).await
+  }
+
+  /// Helper to make a GET request with percent-encoded query parameters
+  async fn get_json_with_query<R>(&self, endpoint: &str, query: &[(&str, String)]) -> Result<R>
   where
     R: serde::de::DeserializeOwned,
   {
     let url = format!("{}{}", self.config.base_url, endpoint);
-    let response = self.execute_with_timeout(|| self.client.get(&url).send()).await?;
+    let response = self.execute_with_timeout(|| self.client.get(&url).query(query).send()).await?;
 
     parse_response(response, HttpMethod::Get, endpoint).await
   }
@@ -296,15 +671,18 @@ impl InsightsClient {
   }
 }
 
-/// Get the configured client (checks environment variables)
+/// Get the configured client (checks settings and environment variables)
 pub fn get_client() -> InsightsClient {
   let base_url =
-    std::env::var("INSIGHTS_SERVER_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+    config::var(&settings::SERVER_URL).unwrap_or_else(|| "http://localhost:3000".to_string());
+
+  let timeout_secs = config::get(&settings::TIMEOUT_SECS).unwrap_or(30);
+
+  let token = std::env::var("INSIGHTS_API_TOKEN").ok().filter(|t| !t.trim().is_empty());
 
-  let timeout_secs =
-    std::env::var("INSIGHTS_TIMEOUT_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(30);
+  let workspace = config::var(&settings::WORKSPACE).filter(|w| !w.trim().is_empty());
 
-  let config = ClientConfig { base_url, timeout_secs };
+  let config = ClientConfig { base_url, timeout_secs, token, workspace };
 
   InsightsClient::with_config(config)
 }
@@ -1,177 +1,1609 @@
 use anyhow::{anyhow, Result};
+use bentley::{confirm, format_size};
+use chrono::{DateTime, NaiveDate, Utc};
 use colored::*;
+use std::path::Path;
 
-use crate::cli::client::get_client;
-use crate::cli::display::display_search_result;
+use crate::cli::client::{get_client, ApiFailure, InsightsClient};
+use crate::cli::display::{display_search_result, format_explanation, format_score_breakdown};
 use crate::cli::server_manager::ensure_server_running;
+use crate::server::models::retention::EXPIRY_WARNING_DAYS;
+use crate::server::models::sharding::ShardStrategy;
+use crate::server::models::tag;
+use crate::server::models::topic::{self, TopicNode};
+use crate::server::models::webhook::WebhookEvent;
+use crate::server::services::auth::{self, Scope};
+use crate::server::services::dedupe;
+use crate::server::services::export::{write_archive, ArchiveFormat};
+use crate::server::services::fuzzy;
+use crate::server::services::reindex::{self, JobState};
+use crate::server::services::search::query::{self, Query};
+use crate::server::services::search::SearchCommandOptions;
+use crate::server::services::slack::{self, SlackExport, SlackImportOptions};
+use crate::server::types::{
+  AddInsightRequest, BatchOperation, BatchRequest, BatchStatus, ClusterInsightData,
+  ConfigureShardsRequest, ConflictPolicy, DuplicateInsightData, ErrorCode, ImportEntry,
+  ImportInsightsResponse, IndexingStatusResponse, LintFindingData, RebalanceShardsResponse,
+  ReindexStatusResponse, RetentionPolicyData, UpdateInsightRequest,
+};
 // CLI is now a pure thin client - no business logic imports needed
 
 /// Add a new insight to the knowledge base (production version)
-pub async fn add_insight(topic: &str, name: &str, overview: &str, details: &str) -> Result<()> {
+pub async fn add_insight(
+  topic: &str,
+  name: &str,
+  overview: &str,
+  details: &str,
+  tags: &[String],
+  allow_sensitive: bool,
+  strict: bool,
+) -> Result<()> {
+  ensure_server_running().await?;
+  let client = get_client();
+  let request = AddInsightRequest {
+    topic: topic.to_string(),
+    name: name.to_string(),
+    overview: overview.to_string(),
+    details: details.to_string(),
+    tags: tags.to_vec(),
+    allow_sensitive,
+    strict,
+  };
+  let response = client.add_insight(&request).await?;
+
+  println!("{} Added insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
+  print_lint_warnings(&response.warnings);
+  Ok(())
+}
+
+/// Bulk-load insights from a JSON or YAML file containing an array of entries
+pub async fn import_insights(
+  file: &Path,
+  on_conflict: ConflictPolicy,
+  allow_sensitive: bool,
+) -> Result<()> {
+  let entries = read_import_file(file)?;
+  if entries.is_empty() {
+    println!("No insights found in {}", file.display());
+    return Ok(());
+  }
+
+  ensure_server_running().await?;
+  let client = get_client();
+  let response = client.import_insights(entries, on_conflict, allow_sensitive).await?;
+  print_import_summary(&response);
+  Ok(())
+}
+
+/// Import every thread of a Slack channel export as an insight
+///
+/// Insights are named after their thread, so importing a newer export of the same
+/// channel skips threads already imported (or refreshes them with `--on-conflict merge`).
+pub async fn import_slack(
+  export: &Path,
+  options: &SlackImportOptions,
+  on_conflict: ConflictPolicy,
+  allow_sensitive: bool,
+) -> Result<()> {
+  let entries = slack::to_entries(&SlackExport::open(export)?, options)?;
+  if entries.is_empty() {
+    println!("No threads found in #{}", options.channel);
+    return Ok(());
+  }
+
+  println!("Found {} threads in #{}", entries.len(), options.channel.cyan());
+  ensure_server_running().await?;
+  let client = get_client();
+  let response = client.import_insights(entries, on_conflict, allow_sensitive).await?;
+  print_import_summary(&response);
+  Ok(())
+}
+
+fn print_import_summary(response: &ImportInsightsResponse) {
+  println!(
+    "{} Imported {} insights ({} merged, {} skipped)",
+    "✓".green(),
+    response.added.len(),
+    response.merged.len(),
+    response.skipped.len()
+  );
+  for id in &response.skipped {
+    println!("  {} {}", "skipped".dimmed(), id.yellow());
+  }
+  if response.embeddings_queued > 0 {
+    println!(
+      "  Computing embeddings for {} insights in the background",
+      response.embeddings_queued
+    );
+  }
+}
+
+/// Apply a file of operations as one batch
+///
+/// The file holds an array of operations, or a whole request body with `operations`
+/// and its own `allow_sensitive` and `strict` flags.
+pub async fn apply_batch(file: &Path, allow_sensitive: bool, strict: bool) -> Result<()> {
+  let mut request = match read_data_file::<BatchFile>(file)? {
+    BatchFile::Operations(operations) => BatchRequest { operations, ..Default::default() },
+    BatchFile::Request(request) => request,
+  };
+  request.allow_sensitive |= allow_sensitive;
+  request.strict |= strict;
+  if request.operations.is_empty() {
+    println!("No operations found in {}", file.display());
+    return Ok(());
+  }
+
+  ensure_server_running().await?;
+  let client = get_client();
+  let response = client.apply_batch(&request).await?;
+
+  println!("{} Applied {} operations", "✓".green(), response.results.len());
+  for result in &response.results {
+    let status = match result.status {
+      BatchStatus::Created => "created",
+      BatchStatus::Updated => "updated",
+      BatchStatus::Deleted => "deleted",
+      BatchStatus::Failed | BatchStatus::NotApplied => "skipped",
+    };
+    println!("  {:<8} {}/{}", status.dimmed(), result.topic.cyan(), result.name.yellow());
+    print_lint_warnings(&result.warnings);
+  }
+  if response.embeddings_queued > 0 {
+    println!(
+      "  Computing embeddings for {} insights in the background",
+      response.embeddings_queued
+    );
+  }
+  Ok(())
+}
+
+/// Contents of a file given to `insights apply`
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum BatchFile {
+  Operations(Vec<BatchOperation>),
+  Request(BatchRequest),
+}
+
+/// Write the knowledge base (or one topic) to a JSON or tar.gz archive
+pub async fn export_insights(
+  output: &Path,
+  topic: Option<String>,
+  include_embeddings: bool,
+  format: Option<ArchiveFormat>,
+) -> Result<()> {
+  let format = format.or_else(|| ArchiveFormat::from_path(output)).ok_or_else(|| {
+    anyhow!("Cannot tell the archive format from {}; pass --format", output.display())
+  })?;
+
+  ensure_server_running().await?;
+  let client = get_client();
+  let archive = client.export_insights(topic, include_embeddings).await?;
+
+  let file = std::fs::File::create(output)
+    .map_err(|e| anyhow!("Failed to create {}: {}", output.display(), e))?;
+  write_archive(&archive, format, std::io::BufWriter::new(file))
+    .map_err(|e| anyhow!("Failed to write {}: {}", output.display(), e))?;
+
+  let embedded = archive.insights.iter().filter(|i| i.embedding.is_some()).count();
+  println!(
+    "{} Exported {} insights from {} topics to {}",
+    "✓".green(),
+    archive.insights.len(),
+    archive.topics.len(),
+    output.display().to_string().cyan()
+  );
+  if include_embeddings {
+    println!("  {embedded} with embeddings");
+  }
+
+  Ok(())
+}
+
+/// Populate a fresh knowledge base from a seed archive
+///
+/// The server downloads and verifies the seed itself, so a local path is made
+/// absolute first in case the server runs from another directory.
+pub async fn bootstrap_insights(source: &str, sha256: Option<String>, force: bool) -> Result<()> {
+  let is_url = source.contains("://");
+  let source = match std::fs::canonicalize(source) {
+    Ok(path) if !is_url => path.display().to_string(),
+    _ => source.to_string(),
+  };
+
+  ensure_server_running().await?;
+  let client = get_client();
+  let response = client.bootstrap_insights(&source, sha256, force).await?;
+
+  println!("{} Restored {} insights from {}", "✓".green(), response.restored.len(), source.cyan());
+  println!("  {} embeddings restored", response.embeddings_restored);
+  if response.embeddings_queued > 0 {
+    println!("  {} queued for embedding in the background", response.embeddings_queued);
+  }
+  if !response.skipped.is_empty() {
+    println!(
+      "  {} skipped (already present): {}",
+      response.skipped.len(),
+      response.skipped.join(", ")
+    );
+  }
+
+  Ok(())
+}
+
+/// Parse an import file, choosing the format from its extension
+fn read_import_file(file: &Path) -> Result<Vec<ImportEntry>> {
+  read_data_file(file)
+}
+
+/// Parse a JSON or YAML file, choosing the format from its extension
+fn read_data_file<T: serde::de::DeserializeOwned>(file: &Path) -> Result<T> {
+  let content = std::fs::read_to_string(file)
+    .map_err(|e| anyhow!("Failed to read {}: {}", file.display(), e))?;
+
+  let is_json = file.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+  if is_json {
+    serde_json::from_str(&content).map_err(|e| anyhow!("Invalid JSON in {}: {}", file.display(), e))
+  } else {
+    serde_yaml::from_str(&content).map_err(|e| anyhow!("Invalid YAML in {}: {}", file.display(), e))
+  }
+}
+
+/// Get content of a specific insight
+pub async fn get_insight(topic: &str, name: &str, overview_only: bool) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.get_insight(topic, name, overview_only).await?;
+
+  if overview_only {
+    println!("{}", response.insight.overview);
+  } else {
+    println!("---\n{}\n---\n\n{}", response.insight.overview, response.insight.details);
+  }
+
+  Ok(())
+}
+
+/// Resolve a possibly mistyped insight id to a stored one
+///
+/// An exact match is used as-is. Otherwise the closest stored ids are offered: with
+/// `fuzzy` the best one is taken when it is a confident match, else it is offered
+/// on a terminal, and the lookup fails listing the suggestions when declined.
+pub async fn resolve_insight(topic: &str, name: &str, fuzzy: bool) -> Result<(String, String)> {
+  use std::io::IsTerminal;
+
+  ensure_server_running().await?;
+  let client = get_client();
+
+  let error = match client.get_insight(topic, name, true).await {
+    Ok(_) => return Ok((topic.to_string(), name.to_string())),
+    Err(e) if is_not_found(&e) => e,
+    Err(e) => return Err(e),
+  };
+
+  let insights = client.list_insights(None, &[]).await?.insights;
+  let ids = insights.iter().map(|insight| (insight.topic.as_str(), insight.name.as_str()));
+  let matches = fuzzy::closest(topic, name, ids, 3);
+  let Some(best) = matches.first() else {
+    return Err(error);
+  };
+
+  if fuzzy && best.score >= fuzzy::CONFIDENT_SCORE {
+    bentley::info!(&format!("using closest match {}/{}", best.topic, best.name));
+    return Ok((best.topic.clone(), best.name.clone()));
+  }
+
+  if std::io::stdin().is_terminal()
+    && confirm(&format!(
+      "Insight {}/{} not found. Did you mean {}/{}?",
+      topic,
+      name,
+      best.topic.cyan(),
+      best.name.yellow()
+    ))?
+  {
+    return Ok((best.topic.clone(), best.name.clone()));
+  }
+
+  let suggestions: Vec<String> =
+    matches.iter().map(|m| format!("{}/{}", m.topic, m.name)).collect();
+  Err(anyhow::Error::new(ApiFailure {
+    code: ErrorCode::NotFound,
+    message: format!("Insight {topic}/{name} not found. Did you mean {}?", suggestions.join(", ")),
+  }))
+}
+
+fn is_not_found(error: &anyhow::Error) -> bool {
+  error.downcast_ref::<ApiFailure>().is_some_and(|failure| failure.code == ErrorCode::NotFound)
+}
+
+/// Current overview and details of an insight, for editing
+pub async fn insight_content(topic: &str, name: &str) -> Result<(String, String)> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.get_insight(topic, name, false).await?;
+  Ok((response.insight.overview, response.insight.details))
+}
+
+pub async fn list_insights(filter: Option<&str>, tags: &[String], verbose: bool) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let insights = client.list_insights(filter, tags).await?.insights;
+
+  if insights.is_empty() {
+    match (filter, tags.is_empty()) {
+      (Some(topic), true) => println!("No insights found for topic: {}", topic.yellow()),
+      (_, false) => println!("No insights found tagged: {}", tags.join(", ").yellow()),
+      (None, true) => println!("No insights found."),
+    }
+    return Ok(());
+  }
+
+  // Group by topic for better display
+  use std::collections::BTreeMap;
+  let mut by_topic: BTreeMap<String, Vec<_>> = BTreeMap::new();
+  for insight in insights {
+    by_topic.entry(insight.topic.clone()).or_insert_with(Vec::new).push(insight);
+  }
+
+  for (topic, insights) in by_topic {
+    println!("{} {}", "📂".cyan(), topic.blue().bold());
+
+    for insight in insights {
+      let expiry = format_expiry(insight.expires_at, Utc::now());
+      let tags = format_tags(&insight.tags);
+      if verbose {
+        println!(
+          "  {} {}{} - {}{}",
+          "📄".yellow(),
+          insight.name.bold(),
+          tags,
+          insight.overview.dimmed(),
+          expiry
+        );
+      } else {
+        println!("  {} {}{}{}", "📄".yellow(), insight.name.bold(), tags, expiry);
+      }
+    }
+    println!();
+  }
+
+  Ok(())
+}
+
+/// Tags shown after an insight's name, if it has any
+fn format_tags(tags: &[String]) -> String {
+  if tags.is_empty() {
+    return String::new();
+  }
+  format!(" {}", tags.iter().map(|tag| format!("#{tag}")).collect::<Vec<_>>().join(" ").magenta())
+}
+
+/// Annotation for insights that have expired or will expire soon
+fn format_expiry(expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> String {
+  let Some(expiry) = expires_at else {
+    return String::new();
+  };
+
+  if expiry <= now {
+    return format!(" {}", "(expired)".red());
+  }
+
+  let days_left = (expiry - now).num_days();
+  if days_left < EXPIRY_WARNING_DAYS {
+    let label = match days_left {
+      0 => "(expires today)".to_string(),
+      1 => "(expires in 1 day)".to_string(),
+      n => format!("(expires in {n} days)"),
+    };
+    return format!(" {}", label.yellow());
+  }
+
+  String::new()
+}
+
+pub async fn list_topics() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.list_topics().await?;
+
+  if response.is_empty() {
+    println!("No topics found.");
+    return Ok(());
+  }
+
+  println!("{} Available topics:", "📂".cyan());
+  print_topic_tree(&topic::tree(&response));
+
+  Ok(())
+}
+
+/// List every tag in use with how many insights carry it
+pub async fn list_tags() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.list_tags().await?;
+
+  if response.tags.is_empty() {
+    println!("No tags found.");
+    return Ok(());
+  }
+
+  println!("{} Tags:", "🏷".cyan());
+  for entry in response.tags {
+    println!("  {} {}", format!("#{}", entry.tag).magenta(), format!("({})", entry.count).dimmed());
+  }
+
+  Ok(())
+}
+
+/// Print nested topics as a tree; parents without insights of their own are dimmed
+fn print_topic_tree(nodes: &[TopicNode]) {
+  for node in nodes {
+    println!("  {}", topic_label(node));
+    print_subtopics(&node.children, "  ");
+  }
+}
+
+fn print_subtopics(nodes: &[TopicNode], prefix: &str) {
+  for (index, node) in nodes.iter().enumerate() {
+    let last = index + 1 == nodes.len();
+    let (branch, continuation) =
+      if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+    println!("{prefix}{branch}{}", topic_label(node));
+    print_subtopics(&node.children, &format!("{prefix}{continuation}"));
+  }
+}
+
+fn topic_label(node: &TopicNode) -> ColoredString {
+  if node.has_insights {
+    node.name.blue()
+  } else {
+    node.name.dimmed()
+  }
+}
+
+pub async fn update_insight(
+  topic: &str,
+  name: &str,
+  overview: Option<&str>,
+  details: Option<&str>,
+  tags: Option<&[String]>,
+  allow_sensitive: bool,
+  strict: bool,
+) -> Result<()> {
+  if overview.is_none() && details.is_none() && tags.is_none() {
+    return Err(anyhow!(
+      "At least one of --overview, --details, --tag or --clear-tags must be specified"
+    ));
+  }
+
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let request = UpdateInsightRequest {
+    topic: topic.to_string(),
+    name: name.to_string(),
+    overview: overview.map(str::to_string),
+    details: details.map(str::to_string),
+    tags: tags.map(<[String]>::to_vec),
+    allow_sensitive,
+    strict,
+  };
+  let response = client.update_insight(&request).await?;
+
+  println!("{} Updated insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
+  print_lint_warnings(&response.warnings);
+  Ok(())
+}
+
+fn print_lint_warnings(warnings: &[LintFindingData]) {
+  for warning in warnings {
+    println!("{} {}: {}", "⚠".yellow(), warning.rule.yellow(), warning.message);
+  }
+}
+
+pub async fn delete_insight(topic: &str, name: &str, force: bool) -> Result<()> {
+  ensure_server_running().await?;
+  let client = get_client();
+
+  // First check if the insight exists
+  match client.get_insight(topic, name, true).await {
+    Ok(_) => {
+      // Insight exists, proceed with deletion
+      if !force {
+        // Ask for confirmation
+        let question =
+          format!("Are you sure you want to delete insight {}/{}?", topic.cyan(), name.yellow());
+        if !confirm(&question)? {
+          println!("Delete operation cancelled.");
+          return Ok(());
+        }
+      }
+
+      // Proceed with deletion
+      client.remove_insight(topic, name).await?;
+      println!("{} Deleted insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
+      Ok(())
+    }
+    Err(_) => {
+      // Insight doesn't exist
+      Err(anyhow!("Insight {}/{} not found", topic, name))
+    }
+  }
+}
+
+/// How often a running re-index is polled for progress
+const REINDEX_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+pub async fn index_insights(_force: bool, concurrency: Option<usize>, status: bool) -> Result<()> {
+  ensure_server_running().await?;
+  let client = get_client();
+
+  if status {
+    let status = client.reindex_status().await?;
+    if status.state != JobState::Running {
+      print_reindex_status(&status);
+      return Ok(());
+    }
+    return watch_reindex(&client).await;
+  }
+
+  match client.reindex_insights(concurrency).await {
+    Ok(status) => {
+      println!(
+        "Re-indexing started ({} insights per batch, {} batches at a time).",
+        status.batch_size, status.concurrency
+      );
+      println!("It keeps running on the server if you stop watching; check on it with `insights index --status`.");
+      watch_reindex(&client).await
+    }
+    Err(e) => {
+      println!("{} Failed to start re-indexing: {}", "✗".red(), e);
+      Err(e)
+    }
+  }
+}
+
+/// Poll the running re-index until it finishes, redrawing one progress line
+async fn watch_reindex(client: &InsightsClient) -> Result<()> {
+  loop {
+    tokio::time::sleep(REINDEX_POLL_INTERVAL).await;
+    let status = client.reindex_status().await?;
+    if status.state != JobState::Running {
+      println!();
+      print_reindex_status(&status);
+      return match status.state {
+        JobState::Failed => {
+          Err(anyhow!("Re-indexing failed: {}", status.error.as_deref().unwrap_or("unknown error")))
+        }
+        _ => Ok(()),
+      };
+    }
+
+    print!("\r\x1b[2K  {}", format_reindex_progress(&status));
+    std::io::Write::flush(&mut std::io::stdout())?;
+  }
+}
+
+/// `120/800 embedded (15%), 2 errors, ETA 1m 05s`
+fn format_reindex_progress(status: &ReindexStatusResponse) -> String {
+  if status.total == 0 {
+    return "Loading insights...".to_string();
+  }
+  let done = status.embedded + status.failed;
+  let mut line = format!("{}/{} embedded ({}%)", done, status.total, done * 100 / status.total);
+  if status.failed > 0 {
+    line.push_str(&format!(", {} errors", status.failed.to_string().red()));
+  }
+  if let Some(eta) = status.eta_secs {
+    line.push_str(&format!(
+      ", ETA {}",
+      reindex::format_duration(std::time::Duration::from_secs(eta))
+    ));
+  }
+  line
+}
+
+fn print_reindex_status(status: &ReindexStatusResponse) {
+  let elapsed = match (status.started_at, status.finished_at) {
+    (Some(started), Some(finished)) => (finished - started).to_std().ok(),
+    _ => None,
+  };
+  let took = elapsed.map_or(String::new(), |d| format!(" in {}", reindex::format_duration(d)));
+
+  match status.state {
+    JobState::Idle => println!("No re-index has run since the server started"),
+    JobState::Running => println!("{} {}", "Re-indexing:".bold(), format_reindex_progress(status)),
+    JobState::Completed => println!(
+      "{} Re-indexed {}/{} insights{took}, {} errors",
+      "✓".green(),
+      status.embedded,
+      status.total,
+      status.failed
+    ),
+    JobState::Failed => println!(
+      "{} Re-indexing failed after {}/{} insights: {}",
+      "✗".red(),
+      status.embedded + status.failed,
+      status.total,
+      status.error.as_deref().unwrap_or("unknown error")
+    ),
+  }
+}
+
+/// Parse a retention date given as YYYY-MM-DD (midnight UTC) or RFC 3339
+fn parse_retention_date(value: &str) -> Result<DateTime<Utc>> {
+  if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+    return Ok(date.and_hms_opt(0, 0, 0).expect("midnight is always valid").and_utc());
+  }
+
+  DateTime::parse_from_rfc3339(value)
+    .map(|date| date.with_timezone(&Utc))
+    .map_err(|_| anyhow!("Invalid date '{}': expected YYYY-MM-DD or RFC 3339", value))
+}
+
+/// Set the retention policy for a topic
+pub async fn set_retention(topic: &str, days: Option<u32>, until: Option<&str>) -> Result<()> {
+  if days.is_none() && until.is_none() {
+    return Err(anyhow!("At least one of --days or --until must be specified"));
+  }
+
+  let expire_on = until.map(parse_retention_date).transpose()?;
+
+  ensure_server_running().await?;
+  let client = get_client();
+  let policy = RetentionPolicyData { topic: topic.to_string(), expire_after_days: days, expire_on };
+  client.set_retention(&policy).await?;
+
+  println!("{} Set retention policy for {}", "✓".green(), topic.cyan());
+  Ok(())
+}
+
+/// List all retention policies
+pub async fn list_retention() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let policies = client.list_retention().await?;
+
+  if policies.is_empty() {
+    println!("No retention policies set.");
+    return Ok(());
+  }
+
+  println!("{} Retention policies:", "⏳".cyan());
+  for policy in policies {
+    let mut rules = Vec::new();
+    if let Some(days) = policy.expire_after_days {
+      rules.push(format!("{days} days after last update"));
+    }
+    if let Some(date) = policy.expire_on {
+      rules.push(format!("on {}", date.format("%Y-%m-%d")));
+    }
+    println!("  {} - expires {}", policy.topic.blue(), rules.join(" or "));
+  }
+
+  Ok(())
+}
+
+/// Remove the retention policy for a topic
+pub async fn remove_retention(topic: &str) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  client.remove_retention(topic).await?;
+
+  println!("{} Removed retention policy for {}", "✓".green(), topic.cyan());
+  Ok(())
+}
+
+/// Archive expired insights immediately
+pub async fn sweep_retention() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let report = client.sweep_retention().await?;
+
+  if report.archived.is_empty() {
+    println!("No expired insights to archive.");
+  } else {
+    println!("{} Archived {} expired insights:", "✓".green(), report.archived.len());
+    for id in &report.archived {
+      println!("  {}", id.yellow());
+    }
+  }
+
+  for error in &report.errors {
+    println!("{} {}", "✗".red(), error);
+  }
+
+  Ok(())
+}
+
+/// Back up insights and the vector database now
+pub async fn create_backup() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.create_backup().await?;
+
+  println!(
+    "{} Created backup {} ({})",
+    "✓".green(),
+    response.backup.id.cyan(),
+    format_size(response.backup.size_bytes)
+  );
+  if !response.pruned.is_empty() {
+    println!("  Pruned {} old backups: {}", response.pruned.len(), response.pruned.join(", "));
+  }
+  Ok(())
+}
+
+/// List backups, newest first
+pub async fn list_backups() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.list_backups().await?;
+
+  if response.backups.is_empty() {
+    println!("No backups in {}.", response.directory);
+    return Ok(());
+  }
+
+  println!("{} Backups in {}:", "🗄".cyan(), response.directory);
+  for backup in response.backups {
+    println!(
+      "  {}  {}  {}",
+      backup.id.cyan(),
+      backup.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+      format_size(backup.size_bytes).dimmed()
+    );
+  }
+  Ok(())
+}
+
+/// Replace the insights with a backup, keeping a backup of what was there
+pub async fn restore_backup(id: &str, force: bool) -> Result<()> {
+  ensure_server_running().await?;
+
+  if !force {
+    let question = format!(
+      "Replace all insights with backup {}? The current state is backed up first.",
+      id.cyan()
+    );
+    if !confirm(&question)? {
+      println!("Restore cancelled.");
+      return Ok(());
+    }
+  }
+
+  let client = get_client();
+  let response = client.restore_backup(id).await?;
+
+  println!("{} Restored {} insights from backup {}", "✓".green(), response.restored, id.cyan());
+  println!(
+    "  The previous state is in backup {}; restore it to undo.",
+    response.safety_backup.cyan()
+  );
+  println!("  Run `insights index --force` to recompute embeddings for semantic search.");
+  Ok(())
+}
+
+/// List workspaces with their insight counts
+pub async fn list_workspaces() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.list_workspaces().await?;
+
+  for workspace in &response.workspaces {
+    println!(
+      "{} {} ({} insights)",
+      workspace.name.cyan().bold(),
+      workspace.insights_root.dimmed(),
+      workspace.insights
+    );
+  }
+  Ok(())
+}
+
+/// Create an empty workspace
+pub async fn create_workspace(name: &str) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let workspace = client.create_workspace(name).await?;
+
+  println!("{} Created workspace {}", "✓".green(), workspace.name.cyan());
+  println!("  Use it with `insights --workspace {}` or INSIGHTS_WORKSPACE.", workspace.name);
+  Ok(())
+}
+
+/// Delete a workspace with its insights and embeddings
+pub async fn delete_workspace(name: &str, force: bool) -> Result<()> {
+  ensure_server_running().await?;
+
+  if !force && !confirm(&format!("Delete workspace {} and all of its insights?", name.cyan()))? {
+    println!("Deletion cancelled.");
+    return Ok(());
+  }
+
+  let client = get_client();
+  client.delete_workspace(name).await?;
+
+  println!("{} Deleted workspace {}", "✓".green(), name.cyan());
+  Ok(())
+}
+
+/// Show the resource limits and state of background indexing
+pub async fn indexing_status() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let status = client.indexing_status().await?;
+  print_indexing_status(&status);
+  Ok(())
+}
+
+/// Pause background indexing
+pub async fn pause_indexing() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let status = client.pause_indexing().await?;
+  println!("{} Background indexing paused", "✓".green());
+  print_indexing_status(&status);
+  Ok(())
+}
+
+/// Resume background indexing
+pub async fn resume_indexing() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let status = client.resume_indexing().await?;
+  println!("{} Background indexing resumed", "✓".green());
+  print_indexing_status(&status);
+  Ok(())
+}
+
+fn print_indexing_status(status: &IndexingStatusResponse) {
+  let state = match &status.waiting_on {
+    Some(reason) => format!("waiting ({reason})").yellow(),
+    None => "running".green(),
+  };
+  println!("  {} {}", "state:".bold(), state);
+
+  let threads = status.max_threads.map_or("all cores".to_string(), |n| n.to_string());
+  println!("  {} {}", "threads:".bold(), threads);
+  let nice = status.nice.map_or("unchanged".to_string(), |n| n.to_string());
+  println!("  {} {}", "nice:".bold(), nice);
+
+  let mut conditions = Vec::new();
+  if status.require_ac_power {
+    conditions.push("on AC power".to_string());
+  }
+  if status.require_idle {
+    conditions.push(format!("idle (load < {:.2} per core)", status.idle_load));
+  }
+  let schedule = if conditions.is_empty() { "always".to_string() } else { conditions.join(", ") };
+  println!("  {} {}", "schedule:".bold(), schedule);
+}
+
+/// Register a webhook for insight changes
+pub async fn add_webhook(
+  url: &str,
+  secret: &str,
+  topics: &[String],
+  events: &[WebhookEvent],
+) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let webhook = client.add_webhook(url, secret, topics, events).await?;
+
+  println!("{} Registered webhook {} for {}", "✓".green(), webhook.id.yellow(), url.cyan());
+  Ok(())
+}
+
+/// List registered webhooks
+pub async fn list_webhooks() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let webhooks = client.list_webhooks().await?;
+
+  if webhooks.is_empty() {
+    println!("No webhooks registered.");
+    return Ok(());
+  }
+
+  println!("{} Webhooks:", "🔔".cyan());
+  for webhook in webhooks {
+    let topics =
+      if webhook.topics.is_empty() { "all topics".to_string() } else { webhook.topics.join(", ") };
+    let events = if webhook.events.is_empty() {
+      "all events".to_string()
+    } else {
+      webhook.events.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    };
+    println!("  {} {} - {} ({})", webhook.id.yellow(), webhook.url.blue(), topics, events);
+  }
+
+  Ok(())
+}
+
+/// Remove a webhook
+pub async fn remove_webhook(id: &str) -> Result<()> {
   ensure_server_running().await?;
+
   let client = get_client();
-  client.add_insight(topic, name, overview, details).await?;
+  client.remove_webhook(id).await?;
 
-  println!("{} Added insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
+  println!("{} Removed webhook {}", "✓".green(), id.yellow());
   Ok(())
 }
 
-/// Get content of a specific insight
-pub async fn get_insight(topic: &str, name: &str, overview_only: bool) -> Result<()> {
+/// Mint an API token and print it once
+///
+/// Only the token's hash is kept, in the secrets vault, so a lost token can't
+/// be recovered; revoke it and mint another instead.
+pub fn mint_token(name: &str, scope: Scope) -> Result<()> {
+  let secrets = secrets::Secrets::new();
+  if secrets.get_secret_raw_no_setup(auth::TOKENS_GROUP, name).is_ok() {
+    return Err(anyhow!("A token named '{name}' already exists; revoke it first"));
+  }
+
+  let minted = auth::mint(scope);
+  secrets.store_secret_raw(auth::TOKENS_GROUP, name, &minted.entry)?;
+  auth::record_tokens_minted(true)?;
+
+  println!("{} Minted {} token {}", "✓".green(), scope, name.yellow());
+  println!("  {}", minted.token.bold());
+  println!("  Store it now (e.g. in INSIGHTS_API_TOKEN); it will not be shown again.");
+  Ok(())
+}
+
+/// Revoke an API token; the server stops accepting it within seconds
+pub fn revoke_token(name: &str) -> Result<()> {
+  let secrets = secrets::Secrets::new();
+  secrets
+    .get_secret_raw_no_setup(auth::TOKENS_GROUP, name)
+    .map_err(|_| anyhow!("No token named '{name}'"))?;
+  secrets.delete_secret(auth::TOKENS_GROUP, name)?;
+
+  println!("{} Revoked token {}", "✓".green(), name.yellow());
+  Ok(())
+}
+
+/// List minted API tokens by name and scope
+pub async fn list_tokens() -> Result<()> {
+  let table = auth::load_table()
+    .await
+    .map_err(|e| anyhow!("Failed to read tokens (is the secrets keeper running?): {e}"))?;
+
+  if table.is_empty() {
+    println!("No API tokens minted; the API is open to anyone on this host.");
+    return Ok(());
+  }
+
+  println!("{} API tokens:", "🔑".cyan());
+  for (name, scope) in table.entries() {
+    println!("  {} ({})", name.yellow(), scope);
+  }
+  Ok(())
+}
+
+/// Show the most used insights
+pub async fn stats(top: usize) -> Result<()> {
   ensure_server_running().await?;
 
   let client = get_client();
-  let response = client.get_insight(topic, name, overview_only).await?;
+  let response = client.stats(top).await?;
 
-  if overview_only {
-    println!("{}", response.insight.overview);
-  } else {
-    println!("---\n{}\n---\n\n{}", response.insight.overview, response.insight.details);
+  if response.insights.is_empty() {
+    println!("No insights have been read or found by a search yet.");
+    return Ok(());
+  }
+
+  println!("{} Most used insights ({} tracked):", "📈".cyan(), response.tracked);
+  for (rank, entry) in response.insights.iter().enumerate() {
+    println!(
+      "  {:>3}. {}/{} {}",
+      rank + 1,
+      entry.topic.cyan(),
+      entry.name.yellow(),
+      format!(
+        "({} reads, {} search hits, last used {})",
+        entry.reads,
+        entry.search_hits,
+        entry.last_accessed.format("%Y-%m-%d")
+      )
+      .dimmed()
+    );
   }
 
   Ok(())
 }
 
-pub async fn list_insights(filter: Option<&str>, verbose: bool) -> Result<()> {
+/// Show recent webhook deliveries
+pub async fn list_deliveries(webhook: Option<&str>, limit: usize) -> Result<()> {
   ensure_server_running().await?;
 
   let client = get_client();
-  let response = client.list_insights(Vec::new()).await?; // TODO: Add topic filtering
+  let deliveries = client.list_deliveries(webhook, limit).await?;
 
-  let insights = if let Some(topic_filter) = filter {
-    response
-      .insights
-      .into_iter()
-      .filter(|insight| insight.topic == topic_filter)
-      .collect::<Vec<_>>()
-  } else {
-    response.insights
-  };
+  if deliveries.is_empty() {
+    println!("No webhook deliveries yet.");
+    return Ok(());
+  }
 
-  if insights.is_empty() {
-    if let Some(topic) = filter {
-      println!("No insights found for topic: {}", topic.yellow());
-    } else {
-      println!("No insights found.");
+  for delivery in deliveries {
+    let mark = if delivery.success { "✓".green() } else { "✗".red() };
+    let status = delivery.status.map(|code| code.to_string()).unwrap_or_else(|| "-".to_string());
+    println!(
+      "{} {} {} {} {} → {} after {} attempt(s)",
+      mark,
+      delivery.finished_at.format("%Y-%m-%d %H:%M:%S"),
+      delivery.webhook_id.yellow(),
+      delivery.event,
+      delivery.insight.cyan(),
+      status,
+      delivery.attempts
+    );
+    if let Some(error) = &delivery.error {
+      println!("    {error}");
     }
+  }
+
+  Ok(())
+}
+
+/// Show the shard layout and the size of every shard
+pub async fn list_shards() -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.list_shards().await?;
+  let config = &response.config;
+
+  println!("{} Shard layout: {}", "🧩".cyan(), config.strategy.to_string().blue());
+  println!(
+    "  buckets: {}, max imbalance: {:.1}x, max shard rows: {}",
+    config.buckets, config.max_imbalance, config.max_shard_rows
+  );
+  for (topic, parts) in &config.split_topics {
+    println!("  topic {} split into {} buckets", topic.blue(), parts);
+  }
+
+  if response.shards.is_empty() {
+    println!("No shard tables yet.");
     return Ok(());
   }
 
-  // Group by topic for better display
-  use std::collections::BTreeMap;
-  let mut by_topic: BTreeMap<String, Vec<_>> = BTreeMap::new();
-  for insight in insights {
-    by_topic.entry(insight.topic.clone()).or_insert_with(Vec::new).push(insight);
+  for shard in &response.shards {
+    println!("  {} - {} rows", shard.table.yellow(), shard.rows);
   }
+  println!("  imbalance: {:.2}x", response.imbalance);
 
-  for (topic, insights) in by_topic {
-    println!("{} {}", "📂".cyan(), topic.blue().bold());
+  for reason in &response.rebalance_reasons {
+    println!("{} Rebalance recommended: {}", "⚠".yellow(), reason);
+  }
 
-    for insight in insights {
-      if verbose {
-        println!("  {} {} - {}", "📄".yellow(), insight.name.bold(), insight.overview.dimmed());
-      } else {
-        println!("  {} {}", "📄".yellow(), insight.name.bold());
+  Ok(())
+}
+
+/// Switch to a new shard layout, migrating existing embeddings
+pub async fn configure_shards(
+  strategy: ShardStrategy,
+  buckets: Option<u32>,
+  max_imbalance: Option<f32>,
+  max_shard_rows: Option<usize>,
+) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let request = ConfigureShardsRequest { strategy, buckets, max_imbalance, max_shard_rows };
+  let response = client.configure_shards(&request).await?;
+
+  println!("{} Shard layout set to {}", "✓".green(), strategy.to_string().cyan());
+  print_rebalance(&response);
+  Ok(())
+}
+
+/// Redistribute embeddings when shards have grown unevenly
+pub async fn rebalance_shards(force: bool) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.rebalance_shards(force).await?;
+
+  if !response.rebalanced {
+    println!("{} Shards are balanced, nothing to do.", "✓".green());
+    return Ok(());
+  }
+
+  println!("{} Rebalanced shards", "✓".green());
+  print_rebalance(&response);
+  Ok(())
+}
+
+fn print_rebalance(response: &RebalanceShardsResponse) {
+  for reason in &response.reasons {
+    println!("  reason: {reason}");
+  }
+  println!("  {} embeddings moved", response.moved);
+}
+
+/// Answer a question from the knowledge base, citing the insights used
+pub async fn ask(question: &str, topic: Option<String>, limit: Option<usize>) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.ask(question, topic, limit).await?;
+
+  if response.sources.is_empty() {
+    println!("No relevant insights found.");
+    return Ok(());
+  }
+
+  match &response.answer {
+    Some(answer) => {
+      println!("{answer}");
+      println!();
+      println!("{}", "Sources:".bold());
+    }
+    None => {
+      if let Some(note) = &response.note {
+        println!("{}", note.dimmed());
+      }
+      println!("{}", "Most relevant insights:".bold());
+    }
+  }
+
+  for source in &response.sources {
+    // Without an answer every source is shown; with one, uncited sources are dimmed
+    let id = format!("{}/{}", source.topic, source.name);
+    let id = if response.answer.is_none() || source.cited { id.cyan() } else { id.dimmed() };
+    println!("  [{}] {} ({:.2})", source.index, id, source.score);
+    if response.answer.is_none() {
+      for line in source.excerpt.lines() {
+        println!("      {line}");
       }
     }
+  }
+
+  Ok(())
+}
+
+/// Outline a topic as clusters, expanding all of them or a single one on request
+pub async fn summarize_topic(
+  topic: &str,
+  expand: bool,
+  cluster: Option<usize>,
+  verbose: bool,
+  max_clusters: Option<usize>,
+) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let summary = client.summarize_topic(topic, max_clusters).await?;
+
+  if let Some(number) = cluster {
+    if number == 0 || number > summary.clusters.len() {
+      return Err(anyhow!(
+        "Cluster {number} does not exist; {} has {} cluster(s)",
+        topic,
+        summary.clusters.len()
+      ));
+    }
+  }
+
+  println!(
+    "{} {} - {} insights in {} clusters (by {})",
+    "🗂".cyan(),
+    summary.topic.blue().bold(),
+    summary.total,
+    summary.clusters.len(),
+    summary.method
+  );
+
+  for (index, group) in summary.clusters.iter().enumerate() {
+    let number = index + 1;
+    let expanded = expand || cluster == Some(number);
+    let marker = if expanded { "▾" } else { "▸" };
+    println!("  {marker} {number}. {} ({})", group.label.yellow(), group.insights.len());
+    if expanded {
+      print_cluster_insights(&group.insights, verbose);
+    }
+  }
+
+  if !summary.unclustered.is_empty() {
+    let marker = if expand { "▾" } else { "▸" };
+    println!("  {marker} {} ({})", "other".dimmed(), summary.unclustered.len());
+    if expand {
+      print_cluster_insights(&summary.unclustered, verbose);
+    }
+  }
+
+  if !expand && cluster.is_none() && !summary.clusters.is_empty() {
+    println!("{}", "Use --cluster <N> or --expand to see the insights.".dimmed());
+  }
+
+  Ok(())
+}
+
+/// Find near-identical insights and merge each cluster into one insight
+///
+/// Every cluster is merged into the insight chosen at the prompt, or into its
+/// oldest insight with `auto_merge`. The kept insight gains the details and tags
+/// the duplicates add, and the duplicates are deleted.
+pub async fn dedupe(topic: Option<&str>, threshold: Option<f32>, auto_merge: bool) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.find_duplicates(topic, threshold).await?;
+
+  if response.clusters.is_empty() {
+    println!(
+      "No duplicates among {} insights (similarity ≥ {:.2} by {}).",
+      response.scanned, response.threshold, response.method
+    );
+    return Ok(());
+  }
+
+  println!(
+    "{} {} cluster(s) of duplicates among {} insights (similarity ≥ {:.2} by {})",
+    "🧬".cyan(),
+    response.clusters.len(),
+    response.scanned,
+    response.threshold,
+    response.method
+  );
+
+  let mut merged = 0;
+  for (index, cluster) in response.clusters.iter().enumerate() {
     println!();
+    println!("  {} ({:.2})", format!("Cluster {}", index + 1).bold(), cluster.similarity);
+    for (number, insight) in cluster.insights.iter().enumerate() {
+      println!(
+        "    {}. {}/{} - {}",
+        number + 1,
+        insight.topic.blue(),
+        insight.name.yellow(),
+        insight.overview.dimmed()
+      );
+    }
+
+    let keep = if auto_merge { Some(0) } else { ask_which_to_keep(cluster.insights.len())? };
+    match keep {
+      Some(keep) => {
+        merge_duplicates(&client, &cluster.insights, keep).await?;
+        merged += 1;
+      }
+      None => println!("    Skipped."),
+    }
   }
 
+  println!();
+  println!("{} Merged {} of {} cluster(s)", "✓".green(), merged, response.clusters.len());
   Ok(())
 }
 
-pub async fn list_topics() -> Result<()> {
+/// Prompt for the insight a cluster is merged into; None skips the cluster
+fn ask_which_to_keep(count: usize) -> Result<Option<usize>> {
+  loop {
+    print!("    Merge into which insight? [1-{count}, Enter for 1, s to skip]: ");
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    match input.trim().to_lowercase().as_str() {
+      "" => return Ok(Some(0)),
+      "s" | "skip" => return Ok(None),
+      choice => match choice.parse::<usize>() {
+        Ok(number) if (1..=count).contains(&number) => return Ok(Some(number - 1)),
+        _ => println!("    Enter a number from 1 to {count}, or s to skip."),
+      },
+    }
+  }
+}
+
+/// Fold every other insight of a cluster into the kept one, then delete them
+async fn merge_duplicates(
+  client: &InsightsClient,
+  cluster: &[DuplicateInsightData],
+  keep: usize,
+) -> Result<()> {
+  let kept = client.get_insight(&cluster[keep].topic, &cluster[keep].name, false).await?.insight;
+  let mut duplicates = Vec::new();
+  for (index, member) in cluster.iter().enumerate() {
+    if index != keep {
+      duplicates.push(client.get_insight(&member.topic, &member.name, false).await?.insight);
+    }
+  }
+
+  let details: Vec<&str> = duplicates.iter().map(|d| d.details.as_str()).collect();
+  let details = dedupe::merge_details(&kept.details, &details);
+  let mut tags = kept.tags.clone();
+  tags.extend(duplicates.iter().flat_map(|d| d.tags.iter().cloned()));
+  let tags = tag::normalize_all(&tags);
+
+  let request = UpdateInsightRequest {
+    topic: kept.topic.clone(),
+    name: kept.name.clone(),
+    overview: None,
+    details: (details != kept.details).then_some(details),
+    tags: (tags != kept.tags).then_some(tags),
+    allow_sensitive: false,
+    strict: false,
+  };
+  if request.details.is_some() || request.tags.is_some() {
+    client.update_insight(&request).await?;
+  }
+
+  for duplicate in &duplicates {
+    client.remove_insight(&duplicate.topic, &duplicate.name).await?;
+  }
+  println!(
+    "    {} Merged {} duplicate(s) into {}/{}",
+    "✓".green(),
+    duplicates.len(),
+    kept.topic.cyan(),
+    kept.name.yellow()
+  );
+  Ok(())
+}
+
+/// Show the insights most similar to a stored insight
+pub async fn related_insights(topic: &str, name: &str, limit: Option<usize>) -> Result<()> {
   ensure_server_running().await?;
 
   let client = get_client();
-  let response = client.list_topics().await?;
+  let response = client.related_insights(topic, name, limit).await?;
 
-  if response.is_empty() {
-    println!("No topics found.");
+  if response.related.is_empty() {
+    println!(
+      "No insights related to {}/{} (similarity ≥ {:.2}).",
+      response.topic.blue(),
+      response.name.yellow(),
+      response.threshold
+    );
     return Ok(());
   }
 
-  println!("{} Available topics:", "📂".cyan());
-  for topic in response {
-    println!("  {}", topic.blue());
+  println!("{} Related to {}/{}:", "🔗".cyan(), response.topic.blue(), response.name.yellow());
+  for related in &response.related {
+    println!(
+      "  {}/{} ({:.2}) - {}",
+      related.topic.blue(),
+      related.name.yellow(),
+      related.similarity,
+      related.overview.dimmed()
+    );
   }
 
   Ok(())
 }
 
-pub async fn update_insight(
+/// List the recorded versions of an insight
+pub async fn history(topic: &str, name: &str) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.history(topic, name).await?;
+
+  println!("{} {}/{}", "🕘".cyan(), response.topic.blue(), response.name.yellow());
+  let latest = response.versions.last().map(|version| version.version);
+  for version in &response.versions {
+    let current = if Some(version.version) == latest {
+      " (current)".green().to_string()
+    } else {
+      String::new()
+    };
+    println!(
+      "  {} {}{}  {}",
+      format!("v{}", version.version).bold(),
+      version.recorded_at.format("%Y-%m-%d %H:%M"),
+      current,
+      version.overview.dimmed()
+    );
+  }
+
+  Ok(())
+}
+
+/// Show what changed between two versions of an insight
+pub async fn diff_versions(
   topic: &str,
   name: &str,
-  overview: Option<&str>,
-  details: Option<&str>,
+  from: Option<u32>,
+  to: Option<u32>,
 ) -> Result<()> {
-  if overview.is_none() && details.is_none() {
-    return Err(anyhow!("At least one of --overview or --details must be specified"));
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.diff_versions(topic, name, from, to).await?;
+
+  if response.diff.is_empty() {
+    println!("v{} and v{} are identical", response.from, response.to);
+    return Ok(());
+  }
+
+  for line in response.diff.lines() {
+    if line.starts_with("+++") || line.starts_with("---") {
+      println!("{}", line.bold());
+    } else if line.starts_with('+') {
+      println!("{}", line.green());
+    } else if line.starts_with('-') {
+      println!("{}", line.red());
+    } else if line.starts_with("@@") {
+      println!("{}", line.cyan());
+    } else {
+      println!("{line}");
+    }
   }
 
+  Ok(())
+}
+
+/// Restore an earlier version of an insight
+pub async fn rollback(topic: &str, name: &str, to: u32) -> Result<()> {
   ensure_server_running().await?;
 
   let client = get_client();
-  client.update_insight(topic, name, overview, details).await?;
+  let response = client.rollback(topic, name, to).await?;
+
+  println!(
+    "{} Rolled back {}/{} to v{} (saved as v{})",
+    "✓".green(),
+    topic.blue(),
+    name.yellow(),
+    response.restored,
+    response.version
+  );
 
-  println!("{} Updated insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
   Ok(())
 }
 
-pub async fn delete_insight(topic: &str, name: &str, force: bool) -> Result<()> {
+fn print_cluster_insights(insights: &[ClusterInsightData], verbose: bool) {
+  for insight in insights {
+    if verbose {
+      println!("      • {}: {}", insight.name.cyan(), insight.overview);
+    } else {
+      println!("      • {}", insight.name.cyan());
+    }
+  }
+}
+
+/// Audit the knowledge base for secrets and personal data
+pub async fn scan_insights() -> Result<()> {
   ensure_server_running().await?;
-  let client = get_client();
 
-  // First check if the insight exists
-  match client.get_insight(topic, name, true).await {
-    Ok(_) => {
-      // Insight exists, proceed with deletion
-      if !force {
-        // Ask for confirmation
-        print!(
-          "Are you sure you want to delete insight {}/{}? (y/N): ",
-          topic.cyan(),
-          name.yellow()
-        );
-        std::io::Write::flush(&mut std::io::stdout())?;
+  let client = get_client();
+  let response = client.scan_insights().await?;
 
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
+  if response.findings.is_empty() {
+    println!("{} No sensitive content found.", "✓".green());
+    return Ok(());
+  }
 
-        let response = input.trim().to_lowercase();
-        if response != "y" && response != "yes" {
-          println!("Delete operation cancelled.");
-          return Ok(());
-        }
-      }
+  for finding in &response.findings {
+    println!(
+      "{} {}/{} line {}: {} ({})",
+      "✗".red(),
+      finding.topic.cyan(),
+      finding.name.yellow(),
+      finding.line,
+      finding.kind,
+      finding.excerpt.dimmed()
+    );
+  }
 
-      // Proceed with deletion
-      client.remove_insight(topic, name).await?;
-      println!("{} Deleted insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
-      Ok(())
-    }
-    Err(_) => {
-      // Insight doesn't exist
-      Err(anyhow!("Insight {}/{} not found", topic, name))
+  Err(
+    ApiFailure {
+      code: ErrorCode::SensitiveContent,
+      message: format!("Found {} sensitive match(es) in stored insights", response.findings.len()),
     }
+    .into(),
+  )
+}
+
+/// Check the knowledge base against the content lint rules
+pub async fn lint_insights(topic: Option<&str>, strict: bool) -> Result<()> {
+  ensure_server_running().await?;
+
+  let client = get_client();
+  let response = client.lint_insights(topic).await?;
+
+  if response.findings.is_empty() {
+    println!("{} No lint issues found.", "✓".green());
+    return Ok(());
   }
+
+  let marker = if strict { "✗".red() } else { "⚠".yellow() };
+  for finding in &response.findings {
+    println!(
+      "{} {}/{} {}: {}",
+      marker,
+      finding.topic.cyan(),
+      finding.name.yellow(),
+      finding.rule.dimmed(),
+      finding.message
+    );
+  }
+
+  if !strict {
+    return Ok(());
+  }
+
+  Err(
+    ApiFailure {
+      code: ErrorCode::ValidationFailed,
+      message: format!("Found {} lint issue(s) in stored insights", response.findings.len()),
+    }
+    .into(),
+  )
 }
 
-pub async fn index_insights(_force: bool) -> Result<()> {
+/// Check stored frontmatter against the insight schema, repairing it with `fix`
+pub async fn validate_insights(topic: Option<&str>, fix: bool) -> Result<()> {
   ensure_server_running().await?;
+
   let client = get_client();
+  let response = client.validate_insights(topic, fix).await?;
 
-  match client.reindex_insights().await {
-    Ok(()) => {
-      println!("Re-indexing started. This will run in the background and may take some time.");
-      println!("Check server logs for progress updates.");
-      Ok(())
+  if response.results.is_empty() {
+    println!("{} All insights fit the schema.", "✓".green());
+    return Ok(());
+  }
+
+  for result in &response.results {
+    let marker = if result.fixed { "✓".green() } else { "✗".red() };
+    let status = if result.fixed { " (fixed)".green() } else { "".normal() };
+    println!("{} {}/{}{}", marker, result.topic.cyan(), result.name.yellow(), status);
+    for problem in &result.problems {
+      let hint = if problem.fixable || result.fixed { "" } else { " (fix by hand)" };
+      println!("    {}: {}{}", problem.field.dimmed(), problem.message, hint.dimmed());
     }
-    Err(e) => {
-      println!("{} Failed to start re-indexing: {}", "✗".red(), e);
-      Err(e)
+  }
+
+  let broken = response.results.iter().filter(|result| !result.fixed).count();
+  if broken == 0 {
+    return Ok(());
+  }
+  if !fix && response.results.iter().any(|result| result.problems.iter().all(|p| p.fixable)) {
+    println!("\nRun `insights validate --fix` to repair the fixable ones.");
+  }
+
+  Err(
+    ApiFailure {
+      code: ErrorCode::ValidationFailed,
+      message: format!("{broken} insight(s) do not fit the schema"),
     }
+    .into(),
+  )
+}
+
+/// Run the REST server in the foreground, or an MCP server on stdio
+#[cfg(not(tarpaulin_include))] // Skip coverage - long-running server
+pub async fn serve(mcp: bool, bind: std::net::SocketAddr, watch: bool, ui: bool) -> Result<()> {
+  if mcp {
+    // The MCP server is a client of the REST server, started on first use
+    let server = crate::cli::mcp::McpServer::new(get_client()).with_auto_start();
+    return crate::cli::mcp::run(server).await;
   }
+
+  crate::server::startup::start_server(bind, watch, ui).await
 }
 
 /// Query daemon logs for debugging and monitoring
@@ -290,22 +1722,21 @@ fn format_duration(duration: f64) -> colored::ColoredString {
 }
 
 /// Search through all insights for matching content
-pub async fn search_insights(
-  terms: &[String],
-  topic: Option<String>,
-  case_sensitive: bool,
-  overview_only: bool,
-  exact: bool,
-  semantic: bool,
-) -> Result<()> {
+pub async fn search_insights(terms: &[String], options: &SearchCommandOptions) -> Result<()> {
   ensure_server_running().await?;
 
+  // The shell already removed the quotes around phrases, so put them back
+  let query = query::quote_args(terms);
   let client = get_client();
-  let response = client
-    .search_insights(terms.to_vec(), topic, case_sensitive, overview_only, exact, semantic)
-    .await?;
+  let response = client.search_insights(query.clone(), options).await?;
+
+  if response.results.is_empty() {
+    println!("No matches found for: {}", terms.join(" ").yellow());
+    return Ok(());
+  }
 
-  display_search_results(&response.results, terms, overview_only);
+  let highlighted = Query::parse(&query.join(" ")).map(|parsed| parsed.terms).unwrap_or_default();
+  display_search_results(&response.results, &highlighted, options.overview_only);
 
   Ok(())
 }
@@ -320,6 +1751,8 @@ fn display_search_results(
     println!("No matches found for: {}", terms.join(" ").yellow());
   } else {
     for result in results {
+      let mut notes: Vec<String> = result.explanation.iter().flat_map(format_explanation).collect();
+      notes.extend(result.ranking.map(|ranking| format_score_breakdown(result.score, &ranking)));
       display_search_result(
         &result.topic,
         &result.name,
@@ -327,6 +1760,7 @@ fn display_search_results(
         &result.details,
         terms,
         overview_only,
+        &notes,
       );
     }
   }
@@ -2,6 +2,8 @@
 
 use colored::*;
 
+use crate::server::types::{MatchPath, ScoreBreakdown, SearchExplanation};
+
 /// Highlight search terms in text
 pub fn highlight_keywords(text: &str, terms: &[String]) -> String {
   let mut result = text.to_string();
@@ -81,6 +83,7 @@ pub fn display_search_result(
   details: &str,
   terms: &[String],
   overview_only: bool,
+  notes: &[String],
 ) {
   let header = format!("=== {}/{} ===", topic.blue().bold(), name.yellow().bold());
 
@@ -97,5 +100,43 @@ pub fn display_search_result(
   for line in wrapped_lines {
     println!("{line}");
   }
+  for note in notes {
+    println!("{}", note.dimmed());
+  }
   println!();
 }
+
+/// Why a hit matched: the retrieval path, its scores before fusion and the matched terms
+pub fn format_explanation(explanation: &SearchExplanation) -> Vec<String> {
+  let scores: Vec<String> =
+    [("keyword", explanation.keyword_score), ("semantic", explanation.semantic_score)]
+      .into_iter()
+      .filter_map(|(path, score)| score.map(|score| format!("{path} {score:.2}")))
+      .collect();
+  let mut lines = vec![match explanation.matched_by {
+    MatchPath::Both => format!("matched by both paths ({} before fusion)", scores.join(", ")),
+    path => format!("matched by {path} ({})", scores.join(", ")),
+  }];
+  for found in &explanation.matches {
+    lines.push(format!(
+      "  {} {}..{} \"{}\": {}",
+      found.field, found.start, found.end, found.term, found.snippet
+    ));
+  }
+  lines
+}
+
+/// How a ranked score was put together, e.g. `score 1.08 = similarity 0.90 × recency 1.20`
+pub fn format_score_breakdown(score: f32, breakdown: &ScoreBreakdown) -> String {
+  let mut line = format!("score {score:.2} = similarity {:.2}", breakdown.similarity);
+  for (label, factor) in [
+    ("recency", breakdown.recency),
+    ("topic boost", breakdown.topic_boost),
+    ("popularity", breakdown.popularity),
+  ] {
+    if factor != 1.0 {
+      line.push_str(&format!(" × {label} {factor:.2}"));
+    }
+  }
+  line
+}
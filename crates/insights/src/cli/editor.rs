@@ -0,0 +1,171 @@
+//! Writing insight content from stdin or in an editor
+//!
+//! Long overviews and details are awkward as shell arguments. `-` in place of either
+//! reads it from stdin, and `--edit` opens a buffer in `$VISUAL` or `$EDITOR` (`vi`
+//! when neither is set). A buffer holds the overview, a line of dashes, then the
+//! details; `insights add topic name -` reads a whole buffer in that layout from stdin.
+
+use anyhow::{anyhow, Context, Result};
+use std::io::Read;
+use std::path::Path;
+
+/// Argument that stands for stdin
+pub const STDIN: &str = "-";
+
+const SEPARATOR: &str = "---";
+const HINT: &str =
+  "<!-- Overview first, then the details below the line of dashes. This line is removed. -->";
+
+/// Buffer an editor opens with
+pub fn render(overview: &str, details: &str) -> String {
+  format!("{HINT}\n{overview}\n{SEPARATOR}\n{details}\n")
+}
+
+/// Overview and details from a buffer
+pub fn parse(buffer: &str) -> Result<(String, String)> {
+  let body = buffer.trim_start().strip_prefix(HINT).unwrap_or(buffer);
+  let (overview, details) = body
+    .split_once(&format!("\n{SEPARATOR}\n"))
+    .or_else(|| body.strip_suffix(&format!("\n{SEPARATOR}")).map(|overview| (overview, "")))
+    .ok_or_else(|| anyhow!("missing the '{SEPARATOR}' line between overview and details"))?;
+  Ok((overview.trim().to_string(), details.trim().to_string()))
+}
+
+/// Overview and details for a new insight, from arguments, stdin or an editor
+pub fn for_add(
+  overview: Option<String>,
+  details: Option<String>,
+  edit: bool,
+) -> Result<(String, String)> {
+  let (overview, details) = match (overview, details) {
+    (Some(overview), None) if overview == STDIN => {
+      let (overview, details) = parse(&read_stdin()?)?;
+      (Some(overview), Some(details))
+    }
+    (overview, details) => read_args(overview, details)?,
+  };
+
+  let (overview, details) = if edit {
+    open(overview.as_deref().unwrap_or_default(), details.as_deref().unwrap_or_default())?
+  } else {
+    match (overview, details) {
+      (Some(overview), Some(details)) => (overview, details),
+      _ => {
+        return Err(anyhow!(
+          "give the overview and details, '-' to read them from stdin, or --edit to write them"
+        ))
+      }
+    }
+  };
+
+  if overview.is_empty() {
+    return Err(anyhow!("overview is empty; insight not saved"));
+  }
+  Ok((overview, details))
+}
+
+/// Changed overview and details for an update; `current` is what the editor starts from
+pub fn for_update(
+  overview: Option<String>,
+  details: Option<String>,
+  current: Option<(String, String)>,
+) -> Result<(Option<String>, Option<String>)> {
+  let (overview, details) = read_args(overview, details)?;
+  let Some((current_overview, current_details)) = current else {
+    return Ok((overview, details));
+  };
+
+  let (overview, details) = open(
+    overview.as_deref().unwrap_or(&current_overview),
+    details.as_deref().unwrap_or(&current_details),
+  )?;
+  if overview.is_empty() {
+    return Err(anyhow!("overview is empty; insight not saved"));
+  }
+  Ok((Some(overview), Some(details)))
+}
+
+/// Read whichever argument is `-` from stdin
+fn read_args(
+  overview: Option<String>,
+  details: Option<String>,
+) -> Result<(Option<String>, Option<String>)> {
+  let from_stdin = |value: &Option<String>| value.as_deref() == Some(STDIN);
+  match (from_stdin(&overview), from_stdin(&details)) {
+    (true, true) => Err(anyhow!("only one of overview and details can be read from stdin")),
+    (true, false) => Ok((Some(read_stdin()?.trim().to_string()), details)),
+    (false, true) => Ok((overview, Some(read_stdin()?.trim().to_string()))),
+    (false, false) => Ok((overview, details)),
+  }
+}
+
+fn read_stdin() -> Result<String> {
+  let mut content = String::new();
+  std::io::stdin().read_to_string(&mut content).context("failed to read stdin")?;
+  Ok(content)
+}
+
+/// Let the user write the content in their editor, starting from the given text
+pub fn open(overview: &str, details: &str) -> Result<(String, String)> {
+  let path = std::env::temp_dir().join(format!("insight-{}.md", uuid::Uuid::new_v4()));
+  std::fs::write(&path, render(overview, details))?;
+
+  let edited = run_editor(&path).and_then(|()| Ok(std::fs::read_to_string(&path)?));
+  let _ = std::fs::remove_file(&path);
+  parse(&edited?)
+}
+
+/// Get the configured editor
+/// Default: vi
+/// Environment: VISUAL, then EDITOR
+fn editor() -> String {
+  ["VISUAL", "EDITOR"]
+    .iter()
+    .filter_map(|var| std::env::var(var).ok())
+    .find(|editor| !editor.trim().is_empty())
+    .unwrap_or_else(|| "vi".to_string())
+}
+
+fn run_editor(path: &Path) -> Result<()> {
+  let editor = editor();
+  // Editors are often configured with arguments, e.g. `code --wait`
+  let mut words = editor.split_whitespace();
+  let program = words.next().unwrap_or("vi");
+  let status = std::process::Command::new(program)
+    .args(words)
+    .arg(path)
+    .status()
+    .with_context(|| format!("failed to start editor '{editor}'"))?;
+  if !status.success() {
+    return Err(anyhow!("editor exited with {status}; insight not saved"));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_reads_rendered_buffer() {
+    let buffer = render("Short summary", "# Heading\n\nBody\n---\nafter a rule");
+    let (overview, details) = parse(&buffer).unwrap();
+    assert_eq!(overview, "Short summary");
+    assert_eq!(details, "# Heading\n\nBody\n---\nafter a rule");
+
+    assert_eq!(parse("Only overview\n---").unwrap(), ("Only overview".into(), String::new()));
+    assert!(parse("no separator here").is_err());
+  }
+
+  #[test]
+  fn test_for_add_needs_content() {
+    let (overview, details) =
+      for_add(Some("Overview".into()), Some("Details".into()), false).unwrap();
+    assert_eq!((overview.as_str(), details.as_str()), ("Overview", "Details"));
+
+    let missing = for_add(Some("Overview".into()), None, false).unwrap_err();
+    assert!(missing.to_string().contains("--edit"), "{missing}");
+    assert!(for_add(Some(STDIN.into()), Some(STDIN.into()), false).is_err());
+    assert!(for_update(Some(STDIN.into()), Some(STDIN.into()), None).is_err());
+  }
+}
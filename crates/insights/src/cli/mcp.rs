@@ -0,0 +1,501 @@
+//! MCP (Model Context Protocol) server mode
+//!
+//! `insights serve --mcp` speaks JSON-RPC 2.0 over stdio, one message per line,
+//! and exposes the core insight operations as MCP tools. Tools go through the
+//! REST server like every other CLI command, so the knowledge base keeps a single
+//! writer. Logs go to stderr; stdout only ever carries protocol messages.
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::cli::client::InsightsClient;
+use crate::cli::server_manager::ensure_server_running;
+use crate::server::services::search::{SearchCommandOptions, SearchMode};
+use crate::server::types::{AddInsightRequest, UpdateInsightRequest, WriteInsightResponse};
+
+/// Newest protocol revision we speak, offered when the client asks for one we don't know
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+const SUPPORTED_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Search results returned when the caller does not ask for a number
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+// JSON-RPC error codes
+// ====================
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// A JSON-RPC error returned to the client
+#[derive(Debug, PartialEq)]
+pub struct RpcError {
+  pub code: i64,
+  pub message: String,
+}
+
+impl RpcError {
+  fn new(code: i64, message: impl Into<String>) -> Self {
+    Self { code, message: message.into() }
+  }
+}
+
+/// Incoming request or notification
+#[derive(Debug, Deserialize)]
+struct RpcMessage {
+  /// Absent for notifications, which never get a response
+  #[serde(default)]
+  id: Option<Value>,
+  method: Option<String>,
+  #[serde(default)]
+  params: Value,
+}
+
+// Tool Arguments
+// ==============
+
+/// Store a new insight
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AddArgs {
+  /// Topic category of the insight
+  topic: String,
+  /// Name of the insight, unique within its topic
+  name: String,
+  /// Brief overview/summary of the insight
+  overview: String,
+  /// Detailed content of the insight
+  details: String,
+  /// Labels for the insight, such as "security" or "how-to"
+  #[serde(default)]
+  tags: Vec<String>,
+  /// Store the insight even if it appears to contain secrets or personal data
+  #[serde(default)]
+  allow_sensitive: bool,
+  /// Reject the insight if it fails lint checks instead of warning
+  #[serde(default)]
+  strict: bool,
+}
+
+/// Search the knowledge base
+#[derive(Debug, Deserialize, JsonSchema)]
+struct SearchArgs {
+  /// Search terms (space-separated); also accepts filters such as `tag:async`,
+  /// `"exact phrase"`, `-excluded` and `before:2024-06`
+  query: String,
+  /// Only search this topic
+  #[serde(default)]
+  topic: Option<String>,
+  /// Only return insights carrying every one of these tags
+  #[serde(default)]
+  tags: Vec<String>,
+  /// Search backend: full-text index, embeddings, or both (default: hybrid)
+  #[serde(default)]
+  mode: Option<SearchMode>,
+  /// Maximum number of results (default: 10)
+  #[serde(default)]
+  limit: Option<usize>,
+}
+
+/// Identifies one insight
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetArgs {
+  /// Topic category of the insight
+  topic: String,
+  /// Name of the insight
+  name: String,
+  /// Return only the overview section
+  #[serde(default)]
+  overview_only: bool,
+}
+
+/// List stored insights
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ListArgs {
+  /// Only list insights in this topic or nested under it
+  #[serde(default)]
+  topic: Option<String>,
+  /// Only list insights carrying every one of these tags
+  #[serde(default)]
+  tags: Vec<String>,
+}
+
+/// Change an existing insight
+#[derive(Debug, Deserialize, JsonSchema)]
+struct UpdateArgs {
+  /// Topic category of the insight
+  topic: String,
+  /// Name of the insight
+  name: String,
+  /// New overview content
+  #[serde(default)]
+  overview: Option<String>,
+  /// New details content
+  #[serde(default)]
+  details: Option<String>,
+  /// Replacement tags; an empty list removes every tag
+  #[serde(default)]
+  tags: Option<Vec<String>>,
+  /// Store the update even if it appears to contain secrets or personal data
+  #[serde(default)]
+  allow_sensitive: bool,
+  /// Reject the update if it fails lint checks instead of warning
+  #[serde(default)]
+  strict: bool,
+}
+
+/// Identifies the insight to delete
+#[derive(Debug, Deserialize, JsonSchema)]
+struct DeleteArgs {
+  /// Topic category of the insight
+  topic: String,
+  /// Name of the insight
+  name: String,
+}
+
+/// Tool definition advertised by `tools/list`
+fn tool<T: JsonSchema>(name: &str, description: &str) -> Value {
+  let mut schema = serde_json::to_value(schemars::schema_for!(T)).unwrap_or_else(|_| json!({}));
+  if let Some(schema) = schema.as_object_mut() {
+    // Clients validate against their own draft; the argument shape is all they need
+    schema.remove("$schema");
+    schema.remove("title");
+  }
+  json!({ "name": name, "description": description, "inputSchema": schema })
+}
+
+fn tools() -> Vec<Value> {
+  vec![
+    tool::<AddArgs>("add_insight", "Store a new insight in the knowledge base"),
+    tool::<SearchArgs>(
+      "search_insights",
+      "Search insights by keywords and meaning; returns the best matches first",
+    ),
+    tool::<GetArgs>("get_insight", "Read one insight in full"),
+    tool::<ListArgs>("list_insights", "List stored insights with their overviews"),
+    tool::<UpdateArgs>("update_insight", "Replace the overview, details and/or tags of an insight"),
+    tool::<DeleteArgs>("delete_insight", "Delete an insight"),
+  ]
+}
+
+fn parse_args<T: DeserializeOwned>(arguments: Value) -> Result<T, RpcError> {
+  // Tools without required arguments may be called with no arguments at all
+  let arguments = if arguments.is_null() { json!({}) } else { arguments };
+  serde_json::from_value(arguments)
+    .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid arguments: {e}")))
+}
+
+fn to_text<T: serde::Serialize>(value: &T) -> Result<String> {
+  Ok(serde_json::to_string_pretty(value)?)
+}
+
+/// Append lint warnings so the agent can fix the insight it just wrote
+fn with_warnings(message: String, response: &WriteInsightResponse) -> String {
+  response.warnings.iter().fold(message, |text, warning| {
+    format!("{text}\nWarning ({}): {}", warning.rule, warning.message)
+  })
+}
+
+/// Answers MCP requests using the insights REST API
+pub struct McpServer {
+  client: InsightsClient,
+  /// Start the local server before the first tool call
+  auto_start: bool,
+  started: bool,
+}
+
+impl McpServer {
+  /// Serve requests through `client`, which must point at a running server
+  pub fn new(client: InsightsClient) -> Self {
+    Self { client, auto_start: false, started: false }
+  }
+
+  /// Start the local insights server on first use, as CLI commands do
+  pub fn with_auto_start(mut self) -> Self {
+    self.auto_start = true;
+    self
+  }
+
+  /// Handle one line of input; `None` means nothing should be written back
+  pub async fn handle_line(&mut self, line: &str) -> Option<Value> {
+    let message: RpcMessage = match serde_json::from_str(line) {
+      Ok(message) => message,
+      Err(e) => {
+        return Some(error_message(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())));
+      }
+    };
+
+    let Some(method) = message.method else {
+      // Responses to requests we never send; nothing to answer
+      return message
+        .id
+        .is_none()
+        .then(|| error_message(Value::Null, RpcError::new(INVALID_REQUEST, "Missing method")));
+    };
+
+    let outcome = self.dispatch(&method, message.params).await;
+    let id = message.id?;
+    Some(match outcome {
+      Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+      Err(error) => error_message(id, error),
+    })
+  }
+
+  async fn dispatch(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+      "initialize" => Ok(initialize(&params)),
+      "ping" => Ok(json!({})),
+      "tools/list" => Ok(json!({ "tools": tools() })),
+      "tools/call" => self.call_tool(params).await,
+      method if method.starts_with("notifications/") => Ok(Value::Null),
+      method => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {method}"))),
+    }
+  }
+
+  async fn call_tool(&mut self, params: Value) -> Result<Value, RpcError> {
+    let name = params
+      .get("name")
+      .and_then(Value::as_str)
+      .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing tool name"))?
+      .to_string();
+    let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+
+    let outcome = match name.as_str() {
+      "add_insight" => self.add(parse_args(arguments)?).await,
+      "search_insights" => self.search(parse_args(arguments)?).await,
+      "get_insight" => self.get(parse_args(arguments)?).await,
+      "list_insights" => self.list(parse_args(arguments)?).await,
+      "update_insight" => self.update(parse_args(arguments)?).await,
+      "delete_insight" => self.delete(parse_args(arguments)?).await,
+      other => return Err(RpcError::new(INVALID_PARAMS, format!("Unknown tool: {other}"))),
+    };
+
+    // Failures of the operation itself are reported to the model, not as protocol errors
+    let (text, is_error) = match outcome {
+      Ok(text) => (text, false),
+      Err(e) => (e.to_string(), true),
+    };
+    Ok(json!({ "content": [{ "type": "text", "text": text }], "isError": is_error }))
+  }
+
+  async fn ensure_started(&mut self) -> Result<()> {
+    if self.auto_start && !self.started {
+      ensure_server_running().await?;
+      self.started = true;
+    }
+    Ok(())
+  }
+
+  async fn add(&mut self, args: AddArgs) -> Result<String> {
+    self.ensure_started().await?;
+    let request = AddInsightRequest {
+      topic: args.topic,
+      name: args.name,
+      overview: args.overview,
+      details: args.details,
+      tags: args.tags,
+      allow_sensitive: args.allow_sensitive,
+      strict: args.strict,
+    };
+    let response = self.client.add_insight(&request).await?;
+    Ok(with_warnings(format!("Added insight {}/{}", request.topic, request.name), &response))
+  }
+
+  async fn search(&mut self, args: SearchArgs) -> Result<String> {
+    self.ensure_started().await?;
+    let terms: Vec<String> = args.query.split_whitespace().map(str::to_string).collect();
+    if terms.is_empty() {
+      return Err(anyhow::anyhow!("Search query must not be empty"));
+    }
+
+    let options = SearchCommandOptions {
+      topic: args.topic,
+      tags: args.tags,
+      case_sensitive: false,
+      overview_only: false,
+      exact: false,
+      semantic: false,
+      mode: args.mode.unwrap_or_default(),
+      hybrid_weight: None,
+      popularity_weight: None,
+      recency_weight: None,
+      recency_half_life: None,
+      topic_boosts: Vec::new(),
+      explain: false,
+    };
+    let mut response = self.client.search_insights(terms, &options).await?;
+    response.results.truncate(args.limit.unwrap_or(DEFAULT_SEARCH_LIMIT));
+    response.count = response.results.len();
+    to_text(&response)
+  }
+
+  async fn get(&mut self, args: GetArgs) -> Result<String> {
+    self.ensure_started().await?;
+    let response = self.client.get_insight(&args.topic, &args.name, args.overview_only).await?;
+    to_text(&response.insight)
+  }
+
+  async fn list(&mut self, args: ListArgs) -> Result<String> {
+    self.ensure_started().await?;
+    let response = self.client.list_insights(args.topic.as_deref(), &args.tags).await?;
+    to_text(&response.insights)
+  }
+
+  async fn update(&mut self, args: UpdateArgs) -> Result<String> {
+    self.ensure_started().await?;
+    if args.overview.is_none() && args.details.is_none() && args.tags.is_none() {
+      return Err(anyhow::anyhow!("Provide new overview, details or tags"));
+    }
+    let request = UpdateInsightRequest {
+      topic: args.topic,
+      name: args.name,
+      overview: args.overview,
+      details: args.details,
+      tags: args.tags,
+      allow_sensitive: args.allow_sensitive,
+      strict: args.strict,
+    };
+    let response = self.client.update_insight(&request).await?;
+    Ok(with_warnings(format!("Updated insight {}/{}", request.topic, request.name), &response))
+  }
+
+  async fn delete(&mut self, args: DeleteArgs) -> Result<String> {
+    self.ensure_started().await?;
+    self.client.remove_insight(&args.topic, &args.name).await?;
+    Ok(format!("Deleted insight {}/{}", args.topic, args.name))
+  }
+}
+
+fn initialize(params: &Value) -> Value {
+  let requested = params.get("protocolVersion").and_then(Value::as_str);
+  let version = requested.filter(|v| SUPPORTED_VERSIONS.contains(v)).unwrap_or(PROTOCOL_VERSION);
+  json!({
+    "protocolVersion": version,
+    "capabilities": { "tools": { "listChanged": false } },
+    "serverInfo": { "name": "insights", "version": env!("CARGO_PKG_VERSION") },
+  })
+}
+
+fn error_message(id: Value, error: RpcError) -> Value {
+  json!({
+    "jsonrpc": "2.0",
+    "id": id,
+    "error": { "code": error.code, "message": error.message },
+  })
+}
+
+/// Serve MCP over stdin/stdout until stdin closes
+#[cfg(not(tarpaulin_include))] // Skip coverage - stdio loop
+pub async fn run(mut server: McpServer) -> Result<()> {
+  let mut lines = BufReader::new(tokio::io::stdin()).lines();
+  let mut stdout = tokio::io::stdout();
+
+  while let Some(line) = lines.next_line().await? {
+    if line.trim().is_empty() {
+      continue;
+    }
+    if let Some(response) = server.handle_line(&line).await {
+      stdout.write_all(format!("{response}\n").as_bytes()).await?;
+      stdout.flush().await?;
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn server() -> McpServer {
+    // Nothing listens here; only used for requests that never reach the API
+    McpServer::new(InsightsClient::with_config(crate::cli::client::ClientConfig {
+      base_url: "http://127.0.0.1:9".to_string(),
+      ..Default::default()
+    }))
+  }
+
+  #[tokio::test]
+  async fn test_initialize_negotiates_protocol_version() {
+    let mut server = server();
+    let known =
+      r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05"}}"#;
+    let response = server.handle_line(known).await.unwrap();
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+    assert_eq!(response["result"]["serverInfo"]["name"], "insights");
+
+    let unknown =
+      r#"{"jsonrpc":"2.0","id":2,"method":"initialize","params":{"protocolVersion":"1999-01-01"}}"#;
+    let response = server.handle_line(unknown).await.unwrap();
+    assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+  }
+
+  #[tokio::test]
+  async fn test_notifications_get_no_response() {
+    let mut server = server();
+    let line = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+    assert_eq!(server.handle_line(line).await, None);
+  }
+
+  #[tokio::test]
+  async fn test_tools_list_describes_every_tool() {
+    let mut server = server();
+    let response =
+      server.handle_line(r#"{"jsonrpc":"2.0","id":"a","method":"tools/list"}"#).await.unwrap();
+    let tools = response["result"]["tools"].as_array().unwrap();
+    let names: Vec<&str> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
+    assert_eq!(
+      names,
+      [
+        "add_insight",
+        "search_insights",
+        "get_insight",
+        "list_insights",
+        "update_insight",
+        "delete_insight"
+      ]
+    );
+
+    let add = &tools[0]["inputSchema"];
+    assert_eq!(add["type"], "object");
+    let required: Vec<&str> =
+      add["required"].as_array().unwrap().iter().map(|v| v.as_str().unwrap()).collect();
+    assert_eq!(required, ["details", "name", "overview", "topic"]);
+  }
+
+  #[tokio::test]
+  async fn test_protocol_errors() {
+    let mut server = server();
+
+    let response = server.handle_line("{not json").await.unwrap();
+    assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+    let response =
+      server.handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"resources/list"}"#).await;
+    assert_eq!(response.unwrap()["error"]["code"], METHOD_NOT_FOUND);
+
+    let unknown_tool =
+      r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"drop_tables"}}"#;
+    let response = server.handle_line(unknown_tool).await.unwrap();
+    assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+    let missing_args = r#"{"jsonrpc":"2.0","id":5,"method":"tools/call","params":{"name":"get_insight","arguments":{"topic":"rust"}}}"#;
+    let response = server.handle_line(missing_args).await.unwrap();
+    assert_eq!(response["error"]["code"], INVALID_PARAMS);
+  }
+
+  #[tokio::test]
+  async fn test_failed_operations_are_tool_errors() {
+    let mut server = server();
+    let line = r#"{"jsonrpc":"2.0","id":6,"method":"tools/call","params":{"name":"update_insight","arguments":{"topic":"rust","name":"tips"}}}"#;
+    let response = server.handle_line(line).await.unwrap();
+    assert_eq!(response["result"]["isError"], true);
+    assert!(response["result"]["content"][0]["text"].as_str().unwrap().contains("overview"));
+  }
+}
@@ -1,4 +1,6 @@
 pub mod client;
 pub mod commands;
 pub mod display;
+pub mod editor;
+pub mod mcp;
 pub mod server_manager;
@@ -5,3 +5,19 @@
 
 pub mod cli;
 pub mod server;
+pub mod settings;
+pub mod store;
+
+/// Typed client for the insights REST API
+///
+/// Wraps every endpoint in a method that takes and returns the server's own
+/// request and response types, so callers stay in step with the API.
+#[cfg(feature = "client")]
+pub mod client {
+  pub use crate::cli::client::{get_client, ApiFailure, ClientConfig, InsightsClient};
+  pub use crate::server::services::events::InsightEvent;
+  pub use crate::server::types;
+}
+
+#[cfg(feature = "testing")]
+pub mod testing;
@@ -1,6 +1,15 @@
 use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
+use insights::cli::client::ApiFailure;
 use insights::cli::commands;
+use insights::cli::editor;
+use insights::server::models::sharding::ShardStrategy;
+use insights::server::models::webhook::WebhookEvent;
+use insights::server::services::auth::Scope;
+use insights::server::services::export::ArchiveFormat;
+use insights::server::services::history::parse_version;
+use insights::server::services::slack::SlackImportOptions;
+use insights::server::types::ConflictPolicy;
 
 #[derive(Parser)]
 #[command(name = "insights")]
@@ -9,6 +18,9 @@ use insights::cli::commands;
 )]
 #[command(version = concat!(env!("CARGO_PKG_VERSION"), ", courtesy of Blizz and Kernelle Software"))]
 struct Cli {
+  /// Workspace to operate on (defaults to the server's default workspace)
+  #[arg(long, env = "INSIGHTS_WORKSPACE")]
+  workspace: Option<String>,
   #[command(subcommand)]
   command: Command,
 }
@@ -22,6 +34,37 @@ struct InsightId {
   name: String,
 }
 
+/// Options shared by every kind of import
+#[derive(Args)]
+struct ImportOptions {
+  /// What to do when an insight already exists
+  #[arg(long, value_enum, default_value = "skip")]
+  on_conflict: ConflictPolicy,
+  /// Import insights even if they appear to contain secrets or personal data
+  #[arg(long)]
+  allow_sensitive: bool,
+}
+
+#[derive(Subcommand)]
+enum ImportSource {
+  /// Turn each thread of a Slack channel export into an insight
+  Slack {
+    /// Slack export zip, or the directory it was unpacked into
+    export: std::path::PathBuf,
+    /// Channel to import
+    #[arg(short, long)]
+    channel: String,
+    /// Topic for the imported insights (defaults to the channel name)
+    #[arg(short, long)]
+    topic: Option<String>,
+    /// Workspace subdomain for permalinks, e.g. `acme` for acme.slack.com
+    #[arg(long)]
+    workspace: Option<String>,
+    #[command(flatten)]
+    options: ImportOptions,
+  },
+}
+
 // violet ignore chunk
 #[derive(Subcommand)]
 enum Command {
@@ -29,19 +72,90 @@ enum Command {
   Add {
     #[command(flatten)]
     id: InsightId,
-    /// Brief overview/summary of the insight
-    overview: String,
-    /// Detailed content of the insight
-    details: String,
+    /// Brief overview/summary of the insight (`-` alone reads overview and details from stdin)
+    overview: Option<String>,
+    /// Detailed content of the insight (`-` reads it from stdin)
+    details: Option<String>,
+    /// Write the overview and details in $VISUAL or $EDITOR
+    #[arg(short, long)]
+    edit: bool,
+    /// Tag the insight (repeatable or comma-separated)
+    #[arg(long = "tag", value_delimiter = ',')]
+    tags: Vec<String>,
+    /// Store the insight even if it appears to contain secrets or personal data
+    #[arg(long)]
+    allow_sensitive: bool,
+    /// Reject the insight if it fails lint checks instead of warning
+    #[arg(long)]
+    strict: bool,
+  },
+  /// Bulk-load insights from a JSON or YAML file of {topic, name, overview, details} entries
+  #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+  Import {
+    #[command(subcommand)]
+    source: Option<ImportSource>,
+    /// File containing an array of insights
+    #[arg(required = true)]
+    file: Option<std::path::PathBuf>,
+    #[command(flatten)]
+    options: ImportOptions,
+  },
+  /// Apply a file of create/update/delete operations as one batch, all or none
+  Apply {
+    /// JSON or YAML file with an array of operations, e.g. {"op": "delete", "topic", "name"}
+    file: std::path::PathBuf,
+    /// Write content even if it appears to contain secrets or personal data
+    #[arg(long)]
+    allow_sensitive: bool,
+    /// Reject the batch if any written insight fails lint checks instead of warning
+    #[arg(long)]
+    strict: bool,
+  },
+  /// Export insights to a portable JSON, tar.gz or tar.zst archive
+  Export {
+    /// Archive to write (.json, .tar.gz, .tgz or .tar.zst)
+    output: std::path::PathBuf,
+    /// Only export this topic
+    #[arg(short, long)]
+    topic: Option<String>,
+    /// Leave embeddings out to keep the archive small
+    #[arg(long)]
+    no_embeddings: bool,
+    /// Archive format (inferred from the file extension when omitted)
+    #[arg(long, value_enum)]
+    format: Option<ArchiveFormat>,
+  },
+  /// Populate a fresh install from a seed archive published by the team
+  Bootstrap {
+    /// Seed archive URL or path (.json, .tar.gz or .tar.zst)
+    source: String,
+    /// Pinned SHA-256 of the seed (otherwise <source>.sig must verify against the seed key)
+    #[arg(long)]
+    sha256: Option<String>,
+    /// Restore even if insights already exist, replacing those in the seed
+    #[arg(short, long)]
+    force: bool,
   },
   /// Search through all insights for matching content
   Search {
     #[command(flatten)]
     options: insights::server::services::search::SearchCommandOptions,
-    /// Search terms (space-separated)
-    #[arg(required = true)]
+    /// Search terms and filters, after any options, e.g.
+    /// topic:rust tag:async "exact phrase" -deprecated before:2024-06
+    #[arg(required = true, allow_hyphen_values = true)]
     terms: Vec<String>,
   },
+  /// Ask a question answered from your insights (with citations if an LLM is configured)
+  Ask {
+    /// The question, in plain language
+    question: String,
+    /// Only retrieve insights from this topic
+    #[arg(short, long)]
+    topic: Option<String>,
+    /// Number of insights to retrieve
+    #[arg(short, long)]
+    limit: Option<usize>,
+  },
   /// Get content of a specific insight
   Get {
     #[command(flatten)]
@@ -49,11 +163,17 @@ enum Command {
     /// Show only the overview section
     #[arg(short, long)]
     overview: bool,
+    /// Use the closest stored insight when there is no exact match
+    #[arg(long)]
+    fuzzy: bool,
   },
   /// List insights in a topic or all topics
   List {
-    /// Optional topic to filter by
+    /// Optional topic to filter by, including the topics nested under it
     topic: Option<String>,
+    /// Only list insights carrying this tag (repeatable or comma-separated; all must match)
+    #[arg(long = "tag", value_delimiter = ',')]
+    tags: Vec<String>,
     /// Show overview content for each insight
     #[arg(short, long)]
     verbose: bool,
@@ -62,12 +182,30 @@ enum Command {
   Update {
     #[command(flatten)]
     id: InsightId,
-    /// New overview content
+    /// New overview content (`-` reads it from stdin)
     #[arg(short, long)]
     overview: Option<String>,
-    /// New details content
+    /// New details content (`-` reads it from stdin)
     #[arg(short, long)]
     details: Option<String>,
+    /// Edit the current overview and details in $VISUAL or $EDITOR
+    #[arg(short, long)]
+    edit: bool,
+    /// Replace the insight's tags (repeatable or comma-separated)
+    #[arg(long = "tag", value_delimiter = ',')]
+    tags: Vec<String>,
+    /// Remove every tag from the insight
+    #[arg(long, conflicts_with = "tags")]
+    clear_tags: bool,
+    /// Store the update even if it appears to contain secrets or personal data
+    #[arg(long)]
+    allow_sensitive: bool,
+    /// Reject the update if it fails lint checks instead of warning
+    #[arg(long)]
+    strict: bool,
+    /// Use the closest stored insight when there is no exact match
+    #[arg(long)]
+    fuzzy: bool,
   },
   /// Delete an insight
   Delete {
@@ -76,14 +214,162 @@ enum Command {
     /// Skip confirmation prompt
     #[arg(short, long)]
     force: bool,
+    /// Use the closest stored insight when there is no exact match
+    #[arg(long)]
+    fuzzy: bool,
   },
   /// List all available topics
   Topics,
+  /// List all tags with how many insights carry each
+  Tags,
+  /// Outline a large topic as clusters of related insights
+  Summarize {
+    /// Topic to summarize
+    topic: String,
+    /// Expand every cluster to show its insights
+    #[arg(short, long)]
+    expand: bool,
+    /// Expand only this cluster (numbered as in the outline)
+    #[arg(short, long, conflicts_with = "expand")]
+    cluster: Option<usize>,
+    /// Show overviews for expanded insights
+    #[arg(short, long)]
+    verbose: bool,
+    /// Upper bound on the number of clusters
+    #[arg(long)]
+    max_clusters: Option<usize>,
+  },
+  /// Find near-identical insights and merge them
+  Dedupe {
+    /// Only compare insights in this topic or nested under it
+    #[arg(short, long)]
+    topic: Option<String>,
+    /// Minimum cosine similarity (0.0-1.0) for two insights to count as duplicates
+    #[arg(long)]
+    threshold: Option<f32>,
+    /// Merge every cluster into its oldest insight without asking
+    #[arg(long)]
+    auto_merge: bool,
+  },
+  /// Show the insights most similar to an insight
+  Related {
+    #[command(flatten)]
+    id: InsightId,
+    /// Maximum number of related insights (default: 5)
+    #[arg(short, long)]
+    limit: Option<usize>,
+  },
+  /// List the recorded versions of an insight
+  History {
+    #[command(flatten)]
+    id: InsightId,
+  },
+  /// Show what changed between two versions of an insight
+  Diff {
+    #[command(flatten)]
+    id: InsightId,
+    /// Older version, e.g. v2 (default: the one before --to)
+    #[arg(long, value_parser = parse_version)]
+    from: Option<u32>,
+    /// Newer version, e.g. v4 (default: the latest)
+    #[arg(long, value_parser = parse_version)]
+    to: Option<u32>,
+  },
+  /// Restore an earlier version of an insight (recorded as a new version)
+  Rollback {
+    #[command(flatten)]
+    id: InsightId,
+    /// Version to restore, e.g. v3
+    #[arg(long, value_parser = parse_version)]
+    to: u32,
+  },
+  /// Show the most used insights by reads and search hits
+  Stats {
+    /// Number of insights to show
+    #[arg(long, default_value = "20")]
+    top: usize,
+  },
+  /// Audit stored insights for secrets and personal data
+  Scan,
+  /// Check stored insights for empty overviews, thin details, missing sources and broken links
+  Lint {
+    /// Only lint this topic
+    #[arg(short, long)]
+    topic: Option<String>,
+    /// Exit with an error if any insight fails
+    #[arg(long)]
+    strict: bool,
+  },
+  /// Check stored insights for missing or malformed frontmatter fields
+  Validate {
+    /// Only validate this topic
+    #[arg(short, long)]
+    topic: Option<String>,
+    /// Repair and normalize files whose problems can all be fixed
+    #[arg(long)]
+    fix: bool,
+  },
   /// Recompute embeddings for all insights
   Index {
     /// Force recompute even for insights that already have embeddings
     #[arg(short, long)]
     force: bool,
+    /// Batches of insights to embed at once (default: INSIGHTS_INDEX_CONCURRENCY or 2)
+    #[arg(short, long)]
+    concurrency: Option<usize>,
+    /// Show the progress of the running or last re-index instead of starting one
+    #[arg(long, conflicts_with_all = ["force", "concurrency"])]
+    status: bool,
+  },
+  /// Control the resources used by background indexing
+  Indexing {
+    #[command(subcommand)]
+    action: IndexingAction,
+  },
+  /// Manage per-topic retention policies for ephemeral insights
+  Retention {
+    #[command(subcommand)]
+    action: RetentionAction,
+  },
+  /// Take, list and restore snapshot backups of insights and the vector database
+  Backup {
+    #[command(subcommand)]
+    action: BackupAction,
+  },
+  /// List, create and delete workspaces, each a separate knowledge base
+  Workspace {
+    #[command(subcommand)]
+    action: WorkspaceAction,
+  },
+  /// Manage webhooks notified when insights change
+  Webhook {
+    #[command(subcommand)]
+    action: WebhookAction,
+  },
+  /// Mint and revoke API tokens for the REST server
+  Token {
+    #[command(subcommand)]
+    action: TokenAction,
+  },
+  /// Manage sharding of the embeddings index
+  Shards {
+    #[command(subcommand)]
+    action: ShardsAction,
+  },
+  /// Run the insights server in the foreground
+  Serve {
+    /// Speak the Model Context Protocol over stdio instead of serving REST
+    #[arg(long)]
+    mcp: bool,
+    /// REST server bind address
+    #[arg(long, default_value = "127.0.0.1:3000", conflicts_with = "mcp")]
+    bind: std::net::SocketAddr,
+    /// Reindex insight files when they are edited on disk
+    #[arg(long, conflicts_with = "mcp")]
+    watch: bool,
+    /// Serve the read-only web UI at /ui
+    #[arg(long, conflicts_with = "mcp")]
+    ui: bool,
   },
   /// Query daemon logs for debugging and monitoring
   Logs {
@@ -96,38 +382,342 @@ enum Command {
   },
 }
 
+#[derive(Subcommand)]
+enum IndexingAction {
+  /// Show resource limits and whether background indexing is waiting
+  Status,
+  /// Hold background re-indexing until resumed
+  Pause,
+  /// Let paused background re-indexing continue
+  Resume,
+}
+
+#[derive(Subcommand)]
+enum RetentionAction {
+  /// Set the retention policy for a topic
+  Set {
+    /// Topic the policy applies to
+    topic: String,
+    /// Expire insights this many days after their last update
+    #[arg(long)]
+    days: Option<u32>,
+    /// Expire every insight in the topic on this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long)]
+    until: Option<String>,
+  },
+  /// List all retention policies
+  List,
+  /// Remove the retention policy for a topic
+  Remove {
+    /// Topic whose policy should be removed
+    topic: String,
+  },
+  /// Archive expired insights now instead of waiting for the scheduled sweep
+  Sweep,
+}
+
+#[derive(Subcommand)]
+enum BackupAction {
+  /// Back up now instead of waiting for the scheduled backup
+  Now,
+  /// List backups, newest first
+  List,
+  /// Replace all insights with a backup (the current state is backed up first)
+  Restore {
+    /// Backup id, as shown by `insights backup list`
+    id: String,
+    /// Skip confirmation prompt
+    #[arg(short, long)]
+    force: bool,
+  },
+}
+
+#[derive(Subcommand)]
+enum WorkspaceAction {
+  /// List workspaces with their insight counts
+  List,
+  /// Create an empty workspace
+  Create {
+    /// Letters, digits, '-' and '_', starting with a letter or digit
+    name: String,
+  },
+  /// Delete a workspace with all of its insights and embeddings
+  Delete {
+    /// Workspace to delete
+    name: String,
+    /// Skip confirmation prompt
+    #[arg(short, long)]
+    force: bool,
+  },
+}
+
+#[derive(Subcommand)]
+enum WebhookAction {
+  /// Register a webhook that receives signed JSON payloads
+  Add {
+    /// Endpoint to POST payloads to
+    url: String,
+    /// Key used to sign payloads (HMAC-SHA256 in the X-Insights-Signature header)
+    #[arg(long, env = "INSIGHTS_WEBHOOK_SECRET")]
+    secret: String,
+    /// Only notify about this topic (repeatable; default: every topic)
+    #[arg(long = "topic")]
+    topics: Vec<String>,
+    /// Only notify about this event (repeatable; default: every event)
+    #[arg(long = "event", value_enum)]
+    events: Vec<WebhookEvent>,
+  },
+  /// List registered webhooks
+  List,
+  /// Remove a webhook
+  Remove {
+    /// Id shown by `insights webhook list`
+    id: String,
+  },
+  /// Show recent deliveries, newest first
+  Deliveries {
+    /// Only show deliveries to this webhook
+    #[arg(long)]
+    webhook: Option<String>,
+    /// Maximum number of deliveries to show
+    #[arg(short, long, default_value = "20")]
+    limit: usize,
+  },
+}
+
+#[derive(Subcommand)]
+enum TokenAction {
+  /// Create a token; it is printed once and only its hash is stored
+  Mint {
+    /// Name to revoke the token by later
+    name: String,
+    /// What the token may do
+    #[arg(long, value_enum, default_value = "read")]
+    scope: Scope,
+  },
+  /// List token names and scopes
+  List,
+  /// Revoke a token
+  Revoke {
+    /// Name given when the token was minted
+    name: String,
+  },
+}
+
+#[derive(Subcommand)]
+enum ShardsAction {
+  /// Show the shard layout and the size of every shard
+  List,
+  /// Switch to a new shard layout, migrating existing embeddings
+  Configure {
+    /// How embeddings are spread across tables
+    #[arg(value_enum)]
+    strategy: ShardStrategy,
+    /// Number of tables for the hash strategy
+    #[arg(long)]
+    buckets: Option<u32>,
+    /// Largest-to-mean shard ratio that triggers a rebalance
+    #[arg(long)]
+    max_imbalance: Option<f32>,
+    /// Shard size that triggers a rebalance
+    #[arg(long)]
+    max_shard_rows: Option<usize>,
+  },
+  /// Redistribute embeddings when shards have grown unevenly
+  Rebalance {
+    /// Rebalance even if the shards are within their limits
+    #[arg(short, long)]
+    force: bool,
+  },
+}
+
 async fn handle(command: Command) -> Result<()> {
   match command {
-    Command::Add { id, overview, details } => {
-      commands::add_insight(&id.topic, &id.name, &overview, &details).await
-    }
-    Command::Search { options, terms } => {
-      commands::search_insights(
-        &terms,
-        options.topic.clone(),
-        options.case_sensitive,
-        options.overview_only,
-        options.exact,
-        options.semantic,
+    Command::Add { id, overview, details, edit, tags, allow_sensitive, strict } => {
+      let (overview, details) = editor::for_add(overview, details, edit)?;
+      commands::add_insight(
+        &id.topic,
+        &id.name,
+        &overview,
+        &details,
+        &tags,
+        allow_sensitive,
+        strict,
+      )
+      .await
+    }
+    Command::Import {
+      source: Some(ImportSource::Slack { export, channel, topic, workspace, options }),
+      ..
+    } => {
+      let slack = SlackImportOptions { channel, topic, workspace };
+      commands::import_slack(&export, &slack, options.on_conflict, options.allow_sensitive).await
+    }
+    Command::Import { file, options, .. } => {
+      let file = file.expect("clap requires a file when no import source is given");
+      commands::import_insights(&file, options.on_conflict, options.allow_sensitive).await
+    }
+    Command::Apply { file, allow_sensitive, strict } => {
+      commands::apply_batch(&file, allow_sensitive, strict).await
+    }
+    Command::Export { output, topic, no_embeddings, format } => {
+      commands::export_insights(&output, topic, !no_embeddings, format).await
+    }
+    Command::Bootstrap { source, sha256, force } => {
+      commands::bootstrap_insights(&source, sha256, force).await
+    }
+    Command::Search { options, terms } => commands::search_insights(&terms, &options).await,
+    Command::Ask { question, topic, limit } => commands::ask(&question, topic, limit).await,
+    Command::Get { id, overview, fuzzy } => {
+      let (topic, name) = commands::resolve_insight(&id.topic, &id.name, fuzzy).await?;
+      commands::get_insight(&topic, &name, overview).await
+    }
+    Command::List { topic, tags, verbose } => {
+      commands::list_insights(topic.as_deref(), &tags, verbose).await
+    }
+    Command::Update {
+      id,
+      overview,
+      details,
+      edit,
+      tags,
+      clear_tags,
+      allow_sensitive,
+      strict,
+      fuzzy,
+    } => {
+      let (topic, name) = commands::resolve_insight(&id.topic, &id.name, fuzzy).await?;
+      let current = if edit { Some(commands::insight_content(&topic, &name).await?) } else { None };
+      let (overview, details) = editor::for_update(overview, details, current)?;
+      let tags = if clear_tags { Some(Vec::new()) } else { (!tags.is_empty()).then_some(tags) };
+      commands::update_insight(
+        &topic,
+        &name,
+        overview.as_deref(),
+        details.as_deref(),
+        tags.as_deref(),
+        allow_sensitive,
+        strict,
       )
       .await
     }
-    Command::Get { id, overview } => commands::get_insight(&id.topic, &id.name, overview).await,
-    Command::List { topic, verbose } => commands::list_insights(topic.as_deref(), verbose).await,
-    Command::Update { id, overview, details } => {
-      commands::update_insight(&id.topic, &id.name, overview.as_deref(), details.as_deref()).await
+    Command::Delete { id, force, fuzzy } => {
+      let (topic, name) = commands::resolve_insight(&id.topic, &id.name, fuzzy).await?;
+      commands::delete_insight(&topic, &name, force).await
     }
-    Command::Delete { id, force } => commands::delete_insight(&id.topic, &id.name, force).await,
     Command::Topics => commands::list_topics().await,
-    Command::Index { force } => commands::index_insights(force).await,
+    Command::Tags => commands::list_tags().await,
+    Command::Summarize { topic, expand, cluster, verbose, max_clusters } => {
+      commands::summarize_topic(&topic, expand, cluster, verbose, max_clusters).await
+    }
+    Command::Dedupe { topic, threshold, auto_merge } => {
+      commands::dedupe(topic.as_deref(), threshold, auto_merge).await
+    }
+    Command::Related { id, limit } => commands::related_insights(&id.topic, &id.name, limit).await,
+    Command::History { id } => commands::history(&id.topic, &id.name).await,
+    Command::Diff { id, from, to } => commands::diff_versions(&id.topic, &id.name, from, to).await,
+    Command::Rollback { id, to } => commands::rollback(&id.topic, &id.name, to).await,
+    Command::Stats { top } => commands::stats(top).await,
+    Command::Scan => commands::scan_insights().await,
+    Command::Lint { topic, strict } => commands::lint_insights(topic.as_deref(), strict).await,
+    Command::Validate { topic, fix } => commands::validate_insights(topic.as_deref(), fix).await,
+    Command::Index { force, concurrency, status } => {
+      commands::index_insights(force, concurrency, status).await
+    }
+    Command::Indexing { action } => handle_indexing(action).await,
+    Command::Retention { action } => handle_retention(action).await,
+    Command::Backup { action } => handle_backup(action).await,
+    Command::Workspace { action } => handle_workspace(action).await,
+    Command::Webhook { action } => handle_webhook(action).await,
+    Command::Token { action } => handle_token(action).await,
+    Command::Shards { action } => handle_shards(action).await,
+    Command::Serve { mcp, bind, watch, ui } => commands::serve(mcp, bind, watch, ui).await,
     Command::Logs { limit, level } => commands::logs(limit, &level).await,
   }
 }
 
+async fn handle_indexing(action: IndexingAction) -> Result<()> {
+  match action {
+    IndexingAction::Status => commands::indexing_status().await,
+    IndexingAction::Pause => commands::pause_indexing().await,
+    IndexingAction::Resume => commands::resume_indexing().await,
+  }
+}
+
+async fn handle_backup(action: BackupAction) -> Result<()> {
+  match action {
+    BackupAction::Now => commands::create_backup().await,
+    BackupAction::List => commands::list_backups().await,
+    BackupAction::Restore { id, force } => commands::restore_backup(&id, force).await,
+  }
+}
+
+async fn handle_workspace(action: WorkspaceAction) -> Result<()> {
+  match action {
+    WorkspaceAction::List => commands::list_workspaces().await,
+    WorkspaceAction::Create { name } => commands::create_workspace(&name).await,
+    WorkspaceAction::Delete { name, force } => commands::delete_workspace(&name, force).await,
+  }
+}
+
+async fn handle_retention(action: RetentionAction) -> Result<()> {
+  match action {
+    RetentionAction::Set { topic, days, until } => {
+      commands::set_retention(&topic, days, until.as_deref()).await
+    }
+    RetentionAction::List => commands::list_retention().await,
+    RetentionAction::Remove { topic } => commands::remove_retention(&topic).await,
+    RetentionAction::Sweep => commands::sweep_retention().await,
+  }
+}
+
+async fn handle_webhook(action: WebhookAction) -> Result<()> {
+  match action {
+    WebhookAction::Add { url, secret, topics, events } => {
+      commands::add_webhook(&url, &secret, &topics, &events).await
+    }
+    WebhookAction::List => commands::list_webhooks().await,
+    WebhookAction::Remove { id } => commands::remove_webhook(&id).await,
+    WebhookAction::Deliveries { webhook, limit } => {
+      commands::list_deliveries(webhook.as_deref(), limit).await
+    }
+  }
+}
+
+async fn handle_token(action: TokenAction) -> Result<()> {
+  match action {
+    TokenAction::Mint { name, scope } => commands::mint_token(&name, scope),
+    TokenAction::List => commands::list_tokens().await,
+    TokenAction::Revoke { name } => commands::revoke_token(&name),
+  }
+}
+
+async fn handle_shards(action: ShardsAction) -> Result<()> {
+  match action {
+    ShardsAction::List => commands::list_shards().await,
+    ShardsAction::Configure { strategy, buckets, max_imbalance, max_shard_rows } => {
+      commands::configure_shards(strategy, buckets, max_imbalance, max_shard_rows).await
+    }
+    ShardsAction::Rebalance { force } => commands::rebalance_shards(force).await,
+  }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
   let cli = Cli::parse();
 
-  handle(cli.command).await?;
+  // The client picks the workspace up from the environment
+  if let Some(workspace) = &cli.workspace {
+    std::env::set_var("INSIGHTS_WORKSPACE", workspace);
+  }
+
+  if let Err(e) = handle(cli.command).await {
+    // Server-reported failures exit with the status assigned to their error code
+    if let Some(failure) = e.downcast_ref::<ApiFailure>() {
+      bentley::error!(&failure.to_string());
+      std::process::exit(failure.code.exit_code());
+    }
+    return Err(e);
+  }
   Ok(())
 }
@@ -0,0 +1,68 @@
+//! Mapping of stable error codes onto HTTP responses
+//!
+//! Handlers pick an [`ErrorCode`]; the HTTP status is always derived from it so
+//! the same failure produces the same status on every endpoint.
+
+use axum::{http::StatusCode, response::Json};
+use uuid::Uuid;
+
+use crate::server::types::{ApiError, BaseResponse, ErrorCode};
+
+/// Error half of every handler's return type
+pub type ErrorResponse = (StatusCode, Json<BaseResponse<()>>);
+
+/// HTTP status for an error code
+pub fn status_for(code: ErrorCode) -> StatusCode {
+  match code {
+    ErrorCode::NotFound => StatusCode::NOT_FOUND,
+    ErrorCode::AlreadyExists | ErrorCode::RevisionConflict => StatusCode::CONFLICT,
+    ErrorCode::ValidationFailed => StatusCode::BAD_REQUEST,
+    ErrorCode::IndexUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+    ErrorCode::SensitiveContent => StatusCode::UNPROCESSABLE_ENTITY,
+    ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+    ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+  }
+}
+
+/// Build an error response whose status matches its code
+pub fn error_response(
+  code: ErrorCode,
+  key: &str,
+  message: &str,
+  transaction_id: Uuid,
+) -> ErrorResponse {
+  error_response_with_context(code, key, message, serde_json::Value::Null, transaction_id)
+}
+
+/// Build an error response carrying structured details alongside the message
+pub fn error_response_with_context(
+  code: ErrorCode,
+  key: &str,
+  message: &str,
+  context: serde_json::Value,
+  transaction_id: Uuid,
+) -> ErrorResponse {
+  let error = ApiError::new(key, message).with_code(code).with_context(context);
+  (status_for(code), Json(BaseResponse::<()>::error(vec![error], transaction_id)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_error_response_status_matches_code() {
+    let (status, Json(body)) =
+      error_response(ErrorCode::NotFound, "insight_not_found", "missing", Uuid::new_v4());
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body.errors[0].code, ErrorCode::NotFound);
+    assert_eq!(body.errors[0].key, "insight_not_found");
+  }
+
+  #[test]
+  fn test_conflicts_share_a_status() {
+    assert_eq!(status_for(ErrorCode::AlreadyExists), StatusCode::CONFLICT);
+    assert_eq!(status_for(ErrorCode::RevisionConflict), StatusCode::CONFLICT);
+  }
+}
@@ -0,0 +1,104 @@
+//! Conversational query endpoint handlers
+
+use anyhow::Result;
+use axum::{
+  extract::{Extension, Json},
+  response::Json as ResponseJson,
+};
+use uuid::Uuid;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::handlers::insights::hybrid_search;
+use crate::server::middleware::RequestContext;
+use crate::server::services::ask::{self, LlmConfig};
+use crate::server::services::search::SearchMode;
+use crate::server::types::{
+  AskRequest, AskResponse, AskSourceData, BaseResponse, ErrorCode, SearchRequest, SearchResultData,
+};
+
+/// POST /ask - Answer a question from the most relevant insights
+pub async fn ask(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<AskRequest>,
+) -> Result<ResponseJson<BaseResponse<AskResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  if request.question.trim().is_empty() {
+    return Err(error_response(
+      ErrorCode::ValidationFailed,
+      "ask_request_invalid",
+      "Question cannot be empty",
+      transaction_id,
+    ));
+  }
+
+  let search = SearchRequest {
+    terms: ask::question_terms(&request.question),
+    topic: request.topic.clone(),
+    tags: Vec::new(),
+    case_sensitive: false,
+    overview_only: false,
+    exact: false,
+    semantic: false,
+    mode: SearchMode::Hybrid,
+    hybrid_weight: None,
+    popularity_weight: None,
+    recency_weight: None,
+    recency_half_life_days: None,
+    topic_boosts: Default::default(),
+    explain: false,
+  };
+  let mut results = hybrid_search(&context, &search, transaction_id).await?;
+  results.truncate(request.limit.unwrap_or(ask::DEFAULT_SOURCES).max(1));
+
+  let (answer, model, note) = if results.is_empty() {
+    (None, None, Some("No relevant insights found".to_string()))
+  } else {
+    synthesize(&context, &request.question, &results).await
+  };
+
+  let cited = answer.as_deref().map(|a| ask::cited_sources(a, results.len())).unwrap_or_default();
+  let sources = results
+    .iter()
+    .enumerate()
+    .map(|(i, result)| AskSourceData {
+      index: i + 1,
+      topic: result.topic.clone(),
+      name: result.name.clone(),
+      excerpt: ask::excerpt(result),
+      score: result.score,
+      cited: cited.contains(&(i + 1)),
+    })
+    .collect();
+
+  let response = AskResponse { answer, model, note, sources };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// Answer from the sources if an LLM is configured; otherwise explain why not
+async fn synthesize(
+  context: &RequestContext,
+  question: &str,
+  sources: &[SearchResultData],
+) -> (Option<String>, Option<String>, Option<String>) {
+  let config = match LlmConfig::load().await {
+    Ok(Some(config)) => config,
+    Ok(None) => return (None, None, Some("No LLM endpoint configured".to_string())),
+    Err(e) => {
+      return (None, None, Some(format!("No LLM endpoint configured (secrets unavailable: {e})")))
+    }
+  };
+
+  match ask::complete(&config, &ask::build_prompt(question, sources)).await {
+    Ok(answer) => {
+      context
+        .log_success(&format!("Answered question with {}", config.model), "insights-ask")
+        .await;
+      (Some(answer), Some(config.model), None)
+    }
+    Err(e) => {
+      context.log_warn(&format!("LLM request failed: {e}"), "insights-ask").await;
+      (None, None, Some(format!("LLM request failed: {e}")))
+    }
+  }
+}
@@ -0,0 +1,148 @@
+//! Backup endpoint handlers
+
+use axum::{
+  extract::{Extension, Json},
+  response::Json as ResponseJson,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::middleware::RequestContext;
+use crate::server::services::backup::{self, Backup, BackupPolicy, Locations};
+use crate::server::services::sync;
+use crate::server::types::{
+  BackupData, BaseResponse, CreateBackupResponse, ErrorCode, ListBackupsResponse,
+  RestoreBackupRequest, RestoreBackupResponse,
+};
+
+fn to_data(backup: Backup) -> BackupData {
+  BackupData { id: backup.id, created_at: backup.created_at, size_bytes: backup.size_bytes }
+}
+
+fn locations(transaction_id: Uuid) -> Result<Locations, ErrorResponse> {
+  Locations::from_env().map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "backup_location_failed",
+      &format!("Failed to resolve backup locations: {e}"),
+      transaction_id,
+    )
+  })
+}
+
+/// GET /insights/backups - List backups, newest first
+pub async fn list_backups() -> Result<ResponseJson<BaseResponse<ListBackupsResponse>>, ErrorResponse>
+{
+  let transaction_id = Uuid::new_v4();
+  let locations = locations(transaction_id)?;
+
+  let backups = backup::list(&locations.backups).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "backup_list_failed",
+      &format!("Failed to list backups: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let response = ListBackupsResponse {
+    directory: locations.backups.to_string_lossy().to_string(),
+    backups: backups.into_iter().map(to_data).collect(),
+  };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// POST /insights/backups - Back up now, then prune old backups
+pub async fn create_backup(
+  Extension(context): Extension<RequestContext>,
+) -> Result<ResponseJson<BaseResponse<CreateBackupResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+  let locations = locations(transaction_id)?;
+  let now = Utc::now();
+
+  let backup = backup::create(&locations, now).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "backup_failed",
+      &format!("Failed to back up insights: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let pruned = backup::prune(&locations.backups, &BackupPolicy::from_env(), now).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "backup_prune_failed",
+      &format!("Backed up to {} but failed to prune old backups: {e}", backup.id),
+      transaction_id,
+    )
+  })?;
+
+  context
+    .log_success(
+      &format!("Backed up insights to {} ({} bytes)", backup.path.display(), backup.size_bytes),
+      "insights-backup",
+    )
+    .await;
+
+  let response = CreateBackupResponse { backup: to_data(backup), pruned };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// POST /insights/backups/restore - Replace the insights with a backup
+pub async fn restore_backup(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<RestoreBackupRequest>,
+) -> Result<ResponseJson<BaseResponse<RestoreBackupResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+  let locations = locations(transaction_id)?;
+
+  let known = backup::list(&locations.backups)
+    .map(|backups| backups.iter().any(|backup| backup.id == request.id))
+    .unwrap_or(false);
+  if !known {
+    return Err(error_response(
+      ErrorCode::NotFound,
+      "backup_not_found",
+      &format!("Backup {} not found", request.id),
+      transaction_id,
+    ));
+  }
+
+  // Keep what is about to be replaced, so a restore can itself be undone
+  let safety = backup::create(&locations, Utc::now()).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "backup_failed",
+      &format!("Refusing to restore without backing up the current insights first: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let restored = backup::restore(&locations, &request.id).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "backup_restore_failed",
+      &format!(
+        "Failed to restore backup {} (current state saved as {}): {e}",
+        request.id, safety.id
+      ),
+      transaction_id,
+    )
+  })?;
+
+  sync::auto_commit(&format!("Restore backup {}", request.id)).await;
+  context
+    .log_success(
+      &format!(
+        "Restored {restored} insights from backup {} (previous state in {})",
+        request.id, safety.id
+      ),
+      "insights-backup",
+    )
+    .await;
+
+  let response = RestoreBackupResponse { id: request.id, restored, safety_backup: safety.id };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
@@ -0,0 +1,116 @@
+//! Duplicate detection endpoint handlers
+
+use anyhow::Result;
+use axum::{
+  extract::{Extension, Json},
+  response::Json as ResponseJson,
+};
+use uuid::Uuid;
+
+#[cfg(feature = "semantic")]
+use crate::server::services::vector_database::VectorDatabase;
+#[cfg(feature = "semantic")]
+use std::collections::HashMap;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::middleware::RequestContext;
+use crate::server::models::insight::{self, Insight};
+use crate::server::services::dedupe;
+use crate::server::types::{
+  BaseResponse, DuplicateClusterData, DuplicateInsightData, ErrorCode, FindDuplicatesRequest,
+  FindDuplicatesResponse,
+};
+
+/// POST /insights/duplicates - Find clusters of near-identical insights
+pub async fn find_duplicates(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<FindDuplicatesRequest>,
+) -> Result<ResponseJson<BaseResponse<FindDuplicatesResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  if request.threshold.is_some_and(|threshold| !(0.0..=1.0).contains(&threshold)) {
+    return Err(error_response(
+      ErrorCode::ValidationFailed,
+      "duplicates_request_invalid",
+      "threshold must be between 0 and 1",
+      transaction_id,
+    ));
+  }
+
+  let topic = request.topic.as_deref().map(str::to_lowercase);
+  let mut insights = insight::get_insights(topic.as_deref()).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "duplicates_load_failed",
+      &format!("Failed to load insights: {e}"),
+      transaction_id,
+    )
+  })?;
+  // Oldest first, so every cluster leads with the insight the others copied
+  insights.sort_by_key(|insight| insight.created_at);
+
+  let texts: Vec<String> =
+    insights.iter().map(|i| format!("{} {} {}", i.name, i.overview, i.details)).collect();
+  let embeddings = stored_embeddings(&context, &insights).await;
+  let found = dedupe::find(&texts, embeddings.as_deref(), request.threshold);
+
+  let entry = |index: usize| DuplicateInsightData {
+    topic: insights[index].topic.clone(),
+    name: insights[index].name.clone(),
+    overview: insights[index].overview.clone(),
+    created_at: insights[index].created_at,
+  };
+  let response = FindDuplicatesResponse {
+    method: found.source.to_string(),
+    threshold: found.threshold,
+    scanned: insights.len(),
+    clusters: found
+      .clusters
+      .into_iter()
+      .map(|cluster| DuplicateClusterData {
+        similarity: cluster.similarity,
+        insights: cluster.members.iter().map(|&i| entry(i)).collect(),
+      })
+      .collect(),
+  };
+
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// Stored embeddings for every insight, or None if any are missing
+#[cfg(feature = "semantic")]
+async fn stored_embeddings(
+  context: &RequestContext,
+  insights: &[Insight],
+) -> Option<Vec<Vec<f32>>> {
+  let mut topics: Vec<String> = insights.iter().map(|i| i.topic.to_lowercase()).collect();
+  topics.sort();
+  topics.dedup();
+
+  let mut stored: HashMap<(String, String), Vec<f32>> = HashMap::new();
+  for topic in topics {
+    match context.vector_db.topic_embeddings(&topic).await {
+      Ok(embeddings) => {
+        stored.extend(embeddings.into_iter().map(|(name, e)| ((topic.clone(), name), e)))
+      }
+      Err(e) => {
+        context.log_warn(&format!("Falling back to term similarity: {e}"), "insights-dedupe").await;
+        return None;
+      }
+    }
+  }
+
+  insights
+    .iter()
+    .map(|insight| stored.remove(&(insight.topic.to_lowercase(), insight.name.to_lowercase())))
+    .collect()
+}
+
+/// Stored embeddings for every insight (never available without the semantic feature)
+#[cfg(not(feature = "semantic"))]
+async fn stored_embeddings(
+  _context: &RequestContext,
+  _insights: &[Insight],
+) -> Option<Vec<Vec<f32>>> {
+  None
+}
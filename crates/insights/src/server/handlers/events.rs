@@ -0,0 +1,63 @@
+//! Live change feed handler
+//!
+//! `/ws/events` sends every insight change over a WebSocket, one text message per
+//! event: `{"type": "insight", "data": {...}}` carrying an [`InsightEvent`], or
+//! `{"type": "lagged", "data": 3}` with the number of changes a slow client missed.
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::Query;
+use axum::response::Response;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+
+use crate::server::services::events::{self, InsightEvent};
+use crate::server::types::EventsQuery;
+
+pub const INSIGHT_EVENT: &str = "insight";
+pub const LAGGED_EVENT: &str = "lagged";
+
+/// GET /ws/events - Stream insight changes over a WebSocket
+pub async fn change_socket(
+  Query(query): Query<EventsQuery>,
+  upgrade: WebSocketUpgrade,
+) -> Response {
+  // Subscribe before answering, so no change made after the handshake is missed
+  let receiver = events::subscribe();
+  upgrade.on_upgrade(move |socket| serve_socket(socket, receiver, query.topic))
+}
+
+/// Push changes until the client leaves or the feed closes
+///
+/// axum answers pings on its own; anything else the client sends is ignored.
+async fn serve_socket(
+  mut socket: WebSocket,
+  mut receiver: Receiver<InsightEvent>,
+  topic: Option<String>,
+) {
+  loop {
+    let message = tokio::select! {
+      incoming = socket.recv() => match incoming {
+        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+        Some(Ok(_)) => continue,
+      },
+      change = receiver.recv() => match change {
+        Ok(change) if change.matches(topic.as_deref()) => socket_message(INSIGHT_EVENT, &change),
+        Ok(_) => continue,
+        Err(RecvError::Lagged(missed)) => socket_message(LAGGED_EVENT, &missed),
+        Err(RecvError::Closed) => {
+          let _ = socket.send(Message::Close(None)).await;
+          return;
+        }
+      },
+    };
+    if socket.send(message).await.is_err() {
+      return;
+    }
+  }
+}
+
+fn socket_message(kind: &str, data: &impl serde::Serialize) -> Message {
+  let text =
+    serde_json::to_string(&serde_json::json!({ "type": kind, "data": data })).unwrap_or_default();
+  Message::Text(text.into())
+}
@@ -0,0 +1,106 @@
+//! Insight version history endpoint handlers
+
+use axum::{
+  extract::{Extension, Json},
+  response::Json as ResponseJson,
+};
+use uuid::Uuid;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::handlers::insights::{attempt_embedding_update, attempt_full_text_update};
+use crate::server::middleware::RequestContext;
+use crate::server::models::webhook::WebhookEvent;
+use crate::server::services::{events, history, sync, webhooks};
+use crate::server::types::{
+  BaseResponse, DiffVersionsRequest, DiffVersionsResponse, ErrorCode, HistoryRequest,
+  HistoryResponse, RollbackRequest, RollbackResponse, VersionData,
+};
+
+/// POST /insights/history - List the recorded versions of an insight
+pub async fn list_history(
+  Json(request): Json<HistoryRequest>,
+) -> Result<ResponseJson<BaseResponse<HistoryResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let versions = history::list(&request.topic, &request.name)
+    .map_err(|e| not_found("insight_history_failed", e, transaction_id))?
+    .into_iter()
+    .map(|version| VersionData {
+      version: version.number,
+      recorded_at: version.insight.last_updated,
+      overview: version.insight.overview,
+      update_count: version.insight.update_count,
+    })
+    .collect();
+
+  let response = HistoryResponse { topic: request.topic, name: request.name, versions };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// POST /insights/history/diff - Compare two versions of an insight
+pub async fn diff_versions(
+  Json(request): Json<DiffVersionsRequest>,
+) -> Result<ResponseJson<BaseResponse<DiffVersionsResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let versions = history::list(&request.topic, &request.name)
+    .map_err(|e| not_found("insight_diff_failed", e, transaction_id))?;
+  let latest = versions.last().map_or(1, |version| version.number);
+  let to = request.to.unwrap_or(latest);
+  let from = request.from.unwrap_or(to.saturating_sub(1).max(1));
+
+  let find = |number: u32| {
+    versions.iter().find(|version| version.number == number).ok_or_else(|| {
+      error_response(
+        ErrorCode::NotFound,
+        "insight_version_not_found",
+        &format!("Version v{number} of {}/{} not found", request.topic, request.name),
+        transaction_id,
+      )
+    })
+  };
+  let diff = history::diff(find(from)?, find(to)?);
+
+  Ok(ResponseJson(BaseResponse::success(DiffVersionsResponse { from, to, diff }, transaction_id)))
+}
+
+/// POST /insights/history/rollback - Restore an earlier version as the newest one
+pub async fn rollback(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<RollbackRequest>,
+) -> Result<ResponseJson<BaseResponse<RollbackResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let (restored, version) =
+    history::rollback(&request.topic, &request.name, request.to).map_err(|e| {
+      error_response(
+        ErrorCode::ValidationFailed,
+        "insight_rollback_failed",
+        &format!("Rollback failed: {e}"),
+        transaction_id,
+      )
+    })?;
+
+  attempt_full_text_update(&context, std::slice::from_ref(&restored)).await;
+  attempt_embedding_update(&context, &restored).await;
+  events::publish(WebhookEvent::Updated, &restored);
+  webhooks::notify(WebhookEvent::Updated, &restored).await;
+  sync::auto_commit(&format!("Roll back {}/{} to v{}", request.topic, request.name, request.to))
+    .await;
+  context
+    .log_success(
+      &format!(
+        "Rolled back {}/{} to v{} (recorded as v{version})",
+        request.topic, request.name, request.to
+      ),
+      "insights-history",
+    )
+    .await;
+
+  let response = RollbackResponse { restored: request.to, version };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+fn not_found(key: &str, error: anyhow::Error, transaction_id: Uuid) -> ErrorResponse {
+  error_response(ErrorCode::NotFound, key, &error.to_string(), transaction_id)
+}
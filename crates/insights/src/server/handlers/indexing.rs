@@ -0,0 +1,52 @@
+//! Background indexing status and pause/resume endpoint handlers
+
+use axum::{extract::Extension, response::Json as ResponseJson};
+use uuid::Uuid;
+
+use crate::server::errors::ErrorResponse;
+use crate::server::middleware::RequestContext;
+use crate::server::services::indexing;
+use crate::server::types::{BaseResponse, IndexingStatusResponse};
+
+fn indexing_status() -> IndexingStatusResponse {
+  let schedule = indexing::get_schedule();
+  IndexingStatusResponse {
+    paused: indexing::is_paused(),
+    waiting_on: indexing::hold_reason(&schedule),
+    max_threads: indexing::get_max_threads(),
+    nice: indexing::get_nice(),
+    require_ac_power: schedule.require_ac_power,
+    require_idle: schedule.require_idle,
+    idle_load: schedule.idle_load,
+  }
+}
+
+/// GET /insights/indexing - Resource limits and state of background indexing
+pub async fn status() -> Result<ResponseJson<BaseResponse<IndexingStatusResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+  Ok(ResponseJson(BaseResponse::success(indexing_status(), transaction_id)))
+}
+
+/// POST /insights/indexing/pause - Hold background indexing until resumed
+pub async fn pause(
+  Extension(context): Extension<RequestContext>,
+) -> Result<ResponseJson<BaseResponse<IndexingStatusResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  indexing::pause();
+  context.log_info("Background indexing paused", "insights-indexing").await;
+
+  Ok(ResponseJson(BaseResponse::success(indexing_status(), transaction_id)))
+}
+
+/// POST /insights/indexing/resume - Let paused background indexing continue
+pub async fn resume(
+  Extension(context): Extension<RequestContext>,
+) -> Result<ResponseJson<BaseResponse<IndexingStatusResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  indexing::resume();
+  context.log_info("Background indexing resumed", "insights-indexing").await;
+
+  Ok(ResponseJson(BaseResponse::success(indexing_status(), transaction_id)))
+}
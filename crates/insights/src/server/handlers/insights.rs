@@ -1,41 +1,203 @@
 //! Insights endpoint handlers
 
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 use crate::server::services::vector_database::{VectorDatabase, VectorSearchResult};
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 use anyhow::anyhow;
 use anyhow::Result;
 use axum::{
-  extract::{Extension, Json},
+  extract::{Extension, Json, Query},
   response::Json as ResponseJson,
 };
 use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::ops::Range;
 use uuid::Uuid;
 
+use crate::server::errors::{error_response, error_response_with_context, ErrorResponse};
 use crate::server::types::{
-  AddInsightRequest, ApiError, BaseResponse, GetInsightRequest, GetInsightResponse, InsightData,
-  InsightSummary, ListInsightsResponse, ListTopicsResponse, RemoveInsightRequest, SearchRequest,
-  SearchResponse, SearchResultData, UpdateInsightRequest,
+  AddInsightRequest, BaseResponse, BatchRequest, BatchResponse, BootstrapRequest,
+  BootstrapResponse, ConflictPolicy, ErrorCode, ExportInsightsRequest, GetInsightRequest,
+  GetInsightResponse, ImportInsightsRequest, ImportInsightsResponse, InsightData, InsightSummary,
+  InsightsArchive, LintFindingData, LintQuery, LintResponse, ListInsightsQuery,
+  ListInsightsResponse, ListTagsResponse, ListTopicsResponse, MatchPath, ReindexQuery,
+  ReindexStatusResponse, RemoveInsightRequest, ScanResponse, SchemaProblemData, SearchRequest,
+  SearchResponse, SearchResultData, SensitiveFindingData, TagCountData, UpdateInsightRequest,
+  ValidateRequest, ValidateResponse, ValidationResultData, WriteInsightResponse,
+};
+use crate::server::{
+  middleware::RequestContext,
+  models::{insight, ranking, retention, schema, stats, tag, topic, webhook::WebhookEvent},
+  services::{
+    batch, bootstrap, events, export, fulltext, history, import, indexing, lint,
+    reindex::{self, JobState},
+    search::{self, query::Query as SearchQuery, SearchMode},
+    sensitive, sync, webhooks, workspace,
+  },
 };
-use crate::server::{middleware::RequestContext, models::insight};
 
 /// PUT /insights/update - Update an existing insight
 pub async fn update_insight(
   Extension(context): Extension<RequestContext>,
   Json(request): Json<UpdateInsightRequest>,
-) -> Result<ResponseJson<BaseResponse<()>>, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)>
-{
+) -> Result<ResponseJson<BaseResponse<WriteInsightResponse>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
 
+  validate_update_request(&request, transaction_id)?;
+  let overview = request.overview.as_deref().unwrap_or_default();
+  let details = request.details.as_deref().unwrap_or_default();
+  reject_sensitive_content(&context, overview, details, request.allow_sensitive, transaction_id)
+    .await?;
   let mut insight_data = load_existing_insight(&request, transaction_id)?;
-  update_insight_with_embedding(&context, &mut insight_data, &request, transaction_id).await
+  let warnings = lint_write(
+    &context,
+    &request.topic,
+    &request.name,
+    request.overview.as_deref().unwrap_or(&insight_data.overview),
+    request.details.as_deref().unwrap_or(&insight_data.details),
+    request.strict,
+    transaction_id,
+  )
+  .await?;
+  update_insight_with_embedding(&context, &mut insight_data, &request, transaction_id).await?;
+
+  Ok(ResponseJson(BaseResponse::success(WriteInsightResponse { warnings }, transaction_id)))
+}
+
+/// Reject updates that would not change anything
+fn validate_update_request(
+  request: &UpdateInsightRequest,
+  transaction_id: Uuid,
+) -> Result<(), ErrorResponse> {
+  if request.overview.is_none() && request.details.is_none() && request.tags.is_none() {
+    return Err(error_response(
+      ErrorCode::ValidationFailed,
+      "insight_update_invalid",
+      "At least one of overview, details or tags must be provided",
+      transaction_id,
+    ));
+  }
+  let tags = request.tags.as_deref().unwrap_or_default();
+  validate_schema(&request.topic, &request.name, tags, transaction_id)
+}
+
+/// Reject topics, names and tags that don't fit the insight schema, listing every problem
+fn validate_schema(
+  topic: &str,
+  name: &str,
+  tags: &[String],
+  transaction_id: Uuid,
+) -> Result<(), ErrorResponse> {
+  let problems = schema::check_fields(topic, name, tags);
+  if problems.is_empty() {
+    return Ok(());
+  }
+  let key = if problems.iter().all(|problem| problem.field == "tags") {
+    "insight_tags_invalid"
+  } else {
+    "insight_schema_invalid"
+  };
+  let details: Vec<SchemaProblemData> = problems.iter().map(schema_problem_data).collect();
+  Err(error_response_with_context(
+    ErrorCode::ValidationFailed,
+    key,
+    &format!("Invalid insight: {}", schema::describe(&problems)),
+    serde_json::json!({ "problems": details }),
+    transaction_id,
+  ))
+}
+
+fn schema_problem_data(problem: &schema::Problem) -> SchemaProblemData {
+  SchemaProblemData {
+    field: problem.field.to_string(),
+    message: problem.message.clone(),
+    fixable: problem.fixable,
+  }
+}
+
+/// Block content that looks like secrets or PII unless explicitly allowed
+async fn reject_sensitive_content(
+  context: &RequestContext,
+  overview: &str,
+  details: &str,
+  allow_sensitive: bool,
+  transaction_id: Uuid,
+) -> Result<(), ErrorResponse> {
+  let findings = sensitive::scan_content(overview, details);
+  if findings.is_empty() {
+    return Ok(());
+  }
+
+  if allow_sensitive {
+    context
+      .log_warn(
+        &format!("Storing content with {} sensitive match(es) on request", findings.len()),
+        "insights-api",
+      )
+      .await;
+    return Ok(());
+  }
+
+  Err(error_response(
+    ErrorCode::SensitiveContent,
+    "insight_sensitive_content",
+    &sensitive::describe(&findings),
+    transaction_id,
+  ))
+}
+
+/// Run lint rules on content about to be written
+///
+/// Findings are returned as warnings, or reject the write in strict mode.
+async fn lint_write(
+  context: &RequestContext,
+  topic: &str,
+  name: &str,
+  overview: &str,
+  details: &str,
+  strict: bool,
+  transaction_id: Uuid,
+) -> Result<Vec<LintFindingData>, ErrorResponse> {
+  let findings = lint::lint_content(topic, overview, details);
+  if findings.is_empty() {
+    return Ok(Vec::new());
+  }
+
+  if strict {
+    return Err(error_response(
+      ErrorCode::ValidationFailed,
+      "insight_lint_failed",
+      &lint::describe(&findings),
+      transaction_id,
+    ));
+  }
+
+  context
+    .log_warn(
+      &format!("Insight {topic}/{name} has {} lint warning(s)", findings.len()),
+      "insights-api",
+    )
+    .await;
+
+  Ok(
+    findings
+      .into_iter()
+      .map(|finding| LintFindingData {
+        topic: topic.to_string(),
+        name: name.to_string(),
+        rule: finding.rule.to_string(),
+        message: finding.message,
+      })
+      .collect(),
+  )
 }
 
 /// Load existing insight or return not found error
 fn load_existing_insight(
   request: &UpdateInsightRequest,
   transaction_id: Uuid,
-) -> Result<insight::Insight, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)> {
+) -> Result<insight::Insight, ErrorResponse> {
   insight::load(&request.topic, &request.name)
     .map_err(|e| create_insight_not_found_error(e, transaction_id))
 }
@@ -46,12 +208,29 @@ async fn update_insight_with_embedding(
   insight_data: &mut insight::Insight,
   request: &UpdateInsightRequest,
   transaction_id: Uuid,
-) -> Result<ResponseJson<BaseResponse<()>>, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)>
-{
+) -> Result<(), ErrorResponse> {
+  record_history(context, insight_data).await;
   perform_insight_update(insight_data, request, transaction_id)?;
+  record_history(context, insight_data).await;
+  attempt_full_text_update(context, std::slice::from_ref(insight_data)).await;
   attempt_embedding_update(context, insight_data).await;
+  events::publish(WebhookEvent::Updated, insight_data);
+  webhooks::notify(WebhookEvent::Updated, insight_data).await;
+  sync::auto_commit(&format!("Update {}/{}", insight_data.topic, insight_data.name)).await;
 
-  Ok(ResponseJson(BaseResponse::success((), transaction_id)))
+  Ok(())
+}
+
+/// Snapshot the insight's content into its version history (non-fatal if fails)
+async fn record_history(context: &RequestContext, insight: &insight::Insight) {
+  if let Err(e) = history::snapshot(insight) {
+    context
+      .log_warn(
+        &format!("Failed to record history for {}/{}: {e}", insight.topic, insight.name),
+        "insights-api",
+      )
+      .await;
+  }
 }
 
 /// Perform the actual insight update operation
@@ -59,13 +238,18 @@ fn perform_insight_update(
   insight_data: &mut insight::Insight,
   request: &UpdateInsightRequest,
   transaction_id: Uuid,
-) -> Result<(), (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)> {
-  insight::update(insight_data, request.overview.as_deref(), request.details.as_deref())
-    .map_err(|e| create_insight_update_error(e, transaction_id))
+) -> Result<(), ErrorResponse> {
+  insight::update_with_tags(
+    insight_data,
+    request.overview.as_deref(),
+    request.details.as_deref(),
+    request.tags.as_deref(),
+  )
+  .map_err(|e| create_insight_update_error(e, transaction_id))
 }
 
 /// Attempt to update embedding (non-fatal if fails)
-async fn attempt_embedding_update(context: &RequestContext, insight: &insight::Insight) {
+pub(crate) async fn attempt_embedding_update(context: &RequestContext, insight: &insight::Insight) {
   match generate_and_store_embedding(context, insight).await {
     Ok(_) => {
       log_embedding_update_success(context, insight).await;
@@ -97,27 +281,22 @@ async fn log_embedding_update_warning(context: &RequestContext, error: anyhow::E
 }
 
 /// Create error response for insight not found
-fn create_insight_not_found_error(
-  error: anyhow::Error,
-  transaction_id: Uuid,
-) -> (axum::http::StatusCode, ResponseJson<BaseResponse<()>>) {
-  let api_error = ApiError::new("insight_not_found", &format!("Insight not found: {error}"));
-  (
-    axum::http::StatusCode::NOT_FOUND,
-    ResponseJson(BaseResponse::<()>::error(vec![api_error], transaction_id)),
+fn create_insight_not_found_error(error: anyhow::Error, transaction_id: Uuid) -> ErrorResponse {
+  error_response(
+    ErrorCode::NotFound,
+    "insight_not_found",
+    &format!("Insight not found: {error}"),
+    transaction_id,
   )
 }
 
 /// Create error response for insight update failure
-fn create_insight_update_error(
-  error: anyhow::Error,
-  transaction_id: Uuid,
-) -> (axum::http::StatusCode, ResponseJson<BaseResponse<()>>) {
-  let api_error =
-    ApiError::new("insight_update_failed", &format!("Failed to update insight: {error}"));
-  (
-    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-    ResponseJson(BaseResponse::<()>::error(vec![api_error], transaction_id)),
+fn create_insight_update_error(error: anyhow::Error, transaction_id: Uuid) -> ErrorResponse {
+  error_response(
+    ErrorCode::Internal,
+    "insight_update_failed",
+    &format!("Failed to update insight: {error}"),
+    transaction_id,
   )
 }
 
@@ -125,8 +304,7 @@ fn create_insight_update_error(
 pub async fn remove_insight(
   Extension(context): Extension<RequestContext>,
   Json(request): Json<RemoveInsightRequest>,
-) -> Result<ResponseJson<BaseResponse<()>>, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)>
-{
+) -> Result<ResponseJson<BaseResponse<()>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
 
   let insight_to_delete = load_insight_for_deletion(&request, transaction_id)?;
@@ -137,7 +315,7 @@ pub async fn remove_insight(
 fn load_insight_for_deletion(
   request: &RemoveInsightRequest,
   transaction_id: Uuid,
-) -> Result<insight::Insight, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)> {
+) -> Result<insight::Insight, ErrorResponse> {
   insight::load(&request.topic, &request.name)
     .map_err(|e| create_insight_not_found_error(e, transaction_id))
 }
@@ -148,10 +326,17 @@ async fn delete_insight_with_embedding(
   insight_to_delete: &insight::Insight,
   request: &RemoveInsightRequest,
   transaction_id: Uuid,
-) -> Result<ResponseJson<BaseResponse<()>>, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)>
-{
+) -> Result<ResponseJson<BaseResponse<()>>, ErrorResponse> {
   perform_insight_deletion(insight_to_delete, transaction_id)?;
+  attempt_full_text_removal(context, request).await;
   attempt_embedding_deletion(context, request).await;
+  events::publish(WebhookEvent::Deleted, insight_to_delete);
+  webhooks::notify(WebhookEvent::Deleted, insight_to_delete).await;
+  if let Err(e) = stats::forget(&insight_to_delete.topic, &insight_to_delete.name) {
+    context.log_warn(&format!("Failed to clear access stats: {e}"), "insights-api").await;
+  }
+  sync::auto_commit(&format!("Delete {}/{}", insight_to_delete.topic, insight_to_delete.name))
+    .await;
 
   Ok(ResponseJson(BaseResponse::success((), transaction_id)))
 }
@@ -160,12 +345,33 @@ async fn delete_insight_with_embedding(
 fn perform_insight_deletion(
   insight_to_delete: &insight::Insight,
   transaction_id: Uuid,
-) -> Result<(), (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)> {
+) -> Result<(), ErrorResponse> {
   insight::delete(insight_to_delete).map_err(|e| create_insight_removal_error(e, transaction_id))
 }
 
+/// Keep the full-text index in step with written insights (non-fatal if fails)
+pub(crate) async fn attempt_full_text_update(
+  context: &RequestContext,
+  insights: &[insight::Insight],
+) {
+  if let Err(e) = fulltext::index_insights(insights) {
+    context
+      .log_warn(&format!("Insights saved but full-text indexing failed: {e}"), "insights-api")
+      .await;
+  }
+}
+
+/// Drop a deleted insight from the full-text index (non-fatal if fails)
+async fn attempt_full_text_removal(context: &RequestContext, request: &RemoveInsightRequest) {
+  if let Err(e) = fulltext::remove_insight(&request.topic, &request.name) {
+    context
+      .log_warn(&format!("Insight deleted but full-text removal failed: {e}"), "insights-api")
+      .await;
+  }
+}
+
 /// Attempt to delete embedding (non-fatal if fails)
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn attempt_embedding_deletion(context: &RequestContext, request: &RemoveInsightRequest) {
   match context.vector_db.delete_embedding(&request.topic, &request.name).await {
     Ok(_) => {
@@ -177,14 +383,14 @@ async fn attempt_embedding_deletion(context: &RequestContext, request: &RemoveIn
   }
 }
 
-/// Attempt to delete embedding (no-op without ml-features)
-#[cfg(not(feature = "ml-features"))]
+/// Attempt to delete embedding (no-op without the semantic feature)
+#[cfg(not(feature = "semantic"))]
 async fn attempt_embedding_deletion(_context: &RequestContext, _request: &RemoveInsightRequest) {
-  // No-op: embeddings not available without ml-features
+  // No-op: embeddings not available without the semantic feature
 }
 
 /// Log successful insight deletion with embedding
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn log_embedding_deletion_success(context: &RequestContext, request: &RemoveInsightRequest) {
   context
     .log_success(
@@ -195,7 +401,7 @@ async fn log_embedding_deletion_success(context: &RequestContext, request: &Remo
 }
 
 /// Log embedding deletion warning (non-fatal)
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn log_embedding_deletion_warning(context: &RequestContext, error: anyhow::Error) {
   context
     .log_warn(&format!("Insight deleted but embedding deletion failed: {error}"), "insights-api")
@@ -203,22 +409,17 @@ async fn log_embedding_deletion_warning(context: &RequestContext, error: anyhow:
 }
 
 /// Create error response for insight removal failure
-fn create_insight_removal_error(
-  error: anyhow::Error,
-  transaction_id: Uuid,
-) -> (axum::http::StatusCode, ResponseJson<BaseResponse<()>>) {
-  let api_error =
-    ApiError::new("insight_remove_failed", &format!("Failed to remove insight: {error}"));
-  (
-    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-    ResponseJson(BaseResponse::<()>::error(vec![api_error], transaction_id)),
+fn create_insight_removal_error(error: anyhow::Error, transaction_id: Uuid) -> ErrorResponse {
+  error_response(
+    ErrorCode::Internal,
+    "insight_remove_failed",
+    &format!("Failed to remove insight: {error}"),
+    transaction_id,
   )
 }
 
 /// DELETE /insights/clear - Clear all insights
-pub async fn clear_insights(
-) -> Result<ResponseJson<BaseResponse<()>>, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)>
-{
+pub async fn clear_insights() -> Result<ResponseJson<BaseResponse<()>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
 
   // TODO: Implement clear insights using existing logic
@@ -228,29 +429,108 @@ pub async fn clear_insights(
 /// DELETE /insights/index - Re-index all insights (delete existing index and rebuild)
 pub async fn reindex(
   Extension(context): Extension<RequestContext>,
-) -> Result<ResponseJson<BaseResponse<()>>, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)>
-{
+  Query(query): Query<ReindexQuery>,
+) -> Result<ResponseJson<BaseResponse<ReindexStatusResponse>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
 
-  context.log_info("Starting insight re-indexing process", "insights-api").await;
+  let concurrency = query.concurrency.unwrap_or_else(reindex::get_concurrency);
+  if !(1..=reindex::MAX_CONCURRENCY).contains(&concurrency) {
+    return Err(error_response(
+      ErrorCode::ValidationFailed,
+      "invalid_concurrency",
+      &format!("Concurrency must be between 1 and {}", reindex::MAX_CONCURRENCY),
+      transaction_id,
+    ));
+  }
+
+  let job = reindex::start(reindex::get_batch_size(), concurrency).map_err(|message| {
+    error_response(ErrorCode::AlreadyExists, "reindex_running", &message, transaction_id)
+  })?;
+
+  context
+    .log_info(
+      &format!(
+        "Starting insight re-indexing process ({} per batch, {concurrency} at a time)",
+        job.batch_size
+      ),
+      "insights-api",
+    )
+    .await;
 
-  // Spawn fire-and-forget task to handle re-indexing
-  tokio::spawn(async move {
-    if let Err(e) = perform_reindexing(context.clone()).await {
-      context.log_error(&format!("Re-indexing failed: {e}"), "insights-api").await;
+  // Spawn fire-and-forget task to handle re-indexing; progress is polled from
+  // GET /insights/index/status
+  workspace::spawn(async move {
+    match perform_reindexing(context.clone(), concurrency).await {
+      Ok(()) => reindex::finish(None),
+      Err(e) => {
+        context.log_error(&format!("Re-indexing failed: {e}"), "insights-api").await;
+        reindex::finish(Some(e.to_string()));
+      }
     }
   });
 
   // Return immediately - don't wait for re-indexing to complete
-  Ok(ResponseJson(BaseResponse::success((), transaction_id)))
+  Ok(ResponseJson(BaseResponse::success(job_status(Some(job)), transaction_id)))
+}
+
+/// GET /insights/index/status - Progress of the current or last re-index
+pub async fn index_status(
+) -> Result<ResponseJson<BaseResponse<ReindexStatusResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+  Ok(ResponseJson(BaseResponse::success(job_status(reindex::current()), transaction_id)))
+}
+
+fn job_status(job: Option<reindex::Job>) -> ReindexStatusResponse {
+  let Some(job) = job else {
+    return ReindexStatusResponse {
+      state: JobState::Idle,
+      total: 0,
+      embedded: 0,
+      failed: 0,
+      batch_size: reindex::get_batch_size(),
+      concurrency: reindex::get_concurrency(),
+      started_at: None,
+      finished_at: None,
+      eta_secs: None,
+      error: None,
+    };
+  };
+
+  let eta = match job.state {
+    JobState::Running => job.progress.eta(Utc::now()).map(|eta| eta.as_secs()),
+    _ => None,
+  };
+  ReindexStatusResponse {
+    state: job.state,
+    total: job.progress.total,
+    embedded: job.progress.embedded,
+    failed: job.progress.errors,
+    batch_size: job.batch_size,
+    concurrency: job.concurrency,
+    started_at: Some(job.progress.started_at),
+    finished_at: job.finished_at,
+    eta_secs: eta,
+    error: job.error,
+  }
 }
 
 /// Perform the actual re-indexing process (fire-and-forget)
-async fn perform_reindexing(context: RequestContext) -> Result<()> {
+async fn perform_reindexing(context: RequestContext, concurrency: usize) -> Result<()> {
   let all_insights = load_all_insights_for_reindexing(&context).await?;
+  reindex::record(&reindex::Progress::new(all_insights.len()));
+  match fulltext::rebuild() {
+    Ok(count) => {
+      context
+        .log_info(&format!("Rebuilt full-text index with {count} insights"), "insights-reindex")
+        .await
+    }
+    Err(e) => {
+      context.log_warn(&format!("Full-text index rebuild failed: {e}"), "insights-reindex").await
+    }
+  }
   clear_existing_embeddings(&context).await?;
-  let stats = process_insights_for_embedding(&context, &all_insights).await;
-  log_reindexing_completion(&context, &stats).await;
+  let progress = embed_in_batches(&context, &all_insights, concurrency, reindex::record).await;
+  log_reindexing_completion(&context, &progress).await;
   Ok(())
 }
 
@@ -281,7 +561,7 @@ async fn load_all_insights_for_reindexing(
 }
 
 /// Clear existing embeddings from database to start fresh
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn clear_existing_embeddings(context: &RequestContext) -> Result<()> {
   context.log_info("Starting clean slate database recreation", "insights-reindex").await;
 
@@ -312,91 +592,172 @@ async fn clear_existing_embeddings(context: &RequestContext) -> Result<()> {
   Ok(())
 }
 
-/// Clear existing embeddings (no-op without ml-features)
-#[cfg(not(feature = "ml-features"))]
+/// Clear existing embeddings (no-op without the semantic feature)
+#[cfg(not(feature = "semantic"))]
 async fn clear_existing_embeddings(context: &RequestContext) -> Result<()> {
   context.log_info("Skipping embedding clearing (no ML features)", "insights-reindex").await;
   Ok(())
 }
 
-/// Statistics for tracking re-indexing progress
-#[derive(Debug, Default)]
-struct ReindexingStats {
-  embedded: usize,
-  errors: usize,
-  total: usize,
-}
-
-/// Process all insights for embedding generation
+/// Process all insights for embedding generation in the background
 async fn process_insights_for_embedding(
   context: &RequestContext,
   insights: &[insight::Insight],
-) -> ReindexingStats {
-  let mut stats = ReindexingStats { total: insights.len(), ..Default::default() };
-
-  for (index, insight) in insights.iter().enumerate() {
-    log_progress_if_needed(context, index, &stats).await;
+) -> reindex::Progress {
+  embed_in_batches(context, insights, reindex::get_concurrency(), |_| {}).await
+}
 
-    match generate_and_store_embedding(context, insight).await {
-      Ok(_) => stats.embedded += 1,
-      Err(e) => {
-        stats.errors += 1;
-        context
-          .log_warn(
-            &format!("Failed to generate embedding for {}/{}: {}", insight.topic, insight.name, e),
-            "insights-reindex",
-          )
-          .await;
+/// Embed insights a batch at a time, with up to `concurrency` batches in flight
+///
+/// Model runs take turns on the one embedding model, so concurrency mostly
+/// overlaps one batch's model run with storing the results of another.
+async fn embed_in_batches(
+  context: &RequestContext,
+  insights: &[insight::Insight],
+  concurrency: usize,
+  mut on_progress: impl FnMut(&reindex::Progress),
+) -> reindex::Progress {
+  let mut progress = reindex::Progress::new(insights.len());
+  on_progress(&progress);
+
+  // Batches are handed around as ranges; borrowed slices trip up the
+  // higher-ranked lifetimes of a spawned stream
+  let batch_size = reindex::get_batch_size();
+  let mut batches = stream::iter((0..insights.len()).step_by(batch_size))
+    .map(|start| embed_batch(context, insights, start..(start + batch_size).min(insights.len())))
+    .buffer_unordered(concurrency.max(1));
+
+  while let Some((range, results)) = batches.next().await {
+    for (insight, result) in insights[range].iter().zip(results) {
+      match result {
+        Ok(()) => progress.embedded += 1,
+        Err(e) => {
+          progress.errors += 1;
+          context
+            .log_warn(
+              &format!(
+                "Failed to generate embedding for {}/{}: {}",
+                insight.topic, insight.name, e
+              ),
+              "insights-reindex",
+            )
+            .await;
+        }
       }
     }
-
-    // Rate limiting to prevent overwhelming embedding service
-    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    on_progress(&progress);
+    log_progress(context, &progress).await;
   }
 
-  stats
+  progress
 }
 
-/// Log progress periodically during processing
-async fn log_progress_if_needed(context: &RequestContext, index: usize, stats: &ReindexingStats) {
-  if (index + 1) % 10 == 0 || index == stats.total - 1 {
-    context
-      .log_info(
-        &format!(
-          "Re-indexing progress: {}/{} (embedded: {}, errors: {})",
-          index + 1,
-          stats.total,
-          stats.embedded,
-          stats.errors
-        ),
-        "insights-reindex",
-      )
-      .await;
-  }
+/// Embed one batch once background indexing may go ahead
+async fn embed_batch(
+  context: &RequestContext,
+  insights: &[insight::Insight],
+  range: Range<usize>,
+) -> (Range<usize>, Vec<Result<()>>) {
+  indexing::wait_for_turn().await;
+  let results = generate_and_store_embeddings(context, &insights[range.clone()]).await;
+  (range, results)
+}
+
+/// Log progress after each batch
+async fn log_progress(context: &RequestContext, progress: &reindex::Progress) {
+  let eta = progress
+    .eta(Utc::now())
+    .map_or(String::new(), |eta| format!(", ETA {}", reindex::format_duration(eta)));
+  context
+    .log_info(
+      &format!(
+        "Re-indexing progress: {}/{} (embedded: {}, errors: {}){eta}",
+        progress.done(),
+        progress.total,
+        progress.embedded,
+        progress.errors
+      ),
+      "insights-reindex",
+    )
+    .await;
 }
 
 /// Log final completion statistics
-async fn log_reindexing_completion(context: &RequestContext, stats: &ReindexingStats) {
+async fn log_reindexing_completion(context: &RequestContext, progress: &reindex::Progress) {
+  let elapsed = (Utc::now() - progress.started_at).to_std().unwrap_or_default();
   context
     .log_success(
       &format!(
-        "Re-indexing completed: {}/{} insights embedded successfully, {} errors",
-        stats.embedded, stats.total, stats.errors
+        "Re-indexing completed in {}: {}/{} insights embedded successfully, {} errors",
+        reindex::format_duration(elapsed),
+        progress.embedded,
+        progress.total,
+        progress.errors
       ),
       "insights-reindex",
     )
     .await;
 }
 
+/// Title and content an insight is embedded as
+#[cfg(feature = "semantic")]
+fn embedding_document(insight: &insight::Insight) -> (String, String) {
+  (
+    format!("{}/{}", insight.topic, insight.name),
+    format!("{} {}", insight.overview, insight.details),
+  )
+}
+
+/// Text recorded as what was embedded for a document
+#[cfg(feature = "semantic")]
+fn embedded_text(document_title: &str, document_content: &str) -> String {
+  format!("title: {document_title} | text: {document_content}")
+}
+
+/// Generate embeddings for a batch of insights in one model run and store them in LanceDB
+#[cfg(feature = "semantic")]
+async fn generate_and_store_embeddings(
+  context: &RequestContext,
+  batch: &[insight::Insight],
+) -> Vec<Result<()>> {
+  let documents: Vec<(String, String)> = batch.iter().map(embedding_document).collect();
+
+  let mut results = Vec::with_capacity(batch.len());
+  match crate::server::services::embeddings::create_document_embeddings(&documents).await {
+    Ok(embeddings) => {
+      for ((insight, (title, content)), embedding) in batch.iter().zip(&documents).zip(embeddings) {
+        results.push(store_embedding(context, insight, title, content, embedding).await);
+      }
+    }
+    // One text the model chokes on fails the whole run, so retry one at a time
+    // to keep that failure to the insight that caused it
+    Err(_) => {
+      for insight in batch {
+        results.push(generate_and_store_embedding(context, insight).await);
+      }
+    }
+  }
+  results
+}
+
+/// Generate embeddings for a batch of insights (no-op without the semantic feature)
+#[cfg(not(feature = "semantic"))]
+async fn generate_and_store_embeddings(
+  _context: &RequestContext,
+  batch: &[insight::Insight],
+) -> Vec<Result<()>> {
+  // No-op: ML features not available
+  batch.iter().map(|_| Ok(())).collect()
+}
+
 /// Generate embedding for an insight and store it in LanceDB
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn generate_and_store_embedding(
   context: &RequestContext,
   insight: &insight::Insight,
 ) -> Result<()> {
   // Create document content and title for proper EmbeddingGemma formatting
-  let document_title = format!("{}/{}", insight.topic, insight.name);
-  let document_content = format!("{} {}", insight.overview, insight.details);
+  let (document_title, document_content) = embedding_document(insight);
 
   // Generate embedding using proper document format for EmbeddingGemma
   let embedding = crate::server::services::embeddings::create_document_embedding(
@@ -406,13 +767,36 @@ async fn generate_and_store_embedding(
   .await
   .map_err(|e| anyhow!("Failed to generate document embedding: {}", e))?;
 
+  store_embedding(context, insight, &document_title, &document_content, embedding).await
+}
+
+/// Generate embedding for an insight and store it in LanceDB (no-op without the semantic feature)
+#[cfg(not(feature = "semantic"))]
+async fn generate_and_store_embedding(
+  _context: &RequestContext,
+  _insight: &insight::Insight,
+) -> Result<()> {
+  // No-op: ML features not available
+  Ok(())
+}
+
+/// Store a computed embedding in LanceDB and in the insight file
+#[cfg(feature = "semantic")]
+async fn store_embedding(
+  context: &RequestContext,
+  insight: &insight::Insight,
+  document_title: &str,
+  document_content: &str,
+  embedding: Vec<f32>,
+) -> Result<()> {
   // Store the properly formatted text that was actually embedded
-  let formatted_embedding_text = format!("title: {document_title} | text: {document_content}");
+  let formatted_embedding_text = embedded_text(document_title, document_content);
 
   // Create insight with embedding data, preserving existing temporal metadata
   let mut insight_with_embedding = insight.clone();
-  insight_with_embedding.embedding_version = Some("embeddinggemma-300m".to_string());
-  insight_with_embedding.embedding = Some(embedding.clone());
+  insight_with_embedding.embedding_version =
+    Some(crate::server::services::embeddings::version().await?);
+  insight_with_embedding.embedding = Some(embedding);
   insight_with_embedding.embedding_text = Some(formatted_embedding_text);
   insight_with_embedding.embedding_computed = Some(chrono::Utc::now());
 
@@ -425,18 +809,8 @@ async fn generate_and_store_embedding(
   Ok(())
 }
 
-/// Generate embedding for an insight and store it in LanceDB (no-op without ml-features)
-#[cfg(not(feature = "ml-features"))]
-async fn generate_and_store_embedding(
-  _context: &RequestContext,
-  _insight: &insight::Insight,
-) -> Result<()> {
-  // No-op: ML features not available
-  Ok(())
-}
-
 /// Perform vector similarity search with reranking using LanceDB
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn perform_vector_search(
   context: &RequestContext,
   request: &SearchRequest,
@@ -444,7 +818,7 @@ async fn perform_vector_search(
   let query_text = request.terms.join(" ");
 
   let query_embedding = embed_query(&query_text).await?;
-  let similar_results = initial_search(context, &query_embedding).await?;
+  let similar_results = initial_search(context, &query_embedding, &request.tags).await?;
   let reranked_results = rerank_results(context, &query_text, similar_results).await;
   let final_results = limit_results(reranked_results);
 
@@ -452,7 +826,7 @@ async fn perform_vector_search(
 }
 
 /// Generate query embedding with proper error handling
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn embed_query(query_text: &str) -> Result<Vec<f32>> {
   crate::server::services::embeddings::create_query_embedding(query_text)
     .await
@@ -460,15 +834,19 @@ async fn embed_query(query_text: &str) -> Result<Vec<f32>> {
 }
 
 /// Retrieve initial candidates using permissive settings
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn initial_search(
   context: &RequestContext,
   query_embedding: &[f32],
+  tags: &[String],
 ) -> Result<Vec<crate::server::services::vector_database::VectorSearchResult>> {
   let initial_limit = get_initial_search_limit();
   let initial_threshold = Some(get_initial_search_threshold());
-  let results =
-    context.vector_db.search_similar(query_embedding, initial_limit, initial_threshold).await?;
+  let tags = tag::normalize_all(tags);
+  let results = context
+    .vector_db
+    .search_similar(query_embedding, initial_limit, initial_threshold, &tags)
+    .await?;
 
   if results.is_empty() {
     return Ok(vec![]);
@@ -478,7 +856,7 @@ async fn initial_search(
 }
 
 /// Rerank search candidates using semantic similarity
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn rerank_results(
   context: &RequestContext,
   query_text: &str,
@@ -497,14 +875,14 @@ async fn rerank_results(
 
 // violet ignore chunk - just a bit long because of the object constructors
 /// Rerank a single candidate result
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn score_single_result(
   context: &RequestContext,
   query_text: &str,
   result: VectorSearchResult,
 ) -> Option<SearchResultData> {
   match insight::load(&result.topic, &result.name) {
-    Ok(_full_insight) => {
+    Ok(full_insight) => {
       let doc_text =
         format!("{} {} {} {}", result.topic, result.name, result.overview, result.details);
       let score = compute_relevance_score(query_text, &doc_text, &result).await;
@@ -514,7 +892,11 @@ async fn score_single_result(
         name: result.name,
         overview: result.overview,
         details: result.details,
+        // The file is authoritative; the index may predate a tag change
+        tags: full_insight.tags,
         score,
+        ranking: None,
+        explanation: None,
       })
     }
     Err(e) => {
@@ -537,7 +919,7 @@ async fn score_single_result(
 }
 
 /// Compute reranking score with fallback
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn compute_relevance_score(
   query_text: &str,
   doc_text: &str,
@@ -556,7 +938,7 @@ async fn compute_relevance_score(
 }
 
 /// Sort and limit reranked search results
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 fn limit_results(mut reranked_results: Vec<SearchResultData>) -> Vec<SearchResultData> {
   reranked_results
     .sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
@@ -566,8 +948,8 @@ fn limit_results(mut reranked_results: Vec<SearchResultData>) -> Vec<SearchResul
   reranked_results
 }
 
-/// Perform vector similarity search using LanceDB (no-op without ml-features)
-#[cfg(not(feature = "ml-features"))]
+/// Perform vector similarity search using LanceDB (no-op without the semantic feature)
+#[cfg(not(feature = "semantic"))]
 async fn perform_vector_search(
   _context: &RequestContext,
   _request: &SearchRequest,
@@ -576,11 +958,130 @@ async fn perform_vector_search(
   Ok(vec![])
 }
 
+/// GET /insights/scan - Audit stored insights for secrets and PII
+pub async fn scan_insights(
+  Extension(context): Extension<RequestContext>,
+) -> Result<ResponseJson<BaseResponse<ScanResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let results = sensitive::scan_all().map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "insights_scan_failed",
+      &format!("Failed to scan insights: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let findings: Vec<SensitiveFindingData> = results
+    .into_iter()
+    .flat_map(|result| {
+      let (topic, name) = (result.topic, result.name);
+      result.findings.into_iter().map(move |finding| SensitiveFindingData {
+        topic: topic.clone(),
+        name: name.clone(),
+        kind: finding.kind.to_string(),
+        line: finding.line,
+        excerpt: finding.excerpt,
+      })
+    })
+    .collect();
+
+  context
+    .log_info(&format!("Sensitive content scan found {} matches", findings.len()), "insights-api")
+    .await;
+
+  Ok(ResponseJson(BaseResponse::success(ScanResponse { findings }, transaction_id)))
+}
+
+/// GET /insights/lint - Check stored insights against the content lint rules
+pub async fn lint_insights(
+  Extension(context): Extension<RequestContext>,
+  Query(query): Query<LintQuery>,
+) -> Result<ResponseJson<BaseResponse<LintResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let results = lint::lint_all(query.topic.as_deref()).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "insights_lint_failed",
+      &format!("Failed to lint insights: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let findings: Vec<LintFindingData> = results
+    .into_iter()
+    .flat_map(|result| {
+      let (topic, name) = (result.topic, result.name);
+      result.findings.into_iter().map(move |finding| LintFindingData {
+        topic: topic.clone(),
+        name: name.clone(),
+        rule: finding.rule.to_string(),
+        message: finding.message,
+      })
+    })
+    .collect();
+
+  context.log_info(&format!("Lint found {} issues", findings.len()), "insights-api").await;
+
+  Ok(ResponseJson(BaseResponse::success(LintResponse { findings }, transaction_id)))
+}
+
+/// POST /insights/validate - Check stored frontmatter against the schema, repairing it on request
+pub async fn validate_insights(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<ValidateRequest>,
+) -> Result<ResponseJson<BaseResponse<ValidateResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let checks = schema::validate_all(request.topic.as_deref(), request.fix).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "insights_validate_failed",
+      &format!("Failed to validate insights: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  // Repaired files are reloaded so search sees the corrected metadata
+  let fixed: Vec<insight::Insight> = checks
+    .iter()
+    .filter(|check| check.fixed)
+    .filter_map(|check| insight::load(&check.topic, &check.name).ok())
+    .collect();
+  if !fixed.is_empty() {
+    attempt_full_text_update(&context, &fixed).await;
+    sync::auto_commit(&format!("Repair frontmatter of {} insight(s)", fixed.len())).await;
+  }
+
+  let results: Vec<ValidationResultData> = checks
+    .into_iter()
+    .map(|check| ValidationResultData {
+      problems: check.problems.iter().map(schema_problem_data).collect(),
+      topic: check.topic,
+      name: check.name,
+      fixed: check.fixed,
+    })
+    .collect();
+
+  context
+    .log_info(
+      &format!(
+        "Validation found {} insight(s) with problems, fixed {}",
+        results.len(),
+        fixed.len()
+      ),
+      "insights-api",
+    )
+    .await;
+
+  Ok(ResponseJson(BaseResponse::success(ValidateResponse { results }, transaction_id)))
+}
+
 /// GET /insights/list/topics - List all topics
-pub async fn list_topics() -> Result<
-  ResponseJson<BaseResponse<ListTopicsResponse>>,
-  (axum::http::StatusCode, ResponseJson<BaseResponse<()>>),
-> {
+pub async fn list_topics() -> Result<ResponseJson<BaseResponse<ListTopicsResponse>>, ErrorResponse>
+{
   let transaction_id = Uuid::new_v4();
 
   match insight::get_topics() {
@@ -588,32 +1089,40 @@ pub async fn list_topics() -> Result<
       let response = ListTopicsResponse { topics };
       Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
     }
-    Err(e) => {
-      let error = ApiError::new("topics_list_failed", &format!("Failed to list topics: {e}"));
-      Err((
-        axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-        ResponseJson(BaseResponse::<()>::error(vec![error], transaction_id)),
-      ))
-    }
+    Err(e) => Err(error_response(
+      ErrorCode::Internal,
+      "topics_list_failed",
+      &format!("Failed to list topics: {e}"),
+      transaction_id,
+    )),
   }
 }
 
-/// GET /insights/list/insights - List insights with optional filtering  
-pub async fn list_insights() -> Result<
-  ResponseJson<BaseResponse<ListInsightsResponse>>,
-  (axum::http::StatusCode, ResponseJson<BaseResponse<()>>),
-> {
+/// GET /insights/list/insights - List insights, optionally by topic and tags
+pub async fn list_insights(
+  Query(query): Query<ListInsightsQuery>,
+) -> Result<ResponseJson<BaseResponse<ListInsightsResponse>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
 
-  // For now, ignore filters and get all insights - we can add filtering later
-  match insight::get_insights(None) {
+  // Missing or unreadable policies shouldn't prevent listing
+  let policies = retention::load_policies().unwrap_or_default();
+  let tags: Vec<String> = query
+    .tags
+    .as_deref()
+    .map(|tags| tags.split(',').map(str::to_string).collect())
+    .unwrap_or_default();
+
+  match insight::get_insights(query.topic.as_deref()) {
     Ok(insights) => {
       let insight_summaries: Vec<InsightSummary> = insights
         .into_iter()
+        .filter(|insight| tag::has_all(&insight.tags, &tags))
         .map(|insight| InsightSummary {
+          expires_at: retention::expires_at(&policies, &insight),
           topic: insight.topic,
           name: insight.name,
           overview: insight.overview,
+          tags: insight.tags,
           created_at: insight.embedding_computed.unwrap_or_else(Utc::now),
           updated_at: insight.embedding_computed.unwrap_or_else(Utc::now),
         })
@@ -622,13 +1131,33 @@ pub async fn list_insights() -> Result<
       let response = ListInsightsResponse { insights: insight_summaries };
       Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
     }
-    Err(e) => {
-      let error = ApiError::new("insights_list_failed", &format!("Failed to list insights: {e}"));
-      Err((
-        axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-        ResponseJson(BaseResponse::<()>::error(vec![error], transaction_id)),
-      ))
+    Err(e) => Err(error_response(
+      ErrorCode::Internal,
+      "insights_list_failed",
+      &format!("Failed to list insights: {e}"),
+      transaction_id,
+    )),
+  }
+}
+
+/// GET /insights/list/tags - Every tag in use with how many insights carry it
+pub async fn list_tags() -> Result<ResponseJson<BaseResponse<ListTagsResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  match insight::get_insights(None) {
+    Ok(insights) => {
+      let tags = tag::counts(&insights)
+        .into_iter()
+        .map(|(tag, count)| TagCountData { tag, count })
+        .collect();
+      Ok(ResponseJson(BaseResponse::success(ListTagsResponse { tags }, transaction_id)))
     }
+    Err(e) => Err(error_response(
+      ErrorCode::Internal,
+      "tags_list_failed",
+      &format!("Failed to list tags: {e}"),
+      transaction_id,
+    )),
   }
 }
 
@@ -637,14 +1166,446 @@ pub async fn list_insights() -> Result<
 pub async fn add_insight(
   Extension(context): Extension<RequestContext>,
   Json(request): Json<AddInsightRequest>,
-) -> Result<ResponseJson<BaseResponse<()>>, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)>
-{
+) -> Result<ResponseJson<BaseResponse<WriteInsightResponse>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
 
   log_insight_addition_start(&context, &request).await;
+  validate_schema(&request.topic, &request.name, &request.tags, transaction_id)?;
+  reject_sensitive_content(
+    &context,
+    &request.overview,
+    &request.details,
+    request.allow_sensitive,
+    transaction_id,
+  )
+  .await?;
+  let warnings = lint_write(
+    &context,
+    &request.topic,
+    &request.name,
+    &request.overview,
+    &request.details,
+    request.strict,
+    transaction_id,
+  )
+  .await?;
   let new_insight = create_insight_from_request(request);
 
-  save_insight_with_embedding(&context, &new_insight, transaction_id).await
+  save_insight_with_embedding(&context, &new_insight, transaction_id).await?;
+
+  Ok(ResponseJson(BaseResponse::success(WriteInsightResponse { warnings }, transaction_id)))
+}
+
+/// POST /insights/import - Add many insights at once, embedding them after all are written
+pub async fn import_insights(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<ImportInsightsRequest>,
+) -> Result<ResponseJson<BaseResponse<ImportInsightsResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  context
+    .log_info(&format!("Importing {} insights", request.insights.len()), "insights-import")
+    .await;
+
+  let problems = import::validate_entries(&request.insights);
+  if !problems.is_empty() {
+    return Err(error_response(
+      ErrorCode::ValidationFailed,
+      "insight_import_invalid",
+      &format!("Import rejected: {}", problems.join("; ")),
+      transaction_id,
+    ));
+  }
+
+  reject_sensitive_imports(&context, &request, transaction_id).await?;
+
+  let outcome = import::import(request.insights, request.on_conflict).map_err(|e| {
+    let code = if request.on_conflict == ConflictPolicy::Fail {
+      ErrorCode::AlreadyExists
+    } else {
+      ErrorCode::Internal
+    };
+    error_response(code, "insight_import_failed", &format!("Import failed: {e}"), transaction_id)
+  })?;
+
+  context
+    .log_success(
+      &format!(
+        "Imported insights: {} added, {} merged, {} skipped",
+        outcome.added.len(),
+        outcome.merged.len(),
+        outcome.skipped.len()
+      ),
+      "insights-import",
+    )
+    .await;
+
+  attempt_full_text_update(&context, &outcome.written).await;
+  for written in &outcome.written {
+    let id = format!("{}/{}", written.topic, written.name);
+    let event =
+      if outcome.added.contains(&id) { WebhookEvent::Created } else { WebhookEvent::Updated };
+    events::publish(event, written);
+    webhooks::notify(event, written).await;
+  }
+  sync::auto_commit(&format!("Import {} insights", outcome.written.len())).await;
+
+  // Embed everything in one background pass once the files are all on disk
+  let embeddings_queued = outcome.written.len();
+  let written = outcome.written;
+  let embedding_context = context.clone();
+  workspace::spawn(async move {
+    let stats = process_insights_for_embedding(&embedding_context, &written).await;
+    log_reindexing_completion(&embedding_context, &stats).await;
+  });
+
+  let response = ImportInsightsResponse {
+    added: outcome.added,
+    merged: outcome.merged,
+    skipped: outcome.skipped,
+    embeddings_queued,
+  };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// Refuse an import if any entry looks like it contains secrets or PII
+async fn reject_sensitive_imports(
+  context: &RequestContext,
+  request: &ImportInsightsRequest,
+  transaction_id: Uuid,
+) -> Result<(), ErrorResponse> {
+  let flagged: Vec<String> = request
+    .insights
+    .iter()
+    .filter_map(|entry| {
+      let findings = sensitive::scan_content(&entry.overview, &entry.details);
+      (!findings.is_empty())
+        .then(|| format!("{}/{}: {}", entry.topic, entry.name, sensitive::describe(&findings)))
+    })
+    .collect();
+
+  if flagged.is_empty() {
+    return Ok(());
+  }
+
+  if request.allow_sensitive {
+    context
+      .log_warn(
+        &format!("Importing {} insight(s) with sensitive matches on request", flagged.len()),
+        "insights-import",
+      )
+      .await;
+    return Ok(());
+  }
+
+  Err(error_response(
+    ErrorCode::SensitiveContent,
+    "insight_sensitive_content",
+    &flagged.join("; "),
+    transaction_id,
+  ))
+}
+
+/// POST /insights/batch - Apply creates, updates and deletes all at once or not at all
+pub async fn batch_insights(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<BatchRequest>,
+) -> Result<ResponseJson<BaseResponse<BatchResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  context
+    .log_info(
+      &format!("Applying batch of {} operations", request.operations.len()),
+      "insights-batch",
+    )
+    .await;
+
+  let outcome = batch::apply(&request).map_err(|rejection| {
+    error_response_with_context(
+      rejection.code,
+      "insight_batch_failed",
+      &rejection.message,
+      serde_json::json!({ "results": rejection.results }),
+      transaction_id,
+    )
+  })?;
+
+  let written = publish_batch_changes(&context, &outcome.changes).await;
+  if !outcome.changes.is_empty() {
+    sync::auto_commit(&format!("Apply batch of {} operations", outcome.changes.len())).await;
+  }
+
+  context
+    .log_success(
+      &format!("Applied batch of {} operations", outcome.changes.len()),
+      "insights-batch",
+    )
+    .await;
+
+  // Embed everything in one background pass, as an import does
+  let embeddings_queued = written.len();
+  let embedding_context = context.clone();
+  workspace::spawn(async move {
+    let stats = process_insights_for_embedding(&embedding_context, &written).await;
+    log_reindexing_completion(&embedding_context, &stats).await;
+  });
+
+  let response = BatchResponse { results: outcome.results, embeddings_queued };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// Index, record and announce an applied batch, returning the insights left to embed
+async fn publish_batch_changes(
+  context: &RequestContext,
+  changes: &[batch::Change],
+) -> Vec<insight::Insight> {
+  let mut written = Vec::new();
+  for change in changes {
+    match change {
+      batch::Change::Created(created) => {
+        events::publish(WebhookEvent::Created, created);
+        webhooks::notify(WebhookEvent::Created, created).await;
+        written.push(created.clone());
+      }
+      batch::Change::Updated { before, after } => {
+        record_history(context, before).await;
+        record_history(context, after).await;
+        events::publish(WebhookEvent::Updated, after);
+        webhooks::notify(WebhookEvent::Updated, after).await;
+        written.push(after.clone());
+      }
+      batch::Change::Deleted(deleted) => {
+        let request =
+          RemoveInsightRequest { topic: deleted.topic.clone(), name: deleted.name.clone() };
+        attempt_full_text_removal(context, &request).await;
+        attempt_embedding_deletion(context, &request).await;
+        if let Err(e) = stats::forget(&deleted.topic, &deleted.name) {
+          context.log_warn(&format!("Failed to clear access stats: {e}"), "insights-batch").await;
+        }
+        events::publish(WebhookEvent::Deleted, deleted);
+        webhooks::notify(WebhookEvent::Deleted, deleted).await;
+      }
+    }
+  }
+  attempt_full_text_update(context, &written).await;
+  written
+}
+
+/// POST /insights/export - Serialize insights (and optionally embeddings) into an archive
+pub async fn export_insights(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<ExportInsightsRequest>,
+) -> Result<ResponseJson<BaseResponse<InsightsArchive>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let topic_filter = request.topic.as_deref().map(str::to_lowercase);
+  let insights = insight::get_insights(topic_filter.as_deref()).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "insight_export_failed",
+      &format!("Failed to load insights for export: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  if let (Some(topic), true) = (&request.topic, insights.is_empty()) {
+    return Err(error_response(
+      ErrorCode::NotFound,
+      "topic_not_found",
+      &format!("Topic '{topic}' has no insights"),
+      transaction_id,
+    ));
+  }
+
+  let embeddings = if request.include_embeddings {
+    load_export_embeddings(&context, &insights).await
+  } else {
+    export::EmbeddingMap::new()
+  };
+
+  let archive = export::build_archive(insights, embeddings);
+  context
+    .log_success(
+      &format!(
+        "Exported {} insights across {} topics",
+        archive.insights.len(),
+        archive.topics.len()
+      ),
+      "insights-export",
+    )
+    .await;
+
+  Ok(ResponseJson(BaseResponse::success(archive, transaction_id)))
+}
+
+/// POST /insights/bootstrap - Populate a fresh knowledge base from a seed archive
+pub async fn bootstrap_insights(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<BootstrapRequest>,
+) -> Result<ResponseJson<BaseResponse<BootstrapResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let fresh = bootstrap::is_fresh().map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "bootstrap_failed",
+      &format!("Failed to inspect the knowledge base: {e}"),
+      transaction_id,
+    )
+  })?;
+  if !fresh && !request.force {
+    return Err(error_response(
+      ErrorCode::AlreadyExists,
+      "knowledge_base_not_empty",
+      "The knowledge base already has insights; bootstrap with force to restore into it anyway",
+      transaction_id,
+    ));
+  }
+
+  context.log_info(&format!("Bootstrapping from {}", request.source), "insights-bootstrap").await;
+
+  let archive =
+    bootstrap::load_seed(&request.source, request.sha256.as_deref()).await.map_err(|e| {
+      error_response(
+        ErrorCode::ValidationFailed,
+        "bootstrap_seed_invalid",
+        &format!("Seed rejected: {e}"),
+        transaction_id,
+      )
+    })?;
+
+  let restored = bootstrap::restore(archive, request.force).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "bootstrap_failed",
+      &format!("Bootstrap failed: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  attempt_full_text_update(&context, &restored.insights).await;
+  sync::auto_commit(&format!("Bootstrap {} insights", restored.insights.len())).await;
+
+  let names = restored.insights.iter().map(|i| format!("{}/{}", i.topic, i.name)).collect();
+  let total = restored.insights.len();
+  let pending = store_seeded_embeddings(&context, restored.insights).await;
+  let embeddings_queued = pending.len();
+
+  context
+    .log_success(
+      &format!(
+        "Bootstrapped {total} insights: {} embeddings restored, {embeddings_queued} to compute",
+        total - embeddings_queued
+      ),
+      "insights-bootstrap",
+    )
+    .await;
+
+  let embedding_context = context.clone();
+  workspace::spawn(async move {
+    let stats = process_insights_for_embedding(&embedding_context, &pending).await;
+    log_reindexing_completion(&embedding_context, &stats).await;
+  });
+
+  let response = BootstrapResponse {
+    restored: names,
+    skipped: restored.skipped,
+    embeddings_restored: total - embeddings_queued,
+    embeddings_queued,
+  };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// Store the embeddings a seed shipped with, returning the insights still without one
+#[cfg(feature = "semantic")]
+async fn store_seeded_embeddings(
+  context: &RequestContext,
+  insights: Vec<insight::Insight>,
+) -> Vec<insight::Insight> {
+  // Seeded vectors are only usable by the provider that answers queries, so they
+  // are recorded under its version; without one, everything is recomputed later
+  let version = match crate::server::services::embeddings::version().await {
+    Ok(version) => version,
+    Err(e) => {
+      context
+        .log_warn(&format!("Not restoring seeded embeddings: {e}"), "insights-bootstrap")
+        .await;
+      return insights
+        .into_iter()
+        .map(|mut seeded| {
+          insight::clear_embedding(&mut seeded);
+          seeded
+        })
+        .collect();
+    }
+  };
+
+  let mut pending = Vec::new();
+  for mut seeded in insights {
+    if seeded.embedding.is_none() {
+      pending.push(seeded);
+      continue;
+    }
+
+    let (title, content) = embedding_document(&seeded);
+    seeded.embedding_version = Some(version.clone());
+    seeded.embedding_text = Some(embedded_text(&title, &content));
+    seeded.embedding_computed = Some(Utc::now());
+    if let Err(e) = context.vector_db.store_embedding(&seeded).await {
+      context
+        .log_warn(
+          &format!("Recomputing embedding for {}/{}: {e}", seeded.topic, seeded.name),
+          "insights-bootstrap",
+        )
+        .await;
+      insight::clear_embedding(&mut seeded);
+      pending.push(seeded);
+    }
+  }
+  pending
+}
+
+/// Without ml-features there is nowhere to store embeddings, so all are pending
+#[cfg(not(feature = "semantic"))]
+async fn store_seeded_embeddings(
+  _context: &RequestContext,
+  insights: Vec<insight::Insight>,
+) -> Vec<insight::Insight> {
+  insights
+}
+
+/// Stored embeddings for every exported topic; missing ones are simply left out
+#[cfg(feature = "semantic")]
+async fn load_export_embeddings(
+  context: &RequestContext,
+  insights: &[insight::Insight],
+) -> export::EmbeddingMap {
+  let topics: std::collections::BTreeSet<String> =
+    insights.iter().map(|i| i.topic.to_lowercase()).collect();
+
+  let mut embeddings = export::EmbeddingMap::new();
+  for topic in topics {
+    match context.vector_db.topic_embeddings(&topic).await {
+      Ok(stored) => {
+        embeddings.extend(stored.into_iter().map(|(name, vector)| ((topic.clone(), name), vector)))
+      }
+      Err(e) => {
+        context
+          .log_warn(&format!("Exporting {topic} without embeddings: {e}"), "insights-export")
+          .await
+      }
+    }
+  }
+  embeddings
+}
+
+/// Stored embeddings for every exported topic (none without the semantic feature)
+#[cfg(not(feature = "semantic"))]
+async fn load_export_embeddings(
+  _context: &RequestContext,
+  _insights: &[insight::Insight],
+) -> export::EmbeddingMap {
+  export::EmbeddingMap::new()
 }
 
 /// Log the start of insight addition operation
@@ -656,7 +1617,10 @@ async fn log_insight_addition_start(context: &RequestContext, request: &AddInsig
 
 /// Create a new insight from the API request
 fn create_insight_from_request(request: AddInsightRequest) -> insight::Insight {
-  insight::Insight::new(request.topic, request.name, request.overview, request.details)
+  let mut new_insight =
+    insight::Insight::new(request.topic, request.name, request.overview, request.details);
+  new_insight.tags = tag::normalize_all(&request.tags);
+  new_insight
 }
 
 /// Save insight and attempt to generate embedding
@@ -664,14 +1628,17 @@ async fn save_insight_with_embedding(
   context: &RequestContext,
   new_insight: &insight::Insight,
   transaction_id: Uuid,
-) -> Result<ResponseJson<BaseResponse<()>>, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)>
-{
+) -> Result<(), ErrorResponse> {
   insight::save(new_insight)
     .map_err(|e| create_insight_save_error(context, new_insight, e, transaction_id))?;
 
+  attempt_full_text_update(context, std::slice::from_ref(new_insight)).await;
   attempt_embedding_generation(context, new_insight).await;
+  events::publish(WebhookEvent::Created, new_insight);
+  webhooks::notify(WebhookEvent::Created, new_insight).await;
+  sync::auto_commit(&format!("Add {}/{}", new_insight.topic, new_insight.name)).await;
 
-  Ok(ResponseJson(BaseResponse::success((), transaction_id)))
+  Ok(())
 }
 
 /// Attempt to generate and store embedding (non-fatal if fails)
@@ -709,7 +1676,7 @@ fn create_insight_save_error(
   insight: &insight::Insight,
   error: anyhow::Error,
   transaction_id: Uuid,
-) -> (axum::http::StatusCode, ResponseJson<BaseResponse<()>>) {
+) -> ErrorResponse {
   // Spawn async logging to avoid blocking the error response
   tokio::spawn({
     let context = context.clone();
@@ -721,21 +1688,26 @@ fn create_insight_save_error(
     }
   });
 
-  let api_error = ApiError::new("insight_add_failed", &format!("Failed to add insight: {error}"));
-  (
-    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-    ResponseJson(BaseResponse::<()>::error(vec![api_error], transaction_id)),
+  let code =
+    if insight_already_exists(insight) { ErrorCode::AlreadyExists } else { ErrorCode::Internal };
+  error_response(
+    code,
+    "insight_add_failed",
+    &format!("Failed to add insight: {error}"),
+    transaction_id,
   )
 }
 
+/// Whether a save failed because the insight is already on disk
+fn insight_already_exists(insight: &insight::Insight) -> bool {
+  insight::file_path(insight).is_ok_and(|path| path.exists())
+}
+
 /// POST /insights/get - Get a specific insight
 pub async fn get_insight(
   Extension(context): Extension<RequestContext>,
   Json(request): Json<GetInsightRequest>,
-) -> Result<
-  ResponseJson<BaseResponse<GetInsightResponse>>,
-  (axum::http::StatusCode, ResponseJson<BaseResponse<()>>),
-> {
+) -> Result<ResponseJson<BaseResponse<GetInsightResponse>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
 
   context
@@ -750,12 +1722,19 @@ pub async fn get_insight(
           "insights-api",
         )
         .await;
+      record_access(
+        &context,
+        stats::Access::Read,
+        [(insight_data.topic.as_str(), insight_data.name.as_str())],
+      )
+      .await;
 
       let insight = InsightData {
         topic: insight_data.topic,
         name: insight_data.name,
         overview: insight_data.overview,
         details: if request.overview_only { String::new() } else { insight_data.details },
+        tags: insight_data.tags,
         embedding_version: insight_data.embedding_version,
         embedding_computed: insight_data.embedding_computed,
       };
@@ -769,10 +1748,11 @@ pub async fn get_insight(
           "insights-api",
         )
         .await;
-      let error = ApiError::new("insight_get_failed", &format!("Failed to get insight: {e}"));
-      Err((
-        axum::http::StatusCode::NOT_FOUND,
-        ResponseJson(BaseResponse::<()>::error(vec![error], transaction_id)),
+      Err(error_response(
+        ErrorCode::NotFound,
+        "insight_get_failed",
+        &format!("Failed to get insight: {e}"),
+        transaction_id,
       ))
     }
   }
@@ -782,26 +1762,225 @@ pub async fn get_insight(
 pub async fn search_insights(
   Extension(context): Extension<RequestContext>,
   Json(request): Json<SearchRequest>,
-) -> Result<
-  ResponseJson<BaseResponse<SearchResponse>>,
-  (axum::http::StatusCode, ResponseJson<BaseResponse<()>>),
-> {
+) -> Result<ResponseJson<BaseResponse<SearchResponse>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
 
-  log_search_start(&context, &request).await;
-  let search_options = build_search_options(&request);
+  let popularity_weight = request.popularity_weight.unwrap_or_else(search::get_popularity_weight);
+  validate_popularity_weight(popularity_weight, transaction_id)?;
+  let ranking = ranking_config(&context, &request, transaction_id).await?;
 
-  let mut all_results =
-    perform_term_search(&context, &request, &search_options, transaction_id).await?;
+  // Retrieval only sees the plain words; the rest of the query filters what it finds
+  let query = parse_search_query(&request.terms, transaction_id)?;
+  let request = SearchRequest { terms: query.terms.clone(), ..request };
+  let mut results = if query.terms.is_empty() && query.has_filters() {
+    list_filtered_insights(&request, &query, transaction_id)?
+  } else {
+    let mut results = hybrid_search(&context, &request, transaction_id).await?;
+    let mut updated = HashMap::new();
+    results.retain(|result| match insight::load(&result.topic, &result.name) {
+      Ok(found) if query.matches(&found) => {
+        updated.insert((result.topic.clone(), result.name.clone()), found.last_updated);
+        true
+      }
+      _ => false,
+    });
+    let last_updated =
+      |topic: &str, name: &str| updated.get(&(topic.to_string(), name.to_string())).copied();
+    search::apply_ranking(&mut results, &ranking, last_updated, Utc::now());
+    results
+  };
+  boost_popular_results(&context, &mut results, popularity_weight).await;
+  if request.explain {
+    explain_matches(&mut results, &query.terms, &request);
+  }
+  let hits = results.iter().map(|result| (result.topic.as_str(), result.name.as_str()));
+  record_access(&context, stats::Access::SearchHit, hits).await;
 
-  let should_finalize = add_embedding_search_results(&context, &request, &mut all_results).await;
+  let response_data = SearchResponse { count: results.len(), results };
+  Ok(ResponseJson(BaseResponse::success(response_data, transaction_id)))
+}
 
-  if should_finalize {
-    Ok(ResponseJson(finalize_search_results(&context, &request, all_results, transaction_id).await))
-  } else {
+/// Parse the search terms as query syntax
+fn parse_search_query(
+  terms: &[String],
+  transaction_id: Uuid,
+) -> Result<SearchQuery, ErrorResponse> {
+  SearchQuery::parse(&terms.join(" ")).map_err(|message| {
+    error_response(ErrorCode::ValidationFailed, "search_query_invalid", &message, transaction_id)
+  })
+}
+
+/// Every insight passing a query that has filters but nothing to search for, newest first
+fn list_filtered_insights(
+  request: &SearchRequest,
+  query: &SearchQuery,
+  transaction_id: Uuid,
+) -> Result<Vec<SearchResultData>, ErrorResponse> {
+  let mut insights = insight::get_insights(None).map_err(|e| {
+    create_search_error_response(&format!("Failed to list insights: {e}"), transaction_id)
+  })?;
+  insights.retain(|found| {
+    request.topic.as_deref().is_none_or(|prefix| topic::within(&found.topic, prefix))
+      && tag::has_all(&found.tags, &request.tags)
+      && query.matches(found)
+  });
+  insights.sort_by(|a, b| b.last_updated.cmp(&a.last_updated));
+
+  Ok(
+    insights
+      .into_iter()
+      .map(|found| SearchResultData {
+        topic: found.topic,
+        name: found.name,
+        overview: found.overview,
+        details: found.details,
+        tags: found.tags,
+        score: 0.0,
+        ranking: None,
+        explanation: None,
+      })
+      .collect(),
+  )
+}
+
+/// Reject negative popularity weights
+fn validate_popularity_weight(weight: f32, transaction_id: Uuid) -> Result<(), ErrorResponse> {
+  if weight.is_finite() && weight >= 0.0 {
+    return Ok(());
+  }
+  Err(error_response(
+    ErrorCode::ValidationFailed,
+    "search_request_invalid",
+    &format!("popularity_weight must be a non-negative number, got {weight}"),
+    transaction_id,
+  ))
+}
+
+/// Ranking from ranking.yaml with the request's overrides (defaults if the file is unreadable)
+async fn ranking_config(
+  context: &RequestContext,
+  request: &SearchRequest,
+  transaction_id: Uuid,
+) -> Result<ranking::RankingConfig, ErrorResponse> {
+  let config = match ranking::load_config() {
+    Ok(config) => config,
+    Err(e) => {
+      context.log_warn(&format!("Failed to load ranking config: {e}"), "insights-api").await;
+      ranking::RankingConfig::default()
+    }
+  };
+  let config = config.with_overrides(
+    request.recency_weight,
+    request.recency_half_life_days,
+    &request.topic_boosts,
+  );
+  config.validate().map_err(|e| {
+    error_response(
+      ErrorCode::ValidationFailed,
+      "search_request_invalid",
+      &e.to_string(),
+      transaction_id,
+    )
+  })?;
+  Ok(config)
+}
+
+/// Re-rank results in favour of frequently read insights (skipped if stats are unreadable)
+async fn boost_popular_results(
+  context: &RequestContext,
+  results: &mut [SearchResultData],
+  weight: f32,
+) {
+  if weight <= 0.0 {
+    return;
+  }
+  match stats::load() {
+    Ok(log) => search::boost_by_popularity(results, &log, weight),
+    Err(e) => {
+      context.log_warn(&format!("Failed to load access stats: {e}"), "insights-api").await;
+    }
+  }
+}
+
+/// Count accesses in the usage stats (non-fatal if it fails)
+async fn record_access<'a>(
+  context: &RequestContext,
+  access: stats::Access,
+  insights: impl IntoIterator<Item = (&'a str, &'a str)>,
+) {
+  if let Err(e) = stats::record(access, insights, Utc::now()) {
+    context.log_warn(&format!("Failed to record access stats: {e}"), "insights-api").await;
+  }
+}
+
+/// Keyword search merged with embedding search when embeddings exist, best match first
+pub async fn hybrid_search(
+  context: &RequestContext,
+  request: &SearchRequest,
+  transaction_id: Uuid,
+) -> Result<Vec<SearchResultData>, ErrorResponse> {
+  log_search_start(context, request).await;
+  validate_hybrid_weight(request, transaction_id)?;
+
+  if request.mode == SearchMode::Semantic {
+    return perform_semantic_only_search(context, request, transaction_id).await;
+  }
+
+  let mut keyword_results = perform_keyword_search(context, request, transaction_id).await?;
+  explain_path(request, &mut keyword_results, MatchPath::Keyword);
+
+  if request.mode == SearchMode::FullText {
+    return Ok(finalize_search_results(context, request, keyword_results).await);
+  }
+
+  let mut semantic_results = Vec::new();
+  let should_finalize = add_embedding_search_results(context, request, &mut semantic_results).await;
+
+  if !should_finalize {
     // No embeddings available - return results as-is
-    let response_data = SearchResponse { count: all_results.len(), results: all_results };
-    Ok(ResponseJson(BaseResponse::success(response_data, transaction_id)))
+    return Ok(keyword_results);
+  }
+  explain_path(request, &mut semantic_results, MatchPath::Semantic);
+
+  let all_results = if semantic_results.is_empty() {
+    keyword_results
+  } else {
+    search::fuse(keyword_results, semantic_results, request.hybrid_weight)
+  };
+  Ok(finalize_search_results(context, request, all_results).await)
+}
+
+/// Fill in where the terms occur in each explained result
+fn explain_matches(results: &mut [SearchResultData], terms: &[String], request: &SearchRequest) {
+  for result in results.iter_mut() {
+    let matches =
+      search::term_matches(result, terms, request.case_sensitive, request.overview_only);
+    if let Some(explanation) = result.explanation.as_mut() {
+      explanation.matches = matches;
+    }
+  }
+}
+
+/// Record which path found each result when the request asks for explanations
+fn explain_path(request: &SearchRequest, results: &mut [SearchResultData], path: MatchPath) {
+  if request.explain {
+    search::mark_path(results, path);
+  }
+}
+
+/// Reject hybrid weights outside 0.0-1.0
+fn validate_hybrid_weight(
+  request: &SearchRequest,
+  transaction_id: Uuid,
+) -> Result<(), ErrorResponse> {
+  match request.hybrid_weight {
+    Some(weight) if !(0.0..=1.0).contains(&weight) => Err(error_response(
+      ErrorCode::ValidationFailed,
+      "search_request_invalid",
+      &format!("hybrid_weight must be between 0.0 and 1.0, got {weight}"),
+      transaction_id,
+    )),
+    _ => Ok(()),
   }
 }
 
@@ -809,7 +1988,10 @@ pub async fn search_insights(
 async fn log_search_start(context: &RequestContext, request: &SearchRequest) {
   context
     .log_info(
-      &format!("Searching insights: terms={:?}, topic={:?}", request.terms, request.topic),
+      &format!(
+        "Searching insights: terms={:?}, topic={:?}, tags={:?}, mode={:?}",
+        request.terms, request.topic, request.tags, request.mode
+      ),
       "insights-api",
     )
     .await;
@@ -819,6 +2001,7 @@ async fn log_search_start(context: &RequestContext, request: &SearchRequest) {
 fn build_search_options(request: &SearchRequest) -> crate::server::services::search::SearchOptions {
   crate::server::services::search::SearchOptions {
     topic: request.topic.clone(),
+    tags: request.tags.clone(),
     case_sensitive: request.case_sensitive,
     overview_only: request.overview_only,
     exact: request.exact,
@@ -826,13 +2009,68 @@ fn build_search_options(request: &SearchRequest) -> crate::server::services::sea
   }
 }
 
+/// Keyword matches from the full-text index, or a file scan for exact/jaccard matching
+async fn perform_keyword_search(
+  context: &RequestContext,
+  request: &SearchRequest,
+  transaction_id: Uuid,
+) -> Result<Vec<SearchResultData>, ErrorResponse> {
+  let search_options = build_search_options(request);
+  if request.exact || request.semantic {
+    return perform_term_search(context, request, &search_options, transaction_id).await;
+  }
+
+  let search_results = fulltext::search(&request.terms, &search_options, fulltext::DEFAULT_LIMIT)
+    .map_err(|e| {
+    create_search_error_response(&format!("Full-text search failed: {e}"), transaction_id)
+  })?;
+
+  context
+    .log_info(
+      &format!("Full-text search found {} results for {:?}", search_results.len(), request.terms),
+      "insights-api",
+    )
+    .await;
+
+  Ok(convert_search_results_to_api_format(search_results))
+}
+
+/// Embedding search alone, failing if there are no embeddings to search
+async fn perform_semantic_only_search(
+  context: &RequestContext,
+  request: &SearchRequest,
+  transaction_id: Uuid,
+) -> Result<Vec<SearchResultData>, ErrorResponse> {
+  match check_embeddings_availability(context, request).await {
+    EmbeddingAvailability::Available => {}
+    EmbeddingAvailability::Unavailable => {
+      return Err(error_response(
+        ErrorCode::IndexUnavailable,
+        "semantic_search_unavailable",
+        "Semantic search needs embeddings; run `insights index` or use --mode full-text",
+        transaction_id,
+      ))
+    }
+    EmbeddingAvailability::Error => {
+      return Err(create_search_error_response("Failed to check for embeddings", transaction_id))
+    }
+  }
+
+  let mut results = perform_vector_search(context, request).await.map_err(|e| {
+    create_search_error_response(&format!("Embedding search failed: {e}"), transaction_id)
+  })?;
+  log_embedding_search_success(context, &results, &request.terms).await;
+  explain_path(request, &mut results, MatchPath::Semantic);
+  Ok(finalize_search_results(context, request, results).await)
+}
+
 /// Perform term-based search and return results
 async fn perform_term_search(
   context: &RequestContext,
   request: &SearchRequest,
   search_options: &crate::server::services::search::SearchOptions,
   transaction_id: Uuid,
-) -> Result<Vec<SearchResultData>, (axum::http::StatusCode, ResponseJson<BaseResponse<()>>)> {
+) -> Result<Vec<SearchResultData>, ErrorResponse> {
   let search_results = crate::server::services::search::search(&request.terms, search_options)
     .map_err(|e| {
       let error_response =
@@ -870,7 +2108,10 @@ fn convert_search_results_to_api_format(
       name: result.name,
       overview: result.overview,
       details: result.details,
+      tags: result.tags,
       score: result.score,
+      ranking: None,
+      explanation: None,
     })
     .collect()
 }
@@ -937,7 +2178,7 @@ async fn log_embedding_search_failure(context: &RequestContext, terms: &[String]
 }
 
 /// Check if embeddings are available for search
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 async fn check_embeddings_availability(
   context: &RequestContext,
   request: &SearchRequest,
@@ -968,8 +2209,8 @@ async fn check_embeddings_availability(
   }
 }
 
-/// Check if embeddings are available for search (no-op without ml-features)
-#[cfg(not(feature = "ml-features"))]
+/// Check if embeddings are available for search (no-op without the semantic feature)
+#[cfg(not(feature = "semantic"))]
 async fn check_embeddings_availability(
   context: &RequestContext,
   request: &SearchRequest,
@@ -984,7 +2225,7 @@ async fn check_embeddings_availability(
 }
 
 /// Embedding availability status
-#[allow(dead_code)] // Some variants only used with ml-features
+#[allow(dead_code)] // Some variants only used with the semantic feature
 enum EmbeddingAvailability {
   Available,
   Unavailable,
@@ -996,8 +2237,13 @@ async fn finalize_search_results(
   context: &RequestContext,
   request: &SearchRequest,
   mut all_results: Vec<SearchResultData>,
-  transaction_id: Uuid,
-) -> BaseResponse<SearchResponse> {
+) -> Vec<SearchResultData> {
+  // Embedding search ranks every topic, so restrict it to the requested subtree here
+  if let Some(prefix) = &request.topic {
+    all_results.retain(|result| topic::within(&result.topic, prefix));
+  }
+  all_results.retain(|result| tag::has_all(&result.tags, &request.tags));
+
   // Sort and deduplicate results
   all_results.sort_by(|a, b| {
     b.score
@@ -1021,45 +2267,34 @@ async fn finalize_search_results(
     )
     .await;
 
-  let response_data = SearchResponse { count: all_results.len(), results: all_results };
-  BaseResponse::success(response_data, transaction_id)
+  all_results
 }
 
 /// Create a standardized error response for search failures
-fn create_search_error_response(
-  message: &str,
-  transaction_id: Uuid,
-) -> (axum::http::StatusCode, ResponseJson<BaseResponse<()>>) {
-  let error = ApiError::new("search_failed", message);
-  (
-    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-    ResponseJson(BaseResponse::<()>::error(vec![error], transaction_id)),
-  )
+fn create_search_error_response(message: &str, transaction_id: Uuid) -> ErrorResponse {
+  error_response(ErrorCode::Internal, "search_failed", message, transaction_id)
 }
 
 /// Get the configured initial limit for reranking candidate retrieval
 /// Default: 128 candidates
-/// Environment: INSIGHTS_RERANK_INITIAL_LIMIT
-#[cfg(feature = "ml-features")]
+/// Setting: insights.rerank.initial_limit (INSIGHTS_RERANK_INITIAL_LIMIT)
+#[cfg(feature = "semantic")]
 fn get_initial_search_limit() -> usize {
-  std::env::var("INSIGHTS_RERANK_INITIAL_LIMIT").ok().and_then(|s| s.parse().ok()).unwrap_or(128)
+  bentley::config::get(&crate::settings::RERANK_INITIAL_LIMIT).unwrap_or(128)
 }
 
 /// Get the configured initial threshold for reranking candidate retrieval
 /// Default: 0.3 (more permissive than final search)
-/// Environment: INSIGHTS_RERANK_INITIAL_THRESHOLD  
-#[cfg(feature = "ml-features")]
+/// Setting: insights.rerank.initial_threshold (INSIGHTS_RERANK_INITIAL_THRESHOLD)
+#[cfg(feature = "semantic")]
 fn get_initial_search_threshold() -> f32 {
-  std::env::var("INSIGHTS_RERANK_INITIAL_THRESHOLD")
-    .ok()
-    .and_then(|s| s.parse().ok())
-    .unwrap_or(0.3)
+  bentley::config::get(&crate::settings::RERANK_INITIAL_THRESHOLD).unwrap_or(0.3)
 }
 
 /// Get the configured final limit for reranking results
 /// Default: 8 final results after reranking
-/// Environment: INSIGHTS_RERANK_FINAL_LIMIT
-#[cfg(feature = "ml-features")]
+/// Setting: insights.rerank.final_limit (INSIGHTS_RERANK_FINAL_LIMIT)
+#[cfg(feature = "semantic")]
 fn get_rerank_limit() -> usize {
-  std::env::var("INSIGHTS_RERANK_FINAL_LIMIT").ok().and_then(|s| s.parse().ok()).unwrap_or(8)
+  bentley::config::get(&crate::settings::RERANK_FINAL_LIMIT).unwrap_or(8)
 }
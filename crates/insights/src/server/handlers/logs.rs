@@ -1,17 +1,18 @@
 //! Logs endpoint handler
 
-use axum::{extract::Extension, http::StatusCode, response::Json};
+use axum::{extract::Extension, response::Json};
 use uuid::Uuid;
 
 use crate::server::{
+  errors::{error_response, ErrorResponse},
   middleware::RequestContext,
-  types::{ApiError, BaseResponse, LogEntry, LogsResponse},
+  types::{BaseResponse, ErrorCode, LogEntry, LogsResponse},
 };
 
 /// GET /logs - Get all logs using request context
 pub async fn get_logs_with_context(
   Extension(context): Extension<RequestContext>,
-) -> Result<Json<BaseResponse<LogsResponse>>, (StatusCode, Json<BaseResponse<()>>)> {
+) -> Result<Json<BaseResponse<LogsResponse>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
 
   context.log_info("Retrieving server logs", "logs-api").await;
@@ -28,10 +29,11 @@ pub async fn get_logs_with_context(
     }
     Err(e) => {
       context.log_error(&format!("Failed to read logs: {e}"), "logs-api").await;
-      let error = ApiError::new("logs_read_failed", &format!("Failed to read logs: {e}"));
-      Err((
-        StatusCode::INTERNAL_SERVER_ERROR,
-        Json(BaseResponse::<()>::error(vec![error], transaction_id)),
+      Err(error_response(
+        ErrorCode::Internal,
+        "logs_read_failed",
+        &format!("Failed to read logs: {e}"),
+        transaction_id,
       ))
     }
   }
@@ -1,5 +1,19 @@
 //! HTTP request handlers for all REST endpoints
 
+pub mod ask;
+pub mod backup;
+pub mod dedupe;
+pub mod events;
+pub mod history;
+pub mod indexing;
 pub mod insights;
 pub mod logs;
+pub mod related;
+pub mod retention;
+pub mod shards;
+pub mod stats;
 pub mod status;
+pub mod summary;
+pub mod ui;
+pub mod webhooks;
+pub mod workspaces;
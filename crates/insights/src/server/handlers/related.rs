@@ -0,0 +1,126 @@
+//! Related insights endpoint handlers
+
+use anyhow::Result;
+use axum::{
+  extract::{Extension, Json},
+  response::Json as ResponseJson,
+};
+use uuid::Uuid;
+
+#[cfg(feature = "semantic")]
+use crate::server::services::vector_database::VectorDatabase;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::middleware::RequestContext;
+use crate::server::models::insight::{self, Insight};
+use crate::server::services::related;
+use crate::server::types::{
+  BaseResponse, ErrorCode, RelatedInsightData, RelatedInsightsRequest, RelatedInsightsResponse,
+};
+
+/// POST /insights/related - Nearest insights to a stored insight by embedding
+pub async fn related_insights(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<RelatedInsightsRequest>,
+) -> Result<ResponseJson<BaseResponse<RelatedInsightsResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  if request.limit == Some(0) {
+    return Err(error_response(
+      ErrorCode::ValidationFailed,
+      "related_request_invalid",
+      "limit must be at least 1",
+      transaction_id,
+    ));
+  }
+
+  let insight = insight::load(&request.topic, &request.name).map_err(|e| {
+    error_response(
+      ErrorCode::NotFound,
+      "insight_not_found",
+      &format!("Insight {}/{} not found: {e}", request.topic, request.name),
+      transaction_id,
+    )
+  })?;
+
+  let limit = request.limit.unwrap_or(related::DEFAULT_LIMIT);
+  let threshold = related::get_threshold();
+  let candidates = find_candidates(&context, &insight, limit, threshold, transaction_id).await?;
+
+  let response = RelatedInsightsResponse {
+    related: related::nearest(candidates, &insight.topic, &insight.name, limit),
+    topic: insight.topic,
+    name: insight.name,
+    threshold,
+  };
+
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// Insights nearest to the stored embedding of `insight`, possibly including itself
+#[cfg(feature = "semantic")]
+async fn find_candidates(
+  context: &RequestContext,
+  insight: &Insight,
+  limit: usize,
+  threshold: f32,
+  transaction_id: Uuid,
+) -> Result<Vec<RelatedInsightData>, ErrorResponse> {
+  let index_error = |e: anyhow::Error| {
+    error_response(
+      ErrorCode::IndexUnavailable,
+      "related_search_failed",
+      &format!("Failed to search embeddings: {e}"),
+      transaction_id,
+    )
+  };
+
+  let mut stored = context.vector_db.topic_embeddings(&insight.topic).await.map_err(index_error)?;
+  let Some(embedding) = stored.remove(&insight.name.to_lowercase()) else {
+    return Err(error_response(
+      ErrorCode::NotFound,
+      "embedding_not_found",
+      &format!(
+        "Insight {}/{} has no embedding yet; try again once indexing catches up",
+        insight.topic, insight.name
+      ),
+      transaction_id,
+    ));
+  };
+
+  // One extra, since the insight is its own nearest neighbour
+  let results = context
+    .vector_db
+    .search_similar(&embedding, limit + 1, Some(threshold), &[])
+    .await
+    .map_err(index_error)?;
+
+  Ok(
+    results
+      .into_iter()
+      .map(|result| RelatedInsightData {
+        topic: result.topic,
+        name: result.name,
+        overview: result.overview,
+        similarity: result.similarity,
+      })
+      .collect(),
+  )
+}
+
+/// Related insights need stored embeddings, which don't exist without the semantic feature
+#[cfg(not(feature = "semantic"))]
+async fn find_candidates(
+  _context: &RequestContext,
+  _insight: &Insight,
+  _limit: usize,
+  _threshold: f32,
+  transaction_id: Uuid,
+) -> Result<Vec<RelatedInsightData>, ErrorResponse> {
+  Err(error_response(
+    ErrorCode::IndexUnavailable,
+    "embeddings_unavailable",
+    "Related insights need embeddings, which this server was built without",
+    transaction_id,
+  ))
+}
@@ -0,0 +1,130 @@
+//! Retention policy endpoint handlers
+
+use axum::{
+  extract::{Extension, Json},
+  response::Json as ResponseJson,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::middleware::RequestContext;
+use crate::server::models::retention::{self, RetentionPolicy};
+use crate::server::services::retention as retention_service;
+use crate::server::types::{
+  BaseResponse, ErrorCode, ListRetentionResponse, RemoveRetentionRequest, RetentionPolicyData,
+  RetentionSweepResponse, SetRetentionRequest,
+};
+
+/// GET /insights/retention - List all retention policies
+pub async fn list_policies(
+) -> Result<ResponseJson<BaseResponse<ListRetentionResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let policies = retention::load_policies().map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "retention_list_failed",
+      &format!("Failed to load retention policies: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let policies = policies
+    .into_iter()
+    .map(|(topic, policy)| RetentionPolicyData {
+      topic,
+      expire_after_days: policy.expire_after_days,
+      expire_on: policy.expire_on,
+    })
+    .collect();
+
+  Ok(ResponseJson(BaseResponse::success(ListRetentionResponse { policies }, transaction_id)))
+}
+
+/// PUT /insights/retention - Create or replace a topic's retention policy
+pub async fn set_policy(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<SetRetentionRequest>,
+) -> Result<ResponseJson<BaseResponse<()>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let policy =
+    RetentionPolicy { expire_after_days: request.expire_after_days, expire_on: request.expire_on };
+
+  policy.validate().map_err(|e| {
+    error_response(
+      ErrorCode::ValidationFailed,
+      "retention_policy_invalid",
+      &format!("Invalid retention policy: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  retention::set_policy(&request.topic, policy).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "retention_set_failed",
+      &format!("Failed to set retention policy: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  context
+    .log_success(&format!("Set retention policy for topic {}", request.topic), "insights-retention")
+    .await;
+
+  Ok(ResponseJson(BaseResponse::success((), transaction_id)))
+}
+
+/// DELETE /insights/retention - Remove a topic's retention policy
+pub async fn remove_policy(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<RemoveRetentionRequest>,
+) -> Result<ResponseJson<BaseResponse<()>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  retention::remove_policy(&request.topic).map_err(|e| {
+    error_response(
+      ErrorCode::NotFound,
+      "retention_remove_failed",
+      &format!("Failed to remove retention policy: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  context
+    .log_success(
+      &format!("Removed retention policy for topic {}", request.topic),
+      "insights-retention",
+    )
+    .await;
+
+  Ok(ResponseJson(BaseResponse::success((), transaction_id)))
+}
+
+/// POST /insights/retention/sweep - Archive expired insights immediately
+pub async fn sweep(
+  Extension(context): Extension<RequestContext>,
+) -> Result<ResponseJson<BaseResponse<RetentionSweepResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let report = retention_service::sweep(Utc::now()).await.map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "retention_sweep_failed",
+      &format!("Retention sweep failed: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  context
+    .log_info(
+      &format!("Retention sweep archived {} insights", report.archived.len()),
+      "insights-retention",
+    )
+    .await;
+
+  let response = RetentionSweepResponse { archived: report.archived, errors: report.errors };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
@@ -0,0 +1,207 @@
+//! Shard management endpoint handlers
+
+use anyhow::Result;
+use axum::{
+  extract::{Extension, Json},
+  response::Json as ResponseJson,
+};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+#[cfg(feature = "semantic")]
+use crate::server::services::vector_database::VectorDatabase;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::middleware::RequestContext;
+use crate::server::models::{
+  insight,
+  sharding::{self, ShardConfig},
+};
+use crate::server::services::sharding::{plan_rebalance, RebalancePlan, ShardStats};
+use crate::server::types::{
+  BaseResponse, ConfigureShardsRequest, ErrorCode, RebalanceShardsRequest, RebalanceShardsResponse,
+  ShardStatsData, ShardsResponse,
+};
+
+/// GET /insights/shards - Show the shard layout and the size of every shard
+pub async fn list_shards(
+  Extension(context): Extension<RequestContext>,
+) -> Result<ResponseJson<BaseResponse<ShardsResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let (config, stats, plan) = current_plan(&context, transaction_id).await?;
+
+  let response = ShardsResponse {
+    config,
+    shards: stats.into_iter().map(|s| ShardStatsData { table: s.table, rows: s.rows }).collect(),
+    imbalance: plan.imbalance,
+    rebalance_reasons: plan.reasons,
+  };
+
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// PUT /insights/shards - Switch to a new shard layout and migrate existing rows
+pub async fn configure_shards(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<ConfigureShardsRequest>,
+) -> Result<ResponseJson<BaseResponse<RebalanceShardsResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let current = load_config(transaction_id)?;
+  let config = ShardConfig {
+    strategy: request.strategy,
+    buckets: request.buckets.unwrap_or(current.buckets),
+    max_imbalance: request.max_imbalance.unwrap_or(current.max_imbalance),
+    max_shard_rows: request.max_shard_rows.unwrap_or(current.max_shard_rows),
+    // Topic splits only make sense for the layout that computed them
+    split_topics: if request.strategy == current.strategy {
+      current.split_topics
+    } else {
+      BTreeMap::new()
+    },
+  };
+
+  config.validate().map_err(|e| {
+    error_response(
+      ErrorCode::ValidationFailed,
+      "shard_config_invalid",
+      &format!("Invalid shard layout: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let reasons = vec![format!("shard layout changed to {}", config.strategy)];
+  let response = apply(&context, config, reasons, transaction_id).await?;
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// POST /insights/shards/rebalance - Redistribute rows when shards have grown unevenly
+pub async fn rebalance_shards(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<RebalanceShardsRequest>,
+) -> Result<ResponseJson<BaseResponse<RebalanceShardsResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let (config, _, plan) = current_plan(&context, transaction_id).await?;
+
+  if !plan.is_needed() && !request.force {
+    let response =
+      RebalanceShardsResponse { rebalanced: false, moved: 0, config, reasons: Vec::new() };
+    return Ok(ResponseJson(BaseResponse::success(response, transaction_id)));
+  }
+
+  let mut reasons = plan.reasons;
+  if reasons.is_empty() {
+    reasons.push("rebalance forced".to_string());
+  }
+
+  let response = apply(&context, plan.target, reasons, transaction_id).await?;
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// Persist a layout and move every row into it
+async fn apply(
+  context: &RequestContext,
+  config: ShardConfig,
+  reasons: Vec<String>,
+  transaction_id: Uuid,
+) -> Result<RebalanceShardsResponse, ErrorResponse> {
+  sharding::save_config(&config).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "shard_config_save_failed",
+      &format!("Failed to save shard layout: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let moved = move_rows(context, &config).await.map_err(|e| {
+    error_response(
+      ErrorCode::IndexUnavailable,
+      "shard_rebalance_failed",
+      &format!("Failed to rebalance shards: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  context
+    .log_success(
+      &format!("Rebalanced embeddings into {} layout, {moved} rows moved", config.strategy),
+      "insights-shards",
+    )
+    .await;
+
+  Ok(RebalanceShardsResponse { rebalanced: true, moved, config, reasons })
+}
+
+/// Load the layout, the shard sizes, and what a rebalance would do
+async fn current_plan(
+  context: &RequestContext,
+  transaction_id: Uuid,
+) -> Result<(ShardConfig, Vec<ShardStats>, RebalancePlan), ErrorResponse> {
+  let config = load_config(transaction_id)?;
+
+  let stats = shard_stats(context).await.map_err(|e| {
+    error_response(
+      ErrorCode::IndexUnavailable,
+      "shard_stats_failed",
+      &format!("Failed to read shard sizes: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let topic_rows = topic_rows().map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "shard_topics_failed",
+      &format!("Failed to count insights per topic: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let plan = plan_rebalance(&config, &stats, &topic_rows);
+  Ok((config, stats, plan))
+}
+
+fn load_config(transaction_id: Uuid) -> Result<ShardConfig, ErrorResponse> {
+  sharding::load_config().map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "shard_config_load_failed",
+      &format!("Failed to load shard layout: {e}"),
+      transaction_id,
+    )
+  })
+}
+
+/// Insights per normalized topic, used to size topic shards
+fn topic_rows() -> Result<BTreeMap<String, usize>> {
+  let mut counts = BTreeMap::new();
+  for insight in insight::get_insights(None)? {
+    *counts.entry(insight.topic.to_lowercase()).or_default() += 1;
+  }
+  Ok(counts)
+}
+
+#[cfg(feature = "semantic")]
+async fn shard_stats(context: &RequestContext) -> Result<Vec<ShardStats>> {
+  context.vector_db.shard_stats().await
+}
+
+/// Shard sizes (always empty without the semantic feature)
+#[cfg(not(feature = "semantic"))]
+async fn shard_stats(_context: &RequestContext) -> Result<Vec<ShardStats>> {
+  Ok(Vec::new())
+}
+
+#[cfg(feature = "semantic")]
+async fn move_rows(context: &RequestContext, config: &ShardConfig) -> Result<usize> {
+  context.vector_db.rebalance(config.clone()).await
+}
+
+/// Move rows into a new layout (no-op without the semantic feature)
+#[cfg(not(feature = "semantic"))]
+async fn move_rows(_context: &RequestContext, _config: &ShardConfig) -> Result<usize> {
+  Ok(0)
+}
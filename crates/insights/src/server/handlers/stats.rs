@@ -0,0 +1,46 @@
+//! Access statistics endpoint handler
+
+use axum::{extract::Query, response::Json as ResponseJson};
+use uuid::Uuid;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::models::stats;
+use crate::server::types::{BaseResponse, ErrorCode, InsightStatsData, StatsQuery, StatsResponse};
+
+/// Insights returned when the request does not ask for a number
+const DEFAULT_TOP: usize = 20;
+
+/// GET /insights/stats - The most used insights, most read first
+pub async fn list_stats(
+  Query(query): Query<StatsQuery>,
+) -> Result<ResponseJson<BaseResponse<StatsResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let log = stats::load().map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "stats_load_failed",
+      &format!("Failed to load access stats: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let insights = stats::top(&log, query.top.unwrap_or(DEFAULT_TOP))
+    .into_iter()
+    .map(|(key, entry)| {
+      let (topic, name) = stats::split_key(key);
+      InsightStatsData {
+        topic: topic.to_string(),
+        name: name.to_string(),
+        reads: entry.reads,
+        search_hits: entry.search_hits,
+        last_accessed: entry.last_accessed,
+      }
+    })
+    .collect();
+
+  Ok(ResponseJson(BaseResponse::success(
+    StatsResponse { insights, tracked: log.len() },
+    transaction_id,
+  )))
+}
@@ -1,15 +1,17 @@
 //! Status and version endpoint handlers
 
-use axum::{http::StatusCode, response::Json};
+use axum::response::Json;
 use uuid::Uuid;
 
+use crate::server::errors::{error_response, ErrorResponse};
 use crate::server::models::insight;
+use crate::server::openapi;
 use crate::server::types::{
-  ApiInfoResponse, ApiVersions, BaseResponse, StatusResponse, VersionResponse,
+  ApiInfoResponse, ApiVersions, BaseResponse, ErrorCode, StatusResponse, VersionResponse,
 };
 
 /// GET /status - Health check endpoint
-pub async fn status() -> Result<Json<BaseResponse<StatusResponse>>, StatusCode> {
+pub async fn status() -> Result<Json<BaseResponse<StatusResponse>>, ErrorResponse> {
   let transaction_id = Uuid::new_v4();
   let version = env!("CARGO_PKG_VERSION");
 
@@ -23,7 +25,12 @@ pub async fn status() -> Result<Json<BaseResponse<StatusResponse>>, StatusCode>
       };
       Ok(Json(BaseResponse::success(response, transaction_id)))
     }
-    Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    Err(e) => Err(error_response(
+      ErrorCode::Internal,
+      "status_failed",
+      &format!("Failed to resolve insights root: {e}"),
+      transaction_id,
+    )),
   }
 }
 
@@ -47,3 +54,8 @@ pub async fn api_info() -> Json<BaseResponse<ApiInfoResponse>> {
 
   Json(BaseResponse::success(response, transaction_id))
 }
+
+/// GET /openapi.json - Returns the OpenAPI description of this API
+pub async fn openapi() -> Json<serde_json::Value> {
+  Json(openapi::spec())
+}
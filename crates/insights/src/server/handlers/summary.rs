@@ -0,0 +1,120 @@
+//! Topic summary endpoint handlers
+
+use anyhow::Result;
+use axum::{
+  extract::{Extension, Json},
+  response::Json as ResponseJson,
+};
+use uuid::Uuid;
+
+#[cfg(feature = "semantic")]
+use crate::server::services::vector_database::VectorDatabase;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::middleware::RequestContext;
+use crate::server::models::insight::{self, Insight};
+use crate::server::services::summary::{self, SummaryOptions};
+use crate::server::types::{
+  BaseResponse, ClusterData, ClusterInsightData, ErrorCode, SummarizeTopicRequest,
+  TopicSummaryResponse,
+};
+
+/// POST /insights/summary - Outline a topic as clusters of related insights
+pub async fn summarize_topic(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<SummarizeTopicRequest>,
+) -> Result<ResponseJson<BaseResponse<TopicSummaryResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  if request.max_clusters == Some(0) {
+    return Err(error_response(
+      ErrorCode::ValidationFailed,
+      "summary_request_invalid",
+      "max_clusters must be at least 1",
+      transaction_id,
+    ));
+  }
+
+  let insights = insight::get_insights(Some(&request.topic.to_lowercase())).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "summary_load_failed",
+      &format!("Failed to load insights for topic '{}': {e}", request.topic),
+      transaction_id,
+    )
+  })?;
+
+  if insights.is_empty() {
+    return Err(error_response(
+      ErrorCode::NotFound,
+      "topic_not_found",
+      &format!("Topic '{}' has no insights", request.topic),
+      transaction_id,
+    ));
+  }
+
+  let texts: Vec<String> = insights.iter().map(|i| format!("{} {}", i.name, i.overview)).collect();
+  let embeddings = topic_embeddings(&context, &request.topic, &insights).await;
+
+  let source = if embeddings.is_some() {
+    summary::ClusterSource::Embeddings
+  } else {
+    summary::ClusterSource::Terms
+  };
+  let mut options = SummaryOptions::for_source(source);
+  if let Some(max_clusters) = request.max_clusters {
+    options.max_clusters = max_clusters;
+  }
+
+  let outline = summary::summarize(&texts, embeddings.as_deref(), Some(options));
+
+  let entry = |index: usize| ClusterInsightData {
+    name: insights[index].name.clone(),
+    overview: insights[index].overview.clone(),
+  };
+  let response = TopicSummaryResponse {
+    topic: request.topic,
+    method: outline.source.to_string(),
+    total: insights.len(),
+    clusters: outline
+      .clusters
+      .into_iter()
+      .map(|cluster| ClusterData {
+        label: cluster.terms.join(", "),
+        insights: cluster.members.iter().map(|&i| entry(i)).collect(),
+        terms: cluster.terms,
+      })
+      .collect(),
+    unclustered: outline.unclustered.iter().map(|&i| entry(i)).collect(),
+  };
+
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
+
+/// Embeddings for every insight in the topic, or None if any are missing
+#[cfg(feature = "semantic")]
+async fn topic_embeddings(
+  context: &RequestContext,
+  topic: &str,
+  insights: &[Insight],
+) -> Option<Vec<Vec<f32>>> {
+  let mut stored = match context.vector_db.topic_embeddings(topic).await {
+    Ok(stored) => stored,
+    Err(e) => {
+      context.log_warn(&format!("Falling back to term clustering: {e}"), "insights-summary").await;
+      return None;
+    }
+  };
+
+  insights.iter().map(|insight| stored.remove(&insight.name.to_lowercase())).collect()
+}
+
+/// Embeddings for every insight in the topic (never available without the semantic feature)
+#[cfg(not(feature = "semantic"))]
+async fn topic_embeddings(
+  _context: &RequestContext,
+  _topic: &str,
+  _insights: &[Insight],
+) -> Option<Vec<Vec<f32>>> {
+  None
+}
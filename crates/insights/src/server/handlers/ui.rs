@@ -0,0 +1,61 @@
+//! Web UI handlers
+//!
+//! The page, script and stylesheet are compiled into the binary and served
+//! under `/ui` when the server runs with `--ui`. The page itself is public; the
+//! data it shows comes from the regular API with the token the user enters.
+
+use axum::{
+  extract::Query,
+  http::header,
+  response::{Html, IntoResponse, Json as ResponseJson},
+};
+use uuid::Uuid;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::models::insight;
+use crate::server::services::markdown;
+use crate::server::types::{BaseResponse, ErrorCode, RenderInsightQuery, RenderInsightResponse};
+
+const INDEX_HTML: &str = include_str!("../ui/index.html");
+const APP_JS: &str = include_str!("../ui/app.js");
+const STYLE_CSS: &str = include_str!("../ui/style.css");
+
+/// GET /ui - The browser page
+pub async fn index() -> Html<&'static str> {
+  Html(INDEX_HTML)
+}
+
+/// GET /ui/app.js
+pub async fn script() -> impl IntoResponse {
+  ([(header::CONTENT_TYPE, "text/javascript; charset=utf-8")], APP_JS)
+}
+
+/// GET /ui/style.css
+pub async fn stylesheet() -> impl IntoResponse {
+  ([(header::CONTENT_TYPE, "text/css; charset=utf-8")], STYLE_CSS)
+}
+
+/// GET /insights/render - An insight with its markdown rendered as HTML
+pub async fn render_insight(
+  Query(query): Query<RenderInsightQuery>,
+) -> Result<ResponseJson<BaseResponse<RenderInsightResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let loaded = insight::load(&query.topic, &query.name).map_err(|e| {
+    error_response(
+      ErrorCode::NotFound,
+      "insight_render_failed",
+      &format!("Failed to get insight: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let response = RenderInsightResponse {
+    overview_html: markdown::to_html(&loaded.overview),
+    details_html: markdown::to_html(&loaded.details),
+    topic: loaded.topic,
+    name: loaded.name,
+    tags: loaded.tags,
+  };
+  Ok(ResponseJson(BaseResponse::success(response, transaction_id)))
+}
@@ -0,0 +1,123 @@
+//! Webhook registration and delivery log endpoint handlers
+
+use axum::{
+  extract::{Extension, Json, Query},
+  response::Json as ResponseJson,
+};
+use uuid::Uuid;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::middleware::RequestContext;
+use crate::server::models::webhook::{self, Webhook};
+use crate::server::services::webhooks;
+use crate::server::types::{
+  AddWebhookRequest, BaseResponse, DeliveriesQuery, ErrorCode, ListDeliveriesResponse,
+  ListWebhooksResponse, RemoveWebhookRequest, WebhookData,
+};
+
+/// Deliveries returned when the request does not ask for a number
+const DEFAULT_DELIVERIES_LIMIT: usize = 50;
+
+fn webhook_data(webhook: Webhook) -> WebhookData {
+  WebhookData {
+    id: webhook.id,
+    url: webhook.url,
+    topics: webhook.topics,
+    events: webhook.events,
+    created_at: webhook.created_at,
+  }
+}
+
+/// GET /insights/webhooks - List all registered webhooks
+pub async fn list_webhooks(
+) -> Result<ResponseJson<BaseResponse<ListWebhooksResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let webhooks = webhook::load_webhooks().map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "webhook_list_failed",
+      &format!("Failed to load webhooks: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  let webhooks = webhooks.into_iter().map(webhook_data).collect();
+  Ok(ResponseJson(BaseResponse::success(ListWebhooksResponse { webhooks }, transaction_id)))
+}
+
+/// POST /insights/webhooks - Register a new webhook
+pub async fn add_webhook(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<AddWebhookRequest>,
+) -> Result<ResponseJson<BaseResponse<WebhookData>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let new_webhook = Webhook::new(&request.url, &request.secret, &request.topics, &request.events);
+  new_webhook.validate().map_err(|e| {
+    error_response(
+      ErrorCode::ValidationFailed,
+      "webhook_invalid",
+      &format!("Invalid webhook: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  webhook::add_webhook(new_webhook.clone()).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "webhook_add_failed",
+      &format!("Failed to register webhook: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  context
+    .log_success(
+      &format!("Registered webhook {} for {}", new_webhook.id, new_webhook.url),
+      "insights-webhooks",
+    )
+    .await;
+
+  Ok(ResponseJson(BaseResponse::success(webhook_data(new_webhook), transaction_id)))
+}
+
+/// DELETE /insights/webhooks - Remove a webhook
+pub async fn remove_webhook(
+  Extension(context): Extension<RequestContext>,
+  Json(request): Json<RemoveWebhookRequest>,
+) -> Result<ResponseJson<BaseResponse<()>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  webhook::remove_webhook(&request.id).map_err(|e| {
+    error_response(
+      ErrorCode::NotFound,
+      "webhook_remove_failed",
+      &format!("Failed to remove webhook: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  context.log_success(&format!("Removed webhook {}", request.id), "insights-webhooks").await;
+
+  Ok(ResponseJson(BaseResponse::success((), transaction_id)))
+}
+
+/// GET /insights/webhooks/deliveries - Recent delivery attempts, newest first
+pub async fn list_deliveries(
+  Query(query): Query<DeliveriesQuery>,
+) -> Result<ResponseJson<BaseResponse<ListDeliveriesResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  let limit = query.limit.unwrap_or(DEFAULT_DELIVERIES_LIMIT);
+  let deliveries = webhooks::recent_deliveries(query.webhook.as_deref(), limit).map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "webhook_deliveries_failed",
+      &format!("Failed to read webhook delivery log: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  Ok(ResponseJson(BaseResponse::success(ListDeliveriesResponse { deliveries }, transaction_id)))
+}
@@ -0,0 +1,124 @@
+//! Workspace management endpoint handlers
+//!
+//! These routes sit outside the per-request workspace, so they behave the same
+//! whatever `X-Insights-Workspace` header the caller sends.
+
+use axum::{extract::Json, response::Json as ResponseJson};
+use uuid::Uuid;
+
+use crate::server::errors::{error_response, ErrorResponse};
+use crate::server::middleware::server_info;
+use crate::server::models::insight;
+use crate::server::services::workspace;
+use crate::server::startup::get_vector_base_path;
+use crate::server::types::{
+  BaseResponse, CreateWorkspaceRequest, DeleteWorkspaceRequest, ErrorCode, ListWorkspacesResponse,
+  WorkspaceData,
+};
+
+/// Root and insight count of a workspace, read from inside it
+async fn describe(name: &str) -> anyhow::Result<WorkspaceData> {
+  workspace::scope(name.to_string(), async {
+    let insights_root = insight::get_insights_root()?;
+    let insights = if insights_root.exists() { insight::get_insights(None)?.len() } else { 0 };
+    Ok(WorkspaceData {
+      name: name.to_string(),
+      insights_root: insights_root.to_string_lossy().to_string(),
+      insights,
+    })
+  })
+  .await
+}
+
+/// GET /workspaces - List workspaces, the default first
+pub async fn list_workspaces(
+) -> Result<ResponseJson<BaseResponse<ListWorkspacesResponse>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+  let failed = |e: anyhow::Error| {
+    error_response(
+      ErrorCode::Internal,
+      "workspace_list_failed",
+      &format!("Failed to list workspaces: {e}"),
+      transaction_id,
+    )
+  };
+
+  let mut workspaces = Vec::new();
+  for name in workspace::list().map_err(failed)? {
+    workspaces.push(describe(&name).await.map_err(failed)?);
+  }
+  Ok(ResponseJson(BaseResponse::success(ListWorkspacesResponse { workspaces }, transaction_id)))
+}
+
+/// POST /workspaces - Create a workspace
+pub async fn create_workspace(
+  Json(request): Json<CreateWorkspaceRequest>,
+) -> Result<ResponseJson<BaseResponse<WorkspaceData>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+
+  if let Err(e) = workspace::validate(&request.name) {
+    return Err(error_response(
+      ErrorCode::ValidationFailed,
+      "invalid_workspace",
+      &e.to_string(),
+      transaction_id,
+    ));
+  }
+  if workspace::exists(&request.name).unwrap_or(false) {
+    return Err(error_response(
+      ErrorCode::AlreadyExists,
+      "workspace_exists",
+      &format!("Workspace '{}' already exists", request.name),
+      transaction_id,
+    ));
+  }
+
+  let created = workspace::create(&request.name);
+  let data = match created {
+    Ok(_) => describe(&request.name).await,
+    Err(e) => Err(e),
+  }
+  .map_err(|e| {
+    error_response(
+      ErrorCode::Internal,
+      "workspace_create_failed",
+      &format!("Failed to create workspace '{}': {e}", request.name),
+      transaction_id,
+    )
+  })?;
+
+  server_info(&format!("Created workspace {}", request.name), "insights-workspaces").await;
+  Ok(ResponseJson(BaseResponse::success(data, transaction_id)))
+}
+
+/// DELETE /workspaces - Delete a workspace with its insights and embeddings
+pub async fn delete_workspace(
+  Json(request): Json<DeleteWorkspaceRequest>,
+) -> Result<ResponseJson<BaseResponse<()>>, ErrorResponse> {
+  let transaction_id = Uuid::new_v4();
+  let name = &request.name;
+
+  // delete explains what is wrong with the name; pick the matching error code for it
+  let code = if workspace::validate(name).is_err() || name == workspace::DEFAULT {
+    ErrorCode::ValidationFailed
+  } else if !workspace::exists(name).unwrap_or(false) {
+    ErrorCode::NotFound
+  } else {
+    // Close the vector database before its files go away
+    #[cfg(feature = "semantic")]
+    crate::server::middleware::forget_workspace_vector_db(name).await;
+    ErrorCode::Internal
+  };
+
+  workspace::delete(name, &get_vector_base_path()).map_err(|e| {
+    error_response(
+      code,
+      "workspace_delete_failed",
+      &format!("Failed to delete workspace: {e}"),
+      transaction_id,
+    )
+  })?;
+
+  server_info(&format!("Deleted workspace {name}"), "insights-workspaces").await;
+  Ok(ResponseJson(BaseResponse::success((), transaction_id)))
+}
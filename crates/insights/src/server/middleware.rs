@@ -1,21 +1,25 @@
 //! Request context and middleware for the insights REST API
 //!
 //! Provides unified request context containing logger and request metadata
-//! that is automatically injected into all endpoints via middleware.
+//! that is automatically injected into all endpoints via middleware, and the
+//! bearer-token check that guards them.
 
 use axum::{
   extract::Request,
-  http::{HeaderMap, Method, Uri},
+  http::{header::AUTHORIZATION, HeaderMap, Method, Uri},
   middleware::Next,
-  response::Response,
+  response::{IntoResponse, Response},
 };
 use bentley::daemon_logs::LogContext;
 use bentley::DaemonLogs;
 use std::sync::Arc;
 use uuid::Uuid;
 
-#[cfg(feature = "ml-features")]
+use crate::server::errors::{error_response, ErrorResponse};
+#[cfg(feature = "semantic")]
 use crate::server::services::vector_database::BoxedVectorDatabase;
+use crate::server::services::{auth, workspace};
+use crate::server::types::ErrorCode;
 
 /// Request context containing logger and request metadata
 #[derive(Clone)]
@@ -30,28 +34,37 @@ pub struct RequestContext {
   pub headers: HeaderMap,
   /// Shared logger instance
   pub logger: Arc<DaemonLogs>,
-  /// Vector database service instance (only available with ml-features)
-  #[cfg(feature = "ml-features")]
+  /// Workspace the request operates on
+  pub workspace: String,
+  /// Vector database of the request's workspace (only available with the semantic feature)
+  #[cfg(feature = "semantic")]
   pub vector_db: Arc<BoxedVectorDatabase>,
 }
 
 impl RequestContext {
   /// Create a new request context (with ML features)
-  #[cfg(feature = "ml-features")]
+  #[cfg(feature = "semantic")]
   pub fn new(
     method: Method,
     uri: Uri,
     headers: HeaderMap,
     logger: Arc<DaemonLogs>,
+    workspace: String,
     vector_db: Arc<BoxedVectorDatabase>,
   ) -> Self {
-    Self { request_id: Uuid::new_v4(), method, uri, headers, logger, vector_db }
+    Self { request_id: Uuid::new_v4(), method, uri, headers, logger, workspace, vector_db }
   }
 
   /// Create a new request context (without ML features)  
-  #[cfg(not(feature = "ml-features"))]
-  pub fn new(method: Method, uri: Uri, headers: HeaderMap, logger: Arc<DaemonLogs>) -> Self {
-    Self { request_id: Uuid::new_v4(), method, uri, headers, logger }
+  #[cfg(not(feature = "semantic"))]
+  pub fn new(
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    logger: Arc<DaemonLogs>,
+    workspace: String,
+  ) -> Self {
+    Self { request_id: Uuid::new_v4(), method, uri, headers, logger, workspace }
   }
 
   /// Log an info message with request context
@@ -176,11 +189,28 @@ static GLOBAL_LOGGER: once_cell::sync::OnceCell<Arc<DaemonLogs>> = once_cell::sy
 /// Global log level (defaults to Info to reduce verbosity)
 static GLOBAL_LOG_LEVEL: once_cell::sync::OnceCell<LogLevel> = once_cell::sync::OnceCell::new();
 
-/// Global vector database service instance (only with ml-features)
-#[cfg(feature = "ml-features")]
+/// Global vector database service instance (only with the semantic feature)
+#[cfg(feature = "semantic")]
 static GLOBAL_VECTOR_DB: once_cell::sync::OnceCell<Arc<BoxedVectorDatabase>> =
   once_cell::sync::OnceCell::new();
 
+/// Opens the current workspace's vector database (only with the semantic feature)
+#[cfg(feature = "semantic")]
+pub type VectorDbOpener = fn() -> std::pin::Pin<
+  Box<dyn std::future::Future<Output = anyhow::Result<BoxedVectorDatabase>> + Send>,
+>;
+
+/// How vector databases of workspaces other than the default are opened
+#[cfg(feature = "semantic")]
+static VECTOR_DB_OPENER: once_cell::sync::OnceCell<VectorDbOpener> =
+  once_cell::sync::OnceCell::new();
+
+/// Vector databases of workspaces other than the default, opened on first use
+#[cfg(feature = "semantic")]
+static WORKSPACE_VECTOR_DBS: once_cell::sync::Lazy<
+  tokio::sync::Mutex<std::collections::HashMap<String, Arc<BoxedVectorDatabase>>>,
+> = once_cell::sync::Lazy::new(Default::default);
+
 /// Initialize the global logger and log level
 pub fn init_global_logger(logger: Arc<DaemonLogs>) -> Result<(), Arc<DaemonLogs>> {
   // Set default log level to Info (less verbose than before)
@@ -222,44 +252,114 @@ pub async fn log_to_both(level: LogLevel, message: &str, component: &str) {
   }
 }
 
-/// Initialize the global vector database service (only with ml-features)
-#[cfg(feature = "ml-features")]
+/// Initialize the global vector database service (only with the semantic feature)
+#[cfg(feature = "semantic")]
 pub fn init_global_vector_db(
   vector_db: Arc<BoxedVectorDatabase>,
 ) -> Result<(), Arc<BoxedVectorDatabase>> {
   GLOBAL_VECTOR_DB.set(vector_db)
 }
 
+/// Set how vector databases of workspaces other than the default are opened
+#[cfg(feature = "semantic")]
+pub fn init_vector_db_opener(opener: VectorDbOpener) -> Result<(), VectorDbOpener> {
+  VECTOR_DB_OPENER.set(opener)
+}
+
+/// Get the vector database of a workspace, opening it on first use
+#[cfg(feature = "semantic")]
+pub async fn workspace_vector_db(name: &str) -> anyhow::Result<Arc<BoxedVectorDatabase>> {
+  if name == workspace::DEFAULT {
+    return Ok(get_global_vector_db().clone());
+  }
+
+  let mut databases = WORKSPACE_VECTOR_DBS.lock().await;
+  if let Some(database) = databases.get(name) {
+    return Ok(database.clone());
+  }
+  let opener = VECTOR_DB_OPENER
+    .get()
+    .ok_or_else(|| anyhow::anyhow!("Workspace vector databases have not been initialized"))?;
+  let database = Arc::new(workspace::scope(name.to_string(), opener()).await?);
+  databases.insert(name.to_string(), database.clone());
+  Ok(database)
+}
+
+/// Close a deleted workspace's vector database
+#[cfg(feature = "semantic")]
+pub async fn forget_workspace_vector_db(name: &str) {
+  WORKSPACE_VECTOR_DBS.lock().await.remove(name);
+}
+
 /// Get the global logger instance
 pub fn get_global_logger() -> &'static Arc<DaemonLogs> {
   GLOBAL_LOGGER.get().expect("Global logger should be initialized before use")
 }
 
-/// Get the global vector database service instance (only with ml-features)
-#[cfg(feature = "ml-features")]
+/// Get the global vector database service instance (only with the semantic feature)
+#[cfg(feature = "semantic")]
 pub fn get_global_vector_db() -> &'static Arc<BoxedVectorDatabase> {
   GLOBAL_VECTOR_DB.get().expect("Global vector database service should be initialized before use")
 }
 
-/// Middleware to inject RequestContext into all requests
+/// Workspace named by a request, checked to exist
+fn requested_workspace(headers: &HeaderMap) -> Result<String, ErrorResponse> {
+  let Some(value) = headers.get(workspace::HEADER) else {
+    return Ok(workspace::DEFAULT.to_string());
+  };
+  let reject =
+    |code, error: &str, message: &str| error_response(code, error, message, Uuid::new_v4());
+
+  let name = value.to_str().unwrap_or_default().trim().to_string();
+  if let Err(e) = workspace::validate(&name) {
+    return Err(reject(ErrorCode::ValidationFailed, "invalid_workspace", &e.to_string()));
+  }
+  match workspace::exists(&name) {
+    Ok(true) => Ok(name),
+    Ok(false) => Err(reject(
+      ErrorCode::NotFound,
+      "workspace_not_found",
+      &format!("Workspace '{name}' not found; create it with `insights workspace create {name}`"),
+    )),
+    Err(e) => Err(reject(ErrorCode::Internal, "workspace_lookup_failed", &e.to_string())),
+  }
+}
+
+/// Middleware to inject RequestContext into all requests and run them in their workspace
 pub async fn request_context_middleware(request: Request, next: Next) -> Response {
   let logger = get_global_logger().clone();
 
   let method = request.method().clone();
   let uri = request.uri().clone();
   let headers = request.headers().clone();
+  let workspace = match requested_workspace(&headers) {
+    Ok(workspace) => workspace,
+    Err(error) => return error.into_response(),
+  };
 
   // Create context conditionally based on ML features availability
   let context = {
-    #[cfg(feature = "ml-features")]
+    #[cfg(feature = "semantic")]
     {
-      let vector_db = get_global_vector_db().clone();
-      RequestContext::new(method, uri, headers, logger, vector_db)
+      let vector_db = match workspace_vector_db(&workspace).await {
+        Ok(vector_db) => vector_db,
+        Err(e) => {
+          let message = format!("Vector database for workspace '{workspace}' unavailable: {e}");
+          return error_response(
+            ErrorCode::IndexUnavailable,
+            "vector_db_unavailable",
+            &message,
+            Uuid::new_v4(),
+          )
+          .into_response();
+        }
+      };
+      RequestContext::new(method, uri, headers, logger, workspace.clone(), vector_db)
     }
 
-    #[cfg(not(feature = "ml-features"))]
+    #[cfg(not(feature = "semantic"))]
     {
-      RequestContext::new(method, uri, headers, logger)
+      RequestContext::new(method, uri, headers, logger, workspace.clone())
     }
   };
 
@@ -272,7 +372,7 @@ pub async fn request_context_middleware(request: Request, next: Next) -> Respons
   request.extensions_mut().insert(context.clone());
 
   // Process the request
-  let response = next.run(request).await;
+  let response = workspace::scope(workspace, next.run(request)).await;
 
   // Log request completion
   let duration = start_time.elapsed();
@@ -281,6 +381,31 @@ pub async fn request_context_middleware(request: Request, next: Next) -> Respons
 
   response
 }
+
+/// Middleware to reject requests without a token of sufficient scope
+pub async fn auth_middleware(request: Request, next: Next) -> Response {
+  let table = auth::current_table().await;
+  let authorization = request.headers().get(AUTHORIZATION).and_then(|value| value.to_str().ok());
+
+  match auth::authorize(
+    table.as_deref(),
+    auth::get_require_auth(),
+    auth::tokens_minted(),
+    request.method(),
+    request.uri().path(),
+    authorization,
+  ) {
+    Ok(_) => next.run(request).await,
+    Err(message) => {
+      let path = request.uri().path().to_string();
+      server_warn(&format!("Rejected {} {path}: {message}", request.method()), "insights-auth")
+        .await;
+      error_response(ErrorCode::Unauthorized, "unauthorized", &message, Uuid::new_v4())
+        .into_response()
+    }
+  }
+}
+
 /// Convenient async logging functions that write to both console and file
 pub async fn server_info(message: &str, component: &str) {
   log_to_both(LogLevel::Info, message, component).await;
@@ -3,9 +3,11 @@
 //! Provides HTTP REST endpoints for the insights knowledge management system.
 //! Uses axum for routing and schemars for OpenAPI documentation generation.
 
+pub mod errors;
 pub mod handlers;
 pub mod middleware;
 pub mod models;
+pub mod openapi;
 pub mod routing;
 pub mod services;
 pub mod startup;
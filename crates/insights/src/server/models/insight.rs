@@ -5,8 +5,12 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::server::models::{tag, topic};
+use crate::server::services::workspace;
+use crate::store::store;
+
 // Default values for backwards compatibility with existing insight files
-fn default_created_at() -> DateTime<Utc> {
+pub(crate) fn default_created_at() -> DateTime<Utc> {
   // For existing insights, use a reasonable fallback date
   DateTime::parse_from_rfc3339("2025-05-01T00:00:00Z").unwrap().with_timezone(&Utc)
 }
@@ -30,6 +34,8 @@ pub struct InsightMetaData {
   #[serde(default)]
   pub name: String,
   pub overview: String,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
 
   // Temporal metadata - always included in files
   #[serde(default = "default_created_at")]
@@ -56,6 +62,8 @@ pub struct Insight {
   pub name: String,
   pub overview: String,
   pub details: String,
+  #[serde(default)]
+  pub tags: Vec<String>,
 
   // Temporal metadata
   pub created_at: DateTime<Utc>,
@@ -77,6 +85,7 @@ impl Insight {
       name,
       overview,
       details,
+      tags: Vec::new(),
       created_at: now,
       last_updated: now,
       update_count: 0,
@@ -90,34 +99,38 @@ impl Insight {
 
 pub fn file_path(insight: &Insight) -> Result<PathBuf> {
   let insights_root = get_insights_root()?;
+  if let Some(problem) = topic::invalid(&insight.topic) {
+    return Err(anyhow!("Invalid insight {}/{}: {problem}", insight.topic, insight.name));
+  }
   // Normalize file paths for x-platform compatibility.
   // Original case is preserved in insight metadata.
-  let normalized_topic = insight.topic.to_lowercase();
   let normalized_name = insight.name.to_lowercase();
-  Ok(insights_root.join(&normalized_topic).join(format!("{normalized_name}.insight.md")))
+  Ok(topic::dir(&insights_root, &insight.topic).join(format!("{normalized_name}.insight.md")))
 }
 
 pub fn save(insight: &Insight) -> Result<()> {
-  let file_path = file_path(insight)?;
-  ensure_parent_dir_exists(&file_path)?;
-  check_insight_is_new(&file_path, &insight.topic, &insight.name)?;
-  write_to_file(insight, &file_path)
+  store()?.save(insight)
 }
 
 /// Save an insight, overwriting if it already exists (used for embedding updates)
 #[allow(dead_code)]
 pub fn save_existing(insight: &Insight) -> Result<()> {
-  let file_path = file_path(insight)?;
-  write_to_file(insight, &file_path)
+  store()?.save_existing(insight)
 }
 
-fn write_to_file(insight: &Insight, file_path: &PathBuf) -> Result<()> {
+pub(crate) fn write_to_file(insight: &Insight, file_path: &PathBuf) -> Result<()> {
   ensure_parent_dir_exists(file_path)?;
+  fs::write(file_path, to_markdown(insight)?)?;
+  Ok(())
+}
 
+/// Render an insight exactly as it is stored on disk
+pub fn to_markdown(insight: &Insight) -> Result<String> {
   let frontmatter = InsightMetaData {
     topic: insight.topic.clone(),
     name: insight.name.clone(),
     overview: insight.overview.clone(),
+    tags: insight.tags.clone(),
     // Include temporal metadata in files - useful for filtering and UX
     created_at: insight.created_at,
     last_updated: insight.last_updated,
@@ -131,30 +144,22 @@ fn write_to_file(insight: &Insight, file_path: &PathBuf) -> Result<()> {
   };
 
   let yaml_content = serde_yaml::to_string(&frontmatter)?;
-  let content = format!("---\n{}---\n\n# Details\n{}", yaml_content, insight.details);
-  fs::write(file_path, content)?;
-
-  Ok(())
+  Ok(format!("---\n{}---\n\n# Details\n{}", yaml_content, insight.details))
 }
 
 pub fn load(topic: &str, name: &str) -> Result<Insight> {
-  let file_path = make_insight_path(topic, name)?;
-
-  if !file_path.exists() {
-    return Err(anyhow!("Insight {}/{} not found", topic, name));
-  }
-
-  let content = fs::read_to_string(&file_path)?;
-  parse_insight_from_content(topic, name, &content)
+  store()?.load(topic, name)
 }
 
 pub fn load_from_path(path: &std::path::Path) -> Result<Insight> {
   let content = fs::read_to_string(path)?;
-  parse_insight_from_content(
-    path.parent().unwrap().file_name().unwrap().to_str().unwrap(),
-    path.file_stem().unwrap().to_str().unwrap(),
-    &content,
-  )
+  let parent = path.parent().unwrap();
+  // Nested topics are the directory path below the root, not just the last directory
+  let topic_name = get_insights_root()
+    .ok()
+    .and_then(|root| topic::from_dir(&root, parent))
+    .unwrap_or_else(|| parent.file_name().unwrap().to_str().unwrap().to_string());
+  parse_insight_from_content(&topic_name, path.file_stem().unwrap().to_str().unwrap(), &content)
 }
 
 pub fn update(
@@ -162,44 +167,38 @@ pub fn update(
   new_overview: Option<&str>,
   new_details: Option<&str>,
 ) -> Result<()> {
+  update_with_tags(insight, new_overview, new_details, None)
+}
+
+/// Update an insight, replacing its tags as well when `new_tags` is given
+pub fn update_with_tags(
+  insight: &mut Insight,
+  new_overview: Option<&str>,
+  new_details: Option<&str>,
+  new_tags: Option<&[String]>,
+) -> Result<()> {
+  if new_overview.is_none() && new_details.is_none() && new_tags.is_none() {
+    return Err(anyhow!("At least one of overview, details or tags must be provided"));
+  }
+
   if let Some(overview) = new_overview {
     insight.overview = overview.to_string();
   }
   if let Some(details) = new_details {
     insight.details = details.to_string();
   }
-
-  if new_overview.is_none() && new_details.is_none() {
-    return Err(anyhow!("At least one of overview or details must be provided"));
+  if let Some(tags) = new_tags {
+    insight.tags = tag::normalize_all(tags);
   }
 
   // Update temporal metadata
   insight.last_updated = Utc::now();
   insight.update_count += 1;
 
-  let existing_file_path = make_insight_path(&insight.topic, &insight.name)?;
-  if !existing_file_path.exists() {
-    return Err(anyhow!("Insight {}/{} not found", insight.topic, insight.name));
-  }
-
-  let new_file_path = file_path(insight)?;
-
   // Gets recomputed lazily on next search.
   clear_embedding(insight);
 
-  // Delete the existing file FIRST to ensure cross-platform compatibility.
-  // Prevents issues on case-insensitive filesystems
-  fs::remove_file(&existing_file_path)?;
-
-  // Clean up empty directory from old location
-  if let Some(parent) = existing_file_path.parent() {
-    let _ = fs::remove_dir(parent);
-  }
-
-  // Now save to the normalized path
-  write_to_file(insight, &new_file_path)?;
-
-  Ok(())
+  store()?.replace(insight)
 }
 
 pub fn clear_embedding(insight: &mut Insight) {
@@ -210,14 +209,16 @@ pub fn clear_embedding(insight: &mut Insight) {
 }
 
 pub fn delete(insight: &Insight) -> Result<()> {
-  let file_path = file_path(insight)?;
-  check_insight_exists(&file_path, &insight.topic, &insight.name)?;
-  fs::remove_file(&file_path)?;
-  cleanup_empty_dir(&file_path)?;
-  Ok(())
+  store()?.delete(insight)
 }
 
+/// Insights root of the current workspace
 pub fn get_insights_root() -> Result<PathBuf> {
+  Ok(workspace::dir(&get_base_root()?, &workspace::current()))
+}
+
+/// Insights root of the default workspace, which holds the other workspaces too
+pub fn get_base_root() -> Result<PathBuf> {
   // Allow tests or callers to override the root directory via env var
   if let Ok(custom_root) = std::env::var("INSIGHTS_ROOT") {
     return Ok(PathBuf::from(custom_root));
@@ -248,7 +249,7 @@ pub fn parse_insight_with_metadata(content: &str) -> Result<(InsightMetaData, St
   }
 }
 
-fn split_frontmatter_content(content: &str) -> Result<(&str, &str)> {
+pub(crate) fn split_frontmatter_content(content: &str) -> Result<(&str, &str)> {
   if !content.starts_with(FRONTMATTER_START) {
     return Err(anyhow!("Invalid insight format: missing frontmatter"));
   }
@@ -285,6 +286,7 @@ fn parse_legacy_format_no_frontmatter(content: &str) -> (InsightMetaData, String
     topic: "".to_string(),
     name: "".to_string(),
     overview,
+    tags: Vec::new(),
     created_at: default_created_at(),
     last_updated: default_last_updated(),
     update_count: 0,
@@ -305,6 +307,7 @@ fn parse_legacy_format(frontmatter_section: &str, body: &str) -> (InsightMetaDat
     topic: "".to_string(),
     name: "".to_string(),
     overview,
+    tags: Vec::new(),
     created_at: default_created_at(),
     last_updated: default_last_updated(),
     update_count: 0,
@@ -317,7 +320,7 @@ fn parse_legacy_format(frontmatter_section: &str, body: &str) -> (InsightMetaDat
   (frontmatter, details)
 }
 
-fn clean_body_content(body: &str) -> String {
+pub(crate) fn clean_body_content(body: &str) -> String {
   body
     .lines()
     .skip_while(|line| line.trim().is_empty() || line.starts_with('#'))
@@ -327,73 +330,41 @@ fn clean_body_content(body: &str) -> String {
     .to_string()
 }
 
+/// Every topic holding insights, nested ones included (`rust/async`), sorted
 pub fn get_topics() -> Result<Vec<String>> {
-  let insights_root = get_insights_root()?;
-
-  if !insights_root.exists() {
-    return Ok(vec![]);
-  }
+  store()?.topics()
+}
 
-  let mut topics = Vec::new();
+pub(crate) fn collect_topics(
+  root: &std::path::Path,
+  dir: &std::path::Path,
+  topics: &mut Vec<String>,
+) -> Result<()> {
+  let mut holds_insights = false;
 
-  for entry in fs::read_dir(&insights_root)? {
+  for entry in fs::read_dir(dir)? {
     let entry = entry?;
+    let path = entry.path();
     if entry.file_type()?.is_dir() {
-      if let Some(name) = entry.file_name().to_str() {
-        topics.push(name.to_string());
+      // Dot-directories hold internal state (e.g. archived insights), not topics
+      let hidden = entry.file_name().to_str().is_none_or(|name| name.starts_with('.'));
+      if !hidden {
+        collect_topics(root, &path, topics)?;
       }
+    } else if dir != root && is_insight_file(&path) {
+      holds_insights = true;
     }
   }
 
-  topics.sort();
-  Ok(topics)
-}
-
-pub fn get_insights(topic_filter: Option<&str>) -> Result<Vec<Insight>> {
-  let search_paths = get_search_paths(topic_filter)?;
-  let mut all_insights = Vec::new();
-
-  for topic_path in search_paths {
-    let mut topic_insights = collect_insights_from_topic(&topic_path)?;
-    all_insights.append(&mut topic_insights);
-  }
-
-  all_insights.sort_by_key(|insight| insight.name.clone());
-  Ok(all_insights)
-}
-
-fn get_search_paths(topic_filter: Option<&str>) -> Result<Vec<std::path::PathBuf>> {
-  let insights_root = get_insights_root()?;
-
-  if let Some(topic) = topic_filter {
-    return Ok(vec![insights_root.join(topic)]);
+  if holds_insights {
+    topics.extend(topic::from_dir(root, dir));
   }
-
-  let paths = get_topics()?.into_iter().map(|topic| insights_root.join(topic)).collect();
-  Ok(paths)
+  Ok(())
 }
 
-fn collect_insights_from_topic(topic_path: &std::path::Path) -> Result<Vec<Insight>> {
-  if !topic_path.exists() {
-    return Ok(Vec::new());
-  }
-
-  let topic_name = extract_topic_name(topic_path);
-  let mut insights = Vec::new();
-
-  for entry in fs::read_dir(topic_path)? {
-    let path = entry?.path();
-
-    if !is_insight_file(&path) {
-      continue;
-    }
-
-    if let Some(insight_name) = extract_insight_name(&path) {
-      insights.push(load(topic_name, &insight_name)?);
-    }
-  }
-
-  Ok(insights)
+/// Insights in a topic and every topic nested under it, or in all topics
+pub fn get_insights(topic_filter: Option<&str>) -> Result<Vec<Insight>> {
+  store()?.insights(topic_filter)
 }
 
 pub fn is_insight_file(path: &std::path::Path) -> bool {
@@ -410,13 +381,12 @@ pub fn is_insight_file(path: &std::path::Path) -> bool {
 
 // Shared helper functions used by multiple public functions
 
-fn make_insight_path(topic: &str, name: &str) -> Result<std::path::PathBuf> {
+pub(crate) fn make_insight_path(topic: &str, name: &str) -> Result<std::path::PathBuf> {
   let root = get_insights_root()?;
 
   // Try normalized case first.
-  let normalized_topic = topic.to_lowercase();
   let normalized_name = name.to_lowercase();
-  let normalized_path = root.join(&normalized_topic).join(format!("{normalized_name}.insight.md"));
+  let normalized_path = topic::dir(&root, topic).join(format!("{normalized_name}.insight.md"));
 
   // If normalized path exists, use it
   if normalized_path.exists() {
@@ -433,28 +403,32 @@ fn make_insight_path(topic: &str, name: &str) -> Result<std::path::PathBuf> {
   Ok(normalized_path)
 }
 
-fn ensure_parent_dir_exists(path: &std::path::Path) -> Result<()> {
+pub(crate) fn ensure_parent_dir_exists(path: &std::path::Path) -> Result<()> {
   if let Some(parent) = path.parent() {
     fs::create_dir_all(parent)?;
   }
   Ok(())
 }
 
-fn check_insight_is_new(path: &std::path::Path, topic: &str, name: &str) -> Result<()> {
+pub(crate) fn check_insight_is_new(path: &std::path::Path, topic: &str, name: &str) -> Result<()> {
   if path.exists() {
     return Err(anyhow!("Insight {}/{} already exists", topic, name));
   }
   Ok(())
 }
 
-fn check_insight_exists(path: &std::path::Path, topic: &str, name: &str) -> Result<()> {
+pub(crate) fn check_insight_exists(path: &std::path::Path, topic: &str, name: &str) -> Result<()> {
   if !path.exists() {
     return Err(anyhow!("Insight {}/{} not found", topic, name));
   }
   Ok(())
 }
 
-fn parse_insight_from_content(topic: &str, name: &str, content: &str) -> Result<Insight> {
+pub(crate) fn parse_insight_from_content(
+  topic: &str,
+  name: &str,
+  content: &str,
+) -> Result<Insight> {
   let (fm, details) = parse_insight_with_metadata(content)?;
 
   Ok(Insight {
@@ -464,6 +438,7 @@ fn parse_insight_from_content(topic: &str, name: &str, content: &str) -> Result<
     name: if !fm.name.is_empty() { fm.name } else { name.to_string() },
     overview: fm.overview,
     details,
+    tags: fm.tags,
     // Handle temporal metadata with backwards compatibility
     created_at: fm.created_at,
     last_updated: fm.last_updated,
@@ -475,20 +450,19 @@ fn parse_insight_from_content(topic: &str, name: &str, content: &str) -> Result<
   })
 }
 
-fn cleanup_empty_dir(path: &std::path::Path) -> Result<()> {
-  if let Some(dir) = path.parent() {
-    if dir.read_dir()?.next().is_none() {
-      fs::remove_dir(dir)?;
+/// Remove the insight's topic directory, and any parents, once they are empty
+pub(crate) fn cleanup_empty_dir(path: &std::path::Path) -> Result<()> {
+  let root = get_insights_root()?;
+  for dir in path.ancestors().skip(1).take_while(|dir| dir.starts_with(&root) && *dir != root) {
+    if dir.read_dir()?.next().is_some() {
+      break;
     }
+    fs::remove_dir(dir)?;
   }
   Ok(())
 }
 
-fn extract_topic_name(topic_path: &std::path::Path) -> &str {
-  topic_path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown")
-}
-
-fn extract_insight_name(path: &std::path::Path) -> Option<String> {
+pub(crate) fn extract_insight_name(path: &std::path::Path) -> Option<String> {
   let file_stem = path.file_stem()?.to_str()?;
 
   if !file_stem.ends_with(".insight") {
@@ -1 +1,9 @@
 pub mod insight;
+pub mod ranking;
+pub mod retention;
+pub mod schema;
+pub mod sharding;
+pub mod stats;
+pub mod tag;
+pub mod topic;
+pub mod webhook;
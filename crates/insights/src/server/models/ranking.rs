@@ -0,0 +1,111 @@
+//! Ranking adjustments applied on top of search similarity
+//!
+//! `ranking.yaml` at the insights root sets how strongly recently updated insights
+//! are favoured and which topics are boosted. Everything is neutral by default, and
+//! a search request can override any of it.
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::server::models::{insight, topic};
+
+const CONFIG_FILE: &str = "ranking.yaml";
+
+const DEFAULT_HALF_LIFE_DAYS: f32 = 30.0;
+
+fn default_half_life_days() -> f32 {
+  DEFAULT_HALF_LIFE_DAYS
+}
+
+/// Recency decay and topic boosts for search ranking
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RankingConfig {
+  /// Share of its score a just-updated insight gains, e.g. 0.2 (0 ignores age)
+  #[serde(default)]
+  pub recency_weight: f32,
+  /// Days for the recency bonus to halve
+  #[serde(default = "default_half_life_days")]
+  pub recency_half_life_days: f32,
+  /// Score multipliers per topic, also covering nested topics; the most specific wins
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub topic_boosts: BTreeMap<String, f32>,
+}
+
+impl Default for RankingConfig {
+  fn default() -> Self {
+    Self {
+      recency_weight: 0.0,
+      recency_half_life_days: DEFAULT_HALF_LIFE_DAYS,
+      topic_boosts: BTreeMap::new(),
+    }
+  }
+}
+
+impl RankingConfig {
+  pub fn validate(&self) -> Result<()> {
+    if !self.recency_weight.is_finite() || self.recency_weight < 0.0 {
+      return Err(anyhow!("Recency weight must be a non-negative number"));
+    }
+    if !self.recency_half_life_days.is_finite() || self.recency_half_life_days <= 0.0 {
+      return Err(anyhow!("Recency half-life must be a positive number of days"));
+    }
+    for (topic, boost) in &self.topic_boosts {
+      if !boost.is_finite() || *boost <= 0.0 {
+        return Err(anyhow!("Boost for topic '{topic}' must be a positive number"));
+      }
+    }
+    Ok(())
+  }
+
+  /// This configuration with a request's overrides applied
+  pub fn with_overrides(
+    mut self,
+    recency_weight: Option<f32>,
+    recency_half_life_days: Option<f32>,
+    topic_boosts: &BTreeMap<String, f32>,
+  ) -> Self {
+    self.recency_weight = recency_weight.unwrap_or(self.recency_weight);
+    self.recency_half_life_days = recency_half_life_days.unwrap_or(self.recency_half_life_days);
+    self.topic_boosts.extend(topic_boosts.iter().map(|(topic, boost)| (topic.clone(), *boost)));
+    self
+  }
+
+  /// Whether ranking would leave every score as it is
+  pub fn is_neutral(&self) -> bool {
+    self.recency_weight == 0.0 && self.topic_boosts.values().all(|boost| *boost == 1.0)
+  }
+
+  /// Multiplier for insights in `topic`, from the most specific boosted topic covering it
+  pub fn topic_boost(&self, topic: &str) -> f32 {
+    self
+      .topic_boosts
+      .iter()
+      .filter(|(boosted, _)| topic::within(topic, boosted))
+      .max_by_key(|(boosted, _)| boosted.len())
+      .map_or(1.0, |(_, boost)| *boost)
+  }
+}
+
+fn config_path() -> Result<PathBuf> {
+  Ok(insight::get_insights_root()?.join(CONFIG_FILE))
+}
+
+pub fn load_config() -> Result<RankingConfig> {
+  let path = config_path()?;
+  if !path.exists() {
+    return Ok(RankingConfig::default());
+  }
+
+  let content = fs::read_to_string(&path)?;
+  if content.trim().is_empty() {
+    return Ok(RankingConfig::default());
+  }
+
+  let config: RankingConfig = serde_yaml::from_str(&content)?;
+  config.validate()?;
+  Ok(config)
+}
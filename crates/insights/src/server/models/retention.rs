@@ -0,0 +1,134 @@
+//! Per-topic retention policies for ephemeral insights
+//!
+//! Policies live in a single YAML file at the insights root. Expired insights are
+//! moved into an archive directory rather than deleted outright.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::server::models::insight::{self, Insight};
+
+const POLICIES_FILE: &str = "retention.yaml";
+
+/// Directory (relative to the insights root) holding archived insights
+pub const ARCHIVE_DIR: &str = ".expired";
+
+/// Window in which upcoming expirations are surfaced to users
+pub const EXPIRY_WARNING_DAYS: i64 = 7;
+
+/// Retention policy for a single topic
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+  /// Expire insights this many days after their last update
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub expire_after_days: Option<u32>,
+  /// Expire every insight in the topic on this date
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub expire_on: Option<DateTime<Utc>>,
+}
+
+impl RetentionPolicy {
+  pub fn validate(&self) -> Result<()> {
+    if self.expire_after_days.is_none() && self.expire_on.is_none() {
+      return Err(anyhow!("A retention policy needs an expiry age or an expiry date"));
+    }
+    if self.expire_after_days == Some(0) {
+      return Err(anyhow!("Expiry age must be at least one day"));
+    }
+    Ok(())
+  }
+
+  /// When the given insight expires under this policy (earliest of the two rules)
+  pub fn expires_at(&self, insight: &Insight) -> Option<DateTime<Utc>> {
+    let by_age =
+      self.expire_after_days.map(|days| insight.last_updated + Duration::days(days as i64));
+
+    match (by_age, self.expire_on) {
+      (Some(age), Some(date)) => Some(age.min(date)),
+      (age, date) => age.or(date),
+    }
+  }
+}
+
+/// Policies keyed by normalized (lowercase) topic name
+pub type RetentionPolicies = BTreeMap<String, RetentionPolicy>;
+
+fn policies_path() -> Result<PathBuf> {
+  Ok(insight::get_insights_root()?.join(POLICIES_FILE))
+}
+
+pub fn load_policies() -> Result<RetentionPolicies> {
+  let path = policies_path()?;
+  if !path.exists() {
+    return Ok(RetentionPolicies::new());
+  }
+
+  let content = fs::read_to_string(&path)?;
+  if content.trim().is_empty() {
+    return Ok(RetentionPolicies::new());
+  }
+
+  Ok(serde_yaml::from_str(&content)?)
+}
+
+fn save_policies(policies: &RetentionPolicies) -> Result<()> {
+  let path = policies_path()?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(path, serde_yaml::to_string(policies)?)?;
+  Ok(())
+}
+
+pub fn set_policy(topic: &str, policy: RetentionPolicy) -> Result<()> {
+  policy.validate()?;
+  let mut policies = load_policies()?;
+  policies.insert(topic.to_lowercase(), policy);
+  save_policies(&policies)
+}
+
+pub fn remove_policy(topic: &str) -> Result<()> {
+  let mut policies = load_policies()?;
+  if policies.remove(&topic.to_lowercase()).is_none() {
+    return Err(anyhow!("No retention policy set for topic {}", topic));
+  }
+  save_policies(&policies)
+}
+
+/// Expiry time for an insight according to the loaded policies
+pub fn expires_at(policies: &RetentionPolicies, insight: &Insight) -> Option<DateTime<Utc>> {
+  policies.get(&insight.topic.to_lowercase()).and_then(|policy| policy.expires_at(insight))
+}
+
+pub fn is_expired(policies: &RetentionPolicies, insight: &Insight, now: DateTime<Utc>) -> bool {
+  expires_at(policies, insight).is_some_and(|expiry| expiry <= now)
+}
+
+/// Move an insight into the archive directory, keeping its topic layout
+pub fn archive(insight: &Insight) -> Result<PathBuf> {
+  let source = insight::file_path(insight)?;
+  if !source.exists() {
+    return Err(anyhow!("Insight {}/{} not found", insight.topic, insight.name));
+  }
+
+  let root = insight::get_insights_root()?;
+  let relative = source.strip_prefix(&root)?;
+  let destination = root.join(ARCHIVE_DIR).join(relative);
+  if let Some(parent) = destination.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  fs::rename(&source, &destination)?;
+
+  if let Some(dir) = source.parent() {
+    if dir.read_dir()?.next().is_none() {
+      fs::remove_dir(dir)?;
+    }
+  }
+
+  Ok(destination)
+}
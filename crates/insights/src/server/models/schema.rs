@@ -0,0 +1,387 @@
+//! Soft schema for insight frontmatter
+//!
+//! Loading is lenient: missing fields get defaults and frontmatter that doesn't parse
+//! falls back to the legacy layout, so a hand- or agent-edited file never stops the
+//! knowledge base from loading. This module says what a well-formed insight looks
+//! like instead. Writes through the API are checked against it, and stored files can
+//! be checked and repaired with `insights validate --fix`. A repair only rewrites
+//! what can be recovered without guessing at content: a file with a problem that
+//! can't be fixed is left untouched.
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+
+use crate::server::models::insight::{self, Insight};
+use crate::server::models::{tag, topic};
+
+/// Frontmatter fields an insight file may have
+const KNOWN_FIELDS: &[&str] = &[
+  "topic",
+  "name",
+  "overview",
+  "tags",
+  "created_at",
+  "last_updated",
+  "update_count",
+  "embedding_version",
+  "embedding",
+  "embedding_text",
+  "embedding_computed",
+];
+
+/// One field that does not fit the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+  /// Frontmatter field at fault (e.g. "tags")
+  pub field: &'static str,
+  /// What is wrong, in a form that can be shown to the author
+  pub message: String,
+  /// Whether `insights validate --fix` can repair it
+  pub fixable: bool,
+}
+
+impl Problem {
+  fn fixable(field: &'static str, message: String) -> Self {
+    Self { field, message, fixable: true }
+  }
+
+  fn unfixable(field: &'static str, message: String) -> Self {
+    Self { field, message, fixable: false }
+  }
+}
+
+/// One sentence covering every problem, for error messages
+pub fn describe(problems: &[Problem]) -> String {
+  problems
+    .iter()
+    .map(|problem| format!("{}: {}", problem.field, problem.message))
+    .collect::<Vec<_>>()
+    .join("; ")
+}
+
+/// Problems with the fields of an insight about to be written
+pub fn check_fields(topic: &str, name: &str, tags: &[String]) -> Vec<Problem> {
+  let mut problems = Vec::new();
+  if let Some(problem) = topic::invalid(topic) {
+    problems.push(Problem::unfixable("topic", problem));
+  }
+  if let Some(problem) = invalid_name(name) {
+    problems.push(Problem::unfixable("name", problem));
+  }
+  problems
+    .extend(tag::problems(tags).into_iter().map(|problem| Problem::unfixable("tags", problem)));
+  problems
+}
+
+/// Why a name can't be used, if it can't
+fn invalid_name(name: &str) -> Option<String> {
+  if name.trim().is_empty() {
+    return Some("name is empty".to_string());
+  }
+  if name.contains(['/', '\\']) || name.starts_with('.') || name.chars().any(char::is_control) {
+    return Some(format!(
+      "name '{name}' may not contain slashes or control characters or start with '.'"
+    ));
+  }
+  None
+}
+
+/// Outcome of checking one stored insight file
+#[derive(Debug, Clone)]
+pub struct FileCheck {
+  pub topic: String,
+  pub name: String,
+  pub problems: Vec<Problem>,
+  /// The file rewritten to fit the schema, when every problem can be fixed
+  pub repaired: Option<String>,
+  /// Whether the repair was written back
+  pub fixed: bool,
+}
+
+/// Check the file of `topic/name`, whose location is taken as the truth
+pub fn check_file(topic: &str, name: &str, content: &str) -> FileCheck {
+  let (problems, repaired) = match insight::split_frontmatter_content(content) {
+    Ok((section, body)) => check_frontmatter(topic, name, section, body),
+    Err(_) => {
+      let problem = Problem::fixable(
+        "frontmatter",
+        "missing; the first line is taken as the overview".to_string(),
+      );
+      (vec![problem], repair_legacy(topic, name, content))
+    }
+  };
+
+  let repaired = match problems.iter().all(|problem| problem.fixable) {
+    true if !problems.is_empty() => {
+      repaired.and_then(|insight| insight::to_markdown(&insight).ok())
+    }
+    _ => None,
+  };
+  FileCheck { topic: topic.to_string(), name: name.to_string(), problems, repaired, fixed: false }
+}
+
+fn repair_legacy(topic: &str, name: &str, content: &str) -> Option<Insight> {
+  let (metadata, details) = insight::parse_insight_with_metadata(content).ok()?;
+  let mut insight = Insight::new(topic.to_string(), name.to_string(), metadata.overview, details);
+  insight.created_at = metadata.created_at;
+  insight.last_updated = metadata.last_updated;
+  Some(insight)
+}
+
+fn check_frontmatter(
+  topic: &str,
+  name: &str,
+  section: &str,
+  body: &str,
+) -> (Vec<Problem>, Option<Insight>) {
+  let fields = match serde_yaml::from_str::<Value>(section) {
+    Ok(Value::Mapping(fields)) => fields,
+    Ok(_) => {
+      return (vec![Problem::unfixable("frontmatter", "is not a mapping".to_string())], None)
+    }
+    Err(e) => {
+      return (vec![Problem::unfixable("frontmatter", format!("is not valid YAML: {e}"))], None)
+    }
+  };
+  let mut problems = Vec::new();
+
+  for key in fields.keys() {
+    let known = key.as_str().is_some_and(|key| KNOWN_FIELDS.contains(&key));
+    if !known {
+      let key = serde_yaml::to_string(key).unwrap_or_default();
+      let message = format!("unknown field '{}'; move it into the details", key.trim());
+      problems.push(Problem::unfixable("frontmatter", message));
+    }
+  }
+
+  let topic = located(&fields, "topic", topic, &mut problems);
+  let name = located(&fields, "name", name, &mut problems);
+  let overview = match fields.get("overview") {
+    Some(Value::String(overview)) => overview.clone(),
+    Some(value @ (Value::Number(_) | Value::Bool(_))) => {
+      let overview = scalar(value);
+      problems.push(Problem::fixable("overview", format!("must be a string, not {overview}")));
+      overview
+    }
+    Some(_) => {
+      problems.push(Problem::unfixable("overview", "must be a string".to_string()));
+      String::new()
+    }
+    None => {
+      problems.push(Problem::unfixable("overview", "is required".to_string()));
+      String::new()
+    }
+  };
+
+  let mut insight = Insight::new(topic, name, overview, insight::clean_body_content(body));
+  insight.tags = check_tags(fields.get("tags"), &mut problems);
+
+  let created_at = timestamp(&fields, "created_at", &mut problems);
+  let last_updated = timestamp(&fields, "last_updated", &mut problems);
+  insight.created_at = created_at.or(last_updated).unwrap_or_else(insight::default_created_at);
+  insight.last_updated = last_updated.unwrap_or(insight.created_at);
+  if insight.last_updated < insight.created_at {
+    problems.push(Problem::fixable("last_updated", "is earlier than created_at".to_string()));
+    insight.last_updated = insight.created_at;
+  }
+
+  insight.update_count = match fields.get("update_count") {
+    None => 0,
+    Some(value) => match value.as_u64().and_then(|count| u32::try_from(count).ok()) {
+      Some(count) => count,
+      None => {
+        let message = format!("must be a non-negative whole number, not {}", scalar(value));
+        problems.push(Problem::fixable("update_count", message));
+        scalar(value).trim().parse().unwrap_or(0)
+      }
+    },
+  };
+
+  (problems, Some(insight))
+}
+
+/// Topic or name from the frontmatter, which must agree with the file's location
+fn located(
+  fields: &Mapping,
+  field: &'static str,
+  location: &str,
+  problems: &mut Vec<Problem>,
+) -> String {
+  match fields.get(field) {
+    // Files are stored lowercased, so the frontmatter keeps the original case
+    Some(Value::String(value)) if value.eq_ignore_ascii_case(location) => value.clone(),
+    Some(Value::String(value)) => {
+      let message = format!("'{value}' does not match the file's location '{location}'");
+      problems.push(Problem::fixable(field, message));
+      location.to_string()
+    }
+    Some(_) => {
+      problems.push(Problem::fixable(field, "must be a string".to_string()));
+      location.to_string()
+    }
+    None => {
+      problems.push(Problem::fixable(field, "is missing".to_string()));
+      location.to_string()
+    }
+  }
+}
+
+/// Valid, normalized tags, recovered from a comma-separated string if need be
+fn check_tags(value: Option<&Value>, problems: &mut Vec<Problem>) -> Vec<String> {
+  let raw: Vec<String> = match value {
+    None => return Vec::new(),
+    Some(Value::Sequence(items)) => items.iter().map(scalar).collect(),
+    Some(Value::String(tags)) => {
+      problems.push(Problem::fixable("tags", "must be a list, not a string".to_string()));
+      tags.split(',').map(str::to_string).collect()
+    }
+    Some(_) => {
+      problems.push(Problem::fixable("tags", "must be a list of strings; dropped".to_string()));
+      return Vec::new();
+    }
+  };
+
+  let (valid, invalid): (Vec<String>, Vec<String>) =
+    raw.iter().cloned().partition(|tag| tag::invalid(tag).is_none());
+  for tag in &invalid {
+    let problem = tag::invalid(tag).unwrap_or_default();
+    problems.push(Problem::fixable("tags", format!("{problem}; dropped")));
+  }
+
+  let normalized = tag::normalize_all(&valid);
+  let listed = matches!(value, Some(Value::Sequence(_)));
+  if listed && invalid.is_empty() && normalized != raw {
+    problems.push(Problem::fixable(
+      "tags",
+      "must be lowercase, sorted and without duplicates".to_string(),
+    ));
+  }
+  normalized
+}
+
+/// A timestamp field, if it holds one; date-only values count as midnight UTC
+fn timestamp(
+  fields: &Mapping,
+  field: &'static str,
+  problems: &mut Vec<Problem>,
+) -> Option<DateTime<Utc>> {
+  let Some(value) = fields.get(field) else {
+    problems.push(Problem::fixable(field, "is missing".to_string()));
+    return None;
+  };
+
+  let text = scalar(value);
+  if let Ok(parsed) = DateTime::parse_from_rfc3339(text.trim()) {
+    return Some(parsed.with_timezone(&Utc));
+  }
+  let date = NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok();
+  let date = date.and_then(|date| date.and_hms_opt(0, 0, 0)).map(|time| time.and_utc());
+  let expected = if date.is_some() { "a full RFC 3339 timestamp" } else { "an RFC 3339 timestamp" };
+  problems.push(Problem::fixable(field, format!("must be {expected}, not '{text}'")));
+  date
+}
+
+/// A YAML scalar as text
+fn scalar(value: &Value) -> String {
+  match value {
+    Value::String(text) => text.clone(),
+    other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+  }
+}
+
+/// Check every stored insight, optionally only one topic and those nested under it
+///
+/// With `fix`, files whose problems can all be fixed are rewritten.
+pub fn validate_all(topic_filter: Option<&str>, fix: bool) -> Result<Vec<FileCheck>> {
+  let root = insight::get_insights_root()?;
+  let mut checks = Vec::new();
+
+  for topic_name in insight::get_topics()? {
+    if topic_filter.is_some_and(|filter| !topic::within(&topic_name, filter)) {
+      continue;
+    }
+    let mut paths: Vec<_> = fs::read_dir(topic::dir(&root, &topic_name))?
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect::<Result<_, _>>()?;
+    paths.sort();
+
+    for path in paths.into_iter().filter(|path| insight::is_insight_file(path)) {
+      let Some(name) = insight::extract_insight_name(&path) else { continue };
+      let mut check = check_file(&topic_name, &name, &fs::read_to_string(&path)?);
+      if check.problems.is_empty() {
+        continue;
+      }
+      if let (true, Some(repaired)) = (fix, &check.repaired) {
+        fs::write(&path, repaired)?;
+        check.fixed = true;
+      }
+      checks.push(check);
+    }
+  }
+  Ok(checks)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const DETAILS: &str = "\n# Details\nUse spawn_blocking for CPU-bound work.";
+
+  fn fields(problems: &[Problem]) -> Vec<&str> {
+    problems.iter().map(|problem| problem.field).collect()
+  }
+
+  #[test]
+  fn test_check_fields() {
+    assert!(check_fields("rust/async", "Pitfalls", &["tokio".to_string()]).is_empty());
+    let problems = check_fields("", "a/b", &["no way".to_string()]);
+    assert_eq!(fields(&problems), ["topic", "name", "tags"]);
+  }
+
+  #[test]
+  fn test_check_file_repairs_malformed_frontmatter() {
+    let content = format!(
+      "---\nname: Pitfalls\noverview: Blocking the runtime\ntags: Tokio, async\ncreated_at: 2025-06-01\nupdate_count: -1\n---\n{DETAILS}"
+    );
+    let check = check_file("rust", "pitfalls", &content);
+    assert_eq!(
+      fields(&check.problems),
+      ["topic", "tags", "created_at", "last_updated", "update_count"]
+    );
+
+    let repaired = insight::parse_insight_with_metadata(&check.repaired.unwrap()).unwrap();
+    let (metadata, details) = repaired;
+    assert_eq!((metadata.topic.as_str(), metadata.name.as_str()), ("rust", "Pitfalls"));
+    assert_eq!(metadata.tags, ["async", "tokio"]);
+    assert_eq!(metadata.created_at.to_rfc3339(), "2025-06-01T00:00:00+00:00");
+    assert_eq!(metadata.last_updated, metadata.created_at);
+    assert_eq!(details, "Use spawn_blocking for CPU-bound work.");
+
+    let markdown = check_file(
+      "rust",
+      "pitfalls",
+      &insight::to_markdown(&Insight::new(
+        "rust".into(),
+        "Pitfalls".into(),
+        "Blocking the runtime".into(),
+        "Details".into(),
+      ))
+      .unwrap(),
+    );
+    assert!(markdown.problems.is_empty());
+  }
+
+  #[test]
+  fn test_check_file_leaves_unfixable_files_alone() {
+    let content = format!("---\ntopic: rust\nname: pitfalls\nsource: blog\n---\n{DETAILS}");
+    let check = check_file("rust", "pitfalls", &content);
+    assert!(check.problems.iter().any(|problem| !problem.fixable));
+    assert!(check.repaired.is_none());
+
+    let check = check_file("rust", "pitfalls", "Just an overview\nand details");
+    assert_eq!(fields(&check.problems), ["frontmatter"]);
+    assert!(check.repaired.unwrap().contains("overview: Just an overview"));
+  }
+}
@@ -0,0 +1,179 @@
+//! Sharding of the embeddings index across multiple vector tables
+//!
+//! The shard layout lives in a YAML file at the insights root. Placement is a pure
+//! function of the layout and an insight's topic and name, so writers and readers
+//! always agree on where an embedding belongs.
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::server::models::insight;
+
+const CONFIG_FILE: &str = "sharding.yaml";
+
+/// Name of the unsharded embeddings table, and the prefix of every shard table
+pub const BASE_TABLE: &str = "insights_embeddings";
+
+const DEFAULT_BUCKETS: u32 = 8;
+const DEFAULT_MAX_IMBALANCE: f32 = 2.0;
+const DEFAULT_MAX_SHARD_ROWS: usize = 50_000;
+
+/// How embeddings are spread across tables
+#[derive(
+  Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ShardStrategy {
+  /// Everything in one table
+  #[default]
+  Single,
+  /// One table per topic, with oversized topics split into hash buckets
+  Topic,
+  /// A fixed number of tables chosen by hashing the insight id
+  Hash,
+}
+
+impl std::fmt::Display for ShardStrategy {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ShardStrategy::Single => write!(f, "single"),
+      ShardStrategy::Topic => write!(f, "topic"),
+      ShardStrategy::Hash => write!(f, "hash"),
+    }
+  }
+}
+
+fn default_buckets() -> u32 {
+  DEFAULT_BUCKETS
+}
+
+fn default_max_imbalance() -> f32 {
+  DEFAULT_MAX_IMBALANCE
+}
+
+fn default_max_shard_rows() -> usize {
+  DEFAULT_MAX_SHARD_ROWS
+}
+
+/// Shard layout for the embeddings index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ShardConfig {
+  #[serde(default)]
+  pub strategy: ShardStrategy,
+  /// Number of tables used by the hash strategy
+  #[serde(default = "default_buckets")]
+  pub buckets: u32,
+  /// Largest shard may hold this many times the mean before a rebalance is due
+  #[serde(default = "default_max_imbalance")]
+  pub max_imbalance: f32,
+  /// Rows a single shard may hold before a rebalance is due
+  #[serde(default = "default_max_shard_rows")]
+  pub max_shard_rows: usize,
+  /// Topics (normalized) split into this many hash buckets under the topic strategy
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub split_topics: BTreeMap<String, u32>,
+}
+
+impl Default for ShardConfig {
+  fn default() -> Self {
+    Self {
+      strategy: ShardStrategy::default(),
+      buckets: DEFAULT_BUCKETS,
+      max_imbalance: DEFAULT_MAX_IMBALANCE,
+      max_shard_rows: DEFAULT_MAX_SHARD_ROWS,
+      split_topics: BTreeMap::new(),
+    }
+  }
+}
+
+impl ShardConfig {
+  pub fn validate(&self) -> Result<()> {
+    if self.buckets == 0 {
+      return Err(anyhow!("Bucket count must be at least one"));
+    }
+    if self.max_imbalance < 1.0 {
+      return Err(anyhow!("Maximum imbalance must be at least 1.0"));
+    }
+    if self.max_shard_rows == 0 {
+      return Err(anyhow!("Maximum shard size must be at least one row"));
+    }
+    if self.split_topics.values().any(|&parts| parts == 0) {
+      return Err(anyhow!("Topic splits must use at least one bucket"));
+    }
+    Ok(())
+  }
+
+  /// Table an insight's embedding belongs in
+  pub fn table_for(&self, topic: &str, name: &str) -> String {
+    match self.strategy {
+      ShardStrategy::Single => BASE_TABLE.to_string(),
+      ShardStrategy::Hash => {
+        format!("{BASE_TABLE}_bucket_{}", bucket(topic, name, self.buckets))
+      }
+      ShardStrategy::Topic => {
+        let key = topic.to_lowercase();
+        let table = format!("{BASE_TABLE}_topic_{}", sanitize(&key));
+        match self.split_topics.get(&key) {
+          Some(&parts) if parts > 1 => format!("{table}_{}", bucket(topic, name, parts)),
+          _ => table,
+        }
+      }
+    }
+  }
+}
+
+/// Whether a table belongs to the embeddings index under any layout
+pub fn is_shard_table(table: &str) -> bool {
+  table == BASE_TABLE
+    || table
+      .strip_prefix(BASE_TABLE)
+      .is_some_and(|rest| rest.starts_with("_bucket_") || rest.starts_with("_topic_"))
+}
+
+/// Stable bucket for an insight id (FNV-1a, so placement survives restarts and upgrades)
+fn bucket(topic: &str, name: &str, buckets: u32) -> u32 {
+  let id = format!("{topic}:{name}");
+  let hash = id.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+    (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+  });
+  (hash % buckets as u64) as u32
+}
+
+/// Reduce a topic to characters that are safe in a table name
+fn sanitize(topic: &str) -> String {
+  topic.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect()
+}
+
+fn config_path() -> Result<PathBuf> {
+  Ok(insight::get_insights_root()?.join(CONFIG_FILE))
+}
+
+pub fn load_config() -> Result<ShardConfig> {
+  let path = config_path()?;
+  if !path.exists() {
+    return Ok(ShardConfig::default());
+  }
+
+  let content = fs::read_to_string(&path)?;
+  if content.trim().is_empty() {
+    return Ok(ShardConfig::default());
+  }
+
+  let config: ShardConfig = serde_yaml::from_str(&content)?;
+  config.validate()?;
+  Ok(config)
+}
+
+pub fn save_config(config: &ShardConfig) -> Result<()> {
+  config.validate()?;
+  let path = config_path()?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(path, serde_yaml::to_string(config)?)?;
+  Ok(())
+}
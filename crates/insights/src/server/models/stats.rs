@@ -0,0 +1,140 @@
+//! Access statistics for insights
+//!
+//! Every time the server hands out an insight, its counters are bumped in a small
+//! JSON file at the insights root: `reads` for direct gets and `search_hits` for
+//! appearances in search results. Only reads feed the popularity boost in search,
+//! since counting hits would let boosted insights keep boosting themselves.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::server::models::insight;
+
+const STATS_FILE: &str = "access-stats.json";
+
+/// Serializes read-modify-write cycles on the stats file
+static STATS_LOCK: Mutex<()> = Mutex::new(());
+
+/// How an insight has been accessed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessStats {
+  /// Times the insight was fetched directly
+  #[serde(default)]
+  pub reads: u64,
+  /// Times the insight was returned by a search
+  #[serde(default)]
+  pub search_hits: u64,
+  pub last_accessed: DateTime<Utc>,
+}
+
+/// Kind of access being recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+  Read,
+  SearchHit,
+}
+
+/// Stats keyed by `topic/name`
+pub type AccessLog = BTreeMap<String, AccessStats>;
+
+pub fn key(topic: &str, name: &str) -> String {
+  format!("{topic}/{name}")
+}
+
+/// Split a stats key back into topic and name (names never contain a slash)
+pub fn split_key(key: &str) -> (&str, &str) {
+  key.rsplit_once('/').unwrap_or(("", key))
+}
+
+fn stats_path() -> Result<PathBuf> {
+  Ok(insight::get_insights_root()?.join(STATS_FILE))
+}
+
+pub fn load() -> Result<AccessLog> {
+  let path = stats_path()?;
+  if !path.exists() {
+    return Ok(AccessLog::new());
+  }
+
+  let content = fs::read_to_string(&path)?;
+  if content.trim().is_empty() {
+    return Ok(AccessLog::new());
+  }
+
+  Ok(serde_json::from_str(&content)?)
+}
+
+fn save(log: &AccessLog) -> Result<()> {
+  let path = stats_path()?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(path, serde_json::to_string(log)?)?;
+  Ok(())
+}
+
+fn update(change: impl FnOnce(&mut AccessLog)) -> Result<()> {
+  let _guard = STATS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+  let mut log = load()?;
+  change(&mut log);
+  save(&log)
+}
+
+/// Count one access to each of the given insights
+pub fn record<'a>(
+  access: Access,
+  insights: impl IntoIterator<Item = (&'a str, &'a str)>,
+  now: DateTime<Utc>,
+) -> Result<()> {
+  let keys: Vec<String> = insights.into_iter().map(|(topic, name)| key(topic, name)).collect();
+  if keys.is_empty() {
+    return Ok(());
+  }
+
+  update(|log| {
+    for key in keys {
+      let stats =
+        log.entry(key).or_insert(AccessStats { reads: 0, search_hits: 0, last_accessed: now });
+      match access {
+        Access::Read => stats.reads += 1,
+        Access::SearchHit => stats.search_hits += 1,
+      }
+      stats.last_accessed = now;
+    }
+  })
+}
+
+/// Drop the stats of a deleted insight
+pub fn forget(topic: &str, name: &str) -> Result<()> {
+  update(|log| {
+    log.remove(&key(topic, name));
+  })
+}
+
+/// The most read insights, then the most found, then the most recently used
+pub fn top(log: &AccessLog, limit: usize) -> Vec<(&String, &AccessStats)> {
+  let mut ranked: Vec<(&String, &AccessStats)> = log.iter().collect();
+  ranked.sort_by(|(a_key, a), (b_key, b)| {
+    (b.reads, b.search_hits, b.last_accessed)
+      .cmp(&(a.reads, a.search_hits, a.last_accessed))
+      .then_with(|| a_key.cmp(b_key))
+  });
+  ranked.truncate(limit);
+  ranked
+}
+
+/// How often an insight is read relative to the most read one, 0.0-1.0 on a log scale
+pub fn popularity(log: &AccessLog, topic: &str, name: &str) -> f32 {
+  let most_reads = log.values().map(|stats| stats.reads).max().unwrap_or(0);
+  if most_reads == 0 {
+    return 0.0;
+  }
+
+  let reads = log.get(&key(topic, name)).map_or(0, |stats| stats.reads);
+  ((reads as f32).ln_1p() / (most_reads as f32).ln_1p()).clamp(0.0, 1.0)
+}
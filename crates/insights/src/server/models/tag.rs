@@ -0,0 +1,61 @@
+//! Insight tags
+//!
+//! Tags are short labels that cut across topics (`security`, `how-to`). They are
+//! kept lowercased, sorted and without duplicates in the insight frontmatter, and
+//! filtering on several tags matches only insights that carry every one of them.
+
+use std::collections::BTreeMap;
+
+use crate::server::models::insight::Insight;
+
+/// Canonical form of a tag as typed (`#Security ` becomes `security`)
+pub fn normalize(tag: &str) -> String {
+  tag.trim().trim_start_matches('#').to_lowercase()
+}
+
+/// Canonical, sorted and deduplicated tags
+pub fn normalize_all(tags: &[String]) -> Vec<String> {
+  let mut normalized: Vec<String> = tags.iter().map(|tag| normalize(tag)).collect();
+  normalized.sort();
+  normalized.dedup();
+  normalized
+}
+
+/// Why a tag can't be used, if it can't
+///
+/// Tags end up in index filters, so they are limited to letters, digits and
+/// `-`, `_` or `.`.
+pub fn invalid(tag: &str) -> Option<String> {
+  let normalized = normalize(tag);
+  if normalized.is_empty() {
+    return Some("tag is empty".to_string());
+  }
+  if !normalized.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+    return Some(format!("tag '{tag}' may only contain letters, digits, '-', '_' and '.'"));
+  }
+  None
+}
+
+/// Problems with any of the given tags
+pub fn problems(tags: &[String]) -> Vec<String> {
+  tags.iter().filter_map(|tag| invalid(tag)).collect()
+}
+
+/// Whether `tags` includes every tag in `required`
+pub fn has_all(tags: &[String], required: &[String]) -> bool {
+  required.iter().all(|wanted| tags.iter().any(|tag| tag.eq_ignore_ascii_case(&normalize(wanted))))
+}
+
+/// Every tag in use and how many insights carry it, most used first
+pub fn counts(insights: &[Insight]) -> Vec<(String, usize)> {
+  let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+  for insight in insights {
+    for tag in normalize_all(&insight.tags) {
+      *counts.entry(tag).or_default() += 1;
+    }
+  }
+
+  let mut counts: Vec<_> = counts.into_iter().collect();
+  counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+  counts
+}
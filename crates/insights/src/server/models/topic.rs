@@ -0,0 +1,127 @@
+//! Hierarchical topics
+//!
+//! Topics nest with `/`: `rust/async` is the `async` topic inside `rust`, and is
+//! stored as a nested directory under the insights root. A topic only needs a
+//! directory of its own once it holds insights, so parents such as `rust` may
+//! exist purely to group their children. Comparisons ignore case, matching the
+//! lowercased directory names on disk.
+
+use std::path::{Path, PathBuf};
+
+/// Separates the levels of a topic
+pub const SEPARATOR: char = '/';
+
+/// The levels of a topic, outermost first
+pub fn segments(topic: &str) -> impl Iterator<Item = &str> {
+  topic.split(SEPARATOR)
+}
+
+/// Why a topic can't be used, if it can't
+pub fn invalid(topic: &str) -> Option<String> {
+  if topic.trim().is_empty() {
+    return Some("topic is empty".to_string());
+  }
+  for segment in segments(topic) {
+    if segment.trim().is_empty() {
+      return Some(format!("topic '{topic}' has an empty level"));
+    }
+    // Dot-directories hold internal state such as history and indexes
+    if segment.contains('\\') || segment.starts_with('.') {
+      return Some(format!("topic '{topic}' has an invalid level '{segment}'"));
+    }
+  }
+  None
+}
+
+/// Whether `topic` is `prefix` itself or nested somewhere under it
+pub fn within(topic: &str, prefix: &str) -> bool {
+  let prefix = prefix.trim_end_matches(SEPARATOR);
+  if prefix.is_empty() {
+    return true;
+  }
+  let (topic, prefix) = (topic.to_lowercase(), prefix.to_lowercase());
+  match topic.strip_prefix(&prefix) {
+    Some(rest) => rest.is_empty() || rest.starts_with(SEPARATOR),
+    None => false,
+  }
+}
+
+/// Directory of a topic under the insights root
+pub fn dir(root: &Path, topic: &str) -> PathBuf {
+  segments(&topic.to_lowercase()).fold(root.to_path_buf(), |path, segment| path.join(segment))
+}
+
+/// Directory of a topic and of every topic nested under it
+pub fn dirs_within(root: &Path, topic: &str) -> Vec<PathBuf> {
+  let mut dirs = vec![dir(root, topic)];
+  let mut index = 0;
+  while index < dirs.len() {
+    if let Ok(entries) = std::fs::read_dir(&dirs[index]) {
+      let nested = entries.flatten().map(|entry| entry.path()).filter(|path| {
+        let hidden = path.file_name().and_then(|n| n.to_str()).is_none_or(|n| n.starts_with('.'));
+        path.is_dir() && !hidden
+      });
+      dirs.extend(nested.collect::<Vec<_>>());
+    }
+    index += 1;
+  }
+  dirs
+}
+
+/// Topic of a directory under the insights root, if it is inside it
+pub fn from_dir(root: &Path, dir: &Path) -> Option<String> {
+  let relative = dir.strip_prefix(root).ok()?;
+  let segments: Vec<&str> =
+    relative.components().map(|c| c.as_os_str().to_str()).collect::<Option<_>>()?;
+  (!segments.is_empty()).then(|| segments.join("/"))
+}
+
+/// A level of the topic tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicNode {
+  /// This level's own name (`async` in `rust/async`)
+  pub name: String,
+  /// Full topic (`rust/async`)
+  pub topic: String,
+  /// Whether the topic holds insights itself, rather than only grouping others
+  pub has_insights: bool,
+  pub children: Vec<TopicNode>,
+}
+
+/// Arrange topics into a tree, adding the parents that only group others
+pub fn tree(topics: &[String]) -> Vec<TopicNode> {
+  let mut roots: Vec<TopicNode> = Vec::new();
+  let mut sorted = topics.to_vec();
+  sorted.sort_by_key(|topic| topic.to_lowercase());
+
+  for topic in &sorted {
+    let mut level = &mut roots;
+    let mut path = String::new();
+    let count = segments(topic).count();
+    for (depth, segment) in segments(topic).enumerate() {
+      if !path.is_empty() {
+        path.push(SEPARATOR);
+      }
+      path.push_str(segment);
+
+      let index = match level.iter().position(|node| node.name.eq_ignore_ascii_case(segment)) {
+        Some(index) => index,
+        None => {
+          level.push(TopicNode {
+            name: segment.to_string(),
+            topic: path.clone(),
+            has_insights: false,
+            children: Vec::new(),
+          });
+          level.len() - 1
+        }
+      };
+      if depth + 1 == count {
+        level[index].has_insights = true;
+      }
+      level = &mut level[index].children;
+    }
+  }
+
+  roots
+}
@@ -0,0 +1,144 @@
+//! Webhooks notified when insights are created, updated or deleted
+//!
+//! Registrations live in a YAML file at the insights root, next to the retention
+//! policies. A webhook with no topics or no events listed matches all of them.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::server::models::insight;
+
+const WEBHOOKS_FILE: &str = "webhooks.yaml";
+
+/// Change to an insight that a webhook can subscribe to
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  PartialOrd,
+  Ord,
+  Serialize,
+  Deserialize,
+  JsonSchema,
+  clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEvent {
+  Created,
+  Updated,
+  Deleted,
+}
+
+impl std::fmt::Display for WebhookEvent {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      WebhookEvent::Created => write!(f, "created"),
+      WebhookEvent::Updated => write!(f, "updated"),
+      WebhookEvent::Deleted => write!(f, "deleted"),
+    }
+  }
+}
+
+/// A registered webhook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Webhook {
+  pub id: String,
+  pub url: String,
+  /// Key used to sign every payload sent to this webhook
+  pub secret: String,
+  /// Normalized (lowercase) topics to notify about; empty means every topic
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub topics: Vec<String>,
+  /// Events to notify about; empty means every event
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub events: Vec<WebhookEvent>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl Webhook {
+  pub fn new(url: &str, secret: &str, topics: &[String], events: &[WebhookEvent]) -> Self {
+    let mut topics: Vec<String> = topics.iter().map(|topic| topic.to_lowercase()).collect();
+    topics.sort();
+    topics.dedup();
+    let mut events = events.to_vec();
+    events.sort();
+    events.dedup();
+
+    let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+    Self {
+      id,
+      url: url.to_string(),
+      secret: secret.to_string(),
+      topics,
+      events,
+      created_at: Utc::now(),
+    }
+  }
+
+  pub fn validate(&self) -> Result<()> {
+    if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+      return Err(anyhow!("Webhook URL must start with http:// or https://"));
+    }
+    if self.secret.is_empty() {
+      return Err(anyhow!("Webhook secret must not be empty"));
+    }
+    Ok(())
+  }
+
+  /// Whether this webhook wants to hear about `event` on `topic`
+  pub fn matches(&self, topic: &str, event: WebhookEvent) -> bool {
+    let topic_matches = self.topics.is_empty() || self.topics.contains(&topic.to_lowercase());
+    let event_matches = self.events.is_empty() || self.events.contains(&event);
+    topic_matches && event_matches
+  }
+}
+
+fn webhooks_path() -> Result<PathBuf> {
+  Ok(insight::get_insights_root()?.join(WEBHOOKS_FILE))
+}
+
+pub fn load_webhooks() -> Result<Vec<Webhook>> {
+  let path = webhooks_path()?;
+  if !path.exists() {
+    return Ok(Vec::new());
+  }
+
+  let content = fs::read_to_string(&path)?;
+  if content.trim().is_empty() {
+    return Ok(Vec::new());
+  }
+
+  Ok(serde_yaml::from_str(&content)?)
+}
+
+fn save_webhooks(webhooks: &[Webhook]) -> Result<()> {
+  let path = webhooks_path()?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(path, serde_yaml::to_string(webhooks)?)?;
+  Ok(())
+}
+
+pub fn add_webhook(webhook: Webhook) -> Result<()> {
+  webhook.validate()?;
+  let mut webhooks = load_webhooks()?;
+  webhooks.push(webhook);
+  save_webhooks(&webhooks)
+}
+
+pub fn remove_webhook(id: &str) -> Result<()> {
+  let mut webhooks = load_webhooks()?;
+  let before = webhooks.len();
+  webhooks.retain(|webhook| webhook.id != id);
+  if webhooks.len() == before {
+    return Err(anyhow!("No webhook with id {}", id));
+  }
+  save_webhooks(&webhooks)
+}
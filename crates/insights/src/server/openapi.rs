@@ -0,0 +1,378 @@
+//! OpenAPI description of the REST API
+//!
+//! Every operation is listed with the request and response types its handler
+//! uses, and their schemas come from the same `JsonSchema` derives, so the spec
+//! follows the types as they change. Served at `GET /openapi.json`.
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+use crate::server::types::*;
+
+const GET: &str = "get";
+const POST: &str = "post";
+const PUT: &str = "put";
+const DELETE: &str = "delete";
+
+/// The OpenAPI 3.0 document for this server
+pub fn spec() -> Value {
+  let mut spec = Spec::new();
+
+  // Status
+  spec.plain::<StatusResponse>(GET, "/status", "Health check");
+  spec.plain::<VersionResponse>(GET, "/version", "Current API version");
+  spec.plain::<ApiInfoResponse>(GET, "/api", "API information and supported versions");
+  spec.plain::<LogsResponse>(GET, "/logs", "Recent server logs");
+  spec.body::<AskRequest, AskResponse>(
+    POST,
+    "/ask",
+    "Answer a question from the most relevant insights",
+  );
+
+  // Insights
+  spec.body::<AddInsightRequest, WriteInsightResponse>(POST, "/insights/add", "Add a new insight");
+  spec.body::<ImportInsightsRequest, ImportInsightsResponse>(
+    POST,
+    "/insights/import",
+    "Add many insights at once",
+  );
+  spec.body::<BatchRequest, BatchResponse>(
+    POST,
+    "/insights/batch",
+    "Apply many creates, updates and deletes, all or none",
+  );
+  spec.body::<ExportInsightsRequest, InsightsArchive>(
+    POST,
+    "/insights/export",
+    "Serialize insights into an archive",
+  );
+  spec.body::<BootstrapRequest, BootstrapResponse>(
+    POST,
+    "/insights/bootstrap",
+    "Populate a fresh knowledge base from a seed archive",
+  );
+  spec.body::<GetInsightRequest, GetInsightResponse>(
+    POST,
+    "/insights/get",
+    "Get a specific insight",
+  );
+  spec.body::<UpdateInsightRequest, WriteInsightResponse>(
+    PUT,
+    "/insights/update",
+    "Update an existing insight",
+  );
+  spec.body::<RemoveInsightRequest, ()>(DELETE, "/insights/remove", "Remove an insight");
+  spec.plain::<()>(DELETE, "/insights/clear", "Clear all insights");
+  spec.query::<ReindexQuery, ReindexStatusResponse>(
+    DELETE,
+    "/insights/index",
+    "Re-index all insights in the background",
+  );
+  spec.plain::<ReindexStatusResponse>(
+    GET,
+    "/insights/index/status",
+    "Progress of the current or last re-index",
+  );
+  spec.plain::<ListTopicsResponse>(GET, "/insights/list/topics", "List all topics");
+  spec.query::<ListInsightsQuery, ListInsightsResponse>(
+    GET,
+    "/insights/list/insights",
+    "List insights, optionally by topic and tags",
+  );
+  spec.plain::<ListTagsResponse>(
+    GET,
+    "/insights/list/tags",
+    "Every tag in use with how many insights carry it",
+  );
+  spec.body::<SearchRequest, SearchResponse>(POST, "/insights/search", "Search insights");
+  spec.plain::<ScanResponse>(GET, "/insights/scan", "Audit stored insights for secrets and PII");
+  spec.query::<LintQuery, LintResponse>(
+    GET,
+    "/insights/lint",
+    "Check stored insights against the content lint rules",
+  );
+  spec.body::<ValidateRequest, ValidateResponse>(
+    POST,
+    "/insights/validate",
+    "Check stored frontmatter against the insight schema, optionally repairing it",
+  );
+  spec.body::<SummarizeTopicRequest, TopicSummaryResponse>(
+    POST,
+    "/insights/summary",
+    "Outline a topic as clusters of related insights",
+  );
+  spec.body::<RelatedInsightsRequest, RelatedInsightsResponse>(
+    POST,
+    "/insights/related",
+    "Nearest insights to a stored insight by embedding",
+  );
+  spec.body::<FindDuplicatesRequest, FindDuplicatesResponse>(
+    POST,
+    "/insights/duplicates",
+    "Find clusters of near-identical insights",
+  );
+  spec.body::<HistoryRequest, HistoryResponse>(
+    POST,
+    "/insights/history",
+    "List the recorded versions of an insight",
+  );
+  spec.body::<DiffVersionsRequest, DiffVersionsResponse>(
+    POST,
+    "/insights/history/diff",
+    "Compare two versions of an insight",
+  );
+  spec.body::<RollbackRequest, RollbackResponse>(
+    POST,
+    "/insights/history/rollback",
+    "Restore an earlier version as the newest one",
+  );
+  spec.query::<RenderInsightQuery, RenderInsightResponse>(
+    GET,
+    "/insights/render",
+    "An insight with its markdown rendered as HTML",
+  );
+  spec.query::<StatsQuery, StatsResponse>(
+    GET,
+    "/insights/stats",
+    "The most used insights, most read first",
+  );
+
+  // Background indexing
+  spec.plain::<IndexingStatusResponse>(
+    GET,
+    "/insights/indexing",
+    "Resource limits and state of background indexing",
+  );
+  spec.plain::<IndexingStatusResponse>(
+    POST,
+    "/insights/indexing/pause",
+    "Hold background indexing until resumed",
+  );
+  spec.plain::<IndexingStatusResponse>(
+    POST,
+    "/insights/indexing/resume",
+    "Let paused background indexing continue",
+  );
+
+  // Retention
+  spec.plain::<ListRetentionResponse>(GET, "/insights/retention", "List all retention policies");
+  spec.body::<SetRetentionRequest, ()>(
+    PUT,
+    "/insights/retention",
+    "Create or replace a topic's retention policy",
+  );
+  spec.body::<RemoveRetentionRequest, ()>(
+    DELETE,
+    "/insights/retention",
+    "Remove a topic's retention policy",
+  );
+  spec.plain::<RetentionSweepResponse>(
+    POST,
+    "/insights/retention/sweep",
+    "Archive expired insights immediately",
+  );
+
+  // Backups
+  spec.plain::<ListBackupsResponse>(GET, "/insights/backups", "List backups, newest first");
+  spec.plain::<CreateBackupResponse>(
+    POST,
+    "/insights/backups",
+    "Back up insights and the vector database now, then prune old backups",
+  );
+  spec.body::<RestoreBackupRequest, RestoreBackupResponse>(
+    POST,
+    "/insights/backups/restore",
+    "Replace the insights with a backup, keeping a backup of the current state",
+  );
+
+  // Workspaces
+  spec.plain::<ListWorkspacesResponse>(GET, "/workspaces", "List workspaces, the default first");
+  spec.body::<CreateWorkspaceRequest, WorkspaceData>(POST, "/workspaces", "Create a workspace");
+  spec.body::<DeleteWorkspaceRequest, ()>(
+    DELETE,
+    "/workspaces",
+    "Delete a workspace with its insights and embeddings",
+  );
+
+  // Webhooks
+  spec.plain::<ListWebhooksResponse>(GET, "/insights/webhooks", "List all registered webhooks");
+  spec.body::<AddWebhookRequest, WebhookData>(POST, "/insights/webhooks", "Register a new webhook");
+  spec.body::<RemoveWebhookRequest, ()>(DELETE, "/insights/webhooks", "Remove a webhook");
+  spec.query::<DeliveriesQuery, ListDeliveriesResponse>(
+    GET,
+    "/insights/webhooks/deliveries",
+    "Recent delivery attempts, newest first",
+  );
+
+  // Shards
+  spec.plain::<ShardsResponse>(
+    GET,
+    "/insights/shards",
+    "Show the shard layout and the size of every shard",
+  );
+  spec.body::<ConfigureShardsRequest, RebalanceShardsResponse>(
+    PUT,
+    "/insights/shards",
+    "Switch to a new shard layout and migrate existing rows",
+  );
+  spec.body::<RebalanceShardsRequest, RebalanceShardsResponse>(
+    POST,
+    "/insights/shards/rebalance",
+    "Redistribute rows when shards have grown unevenly",
+  );
+
+  spec.finish()
+}
+
+/// Paths and schemas collected so far
+struct Spec {
+  generator: SchemaGenerator,
+  paths: Map<String, Value>,
+}
+
+impl Spec {
+  fn new() -> Self {
+    Self { generator: SchemaSettings::openapi3().into_generator(), paths: Map::new() }
+  }
+
+  /// An operation that takes no input
+  fn plain<R: JsonSchema>(&mut self, method: &str, path: &str, summary: &str) {
+    let operation = self.operation::<R>(summary);
+    self.add(method, path, operation);
+  }
+
+  /// An operation that takes a JSON body
+  fn body<B: JsonSchema, R: JsonSchema>(&mut self, method: &str, path: &str, summary: &str) {
+    let mut operation = self.operation::<R>(summary);
+    operation["requestBody"] = json!({
+      "required": true,
+      "content": { "application/json": { "schema": self.generator.subschema_for::<B>() } },
+    });
+    self.add(method, path, operation);
+  }
+
+  /// An operation that takes query parameters
+  fn query<Q: JsonSchema, R: JsonSchema>(&mut self, method: &str, path: &str, summary: &str) {
+    let mut operation = self.operation::<R>(summary);
+    operation["parameters"] = Value::Array(query_parameters::<Q>());
+    self.add(method, path, operation);
+  }
+
+  fn operation<R: JsonSchema>(&mut self, summary: &str) -> Value {
+    json!({
+      "summary": summary,
+      "responses": {
+        "200": {
+          "description": "Success",
+          "content": {
+            "application/json": { "schema": self.generator.subschema_for::<BaseResponse<R>>() },
+          },
+        },
+        "default": {
+          "description": "Error, described in `errors`",
+          "content": {
+            "application/json": { "schema": self.generator.subschema_for::<BaseResponse<()>>() },
+          },
+        },
+      },
+    })
+  }
+
+  fn add(&mut self, method: &str, path: &str, operation: Value) {
+    let item = self.paths.entry(path).or_insert_with(|| json!({}));
+    item[method] = operation;
+  }
+
+  fn finish(self) -> Value {
+    json!({
+      "openapi": "3.0.3",
+      "info": {
+        "title": "Insights API",
+        "version": env!("CARGO_PKG_VERSION"),
+        "description": "Knowledge base of insights for development workflows. Requests use \
+                        the default workspace unless they name another in the \
+                        `X-Insights-Workspace` header.",
+      },
+      "paths": self.paths,
+      "components": {
+        "schemas": self.generator.definitions(),
+        "securitySchemes": { "bearer": { "type": "http", "scheme": "bearer" } },
+      },
+      // Tokens are only enforced once one has been minted
+      "security": [{ "bearer": [] }, {}],
+    })
+  }
+}
+
+/// One query parameter per field of `Q`
+fn query_parameters<Q: JsonSchema>() -> Vec<Value> {
+  let mut settings = SchemaSettings::openapi3();
+  settings.inline_subschemas = true;
+  let root = settings.into_generator().into_root_schema_for::<Q>();
+  let Some(object) = root.schema.object else { return Vec::new() };
+
+  object
+    .properties
+    .into_iter()
+    .map(|(name, schema)| {
+      let description = match &schema {
+        Schema::Object(SchemaObject { metadata: Some(metadata), .. }) => {
+          metadata.description.clone()
+        }
+        _ => None,
+      };
+      json!({
+        "name": name,
+        "in": "query",
+        "required": object.required.contains(&name) && !is_nullable(&schema),
+        "description": description,
+        "schema": schema,
+      })
+    })
+    .collect()
+}
+
+fn is_nullable(schema: &Schema) -> bool {
+  match schema {
+    Schema::Object(object) => {
+      object.extensions.get("nullable") == Some(&Value::Bool(true))
+        || matches!(&object.instance_type, Some(SingleOrVec::Vec(types)) if types.contains(&InstanceType::Null))
+    }
+    Schema::Bool(_) => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_spec_references_resolve() {
+    let spec = spec();
+    let schemas = spec["components"]["schemas"].as_object().unwrap();
+    assert!(schemas.contains_key("AddInsightRequest"));
+
+    let text = spec.to_string();
+    for reference in text.split("\"$ref\":\"#/components/schemas/").skip(1) {
+      let name = &reference[..reference.find('"').unwrap()];
+      assert!(schemas.contains_key(name), "dangling reference to {name}");
+    }
+  }
+
+  #[test]
+  fn test_operations_describe_their_inputs() {
+    let spec = spec();
+    let paths = &spec["paths"];
+
+    assert!(paths["/insights/add"]["post"]["requestBody"].is_object());
+    assert!(paths["/insights/shards"]["get"]["requestBody"].is_null());
+    assert!(paths["/insights/shards"]["put"]["requestBody"].is_object());
+
+    let parameters = paths["/insights/render"]["get"]["parameters"].as_array().unwrap();
+    let names: Vec<&str> = parameters.iter().filter_map(|p| p["name"].as_str()).collect();
+    assert_eq!(names, ["name", "topic"]);
+    assert!(parameters.iter().all(|p| p["in"] == "query" && p["required"] == true));
+  }
+}
@@ -6,7 +6,10 @@ use axum::{
   Router,
 };
 
-use crate::server::handlers::{insights, logs, status};
+use crate::server::handlers::{
+  ask, backup, dedupe, events, history, indexing, insights, logs, related, retention, shards,
+  stats, status, summary, ui, webhooks, workspaces,
+};
 use crate::server::middleware::request_context_middleware;
 
 /// Create the main application router
@@ -16,17 +19,76 @@ pub fn create_router() -> Router {
     .route("/status", get(status::status))
     .route("/version", get(status::version))
     .route("/api", get(status::api_info))
+    .route("/openapi.json", get(status::openapi))
     // Logs endpoint
     .route("/logs", get(logs::get_logs_with_context))
+    // Conversational query endpoint
+    .route("/ask", post(ask::ask))
+    // Live change feed
+    .route("/ws/events", get(events::change_socket))
     // Insights endpoints
     .route("/insights/add", post(insights::add_insight))
+    .route("/insights/import", post(insights::import_insights))
+    .route("/insights/batch", post(insights::batch_insights))
+    .route("/insights/export", post(insights::export_insights))
+    .route("/insights/bootstrap", post(insights::bootstrap_insights))
     .route("/insights/get", post(insights::get_insight))
     .route("/insights/update", put(insights::update_insight))
     .route("/insights/remove", delete(insights::remove_insight))
     .route("/insights/clear", delete(insights::clear_insights))
     .route("/insights/index", delete(insights::reindex))
+    .route("/insights/index/status", get(insights::index_status))
     .route("/insights/list/topics", get(insights::list_topics))
     .route("/insights/list/insights", get(insights::list_insights))
+    .route("/insights/list/tags", get(insights::list_tags))
     .route("/insights/search", post(insights::search_insights))
+    .route("/insights/scan", get(insights::scan_insights))
+    .route("/insights/lint", get(insights::lint_insights))
+    .route("/insights/validate", post(insights::validate_insights))
+    .route("/insights/summary", post(summary::summarize_topic))
+    .route("/insights/related", post(related::related_insights))
+    .route("/insights/duplicates", post(dedupe::find_duplicates))
+    .route("/insights/history", post(history::list_history))
+    .route("/insights/history/diff", post(history::diff_versions))
+    .route("/insights/history/rollback", post(history::rollback))
+    .route("/insights/render", get(ui::render_insight))
+    .route("/insights/stats", get(stats::list_stats))
+    // Background indexing endpoints
+    .route("/insights/indexing", get(indexing::status))
+    .route("/insights/indexing/pause", post(indexing::pause))
+    .route("/insights/indexing/resume", post(indexing::resume))
+    // Retention policy endpoints
+    .route(
+      "/insights/retention",
+      get(retention::list_policies).put(retention::set_policy).delete(retention::remove_policy),
+    )
+    .route("/insights/retention/sweep", post(retention::sweep))
+    // Backup endpoints
+    .route("/insights/backups", get(backup::list_backups).post(backup::create_backup))
+    .route("/insights/backups/restore", post(backup::restore_backup))
+    // Webhook endpoints
+    .route(
+      "/insights/webhooks",
+      get(webhooks::list_webhooks).post(webhooks::add_webhook).delete(webhooks::remove_webhook),
+    )
+    .route("/insights/webhooks/deliveries", get(webhooks::list_deliveries))
+    // Shard management endpoints
+    .route("/insights/shards", get(shards::list_shards).put(shards::configure_shards))
+    .route("/insights/shards/rebalance", post(shards::rebalance_shards))
     .layer(middleware::from_fn(request_context_middleware))
+    // Workspace management is added after the context layer, so it is not bound to a workspace
+    .route(
+      "/workspaces",
+      get(workspaces::list_workspaces)
+        .post(workspaces::create_workspace)
+        .delete(workspaces::delete_workspace),
+    )
+}
+
+/// Routes for the browser UI, merged into the main router with `--ui`
+pub fn create_ui_router() -> Router {
+  Router::new()
+    .route("/ui", get(ui::index))
+    .route("/ui/app.js", get(ui::script))
+    .route("/ui/style.css", get(ui::stylesheet))
 }
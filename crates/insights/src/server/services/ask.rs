@@ -0,0 +1,228 @@
+//! Retrieval-augmented answers to natural-language questions
+//!
+//! Relevant insights are found with the regular hybrid search. If an LLM endpoint
+//! is stored in the `insights` secrets group, the top excerpts are sent to it as
+//! numbered sources and the answer cites them as `[n]`.
+//!
+//! The endpoint must speak the OpenAI chat completions protocol. Settings:
+//! `llm_endpoint` (full URL, required), `llm_model` (required), `llm_api_key`.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::server::services::similarity::extract_words;
+use crate::server::types::SearchResultData;
+
+/// Secrets group holding the LLM settings
+pub const SECRETS_GROUP: &str = "insights";
+
+/// Sources used when the request does not ask for a specific number
+pub const DEFAULT_SOURCES: usize = 5;
+
+/// Longest excerpt sent to the model or returned per source
+const EXCERPT_CHARS: usize = 600;
+
+/// Stay under the CLI's 30 second request timeout so excerpts still come back
+const LLM_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// Words that frame a question but say nothing about its subject
+const QUESTION_WORDS: &[&str] = &[
+  "how", "what", "why", "when", "where", "which", "who", "i", "me", "my", "can", "this", "that",
+  "there",
+];
+
+static CITATION: Lazy<Regex> =
+  Lazy::new(|| Regex::new(r"\[(\d+(?:\s*,\s*\d+)*)\]").expect("valid citation regex"));
+
+/// Where and how to reach the answering model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmConfig {
+  pub endpoint: String,
+  pub model: String,
+  pub api_key: Option<String>,
+}
+
+impl LlmConfig {
+  /// Settings from a secrets group, or None if the endpoint or model is missing
+  pub fn from_secrets(group: &HashMap<String, String>) -> Option<Self> {
+    let value = |key: &str| group.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    Some(Self {
+      endpoint: value("llm_endpoint")?,
+      model: value("llm_model")?,
+      api_key: value("llm_api_key"),
+    })
+  }
+
+  /// Read the settings through the secrets keeper without ever prompting
+  pub async fn load() -> Result<Option<Self>> {
+    let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
+      PathBuf::from(blizz_dir)
+    } else {
+      dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
+    };
+
+    let group = secrets::keeper_client::read_group(&base_path, SECRETS_GROUP).await?;
+    Ok(Self::from_secrets(&group))
+  }
+}
+
+/// Search terms for a question, in question order without stop words
+pub fn question_terms(question: &str) -> Vec<String> {
+  let meaningful = extract_words(question);
+  let mut seen = BTreeSet::new();
+  let terms: Vec<String> = question
+    .split_whitespace()
+    .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+    .filter(|word| meaningful.contains(word) && !QUESTION_WORDS.contains(&word.as_str()))
+    .filter(|word| seen.insert(word.clone()))
+    .collect();
+
+  if terms.is_empty() {
+    // A question made only of stop words still deserves a search
+    question.split_whitespace().map(str::to_lowercase).collect()
+  } else {
+    terms
+  }
+}
+
+/// Overview followed by the start of the details, cut at a word boundary
+pub fn excerpt(result: &SearchResultData) -> String {
+  let text = if result.details.trim().is_empty() {
+    result.overview.trim().to_string()
+  } else {
+    format!("{}\n{}", result.overview.trim(), result.details.trim())
+  };
+
+  if text.chars().count() <= EXCERPT_CHARS {
+    return text;
+  }
+  let cut: String = text.chars().take(EXCERPT_CHARS).collect();
+  let cut = cut.rsplit_once(char::is_whitespace).map_or(cut.as_str(), |(head, _)| head);
+  format!("{}…", cut.trim_end())
+}
+
+/// Prompt asking the model to answer only from the numbered sources
+pub fn build_prompt(question: &str, sources: &[SearchResultData]) -> String {
+  let mut prompt = String::from(
+    "Answer the question using only the numbered sources below. Cite every claim with the \
+     source number in square brackets, like [1]. If the sources do not answer the question, \
+     say so.\n\n",
+  );
+  for (index, source) in sources.iter().enumerate() {
+    prompt.push_str(&format!(
+      "[{}] {}/{}\n{}\n\n",
+      index + 1,
+      source.topic,
+      source.name,
+      excerpt(source)
+    ));
+  }
+  prompt.push_str(&format!("Question: {question}"));
+  prompt
+}
+
+/// Source numbers (1-based) cited in an answer, ignoring numbers out of range
+pub fn cited_sources(answer: &str, source_count: usize) -> BTreeSet<usize> {
+  CITATION
+    .captures_iter(answer)
+    .flat_map(|capture| {
+      capture[1].split(',').filter_map(|n| n.trim().parse::<usize>().ok()).collect::<Vec<_>>()
+    })
+    .filter(|n| (1..=source_count).contains(n))
+    .collect()
+}
+
+/// Ask the model and return its answer text
+pub async fn complete(config: &LlmConfig, prompt: &str) -> Result<String> {
+  let client = reqwest::Client::builder().timeout(LLM_TIMEOUT).build()?;
+  let body = json!({
+    "model": config.model,
+    "messages": [
+      { "role": "system", "content": "You answer questions about a personal knowledge base." },
+      { "role": "user", "content": prompt },
+    ],
+    "temperature": 0.2,
+  });
+
+  let mut request = client.post(&config.endpoint).json(&body);
+  if let Some(api_key) = &config.api_key {
+    request = request.bearer_auth(api_key);
+  }
+
+  let response = request.send().await?;
+  if !response.status().is_success() {
+    return Err(anyhow!("LLM endpoint returned HTTP {}", response.status()));
+  }
+
+  let payload: serde_json::Value = response.json().await?;
+  payload["choices"][0]["message"]["content"]
+    .as_str()
+    .map(|answer| answer.trim().to_string())
+    .filter(|answer| !answer.is_empty())
+    .ok_or_else(|| anyhow!("LLM response contained no answer"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn source(name: &str, overview: &str, details: &str) -> SearchResultData {
+    SearchResultData {
+      topic: "rust".to_string(),
+      name: name.to_string(),
+      overview: overview.to_string(),
+      details: details.to_string(),
+      tags: Vec::new(),
+      score: 1.0,
+      ranking: None,
+      explanation: None,
+    }
+  }
+
+  #[test]
+  fn test_question_terms_drop_stop_words_and_keep_order() {
+    assert_eq!(
+      question_terms("How do I configure the tokio runtime? Tokio!"),
+      vec!["configure", "tokio", "runtime"]
+    );
+  }
+
+  #[test]
+  fn test_llm_config_requires_endpoint_and_model() {
+    let mut group = HashMap::from([("llm_endpoint".to_string(), "http://llm".to_string())]);
+    assert_eq!(LlmConfig::from_secrets(&group), None);
+
+    group.insert("llm_model".to_string(), "local".to_string());
+    let config = LlmConfig::from_secrets(&group).unwrap();
+    assert_eq!(config.model, "local");
+    assert_eq!(config.api_key, None);
+  }
+
+  #[test]
+  fn test_excerpt_truncates_at_word_boundary() {
+    let long = "word ".repeat(200);
+    let text = excerpt(&source("long", "Overview", &long));
+    assert!(text.starts_with("Overview\nword"));
+    assert!(text.ends_with("word…"));
+    assert!(text.chars().count() <= EXCERPT_CHARS + 1);
+  }
+
+  #[test]
+  fn test_build_prompt_numbers_sources() {
+    let prompt = build_prompt("Why?", &[source("a", "First", ""), source("b", "Second", "")]);
+    assert!(prompt.contains("[1] rust/a\nFirst"));
+    assert!(prompt.contains("[2] rust/b\nSecond"));
+    assert!(prompt.ends_with("Question: Why?"));
+  }
+
+  #[test]
+  fn test_cited_sources() {
+    let cited = cited_sources("Use spawn [1]. Also see [2, 3] and [9].", 3);
+    assert_eq!(cited.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+  }
+}
@@ -0,0 +1,273 @@
+//! Bearer tokens for the REST API
+//!
+//! Tokens live in the `insights-tokens` secrets group, one entry per token name,
+//! stored as `<scope>:<sha256 of the token>`. The vault never holds a usable token;
+//! the token itself is only shown once, when it is minted. The server reads the
+//! group through the secrets keeper and caches it briefly, so a revoked token stops
+//! working within a few seconds.
+//!
+//! Auth is enforced as soon as one token exists. Until then the API stays open
+//! unless `INSIGHTS_REQUIRE_AUTH` is set. Because the vault can only be read while
+//! the keeper is unlocked, an `auth-enabled` file beside the insights records
+//! whether tokens exist. While the vault can't be read, requests that need a scope
+//! are refused only if that file or `INSIGHTS_REQUIRE_AUTH` says auth is on.
+
+use crate::server::models::insight::get_base_root;
+use crate::settings;
+use anyhow::Result;
+use axum::http::Method;
+use bentley::config;
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Secrets group holding the token hashes
+pub const TOKENS_GROUP: &str = "insights-tokens";
+
+/// Marks minted tokens so they are recognisable in configs and logs
+const TOKEN_PREFIX: &str = "ins_";
+
+/// File beside the insights that exists while tokens have been minted
+const MARKER_FILE: &str = "auth-enabled";
+
+/// How long the token group is trusted before it is read again
+const CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Endpoints anyone may call, so health checks work without a token
+const PUBLIC_PATHS: &[&str] = &["/status", "/version", "/openapi.json", "/ui"];
+
+/// Static pages of the web UI, which hold no data of their own
+const PUBLIC_PREFIXES: &[&str] = &["/ui/"];
+
+/// POST endpoints that only read the knowledge base
+const READ_ONLY_POSTS: &[&str] = &[
+  "/ask",
+  "/insights/get",
+  "/insights/search",
+  "/insights/export",
+  "/insights/summary",
+  "/insights/related",
+  "/insights/duplicates",
+  "/insights/history",
+  "/insights/history/diff",
+];
+
+/// What a token may do; write implies read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum Scope {
+  Read,
+  Write,
+}
+
+impl Scope {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Scope::Read => "read",
+      Scope::Write => "write",
+    }
+  }
+
+  fn parse(value: &str) -> Option<Self> {
+    match value {
+      "read" => Some(Scope::Read),
+      "write" => Some(Scope::Write),
+      _ => None,
+    }
+  }
+}
+
+impl std::fmt::Display for Scope {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+/// A freshly minted token and the vault entry that recognises it
+#[derive(Debug, Clone)]
+pub struct MintedToken {
+  /// The bearer token, shown to the user once
+  pub token: String,
+  /// Value stored in the secrets group under the token's name
+  pub entry: String,
+}
+
+/// Create a new random token with the given scope
+pub fn mint(scope: Scope) -> MintedToken {
+  let token = format!("{TOKEN_PREFIX}{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+  let entry = format!("{}:{}", scope, hash(&token));
+  MintedToken { token, entry }
+}
+
+fn hash(token: &str) -> String {
+  hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Known tokens, keyed by hash
+#[derive(Debug, Clone, Default)]
+pub struct TokenTable {
+  tokens: HashMap<String, (String, Scope)>,
+}
+
+impl TokenTable {
+  /// Build the table from the secrets group; malformed entries are ignored
+  pub fn from_group(group: &HashMap<String, String>) -> Self {
+    let tokens = group
+      .iter()
+      .filter_map(|(name, entry)| {
+        let (scope, hash) = entry.split_once(':')?;
+        Some((hash.to_string(), (name.clone(), Scope::parse(scope)?)))
+      })
+      .collect();
+    Self { tokens }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.tokens.is_empty()
+  }
+
+  /// Name and scope of the token, if it is known
+  pub fn authenticate(&self, token: &str) -> Option<(&str, Scope)> {
+    self.tokens.get(&hash(token)).map(|(name, scope)| (name.as_str(), *scope))
+  }
+
+  /// Token names and scopes, sorted by name
+  pub fn entries(&self) -> Vec<(String, Scope)> {
+    let mut entries: Vec<_> = self.tokens.values().cloned().collect();
+    entries.sort();
+    entries
+  }
+}
+
+/// Scope a request needs, or None for public endpoints
+pub fn required_scope(method: &Method, path: &str) -> Option<Scope> {
+  if PUBLIC_PATHS.contains(&path) || PUBLIC_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) {
+    return None;
+  }
+  let reads = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+    || (method == Method::POST && READ_ONLY_POSTS.contains(&path));
+  Some(if reads { Scope::Read } else { Scope::Write })
+}
+
+/// Decide whether a request may proceed
+///
+/// `table` is None when the token group could not be read; the request is then
+/// refused if `minted` (see [`tokens_minted`]) or `require_auth` says auth is on.
+/// Returns the name of the authenticated token (if any) or why the request was
+/// refused.
+pub fn authorize(
+  table: Option<&TokenTable>,
+  require_auth: bool,
+  minted: bool,
+  method: &Method,
+  path: &str,
+  authorization: Option<&str>,
+) -> Result<Option<String>, String> {
+  let Some(needed) = required_scope(method, path) else { return Ok(None) };
+
+  let table = match table {
+    Some(table) if !table.is_empty() => table,
+    Some(_) if require_auth => return Err("No API tokens have been minted".to_string()),
+    None if require_auth || minted => {
+      return Err("API tokens are unavailable; is the secrets keeper unlocked?".to_string())
+    }
+    _ => return Ok(None),
+  };
+
+  let token = authorization
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .map(str::trim)
+    .ok_or_else(|| "Missing bearer token".to_string())?;
+  let (name, scope) = table.authenticate(token).ok_or_else(|| "Invalid API token".to_string())?;
+
+  if scope < needed {
+    return Err(format!("Token '{name}' is {scope}-only; this endpoint needs {needed} access"));
+  }
+  Ok(Some(name.to_string()))
+}
+
+/// Whether tokens have been minted, known without unlocking the vault
+pub fn tokens_minted() -> bool {
+  marker_path().is_ok_and(|path| path.exists())
+}
+
+/// Record whether tokens exist, so the server knows to stay closed while the vault is locked
+pub fn record_tokens_minted(minted: bool) -> Result<()> {
+  let path = marker_path()?;
+  if !minted {
+    return match fs::remove_file(&path) {
+      Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+      _ => Ok(()),
+    };
+  }
+
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  let mut options = fs::OpenOptions::new();
+  options.write(true).create(true).truncate(true);
+  #[cfg(unix)]
+  std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+  options.open(&path)?;
+  Ok(())
+}
+
+fn marker_path() -> Result<PathBuf> {
+  Ok(get_base_root()?.join(MARKER_FILE))
+}
+
+/// Whether requests are refused when no tokens can be checked
+/// Default: false
+/// Setting: insights.require_auth (INSIGHTS_REQUIRE_AUTH)
+pub fn get_require_auth() -> bool {
+  config::var(&settings::REQUIRE_AUTH)
+    .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+    .unwrap_or(false)
+}
+
+// Token Cache
+// ===========
+
+struct Cached {
+  loaded_at: Instant,
+  table: Option<Arc<TokenTable>>,
+}
+
+static CACHE: Lazy<Mutex<Option<Cached>>> = Lazy::new(|| Mutex::new(None));
+
+/// The current token table, read through the keeper at most every few seconds
+///
+/// If the group cannot be read, the last table that could is kept, so locking
+/// the keeper never switches auth off once tokens have been seen.
+pub async fn current_table() -> Option<Arc<TokenTable>> {
+  let mut cache = CACHE.lock().await;
+  if let Some(cached) = cache.as_ref().filter(|c| c.loaded_at.elapsed() < CACHE_TTL) {
+    return cached.table.clone();
+  }
+
+  let previous = cache.take().and_then(|c| c.table);
+  let table = match load_table().await {
+    Ok(table) => Some(Arc::new(table)),
+    Err(_) => previous,
+  };
+  *cache = Some(Cached { loaded_at: Instant::now(), table: table.clone() });
+  table
+}
+
+/// Read the token group through the secrets keeper without prompting
+///
+/// A successful read also brings the `auth-enabled` file up to date, which is
+/// how it disappears once the last token is revoked.
+pub async fn load_table() -> Result<TokenTable> {
+  let group = secrets::keeper_client::read_group(&secrets::base_path(), TOKENS_GROUP).await?;
+  let table = TokenTable::from_group(&group);
+  if table.is_empty() == tokens_minted() {
+    let _ = record_tokens_minted(!table.is_empty());
+  }
+  Ok(table)
+}
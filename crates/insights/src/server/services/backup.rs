@@ -0,0 +1,438 @@
+//! Scheduled snapshot backups of the insights directory and vector database
+//!
+//! Every backup is a `<id>.tar.gz` in the backups folder, named after the UTC time
+//! it was taken (`20261016T093000Z`). It holds the insights root under `insights/`
+//! (without sync's `.git`, the rebuildable full-text index, other workspaces or the
+//! backups folder itself) and the vector database files under `vectors/`. After each backup the
+//! folder is pruned: a backup is kept while it is one of the newest `keep_last`, or
+//! younger than `keep_days` when that is set.
+//!
+//! Restoring puts the insight files back and rebuilds the full-text index;
+//! embeddings are recomputed by re-indexing. The `vectors/` copy is for recovering a
+//! machine by hand, with the server stopped.
+
+use anyhow::{anyhow, Result};
+use bentley::config;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::server::middleware::{server_info, server_warn};
+use crate::server::models::insight;
+use crate::server::services::{fulltext, workspace};
+use crate::server::startup::get_vector_data_path;
+use crate::settings;
+
+/// Default interval between scheduled backups (one day)
+const DEFAULT_BACKUP_INTERVAL_SECS: u64 = 86400;
+
+/// Default number of backups kept regardless of age
+const DEFAULT_KEEP_LAST: usize = 7;
+
+/// Folder inside the insights root that holds backups unless configured elsewhere
+const BACKUPS_DIR: &str = ".backups";
+
+const ID_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const EXTENSION: &str = ".tar.gz";
+const INSIGHTS_PREFIX: &str = "insights";
+const VECTORS_PREFIX: &str = "vectors";
+
+/// A backup archive on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+  pub id: String,
+  pub created_at: DateTime<Utc>,
+  pub size_bytes: u64,
+  pub path: PathBuf,
+}
+
+/// Which backups survive pruning
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupPolicy {
+  /// Always keep this many of the newest backups (at least one is always kept)
+  pub keep_last: usize,
+  /// Also keep every backup younger than this many days
+  pub keep_days: Option<u32>,
+}
+
+impl Default for BackupPolicy {
+  fn default() -> Self {
+    Self { keep_last: DEFAULT_KEEP_LAST, keep_days: None }
+  }
+}
+
+impl BackupPolicy {
+  /// Get the configured backup policy
+  /// Default: keep the last 7 backups
+  /// Setting: insights.backup.keep (INSIGHTS_BACKUP_KEEP),
+  /// insights.backup.keep_days (INSIGHTS_BACKUP_KEEP_DAYS)
+  pub fn from_env() -> Self {
+    let keep_last = config::get(&settings::BACKUP_KEEP).unwrap_or(DEFAULT_KEEP_LAST);
+    let keep_days = config::get(&settings::BACKUP_KEEP_DAYS).filter(|days| *days > 0);
+    Self { keep_last, keep_days }
+  }
+
+  fn keeps(&self, position: usize, backup: &Backup, now: DateTime<Utc>) -> bool {
+    position < self.keep_last.max(1)
+      || self
+        .keep_days
+        .is_some_and(|days| now - backup.created_at < ChronoDuration::days(days.into()))
+  }
+}
+
+/// Where backups are read from and written to
+#[derive(Debug, Clone)]
+pub struct Locations {
+  pub insights_root: PathBuf,
+  pub vector_data: PathBuf,
+  pub backups: PathBuf,
+}
+
+impl Locations {
+  pub fn from_env() -> Result<Self> {
+    let insights_root = insight::get_insights_root()?;
+    Ok(Self {
+      backups: get_backups_dir(&insights_root),
+      vector_data: get_vector_data_path(),
+      insights_root,
+    })
+  }
+}
+
+/// Get the folder backups are kept in
+/// Default: `.backups` inside the insights root
+/// Setting: insights.backup.dir (INSIGHTS_BACKUP_DIR)
+pub fn get_backups_dir(insights_root: &Path) -> PathBuf {
+  match config::var(&settings::BACKUP_DIR) {
+    Some(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
+    _ => insights_root.join(BACKUPS_DIR),
+  }
+}
+
+/// Get the configured interval between scheduled backups, `None` when disabled
+/// Default: 86400 seconds
+/// Setting: insights.backup.interval_secs (INSIGHTS_BACKUP_INTERVAL_SECS, 0 disables
+/// scheduled backups)
+pub fn get_backup_interval() -> Option<Duration> {
+  let secs = config::get(&settings::BACKUP_INTERVAL_SECS).unwrap_or(DEFAULT_BACKUP_INTERVAL_SECS);
+  (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Snapshot the insights root and vector data into a new archive
+///
+/// Ids have a resolution of one second, so a backup taken in the same second as
+/// an existing one is stamped with the next free second.
+pub fn create(locations: &Locations, now: DateTime<Utc>) -> Result<Backup> {
+  let (id, created_at, path) = (0..60)
+    .map(|offset| {
+      let created_at = now + ChronoDuration::seconds(offset);
+      let id = created_at.format(ID_FORMAT).to_string();
+      let path = archive_path(&locations.backups, &id);
+      (id, created_at, path)
+    })
+    .find(|(_, _, path)| !path.exists())
+    .ok_or_else(|| anyhow!("Too many backups taken in the last minute"))?;
+
+  fs::create_dir_all(&locations.backups)?;
+  let partial = path.with_extension("gz.partial");
+  let result = write_archive(locations, &partial).and_then(|()| Ok(fs::rename(&partial, &path)?));
+  if let Err(e) = result {
+    let _ = fs::remove_file(&partial);
+    return Err(e);
+  }
+
+  let size_bytes = fs::metadata(&path)?.len();
+  Ok(Backup { id, created_at, size_bytes, path })
+}
+
+fn write_archive(locations: &Locations, path: &Path) -> Result<()> {
+  let mut tar = tar::Builder::new(GzEncoder::new(File::create(path)?, Compression::default()));
+  tar.follow_symlinks(false);
+
+  let skip = [
+    locations.insights_root.join(".git"),
+    locations.insights_root.join(fulltext::INDEX_DIR),
+    locations.insights_root.join(workspace::WORKSPACES_DIR),
+    locations.vector_data.join(workspace::WORKSPACES_DIR),
+    locations.backups.clone(),
+  ];
+  if locations.insights_root.is_dir() {
+    append_tree(&mut tar, &locations.insights_root, Path::new(INSIGHTS_PREFIX), &skip)?;
+  }
+  if locations.vector_data.is_dir() {
+    append_tree(&mut tar, &locations.vector_data, Path::new(VECTORS_PREFIX), &skip)?;
+  }
+
+  tar.into_inner()?.finish()?.flush()?;
+  Ok(())
+}
+
+fn append_tree<W: Write>(
+  tar: &mut tar::Builder<W>,
+  dir: &Path,
+  prefix: &Path,
+  skip: &[PathBuf],
+) -> Result<()> {
+  tar.append_dir(prefix, dir)?;
+  let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+  entries.sort_by_key(|entry| entry.file_name());
+
+  for entry in entries {
+    let path = entry.path();
+    if skip.contains(&path) {
+      continue;
+    }
+    let name = prefix.join(entry.file_name());
+    if entry.file_type()?.is_dir() {
+      append_tree(tar, &path, &name, skip)?;
+    } else {
+      tar.append_path_with_name(&path, &name)?;
+    }
+  }
+  Ok(())
+}
+
+/// Every backup in `dir`, newest first
+pub fn list(dir: &Path) -> Result<Vec<Backup>> {
+  if !dir.exists() {
+    return Ok(Vec::new());
+  }
+
+  let mut backups = Vec::new();
+  for entry in fs::read_dir(dir)? {
+    let entry = entry?;
+    let file_name = entry.file_name();
+    let Some(id) = file_name.to_str().and_then(|name| name.strip_suffix(EXTENSION)) else {
+      continue;
+    };
+    let Some(created_at) = parse_id(id) else { continue };
+    backups.push(Backup {
+      id: id.to_string(),
+      created_at,
+      size_bytes: entry.metadata()?.len(),
+      path: entry.path(),
+    });
+  }
+
+  backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+  Ok(backups)
+}
+
+/// Delete the backups `policy` no longer keeps, returning their ids
+pub fn prune(dir: &Path, policy: &BackupPolicy, now: DateTime<Utc>) -> Result<Vec<String>> {
+  let mut pruned = Vec::new();
+  for (position, backup) in list(dir)?.into_iter().enumerate() {
+    if !policy.keeps(position, &backup, now) {
+      fs::remove_file(&backup.path)?;
+      pruned.push(backup.id);
+    }
+  }
+  Ok(pruned)
+}
+
+/// Replace the insight files with those in backup `id`, returning how many insights it held
+///
+/// Sync's `.git`, the full-text index and the backups folder are left alone; the
+/// full-text index is rebuilt from the restored files.
+pub fn restore(locations: &Locations, id: &str) -> Result<usize> {
+  if parse_id(id).is_none() {
+    return Err(anyhow!("Invalid backup id '{id}' (expected e.g. 20261016T093000Z)"));
+  }
+  let path = archive_path(&locations.backups, id);
+  if !path.exists() {
+    return Err(anyhow!("Backup {id} not found"));
+  }
+
+  let root = &locations.insights_root;
+  fs::create_dir_all(root)?;
+  let staging = root.join(format!(".restore-{id}"));
+  let _ = fs::remove_dir_all(&staging);
+  tar::Archive::new(GzDecoder::new(File::open(&path)?)).unpack(&staging)?;
+
+  let result = swap_in(root, &staging.join(INSIGHTS_PREFIX), &staging, &locations.backups);
+  let _ = fs::remove_dir_all(&staging);
+  result?;
+
+  fulltext::rebuild()
+}
+
+/// Move everything in `restored` into `root`, dropping what was there before
+fn swap_in(root: &Path, restored: &Path, staging: &Path, backups: &Path) -> Result<()> {
+  let keep = [
+    root.join(".git"),
+    root.join(fulltext::INDEX_DIR),
+    root.join(workspace::WORKSPACES_DIR),
+    backups.to_path_buf(),
+  ];
+  for entry in fs::read_dir(root)? {
+    let path = entry?.path();
+    if path == staging || keep.contains(&path) {
+      continue;
+    }
+    if path.is_dir() {
+      fs::remove_dir_all(&path)?;
+    } else {
+      fs::remove_file(&path)?;
+    }
+  }
+
+  if !restored.exists() {
+    return Ok(());
+  }
+  for entry in fs::read_dir(restored)? {
+    let entry = entry?;
+    let target = root.join(entry.file_name());
+    if keep.contains(&target) {
+      continue;
+    }
+    fs::rename(entry.path(), target)?;
+  }
+  Ok(())
+}
+
+fn archive_path(dir: &Path, id: &str) -> PathBuf {
+  dir.join(format!("{id}{EXTENSION}"))
+}
+
+fn parse_id(id: &str) -> Option<DateTime<Utc>> {
+  NaiveDateTime::parse_from_str(id, ID_FORMAT).ok().map(|time| time.and_utc())
+}
+
+/// Take a backup and prune old ones, as the scheduler does
+pub fn run_once(now: DateTime<Utc>) -> Result<(Backup, Vec<String>)> {
+  let locations = Locations::from_env()?;
+  let backup = create(&locations, now)?;
+  let pruned = prune(&locations.backups, &BackupPolicy::from_env(), now)?;
+  Ok((backup, pruned))
+}
+
+/// Spawn the background job that periodically backs up insights
+#[cfg(not(tarpaulin_include))] // Skip coverage - long-running background task
+pub fn spawn_scheduler(interval: Duration) -> tokio::task::JoinHandle<()> {
+  tokio::spawn(async move {
+    // The first backup waits a full interval so restarts do not pile up backups
+    let start = tokio::time::Instant::now() + interval;
+    let mut ticker = tokio::time::interval_at(start, interval);
+    loop {
+      ticker.tick().await;
+      match tokio::task::spawn_blocking(|| run_once(Utc::now())).await {
+        Ok(Ok((backup, pruned))) => {
+          let message = format!(
+            "Backed up insights to {} ({} bytes, pruned {})",
+            backup.path.display(),
+            backup.size_bytes,
+            pruned.len()
+          );
+          server_info(&message, "insights-backup").await;
+        }
+        Ok(Err(e)) => {
+          server_warn(&format!("Scheduled backup failed: {e}"), "insights-backup").await
+        }
+        Err(e) => server_warn(&format!("Scheduled backup panicked: {e}"), "insights-backup").await,
+      }
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+  use tempfile::TempDir;
+
+  fn at(hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 16, hour, 0, 0).unwrap()
+  }
+
+  fn locations(dir: &TempDir) -> Locations {
+    let root = dir.path().join("insights");
+    Locations {
+      backups: root.join(BACKUPS_DIR),
+      vector_data: dir.path().join("vectors"),
+      insights_root: root,
+    }
+  }
+
+  fn write(path: &Path, content: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+  }
+
+  fn entries(backup: &Backup) -> Vec<String> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(&backup.path).unwrap()));
+    let mut names: Vec<String> = archive
+      .entries()
+      .unwrap()
+      .map(|entry| {
+        entry.unwrap().path().unwrap().to_string_lossy().trim_end_matches('/').to_string()
+      })
+      .collect();
+    names.sort();
+    names
+  }
+
+  #[test]
+  fn test_create_archives_insights_and_vectors_but_not_internal_state() {
+    let dir = TempDir::new().unwrap();
+    let locations = locations(&dir);
+    write(&locations.insights_root.join("rust/async.insight.md"), "notes");
+    write(&locations.insights_root.join(".git/HEAD"), "ref");
+    write(&locations.insights_root.join(".fulltext/meta.json"), "{}");
+    write(&locations.vector_data.join("memory/vectors.jsonl"), "{}");
+    write(&locations.backups.join("old.tar.gz"), "");
+
+    let backup = create(&locations, at(9)).unwrap();
+    assert_eq!(backup.id, "20261016T090000Z");
+    assert!(backup.size_bytes > 0);
+    assert_eq!(
+      entries(&backup),
+      [
+        "insights",
+        "insights/rust",
+        "insights/rust/async.insight.md",
+        "vectors",
+        "vectors/memory",
+        "vectors/memory/vectors.jsonl"
+      ]
+    );
+
+    assert_eq!(create(&locations, at(9)).unwrap().id, "20261016T090001Z");
+  }
+
+  #[test]
+  fn test_list_is_newest_first_and_ignores_other_files() {
+    let dir = TempDir::new().unwrap();
+    let locations = locations(&dir);
+    assert!(list(&locations.backups).unwrap().is_empty());
+
+    create(&locations, at(8)).unwrap();
+    create(&locations, at(10)).unwrap();
+    write(&locations.backups.join("notes.txt"), "");
+    write(&locations.backups.join("garbage.tar.gz"), "");
+
+    let ids: Vec<String> = list(&locations.backups).unwrap().into_iter().map(|b| b.id).collect();
+    assert_eq!(ids, ["20261016T100000Z", "20261016T080000Z"]);
+  }
+
+  #[test]
+  fn test_prune_keeps_newest_and_recent_backups() {
+    let dir = TempDir::new().unwrap();
+    let locations = locations(&dir);
+    for hour in [1, 2, 3, 4] {
+      create(&locations, at(hour)).unwrap();
+    }
+
+    let recent = BackupPolicy { keep_last: 1, keep_days: Some(1) };
+    assert!(prune(&locations.backups, &recent, at(12)).unwrap().is_empty());
+
+    let pruned = prune(&locations.backups, &BackupPolicy { keep_last: 2, keep_days: None }, at(12));
+    assert_eq!(pruned.unwrap(), ["20261016T020000Z", "20261016T010000Z"]);
+
+    let nothing = BackupPolicy { keep_last: 0, keep_days: None };
+    assert_eq!(prune(&locations.backups, &nothing, at(12)).unwrap(), ["20261016T030000Z"]);
+    assert_eq!(list(&locations.backups).unwrap().len(), 1, "the newest backup is always kept");
+  }
+}
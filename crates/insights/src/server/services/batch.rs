@@ -0,0 +1,312 @@
+//! Transactional batches of insight writes
+//!
+//! Every operation is checked before anything touches disk, and if a write still
+//! fails the writes before it are undone in reverse, so a batch is applied entirely
+//! or not at all. Indexing, embeddings and notifications are left to the caller to
+//! run once the whole batch is in.
+
+use anyhow::Result;
+use std::collections::HashSet;
+
+use crate::server::models::insight::{self, Insight};
+use crate::server::models::{schema, tag, topic};
+use crate::server::services::{import, lint, sensitive};
+use crate::server::types::{
+  BatchOperation, BatchRequest, BatchResultData, BatchStatus, ErrorCode, LintFindingData,
+};
+
+/// A write that was applied, with what is needed to undo it
+#[derive(Debug, Clone)]
+pub enum Change {
+  Created(Insight),
+  Updated { before: Box<Insight>, after: Insight },
+  Deleted(Insight),
+}
+
+/// A batch that was applied in full
+#[derive(Debug)]
+pub struct BatchOutcome {
+  pub results: Vec<BatchResultData>,
+  /// Applied writes, in request order
+  pub changes: Vec<Change>,
+}
+
+/// A batch that left the knowledge base untouched
+#[derive(Debug)]
+pub struct BatchRejection {
+  /// The failed operations' code when they agree, otherwise `ValidationFailed`
+  pub code: ErrorCode,
+  pub message: String,
+  /// Every operation, with the ones at fault marked `Failed`
+  pub results: Vec<BatchResultData>,
+}
+
+/// Why one operation can't be applied
+type Failure = (ErrorCode, String);
+
+/// An operation that passed its checks, ready to be written
+enum Planned {
+  Create(Insight),
+  Update {
+    before: Insight,
+    overview: Option<String>,
+    details: Option<String>,
+    tags: Option<Vec<String>>,
+  },
+  Delete(Insight),
+}
+
+/// Check every operation, then write them all or none
+pub fn apply(request: &BatchRequest) -> Result<BatchOutcome, BatchRejection> {
+  let mut seen = HashSet::new();
+  let checked: Vec<Result<(Planned, Vec<LintFindingData>), Failure>> =
+    request.operations.iter().map(|operation| check(operation, request, &mut seen)).collect();
+
+  if checked.iter().any(Result::is_err) {
+    let failures: Vec<Option<&Failure>> =
+      checked.iter().map(|check| check.as_ref().err()).collect();
+    return Err(rejection(request, &failures));
+  }
+
+  let mut planned = Vec::new();
+  let mut warnings = Vec::new();
+  for (plan, findings) in checked.into_iter().flatten() {
+    planned.push(plan);
+    warnings.push(findings);
+  }
+
+  let mut changes: Vec<Change> = Vec::new();
+  for (index, plan) in planned.into_iter().enumerate() {
+    match write(plan) {
+      Ok(change) => changes.push(change),
+      Err(e) => {
+        let mut message = format!("Failed to write: {e}");
+        for change in changes.iter().rev() {
+          if let Err(e) = undo(change) {
+            let (topic, name) = change_id(change);
+            message.push_str(&format!("; undoing {topic}/{name} also failed: {e}"));
+          }
+        }
+        let mut failures = vec![None; request.operations.len()];
+        let failure = (ErrorCode::Internal, message);
+        failures[index] = Some(&failure);
+        return Err(rejection(request, &failures));
+      }
+    }
+  }
+
+  let results = request
+    .operations
+    .iter()
+    .zip(warnings)
+    .enumerate()
+    .map(|(index, (operation, warnings))| {
+      let status = match operation {
+        BatchOperation::Create { .. } => BatchStatus::Created,
+        BatchOperation::Update { .. } => BatchStatus::Updated,
+        BatchOperation::Delete { .. } => BatchStatus::Deleted,
+      };
+      BatchResultData { warnings, ..result(index, operation, status) }
+    })
+    .collect();
+
+  Ok(BatchOutcome { results, changes })
+}
+
+/// Validate one operation against the request's options and what is on disk
+fn check(
+  operation: &BatchOperation,
+  request: &BatchRequest,
+  seen: &mut HashSet<(String, String)>,
+) -> Result<(Planned, Vec<LintFindingData>), Failure> {
+  let (topic, name) = (operation.topic(), operation.name());
+  let problems = match operation {
+    BatchOperation::Create { tags, .. } => schema::check_fields(topic, name, tags),
+    BatchOperation::Update { tags, .. } => {
+      schema::check_fields(topic, name, tags.as_deref().unwrap_or_default())
+    }
+    // Stored names may predate the schema, so a delete only needs a safe path
+    BatchOperation::Delete { .. } => match topic::invalid(topic) {
+      Some(problem) => return Err(invalid(problem)),
+      None => Vec::new(),
+    },
+  };
+  if !problems.is_empty() {
+    return Err(invalid(format!("Invalid insight: {}", schema::describe(&problems))));
+  }
+  if let Some(problem) = import::invalid_segment("name", name) {
+    return Err(invalid(problem));
+  }
+  if !seen.insert((topic.to_lowercase(), name.to_lowercase())) {
+    return Err(invalid(format!("{topic}/{name} appears more than once in the batch")));
+  }
+
+  match operation {
+    BatchOperation::Create { overview, details, tags, .. } => {
+      let mut new_insight =
+        Insight::new(topic.to_string(), name.to_string(), overview.clone(), details.clone());
+      new_insight.tags = tag::normalize_all(tags);
+      if insight::file_path(&new_insight).map_err(|e| invalid(e.to_string()))?.exists() {
+        return Err((ErrorCode::AlreadyExists, format!("Insight {topic}/{name} already exists")));
+      }
+      let warnings = check_content(topic, name, overview, details, request)?;
+      Ok((Planned::Create(new_insight), warnings))
+    }
+    BatchOperation::Update { overview, details, tags, .. } => {
+      if overview.is_none() && details.is_none() && tags.is_none() {
+        return Err(invalid("At least one of overview, details or tags must be provided".into()));
+      }
+      let before = load(topic, name)?;
+      let warnings = check_content(
+        topic,
+        name,
+        overview.as_deref().unwrap_or(&before.overview),
+        details.as_deref().unwrap_or(&before.details),
+        request,
+      )?;
+      let plan = Planned::Update {
+        before,
+        overview: overview.clone(),
+        details: details.clone(),
+        tags: tags.clone(),
+      };
+      Ok((plan, warnings))
+    }
+    BatchOperation::Delete { .. } => Ok((Planned::Delete(load(topic, name)?), Vec::new())),
+  }
+}
+
+/// Sensitive content and lint checks for content about to be written
+fn check_content(
+  topic: &str,
+  name: &str,
+  overview: &str,
+  details: &str,
+  request: &BatchRequest,
+) -> Result<Vec<LintFindingData>, Failure> {
+  let findings = sensitive::scan_content(overview, details);
+  if !findings.is_empty() && !request.allow_sensitive {
+    return Err((ErrorCode::SensitiveContent, sensitive::describe(&findings)));
+  }
+
+  let findings = lint::lint_content(topic, overview, details);
+  if !findings.is_empty() && request.strict {
+    return Err(invalid(lint::describe(&findings)));
+  }
+  Ok(
+    findings
+      .into_iter()
+      .map(|finding| LintFindingData {
+        topic: topic.to_string(),
+        name: name.to_string(),
+        rule: finding.rule.to_string(),
+        message: finding.message,
+      })
+      .collect(),
+  )
+}
+
+fn load(topic: &str, name: &str) -> Result<Insight, Failure> {
+  insight::load(topic, name).map_err(|e| (ErrorCode::NotFound, format!("Insight not found: {e}")))
+}
+
+fn invalid(message: String) -> Failure {
+  (ErrorCode::ValidationFailed, message)
+}
+
+fn write(plan: Planned) -> Result<Change> {
+  match plan {
+    Planned::Create(new_insight) => {
+      insight::save(&new_insight)?;
+      Ok(Change::Created(new_insight))
+    }
+    Planned::Update { before, overview, details, tags } => {
+      let mut after = before.clone();
+      insight::update_with_tags(
+        &mut after,
+        overview.as_deref(),
+        details.as_deref(),
+        tags.as_deref(),
+      )?;
+      Ok(Change::Updated { before: Box::new(before), after })
+    }
+    Planned::Delete(existing) => {
+      insight::delete(&existing)?;
+      Ok(Change::Deleted(existing))
+    }
+  }
+}
+
+/// Put the knowledge base back the way it was before `change`
+fn undo(change: &Change) -> Result<()> {
+  match change {
+    Change::Created(created) => insight::delete(created),
+    Change::Updated { before, after } => {
+      insight::delete(after)?;
+      insight::save_existing(before)
+    }
+    Change::Deleted(deleted) => insight::save_existing(deleted),
+  }
+}
+
+fn change_id(change: &Change) -> (&str, &str) {
+  let insight = match change {
+    Change::Created(insight)
+    | Change::Updated { after: insight, .. }
+    | Change::Deleted(insight) => insight,
+  };
+  (&insight.topic, &insight.name)
+}
+
+fn result(index: usize, operation: &BatchOperation, status: BatchStatus) -> BatchResultData {
+  BatchResultData {
+    index,
+    op: operation.action(),
+    topic: operation.topic().to_string(),
+    name: operation.name().to_string(),
+    status,
+    error: None,
+    code: None,
+    warnings: Vec::new(),
+  }
+}
+
+fn rejection(request: &BatchRequest, failures: &[Option<&Failure>]) -> BatchRejection {
+  let results: Vec<BatchResultData> = request
+    .operations
+    .iter()
+    .zip(failures)
+    .enumerate()
+    .map(|(index, (operation, failure))| match failure {
+      Some((code, message)) => BatchResultData {
+        error: Some(message.clone()),
+        code: Some(*code),
+        ..result(index, operation, BatchStatus::Failed)
+      },
+      None => result(index, operation, BatchStatus::NotApplied),
+    })
+    .collect();
+
+  let failed: Vec<&BatchResultData> =
+    results.iter().filter(|result| result.status == BatchStatus::Failed).collect();
+  let code = match failed.first().and_then(|result| result.code) {
+    Some(code) if failed.iter().all(|result| result.code == Some(code)) => code,
+    _ => ErrorCode::ValidationFailed,
+  };
+  let reasons: Vec<String> = failed
+    .iter()
+    .map(|result| {
+      format!(
+        "operation {} ({} {}/{}): {}",
+        result.index + 1,
+        result.op,
+        result.topic,
+        result.name,
+        result.error.as_deref().unwrap_or_default()
+      )
+    })
+    .collect();
+  let message = format!("Batch rejected, nothing was applied: {}", reasons.join("; "));
+
+  BatchRejection { code, message, results }
+}
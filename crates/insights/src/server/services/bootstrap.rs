@@ -0,0 +1,183 @@
+//! Cold-start bootstrap from a seed archive
+//!
+//! A seed is an ordinary export archive (JSON, tar.gz or tar.zst) published
+//! somewhere a new install can reach. It is only restored once it is trusted:
+//! either its SHA-256 matches a digest pinned by the caller, or `<seed>.sig`
+//! holds a valid ed25519 signature of it by the key in
+//! `insights.bootstrap.public_key`. A checksum fetched from the same place as the
+//! seed would only catch corruption, so none is ever read from there. By default
+//! seeds only go into an empty knowledge base. Embeddings shipped in the seed are
+//! stored as they are, so search works without re-indexing everything from
+//! scratch.
+
+use anyhow::{anyhow, Context, Result};
+use bentley::config;
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+use crate::server::models::insight::{self, Insight};
+use crate::server::models::topic;
+use crate::server::services::export::{self, ArchiveFormat};
+use crate::server::services::import;
+use crate::server::types::InsightsArchive;
+use crate::settings;
+
+/// Seeds can be large, so downloads get far longer than an API call
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Insights restored from a seed
+#[derive(Debug, Default)]
+pub struct Restored {
+  /// Every insight written to disk, with its seeded embedding if there was one
+  pub insights: Vec<Insight>,
+  /// `topic/name` of insights left alone because they already existed
+  pub skipped: Vec<String>,
+}
+
+/// Download (or read) a seed, verify it and decode it
+///
+/// `source` is an http(s) URL, a `file://` URL or a local path. Without a pinned
+/// checksum, a signing key must be configured and `<seed>.sig` must exist.
+pub async fn load_seed(source: &str, sha256: Option<&str>) -> Result<InsightsArchive> {
+  let format =
+    ArchiveFormat::from_path(std::path::Path::new(strip_query(source))).ok_or_else(|| {
+      anyhow!("Cannot tell the seed format from {source} (.json, .tar.gz or .tar.zst)")
+    })?;
+
+  let data = match sha256 {
+    Some(pinned) => {
+      let expected = parse_checksum(pinned)?;
+      let data = fetch(source).await?;
+      verify(&data, &expected)?;
+      data
+    }
+    None => {
+      let key = get_seed_public_key()?.ok_or_else(|| {
+        anyhow!(
+          "Cannot verify the seed: pass its sha256 or set {} to the publisher's signing key",
+          settings::SEED_PUBLIC_KEY.name
+        )
+      })?;
+      let signature = fetch(&sibling(source, ".sig"))
+        .await
+        .context("No sha256 given and no .sig file published beside the seed")?;
+      let data = fetch(source).await?;
+      verify_signature(&data, &String::from_utf8_lossy(&signature), &key)?;
+      data
+    }
+  };
+
+  export::read_archive(format, data.as_slice())
+    .map_err(|e| anyhow!("Seed {source} is not a valid insights archive: {e}"))
+}
+
+/// Get the configured key seeds are signed with
+/// Default: none, so seeds need a pinned sha256
+/// Setting: insights.bootstrap.public_key (INSIGHTS_SEED_PUBLIC_KEY)
+pub fn get_seed_public_key() -> Result<Option<VerifyingKey>> {
+  match config::var(&settings::SEED_PUBLIC_KEY) {
+    Some(value) if !value.trim().is_empty() => parse_public_key(&value).map(Some),
+    _ => Ok(None),
+  }
+}
+
+/// Whether the knowledge base has no insights yet
+pub fn is_fresh() -> Result<bool> {
+  Ok(insight::get_insights(None)?.is_empty())
+}
+
+/// Write every seeded insight to disk, keeping its original timestamps
+///
+/// Existing insights are only replaced with `force`; embeddings are attached to
+/// the returned insights for the caller to store.
+pub fn restore(archive: InsightsArchive, force: bool) -> Result<Restored> {
+  let problems: Vec<String> = archive
+    .insights
+    .iter()
+    .flat_map(|i| {
+      let label = format!("{}/{}", i.topic, i.name);
+      [topic::invalid(&i.topic), import::invalid_segment("name", &i.name)]
+        .into_iter()
+        .flatten()
+        .map(move |problem| format!("{label}: {problem}"))
+    })
+    .collect();
+  if !problems.is_empty() {
+    return Err(anyhow!("Seed rejected: {}", problems.join("; ")));
+  }
+
+  let mut restored = Restored::default();
+  for exported in archive.insights {
+    let mut seeded = export::to_insight(&exported);
+    if insight::file_path(&seeded)?.exists() && !force {
+      restored.skipped.push(format!("{}/{}", seeded.topic, seeded.name));
+      continue;
+    }
+
+    insight::save_existing(&seeded)?;
+    seeded.embedding = exported.embedding;
+    restored.insights.push(seeded);
+  }
+
+  Ok(restored)
+}
+
+/// Check downloaded bytes against a hex SHA-256
+pub fn verify(data: &[u8], expected: &str) -> Result<()> {
+  let actual = hex::encode(Sha256::digest(data));
+  if !actual.eq_ignore_ascii_case(expected.trim()) {
+    return Err(anyhow!("Seed checksum mismatch: expected {}, got {actual}", expected.trim()));
+  }
+  Ok(())
+}
+
+/// The digest from a `sha256sum`-style line (`<hex>  <file>`) or a bare digest
+pub fn parse_checksum(content: &str) -> Result<String> {
+  let digest = content.split_whitespace().next().unwrap_or_default();
+  if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+    return Err(anyhow!("'{}' is not a SHA-256 checksum", content.trim()));
+  }
+  Ok(digest.to_lowercase())
+}
+
+/// Check downloaded bytes against a hex ed25519 signature
+pub fn verify_signature(data: &[u8], signature: &str, key: &VerifyingKey) -> Result<()> {
+  let bytes = hex::decode(signature.trim())
+    .map_err(|_| anyhow!("'{}' is not a hex ed25519 signature", signature.trim()))?;
+  let signature = Signature::from_slice(&bytes)
+    .map_err(|_| anyhow!("'{}' is not a hex ed25519 signature", signature.trim()))?;
+  key
+    .verify_strict(data, &signature)
+    .map_err(|_| anyhow!("Seed signature does not match {}", settings::SEED_PUBLIC_KEY.name))
+}
+
+/// A hex ed25519 public key
+pub fn parse_public_key(value: &str) -> Result<VerifyingKey> {
+  let invalid = || anyhow!("'{}' is not a hex ed25519 public key", value.trim());
+  let bytes: [u8; 32] =
+    hex::decode(value.trim()).map_err(|_| invalid())?.try_into().map_err(|_| invalid())?;
+  VerifyingKey::from_bytes(&bytes).map_err(|_| invalid())
+}
+
+async fn fetch(source: &str) -> Result<Vec<u8>> {
+  if source.starts_with("http://") || source.starts_with("https://") {
+    let client = reqwest::Client::builder().timeout(DOWNLOAD_TIMEOUT).build()?;
+    let response = client.get(source).send().await?.error_for_status()?;
+    return Ok(response.bytes().await?.to_vec());
+  }
+
+  let path = source.strip_prefix("file://").unwrap_or(source);
+  tokio::fs::read(path).await.map_err(|e| anyhow!("Failed to read {path}: {e}"))
+}
+
+/// A file published next to the seed, keeping the seed's query string
+pub fn sibling(source: &str, suffix: &str) -> String {
+  let path = strip_query(source);
+  format!("{path}{suffix}{}", &source[path.len()..])
+}
+
+/// URLs may carry a query string after the file name
+fn strip_query(source: &str) -> &str {
+  source.split(['?', '#']).next().unwrap_or(source)
+}
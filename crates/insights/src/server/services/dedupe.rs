@@ -0,0 +1,147 @@
+//! Near-duplicate detection
+//!
+//! Insights are compared pairwise by the cosine similarity of their stored
+//! embeddings or, when any are missing, of their TF-IDF term vectors. Pairs at or
+//! above the threshold are linked, and every connected group of linked insights
+//! is reported as one cluster of duplicates.
+
+use std::collections::BTreeMap;
+
+use crate::server::services::summary::{self, ClusterSource};
+
+/// Threshold used when the caller does not give one
+///
+/// Term vectors of the same text reworded score far lower than its embeddings.
+pub fn default_threshold(source: ClusterSource) -> f32 {
+  match source {
+    ClusterSource::Embeddings => 0.92,
+    ClusterSource::Terms => 0.8,
+  }
+}
+
+/// A group of insights that are near-identical to one another
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateCluster {
+  /// Indices into the compared documents, in their original order
+  pub members: Vec<usize>,
+  /// Similarity of the closest pair in the cluster
+  pub similarity: f32,
+}
+
+/// Every cluster of duplicates, closest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicates {
+  pub source: ClusterSource,
+  pub threshold: f32,
+  pub clusters: Vec<DuplicateCluster>,
+}
+
+/// Find clusters of near-identical documents, by embeddings when every document has one
+pub fn find(
+  texts: &[String],
+  embeddings: Option<&[Vec<f32>]>,
+  threshold: Option<f32>,
+) -> Duplicates {
+  let (source, similarities) = summary::pairwise_similarities(texts, embeddings);
+  let threshold = threshold.unwrap_or_else(|| default_threshold(source));
+
+  let mut parent: Vec<usize> = (0..texts.len()).collect();
+  for (a, row) in similarities.iter().enumerate() {
+    for (b, &similarity) in row.iter().enumerate().skip(a + 1) {
+      if similarity >= threshold {
+        let (root_a, root_b) = (root(&mut parent, a), root(&mut parent, b));
+        parent[root_b.max(root_a)] = root_a.min(root_b);
+      }
+    }
+  }
+
+  let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+  for index in 0..texts.len() {
+    let group = root(&mut parent, index);
+    groups.entry(group).or_default().push(index);
+  }
+
+  let mut clusters: Vec<DuplicateCluster> = groups
+    .into_values()
+    .filter(|members| members.len() > 1)
+    .map(|members| {
+      let similarity = members
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &a)| members[i + 1..].iter().map(move |&b| (a, b)))
+        .map(|(a, b)| similarities[a][b])
+        .fold(0.0, f32::max);
+      DuplicateCluster { members, similarity }
+    })
+    .collect();
+  clusters.sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then(a.members.cmp(&b.members)));
+
+  Duplicates { source, threshold, clusters }
+}
+
+fn root(parent: &mut [usize], mut index: usize) -> usize {
+  while parent[index] != index {
+    parent[index] = parent[parent[index]];
+    index = parent[index];
+  }
+  index
+}
+
+/// Details of a merged insight: the kept details, then whatever the duplicates add
+pub fn merge_details(kept: &str, duplicates: &[&str]) -> String {
+  let mut merged = kept.trim_end().to_string();
+  for details in duplicates {
+    let details = details.trim();
+    if details.is_empty() || merged.contains(details) {
+      continue;
+    }
+    merged.push_str("\n\n");
+    merged.push_str(details);
+  }
+  merged
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn texts(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+  }
+
+  #[test]
+  fn test_linked_pairs_form_one_cluster() {
+    let docs = texts(&["a", "b", "c", "d"]);
+    let embeddings =
+      vec![vec![1.0, 0.0, 0.0], vec![0.0, 0.0, 1.0], vec![0.99, 0.1, 0.0], vec![0.95, 0.3, 0.0]];
+    let found = find(&docs, Some(&embeddings), Some(0.95));
+
+    assert_eq!(found.source, ClusterSource::Embeddings);
+    assert_eq!(found.clusters.len(), 1);
+    assert_eq!(found.clusters[0].members, vec![0, 2, 3]);
+    assert!(found.clusters[0].similarity > 0.99);
+  }
+
+  #[test]
+  fn test_terms_catch_copies_without_embeddings() {
+    let docs = texts(&[
+      "tokio spawn_blocking keeps cpu work off the async runtime",
+      "postgres vacuum reclaims dead tuples",
+      "tokio spawn_blocking keeps cpu work off the async runtime threads",
+    ]);
+    let found = find(&docs, None, None);
+
+    assert_eq!(found.source, ClusterSource::Terms);
+    assert_eq!(found.threshold, default_threshold(ClusterSource::Terms));
+    assert_eq!(
+      found.clusters.iter().map(|c| c.members.clone()).collect::<Vec<_>>(),
+      vec![vec![0, 2]]
+    );
+  }
+
+  #[test]
+  fn test_merge_details_skips_repeated_content() {
+    let merged = merge_details("Use spawn_blocking.\n", &["Use spawn_blocking.", "", "Or rayon."]);
+    assert_eq!(merged, "Use spawn_blocking.\n\nOr rayon.");
+  }
+}
@@ -1,411 +1,140 @@
 use anyhow::{anyhow, Result};
-use hf_hub::api::tokio::Api;
-use ndarray::Array2;
-use std::collections::HashMap;
-use std::sync::Mutex;
-use tokenizers::Tokenizer;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-const MODEL_NAME: &str = "onnx-community/embeddinggemma-300m-ONNX";
-const TOKENIZER_FILE: &str = "tokenizer.json";
-const MODEL_FILE: &str = "onnx/model.onnx";
+#[cfg(feature = "ml-features")]
+pub mod local;
+pub mod remote;
 
-/// Trait for extracting tensor data - allows testing without ONNX complexity
-trait EmbeddingOutput {
-  fn get_tensor(&self, key: &str) -> Option<&dyn TensorData>;
-}
-
-trait TensorData {
-  fn extract_f32_data(&self) -> Result<(&[i64], &[f32])>;
-}
+#[cfg(feature = "ml-features")]
+pub use local::LocalProvider;
 
-/// Trait abstractions for testable tensor preparation
-trait TokenEncoding {
-  fn get_ids(&self) -> &[u32];
-  fn get_attention_mask(&self) -> &[u32];
-  fn get_type_ids(&self) -> &[u32];
-}
+/// Set when embeddings are switched off for the whole process
+static DISABLED: AtomicBool = AtomicBool::new(false);
 
-trait SessionInputs {
-  fn input_names(&self) -> Vec<String>;
-}
-
-/// Trait abstraction for testable tokenization
-trait TextTokenizer {
-  fn encode_text(&self, text: &str, add_special_tokens: bool) -> Result<Box<dyn TokenizerOutput>>;
+/// Stop using the embedding provider; every embedding request fails from now on
+///
+/// Handlers already treat embedding failures as non-fatal, so the server keeps
+/// working without semantic search. Used by `insights::testing`.
+pub fn disable() {
+  DISABLED.store(true, Ordering::Relaxed);
 }
 
-trait TokenizerOutput: std::fmt::Debug + TokenEncoding {}
-
-#[cfg(target_os = "linux")]
-use ort::{
-  execution_providers::{CPUExecutionProvider, CUDAExecutionProvider, ExecutionProviderDispatch},
-  session::Session,
-  value::Value,
-};
-
-#[cfg(target_os = "macos")]
-use ort::{
-  execution_providers::{CPUExecutionProvider, CoreMLExecutionProvider, ExecutionProviderDispatch},
-  session::Session,
-  value::Value,
-};
-
-// Implementations for real ONNX types
-#[cfg(not(tarpaulin_include))]
-impl<'s> EmbeddingOutput for ort::session::SessionOutputs<'s> {
-  fn get_tensor(&self, key: &str) -> Option<&dyn TensorData> {
-    self.get(key).map(|v| v as &dyn TensorData)
-  }
-}
+// Providers
+// =========
 
-#[cfg(not(tarpaulin_include))]
-impl TensorData for ort::value::Value {
-  fn extract_f32_data(&self) -> Result<(&[i64], &[f32])> {
-    let (shape, data) = self.try_extract_tensor::<f32>()?;
-    Ok((shape.as_ref(), data))
-  }
-}
+/// Recorded as the embedding version of insights embedded by the local model
+pub const LOCAL_VERSION: &str = "embeddinggemma-300m";
 
-// Implementations for real types
-#[cfg(not(tarpaulin_include))]
-impl TokenEncoding for tokenizers::Encoding {
-  fn get_ids(&self) -> &[u32] {
-    self.get_ids()
-  }
-  fn get_attention_mask(&self) -> &[u32] {
-    self.get_attention_mask()
-  }
-  fn get_type_ids(&self) -> &[u32] {
-    self.get_type_ids()
-  }
-}
+/// Turns texts into embedding vectors
+#[async_trait::async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+  /// Stored as each insight's `embedding_version`, so vectors from different
+  /// models are never mistaken for one another
+  fn version(&self) -> String;
 
-#[cfg(not(tarpaulin_include))]
-impl SessionInputs for Session {
-  fn input_names(&self) -> Vec<String> {
-    self.inputs.iter().map(|input| input.name.to_string()).collect()
+  /// Whether texts get EmbeddingGemma's task prompts (`task: search result | query: ...`)
+  fn uses_task_prompts(&self) -> bool {
+    false
   }
-}
 
-// Real tokenizer implementations
-#[cfg(not(tarpaulin_include))]
-impl TextTokenizer for Tokenizer {
-  fn encode_text(&self, text: &str, add_special_tokens: bool) -> Result<Box<dyn TokenizerOutput>> {
-    let encoding =
-      self.encode(text, add_special_tokens).map_err(|e| anyhow!("Tokenization failed: {}", e))?;
-    Ok(Box::new(encoding))
-  }
+  /// One embedding per text, in the same order
+  async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
 }
 
-impl TokenizerOutput for tokenizers::Encoding {}
-
-pub struct EmbeddingModel {
-  session: Session,
-  tokenizer: Tokenizer,
+/// Which provider computes embeddings, and with what model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderConfig {
+  Local,
+  /// Any server speaking the OpenAI embeddings API
+  OpenAi {
+    url: String,
+    model: String,
+  },
+  Ollama {
+    url: String,
+    model: String,
+  },
 }
 
-struct ModelFiles {
-  tokenizer_file: std::path::PathBuf,
-  model_path: std::path::PathBuf,
-}
-
-// Public API
-#[cfg(not(tarpaulin_include))] // [rag-stack] - add CI/CD testing for cross-platform loading/unloading
-impl EmbeddingModel {
-  /// Load the GTE-Base model from HuggingFace
-  pub async fn load() -> Result<Self> {
-    bentley::info!("loading model...");
-
-    let model_files = Self::download_model().await?;
-    let tokenizer = Self::load_tokenizer(model_files.tokenizer_file)?;
-    let session = Self::load_model(model_files.model_path)?;
-    Ok(Self { session, tokenizer })
-  }
-
-  /// Generate embeddings for a single text
-  pub fn embed(&mut self, text: &str) -> Result<Vec<f32>> {
-    let tokens = Self::tokenize(text, &self.tokenizer)?;
-    let input = Self::prepare(tokens.as_ref(), &self.session)?;
-    let output = self.session.run(input)?;
-    let raw_embedding = Self::extract_embedding(&output)?;
-    Self::normalize_embedding(raw_embedding)
-  }
-}
-
-// Model initialization
-// violet ignore chunk - this is about as simple and flat as it's going to get without breaking this into
-// singlet implementation blocks.
-#[cfg(not(tarpaulin_include))] // [rag-stack] - add CI/CD testing for cross-platform loading/unloading
-impl EmbeddingModel {
-  async fn download_model() -> Result<ModelFiles> {
-    let api = Api::new().map_err(|e| anyhow!("HF API initialization failed: {}", e))?;
-
-    let repo = api.model(MODEL_NAME.to_string());
-
-    let tokenizer_file =
-      repo.get(TOKENIZER_FILE).await.map_err(|e| anyhow!("Failed to download tokenizer: {}", e))?;
-
-    let model_path =
-      repo.get(MODEL_FILE).await.map_err(|e| anyhow!("Failed to download ONNX model: {}", e))?;
-
-    // Download config files to understand model architecture
-    Self::ensure_config_files(&repo).await?;
-
-    // Check and download external data file if needed
-    Self::ensure_external_data_file(&model_path, &repo).await?;
-
-    Ok(ModelFiles { tokenizer_file, model_path })
-  }
-
-  async fn ensure_config_files(repo: &hf_hub::api::tokio::ApiRepo) -> Result<()> {
-    bentley::info!("Downloading model configuration files...");
-
-    // Download essential config files
-    let config_files = ["config.json", "generation_config.json", "tokenizer_config.json"];
-
-    for config_file in &config_files {
-      match repo.get(config_file).await {
-        Ok(_) => bentley::info!(&format!("Downloaded {config_file}")),
-        Err(e) => bentley::warn!(&format!("Could not download {config_file}: {e}")),
+impl ProviderConfig {
+  /// Get the configured embedding provider
+  /// Default: local
+  /// Environment: INSIGHTS_EMBEDDING_PROVIDER (local, openai or ollama),
+  /// INSIGHTS_EMBEDDING_MODEL, INSIGHTS_EMBEDDING_URL
+  pub fn from_env() -> Result<Self> {
+    let provider =
+      std::env::var("INSIGHTS_EMBEDDING_PROVIDER").unwrap_or_else(|_| "local".to_string());
+    let setting = |var: &str| std::env::var(var).ok().filter(|v| !v.trim().is_empty());
+    Self::parse(&provider, setting("INSIGHTS_EMBEDDING_MODEL"), setting("INSIGHTS_EMBEDDING_URL"))
+  }
+
+  /// Provider settings, filling in each remote provider's default model and URL
+  pub fn parse(provider: &str, model: Option<String>, url: Option<String>) -> Result<Self> {
+    let url = |default: &str| url.clone().unwrap_or_else(|| default.to_string());
+    match provider.trim().to_lowercase().as_str() {
+      "local" | "onnx" => Ok(ProviderConfig::Local),
+      "openai" => Ok(ProviderConfig::OpenAi {
+        url: url(remote::OPENAI_URL),
+        model: model.unwrap_or_else(|| remote::OPENAI_MODEL.to_string()),
+      }),
+      "ollama" => Ok(ProviderConfig::Ollama {
+        url: url(remote::OLLAMA_URL),
+        model: model.unwrap_or_else(|| remote::OLLAMA_MODEL.to_string()),
+      }),
+      other => {
+        Err(anyhow!("Unknown embedding provider '{other}' (expected local, openai or ollama)"))
       }
     }
-
-    Ok(())
   }
 
-  async fn ensure_external_data_file(
-    model_path: &std::path::Path,
-    repo: &hf_hub::api::tokio::ApiRepo,
-  ) -> Result<()> {
-    // Check if external data file exists
-    let external_data_path = model_path.with_file_name("model.onnx_data");
-
-    if !external_data_path.exists() {
-      bentley::info!("External data file missing, downloading model.onnx_data...");
-
-      // Download the external data file
-      let _external_data_file = repo
-        .get("onnx/model.onnx_data")
-        .await
-        .map_err(|e| anyhow!("Failed to download external data file: {}", e))?;
-
-      bentley::info!("External data file downloaded successfully");
-    }
-
-    Ok(())
-  }
-
-  fn load_tokenizer(path: std::path::PathBuf) -> Result<Tokenizer> {
-    Tokenizer::from_file(path).map_err(|e| anyhow!("Failed to load tokenizer: {}", e))
-  }
-
-  fn load_model(model_path: std::path::PathBuf) -> Result<Session> {
-    let providers = Self::get_execution_providers();
-
-    let session =
-      Session::builder()?.with_execution_providers(providers)?.commit_from_file(model_path)?;
-
-    Ok(session)
-  }
-}
-
-// Hardware detection
-#[cfg(not(tarpaulin_include))] // [rag-stack] - add CI/CD testing for xplat
-impl EmbeddingModel {
-  fn get_execution_providers() -> Vec<ExecutionProviderDispatch> {
-    let mut providers = Vec::new();
-
-    #[cfg(target_os = "macos")]
-    {
-      providers.push(CoreMLExecutionProvider::default().into());
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-      if Self::is_cuda_available() {
-        providers.push(CUDAExecutionProvider::default().build().error_on_failure());
-      }
-    }
-
-    providers.push(CPUExecutionProvider::default().into());
-    providers
-  }
-
-  /// Check if CUDA is available using ONNX Runtime's ExecutionProvider::is_available()
-  #[cfg(target_os = "linux")]
-  fn is_cuda_available() -> bool {
-    // First check if nvidia-smi exists (hardware level)
-
-    std::process::Command::new("nvidia-smi")
-      .output()
-      .map(|output| output.status.success())
-      .unwrap_or(false)
-  }
-}
-
-// violet ignore chunk - this is about as simple and flat as it's going to get without being terse.
-// Embedding processing
-impl EmbeddingModel {
-  /// Testable tokenization logic
-  fn tokenize(text: &str, tokenizer: &dyn TextTokenizer) -> Result<Box<dyn TokenizerOutput>> {
-    let tokens = tokenizer.encode_text(text, true)?;
-
-    let token_count = tokens.get_ids().len();
-    Self::validate_sequence_length(token_count)?;
-
-    Ok(tokens)
-  }
-
-  /// Validate token sequence length - extracted for easy testing
-  fn validate_sequence_length(token_count: usize) -> Result<()> {
-    const MAX_SEQUENCE_LENGTH: usize = 511; // GTE-Base limit is 512
-
-    if token_count > MAX_SEQUENCE_LENGTH {
-      bentley::warn!(&format!(
-        "Tokenizer bug detected: {token_count} tokens for what should be short text. This suggests a tokenizer malfunction. Temporarily allowing to proceed."
-      ));
-      // TEMPORARY WORKAROUND: Allow processing to continue instead of failing
-      // TODO: Fix the underlying tokenizer bug that's causing all text to tokenize to exactly 512 tokens
-    }
-
-    Ok(())
-  }
-
-  /// Testable tensor preparation logic
-  fn prepare(
-    tokens: &dyn TokenEncoding,
-    session: &dyn SessionInputs,
-  ) -> Result<std::collections::HashMap<String, Value>> {
-    let input_ids_tensor = Self::to_tensor(tokens.get_ids())?;
-    let attention_mask_tensor = Self::to_tensor(tokens.get_attention_mask())?;
-    let token_type_ids_tensor = Self::to_tensor(tokens.get_type_ids())?;
-
-    // Generate position IDs: [0, 1, 2, ..., seq_len-1]
-    let seq_len = tokens.get_ids().len();
-    let position_ids: Vec<u32> = (0..seq_len as u32).collect();
-    let position_ids_tensor = Self::to_tensor(&position_ids)?;
-
-    // Create input based on what the model expects
-    let mut input = HashMap::new();
-    input.insert("input_ids".to_string(), input_ids_tensor);
-    input.insert("attention_mask".to_string(), attention_mask_tensor);
-
-    // Get model input names to determine what the model expects
-    let model_input_names = session.input_names();
-
-    if model_input_names.contains(&"token_type_ids".to_string()) {
-      input.insert("token_type_ids".to_string(), token_type_ids_tensor);
-    }
-
-    if model_input_names.contains(&"position_ids".to_string()) {
-      input.insert("position_ids".to_string(), position_ids_tensor);
-    }
-
-    // Add past_key_values for CausalLM models (empty for embedding tasks)
-    Self::add_past_key_values(&mut input, &model_input_names)?;
-
-    Ok(input)
-  }
-
-  fn add_past_key_values(
-    input: &mut HashMap<String, Value>,
-    model_input_names: &[String],
-  ) -> Result<()> {
-    // Check if model expects past key values (common for CausalLM-based embedding models)
-    let past_key_names: Vec<&String> =
-      model_input_names.iter().filter(|name| name.starts_with("past_key_values.")).collect();
-
-    if !past_key_names.is_empty() {
-      bentley::verbose!(&format!(
-        "Adding {} empty past_key_values tensors for CausalLM",
-        past_key_names.len()
-      ));
-
-      for past_key_name in past_key_names {
-        let empty_tensor = Self::create_empty_past_key_value_tensor()?;
-        input.insert(past_key_name.clone(), empty_tensor);
+  /// Create the provider, reading the API key of remote providers from secrets
+  pub async fn connect(&self) -> Result<Arc<dyn EmbeddingProvider>> {
+    Ok(match self {
+      #[cfg(feature = "ml-features")]
+      ProviderConfig::Local => Arc::new(LocalProvider),
+      #[cfg(not(feature = "ml-features"))]
+      ProviderConfig::Local => {
+        return Err(anyhow!(
+          "The local embedding model needs the ml-features build; set INSIGHTS_EMBEDDING_PROVIDER to openai or ollama"
+        ))
       }
-    }
-
-    Ok(())
-  }
-
-  fn create_empty_past_key_value_tensor() -> Result<Value> {
-    // Create empty tensor with shape [1, num_heads, 0, head_dim]
-    // For Qwen3: num_heads=8, head_dim=128 (from config)
-    use ndarray::Array4;
-    let empty_array: Array4<f32> = Array4::zeros((1, 8, 0, 128));
-    Ok(Value::from_array(empty_array)?.into())
-  }
-
-  fn to_tensor<T: Copy + Into<i64>>(values: &[T]) -> Result<Value> {
-    let seq_len = values.len();
-    let array: Array2<i64> = Array2::from_shape_vec((1, seq_len), Self::to_i64(values))?;
-    let tensor: Value = Value::from_array(array)?.into();
-    Ok(tensor)
-  }
-
-  fn to_i64<T: Copy + Into<i64>>(values: &[T]) -> Vec<i64> {
-    values.iter().map(|&x| x.into()).collect()
-  }
-
-  /// Testable tensor extraction logic
-  fn extract_embedding(output: &dyn EmbeddingOutput) -> Result<Vec<f32>> {
-    let tensor = output
-      .get_tensor("last_hidden_state")
-      .or_else(|| output.get_tensor("0"))
-      .ok_or_else(|| anyhow!("No output found from model - expected 'last_hidden_state' or '0'"))?;
-
-    let (shape, data) = tensor.extract_f32_data()?;
-
-    Self::mean_pool((shape, data))
-  }
-
-  /// Perform mean pooling over sequence dimension for sentence embeddings
-  pub fn mean_pool(embedding: (&[i64], &[f32])) -> Result<Vec<f32>> {
-    let (shape, data) = embedding;
-
-    let seq_length = shape[1] as usize;
-    let hidden_size = shape[2] as usize;
-
-    let mut embedding = vec![0.0f32; hidden_size];
-    for token_idx in 0..seq_length {
-      let start = token_idx * hidden_size;
-      let end = start + hidden_size;
-      for (i, &value) in data[start..end].iter().enumerate() {
-        embedding[i] += value;
+      ProviderConfig::OpenAi { url, model } => {
+        Arc::new(remote::OpenAiProvider::new(url, model, remote::load_api_key().await?))
       }
-    }
-
-    for value in embedding.iter_mut() {
-      *value /= seq_length as f32;
-    }
-
-    Ok(embedding)
+      ProviderConfig::Ollama { url, model } => Arc::new(remote::OllamaProvider::new(url, model)),
+    })
   }
+}
 
-  /// Normalize embedding vector to unit length for consistent similarity comparisons
-  pub fn normalize_embedding(mut embedding: Vec<f32>) -> Result<Vec<f32>> {
-    // Calculate magnitude (L2 norm)
-    let magnitude: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
-
-    // Avoid division by zero
-    if magnitude < f32::EPSILON {
-      bentley::warn!("Zero-magnitude embedding detected - returning unchanged");
-      return Ok(embedding);
-    }
+// Global provider, created on first use
+static PROVIDER: tokio::sync::OnceCell<Arc<dyn EmbeddingProvider>> =
+  tokio::sync::OnceCell::const_new();
 
-    // Normalize to unit length
-    for value in embedding.iter_mut() {
-      *value /= magnitude;
-    }
+/// The configured embedding provider
+///
+/// A provider that could not be created (say, the secrets keeper was locked)
+/// is not remembered, so the next embedding tries again.
+pub async fn provider() -> Result<Arc<dyn EmbeddingProvider>> {
+  if DISABLED.load(Ordering::Relaxed) {
+    return Err(anyhow!("Embeddings are disabled"));
+  }
+
+  let provider = PROVIDER
+    .get_or_try_init(|| async {
+      let config = ProviderConfig::from_env()?;
+      bentley::info!(&format!("Using embedding provider: {config:?}"));
+      config.connect().await
+    })
+    .await?;
+  Ok(provider.clone())
+}
 
-    Ok(embedding)
-  }
+/// Embedding version recorded for newly embedded insights
+pub async fn version() -> Result<String> {
+  Ok(provider().await?.version())
 }
 
-// Global singleton for the embedding model
-static MODEL: std::sync::OnceLock<Mutex<Option<EmbeddingModel>>> = std::sync::OnceLock::new();
 /// Detect the current embedding model's output dimension by creating a test embedding
 #[cfg(not(tarpaulin_include))]
 pub async fn detect_embedding_dimension() -> Result<usize> {
@@ -421,7 +150,7 @@ pub async fn detect_embedding_dimension() -> Result<usize> {
 /// consider using the task-specific functions like `create_query_embedding` or `create_document_embedding`.
 #[cfg(not(tarpaulin_include))]
 pub async fn create_embedding(text: &str) -> Result<Vec<f32>> {
-  create_embedding_with_prompt(text).await
+  embed_one(text.to_string(), text).await
 }
 
 /// Create embeddings optimized for search queries using EmbeddingGemma prompt format
@@ -431,7 +160,7 @@ pub async fn create_query_embedding(query: &str) -> Result<Vec<f32>> {
   let formatted_query = format!("task: search result | query: {query}");
   // Reduced verbosity: only log at verbose level and with less detail
   // bentley::verbose!("Creating query embedding");
-  create_embedding_with_prompt(&formatted_query).await
+  embed_one(formatted_query, query).await
 }
 
 /// Create embeddings optimized for documents using EmbeddingGemma prompt format
@@ -442,7 +171,22 @@ pub async fn create_document_embedding(content: &str, title: Option<&str>) -> Re
   let formatted_doc = format!("title: {title_part} | text: {content}");
   // Reduced verbosity: only log at verbose level
   // bentley::verbose!("Creating document embedding");
-  create_embedding_with_prompt(&formatted_doc).await
+  embed_one(formatted_doc, &plain_document(title, content)).await
+}
+
+/// Create document embeddings for a batch of `(title, content)` pairs in one model run
+/// Uses the same format as `create_document_embedding` for each document
+#[cfg(not(tarpaulin_include))]
+pub async fn create_document_embeddings(documents: &[(String, String)]) -> Result<Vec<Vec<f32>>> {
+  let provider = provider().await?;
+  let texts: Vec<String> = documents
+    .iter()
+    .map(|(title, content)| match provider.uses_task_prompts() {
+      true => format!("title: {title} | text: {content}"),
+      false => plain_document(Some(title), content),
+    })
+    .collect();
+  provider.embed(&texts).await
 }
 
 /// Create embeddings optimized for semantic similarity using EmbeddingGemma prompt format
@@ -453,32 +197,27 @@ pub async fn create_semantic_similarity_embedding(content: &str) -> Result<Vec<f
   let formatted_content = format!("task: sentence similarity | query: {content}");
   // Reduced verbosity: only log at verbose level
   // bentley::verbose!("Creating semantic similarity embedding");
-  create_embedding_with_prompt(&formatted_content).await
+  embed_one(formatted_content, content).await
 }
 
-/// Internal function to create embeddings with proper model initialization
-#[cfg(not(tarpaulin_include))]
-async fn create_embedding_with_prompt(formatted_text: &str) -> Result<Vec<f32>> {
-  let mutex = MODEL.get_or_init(|| Mutex::new(None));
-
-  // Check if we need to initialize the model
-  let needs_init = {
-    let guard = mutex.lock().map_err(|_| anyhow!("Failed to lock model mutex"))?;
-    guard.is_none()
-  };
-
-  // Initialize model if needed (outside of the lock to avoid holding across await)
-  if needs_init {
-    bentley::info!("Initializing embedding model...");
-    let model = EmbeddingModel::load().await?;
-    let mut guard = mutex.lock().map_err(|_| anyhow!("Failed to lock model mutex"))?;
-    *guard = Some(model);
+/// A document as sent to providers without task prompts
+fn plain_document(title: Option<&str>, content: &str) -> String {
+  match title {
+    Some(title) => format!("{title}\n\n{content}"),
+    None => content.to_string(),
   }
+}
 
-  // Get embedding
-  let mut guard = mutex.lock().map_err(|_| anyhow!("Failed to lock model mutex"))?;
-  let model = guard.as_mut().ok_or_else(|| anyhow!("Model not initialized"))?;
-  model.embed(formatted_text)
+/// Internal function to embed one text, prompted if the provider uses task prompts
+#[cfg(not(tarpaulin_include))]
+async fn embed_one(prompted: String, plain: &str) -> Result<Vec<f32>> {
+  let provider = provider().await?;
+  let text = if provider.uses_task_prompts() { prompted } else { plain.to_string() };
+  provider
+    .embed(&[text])
+    .await?
+    .pop()
+    .ok_or_else(|| anyhow!("Embedding provider returned no embedding"))
 }
 
 /// Generate a reranking relevance score using EmbeddingGemma semantic similarity task
@@ -538,117 +277,9 @@ fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
   ((similarity + 1.0) / 2.0).clamp(0.0, 1.0)
 }
 
-/// No-op functions when ML features are not available
-#[cfg(not(feature = "ml-features"))]
-pub async fn create_query_embedding(_query: &str) -> Result<Vec<f32>> {
-  Err(anyhow!("ML features not available"))
-}
-
-#[cfg(not(feature = "ml-features"))]
-pub async fn create_document_embedding(_content: &str, _title: Option<&str>) -> Result<Vec<f32>> {
-  Err(anyhow!("ML features not available"))
-}
-
-#[cfg(not(feature = "ml-features"))]
-pub async fn create_semantic_similarity_embedding(_content: &str) -> Result<Vec<f32>> {
-  Err(anyhow!("ML features not available"))
-}
-
-#[cfg(not(feature = "ml-features"))]
-pub async fn create_reranking_score(_query: &str, _document: &str) -> Result<f32> {
-  // Return neutral score when ML features not available
-  Ok(0.5)
-}
-
 #[cfg(test)]
-mod gte_base_tests {
+mod tests {
   use super::*;
-  use anyhow::Result;
-  use std::collections::HashMap;
-
-  /// Mock implementations for testing
-  struct MockTensorExtractor {
-    tensors: HashMap<String, MockTensorData>,
-  }
-
-  struct MockTensorData {
-    shape: Vec<i64>,
-    data: Vec<f32>,
-  }
-
-  struct MockTokenEncoding {
-    ids: Vec<u32>,
-    attention_mask: Vec<u32>,
-    type_ids: Vec<u32>,
-  }
-
-  struct MockSessionInputs {
-    input_names: Vec<String>,
-  }
-
-  /// Mock tokenizer implementations
-  struct MockTextTokenizer {
-    should_fail: bool,
-    token_ids: Vec<u32>,
-  }
-
-  #[derive(Debug)]
-  struct MockTokenizerOutput {
-    ids: Vec<u32>,
-    attention_mask: Vec<u32>,
-    type_ids: Vec<u32>,
-  }
-
-  impl TextTokenizer for MockTextTokenizer {
-    fn encode_text(
-      &self,
-      _text: &str,
-      _add_special_tokens: bool,
-    ) -> Result<Box<dyn TokenizerOutput>> {
-      if self.should_fail {
-        return Err(anyhow!("Mock tokenization failure"));
-      }
-
-      let len = self.token_ids.len();
-      Ok(Box::new(MockTokenizerOutput {
-        ids: self.token_ids.clone(),
-        attention_mask: vec![1; len],
-        type_ids: vec![0; len],
-      }))
-    }
-  }
-
-  impl TokenEncoding for MockTokenizerOutput {
-    fn get_ids(&self) -> &[u32] {
-      &self.ids
-    }
-    fn get_attention_mask(&self) -> &[u32] {
-      &self.attention_mask
-    }
-    fn get_type_ids(&self) -> &[u32] {
-      &self.type_ids
-    }
-  }
-
-  impl TokenizerOutput for MockTokenizerOutput {}
-
-  impl TokenEncoding for MockTokenEncoding {
-    fn get_ids(&self) -> &[u32] {
-      &self.ids
-    }
-    fn get_attention_mask(&self) -> &[u32] {
-      &self.attention_mask
-    }
-    fn get_type_ids(&self) -> &[u32] {
-      &self.type_ids
-    }
-  }
-
-  impl SessionInputs for MockSessionInputs {
-    fn input_names(&self) -> Vec<String> {
-      self.input_names.clone()
-    }
-  }
 
   /// Test cosine similarity calculation
   #[test]
@@ -699,716 +330,24 @@ mod gte_base_tests {
     assert!(similar_score > different_score, "Similar vectors should have higher similarity");
   }
 
-  /// Test prepare_from with token_type_ids expected by model
-  #[test]
-  fn test_prepare_with_token_type_ids_expected() -> Result<()> {
-    let tokens = MockTokenEncoding {
-      ids: vec![101, 7592, 102], // [CLS] hello [SEP]
-      attention_mask: vec![1, 1, 1],
-      type_ids: vec![0, 0, 0],
-    };
-
-    let session = MockSessionInputs {
-      input_names: vec![
-        "input_ids".to_string(),
-        "attention_mask".to_string(),
-        "token_type_ids".to_string(), // Model expects this
-      ],
-    };
-
-    let result = EmbeddingModel::prepare(&tokens, &session)?;
-
-    // Should contain all three tensors
-    assert_eq!(result.len(), 3);
-    assert!(result.contains_key("input_ids"));
-    assert!(result.contains_key("attention_mask"));
-    assert!(result.contains_key("token_type_ids"));
-
-    Ok(())
-  }
-
-  /// Test prepare_from with token_type_ids NOT expected by model
-  #[test]
-  fn test_prepare_without_token_type_ids_expected() -> Result<()> {
-    let tokens = MockTokenEncoding {
-      ids: vec![101, 7592, 102],
-      attention_mask: vec![1, 1, 1],
-      type_ids: vec![0, 0, 0],
-    };
-
-    let session = MockSessionInputs {
-      input_names: vec!["input_ids".to_string(), "attention_mask".to_string()],
-    };
-
-    let result = EmbeddingModel::prepare(&tokens, &session)?;
-
-    // Should contain only two tensors
-    assert_eq!(result.len(), 2);
-    assert!(result.contains_key("input_ids"));
-    assert!(result.contains_key("attention_mask"));
-    assert!(!result.contains_key("token_type_ids")); // Should be excluded
-
-    Ok(())
-  }
-
-  /// Test prepare_from with single token
-  #[test]
-  fn test_prepare_single_token() -> Result<()> {
-    let tokens = MockTokenEncoding {
-      ids: vec![101], // Just [CLS]
-      attention_mask: vec![1],
-      type_ids: vec![0],
-    };
-
-    let session = MockSessionInputs {
-      input_names: vec!["input_ids".to_string(), "attention_mask".to_string()],
-    };
-
-    let result = EmbeddingModel::prepare(&tokens, &session)?;
-
-    assert_eq!(result.len(), 2);
-    assert!(result.contains_key("input_ids"));
-    assert!(result.contains_key("attention_mask"));
-
-    Ok(())
-  }
-
-  /// Test prepare_from with empty tokens (edge case)
-  #[test]
-  fn test_prepare_empty_tokens() -> Result<()> {
-    let tokens = MockTokenEncoding { ids: vec![], attention_mask: vec![], type_ids: vec![] };
-
-    let session = MockSessionInputs {
-      input_names: vec!["input_ids".to_string(), "attention_mask".to_string()],
-    };
-
-    let result = EmbeddingModel::prepare(&tokens, &session)?;
-
-    assert_eq!(result.len(), 2);
-    assert!(result.contains_key("input_ids"));
-    assert!(result.contains_key("attention_mask"));
-
-    Ok(())
-  }
-
-  /// Test prepare_from with long sequence
-  #[test]
-  fn test_prepare_long_sequence() -> Result<()> {
-    let tokens = MockTokenEncoding {
-      ids: (0..512).collect(), // 512 tokens
-      attention_mask: vec![1; 512],
-      type_ids: vec![0; 256].into_iter().chain(vec![1; 256]).collect(), // Mixed type IDs
-    };
-
-    let session = MockSessionInputs {
-      input_names: vec![
-        "input_ids".to_string(),
-        "attention_mask".to_string(),
-        "token_type_ids".to_string(),
-      ],
-    };
-
-    let result = EmbeddingModel::prepare(&tokens, &session)?;
-
-    assert_eq!(result.len(), 3);
-    assert!(result.contains_key("input_ids"));
-    assert!(result.contains_key("attention_mask"));
-    assert!(result.contains_key("token_type_ids"));
-
-    Ok(())
-  }
-
-  /// Test prepare_from with unconventional model input names
+  /// Test provider selection fills in each remote provider's defaults
   #[test]
-  fn test_prepare_custom_input_names() -> Result<()> {
-    let tokens = MockTokenEncoding {
-      ids: vec![101, 7592, 102],
-      attention_mask: vec![1, 1, 1],
-      type_ids: vec![0, 0, 0],
-    };
-
-    let session = MockSessionInputs {
-      input_names: vec![
-        "input_ids".to_string(),
-        "attention_mask".to_string(),
-        "custom_input".to_string(), // Different name, not token_type_ids
-      ],
-    };
-
-    let result = EmbeddingModel::prepare(&tokens, &session)?;
-
-    // Should not include token_type_ids since "token_type_ids" not in input names
-    assert_eq!(result.len(), 2);
-    assert!(result.contains_key("input_ids"));
-    assert!(result.contains_key("attention_mask"));
-    assert!(!result.contains_key("token_type_ids"));
-
-    Ok(())
-  }
-
-  /// Test sequence length validation - extracted logic
-  #[test]
-  fn test_validate_sequence_length_within_limit() -> Result<()> {
-    // Test at various valid lengths
-    assert!(EmbeddingModel::validate_sequence_length(1).is_ok());
-    assert!(EmbeddingModel::validate_sequence_length(100).is_ok());
-    assert!(EmbeddingModel::validate_sequence_length(511).is_ok()); // Exactly at limit
-
-    Ok(())
-  }
-
-  #[test]
-  fn test_validate_sequence_length_exceeds_limit() {
-    // Test over the limit - should succeed due to temporary workaround
-    let result = EmbeddingModel::validate_sequence_length(512);
-    assert!(result.is_ok());
-
-    // Test well over the limit - should also succeed due to temporary workaround
-    let result = EmbeddingModel::validate_sequence_length(1000);
-    assert!(result.is_ok());
-  }
-
-  /// Test tokenize with normal case
-  #[test]
-  fn test_tokenize_normal_case() -> Result<()> {
-    let tokenizer = MockTextTokenizer {
-      should_fail: false,
-      token_ids: vec![101, 7592, 2256, 102], // [CLS] hello world [SEP]
-    };
-
-    let result = EmbeddingModel::tokenize("hello world", &tokenizer)?;
-
-    assert_eq!(result.get_ids().len(), 4);
-    assert_eq!(result.get_ids(), &[101, 7592, 2256, 102]);
-    assert_eq!(result.get_attention_mask(), &[1, 1, 1, 1]);
-    assert_eq!(result.get_type_ids(), &[0, 0, 0, 0]);
-
-    Ok(())
-  }
-
-  /// Test tokenize with tokenization failure
-  #[test]
-  fn test_tokenize_tokenization_failure() {
-    let tokenizer = MockTextTokenizer { should_fail: true, token_ids: vec![] };
-
-    let result = EmbeddingModel::tokenize("any text", &tokenizer);
-
-    assert!(result.is_err());
-    let error_msg = result.unwrap_err().to_string();
-    assert!(error_msg.contains("Mock tokenization failure"));
-  }
-
-  /// Test tokenize with sequence length at exactly the limit
-  #[test]
-  fn test_tokenize_at_sequence_limit() -> Result<()> {
-    let tokenizer = MockTextTokenizer {
-      should_fail: false,
-      token_ids: vec![1; 511], // Exactly 511 tokens (the limit)
-    };
-
-    let result = EmbeddingModel::tokenize("long text", &tokenizer)?;
-
-    assert_eq!(result.get_ids().len(), 511);
-
-    Ok(())
-  }
-
-  /// Test tokenize with sequence length exceeding limit
-  #[test]
-  fn test_tokenize_exceeds_sequence_limit() {
-    let tokenizer = MockTextTokenizer {
-      should_fail: false,
-      token_ids: vec![1; 512], // 512 tokens - exceeds limit of 511
-    };
-
-    let result = EmbeddingModel::tokenize("very long text", &tokenizer);
-
-    // Should succeed due to temporary workaround
-    assert!(result.is_ok());
-    assert_eq!(result.unwrap().get_ids().len(), 512);
-  }
-
-  /// Test tokenize with empty input (edge case)
-  #[test]
-  fn test_tokenize_empty_input() -> Result<()> {
-    let tokenizer = MockTextTokenizer {
-      should_fail: false,
-      token_ids: vec![101, 102], // Just [CLS] [SEP]
-    };
-
-    let result = EmbeddingModel::tokenize("", &tokenizer)?;
-
-    assert_eq!(result.get_ids().len(), 2); // Should have special tokens
-    assert_eq!(result.get_ids(), &[101, 102]);
-
-    Ok(())
-  }
-
-  /// Test tokenize with single character
-  #[test]
-  fn test_tokenize_single_character() -> Result<()> {
-    let tokenizer = MockTextTokenizer {
-      should_fail: false,
-      token_ids: vec![101, 1037, 102], // [CLS] a [SEP]
-    };
-
-    let result = EmbeddingModel::tokenize("a", &tokenizer)?;
-
-    assert_eq!(result.get_ids().len(), 3);
-    assert_eq!(result.get_ids(), &[101, 1037, 102]);
-
-    Ok(())
-  }
-
-  /// Test tokenize with very long sequence over limit
-  #[test]
-  fn test_tokenize_way_over_limit() {
-    let tokenizer = MockTextTokenizer {
-      should_fail: false,
-      token_ids: vec![1; 1000], // Way over limit
-    };
-
-    let result = EmbeddingModel::tokenize("extremely long text", &tokenizer);
-
-    // Should succeed due to temporary workaround
-    assert!(result.is_ok());
-    assert_eq!(result.unwrap().get_ids().len(), 1000);
-  }
-
-  impl EmbeddingOutput for MockTensorExtractor {
-    fn get_tensor(&self, key: &str) -> Option<&dyn TensorData> {
-      self.tensors.get(key).map(|t| t as &dyn TensorData)
-    }
-  }
-
-  impl TensorData for MockTensorData {
-    fn extract_f32_data(&self) -> Result<(&[i64], &[f32])> {
-      Ok((&self.shape, &self.data))
-    }
-  }
-
-  /// Test extract_embedding with "last_hidden_state" tensor
-  #[test]
-  fn test_extract_embedding_last_hidden_state() -> Result<()> {
-    let mut tensors = HashMap::new();
-    tensors.insert(
-      "last_hidden_state".to_string(),
-      MockTensorData {
-        shape: vec![1, 2, 3],                     // batch=1, seq=2, hidden=3
-        data: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], // 2 tokens, 3 dims each
-      },
-    );
-
-    let output = MockTensorExtractor { tensors };
-    let result = EmbeddingModel::extract_embedding(&output)?;
-
-    assert_eq!(result.len(), 3);
-    assert_eq!(result[0], 2.5); // mean of [1.0, 4.0]
-    assert_eq!(result[1], 3.5); // mean of [2.0, 5.0]
-    assert_eq!(result[2], 4.5); // mean of [3.0, 6.0]
-
-    Ok(())
-  }
-
-  /// Test extract_embedding fallback to "0" tensor
-  #[test]
-  fn test_extract_embedding_fallback_to_zero() -> Result<()> {
-    let mut tensors = HashMap::new();
-    tensors.insert(
-      "0".to_string(),
-      MockTensorData {
-        shape: vec![1, 1, 2],   // batch=1, seq=1, hidden=2
-        data: vec![10.0, 20.0], // single token
-      },
-    );
-
-    let output = MockTensorExtractor { tensors };
-    let result = EmbeddingModel::extract_embedding(&output)?;
-
-    assert_eq!(result, vec![10.0, 20.0]); // no averaging needed for single token
-
-    Ok(())
-  }
-
-  /// Test extract_embedding with both tensors - should prefer "last_hidden_state"
-  #[test]
-  fn test_extract_embedding_prefers_last_hidden_state() -> Result<()> {
-    let mut tensors = HashMap::new();
-    tensors.insert(
-      "last_hidden_state".to_string(),
-      MockTensorData {
-        shape: vec![1, 1, 2],
-        data: vec![100.0, 200.0], // This should be used
-      },
-    );
-    tensors.insert(
-      "0".to_string(),
-      MockTensorData {
-        shape: vec![1, 1, 2],
-        data: vec![1.0, 2.0], // This should be ignored
-      },
-    );
-
-    let output = MockTensorExtractor { tensors };
-    let result = EmbeddingModel::extract_embedding(&output)?;
-
-    assert_eq!(result, vec![100.0, 200.0]); // Used last_hidden_state
-
-    Ok(())
-  }
-
-  /// Test extract_embedding when no tensor is found
-  #[test]
-  fn test_extract_embedding_no_tensor_found() {
-    let tensors = HashMap::new(); // empty - no tensors
-    let output = MockTensorExtractor { tensors };
-
-    let result = EmbeddingModel::extract_embedding(&output);
-
-    assert!(result.is_err());
-    let error_msg = result.unwrap_err().to_string();
-    assert!(error_msg.contains("No output found from model"));
-    assert!(error_msg.contains("last_hidden_state"));
-    assert!(error_msg.contains("0"));
-  }
-
-  /// Test extract_embedding with complex multi-token scenario
-  #[test]
-  fn test_extract_embedding_complex_scenario() -> Result<()> {
-    let mut tensors = HashMap::new();
-    tensors.insert(
-      "last_hidden_state".to_string(),
-      MockTensorData {
-        shape: vec![1, 4, 3], // batch=1, seq=4, hidden=3
-        data: vec![
-          1.0, 2.0, 3.0, // token 1
-          4.0, 5.0, 6.0, // token 2
-          7.0, 8.0, 9.0, // token 3
-          10.0, 11.0, 12.0, // token 4
-        ],
-      },
-    );
-
-    let output = MockTensorExtractor { tensors };
-    let result = EmbeddingModel::extract_embedding(&output)?;
-
-    assert_eq!(result.len(), 3);
-    assert_eq!(result[0], 5.5); // mean of [1.0, 4.0, 7.0, 10.0]
-    assert_eq!(result[1], 6.5); // mean of [2.0, 5.0, 8.0, 11.0]
-    assert_eq!(result[2], 7.5); // mean of [3.0, 6.0, 9.0, 12.0]
-
-    Ok(())
-  }
-
-  /// Test normal mean pooling behavior with valid inputs
-  #[test]
-  fn test_mean_pool_normal_case() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=2, hidden_size=3]
-    let shape = vec![1i64, 2i64, 3i64];
-
-    // Data for 2 tokens, each with 3 hidden dimensions
-    // Token 1: [1.0, 2.0, 3.0]
-    // Token 2: [4.0, 5.0, 6.0]
-    // Expected mean: [2.5, 3.5, 4.5]
-    let data = vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 3);
-    assert_eq!(result[0], 2.5);
-    assert_eq!(result[1], 3.5);
-    assert_eq!(result[2], 4.5);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with single token
-  #[test]
-  fn test_mean_pool_single_token() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=1, hidden_size=4]
-    let shape = vec![1i64, 1i64, 4i64];
-
-    // Data for 1 token with 4 hidden dimensions
-    let data = vec![10.0f32, 20.0, 30.0, 40.0];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 4);
-    assert_eq!(result[0], 10.0);
-    assert_eq!(result[1], 20.0);
-    assert_eq!(result[2], 30.0);
-    assert_eq!(result[3], 40.0);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with multiple tokens
-  #[test]
-  fn test_mean_pool_multiple_tokens() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=3, hidden_size=2]
-    let shape = vec![1i64, 3i64, 2i64];
-
-    // Data for 3 tokens, each with 2 hidden dimensions
-    // Token 1: [1.0, 2.0]
-    // Token 2: [3.0, 4.0]
-    // Token 3: [5.0, 6.0]
-    // Expected mean: [3.0, 4.0]
-    let data = vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 2);
-    assert_eq!(result[0], 3.0);
-    assert_eq!(result[1], 4.0);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with negative values
-  #[test]
-  fn test_mean_pool_negative_values() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=2, hidden_size=2]
-    let shape = vec![1i64, 2i64, 2i64];
-
-    // Data with negative values
-    // Token 1: [-1.0, 2.0]
-    // Token 2: [3.0, -4.0]
-    // Expected mean: [1.0, -1.0]
-    let data = vec![-1.0f32, 2.0, 3.0, -4.0];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 2);
-    assert_eq!(result[0], 1.0);
-    assert_eq!(result[1], -1.0);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with zero values
-  #[test]
-  fn test_mean_pool_zero_values() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=2, hidden_size=3]
-    let shape = vec![1i64, 2i64, 3i64];
-
-    // Data with all zeros
-    let data = vec![0.0f32; 6];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 3);
-    assert_eq!(result[0], 0.0);
-    assert_eq!(result[1], 0.0);
-    assert_eq!(result[2], 0.0);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with mixed positive/negative/zero values
-  #[test]
-  fn test_mean_pool_mixed_values() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=3, hidden_size=1]
-    let shape = vec![1i64, 3i64, 1i64];
-
-    // Data: [-5.0, 0.0, 5.0]
-    // Expected mean: [0.0]
-    let data = vec![-5.0f32, 0.0, 5.0];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 1);
-    assert_eq!(result[0], 0.0);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with empty sequence - should handle division by zero
-  #[test]
-  fn test_mean_pool_empty_sequence() {
-    // Shape: [batch_size=1, seq_length=0, hidden_size=3]
-    let shape = vec![1i64, 0i64, 3i64];
-    let data = vec![];
-
-    // This should either return an error or handle the division by zero gracefully
-    let result = EmbeddingModel::mean_pool((&shape, &data));
-
-    // The current implementation will cause division by zero
-    // This test documents the current behavior and should be updated if the function is fixed
-    match result {
-      Ok(embedding) => {
-        // If it succeeds, all values should be NaN due to 0/0
-        assert_eq!(embedding.len(), 3);
-        for value in embedding {
-          assert!(value.is_nan());
-        }
+  fn test_provider_config_parse() {
+    assert_eq!(ProviderConfig::parse("local", None, None).unwrap(), ProviderConfig::Local);
+    assert_eq!(
+      ProviderConfig::parse("OpenAI", None, Some("http://gpu:8000/v1".to_string())).unwrap(),
+      ProviderConfig::OpenAi {
+        url: "http://gpu:8000/v1".to_string(),
+        model: remote::OPENAI_MODEL.to_string()
       }
-      Err(_) => {
-        // Error is also acceptable for this edge case
+    );
+    assert_eq!(
+      ProviderConfig::parse("ollama", Some("mxbai-embed-large".to_string()), None).unwrap(),
+      ProviderConfig::Ollama {
+        url: remote::OLLAMA_URL.to_string(),
+        model: "mxbai-embed-large".to_string()
       }
-    }
-  }
-
-  /// Test mean pooling with empty hidden dimension
-  #[test]
-  fn test_mean_pool_empty_hidden_dimension() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=2, hidden_size=0]
-    let shape = vec![1i64, 2i64, 0i64];
-    let data = vec![];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    // Should return empty vector
-    assert_eq!(result.len(), 0);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with decimal averages
-  #[test]
-  fn test_mean_pool_decimal_averages() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=3, hidden_size=1]
-    let shape = vec![1i64, 3i64, 1i64];
-
-    // Data: [1.0, 2.0, 3.0]
-    // Expected mean: [2.0]
-    let data = vec![1.0f32, 2.0, 3.0];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 1);
-    assert_eq!(result[0], 2.0);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with floating point precision
-  #[test]
-  fn test_mean_pool_floating_point_precision() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=3, hidden_size=2]
-    let shape = vec![1i64, 3i64, 2i64];
-
-    // Data that will create floating point division
-    // Token 1: [1.0, 1.0]
-    // Token 2: [1.0, 1.0]
-    // Token 3: [1.0, 1.0]
-    // Expected mean: [1.0, 1.0]
-    let data = vec![1.0f32, 1.0, 1.0, 1.0, 1.0, 1.0];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 2);
-    assert!((result[0] - 1.0).abs() < f32::EPSILON);
-    assert!((result[1] - 1.0).abs() < f32::EPSILON);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with large values
-  #[test]
-  fn test_mean_pool_large_values() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=2, hidden_size=2]
-    let shape = vec![1i64, 2i64, 2i64];
-
-    // Data with large values
-    let data = vec![1000.0f32, 2000.0, 3000.0, 4000.0];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 2);
-    assert_eq!(result[0], 2000.0);
-    assert_eq!(result[1], 3000.0);
-
-    Ok(())
-  }
-
-  /// Test mean pooling with very small values
-  #[test]
-  fn test_mean_pool_small_values() -> Result<()> {
-    // Shape: [batch_size=1, seq_length=2, hidden_size=2]
-    let shape = vec![1i64, 2i64, 2i64];
-
-    // Data with very small values
-    let data = vec![0.001f32, 0.002, 0.003, 0.004];
-
-    let result = EmbeddingModel::mean_pool((&shape, &data))?;
-
-    assert_eq!(result.len(), 2);
-    assert!((result[0] - 0.002).abs() < f32::EPSILON);
-    assert!((result[1] - 0.003).abs() < f32::EPSILON);
-
-    Ok(())
-  }
-
-  /// Test normalization with normal vector
-  #[test]
-  fn test_normalize_embedding_normal() -> Result<()> {
-    let embedding = vec![3.0, 4.0, 0.0]; // magnitude = 5.0
-    let result = EmbeddingModel::normalize_embedding(embedding)?;
-
-    assert_eq!(result.len(), 3);
-    assert!((result[0] - 0.6).abs() < f32::EPSILON); // 3/5
-    assert!((result[1] - 0.8).abs() < f32::EPSILON); // 4/5
-    assert!((result[2] - 0.0).abs() < f32::EPSILON); // 0/5
-
-    // Check magnitude is now 1.0
-    let magnitude: f32 = result.iter().map(|x| x * x).sum::<f32>().sqrt();
-    assert!((magnitude - 1.0).abs() < f32::EPSILON);
-
-    Ok(())
-  }
-
-  /// Test normalization preserves zero vector
-  #[test]
-  fn test_normalize_embedding_zero_vector() -> Result<()> {
-    let embedding = vec![0.0, 0.0, 0.0];
-    let result = EmbeddingModel::normalize_embedding(embedding.clone())?;
-
-    assert_eq!(result, embedding); // Should be unchanged
-    Ok(())
-  }
-
-  /// Test normalization with unit vector
-  #[test]
-  fn test_normalize_embedding_unit_vector() -> Result<()> {
-    let embedding = vec![1.0, 0.0, 0.0]; // Already unit length
-    let result = EmbeddingModel::normalize_embedding(embedding.clone())?;
-
-    assert_eq!(result, embedding); // Should be unchanged
-    Ok(())
-  }
-
-  /// Test normalization with large values
-  #[test]
-  fn test_normalize_embedding_large_values() -> Result<()> {
-    let embedding = vec![1000.0, 2000.0]; // magnitude = sqrt(5000000) ≈ 2236
-    let result = EmbeddingModel::normalize_embedding(embedding)?;
-
-    assert_eq!(result.len(), 2);
-
-    // Check magnitude is now 1.0
-    let magnitude: f32 = result.iter().map(|x| x * x).sum::<f32>().sqrt();
-    assert!((magnitude - 1.0).abs() < f32::EPSILON);
-
-    // Check direction is preserved (ratio should be 1:2)
-    assert!((result[1] / result[0] - 2.0).abs() < 0.001);
-
-    Ok(())
-  }
-
-  /// Test normalization with negative values
-  #[test]
-  fn test_normalize_embedding_negative_values() -> Result<()> {
-    let embedding = vec![-3.0, 4.0]; // magnitude = 5.0
-    let result = EmbeddingModel::normalize_embedding(embedding)?;
-
-    assert_eq!(result.len(), 2);
-    assert!((result[0] - (-0.6)).abs() < f32::EPSILON); // -3/5
-    assert!((result[1] - 0.8).abs() < f32::EPSILON); //  4/5
-
-    // Check magnitude is now 1.0
-    let magnitude: f32 = result.iter().map(|x| x * x).sum::<f32>().sqrt();
-    assert!((magnitude - 1.0).abs() < f32::EPSILON);
-
-    Ok(())
+    );
+    assert!(ProviderConfig::parse("cohere", None, None).is_err());
   }
 }
@@ -0,0 +1,1456 @@
+//! The bundled EmbeddingGemma model, run locally through ONNX Runtime
+
+use anyhow::{anyhow, Result};
+use hf_hub::api::tokio::Api;
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokenizers::Tokenizer;
+
+use super::{EmbeddingProvider, LOCAL_VERSION};
+
+const MODEL_NAME: &str = "onnx-community/embeddinggemma-300m-ONNX";
+const TOKENIZER_FILE: &str = "tokenizer.json";
+const MODEL_FILE: &str = "onnx/model.onnx";
+
+/// Trait for extracting tensor data - allows testing without ONNX complexity
+trait EmbeddingOutput {
+  fn get_tensor(&self, key: &str) -> Option<&dyn TensorData>;
+}
+
+trait TensorData {
+  fn extract_f32_data(&self) -> Result<(&[i64], &[f32])>;
+}
+
+/// Trait abstractions for testable tensor preparation
+trait TokenEncoding {
+  fn get_ids(&self) -> &[u32];
+  fn get_attention_mask(&self) -> &[u32];
+  fn get_type_ids(&self) -> &[u32];
+}
+
+trait SessionInputs {
+  fn input_names(&self) -> Vec<String>;
+}
+
+/// Trait abstraction for testable tokenization
+trait TextTokenizer {
+  fn encode_text(&self, text: &str, add_special_tokens: bool) -> Result<Box<dyn TokenizerOutput>>;
+}
+
+trait TokenizerOutput: std::fmt::Debug + TokenEncoding {}
+
+#[cfg(target_os = "linux")]
+use ort::{
+  execution_providers::{CPUExecutionProvider, CUDAExecutionProvider, ExecutionProviderDispatch},
+  session::Session,
+  value::Value,
+};
+
+#[cfg(target_os = "macos")]
+use ort::{
+  execution_providers::{CPUExecutionProvider, CoreMLExecutionProvider, ExecutionProviderDispatch},
+  session::Session,
+  value::Value,
+};
+
+// Implementations for real ONNX types
+#[cfg(not(tarpaulin_include))]
+impl<'s> EmbeddingOutput for ort::session::SessionOutputs<'s> {
+  fn get_tensor(&self, key: &str) -> Option<&dyn TensorData> {
+    self.get(key).map(|v| v as &dyn TensorData)
+  }
+}
+
+#[cfg(not(tarpaulin_include))]
+impl TensorData for ort::value::Value {
+  fn extract_f32_data(&self) -> Result<(&[i64], &[f32])> {
+    let (shape, data) = self.try_extract_tensor::<f32>()?;
+    Ok((shape.as_ref(), data))
+  }
+}
+
+// Implementations for real types
+#[cfg(not(tarpaulin_include))]
+impl TokenEncoding for tokenizers::Encoding {
+  fn get_ids(&self) -> &[u32] {
+    self.get_ids()
+  }
+  fn get_attention_mask(&self) -> &[u32] {
+    self.get_attention_mask()
+  }
+  fn get_type_ids(&self) -> &[u32] {
+    self.get_type_ids()
+  }
+}
+
+#[cfg(not(tarpaulin_include))]
+impl SessionInputs for Session {
+  fn input_names(&self) -> Vec<String> {
+    self.inputs.iter().map(|input| input.name.to_string()).collect()
+  }
+}
+
+// Real tokenizer implementations
+#[cfg(not(tarpaulin_include))]
+impl TextTokenizer for Tokenizer {
+  fn encode_text(&self, text: &str, add_special_tokens: bool) -> Result<Box<dyn TokenizerOutput>> {
+    let encoding =
+      self.encode(text, add_special_tokens).map_err(|e| anyhow!("Tokenization failed: {}", e))?;
+    Ok(Box::new(encoding))
+  }
+}
+
+impl TokenizerOutput for tokenizers::Encoding {}
+
+pub struct EmbeddingModel {
+  session: Session,
+  tokenizer: Tokenizer,
+}
+
+struct ModelFiles {
+  tokenizer_file: std::path::PathBuf,
+  model_path: std::path::PathBuf,
+}
+
+// Public API
+#[cfg(not(tarpaulin_include))] // [rag-stack] - add CI/CD testing for cross-platform loading/unloading
+impl EmbeddingModel {
+  /// Load the GTE-Base model from HuggingFace
+  pub async fn load() -> Result<Self> {
+    bentley::info!("loading model...");
+
+    let model_files = Self::download_model().await?;
+    let tokenizer = Self::load_tokenizer(model_files.tokenizer_file)?;
+    let session = Self::load_model(model_files.model_path)?;
+    Ok(Self { session, tokenizer })
+  }
+
+  /// Generate embeddings for a single text
+  pub fn embed(&mut self, text: &str) -> Result<Vec<f32>> {
+    let tokens = Self::tokenize(text, &self.tokenizer)?;
+    let input = Self::prepare(tokens.as_ref(), &self.session)?;
+    let output = self.session.run(input)?;
+    let raw_embedding = Self::extract_embedding(&output)?;
+    Self::normalize_embedding(raw_embedding)
+  }
+
+  /// Generate embeddings for several texts in one run of the model
+  ///
+  /// Shorter texts are padded to the longest one and the padding is masked out
+  /// of the pooled embeddings, so each result matches what `embed` gives.
+  pub fn embed_batch(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+    if texts.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    let tokens =
+      texts.iter().map(|text| Self::tokenize(text, &self.tokenizer)).collect::<Result<Vec<_>>>()?;
+    let pad_id = self
+      .tokenizer
+      .get_padding()
+      .map(|padding| padding.pad_id)
+      .or_else(|| self.tokenizer.token_to_id("<pad>"))
+      .unwrap_or(0);
+    let batch = Self::pad_batch(&tokens, pad_id);
+
+    let input = Self::prepare_batch(&batch, &self.session)?;
+    let output = self.session.run(input)?;
+    let raw_embeddings = Self::extract_batch_embeddings(&output, &batch.attention_mask)?;
+    raw_embeddings.into_iter().map(Self::normalize_embedding).collect()
+  }
+}
+
+/// Token ids of a batch of texts, right-padded to a common length
+#[derive(Debug)]
+struct PaddedBatch {
+  rows: usize,
+  seq_len: usize,
+  ids: Vec<i64>,
+  attention_mask: Vec<i64>,
+  type_ids: Vec<i64>,
+}
+
+// Model initialization
+// violet ignore chunk - this is about as simple and flat as it's going to get without breaking this into
+// singlet implementation blocks.
+#[cfg(not(tarpaulin_include))] // [rag-stack] - add CI/CD testing for cross-platform loading/unloading
+impl EmbeddingModel {
+  async fn download_model() -> Result<ModelFiles> {
+    let api = Api::new().map_err(|e| anyhow!("HF API initialization failed: {}", e))?;
+
+    let repo = api.model(MODEL_NAME.to_string());
+
+    let tokenizer_file =
+      repo.get(TOKENIZER_FILE).await.map_err(|e| anyhow!("Failed to download tokenizer: {}", e))?;
+
+    let model_path =
+      repo.get(MODEL_FILE).await.map_err(|e| anyhow!("Failed to download ONNX model: {}", e))?;
+
+    // Download config files to understand model architecture
+    Self::ensure_config_files(&repo).await?;
+
+    // Check and download external data file if needed
+    Self::ensure_external_data_file(&model_path, &repo).await?;
+
+    Ok(ModelFiles { tokenizer_file, model_path })
+  }
+
+  async fn ensure_config_files(repo: &hf_hub::api::tokio::ApiRepo) -> Result<()> {
+    bentley::info!("Downloading model configuration files...");
+
+    // Download essential config files
+    let config_files = ["config.json", "generation_config.json", "tokenizer_config.json"];
+
+    for config_file in &config_files {
+      match repo.get(config_file).await {
+        Ok(_) => bentley::info!(&format!("Downloaded {config_file}")),
+        Err(e) => bentley::warn!(&format!("Could not download {config_file}: {e}")),
+      }
+    }
+
+    Ok(())
+  }
+
+  async fn ensure_external_data_file(
+    model_path: &std::path::Path,
+    repo: &hf_hub::api::tokio::ApiRepo,
+  ) -> Result<()> {
+    // Check if external data file exists
+    let external_data_path = model_path.with_file_name("model.onnx_data");
+
+    if !external_data_path.exists() {
+      bentley::info!("External data file missing, downloading model.onnx_data...");
+
+      // Download the external data file
+      let _external_data_file = repo
+        .get("onnx/model.onnx_data")
+        .await
+        .map_err(|e| anyhow!("Failed to download external data file: {}", e))?;
+
+      bentley::info!("External data file downloaded successfully");
+    }
+
+    Ok(())
+  }
+
+  fn load_tokenizer(path: std::path::PathBuf) -> Result<Tokenizer> {
+    Tokenizer::from_file(path).map_err(|e| anyhow!("Failed to load tokenizer: {}", e))
+  }
+
+  fn load_model(model_path: std::path::PathBuf) -> Result<Session> {
+    let providers = Self::get_execution_providers();
+
+    let mut builder = Session::builder()?.with_execution_providers(providers)?;
+    if let Some(threads) = crate::server::services::indexing::get_max_threads() {
+      builder = builder.with_intra_threads(threads)?;
+    }
+    let session = builder.commit_from_file(model_path)?;
+
+    Ok(session)
+  }
+}
+
+// Hardware detection
+#[cfg(not(tarpaulin_include))] // [rag-stack] - add CI/CD testing for xplat
+impl EmbeddingModel {
+  fn get_execution_providers() -> Vec<ExecutionProviderDispatch> {
+    let mut providers = Vec::new();
+
+    #[cfg(target_os = "macos")]
+    {
+      providers.push(CoreMLExecutionProvider::default().into());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+      if Self::is_cuda_available() {
+        providers.push(CUDAExecutionProvider::default().build().error_on_failure());
+      }
+    }
+
+    providers.push(CPUExecutionProvider::default().into());
+    providers
+  }
+
+  /// Check if CUDA is available using ONNX Runtime's ExecutionProvider::is_available()
+  #[cfg(target_os = "linux")]
+  fn is_cuda_available() -> bool {
+    // First check if nvidia-smi exists (hardware level)
+
+    std::process::Command::new("nvidia-smi")
+      .output()
+      .map(|output| output.status.success())
+      .unwrap_or(false)
+  }
+}
+
+// violet ignore chunk - this is about as simple and flat as it's going to get without being terse.
+// Embedding processing
+impl EmbeddingModel {
+  /// Testable tokenization logic
+  fn tokenize(text: &str, tokenizer: &dyn TextTokenizer) -> Result<Box<dyn TokenizerOutput>> {
+    let tokens = tokenizer.encode_text(text, true)?;
+
+    let token_count = tokens.get_ids().len();
+    Self::validate_sequence_length(token_count)?;
+
+    Ok(tokens)
+  }
+
+  /// Validate token sequence length - extracted for easy testing
+  fn validate_sequence_length(token_count: usize) -> Result<()> {
+    const MAX_SEQUENCE_LENGTH: usize = 511; // GTE-Base limit is 512
+
+    if token_count > MAX_SEQUENCE_LENGTH {
+      bentley::warn!(&format!(
+        "Tokenizer bug detected: {token_count} tokens for what should be short text. This suggests a tokenizer malfunction. Temporarily allowing to proceed."
+      ));
+      // TEMPORARY WORKAROUND: Allow processing to continue instead of failing
+      // TODO: Fix the underlying tokenizer bug that's causing all text to tokenize to exactly 512 tokens
+    }
+
+    Ok(())
+  }
+
+  /// Testable tensor preparation logic
+  fn prepare(
+    tokens: &dyn TokenEncoding,
+    session: &dyn SessionInputs,
+  ) -> Result<std::collections::HashMap<String, Value>> {
+    let input_ids_tensor = Self::to_tensor(tokens.get_ids())?;
+    let attention_mask_tensor = Self::to_tensor(tokens.get_attention_mask())?;
+    let token_type_ids_tensor = Self::to_tensor(tokens.get_type_ids())?;
+
+    // Generate position IDs: [0, 1, 2, ..., seq_len-1]
+    let seq_len = tokens.get_ids().len();
+    let position_ids: Vec<u32> = (0..seq_len as u32).collect();
+    let position_ids_tensor = Self::to_tensor(&position_ids)?;
+
+    // Create input based on what the model expects
+    let mut input = HashMap::new();
+    input.insert("input_ids".to_string(), input_ids_tensor);
+    input.insert("attention_mask".to_string(), attention_mask_tensor);
+
+    // Get model input names to determine what the model expects
+    let model_input_names = session.input_names();
+
+    if model_input_names.contains(&"token_type_ids".to_string()) {
+      input.insert("token_type_ids".to_string(), token_type_ids_tensor);
+    }
+
+    if model_input_names.contains(&"position_ids".to_string()) {
+      input.insert("position_ids".to_string(), position_ids_tensor);
+    }
+
+    // Add past_key_values for CausalLM models (empty for embedding tasks)
+    Self::add_past_key_values(&mut input, &model_input_names, 1)?;
+
+    Ok(input)
+  }
+
+  /// Pad tokenized texts to the longest one, masking out the padding
+  fn pad_batch(tokens: &[Box<dyn TokenizerOutput>], pad_id: u32) -> PaddedBatch {
+    let rows = tokens.len();
+    let seq_len = tokens.iter().map(|t| t.get_ids().len()).max().unwrap_or(0);
+    let mut batch = PaddedBatch {
+      rows,
+      seq_len,
+      ids: Vec::with_capacity(rows * seq_len),
+      attention_mask: Vec::with_capacity(rows * seq_len),
+      type_ids: Vec::with_capacity(rows * seq_len),
+    };
+
+    for row in tokens {
+      let padding = seq_len - row.get_ids().len();
+      batch.ids.extend(Self::to_i64(row.get_ids()));
+      batch.ids.extend(std::iter::repeat_n(pad_id as i64, padding));
+      batch.attention_mask.extend(Self::to_i64(row.get_attention_mask()));
+      batch.attention_mask.extend(std::iter::repeat_n(0, padding));
+      batch.type_ids.extend(Self::to_i64(row.get_type_ids()));
+      batch.type_ids.extend(std::iter::repeat_n(0, padding));
+    }
+
+    batch
+  }
+
+  /// Tensor preparation for a padded batch, shaped `[rows, seq_len]`
+  fn prepare_batch(
+    batch: &PaddedBatch,
+    session: &dyn SessionInputs,
+  ) -> Result<std::collections::HashMap<String, Value>> {
+    let shape = (batch.rows, batch.seq_len);
+    let position_ids: Vec<i64> = (0..batch.rows).flat_map(|_| 0..batch.seq_len as i64).collect();
+
+    let mut input = HashMap::new();
+    input.insert("input_ids".to_string(), Self::to_batch_tensor(shape, batch.ids.clone())?);
+    input.insert(
+      "attention_mask".to_string(),
+      Self::to_batch_tensor(shape, batch.attention_mask.clone())?,
+    );
+
+    let model_input_names = session.input_names();
+
+    if model_input_names.contains(&"token_type_ids".to_string()) {
+      input.insert(
+        "token_type_ids".to_string(),
+        Self::to_batch_tensor(shape, batch.type_ids.clone())?,
+      );
+    }
+
+    if model_input_names.contains(&"position_ids".to_string()) {
+      input.insert("position_ids".to_string(), Self::to_batch_tensor(shape, position_ids)?);
+    }
+
+    Self::add_past_key_values(&mut input, &model_input_names, batch.rows)?;
+
+    Ok(input)
+  }
+
+  fn to_batch_tensor(shape: (usize, usize), values: Vec<i64>) -> Result<Value> {
+    let array: Array2<i64> = Array2::from_shape_vec(shape, values)?;
+    Ok(Value::from_array(array)?.into())
+  }
+
+  fn add_past_key_values(
+    input: &mut HashMap<String, Value>,
+    model_input_names: &[String],
+    batch_size: usize,
+  ) -> Result<()> {
+    // Check if model expects past key values (common for CausalLM-based embedding models)
+    let past_key_names: Vec<&String> =
+      model_input_names.iter().filter(|name| name.starts_with("past_key_values.")).collect();
+
+    if !past_key_names.is_empty() {
+      bentley::verbose!(&format!(
+        "Adding {} empty past_key_values tensors for CausalLM",
+        past_key_names.len()
+      ));
+
+      for past_key_name in past_key_names {
+        let empty_tensor = Self::create_empty_past_key_value_tensor(batch_size)?;
+        input.insert(past_key_name.clone(), empty_tensor);
+      }
+    }
+
+    Ok(())
+  }
+
+  fn create_empty_past_key_value_tensor(batch_size: usize) -> Result<Value> {
+    // Create empty tensor with shape [batch_size, num_heads, 0, head_dim]
+    // For Qwen3: num_heads=8, head_dim=128 (from config)
+    use ndarray::Array4;
+    let empty_array: Array4<f32> = Array4::zeros((batch_size, 8, 0, 128));
+    Ok(Value::from_array(empty_array)?.into())
+  }
+
+  fn to_tensor<T: Copy + Into<i64>>(values: &[T]) -> Result<Value> {
+    let seq_len = values.len();
+    let array: Array2<i64> = Array2::from_shape_vec((1, seq_len), Self::to_i64(values))?;
+    let tensor: Value = Value::from_array(array)?.into();
+    Ok(tensor)
+  }
+
+  fn to_i64<T: Copy + Into<i64>>(values: &[T]) -> Vec<i64> {
+    values.iter().map(|&x| x.into()).collect()
+  }
+
+  /// Testable tensor extraction logic
+  fn extract_embedding(output: &dyn EmbeddingOutput) -> Result<Vec<f32>> {
+    let tensor = output
+      .get_tensor("last_hidden_state")
+      .or_else(|| output.get_tensor("0"))
+      .ok_or_else(|| anyhow!("No output found from model - expected 'last_hidden_state' or '0'"))?;
+
+    let (shape, data) = tensor.extract_f32_data()?;
+
+    Self::mean_pool((shape, data))
+  }
+
+  /// Tensor extraction for a batch, one pooled embedding per row
+  fn extract_batch_embeddings(
+    output: &dyn EmbeddingOutput,
+    attention_mask: &[i64],
+  ) -> Result<Vec<Vec<f32>>> {
+    let tensor = output
+      .get_tensor("last_hidden_state")
+      .or_else(|| output.get_tensor("0"))
+      .ok_or_else(|| anyhow!("No output found from model - expected 'last_hidden_state' or '0'"))?;
+
+    let (shape, data) = tensor.extract_f32_data()?;
+
+    Self::masked_mean_pool((shape, data), attention_mask)
+  }
+
+  /// Mean pooling for every row of a batch, leaving out masked (padding) tokens
+  pub fn masked_mean_pool(
+    embedding: (&[i64], &[f32]),
+    attention_mask: &[i64],
+  ) -> Result<Vec<Vec<f32>>> {
+    let (shape, data) = embedding;
+
+    let rows = shape[0] as usize;
+    let seq_length = shape[1] as usize;
+    let hidden_size = shape[2] as usize;
+    if attention_mask.len() != rows * seq_length {
+      return Err(anyhow!("Attention mask does not match the model output shape"));
+    }
+
+    let mut pooled = Vec::with_capacity(rows);
+    for row in 0..rows {
+      let mut embedding = vec![0.0f32; hidden_size];
+      let mut tokens = 0usize;
+      for token_idx in 0..seq_length {
+        let position = row * seq_length + token_idx;
+        if attention_mask[position] == 0 {
+          continue;
+        }
+        let start = position * hidden_size;
+        for (i, &value) in data[start..start + hidden_size].iter().enumerate() {
+          embedding[i] += value;
+        }
+        tokens += 1;
+      }
+
+      for value in embedding.iter_mut() {
+        *value /= tokens.max(1) as f32;
+      }
+      pooled.push(embedding);
+    }
+
+    Ok(pooled)
+  }
+
+  /// Perform mean pooling over sequence dimension for sentence embeddings
+  pub fn mean_pool(embedding: (&[i64], &[f32])) -> Result<Vec<f32>> {
+    let (shape, data) = embedding;
+
+    let seq_length = shape[1] as usize;
+    let hidden_size = shape[2] as usize;
+
+    let mut embedding = vec![0.0f32; hidden_size];
+    for token_idx in 0..seq_length {
+      let start = token_idx * hidden_size;
+      let end = start + hidden_size;
+      for (i, &value) in data[start..end].iter().enumerate() {
+        embedding[i] += value;
+      }
+    }
+
+    for value in embedding.iter_mut() {
+      *value /= seq_length as f32;
+    }
+
+    Ok(embedding)
+  }
+
+  /// Normalize embedding vector to unit length for consistent similarity comparisons
+  pub fn normalize_embedding(mut embedding: Vec<f32>) -> Result<Vec<f32>> {
+    // Calculate magnitude (L2 norm)
+    let magnitude: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    // Avoid division by zero
+    if magnitude < f32::EPSILON {
+      bentley::warn!("Zero-magnitude embedding detected - returning unchanged");
+      return Ok(embedding);
+    }
+
+    // Normalize to unit length
+    for value in embedding.iter_mut() {
+      *value /= magnitude;
+    }
+
+    Ok(embedding)
+  }
+}
+
+// Global singleton for the embedding model
+static MODEL: std::sync::OnceLock<Mutex<Option<EmbeddingModel>>> = std::sync::OnceLock::new();
+
+/// The bundled EmbeddingGemma model, run locally through ONNX Runtime
+pub struct LocalProvider;
+
+#[async_trait::async_trait]
+impl EmbeddingProvider for LocalProvider {
+  fn version(&self) -> String {
+    LOCAL_VERSION.to_string()
+  }
+
+  fn uses_task_prompts(&self) -> bool {
+    true
+  }
+
+  async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+    match texts {
+      [text] => with_model(|model| model.embed(text)).await.map(|embedding| vec![embedding]),
+      _ => with_model(|model| model.embed_batch(texts)).await,
+    }
+  }
+}
+
+/// Run `f` on the local embedding model, loading it on first use
+#[cfg(not(tarpaulin_include))]
+async fn with_model<T>(f: impl FnOnce(&mut EmbeddingModel) -> Result<T>) -> Result<T> {
+  let mutex = MODEL.get_or_init(|| Mutex::new(None));
+
+  // Check if we need to initialize the model
+  let needs_init = {
+    let guard = mutex.lock().map_err(|_| anyhow!("Failed to lock model mutex"))?;
+    guard.is_none()
+  };
+
+  // Initialize model if needed (outside of the lock to avoid holding across await)
+  if needs_init {
+    bentley::info!("Initializing embedding model...");
+    let model = EmbeddingModel::load().await?;
+    let mut guard = mutex.lock().map_err(|_| anyhow!("Failed to lock model mutex"))?;
+    *guard = Some(model);
+  }
+
+  // Get embedding
+  let mut guard = mutex.lock().map_err(|_| anyhow!("Failed to lock model mutex"))?;
+  let model = guard.as_mut().ok_or_else(|| anyhow!("Model not initialized"))?;
+  f(model)
+}
+
+#[cfg(test)]
+mod gte_base_tests {
+  use super::*;
+  use anyhow::Result;
+  use std::collections::HashMap;
+
+  /// Mock implementations for testing
+  struct MockTensorExtractor {
+    tensors: HashMap<String, MockTensorData>,
+  }
+
+  struct MockTensorData {
+    shape: Vec<i64>,
+    data: Vec<f32>,
+  }
+
+  struct MockTokenEncoding {
+    ids: Vec<u32>,
+    attention_mask: Vec<u32>,
+    type_ids: Vec<u32>,
+  }
+
+  struct MockSessionInputs {
+    input_names: Vec<String>,
+  }
+
+  /// Mock tokenizer implementations
+  struct MockTextTokenizer {
+    should_fail: bool,
+    token_ids: Vec<u32>,
+  }
+
+  #[derive(Debug)]
+  struct MockTokenizerOutput {
+    ids: Vec<u32>,
+    attention_mask: Vec<u32>,
+    type_ids: Vec<u32>,
+  }
+
+  impl TextTokenizer for MockTextTokenizer {
+    fn encode_text(
+      &self,
+      _text: &str,
+      _add_special_tokens: bool,
+    ) -> Result<Box<dyn TokenizerOutput>> {
+      if self.should_fail {
+        return Err(anyhow!("Mock tokenization failure"));
+      }
+
+      let len = self.token_ids.len();
+      Ok(Box::new(MockTokenizerOutput {
+        ids: self.token_ids.clone(),
+        attention_mask: vec![1; len],
+        type_ids: vec![0; len],
+      }))
+    }
+  }
+
+  impl TokenEncoding for MockTokenizerOutput {
+    fn get_ids(&self) -> &[u32] {
+      &self.ids
+    }
+    fn get_attention_mask(&self) -> &[u32] {
+      &self.attention_mask
+    }
+    fn get_type_ids(&self) -> &[u32] {
+      &self.type_ids
+    }
+  }
+
+  impl TokenizerOutput for MockTokenizerOutput {}
+
+  impl TokenEncoding for MockTokenEncoding {
+    fn get_ids(&self) -> &[u32] {
+      &self.ids
+    }
+    fn get_attention_mask(&self) -> &[u32] {
+      &self.attention_mask
+    }
+    fn get_type_ids(&self) -> &[u32] {
+      &self.type_ids
+    }
+  }
+
+  impl SessionInputs for MockSessionInputs {
+    fn input_names(&self) -> Vec<String> {
+      self.input_names.clone()
+    }
+  }
+
+  /// Test prepare_from with token_type_ids expected by model
+  #[test]
+  fn test_prepare_with_token_type_ids_expected() -> Result<()> {
+    let tokens = MockTokenEncoding {
+      ids: vec![101, 7592, 102], // [CLS] hello [SEP]
+      attention_mask: vec![1, 1, 1],
+      type_ids: vec![0, 0, 0],
+    };
+
+    let session = MockSessionInputs {
+      input_names: vec![
+        "input_ids".to_string(),
+        "attention_mask".to_string(),
+        "token_type_ids".to_string(), // Model expects this
+      ],
+    };
+
+    let result = EmbeddingModel::prepare(&tokens, &session)?;
+
+    // Should contain all three tensors
+    assert_eq!(result.len(), 3);
+    assert!(result.contains_key("input_ids"));
+    assert!(result.contains_key("attention_mask"));
+    assert!(result.contains_key("token_type_ids"));
+
+    Ok(())
+  }
+
+  /// Test prepare_from with token_type_ids NOT expected by model
+  #[test]
+  fn test_prepare_without_token_type_ids_expected() -> Result<()> {
+    let tokens = MockTokenEncoding {
+      ids: vec![101, 7592, 102],
+      attention_mask: vec![1, 1, 1],
+      type_ids: vec![0, 0, 0],
+    };
+
+    let session = MockSessionInputs {
+      input_names: vec!["input_ids".to_string(), "attention_mask".to_string()],
+    };
+
+    let result = EmbeddingModel::prepare(&tokens, &session)?;
+
+    // Should contain only two tensors
+    assert_eq!(result.len(), 2);
+    assert!(result.contains_key("input_ids"));
+    assert!(result.contains_key("attention_mask"));
+    assert!(!result.contains_key("token_type_ids")); // Should be excluded
+
+    Ok(())
+  }
+
+  /// Test prepare_from with single token
+  #[test]
+  fn test_prepare_single_token() -> Result<()> {
+    let tokens = MockTokenEncoding {
+      ids: vec![101], // Just [CLS]
+      attention_mask: vec![1],
+      type_ids: vec![0],
+    };
+
+    let session = MockSessionInputs {
+      input_names: vec!["input_ids".to_string(), "attention_mask".to_string()],
+    };
+
+    let result = EmbeddingModel::prepare(&tokens, &session)?;
+
+    assert_eq!(result.len(), 2);
+    assert!(result.contains_key("input_ids"));
+    assert!(result.contains_key("attention_mask"));
+
+    Ok(())
+  }
+
+  /// Test prepare_from with empty tokens (edge case)
+  #[test]
+  fn test_prepare_empty_tokens() -> Result<()> {
+    let tokens = MockTokenEncoding { ids: vec![], attention_mask: vec![], type_ids: vec![] };
+
+    let session = MockSessionInputs {
+      input_names: vec!["input_ids".to_string(), "attention_mask".to_string()],
+    };
+
+    let result = EmbeddingModel::prepare(&tokens, &session)?;
+
+    assert_eq!(result.len(), 2);
+    assert!(result.contains_key("input_ids"));
+    assert!(result.contains_key("attention_mask"));
+
+    Ok(())
+  }
+
+  /// Test prepare_from with long sequence
+  #[test]
+  fn test_prepare_long_sequence() -> Result<()> {
+    let tokens = MockTokenEncoding {
+      ids: (0..512).collect(), // 512 tokens
+      attention_mask: vec![1; 512],
+      type_ids: vec![0; 256].into_iter().chain(vec![1; 256]).collect(), // Mixed type IDs
+    };
+
+    let session = MockSessionInputs {
+      input_names: vec![
+        "input_ids".to_string(),
+        "attention_mask".to_string(),
+        "token_type_ids".to_string(),
+      ],
+    };
+
+    let result = EmbeddingModel::prepare(&tokens, &session)?;
+
+    assert_eq!(result.len(), 3);
+    assert!(result.contains_key("input_ids"));
+    assert!(result.contains_key("attention_mask"));
+    assert!(result.contains_key("token_type_ids"));
+
+    Ok(())
+  }
+
+  /// Test prepare_from with unconventional model input names
+  #[test]
+  fn test_prepare_custom_input_names() -> Result<()> {
+    let tokens = MockTokenEncoding {
+      ids: vec![101, 7592, 102],
+      attention_mask: vec![1, 1, 1],
+      type_ids: vec![0, 0, 0],
+    };
+
+    let session = MockSessionInputs {
+      input_names: vec![
+        "input_ids".to_string(),
+        "attention_mask".to_string(),
+        "custom_input".to_string(), // Different name, not token_type_ids
+      ],
+    };
+
+    let result = EmbeddingModel::prepare(&tokens, &session)?;
+
+    // Should not include token_type_ids since "token_type_ids" not in input names
+    assert_eq!(result.len(), 2);
+    assert!(result.contains_key("input_ids"));
+    assert!(result.contains_key("attention_mask"));
+    assert!(!result.contains_key("token_type_ids"));
+
+    Ok(())
+  }
+
+  /// Test sequence length validation - extracted logic
+  #[test]
+  fn test_validate_sequence_length_within_limit() -> Result<()> {
+    // Test at various valid lengths
+    assert!(EmbeddingModel::validate_sequence_length(1).is_ok());
+    assert!(EmbeddingModel::validate_sequence_length(100).is_ok());
+    assert!(EmbeddingModel::validate_sequence_length(511).is_ok()); // Exactly at limit
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_validate_sequence_length_exceeds_limit() {
+    // Test over the limit - should succeed due to temporary workaround
+    let result = EmbeddingModel::validate_sequence_length(512);
+    assert!(result.is_ok());
+
+    // Test well over the limit - should also succeed due to temporary workaround
+    let result = EmbeddingModel::validate_sequence_length(1000);
+    assert!(result.is_ok());
+  }
+
+  /// Test tokenize with normal case
+  #[test]
+  fn test_tokenize_normal_case() -> Result<()> {
+    let tokenizer = MockTextTokenizer {
+      should_fail: false,
+      token_ids: vec![101, 7592, 2256, 102], // [CLS] hello world [SEP]
+    };
+
+    let result = EmbeddingModel::tokenize("hello world", &tokenizer)?;
+
+    assert_eq!(result.get_ids().len(), 4);
+    assert_eq!(result.get_ids(), &[101, 7592, 2256, 102]);
+    assert_eq!(result.get_attention_mask(), &[1, 1, 1, 1]);
+    assert_eq!(result.get_type_ids(), &[0, 0, 0, 0]);
+
+    Ok(())
+  }
+
+  /// Test tokenize with tokenization failure
+  #[test]
+  fn test_tokenize_tokenization_failure() {
+    let tokenizer = MockTextTokenizer { should_fail: true, token_ids: vec![] };
+
+    let result = EmbeddingModel::tokenize("any text", &tokenizer);
+
+    assert!(result.is_err());
+    let error_msg = result.unwrap_err().to_string();
+    assert!(error_msg.contains("Mock tokenization failure"));
+  }
+
+  /// Test tokenize with sequence length at exactly the limit
+  #[test]
+  fn test_tokenize_at_sequence_limit() -> Result<()> {
+    let tokenizer = MockTextTokenizer {
+      should_fail: false,
+      token_ids: vec![1; 511], // Exactly 511 tokens (the limit)
+    };
+
+    let result = EmbeddingModel::tokenize("long text", &tokenizer)?;
+
+    assert_eq!(result.get_ids().len(), 511);
+
+    Ok(())
+  }
+
+  /// Test tokenize with sequence length exceeding limit
+  #[test]
+  fn test_tokenize_exceeds_sequence_limit() {
+    let tokenizer = MockTextTokenizer {
+      should_fail: false,
+      token_ids: vec![1; 512], // 512 tokens - exceeds limit of 511
+    };
+
+    let result = EmbeddingModel::tokenize("very long text", &tokenizer);
+
+    // Should succeed due to temporary workaround
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().get_ids().len(), 512);
+  }
+
+  /// Test tokenize with empty input (edge case)
+  #[test]
+  fn test_tokenize_empty_input() -> Result<()> {
+    let tokenizer = MockTextTokenizer {
+      should_fail: false,
+      token_ids: vec![101, 102], // Just [CLS] [SEP]
+    };
+
+    let result = EmbeddingModel::tokenize("", &tokenizer)?;
+
+    assert_eq!(result.get_ids().len(), 2); // Should have special tokens
+    assert_eq!(result.get_ids(), &[101, 102]);
+
+    Ok(())
+  }
+
+  /// Test tokenize with single character
+  #[test]
+  fn test_tokenize_single_character() -> Result<()> {
+    let tokenizer = MockTextTokenizer {
+      should_fail: false,
+      token_ids: vec![101, 1037, 102], // [CLS] a [SEP]
+    };
+
+    let result = EmbeddingModel::tokenize("a", &tokenizer)?;
+
+    assert_eq!(result.get_ids().len(), 3);
+    assert_eq!(result.get_ids(), &[101, 1037, 102]);
+
+    Ok(())
+  }
+
+  /// Test tokenize with very long sequence over limit
+  #[test]
+  fn test_tokenize_way_over_limit() {
+    let tokenizer = MockTextTokenizer {
+      should_fail: false,
+      token_ids: vec![1; 1000], // Way over limit
+    };
+
+    let result = EmbeddingModel::tokenize("extremely long text", &tokenizer);
+
+    // Should succeed due to temporary workaround
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().get_ids().len(), 1000);
+  }
+
+  impl EmbeddingOutput for MockTensorExtractor {
+    fn get_tensor(&self, key: &str) -> Option<&dyn TensorData> {
+      self.tensors.get(key).map(|t| t as &dyn TensorData)
+    }
+  }
+
+  impl TensorData for MockTensorData {
+    fn extract_f32_data(&self) -> Result<(&[i64], &[f32])> {
+      Ok((&self.shape, &self.data))
+    }
+  }
+
+  /// Test extract_embedding with "last_hidden_state" tensor
+  #[test]
+  fn test_extract_embedding_last_hidden_state() -> Result<()> {
+    let mut tensors = HashMap::new();
+    tensors.insert(
+      "last_hidden_state".to_string(),
+      MockTensorData {
+        shape: vec![1, 2, 3],                     // batch=1, seq=2, hidden=3
+        data: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], // 2 tokens, 3 dims each
+      },
+    );
+
+    let output = MockTensorExtractor { tensors };
+    let result = EmbeddingModel::extract_embedding(&output)?;
+
+    assert_eq!(result.len(), 3);
+    assert_eq!(result[0], 2.5); // mean of [1.0, 4.0]
+    assert_eq!(result[1], 3.5); // mean of [2.0, 5.0]
+    assert_eq!(result[2], 4.5); // mean of [3.0, 6.0]
+
+    Ok(())
+  }
+
+  /// Test extract_embedding fallback to "0" tensor
+  #[test]
+  fn test_extract_embedding_fallback_to_zero() -> Result<()> {
+    let mut tensors = HashMap::new();
+    tensors.insert(
+      "0".to_string(),
+      MockTensorData {
+        shape: vec![1, 1, 2],   // batch=1, seq=1, hidden=2
+        data: vec![10.0, 20.0], // single token
+      },
+    );
+
+    let output = MockTensorExtractor { tensors };
+    let result = EmbeddingModel::extract_embedding(&output)?;
+
+    assert_eq!(result, vec![10.0, 20.0]); // no averaging needed for single token
+
+    Ok(())
+  }
+
+  /// Test extract_embedding with both tensors - should prefer "last_hidden_state"
+  #[test]
+  fn test_extract_embedding_prefers_last_hidden_state() -> Result<()> {
+    let mut tensors = HashMap::new();
+    tensors.insert(
+      "last_hidden_state".to_string(),
+      MockTensorData {
+        shape: vec![1, 1, 2],
+        data: vec![100.0, 200.0], // This should be used
+      },
+    );
+    tensors.insert(
+      "0".to_string(),
+      MockTensorData {
+        shape: vec![1, 1, 2],
+        data: vec![1.0, 2.0], // This should be ignored
+      },
+    );
+
+    let output = MockTensorExtractor { tensors };
+    let result = EmbeddingModel::extract_embedding(&output)?;
+
+    assert_eq!(result, vec![100.0, 200.0]); // Used last_hidden_state
+
+    Ok(())
+  }
+
+  /// Test extract_embedding when no tensor is found
+  #[test]
+  fn test_extract_embedding_no_tensor_found() {
+    let tensors = HashMap::new(); // empty - no tensors
+    let output = MockTensorExtractor { tensors };
+
+    let result = EmbeddingModel::extract_embedding(&output);
+
+    assert!(result.is_err());
+    let error_msg = result.unwrap_err().to_string();
+    assert!(error_msg.contains("No output found from model"));
+    assert!(error_msg.contains("last_hidden_state"));
+    assert!(error_msg.contains("0"));
+  }
+
+  /// Test extract_embedding with complex multi-token scenario
+  #[test]
+  fn test_extract_embedding_complex_scenario() -> Result<()> {
+    let mut tensors = HashMap::new();
+    tensors.insert(
+      "last_hidden_state".to_string(),
+      MockTensorData {
+        shape: vec![1, 4, 3], // batch=1, seq=4, hidden=3
+        data: vec![
+          1.0, 2.0, 3.0, // token 1
+          4.0, 5.0, 6.0, // token 2
+          7.0, 8.0, 9.0, // token 3
+          10.0, 11.0, 12.0, // token 4
+        ],
+      },
+    );
+
+    let output = MockTensorExtractor { tensors };
+    let result = EmbeddingModel::extract_embedding(&output)?;
+
+    assert_eq!(result.len(), 3);
+    assert_eq!(result[0], 5.5); // mean of [1.0, 4.0, 7.0, 10.0]
+    assert_eq!(result[1], 6.5); // mean of [2.0, 5.0, 8.0, 11.0]
+    assert_eq!(result[2], 7.5); // mean of [3.0, 6.0, 9.0, 12.0]
+
+    Ok(())
+  }
+
+  /// Test normal mean pooling behavior with valid inputs
+  #[test]
+  fn test_mean_pool_normal_case() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=2, hidden_size=3]
+    let shape = vec![1i64, 2i64, 3i64];
+
+    // Data for 2 tokens, each with 3 hidden dimensions
+    // Token 1: [1.0, 2.0, 3.0]
+    // Token 2: [4.0, 5.0, 6.0]
+    // Expected mean: [2.5, 3.5, 4.5]
+    let data = vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 3);
+    assert_eq!(result[0], 2.5);
+    assert_eq!(result[1], 3.5);
+    assert_eq!(result[2], 4.5);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with single token
+  #[test]
+  fn test_mean_pool_single_token() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=1, hidden_size=4]
+    let shape = vec![1i64, 1i64, 4i64];
+
+    // Data for 1 token with 4 hidden dimensions
+    let data = vec![10.0f32, 20.0, 30.0, 40.0];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 4);
+    assert_eq!(result[0], 10.0);
+    assert_eq!(result[1], 20.0);
+    assert_eq!(result[2], 30.0);
+    assert_eq!(result[3], 40.0);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with multiple tokens
+  #[test]
+  fn test_mean_pool_multiple_tokens() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=3, hidden_size=2]
+    let shape = vec![1i64, 3i64, 2i64];
+
+    // Data for 3 tokens, each with 2 hidden dimensions
+    // Token 1: [1.0, 2.0]
+    // Token 2: [3.0, 4.0]
+    // Token 3: [5.0, 6.0]
+    // Expected mean: [3.0, 4.0]
+    let data = vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0], 3.0);
+    assert_eq!(result[1], 4.0);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with negative values
+  #[test]
+  fn test_mean_pool_negative_values() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=2, hidden_size=2]
+    let shape = vec![1i64, 2i64, 2i64];
+
+    // Data with negative values
+    // Token 1: [-1.0, 2.0]
+    // Token 2: [3.0, -4.0]
+    // Expected mean: [1.0, -1.0]
+    let data = vec![-1.0f32, 2.0, 3.0, -4.0];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0], 1.0);
+    assert_eq!(result[1], -1.0);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with zero values
+  #[test]
+  fn test_mean_pool_zero_values() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=2, hidden_size=3]
+    let shape = vec![1i64, 2i64, 3i64];
+
+    // Data with all zeros
+    let data = vec![0.0f32; 6];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 3);
+    assert_eq!(result[0], 0.0);
+    assert_eq!(result[1], 0.0);
+    assert_eq!(result[2], 0.0);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with mixed positive/negative/zero values
+  #[test]
+  fn test_mean_pool_mixed_values() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=3, hidden_size=1]
+    let shape = vec![1i64, 3i64, 1i64];
+
+    // Data: [-5.0, 0.0, 5.0]
+    // Expected mean: [0.0]
+    let data = vec![-5.0f32, 0.0, 5.0];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0], 0.0);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with empty sequence - should handle division by zero
+  #[test]
+  fn test_mean_pool_empty_sequence() {
+    // Shape: [batch_size=1, seq_length=0, hidden_size=3]
+    let shape = vec![1i64, 0i64, 3i64];
+    let data = vec![];
+
+    // This should either return an error or handle the division by zero gracefully
+    let result = EmbeddingModel::mean_pool((&shape, &data));
+
+    // The current implementation will cause division by zero
+    // This test documents the current behavior and should be updated if the function is fixed
+    match result {
+      Ok(embedding) => {
+        // If it succeeds, all values should be NaN due to 0/0
+        assert_eq!(embedding.len(), 3);
+        for value in embedding {
+          assert!(value.is_nan());
+        }
+      }
+      Err(_) => {
+        // Error is also acceptable for this edge case
+      }
+    }
+  }
+
+  /// Test mean pooling with empty hidden dimension
+  #[test]
+  fn test_mean_pool_empty_hidden_dimension() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=2, hidden_size=0]
+    let shape = vec![1i64, 2i64, 0i64];
+    let data = vec![];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    // Should return empty vector
+    assert_eq!(result.len(), 0);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with decimal averages
+  #[test]
+  fn test_mean_pool_decimal_averages() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=3, hidden_size=1]
+    let shape = vec![1i64, 3i64, 1i64];
+
+    // Data: [1.0, 2.0, 3.0]
+    // Expected mean: [2.0]
+    let data = vec![1.0f32, 2.0, 3.0];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0], 2.0);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with floating point precision
+  #[test]
+  fn test_mean_pool_floating_point_precision() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=3, hidden_size=2]
+    let shape = vec![1i64, 3i64, 2i64];
+
+    // Data that will create floating point division
+    // Token 1: [1.0, 1.0]
+    // Token 2: [1.0, 1.0]
+    // Token 3: [1.0, 1.0]
+    // Expected mean: [1.0, 1.0]
+    let data = vec![1.0f32, 1.0, 1.0, 1.0, 1.0, 1.0];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 2);
+    assert!((result[0] - 1.0).abs() < f32::EPSILON);
+    assert!((result[1] - 1.0).abs() < f32::EPSILON);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with large values
+  #[test]
+  fn test_mean_pool_large_values() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=2, hidden_size=2]
+    let shape = vec![1i64, 2i64, 2i64];
+
+    // Data with large values
+    let data = vec![1000.0f32, 2000.0, 3000.0, 4000.0];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0], 2000.0);
+    assert_eq!(result[1], 3000.0);
+
+    Ok(())
+  }
+
+  /// Test mean pooling with very small values
+  #[test]
+  fn test_mean_pool_small_values() -> Result<()> {
+    // Shape: [batch_size=1, seq_length=2, hidden_size=2]
+    let shape = vec![1i64, 2i64, 2i64];
+
+    // Data with very small values
+    let data = vec![0.001f32, 0.002, 0.003, 0.004];
+
+    let result = EmbeddingModel::mean_pool((&shape, &data))?;
+
+    assert_eq!(result.len(), 2);
+    assert!((result[0] - 0.002).abs() < f32::EPSILON);
+    assert!((result[1] - 0.003).abs() < f32::EPSILON);
+
+    Ok(())
+  }
+
+  /// Test normalization with normal vector
+  #[test]
+  fn test_normalize_embedding_normal() -> Result<()> {
+    let embedding = vec![3.0, 4.0, 0.0]; // magnitude = 5.0
+    let result = EmbeddingModel::normalize_embedding(embedding)?;
+
+    assert_eq!(result.len(), 3);
+    assert!((result[0] - 0.6).abs() < f32::EPSILON); // 3/5
+    assert!((result[1] - 0.8).abs() < f32::EPSILON); // 4/5
+    assert!((result[2] - 0.0).abs() < f32::EPSILON); // 0/5
+
+    // Check magnitude is now 1.0
+    let magnitude: f32 = result.iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((magnitude - 1.0).abs() < f32::EPSILON);
+
+    Ok(())
+  }
+
+  /// Test normalization preserves zero vector
+  #[test]
+  fn test_normalize_embedding_zero_vector() -> Result<()> {
+    let embedding = vec![0.0, 0.0, 0.0];
+    let result = EmbeddingModel::normalize_embedding(embedding.clone())?;
+
+    assert_eq!(result, embedding); // Should be unchanged
+    Ok(())
+  }
+
+  /// Test normalization with unit vector
+  #[test]
+  fn test_normalize_embedding_unit_vector() -> Result<()> {
+    let embedding = vec![1.0, 0.0, 0.0]; // Already unit length
+    let result = EmbeddingModel::normalize_embedding(embedding.clone())?;
+
+    assert_eq!(result, embedding); // Should be unchanged
+    Ok(())
+  }
+
+  /// Test normalization with large values
+  #[test]
+  fn test_normalize_embedding_large_values() -> Result<()> {
+    let embedding = vec![1000.0, 2000.0]; // magnitude = sqrt(5000000) ≈ 2236
+    let result = EmbeddingModel::normalize_embedding(embedding)?;
+
+    assert_eq!(result.len(), 2);
+
+    // Check magnitude is now 1.0
+    let magnitude: f32 = result.iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((magnitude - 1.0).abs() < f32::EPSILON);
+
+    // Check direction is preserved (ratio should be 1:2)
+    assert!((result[1] / result[0] - 2.0).abs() < 0.001);
+
+    Ok(())
+  }
+
+  /// Test normalization with negative values
+  #[test]
+  fn test_normalize_embedding_negative_values() -> Result<()> {
+    let embedding = vec![-3.0, 4.0]; // magnitude = 5.0
+    let result = EmbeddingModel::normalize_embedding(embedding)?;
+
+    assert_eq!(result.len(), 2);
+    assert!((result[0] - (-0.6)).abs() < f32::EPSILON); // -3/5
+    assert!((result[1] - 0.8).abs() < f32::EPSILON); //  4/5
+
+    // Check magnitude is now 1.0
+    let magnitude: f32 = result.iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((magnitude - 1.0).abs() < f32::EPSILON);
+
+    Ok(())
+  }
+
+  /// Test padding a batch to its longest text
+  #[test]
+  fn test_pad_batch() {
+    let tokens: Vec<Box<dyn TokenizerOutput>> = vec![
+      Box::new(MockTokenizerOutput {
+        ids: vec![5, 6, 7],
+        attention_mask: vec![1, 1, 1],
+        type_ids: vec![0, 0, 0],
+      }),
+      Box::new(MockTokenizerOutput { ids: vec![8], attention_mask: vec![1], type_ids: vec![0] }),
+    ];
+
+    let batch = EmbeddingModel::pad_batch(&tokens, 0);
+
+    assert_eq!((batch.rows, batch.seq_len), (2, 3));
+    assert_eq!(batch.ids, vec![5, 6, 7, 8, 0, 0]);
+    assert_eq!(batch.attention_mask, vec![1, 1, 1, 1, 0, 0]);
+    assert_eq!(batch.type_ids, vec![0; 6]);
+  }
+
+  /// Test masked mean pooling leaves padding out of each row
+  #[test]
+  fn test_masked_mean_pool() -> Result<()> {
+    let shape = vec![2, 2, 2]; // batch=2, seq=2, hidden=2
+    let data = vec![
+      1.0, 2.0, 3.0, 4.0, // row 1: two real tokens
+      5.0, 6.0, 99.0, 99.0, // row 2: one real token, one padding
+    ];
+    let mask = vec![1, 1, 1, 0];
+
+    let result = EmbeddingModel::masked_mean_pool((&shape, &data), &mask)?;
+
+    assert_eq!(result, vec![vec![2.0, 3.0], vec![5.0, 6.0]]);
+    assert!(EmbeddingModel::masked_mean_pool((&shape, &data), &[1, 1]).is_err());
+
+    Ok(())
+  }
+}
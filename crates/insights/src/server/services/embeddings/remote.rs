@@ -0,0 +1,212 @@
+//! Embeddings from HTTP services
+//!
+//! `openai` speaks the OpenAI embeddings API (`POST {url}/embeddings`), which
+//! most hosted and self-hosted inference servers also implement; `ollama` uses
+//! Ollama's own `POST {url}/api/embed`. Both embed a whole batch per request.
+//!
+//! The OpenAI API key is read through the secrets keeper from the `insights`
+//! group as `embedding_api_key`, and is optional so keyless compatible servers
+//! work too. Switching providers changes the embedding dimension, so it needs a
+//! full `insights index` afterwards.
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::EmbeddingProvider;
+use crate::server::services::ask::SECRETS_GROUP;
+
+pub const OPENAI_URL: &str = "https://api.openai.com/v1";
+pub const OPENAI_MODEL: &str = "text-embedding-3-small";
+pub const OLLAMA_URL: &str = "http://localhost:11434";
+pub const OLLAMA_MODEL: &str = "nomic-embed-text";
+
+/// Secret in the `insights` group holding the OpenAI API key
+pub const API_KEY_SECRET: &str = "embedding_api_key";
+
+/// A batch of documents can take a while on a busy or CPU-only server
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Read the API key through the secrets keeper without ever prompting
+pub async fn load_api_key() -> Result<Option<String>> {
+  let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
+    PathBuf::from(blizz_dir)
+  } else {
+    dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
+  };
+
+  let group = secrets::keeper_client::read_group(&base_path, SECRETS_GROUP).await.map_err(|e| {
+    anyhow!("Could not read the embedding API key; is the secrets keeper unlocked? {e}")
+  })?;
+  Ok(group.get(API_KEY_SECRET).map(|key| key.trim().to_string()).filter(|key| !key.is_empty()))
+}
+
+fn http_client() -> Result<reqwest::Client> {
+  Ok(reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?)
+}
+
+async fn post(request: reqwest::RequestBuilder, body: &Value) -> Result<Value> {
+  let response = request.json(body).send().await?;
+  let status = response.status();
+  if !status.is_success() {
+    let detail = response.text().await.unwrap_or_default();
+    return Err(anyhow!("Embedding provider returned HTTP {status}: {}", detail.trim()));
+  }
+  Ok(response.json().await?)
+}
+
+// OpenAI
+// ======
+
+pub struct OpenAiProvider {
+  client: reqwest::Client,
+  url: String,
+  model: String,
+  api_key: Option<String>,
+}
+
+impl OpenAiProvider {
+  pub fn new(url: &str, model: &str, api_key: Option<String>) -> Self {
+    Self {
+      client: http_client().unwrap_or_default(),
+      url: url.trim_end_matches('/').to_string(),
+      model: model.to_string(),
+      api_key,
+    }
+  }
+}
+
+#[async_trait::async_trait]
+impl EmbeddingProvider for OpenAiProvider {
+  fn version(&self) -> String {
+    format!("openai:{}", self.model)
+  }
+
+  async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+    let mut request = self.client.post(format!("{}/embeddings", self.url));
+    if let Some(api_key) = &self.api_key {
+      request = request.bearer_auth(api_key);
+    }
+    let payload = post(request, &json!({ "model": self.model, "input": texts })).await?;
+    parse_openai(&payload, texts.len())
+  }
+}
+
+/// Embeddings from an OpenAI response, put back in input order
+pub fn parse_openai(payload: &Value, expected: usize) -> Result<Vec<Vec<f32>>> {
+  let data = payload["data"].as_array().ok_or_else(|| anyhow!("Response has no data"))?;
+
+  let mut embeddings: Vec<Option<Vec<f32>>> = vec![None; expected];
+  for (position, item) in data.iter().enumerate() {
+    let index = item["index"].as_u64().map_or(position, |i| i as usize);
+    let slot = embeddings.get_mut(index).ok_or_else(|| anyhow!("Unexpected embedding {index}"))?;
+    *slot = Some(parse_vector(&item["embedding"])?);
+  }
+  embeddings
+    .into_iter()
+    .collect::<Option<_>>()
+    .ok_or_else(|| anyhow!("Response is missing embeddings"))
+}
+
+// Ollama
+// ======
+
+pub struct OllamaProvider {
+  client: reqwest::Client,
+  url: String,
+  model: String,
+}
+
+impl OllamaProvider {
+  pub fn new(url: &str, model: &str) -> Self {
+    Self {
+      client: http_client().unwrap_or_default(),
+      url: url.trim_end_matches('/').to_string(),
+      model: model.to_string(),
+    }
+  }
+}
+
+#[async_trait::async_trait]
+impl EmbeddingProvider for OllamaProvider {
+  fn version(&self) -> String {
+    format!("ollama:{}", self.model)
+  }
+
+  async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+    let request = self.client.post(format!("{}/api/embed", self.url));
+    let payload = post(request, &json!({ "model": self.model, "input": texts })).await?;
+    parse_ollama(&payload, texts.len())
+  }
+}
+
+/// Embeddings from an Ollama `/api/embed` response
+pub fn parse_ollama(payload: &Value, expected: usize) -> Result<Vec<Vec<f32>>> {
+  let embeddings = payload["embeddings"]
+    .as_array()
+    .ok_or_else(|| anyhow!("Response has no embeddings"))?
+    .iter()
+    .map(parse_vector)
+    .collect::<Result<Vec<_>>>()?;
+  if embeddings.len() != expected {
+    return Err(anyhow!("Expected {expected} embeddings, got {}", embeddings.len()));
+  }
+  Ok(embeddings)
+}
+
+fn parse_vector(value: &Value) -> Result<Vec<f32>> {
+  value
+    .as_array()
+    .ok_or_else(|| anyhow!("Embedding is not a list of numbers"))?
+    .iter()
+    .map(|n| {
+      n.as_f64().map(|n| n as f32).ok_or_else(|| anyhow!("Embedding is not a list of numbers"))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_openai_restores_input_order() {
+    let payload = json!({
+      "data": [
+        { "index": 1, "embedding": [0.5, 0.25] },
+        { "index": 0, "embedding": [1.0, 0.0] },
+      ]
+    });
+
+    let embeddings = parse_openai(&payload, 2).unwrap();
+    assert_eq!(embeddings, vec![vec![1.0, 0.0], vec![0.5, 0.25]]);
+
+    assert!(parse_openai(&payload, 3).is_err());
+    assert!(parse_openai(&json!({ "error": "bad key" }), 1).is_err());
+  }
+
+  #[test]
+  fn test_parse_ollama() {
+    let payload = json!({ "model": "nomic-embed-text", "embeddings": [[0.1, 0.2], [0.3, 0.4]] });
+
+    let embeddings = parse_ollama(&payload, 2).unwrap();
+    assert_eq!(embeddings.len(), 2);
+    assert!((embeddings[1][0] - 0.3).abs() < f32::EPSILON);
+
+    assert!(parse_ollama(&payload, 1).is_err());
+    assert!(parse_ollama(&json!({ "embeddings": [["x"]] }), 1).is_err());
+  }
+
+  #[test]
+  fn test_versions_name_provider_and_model() {
+    assert_eq!(
+      OpenAiProvider::new(OPENAI_URL, OPENAI_MODEL, None).version(),
+      "openai:text-embedding-3-small"
+    );
+    assert_eq!(
+      OllamaProvider::new(OLLAMA_URL, "mxbai-embed-large").version(),
+      "ollama:mxbai-embed-large"
+    );
+  }
+}
@@ -0,0 +1,55 @@
+//! In-process change feed for live clients
+//!
+//! Every handler that creates, updates or deletes an insight publishes to a
+//! broadcast channel, and each `/ws/events` subscriber gets its own receiver. The
+//! channel is bounded: a subscriber that falls too far behind is told how many
+//! changes it missed and should refetch whatever it is showing.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::server::models::insight::Insight;
+use crate::server::models::webhook::WebhookEvent;
+
+/// Changes buffered per subscriber before it starts missing some
+const CAPACITY: usize = 256;
+
+static BUS: Lazy<broadcast::Sender<InsightEvent>> = Lazy::new(|| broadcast::channel(CAPACITY).0);
+
+/// One change pushed to live clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct InsightEvent {
+  pub event: WebhookEvent,
+  pub topic: String,
+  pub name: String,
+  pub occurred_at: DateTime<Utc>,
+}
+
+impl InsightEvent {
+  pub fn new(event: WebhookEvent, insight: &Insight) -> Self {
+    Self {
+      event,
+      topic: insight.topic.clone(),
+      name: insight.name.clone(),
+      occurred_at: Utc::now(),
+    }
+  }
+
+  /// Whether a subscriber filtering on `topic` wants this change
+  pub fn matches(&self, topic: Option<&str>) -> bool {
+    topic.is_none_or(|topic| self.topic.eq_ignore_ascii_case(topic))
+  }
+}
+
+/// Tell every live client about a change; a no-op when nobody is listening
+pub fn publish(event: WebhookEvent, insight: &Insight) {
+  let _ = BUS.send(InsightEvent::new(event, insight));
+}
+
+/// Receive every change published from now on
+pub fn subscribe() -> broadcast::Receiver<InsightEvent> {
+  BUS.subscribe()
+}
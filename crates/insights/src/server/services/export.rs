@@ -0,0 +1,268 @@
+//! Portable archives of the knowledge base
+//!
+//! A JSON archive is the export response written verbatim. A tar.gz or tar.zst
+//! archive mirrors the insights directory (`<topic>/<name>.insight.md`) so it can be
+//! unpacked straight into an insights root, with a manifest and any embeddings
+//! stored beside it. The same layouts are read back when bootstrapping from a seed.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::io::{Read, Write};
+
+use crate::server::models::insight::{self, Insight};
+use crate::server::types::{ExportedInsight, InsightsArchive};
+
+/// Bumped whenever the archive layout changes incompatibly
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Archive file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ArchiveFormat {
+  Json,
+  TarGz,
+  TarZst,
+}
+
+impl ArchiveFormat {
+  /// Pick a format from a file name, if its extension is recognised
+  pub fn from_path(path: &std::path::Path) -> Option<Self> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".json") {
+      Some(ArchiveFormat::Json)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+      Some(ArchiveFormat::TarGz)
+    } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+      Some(ArchiveFormat::TarZst)
+    } else {
+      None
+    }
+  }
+}
+
+/// Stored embeddings keyed by lowercased `(topic, name)`
+pub type EmbeddingMap = HashMap<(String, String), Vec<f32>>;
+
+/// Assemble an archive from insights and whatever embeddings were found for them
+pub fn build_archive(insights: Vec<Insight>, mut embeddings: EmbeddingMap) -> InsightsArchive {
+  let topics: BTreeSet<String> = insights.iter().map(|i| i.topic.clone()).collect();
+
+  let insights = insights
+    .into_iter()
+    .map(|insight| {
+      let key = (insight.topic.to_lowercase(), insight.name.to_lowercase());
+      ExportedInsight {
+        embedding: embeddings.remove(&key),
+        topic: insight.topic,
+        name: insight.name,
+        overview: insight.overview,
+        details: insight.details,
+        tags: insight.tags,
+        created_at: insight.created_at,
+        last_updated: insight.last_updated,
+        update_count: insight.update_count,
+      }
+    })
+    .collect();
+
+  InsightsArchive {
+    format_version: ARCHIVE_FORMAT_VERSION,
+    exported_at: Utc::now(),
+    topics: topics.into_iter().collect(),
+    insights,
+  }
+}
+
+/// Serialize an archive in the requested format
+pub fn write_archive(
+  archive: &InsightsArchive,
+  format: ArchiveFormat,
+  writer: impl Write,
+) -> Result<()> {
+  match format {
+    ArchiveFormat::Json => Ok(serde_json::to_writer_pretty(writer, archive)?),
+    ArchiveFormat::TarGz => {
+      write_tar(archive, GzEncoder::new(writer, Compression::default()))?.finish()?.flush()?;
+      Ok(())
+    }
+    ArchiveFormat::TarZst => {
+      write_tar(archive, zstd::Encoder::new(writer, 0)?)?.finish()?.flush()?;
+      Ok(())
+    }
+  }
+}
+
+/// Read an archive back, in any format it can be written in
+pub fn read_archive(format: ArchiveFormat, reader: impl Read) -> Result<InsightsArchive> {
+  let archive = match format {
+    ArchiveFormat::Json => serde_json::from_reader(reader)?,
+    ArchiveFormat::TarGz => read_tar(GzDecoder::new(reader))?,
+    ArchiveFormat::TarZst => read_tar(zstd::Decoder::new(reader)?)?,
+  };
+  if archive.format_version > ARCHIVE_FORMAT_VERSION {
+    return Err(anyhow!(
+      "archive format version {} is newer than this build supports ({ARCHIVE_FORMAT_VERSION})",
+      archive.format_version
+    ));
+  }
+  Ok(archive)
+}
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+  format_version: u32,
+  exported_at: String,
+  topics: &'a [String],
+  insights: usize,
+}
+
+#[derive(Deserialize)]
+struct ManifestEntry {
+  format_version: u32,
+  exported_at: DateTime<Utc>,
+  topics: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct EmbeddingEntry<'a> {
+  topic: &'a str,
+  name: &'a str,
+  embedding: &'a [f32],
+}
+
+#[derive(Deserialize)]
+struct StoredEmbedding {
+  topic: String,
+  name: String,
+  embedding: Vec<f32>,
+}
+
+/// Write the tar entries, handing back the (still unfinished) compressor
+fn write_tar<W: Write>(archive: &InsightsArchive, writer: W) -> Result<W> {
+  let mut tar = tar::Builder::new(writer);
+
+  let manifest = Manifest {
+    format_version: archive.format_version,
+    exported_at: archive.exported_at.to_rfc3339(),
+    topics: &archive.topics,
+    insights: archive.insights.len(),
+  };
+  append(&mut tar, "manifest.json", &serde_json::to_vec_pretty(&manifest)?)?;
+
+  for exported in &archive.insights {
+    let path = format!(
+      "insights/{}/{}.insight.md",
+      exported.topic.to_lowercase(),
+      exported.name.to_lowercase()
+    );
+    append(&mut tar, &path, insight::to_markdown(&to_insight(exported))?.as_bytes())?;
+  }
+
+  let embeddings: Vec<EmbeddingEntry> = archive
+    .insights
+    .iter()
+    .filter_map(|i| {
+      i.embedding.as_deref().map(|embedding| EmbeddingEntry {
+        topic: &i.topic,
+        name: &i.name,
+        embedding,
+      })
+    })
+    .collect();
+  if !embeddings.is_empty() {
+    append(&mut tar, "embeddings.json", &serde_json::to_vec(&embeddings)?)?;
+  }
+
+  Ok(tar.into_inner()?)
+}
+
+fn read_tar(reader: impl Read) -> Result<InsightsArchive> {
+  let mut manifest = None;
+  let mut insights = Vec::new();
+  let mut embeddings = EmbeddingMap::new();
+
+  let mut tar = tar::Archive::new(reader);
+  for entry in tar.entries()? {
+    let mut entry = entry?;
+    let path = entry.path()?.to_string_lossy().into_owned();
+    let mut content = String::new();
+
+    if path == "manifest.json" {
+      entry.read_to_string(&mut content)?;
+      manifest = Some(serde_json::from_str::<ManifestEntry>(&content)?);
+    } else if path == "embeddings.json" {
+      entry.read_to_string(&mut content)?;
+      let stored: Vec<StoredEmbedding> = serde_json::from_str(&content)?;
+      embeddings.extend(
+        stored.into_iter().map(|e| ((e.topic.to_lowercase(), e.name.to_lowercase()), e.embedding)),
+      );
+    } else if let Some((topic, name)) = insight_path(&path) {
+      entry.read_to_string(&mut content)?;
+      let (metadata, details) = insight::parse_insight_with_metadata(&content)?;
+      let or_path = |value: String, fallback: &str| {
+        if value.is_empty() {
+          fallback.to_string()
+        } else {
+          value
+        }
+      };
+      insights.push(ExportedInsight {
+        topic: or_path(metadata.topic, topic),
+        name: or_path(metadata.name, name),
+        overview: metadata.overview,
+        details,
+        tags: metadata.tags,
+        created_at: metadata.created_at,
+        last_updated: metadata.last_updated,
+        update_count: metadata.update_count,
+        embedding: None,
+      });
+    }
+  }
+
+  let manifest = manifest.ok_or_else(|| anyhow!("archive has no manifest.json"))?;
+  for exported in &mut insights {
+    let key = (exported.topic.to_lowercase(), exported.name.to_lowercase());
+    exported.embedding = embeddings.remove(&key);
+  }
+
+  Ok(InsightsArchive {
+    format_version: manifest.format_version,
+    exported_at: manifest.exported_at,
+    topics: manifest.topics,
+    insights,
+  })
+}
+
+/// `(topic, name)` of an `insights/<topic>/<name>.insight.md` entry; topics may nest
+fn insight_path(path: &str) -> Option<(&str, &str)> {
+  let (topic, file) = path.strip_prefix("insights/")?.rsplit_once('/')?;
+  let name = file.strip_suffix(".insight.md")?;
+  (!topic.is_empty() && !name.is_empty()).then_some((topic, name))
+}
+
+fn append<W: Write>(tar: &mut tar::Builder<W>, path: &str, data: &[u8]) -> Result<()> {
+  let mut header = tar::Header::new_gnu();
+  header.set_size(data.len() as u64);
+  header.set_mode(0o644);
+  header.set_mtime(Utc::now().timestamp().max(0) as u64);
+  tar.append_data(&mut header, path, data)?;
+  Ok(())
+}
+
+/// Rebuild an insight from its archived form, keeping its original timestamps
+pub(crate) fn to_insight(exported: &ExportedInsight) -> Insight {
+  let mut insight = Insight::new(
+    exported.topic.clone(),
+    exported.name.clone(),
+    exported.overview.clone(),
+    exported.details.clone(),
+  );
+  insight.tags = exported.tags.clone();
+  insight.created_at = exported.created_at;
+  insight.last_updated = exported.last_updated;
+  insight.update_count = exported.update_count;
+  insight
+}
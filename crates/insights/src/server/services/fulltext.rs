@@ -0,0 +1,282 @@
+//! Inverted full-text index over insights
+//!
+//! Keyword search used to read every insight file on every query. This keeps a
+//! tantivy index in `<insights root>/.fulltext` instead, updated whenever the server
+//! adds, updates or removes an insight and rebuilt from the files when it is missing.
+//! Matches are ranked with BM25 across the name, overview and details.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, Value, STORED, STRING, TEXT};
+use tantivy::{
+  doc, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, TantivyDocument, Term,
+};
+
+use crate::server::models::insight::{self, Insight};
+use crate::server::models::{tag, topic};
+use crate::server::services::search::{SearchOptions, SearchResult};
+
+/// Directory under the insights root holding the index
+pub const INDEX_DIR: &str = ".fulltext";
+
+/// Most matches returned by a single query
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Indexing memory budget (tantivy's minimum is 15MB)
+const WRITER_HEAP_BYTES: usize = 20_000_000;
+
+/// One open index per insights root, so tests with their own root stay isolated
+static INDEXES: Lazy<Mutex<HashMap<PathBuf, Arc<FullTextIndex>>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct Fields {
+  id: Field,
+  topic: Field,
+  name: Field,
+  overview: Field,
+  details: Field,
+  tags: Field,
+}
+
+struct FullTextIndex {
+  index: Index,
+  reader: IndexReader,
+  writer: Mutex<IndexWriter>,
+  fields: Fields,
+}
+
+fn build_schema() -> (Schema, Fields) {
+  let mut builder = Schema::builder();
+  let fields = Fields {
+    id: builder.add_text_field("id", STRING | STORED),
+    topic: builder.add_text_field("topic", STRING | STORED),
+    name: builder.add_text_field("name", TEXT | STORED),
+    overview: builder.add_text_field("overview", TEXT | STORED),
+    details: builder.add_text_field("details", TEXT | STORED),
+    tags: builder.add_text_field("tags", STRING | STORED),
+  };
+  (builder.build(), fields)
+}
+
+/// Stable document key for an insight
+fn document_id(topic: &str, name: &str) -> String {
+  format!("{}/{}", topic.to_lowercase(), name.to_lowercase())
+}
+
+impl FullTextIndex {
+  fn open(path: &Path) -> Result<(Self, bool)> {
+    std::fs::create_dir_all(path)?;
+    let mut directory = MmapDirectory::open(path)?;
+    let mut existed = Index::exists(&directory)?;
+    let (schema, fields) = build_schema();
+    if existed && Index::open(directory.clone())?.schema() != schema {
+      // Written by a version with other fields; start over and rebuild from the files
+      std::fs::remove_dir_all(path)?;
+      std::fs::create_dir_all(path)?;
+      directory = MmapDirectory::open(path)?;
+      existed = false;
+    }
+    let index = Index::open_or_create(directory, schema)?;
+
+    let reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
+    let writer = index.writer_with_num_threads(1, WRITER_HEAP_BYTES)?;
+    Ok((Self { index, reader, writer: Mutex::new(writer), fields }, !existed))
+  }
+
+  fn writer(&self) -> Result<std::sync::MutexGuard<'_, IndexWriter>> {
+    self.writer.lock().map_err(|_| anyhow!("full-text index writer poisoned"))
+  }
+
+  fn add(&self, writer: &IndexWriter, insight: &Insight) -> Result<()> {
+    let id = document_id(&insight.topic, &insight.name);
+    writer.delete_term(Term::from_field_text(self.fields.id, &id));
+    let mut document = doc!(
+      self.fields.id => id,
+      self.fields.topic => insight.topic.clone(),
+      self.fields.name => insight.name.clone(),
+      self.fields.overview => insight.overview.clone(),
+      self.fields.details => insight.details.clone(),
+    );
+    for tag in tag::normalize_all(&insight.tags) {
+      document.add_text(self.fields.tags, tag);
+    }
+    writer.add_document(document)?;
+    Ok(())
+  }
+
+  fn commit(&self, writer: &mut IndexWriter) -> Result<()> {
+    writer.commit()?;
+    self.reader.reload()?;
+    Ok(())
+  }
+
+  fn upsert(&self, insights: &[Insight]) -> Result<()> {
+    let mut writer = self.writer()?;
+    for insight in insights {
+      self.add(&writer, insight)?;
+    }
+    self.commit(&mut writer)
+  }
+
+  fn remove(&self, topic: &str, name: &str) -> Result<()> {
+    let mut writer = self.writer()?;
+    writer.delete_term(Term::from_field_text(self.fields.id, &document_id(topic, name)));
+    self.commit(&mut writer)
+  }
+
+  fn rebuild(&self, insights: &[Insight]) -> Result<()> {
+    let mut writer = self.writer()?;
+    writer.delete_all_documents()?;
+    for insight in insights {
+      self.add(&writer, insight)?;
+    }
+    self.commit(&mut writer)
+  }
+
+  fn search(
+    &self,
+    terms: &[String],
+    options: &SearchOptions,
+    limit: usize,
+  ) -> Result<Vec<SearchResult>> {
+    let default_fields = if options.overview_only {
+      vec![self.fields.name, self.fields.overview]
+    } else {
+      vec![self.fields.name, self.fields.overview, self.fields.details]
+    };
+    let parser = QueryParser::for_index(&self.index, default_fields);
+    // Lenient parsing so stray punctuation in search terms never fails a query
+    let (mut query, _) = parser.parse_query_lenient(&terms.join(" "));
+
+    let searcher = self.reader.searcher();
+    if let Some(prefix) = &options.topic {
+      let topic_query = BooleanQuery::new(
+        self
+          .topics_within(&searcher, prefix)?
+          .into_iter()
+          .map(|topic| {
+            let term = Term::from_field_text(self.fields.topic, &topic);
+            (
+              Occur::Should,
+              Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>,
+            )
+          })
+          .collect(),
+      );
+      query = Box::new(BooleanQuery::new(vec![
+        (Occur::Must, query),
+        (Occur::Must, Box::new(topic_query) as Box<dyn Query>),
+      ]));
+    }
+    if !options.tags.is_empty() {
+      let mut clauses = vec![(Occur::Must, query)];
+      for wanted in tag::normalize_all(&options.tags) {
+        let term = Term::from_field_text(self.fields.tags, &wanted);
+        clauses.push((Occur::Must, Box::new(TermQuery::new(term, IndexRecordOption::Basic))));
+      }
+      query = Box::new(BooleanQuery::new(clauses));
+    }
+
+    let mut results = Vec::new();
+    for (score, address) in searcher.search(&query, &TopDocs::with_limit(limit))? {
+      let document: TantivyDocument = searcher.doc(address)?;
+      let text = |field: Field| {
+        document.get_first(field).and_then(|value| value.as_str()).unwrap_or_default().to_string()
+      };
+      let result = SearchResult {
+        topic: text(self.fields.topic),
+        name: text(self.fields.name),
+        overview: text(self.fields.overview),
+        details: text(self.fields.details),
+        tags: document
+          .get_all(self.fields.tags)
+          .filter_map(|value| value.as_str())
+          .map(str::to_string)
+          .collect(),
+        score,
+      };
+      if !options.case_sensitive || matches_case(&result, terms, options.overview_only) {
+        results.push(result);
+      }
+    }
+    Ok(results)
+  }
+}
+
+impl FullTextIndex {
+  /// Indexed topics equal to or nested under a prefix, as stored (original case)
+  fn topics_within(&self, searcher: &Searcher, prefix: &str) -> Result<Vec<String>> {
+    let mut topics = BTreeSet::new();
+    for segment in searcher.segment_readers() {
+      let inverted = segment.inverted_index(self.fields.topic)?;
+      let mut stream = inverted.terms().stream()?;
+      while stream.advance() {
+        match std::str::from_utf8(stream.key()) {
+          Ok(indexed) if topic::within(indexed, prefix) => {
+            topics.insert(indexed.to_string());
+          }
+          _ => {}
+        }
+      }
+    }
+    Ok(topics.into_iter().collect())
+  }
+}
+
+/// The index tokenizer ignores case, so case-sensitive queries are checked afterwards
+fn matches_case(result: &SearchResult, terms: &[String], overview_only: bool) -> bool {
+  terms.iter().any(|term| {
+    result.name.contains(term.as_str())
+      || result.overview.contains(term.as_str())
+      || (!overview_only && result.details.contains(term.as_str()))
+  })
+}
+
+/// The index for the current insights root, built from the files on first use
+fn open_index() -> Result<Arc<FullTextIndex>> {
+  let root = insight::get_insights_root()?;
+  let mut indexes = INDEXES.lock().map_err(|_| anyhow!("full-text index registry poisoned"))?;
+  if let Some(index) = indexes.get(&root) {
+    return Ok(index.clone());
+  }
+
+  let (index, created) = FullTextIndex::open(&root.join(INDEX_DIR))?;
+  if created {
+    index.rebuild(&insight::get_insights(None)?)?;
+  }
+  let index = Arc::new(index);
+  indexes.insert(root, index.clone());
+  Ok(index)
+}
+
+/// Add or replace insights in the index with a single commit
+pub fn index_insights(insights: &[Insight]) -> Result<()> {
+  open_index()?.upsert(insights)
+}
+
+/// Drop an insight from the index
+pub fn remove_insight(topic: &str, name: &str) -> Result<()> {
+  open_index()?.remove(topic, name)
+}
+
+/// Re-index every insight file from scratch, returning how many were indexed
+pub fn rebuild() -> Result<usize> {
+  let insights = insight::get_insights(None)?;
+  open_index()?.rebuild(&insights)?;
+  Ok(insights.len())
+}
+
+/// Best matches for the terms, highest score first
+pub fn search(
+  terms: &[String],
+  options: &SearchOptions,
+  limit: usize,
+) -> Result<Vec<SearchResult>> {
+  open_index()?.search(terms, options, limit)
+}
@@ -0,0 +1,95 @@
+//! Fuzzy resolution of topic and insight names
+//!
+//! A mistyped `topic/name` is compared with every stored id by normalized
+//! Levenshtein distance, topic and name separately, so a typo in a short topic
+//! weighs as much as one in a long name. Scores run from 0 (nothing in common) to
+//! 1 (identical, ignoring case).
+
+/// Score a match must reach to be picked without asking
+pub const CONFIDENT_SCORE: f64 = 0.8;
+
+/// Score below which an id is not even suggested
+pub const SUGGEST_SCORE: f64 = 0.5;
+
+/// A stored insight close to the requested one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+  pub topic: String,
+  pub name: String,
+  pub score: f64,
+}
+
+/// Edit distance between two strings, counted in characters
+pub fn levenshtein(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut previous: Vec<usize> = (0..=b.len()).collect();
+  let mut current = vec![0; b.len() + 1];
+
+  for (i, ca) in a.chars().enumerate() {
+    current[0] = i + 1;
+    for (j, cb) in b.iter().enumerate() {
+      let substitution = previous[j] + usize::from(ca != *cb);
+      current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+    }
+    std::mem::swap(&mut previous, &mut current);
+  }
+  previous[b.len()]
+}
+
+/// Similarity of two names, ignoring case
+pub fn similarity(a: &str, b: &str) -> f64 {
+  let (a, b) = (a.to_lowercase(), b.to_lowercase());
+  let longest = a.chars().count().max(b.chars().count());
+  if longest == 0 {
+    return 1.0;
+  }
+  1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// Stored ids closest to `topic/name`, best first, at most `limit` of them
+pub fn closest<'a>(
+  topic: &str,
+  name: &str,
+  ids: impl IntoIterator<Item = (&'a str, &'a str)>,
+  limit: usize,
+) -> Vec<Match> {
+  let mut matches: Vec<Match> = ids
+    .into_iter()
+    .map(|(candidate_topic, candidate_name)| Match {
+      topic: candidate_topic.to_string(),
+      name: candidate_name.to_string(),
+      score: (similarity(topic, candidate_topic) + similarity(name, candidate_name)) / 2.0,
+    })
+    .filter(|m| m.score >= SUGGEST_SCORE)
+    .collect();
+  matches.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+  matches.truncate(limit);
+  matches
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_levenshtein() {
+    assert_eq!(levenshtein("", "abc"), 3);
+    assert_eq!(levenshtein("kitten", "sitting"), 3);
+    assert_eq!(levenshtein("async-pitfals", "async-pitfalls"), 1);
+    assert_eq!(similarity("Rust", "rust"), 1.0);
+  }
+
+  #[test]
+  fn test_closest_ranks_typos_first() {
+    let ids = [("rust", "async-pitfalls"), ("rust", "async-runtime"), ("go", "channels")];
+    let matches = closest("rust", "async-pitfals", ids, 5);
+
+    assert_eq!(matches[0].name, "async-pitfalls");
+    assert!(matches[0].score >= CONFIDENT_SCORE);
+    assert!(matches[1].score < CONFIDENT_SCORE);
+    assert!(matches.iter().all(|m| m.topic == "rust"));
+
+    let typo_in_topic = closest("rsut", "async-pitfalls", ids, 1);
+    assert_eq!(typo_in_topic[0].name, "async-pitfalls");
+  }
+}
@@ -0,0 +1,187 @@
+//! Version history for insights
+//!
+//! Each version is a full copy of the insight file under
+//! `<insights root>/.history/<topic>/<name>/v<N>.insight.md`. A snapshot is taken
+//! before and after every update, skipped when nothing changed since the latest
+//! version, so history starts the first time an insight is edited and still picks
+//! up edits made to the file by hand. Rolling back records a new version rather
+//! than discarding the ones after it.
+
+use anyhow::{anyhow, Result};
+use similar::TextDiff;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::server::models::insight::{self, Insight};
+
+/// Directory (relative to the insights root) holding version history
+pub const HISTORY_DIR: &str = ".history";
+
+/// One recorded state of an insight
+#[derive(Debug, Clone)]
+pub struct Version {
+  pub number: u32,
+  pub insight: Insight,
+}
+
+/// Accept `v3` as well as `3`
+pub fn parse_version(value: &str) -> std::result::Result<u32, String> {
+  let digits = value.strip_prefix(['v', 'V']).unwrap_or(value);
+  match digits.parse::<u32>() {
+    Ok(number) if number > 0 => Ok(number),
+    _ => Err(format!("'{value}' is not a version like v3")),
+  }
+}
+
+fn history_dir(topic: &str, name: &str) -> Result<PathBuf> {
+  Ok(
+    insight::get_insights_root()?
+      .join(HISTORY_DIR)
+      .join(topic.to_lowercase())
+      .join(name.to_lowercase()),
+  )
+}
+
+fn version_path(topic: &str, name: &str, number: u32) -> Result<PathBuf> {
+  Ok(history_dir(topic, name)?.join(format!("v{number}.insight.md")))
+}
+
+/// Recorded version numbers, oldest first
+fn version_numbers(topic: &str, name: &str) -> Result<Vec<u32>> {
+  let dir = history_dir(topic, name)?;
+  if !dir.exists() {
+    return Ok(Vec::new());
+  }
+
+  let mut numbers: Vec<u32> = fs::read_dir(dir)?
+    .flatten()
+    .filter_map(|entry| {
+      let file_name = entry.file_name();
+      let stem = file_name.to_str()?.strip_suffix(".insight.md")?;
+      stem.strip_prefix('v')?.parse().ok()
+    })
+    .collect();
+  numbers.sort_unstable();
+  Ok(numbers)
+}
+
+fn load_version_file(topic: &str, name: &str, number: u32) -> Result<Version> {
+  let path = version_path(topic, name, number)?;
+  if !path.exists() {
+    return Err(anyhow!("Version v{number} of {topic}/{name} not found"));
+  }
+  Ok(Version { number, insight: insight::load_from_path(&path)? })
+}
+
+fn same_content(a: &Insight, b: &Insight) -> bool {
+  a.overview == b.overview && a.details == b.details && a.tags == b.tags
+}
+
+/// Record the insight as a new version unless it matches the latest one
+///
+/// Returns the new version number, or None if nothing changed.
+pub fn snapshot(current: &Insight) -> Result<Option<u32>> {
+  let numbers = version_numbers(&current.topic, &current.name)?;
+  if let Some(&latest) = numbers.last() {
+    if same_content(&load_version_file(&current.topic, &current.name, latest)?.insight, current) {
+      return Ok(None);
+    }
+  }
+
+  let number = numbers.last().map_or(1, |latest| latest + 1);
+  let path = version_path(&current.topic, &current.name, number)?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(path, insight::to_markdown(current)?)?;
+  Ok(Some(number))
+}
+
+/// Every version of an insight, oldest first
+///
+/// An insight that was never edited has a single version: its current content.
+pub fn list(topic: &str, name: &str) -> Result<Vec<Version>> {
+  let current = insight::load(topic, name)?;
+  let numbers = version_numbers(topic, name)?;
+  if numbers.is_empty() {
+    return Ok(vec![Version { number: 1, insight: current }]);
+  }
+
+  numbers.into_iter().map(|number| load_version_file(topic, name, number)).collect()
+}
+
+/// A single version of an insight
+pub fn get(topic: &str, name: &str, number: u32) -> Result<Version> {
+  list(topic, name)?
+    .into_iter()
+    .find(|version| version.number == number)
+    .ok_or_else(|| anyhow!("Version v{number} of {topic}/{name} not found"))
+}
+
+/// The text compared between versions
+fn diff_text(insight: &Insight) -> String {
+  format!("# Overview\n{}\n\n# Details\n{}\n", insight.overview.trim(), insight.details.trim())
+}
+
+/// Unified diff between two versions (empty when their content is identical)
+pub fn diff(from: &Version, to: &Version) -> String {
+  let old = diff_text(&from.insight);
+  let new = diff_text(&to.insight);
+  TextDiff::from_lines(&old, &new)
+    .unified_diff()
+    .context_radius(3)
+    .header(&format!("v{}", from.number), &format!("v{}", to.number))
+    .to_string()
+}
+
+/// Restore an earlier version's content as the newest version
+///
+/// Returns the updated insight and the version number it was recorded as.
+pub fn rollback(topic: &str, name: &str, number: u32) -> Result<(Insight, u32)> {
+  let mut current = insight::load(topic, name)?;
+  // Keep hand edits made since the last recorded version
+  snapshot(&current)?;
+
+  let target = get(topic, name, number)?;
+  if same_content(&target.insight, &current) {
+    return Err(anyhow!("{topic}/{name} already matches v{number}"));
+  }
+
+  insight::update_with_tags(
+    &mut current,
+    Some(&target.insight.overview),
+    Some(&target.insight.details),
+    Some(&target.insight.tags),
+  )?;
+  let recorded = snapshot(&current)?.ok_or_else(|| anyhow!("Rollback did not change anything"))?;
+  Ok((current, recorded))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_version() {
+    assert_eq!(parse_version("v3"), Ok(3));
+    assert_eq!(parse_version("12"), Ok(12));
+    assert!(parse_version("v0").is_err());
+    assert!(parse_version("latest").is_err());
+  }
+
+  #[test]
+  fn test_diff_shows_changed_lines() {
+    let version = |number: u32, details: &str| Version {
+      number,
+      insight: Insight::new("t".into(), "n".into(), "Same overview".into(), details.into()),
+    };
+
+    let diff = diff(&version(2, "keep\nold line"), &version(4, "keep\nnew line"));
+    assert!(diff.starts_with("--- v2\n+++ v4\n"));
+    assert!(diff.contains("-old line\n"));
+    assert!(diff.contains("+new line\n"));
+    assert!(diff.contains(" keep\n"));
+
+    assert!(super::diff(&version(1, "same"), &version(2, "same")).is_empty());
+  }
+}
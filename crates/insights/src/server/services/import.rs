@@ -0,0 +1,132 @@
+//! Bulk import of insights
+//!
+//! Entries are validated as a whole before anything touches disk, so a bad file
+//! never leaves the knowledge base half-imported.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+
+use crate::server::models::insight::{self, Insight};
+use crate::server::models::{tag, topic};
+use crate::server::services::history;
+use crate::server::types::{ConflictPolicy, ImportEntry};
+
+/// What happened to each entry of an import
+#[derive(Debug, Default)]
+pub struct ImportOutcome {
+  pub added: Vec<String>,
+  pub merged: Vec<String>,
+  pub skipped: Vec<String>,
+  /// Insights written to disk, still waiting for embeddings
+  pub written: Vec<Insight>,
+}
+
+/// Problems with the entries themselves, one message per problem
+pub fn validate_entries(entries: &[ImportEntry]) -> Vec<String> {
+  let mut problems = Vec::new();
+  let mut seen = HashSet::new();
+
+  for (index, entry) in entries.iter().enumerate() {
+    let label = format!("entry {} ({}/{})", index + 1, entry.topic, entry.name);
+
+    if let Some(problem) = topic::invalid(&entry.topic) {
+      problems.push(format!("{label}: {problem}"));
+    }
+    if let Some(problem) = invalid_segment("name", &entry.name) {
+      problems.push(format!("{label}: {problem}"));
+    }
+    if entry.overview.trim().is_empty() {
+      problems.push(format!("{label}: overview is empty"));
+    }
+    for problem in tag::problems(&entry.tags) {
+      problems.push(format!("{label}: {problem}"));
+    }
+
+    let id = (entry.topic.to_lowercase(), entry.name.to_lowercase());
+    if !seen.insert(id) {
+      problems.push(format!("{label}: appears more than once in the import"));
+    }
+  }
+
+  problems
+}
+
+/// `topic/name` of every entry that already exists on disk
+pub fn existing_entries(entries: &[ImportEntry]) -> Result<Vec<String>> {
+  let mut existing = Vec::new();
+  for entry in entries {
+    if exists(entry)? {
+      existing.push(id(entry));
+    }
+  }
+  Ok(existing)
+}
+
+/// Write every entry to disk according to the conflict policy
+///
+/// Embeddings are left to the caller so they can be computed in one pass.
+pub fn import(entries: Vec<ImportEntry>, on_conflict: ConflictPolicy) -> Result<ImportOutcome> {
+  if on_conflict == ConflictPolicy::Fail {
+    let existing = existing_entries(&entries)?;
+    if !existing.is_empty() {
+      return Err(anyhow!("Insights already exist: {}", existing.join(", ")));
+    }
+  }
+
+  let mut outcome = ImportOutcome::default();
+
+  for entry in entries {
+    let id = id(&entry);
+
+    if !exists(&entry)? {
+      let mut new_insight = Insight::new(entry.topic, entry.name, entry.overview, entry.details);
+      new_insight.tags = tag::normalize_all(&entry.tags);
+      insight::save(&new_insight)?;
+      outcome.added.push(id);
+      outcome.written.push(new_insight);
+      continue;
+    }
+
+    match on_conflict {
+      ConflictPolicy::Skip | ConflictPolicy::Fail => outcome.skipped.push(id),
+      ConflictPolicy::Merge => {
+        let mut existing = insight::load(&entry.topic, &entry.name)?;
+        history::snapshot(&existing)?;
+        // Merging keeps the tags already there and adds the imported ones
+        let tags = [existing.tags.clone(), entry.tags].concat();
+        insight::update_with_tags(
+          &mut existing,
+          Some(&entry.overview),
+          Some(&entry.details),
+          Some(&tags),
+        )?;
+        history::snapshot(&existing)?;
+        outcome.merged.push(id);
+        outcome.written.push(existing);
+      }
+    }
+  }
+
+  Ok(outcome)
+}
+
+fn exists(entry: &ImportEntry) -> Result<bool> {
+  let candidate =
+    Insight::new(entry.topic.clone(), entry.name.clone(), String::new(), String::new());
+  Ok(insight::file_path(&candidate)?.exists())
+}
+
+fn id(entry: &ImportEntry) -> String {
+  format!("{}/{}", entry.topic, entry.name)
+}
+
+/// Names become file names, so they must be plain names
+pub(crate) fn invalid_segment(field: &str, value: &str) -> Option<String> {
+  if value.trim().is_empty() {
+    Some(format!("{field} is empty"))
+  } else if value.contains(['/', '\\']) || value == "." || value == ".." {
+    Some(format!("{field} '{value}' is not a valid file name"))
+  } else {
+    None
+  }
+}
@@ -0,0 +1,279 @@
+//! Resource limits for background indexing
+//!
+//! Embedding a large batch of insights keeps every core busy for minutes. These
+//! limits keep that work out of the way: the ONNX runtime can be capped to a
+//! number of threads, the server can lower its own scheduling priority, and
+//! background re-indexing (the watcher, full re-indexes and imports) can be
+//! paused, or held back until the machine is on AC power and otherwise idle.
+//! Embeddings computed while answering a request are never held back.
+
+use bentley::config;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::server::middleware::server_info;
+use crate::settings;
+
+/// How often held-back indexing checks whether it may continue
+const WAIT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default load average per core under which the machine counts as idle
+const DEFAULT_IDLE_LOAD: f64 = 0.5;
+
+/// Lowest priority a process can ask for
+const MAX_NICE: i32 = 19;
+
+#[cfg(not(target_os = "macos"))]
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Set while background indexing is paused
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Conditions background indexing waits for
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SchedulePolicy {
+  /// Only index while running on mains power
+  pub require_ac_power: bool,
+  /// Only index while the load average per core is below `idle_load`
+  pub require_idle: bool,
+  pub idle_load: f64,
+}
+
+impl SchedulePolicy {
+  /// Parse a comma-separated list of `ac-power` and `idle` (or `always`)
+  pub fn parse(value: &str, idle_load: f64) -> Result<Self, String> {
+    let mut policy = SchedulePolicy { idle_load, ..Default::default() };
+    for condition in value.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+      match condition.to_lowercase().as_str() {
+        "always" => {}
+        "ac-power" | "ac" => policy.require_ac_power = true,
+        "idle" => policy.require_idle = true,
+        other => return Err(format!("unknown indexing schedule condition '{other}'")),
+      }
+    }
+    Ok(policy)
+  }
+
+  /// Why indexing should wait, given the current machine state
+  pub fn hold_reason(&self, on_ac_power: bool, load_per_core: Option<f64>) -> Option<String> {
+    if self.require_ac_power && !on_ac_power {
+      return Some("running on battery".to_string());
+    }
+    match load_per_core {
+      Some(load) if self.require_idle && load >= self.idle_load => {
+        Some(format!("system busy (load {load:.2} per core)"))
+      }
+      _ => None,
+    }
+  }
+}
+
+/// Get the configured cap on embedding threads
+/// Default: none (the ONNX runtime uses every core)
+/// Setting: insights.index.threads (INSIGHTS_INDEX_THREADS)
+pub fn get_max_threads() -> Option<usize> {
+  config::get(&settings::INDEX_THREADS).filter(|n| *n > 0)
+}
+
+/// Get the configured niceness for the server process
+/// Default: none (the priority it was started with)
+/// Setting: insights.index.nice (INSIGHTS_INDEX_NICE)
+pub fn get_nice() -> Option<i32> {
+  config::get(&settings::INDEX_NICE).filter(|n| *n > 0).map(|n: i32| n.min(MAX_NICE))
+}
+
+/// Get the configured background indexing schedule
+/// Default: always
+/// Setting: insights.index.schedule (INSIGHTS_INDEX_SCHEDULE, comma-separated `ac-power`,
+/// `idle`), insights.index.idle_load (INSIGHTS_INDEX_IDLE_LOAD, load average per core,
+/// default 0.5)
+pub fn get_schedule() -> SchedulePolicy {
+  let idle_load = config::get(&settings::INDEX_IDLE_LOAD)
+    .filter(|load: &f64| *load > 0.0)
+    .unwrap_or(DEFAULT_IDLE_LOAD);
+  let value = config::var(&settings::INDEX_SCHEDULE).unwrap_or_default();
+  SchedulePolicy::parse(&value, idle_load).unwrap_or_else(|e| {
+    bentley::warn!(&format!("Ignoring invalid INSIGHTS_INDEX_SCHEDULE: {e}"));
+    SchedulePolicy { idle_load, ..Default::default() }
+  })
+}
+
+pub fn pause() {
+  PAUSED.store(true, Ordering::Relaxed);
+}
+
+pub fn resume() {
+  PAUSED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_paused() -> bool {
+  PAUSED.load(Ordering::Relaxed)
+}
+
+/// Whether the machine is running on mains power
+///
+/// Machines without a battery (or platforms we cannot query) count as plugged in.
+pub fn on_ac_power() -> bool {
+  #[cfg(target_os = "macos")]
+  {
+    std::process::Command::new("pmset")
+      .args(["-g", "batt"])
+      .output()
+      .map(|output| !String::from_utf8_lossy(&output.stdout).contains("Battery Power"))
+      .unwrap_or(true)
+  }
+
+  #[cfg(not(target_os = "macos"))]
+  {
+    on_ac_power_in(Path::new(POWER_SUPPLY_DIR))
+  }
+}
+
+/// Read the Linux power supply class: on AC unless a battery exists and no mains supply is online
+pub fn on_ac_power_in(dir: &Path) -> bool {
+  let Ok(entries) = std::fs::read_dir(dir) else {
+    return true;
+  };
+
+  let read = |path: &Path, file: &str| {
+    std::fs::read_to_string(path.join(file)).map(|s| s.trim().to_string()).unwrap_or_default()
+  };
+
+  let mut has_battery = false;
+  for entry in entries.flatten() {
+    let path = entry.path();
+    match read(&path, "type").as_str() {
+      "Mains" | "USB" if read(&path, "online") == "1" => return true,
+      "Battery" => has_battery = true,
+      _ => {}
+    }
+  }
+  !has_battery
+}
+
+/// One-minute load average divided by the number of cores
+pub fn load_per_core() -> Option<f64> {
+  #[cfg(target_os = "macos")]
+  let load = {
+    let output = std::process::Command::new("sysctl").args(["-n", "vm.loadavg"]).output().ok()?;
+    parse_load_average(String::from_utf8_lossy(&output.stdout).trim_start_matches(['{', ' ']))?
+  };
+
+  #[cfg(not(target_os = "macos"))]
+  let load = parse_load_average(&std::fs::read_to_string("/proc/loadavg").ok()?)?;
+
+  let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+  Some(load / cores as f64)
+}
+
+/// First field of a load average line such as `/proc/loadavg`
+pub fn parse_load_average(line: &str) -> Option<f64> {
+  line.split_whitespace().next()?.parse().ok()
+}
+
+/// Why background indexing is currently held back, if it is
+pub fn hold_reason(policy: &SchedulePolicy) -> Option<String> {
+  if is_paused() {
+    return Some("paused".to_string());
+  }
+  if !policy.require_ac_power && !policy.require_idle {
+    return None;
+  }
+  policy.hold_reason(on_ac_power(), load_per_core())
+}
+
+/// Wait until background indexing is allowed to run
+pub async fn wait_for_turn() {
+  let policy = get_schedule();
+  let Some(reason) = hold_reason(&policy) else {
+    return;
+  };
+
+  server_info(&format!("Background indexing waiting: {reason}"), "insights-indexing").await;
+  loop {
+    tokio::time::sleep(WAIT_INTERVAL).await;
+    if hold_reason(&policy).is_none() {
+      break;
+    }
+  }
+  server_info("Background indexing continuing", "insights-indexing").await;
+}
+
+/// Lower the scheduling priority of the whole server process
+#[cfg(unix)]
+pub fn apply_nice(nice: i32) -> anyhow::Result<()> {
+  let output = std::process::Command::new("renice")
+    .args(["-n", &nice.to_string(), "-p", &std::process::id().to_string()])
+    .output()?;
+  if !output.status.success() {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    return Err(anyhow::anyhow!("renice failed: {}", stderr.trim()));
+  }
+  Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn apply_nice(_nice: i32) -> anyhow::Result<()> {
+  Err(anyhow::anyhow!("lowering the server priority is only supported on Unix"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs;
+
+  fn supply(dir: &Path, name: &str, kind: &str, online: Option<&str>) {
+    let path = dir.join(name);
+    fs::create_dir_all(&path).unwrap();
+    fs::write(path.join("type"), format!("{kind}\n")).unwrap();
+    if let Some(online) = online {
+      fs::write(path.join("online"), format!("{online}\n")).unwrap();
+    }
+  }
+
+  #[test]
+  fn test_parse_schedule() {
+    assert_eq!(
+      SchedulePolicy::parse("", 0.5).unwrap(),
+      SchedulePolicy { idle_load: 0.5, ..Default::default() }
+    );
+    let both = SchedulePolicy::parse("ac-power, IDLE", 0.5).unwrap();
+    assert!(both.require_ac_power && both.require_idle);
+    assert!(SchedulePolicy::parse("weekends", 0.5).is_err());
+  }
+
+  #[test]
+  fn test_hold_reason_checks_only_required_conditions() {
+    let always = SchedulePolicy::parse("always", 0.5).unwrap();
+    assert_eq!(always.hold_reason(false, Some(4.0)), None);
+
+    let policy = SchedulePolicy::parse("ac-power,idle", 0.5).unwrap();
+    assert_eq!(policy.hold_reason(false, Some(0.1)).as_deref(), Some("running on battery"));
+    assert!(policy.hold_reason(true, Some(0.75)).unwrap().contains("0.75"));
+    assert_eq!(policy.hold_reason(true, Some(0.2)), None);
+    // An unknown load never blocks indexing forever
+    assert_eq!(policy.hold_reason(true, None), None);
+  }
+
+  #[test]
+  fn test_on_ac_power_reads_power_supplies() {
+    let desktop = tempfile::TempDir::new().unwrap();
+    assert!(on_ac_power_in(desktop.path()));
+
+    let laptop = tempfile::TempDir::new().unwrap();
+    supply(laptop.path(), "BAT0", "Battery", None);
+    supply(laptop.path(), "AC", "Mains", Some("0"));
+    assert!(!on_ac_power_in(laptop.path()));
+
+    supply(laptop.path(), "AC", "Mains", Some("1"));
+    assert!(on_ac_power_in(laptop.path()));
+  }
+
+  #[test]
+  fn test_parse_load_average() {
+    assert_eq!(parse_load_average("0.42 0.30 0.25 1/523 12345\n"), Some(0.42));
+    assert_eq!(parse_load_average("1.50 1.20 0.90 }"), Some(1.5));
+    assert_eq!(parse_load_average(""), None);
+  }
+}
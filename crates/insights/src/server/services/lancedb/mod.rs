@@ -12,57 +12,89 @@ pub mod vector_database;
 
 use anyhow::{anyhow, Result};
 use chrono::Utc;
+use futures::future::try_join_all;
+use futures::stream::TryStreamExt;
+use lancedb::query::ExecutableQuery;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::sync::RwLock;
 
 use crate::server::models::insight;
+use crate::server::models::sharding::{self, ShardConfig};
+use crate::server::services::sharding::{merge_top_k, ShardStats};
 use connection::create_connection;
+use records::arrow_batch_to_records;
 use search::search_similar_embeddings;
 use table_manager::TableManager;
 
+/// Column added after the first release of the schema
+const TAGS_COLUMN: &str = "tags";
+
 // Re-export commonly used types for external use
 pub use models::{EmbeddingSearchResult, InsightRecord};
 pub use vector_database::LanceDbVectorDatabase;
 
 /// LanceDB service for vector operations
 pub struct LanceDbService {
-  table_manager: TableManager,
+  connection: lancedb::Connection,
+  shard_config: RwLock<ShardConfig>,
 }
 
 impl LanceDbService {
-  /// Create a new LanceDB service
-  pub async fn new(data_dir: PathBuf, table_name: &str) -> Result<Self> {
+  /// Create a new LanceDB service using the given shard layout
+  pub async fn new(data_dir: PathBuf, shard_config: ShardConfig) -> Result<Self> {
     let connection = create_connection(data_dir).await?;
-    let table_manager = TableManager::new(connection, table_name.to_string());
 
-    Ok(Self { table_manager })
+    Ok(Self { connection, shard_config: RwLock::new(shard_config) })
   }
 
   /// Store an insight's embedding in LanceDB
   pub async fn store_embedding(&self, insight: &insight::Insight) -> Result<()> {
     let embedding = validate_insight_has_embedding(insight)?;
     let record = create_insight_record(insight, embedding);
-    store_record_appropriately(&self.table_manager, &record).await
+    let table_manager = self.table_for(&insight.topic, &insight.name);
+    store_record_appropriately(&table_manager, &record).await
   }
 
-  /// Check if any embeddings exist in the database
+  /// Check if any shard holds embeddings
   pub async fn has_embeddings(&self) -> Result<bool> {
-    self.table_manager.has_embeddings().await
+    for table_manager in self.shard_tables().await? {
+      if table_manager.has_embeddings().await? {
+        return Ok(true);
+      }
+    }
+    Ok(false)
   }
 
-  /// Search for similar embeddings
+  /// Search every shard and merge the per-shard top-K results
   pub async fn search_similar(
     &self,
     query_embedding: &[f32],
     limit: usize,
     threshold: Option<f32>,
+    tags: &[String],
   ) -> Result<Vec<models::EmbeddingSearchResult>> {
-    let table = self.table_manager.get_table().await?;
-    search_similar_embeddings(&table, query_embedding, limit, threshold).await
+    let shards = self.shard_tables().await?;
+    let searches = shards.iter().map(|table_manager| async move {
+      // Shards written before tags existed can't hold a tagged row
+      if !tags.is_empty() && !table_manager.has_column(TAGS_COLUMN).await? {
+        return Ok(Vec::new());
+      }
+      let table = table_manager.get_table().await?;
+      search_similar_embeddings(&table, query_embedding, limit, threshold, tags).await
+    });
+
+    let per_shard = try_join_all(searches).await?;
+    Ok(merge_top_k(per_shard, limit, |result| result.similarity))
   }
 
-  /// Delete an insight's embedding
+  /// Delete an insight's embedding from whichever shard holds it
   pub async fn delete_embedding(&self, topic: &str, name: &str) -> Result<()> {
-    self.table_manager.delete_embedding(topic, name).await
+    // Rows may still sit in a previous layout's shard until the next rebalance
+    for table_manager in self.shard_tables().await? {
+      table_manager.delete_embedding(topic, name).await?;
+    }
+    Ok(())
   }
 
   /// Update an insight's embedding
@@ -80,15 +112,118 @@ impl LanceDbService {
     Ok(Vec::new())
   }
 
-  /// Clear all embeddings from the table
+  /// Clear all embeddings from every shard
   pub async fn clear_all_embeddings(&self) -> Result<()> {
-    execute_table_clear(&self.table_manager).await
+    for table_manager in self.shard_tables().await? {
+      execute_table_clear(&table_manager).await?;
+    }
+    Ok(())
   }
 
   /// Reshape the database with fresh schema (clean slate approach)
   pub async fn reshape_database(&self, embedding_dimension: usize) -> Result<()> {
-    recreate_database_directory(&self.table_manager, embedding_dimension).await
+    recreate_database_directory(&self.connection, embedding_dimension).await
+  }
+
+  /// Row counts for every shard table
+  pub async fn shard_stats(&self) -> Result<Vec<ShardStats>> {
+    let mut stats = Vec::new();
+    for table_manager in self.shard_tables().await? {
+      let rows = table_manager.get_table().await?.count_rows(None).await?;
+      stats.push(ShardStats { table: table_manager.table_name().to_string(), rows });
+    }
+    Ok(stats)
+  }
+
+  /// Stored embeddings for one topic, keyed by lowercased insight name
+  pub async fn topic_embeddings(&self, topic: &str) -> Result<HashMap<String, Vec<f32>>> {
+    let mut embeddings = HashMap::new();
+    for table_manager in self.shard_tables().await? {
+      for record in read_all_records(&table_manager).await? {
+        if record.topic.eq_ignore_ascii_case(topic) {
+          embeddings.insert(record.name.to_lowercase(), record.embedding);
+        }
+      }
+    }
+    Ok(embeddings)
+  }
+
+  /// Switch to a new shard layout and move every row into its new shard
+  ///
+  /// Returns the number of rows that changed tables.
+  pub async fn rebalance(&self, config: ShardConfig) -> Result<usize> {
+    let mut placements: BTreeMap<String, Vec<InsightRecord>> = BTreeMap::new();
+    let mut moved = 0;
+
+    for table_manager in self.shard_tables().await? {
+      for record in read_all_records(&table_manager).await? {
+        let target = config.table_for(&record.topic, &record.name);
+        if target != table_manager.table_name() {
+          moved += 1;
+        }
+        placements.entry(target).or_default().push(record);
+      }
+    }
+
+    // Drop the old layout before writing so no row is ever stored twice; if a write
+    // fails part way the index can always be rebuilt from the insight files
+    for table_manager in self.shard_tables().await? {
+      table_manager.drop_table().await?;
+    }
+
+    for (table, records) in placements {
+      TableManager::new(self.connection.clone(), table).create_table_with_records(records).await?;
+    }
+
+    *self.shard_config.write().map_err(|_| anyhow!("Shard config lock poisoned"))? = config;
+    bentley::info!(&format!("Rebalanced embeddings index, {moved} rows moved"));
+    Ok(moved)
+  }
+
+  /// Table manager for the shard an insight belongs in under the current layout
+  fn table_for(&self, topic: &str, name: &str) -> TableManager {
+    let table = match self.shard_config.read() {
+      Ok(config) => config.table_for(topic, name),
+      Err(poisoned) => poisoned.into_inner().table_for(topic, name),
+    };
+    TableManager::new(self.connection.clone(), table)
   }
+
+  /// Table managers for every existing shard, whatever layout created it
+  async fn shard_tables(&self) -> Result<Vec<TableManager>> {
+    let tables = self
+      .connection
+      .table_names()
+      .execute()
+      .await
+      .map_err(|e| anyhow!("Failed to list tables: {}", e))?;
+
+    Ok(
+      tables
+        .into_iter()
+        .filter(|table| sharding::is_shard_table(table))
+        .map(|table| TableManager::new(self.connection.clone(), table))
+        .collect(),
+    )
+  }
+}
+
+/// Read every row of a shard back into records
+async fn read_all_records(table_manager: &TableManager) -> Result<Vec<InsightRecord>> {
+  let table = table_manager.get_table().await?;
+  let batches: Vec<_> = table
+    .query()
+    .execute()
+    .await
+    .map_err(|e| anyhow!("Failed to read table '{}': {}", table_manager.table_name(), e))?
+    .try_collect()
+    .await?;
+
+  let mut records = Vec::new();
+  for batch in &batches {
+    records.extend(arrow_batch_to_records(batch)?);
+  }
+  Ok(records)
 }
 
 /// Validate that insight has an embedding
@@ -106,6 +241,7 @@ fn create_insight_record(insight: &insight::Insight, embedding: &[f32]) -> model
     insight.name.clone(),
     insight.overview.clone(),
     insight.details.clone(),
+    insight.tags.clone(),
     embedding.to_vec(),
     created_timestamp,
     updated_timestamp,
@@ -118,12 +254,28 @@ async fn store_record_appropriately(
   record: &models::InsightRecord,
 ) -> Result<()> {
   if table_manager.table_exists().await? {
+    if !table_manager.has_column(TAGS_COLUMN).await? {
+      return migrate_table_with_record(table_manager, record).await;
+    }
     table_manager.add_record_to_existing_table(record).await
   } else {
     table_manager.create_table_with_first_record(record).await
   }
 }
 
+/// Rewrite a table from before tags existed in the current schema, adding the record
+async fn migrate_table_with_record(
+  table_manager: &TableManager,
+  record: &models::InsightRecord,
+) -> Result<()> {
+  let mut records = read_all_records(table_manager).await?;
+  records.push(record.clone());
+  table_manager.drop_table().await?;
+  table_manager.create_table_with_records(records).await?;
+  bentley::info!(&format!("Added tags to embeddings table '{}'", table_manager.table_name()));
+  Ok(())
+}
+
 /// Execute table clear operation
 async fn execute_table_clear(table_manager: &TableManager) -> Result<()> {
   if table_manager.table_exists().await? {
@@ -141,11 +293,10 @@ fn extract_created_timestamp(insight: &insight::Insight) -> String {
 
 /// Completely recreate the database directory for clean slate approach
 async fn recreate_database_directory(
-  table_manager: &TableManager,
+  connection: &lancedb::Connection,
   embedding_dimension: usize,
 ) -> Result<()> {
   // Delete the entire database directory to ensure clean schema
-  let connection = &table_manager.connection;
   let db_path = get_database_path_from_connection(connection).await?;
 
   bentley::info!(&format!(
@@ -10,6 +10,7 @@ pub struct InsightRecord {
   pub name: String,
   pub overview: String,
   pub details: String,
+  pub tags: Vec<String>,
   pub embedding: Vec<f32>,
   pub created_at: String,
   pub updated_at: String,
@@ -21,15 +22,38 @@ impl InsightRecord {
     name: String,
     overview: String,
     details: String,
+    tags: Vec<String>,
     embedding: Vec<f32>,
     created_at: String,
     updated_at: String,
   ) -> Self {
     let id = format!("{topic}:{name}");
-    Self { id, topic, name, overview, details, embedding, created_at, updated_at }
+    Self { id, topic, name, overview, details, tags, embedding, created_at, updated_at }
   }
 }
 
+/// Tags as stored in the `tags` column: `,rust,async,`
+///
+/// The surrounding commas let a filter match whole tags with `LIKE '%,rust,%'`.
+pub fn encode_tags(tags: &[String]) -> String {
+  if tags.is_empty() {
+    String::new()
+  } else {
+    format!(",{},", tags.join(","))
+  }
+}
+
+/// Tags read back from the `tags` column
+pub fn decode_tags(stored: &str) -> Vec<String> {
+  stored.split(',').filter(|tag| !tag.is_empty()).map(str::to_string).collect()
+}
+
+/// SQL filter matching rows that carry every one of the tags
+pub fn tags_filter(tags: &[String]) -> Option<String> {
+  let clauses: Vec<String> = tags.iter().map(|tag| format!("tags LIKE '%,{tag},%'")).collect();
+  (!clauses.is_empty()).then(|| clauses.join(" AND "))
+}
+
 /// Result of an embedding similarity search
 #[derive(Debug, Clone)]
 pub struct EmbeddingSearchResult {
@@ -38,5 +62,6 @@ pub struct EmbeddingSearchResult {
   pub name: String,
   pub overview: String,
   pub details: String,
+  pub tags: Vec<String>,
   pub similarity: f32,
 }
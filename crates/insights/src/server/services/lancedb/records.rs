@@ -1,13 +1,13 @@
 //! Arrow RecordBatch conversion utilities for LanceDB
 
 use anyhow::{anyhow, Result};
-use arrow::array::{Array, Float32Array, StringArray};
+use arrow::array::{Array, FixedSizeListArray, Float32Array, StringArray};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
 use std::sync::Arc;
 
 use super::get_schema_dimension;
-use super::models::InsightRecord;
+use super::models::{decode_tags, encode_tags, InsightRecord};
 
 /// Convert InsightRecord to Arrow RecordBatch
 pub fn records_to_arrow_batch(records: Vec<InsightRecord>) -> Result<RecordBatch> {
@@ -20,6 +20,53 @@ pub fn records_to_arrow_batch(records: Vec<InsightRecord>) -> Result<RecordBatch
   assemble_record_batch(schema, string_arrays, embedding_array)
 }
 
+/// Convert an Arrow RecordBatch read from a table back into InsightRecords
+pub fn arrow_batch_to_records(batch: &RecordBatch) -> Result<Vec<InsightRecord>> {
+  let id = string_column(batch, "id")?;
+  let topic = string_column(batch, "topic")?;
+  let name = string_column(batch, "name")?;
+  let overview = string_column(batch, "overview")?;
+  let details = string_column(batch, "details")?;
+  let created_at = string_column(batch, "created_at")?;
+  let updated_at = string_column(batch, "updated_at")?;
+  // Tables written before tags existed have no tags column
+  let tags = string_column(batch, "tags").ok();
+  let embedding = batch
+    .column_by_name("embedding")
+    .and_then(|col| col.as_any().downcast_ref::<FixedSizeListArray>())
+    .ok_or_else(|| anyhow!("Missing or malformed 'embedding' column"))?;
+
+  (0..batch.num_rows())
+    .map(|row| {
+      let values = embedding.value(row);
+      let values = values
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .ok_or_else(|| anyhow!("Embedding values are not Float32"))?;
+
+      Ok(InsightRecord {
+        id: id.value(row).to_string(),
+        topic: topic.value(row).to_string(),
+        name: name.value(row).to_string(),
+        overview: overview.value(row).to_string(),
+        details: details.value(row).to_string(),
+        tags: tags.map(|tags| decode_tags(tags.value(row))).unwrap_or_default(),
+        embedding: values.values().to_vec(),
+        created_at: created_at.value(row).to_string(),
+        updated_at: updated_at.value(row).to_string(),
+      })
+    })
+    .collect()
+}
+
+/// Look up a string column by name
+fn string_column<'a>(batch: &'a RecordBatch, column_name: &str) -> Result<&'a StringArray> {
+  batch
+    .column_by_name(column_name)
+    .and_then(|col| col.as_any().downcast_ref::<StringArray>())
+    .ok_or_else(|| anyhow!("Missing or malformed '{}' column", column_name))
+}
+
 /// Validate that records vector is not empty
 fn validate_records_not_empty(records: &[InsightRecord]) -> Result<()> {
   if records.is_empty() {
@@ -47,6 +94,7 @@ fn create_insight_record_schema() -> Arc<Schema> {
     ),
     Field::new("created_at", DataType::Utf8, false),
     Field::new("updated_at", DataType::Utf8, false),
+    Field::new("tags", DataType::Utf8, false),
   ]))
 }
 
@@ -59,6 +107,7 @@ struct RecordStringArrays {
   details_array: StringArray,
   created_at_array: StringArray,
   updated_at_array: StringArray,
+  tags_array: StringArray,
 }
 
 /// Create string arrays from insight records
@@ -71,6 +120,9 @@ fn create_string_arrays_from_records(records: &[InsightRecord]) -> RecordStringA
     details_array: extract_string_field(records, |r| &r.details),
     created_at_array: extract_string_field(records, |r| &r.created_at),
     updated_at_array: extract_string_field(records, |r| &r.updated_at),
+    tags_array: StringArray::from(
+      records.iter().map(|r| Some(encode_tags(&r.tags))).collect::<Vec<_>>(),
+    ),
   }
 }
 
@@ -139,5 +191,6 @@ fn prepare_column_arrays(
     Arc::new(embedding_array),
     Arc::new(string_arrays.created_at_array),
     Arc::new(string_arrays.updated_at_array),
+    Arc::new(string_arrays.tags_array),
   ]
 }
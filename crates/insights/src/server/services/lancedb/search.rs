@@ -7,16 +7,20 @@ use futures::stream::StreamExt;
 use lancedb::query::{ExecutableQuery, QueryBase};
 use lancedb::Table;
 
-use super::models::EmbeddingSearchResult;
+use super::models::{decode_tags, tags_filter, EmbeddingSearchResult};
 
 /// Perform vector search and return processed results
+///
+/// Only rows carrying every one of `tags` are considered.
 pub async fn search_similar_embeddings(
   table: &Table,
   query_embedding: &[f32],
   limit: usize,
   threshold: Option<f32>,
+  tags: &[String],
 ) -> Result<Vec<EmbeddingSearchResult>> {
-  let mut results_stream = create_search_query(table, query_embedding, limit, threshold).await?;
+  let mut results_stream =
+    create_search_query(table, query_embedding, limit, threshold, tags).await?;
   let search_results = process_all_batches(&mut results_stream, threshold).await?;
 
   // Only log search results for debugging purposes
@@ -32,8 +36,13 @@ async fn create_search_query<'a>(
   query_embedding: &[f32],
   limit: usize,
   _threshold: Option<f32>,
+  tags: &[String],
 ) -> Result<impl futures::stream::Stream<Item = Result<RecordBatch, lancedb::Error>> + 'a> {
-  let query = table.vector_search(query_embedding)?.column("embedding").limit(limit);
+  let mut query = table.vector_search(query_embedding)?.column("embedding").limit(limit);
+  // Filtered before the nearest neighbours are picked, so the limit still applies in full
+  if let Some(filter) = tags_filter(tags) {
+    query = query.only_if(filter);
+  }
 
   // Skip verbose threshold logging to reduce noise
 
@@ -86,6 +95,7 @@ struct BatchColumnArrays<'a> {
   name_array: &'a StringArray,
   overview_array: &'a StringArray,
   details_array: &'a StringArray,
+  tags_array: Option<&'a StringArray>,
   distance_array: Option<&'a Float32Array>,
 }
 
@@ -96,6 +106,7 @@ fn extract_column_arrays_from_batch(batch: &RecordBatch) -> Result<BatchColumnAr
   let name_array = extract_string_column(batch, "name")?;
   let overview_array = extract_string_column(batch, "overview")?;
   let details_array = extract_string_column(batch, "details")?;
+  let tags_array = extract_string_column(batch, "tags").ok();
   let distance_array = extract_distance_column(batch);
 
   Ok(BatchColumnArrays {
@@ -104,6 +115,7 @@ fn extract_column_arrays_from_batch(batch: &RecordBatch) -> Result<BatchColumnAr
     name_array,
     overview_array,
     details_array,
+    tags_array,
     distance_array,
   })
 }
@@ -166,6 +178,10 @@ fn create_search_result_from_arrays(
     name: column_arrays.name_array.value(row_index).to_string(),
     overview: column_arrays.overview_array.value(row_index).to_string(),
     details: column_arrays.details_array.value(row_index).to_string(),
+    tags: column_arrays
+      .tags_array
+      .map(|tags| decode_tags(tags.value(row_index)))
+      .unwrap_or_default(),
     similarity,
   }
 }
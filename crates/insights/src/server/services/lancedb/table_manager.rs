@@ -18,6 +18,11 @@ impl TableManager {
     Self { connection, table_name }
   }
 
+  /// Name of the managed table
+  pub fn table_name(&self) -> &str {
+    &self.table_name
+  }
+
   /// Check if the target table exists
   pub async fn table_exists(&self) -> Result<bool> {
     check_if_table_exists(&self.connection, &self.table_name).await
@@ -43,6 +48,33 @@ impl TableManager {
     Ok(())
   }
 
+  /// Create a new table holding the given records
+  pub async fn create_table_with_records(&self, records: Vec<InsightRecord>) -> Result<()> {
+    let count = records.len();
+    let batch = records_to_arrow_batch(records)?;
+    let schema = batch.schema();
+    let batch_iter = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema);
+
+    self
+      .connection
+      .create_table(&self.table_name, batch_iter)
+      .execute()
+      .await
+      .map_err(|e| anyhow!("Failed to create table '{}': {}", self.table_name, e))?;
+
+    bentley::info!(&format!("Created table '{}' with {count} records", self.table_name));
+    Ok(())
+  }
+
+  /// Drop the table and all of its rows
+  pub async fn drop_table(&self) -> Result<()> {
+    self
+      .connection
+      .drop_table(&self.table_name, &[])
+      .await
+      .map_err(|e| anyhow!("Failed to drop table '{}': {}", self.table_name, e))
+  }
+
   /// Add a record to an existing table
   pub async fn add_record_to_existing_table(&self, record: &InsightRecord) -> Result<()> {
     let batch_iter = prepare_record_batch_iterator(record)?;
@@ -58,6 +90,12 @@ impl TableManager {
     Ok(())
   }
 
+  /// Whether the table has a column, so tables from older schemas can be told apart
+  pub async fn has_column(&self, column: &str) -> Result<bool> {
+    let schema = self.get_table().await?.schema().await?;
+    Ok(schema.field_with_name(column).is_ok())
+  }
+
   /// Check if any embeddings exist in the database
   pub async fn has_embeddings(&self) -> Result<bool> {
     check_embeddings_exist(&self.connection, &self.table_name).await
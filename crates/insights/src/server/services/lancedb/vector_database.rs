@@ -5,10 +5,13 @@
 
 use anyhow::Result;
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::server::models::insight;
+use crate::server::models::sharding::ShardConfig;
 use crate::server::services::lancedb::LanceDbService;
+use crate::server::services::sharding::ShardStats;
 use crate::server::services::vector_database::{VectorDatabase, VectorSearchResult};
 
 /// LanceDB implementation of the VectorDatabase trait
@@ -18,8 +21,8 @@ pub struct LanceDbVectorDatabase {
 
 impl LanceDbVectorDatabase {
   /// Create a new LanceDB vector database instance
-  pub async fn new(data_dir: PathBuf, table_name: &str) -> Result<Self> {
-    let service = LanceDbService::new(data_dir, table_name).await?;
+  pub async fn new(data_dir: PathBuf, shard_config: ShardConfig) -> Result<Self> {
+    let service = LanceDbService::new(data_dir, shard_config).await?;
     Ok(Self { service })
   }
 }
@@ -37,8 +40,10 @@ impl VectorDatabase for LanceDbVectorDatabase {
     query_embedding: &[f32],
     limit: usize,
     threshold: Option<f32>,
+    tags: &[String],
   ) -> Result<Vec<VectorSearchResult>> {
-    let lance_results = self.service.search_similar(query_embedding, limit, threshold).await?;
+    let lance_results =
+      self.service.search_similar(query_embedding, limit, threshold, tags).await?;
 
     // Convert LanceDB-specific results to generic VectorSearchResult
     let generic_results = lance_results
@@ -49,6 +54,7 @@ impl VectorDatabase for LanceDbVectorDatabase {
         name: result.name,
         overview: result.overview,
         details: result.details,
+        tags: result.tags,
         similarity: result.similarity,
       })
       .collect();
@@ -84,6 +90,7 @@ impl VectorDatabase for LanceDbVectorDatabase {
         name: result.name,
         overview: result.overview,
         details: result.details,
+        tags: result.tags,
         similarity: result.similarity,
       })
       .collect();
@@ -100,4 +107,19 @@ impl VectorDatabase for LanceDbVectorDatabase {
   async fn reshape_database(&self, embedding_dimension: usize) -> Result<()> {
     self.service.reshape_database(embedding_dimension).await
   }
+
+  /// Row counts for every LanceDB shard table
+  async fn shard_stats(&self) -> Result<Vec<ShardStats>> {
+    self.service.shard_stats().await
+  }
+
+  /// Read one topic's embeddings back from every shard
+  async fn topic_embeddings(&self, topic: &str) -> Result<HashMap<String, Vec<f32>>> {
+    self.service.topic_embeddings(topic).await
+  }
+
+  /// Move LanceDB rows into a new shard layout
+  async fn rebalance(&self, config: ShardConfig) -> Result<usize> {
+    self.service.rebalance(config).await
+  }
 }
@@ -0,0 +1,183 @@
+//! Content lint rules for insights
+//!
+//! Unlike the sensitive-content scan, lint findings are about quality rather than
+//! safety: they are reported as warnings on every write and only block the write
+//! when the caller asks for strict mode.
+
+use bentley::config;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashSet;
+
+use crate::server::models::insight;
+use crate::settings;
+
+/// Details shorter than this are flagged
+const DEFAULT_MIN_DETAILS_CHARS: usize = 40;
+
+/// A single lint finding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+  /// Rule that failed (e.g. "empty-overview")
+  pub rule: &'static str,
+  /// What is wrong, in a form that can be shown to the author
+  pub message: String,
+}
+
+/// Findings for a stored insight
+#[derive(Debug, Clone)]
+pub struct InsightFindings {
+  pub topic: String,
+  pub name: String,
+  pub findings: Vec<Finding>,
+}
+
+/// Tunable parts of the rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintConfig {
+  /// Topics whose insights must cite at least one source
+  pub source_topics: Vec<String>,
+  /// Minimum length of the details, in characters
+  pub min_details_chars: usize,
+}
+
+impl Default for LintConfig {
+  fn default() -> Self {
+    Self { source_topics: Vec::new(), min_details_chars: DEFAULT_MIN_DETAILS_CHARS }
+  }
+}
+
+/// Cross-links are written `[[topic/name]]`; the topic may nest (`[[rust/async/pitfalls]]`)
+static CROSS_LINK: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(r"\[\[\s*([^\]]+?)\s*/\s*([^\]/]+?)\s*\]\]").expect("valid link pattern")
+});
+
+static URL: Lazy<Regex> = Lazy::new(|| Regex::new(r"https?://\S+").expect("valid url pattern"));
+
+/// Get the configured lint rules
+/// Default: no topics require sources, details need 40 characters
+/// Setting: insights.lint.source_topics (INSIGHTS_LINT_SOURCE_TOPICS, comma-separated topics),
+/// insights.lint.min_details (INSIGHTS_LINT_MIN_DETAILS, characters)
+pub fn get_config() -> LintConfig {
+  let source_topics = config::var(&settings::LINT_SOURCE_TOPICS)
+    .unwrap_or_default()
+    .split(',')
+    .map(str::trim)
+    .filter(|topic| !topic.is_empty())
+    .map(str::to_string)
+    .collect();
+  let min_details_chars =
+    config::get(&settings::LINT_MIN_DETAILS).unwrap_or(DEFAULT_MIN_DETAILS_CHARS);
+
+  LintConfig { source_topics, min_details_chars }
+}
+
+/// Lint the content of an insight about to be written
+pub fn lint_content(topic: &str, overview: &str, details: &str) -> Vec<Finding> {
+  check(topic, overview, details, &get_config(), |topic, name| insight::load(topic, name).is_ok())
+}
+
+/// Lint every stored insight, returning only those with findings
+pub fn lint_all(topic_filter: Option<&str>) -> anyhow::Result<Vec<InsightFindings>> {
+  let config = get_config();
+  let existing: HashSet<(String, String)> =
+    insight::get_insights(None)?.into_iter().map(|insight| (insight.topic, insight.name)).collect();
+  let exists = |topic: &str, name: &str| existing.contains(&(topic.to_string(), name.to_string()));
+
+  let results = insight::get_insights(topic_filter)?
+    .into_iter()
+    .filter_map(|insight| {
+      let findings = check(&insight.topic, &insight.overview, &insight.details, &config, exists);
+      (!findings.is_empty()).then_some(InsightFindings {
+        topic: insight.topic,
+        name: insight.name,
+        findings,
+      })
+    })
+    .collect();
+
+  Ok(results)
+}
+
+/// Run every rule against one insight's content
+pub fn check(
+  topic: &str,
+  overview: &str,
+  details: &str,
+  config: &LintConfig,
+  exists: impl Fn(&str, &str) -> bool,
+) -> Vec<Finding> {
+  let mut findings = Vec::new();
+  let mut fail = |rule: &'static str, message: String| findings.push(Finding { rule, message });
+
+  if overview.trim().is_empty() {
+    fail("empty-overview", "Overview is empty".to_string());
+  } else if normalize(overview) == normalize(first_sentence(details)) {
+    fail(
+      "overview-repeats-details",
+      "Overview repeats the first sentence of the details; summarise instead".to_string(),
+    );
+  }
+
+  let length = details.trim().chars().count();
+  if length < config.min_details_chars {
+    fail(
+      "short-details",
+      format!("Details are {length} characters; expected at least {}", config.min_details_chars),
+    );
+  }
+
+  if config.source_topics.iter().any(|t| t.eq_ignore_ascii_case(topic)) && !cites_source(details) {
+    fail(
+      "missing-sources",
+      format!("Insights in '{topic}' must cite a source (a URL or a 'Sources:' section)"),
+    );
+  }
+
+  for link in CROSS_LINK.captures_iter(details) {
+    let (linked_topic, linked_name) = (&link[1], &link[2]);
+    if !exists(linked_topic, linked_name) {
+      fail("broken-link", format!("Link to {linked_topic}/{linked_name} has no matching insight"));
+    }
+  }
+
+  findings
+}
+
+/// One-line summary of findings for error messages
+pub fn describe(findings: &[Finding]) -> String {
+  let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+  format!("Insight failed lint checks: {}", messages.join("; "))
+}
+
+/// Text up to the first sentence break or line break
+fn first_sentence(text: &str) -> &str {
+  let text = text.trim_start();
+  let line = text.lines().next().unwrap_or_default();
+  let mut chars = line.char_indices().peekable();
+  while let Some((index, c)) = chars.next() {
+    let at_break = chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+    if matches!(c, '.' | '!' | '?') && at_break {
+      return &line[..index];
+    }
+  }
+  line
+}
+
+/// Lowercased words only, so punctuation and spacing don't hide a duplicate
+fn normalize(text: &str) -> String {
+  text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(str::to_lowercase)
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn cites_source(details: &str) -> bool {
+  URL.is_match(details)
+    || details.lines().any(|line| {
+      let line = line.trim_start().to_lowercase();
+      line.starts_with("sources:") || line.starts_with("source:")
+    })
+}
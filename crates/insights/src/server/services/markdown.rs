@@ -0,0 +1,242 @@
+//! Markdown to HTML for displaying insights
+//!
+//! Covers what insight bodies actually use: headings, paragraphs, lists, block
+//! quotes, fenced code, rules, and inline code, emphasis and links. All text is
+//! escaped and links are limited to web, mail and relative targets, so rendered
+//! insights are safe to drop into a page.
+
+/// Render markdown as an HTML fragment
+pub fn to_html(markdown: &str) -> String {
+  let lines: Vec<&str> = markdown.lines().collect();
+  let mut html = String::new();
+  let mut i = 0;
+
+  while i < lines.len() {
+    let line = lines[i];
+    let trimmed = line.trim();
+
+    if trimmed.is_empty() {
+      i += 1;
+    } else if let Some(language) = trimmed.strip_prefix("```") {
+      let start = i + 1;
+      let end = (start..lines.len()).find(|&j| lines[j].trim().starts_with("```"));
+      let code = lines[start..end.unwrap_or(lines.len())].join("\n");
+      let class = match language.trim() {
+        "" => String::new(),
+        language => format!(" class=\"language-{}\"", escape(language)),
+      };
+      html.push_str(&format!("<pre><code{class}>{}</code></pre>\n", escape(&code)));
+      i = end.map_or(lines.len(), |end| end + 1);
+    } else if let Some((level, text)) = heading(trimmed) {
+      html.push_str(&format!("<h{level}>{}</h{level}>\n", inline(text)));
+      i += 1;
+    } else if is_rule(trimmed) {
+      html.push_str("<hr>\n");
+      i += 1;
+    } else if trimmed.starts_with('>') {
+      let mut quoted = Vec::new();
+      while i < lines.len() && lines[i].trim().starts_with('>') {
+        let text = lines[i].trim().trim_start_matches('>');
+        quoted.push(text.strip_prefix(' ').unwrap_or(text));
+        i += 1;
+      }
+      html.push_str(&format!("<blockquote>\n{}</blockquote>\n", to_html(&quoted.join("\n"))));
+    } else if let Some((ordered, _)) = list_item(trimmed) {
+      let tag = if ordered { "ol" } else { "ul" };
+      let mut items: Vec<String> = Vec::new();
+      while i < lines.len() && !lines[i].trim().is_empty() {
+        match list_item(lines[i].trim()) {
+          Some((item_ordered, text)) if item_ordered == ordered => items.push(text.to_string()),
+          Some(_) => break,
+          // Lines that aren't items continue the one before
+          None => {
+            let item = items.last_mut().expect("a list starts with an item");
+            item.push(' ');
+            item.push_str(lines[i].trim());
+          }
+        }
+        i += 1;
+      }
+      html.push_str(&format!("<{tag}>\n"));
+      for item in items {
+        html.push_str(&format!("<li>{}</li>\n", inline(item.trim())));
+      }
+      html.push_str(&format!("</{tag}>\n"));
+    } else {
+      let mut paragraph = Vec::new();
+      while i < lines.len() && !starts_block(lines[i].trim()) {
+        paragraph.push(lines[i].trim());
+        i += 1;
+      }
+      html.push_str(&format!("<p>{}</p>\n", inline(&paragraph.join("\n"))));
+    }
+  }
+  html
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+  let level = line.chars().take_while(|&c| c == '#').count();
+  let text = line[level..].strip_prefix(' ')?;
+  (1..=6).contains(&level).then(|| (level, text.trim().trim_end_matches('#').trim_end()))
+}
+
+fn is_rule(line: &str) -> bool {
+  let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+  compact.len() >= 3 && ['-', '*', '_'].iter().any(|&marker| compact.chars().all(|c| c == marker))
+}
+
+/// Whether the line is a list item, whether it is numbered, and its text
+fn list_item(line: &str) -> Option<(bool, &str)> {
+  for marker in ["- ", "* ", "+ "] {
+    if let Some(text) = line.strip_prefix(marker) {
+      return Some((false, text));
+    }
+  }
+  let digits = line.chars().take_while(char::is_ascii_digit).count();
+  let text = line[digits..].strip_prefix(". ").or_else(|| line[digits..].strip_prefix(") "))?;
+  (digits > 0).then_some((true, text))
+}
+
+/// Whether a line ends the paragraph before it
+fn starts_block(line: &str) -> bool {
+  line.is_empty()
+    || line.starts_with("```")
+    || line.starts_with('>')
+    || heading(line).is_some()
+    || is_rule(line)
+    || list_item(line).is_some()
+}
+
+/// Render inline markup: code, strong, emphasis, links and escapes
+fn inline(text: &str) -> String {
+  let chars: Vec<char> = text.chars().collect();
+  let mut html = String::new();
+  let mut i = 0;
+
+  while i < chars.len() {
+    let c = chars[i];
+    match c {
+      '\\' if chars.get(i + 1).is_some_and(|next| next.is_ascii_punctuation()) => {
+        html.push_str(&escape(&chars[i + 1].to_string()));
+        i += 2;
+        continue;
+      }
+      '`' => {
+        if let Some(end) = find(&chars, i + 1, "`") {
+          let code: String = chars[i + 1..end].iter().collect();
+          html.push_str(&format!("<code>{}</code>", escape(&code)));
+          i = end + 1;
+          continue;
+        }
+      }
+      '*' | '_'
+        if c == '*' || !chars.get(i.wrapping_sub(1)).is_some_and(|p| p.is_alphanumeric()) =>
+      {
+        let double = chars.get(i + 1) == Some(&c);
+        let marker: String = if double { format!("{c}{c}") } else { c.to_string() };
+        let start = i + marker.len();
+        if let Some(end) = find(&chars, start, &marker).filter(|&end| end > start) {
+          let inner: String = chars[start..end].iter().collect();
+          let tag = if double { "strong" } else { "em" };
+          html.push_str(&format!("<{tag}>{}</{tag}>", inline(&inner)));
+          i = end + marker.len();
+          continue;
+        }
+      }
+      '[' => {
+        if let Some((anchor, end)) = link(&chars, i) {
+          html.push_str(&anchor);
+          i = end;
+          continue;
+        }
+      }
+      _ => {}
+    }
+    html.push_str(&escape(&c.to_string()));
+    i += 1;
+  }
+  html
+}
+
+/// A `[label](url)` link starting at `start`, and where it ends
+fn link(chars: &[char], start: usize) -> Option<(String, usize)> {
+  let label_end = find(chars, start + 1, "](")?;
+  let url_end = find(chars, label_end + 2, ")")?;
+  let label: String = chars[start + 1..label_end].iter().collect();
+  let url: String = chars[label_end + 2..url_end].iter().collect();
+  let url = url.trim();
+
+  let safe = ["http://", "https://", "mailto:", "/", "#"].iter().any(|p| url.starts_with(p));
+  if !safe || url.starts_with("//") {
+    return None;
+  }
+  Some((format!("<a href=\"{}\">{}</a>", escape(url), inline(&label)), url_end + 1))
+}
+
+/// Position of the next `needle` at or after `from`
+fn find(chars: &[char], from: usize, needle: &str) -> Option<usize> {
+  let needle: Vec<char> = needle.chars().collect();
+  (from..chars.len().saturating_sub(needle.len() - 1))
+    .find(|&i| chars[i..i + needle.len()] == needle[..])
+}
+
+/// Escape text for HTML content and attribute values
+pub fn escape(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      _ => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_blocks() {
+    let markdown = "# Title\n\nFirst line\nsecond line\n\n- one\n- two\n  continued\n\n1. first\n2. second\n\n> quoted *text*\n\n---\n```rust\nfn main() {}\n```\n";
+    assert_eq!(
+      to_html(markdown),
+      "<h1>Title</h1>\n\
+       <p>First line\nsecond line</p>\n\
+       <ul>\n<li>one</li>\n<li>two continued</li>\n</ul>\n\
+       <ol>\n<li>first</li>\n<li>second</li>\n</ol>\n\
+       <blockquote>\n<p>quoted <em>text</em></p>\n</blockquote>\n\
+       <hr>\n\
+       <pre><code class=\"language-rust\">fn main() {}</code></pre>\n"
+    );
+  }
+
+  #[test]
+  fn test_inline_markup() {
+    assert_eq!(
+      inline("**Bold** and *em* with `a < b` and snake_case_name"),
+      "<strong>Bold</strong> and <em>em</em> with <code>a &lt; b</code> and snake_case_name"
+    );
+    assert_eq!(
+      inline("See [the **docs**](https://docs.rs/axum) or \\*this\\*"),
+      "See <a href=\"https://docs.rs/axum\">the <strong>docs</strong></a> or *this*"
+    );
+    assert_eq!(inline("Unclosed *star and [text] (x)"), "Unclosed *star and [text] (x)");
+  }
+
+  #[test]
+  fn test_output_is_escaped() {
+    assert_eq!(
+      to_html("<script>alert('x')</script>"),
+      "<p>&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;</p>\n"
+    );
+    assert_eq!(inline("[click](javascript:alert(1))"), "[click](javascript:alert(1))");
+    assert_eq!(inline("[x](//evil.example)"), "[x](//evil.example)");
+    assert_eq!(inline("[x](/a\"onclick=\"y)"), "<a href=\"/a&quot;onclick=&quot;y\">x</a>");
+    assert_eq!(to_html("```\n<b>\n```"), "<pre><code>&lt;b&gt;</code></pre>\n");
+  }
+}
@@ -0,0 +1,378 @@
+//! In-process implementation of the VectorDatabase trait
+//!
+//! Keeps every embedding in memory and searches them by brute force, which is
+//! plenty for a personal knowledge base and needs no native dependencies. Each
+//! change is appended to a JSON lines journal under the data directory; the
+//! journal is replayed and compacted when the store opens, so a re-index costs
+//! one line per insight rather than a rewrite of the whole index.
+//!
+//! Scores match LanceDB's: the squared Euclidean distance between normalized
+//! vectors, mapped onto 0.0-1.0.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::server::models::sharding::ShardConfig;
+use crate::server::models::{insight, tag};
+use crate::server::services::sharding::ShardStats;
+use crate::server::services::vector_database::{VectorDatabase, VectorSearchResult};
+
+/// Journal file inside the data directory
+pub const JOURNAL_FILE: &str = "vectors.jsonl";
+
+/// One stored embedding, with the shard it was filed under
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StoredRecord {
+  table: String,
+  topic: String,
+  name: String,
+  overview: String,
+  details: String,
+  tags: Vec<String>,
+  embedding: Vec<f32>,
+}
+
+/// A line of the journal
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+enum Entry {
+  Put(StoredRecord),
+  Delete { topic: String, name: String },
+}
+
+type Key = (String, String);
+
+struct State {
+  shard_config: ShardConfig,
+  records: BTreeMap<Key, StoredRecord>,
+}
+
+/// Brute-force vector store, optionally persisted to disk
+pub struct MemoryVectorDatabase {
+  journal: Option<PathBuf>,
+  state: Mutex<State>,
+}
+
+impl MemoryVectorDatabase {
+  /// Open the store kept in `data_dir`, creating it if needed
+  pub fn open(data_dir: PathBuf, shard_config: ShardConfig) -> Result<Self> {
+    fs::create_dir_all(&data_dir)?;
+    let journal = data_dir.join(JOURNAL_FILE);
+    let records = if journal.exists() { replay(&journal)? } else { BTreeMap::new() };
+
+    let db = Self { journal: Some(journal), state: Mutex::new(State { shard_config, records }) };
+    db.compact(&db.lock())?;
+    Ok(db)
+  }
+
+  /// A store that lives only as long as the process
+  pub fn in_memory(shard_config: ShardConfig) -> Self {
+    Self { journal: None, state: Mutex::new(State { shard_config, records: BTreeMap::new() }) }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, State> {
+    self.state.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Append changes to the journal
+  fn append(&self, entries: &[Entry]) -> Result<()> {
+    let Some(journal) = &self.journal else { return Ok(()) };
+    let mut file = OpenOptions::new().create(true).append(true).open(journal)?;
+    let mut lines = String::new();
+    for entry in entries {
+      lines.push_str(&serde_json::to_string(entry)?);
+      lines.push('\n');
+    }
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+  }
+
+  /// Replace the journal with one line per stored record
+  fn compact(&self, state: &State) -> Result<()> {
+    let Some(journal) = &self.journal else { return Ok(()) };
+    let temp = journal.with_extension("jsonl.tmp");
+    {
+      let mut writer = BufWriter::new(File::create(&temp)?);
+      for record in state.records.values() {
+        serde_json::to_writer(&mut writer, &Entry::Put(record.clone()))?;
+        writer.write_all(b"\n")?;
+      }
+      writer.flush()?;
+    }
+    fs::rename(&temp, journal)?;
+    Ok(())
+  }
+}
+
+/// Rebuild the records from a journal
+///
+/// A line cut short by a crash can only be the last one, so it is dropped.
+fn replay(journal: &Path) -> Result<BTreeMap<Key, StoredRecord>> {
+  let mut records = BTreeMap::new();
+  let lines: Vec<String> =
+    BufReader::new(File::open(journal)?).lines().collect::<Result<_, _>>()?;
+  let last = lines.len().saturating_sub(1);
+
+  for (number, line) in lines.iter().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+    match serde_json::from_str(line) {
+      Ok(Entry::Put(record)) => {
+        records.insert((record.topic.clone(), record.name.clone()), record);
+      }
+      Ok(Entry::Delete { topic, name }) => {
+        records.remove(&(topic, name));
+      }
+      Err(e) if number == last => {
+        bentley::warn!(&format!("Dropping incomplete last line of {}: {e}", journal.display()));
+      }
+      Err(e) => {
+        return Err(anyhow!(
+          "Corrupt vector journal {} line {}: {e}",
+          journal.display(),
+          number + 1
+        ))
+      }
+    }
+  }
+  Ok(records)
+}
+
+/// Similarity on LanceDB's scale: 1.0 for identical normalized vectors, 0.0 for opposite ones
+fn similarity(a: &[f32], b: &[f32]) -> f32 {
+  let distance: f32 = a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum();
+  (2.0 - distance.min(2.0)) / 2.0
+}
+
+fn search_result(record: &StoredRecord, similarity: f32) -> VectorSearchResult {
+  VectorSearchResult {
+    id: format!("{}:{}", record.topic, record.name),
+    topic: record.topic.clone(),
+    name: record.name.clone(),
+    overview: record.overview.clone(),
+    details: record.details.clone(),
+    tags: record.tags.clone(),
+    similarity,
+  }
+}
+
+#[async_trait]
+impl VectorDatabase for MemoryVectorDatabase {
+  /// Store an insight's embedding, replacing any previous one
+  async fn store_embedding(&self, insight: &insight::Insight) -> Result<()> {
+    let embedding =
+      insight.embedding.clone().ok_or_else(|| anyhow!("Insight has no embedding to store"))?;
+    let mut state = self.lock();
+    let record = StoredRecord {
+      table: state.shard_config.table_for(&insight.topic, &insight.name),
+      topic: insight.topic.clone(),
+      name: insight.name.clone(),
+      overview: insight.overview.clone(),
+      details: insight.details.clone(),
+      tags: insight.tags.clone(),
+      embedding,
+    };
+
+    self.append(&[Entry::Put(record.clone())])?;
+    state.records.insert((record.topic.clone(), record.name.clone()), record);
+    Ok(())
+  }
+
+  /// Score every stored embedding against the query and keep the best
+  async fn search_similar(
+    &self,
+    query_embedding: &[f32],
+    limit: usize,
+    threshold: Option<f32>,
+    tags: &[String],
+  ) -> Result<Vec<VectorSearchResult>> {
+    let state = self.lock();
+    let mut results: Vec<VectorSearchResult> = state
+      .records
+      .values()
+      // Vectors from another model can't be compared; a re-index replaces them
+      .filter(|record| record.embedding.len() == query_embedding.len())
+      .filter(|record| tag::has_all(&record.tags, tags))
+      .map(|record| search_result(record, similarity(query_embedding, &record.embedding)))
+      .filter(|result| threshold.is_none_or(|threshold| result.similarity >= threshold))
+      .collect();
+
+    results.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    results.truncate(limit);
+    Ok(results)
+  }
+
+  /// Check if any embeddings are stored
+  async fn has_embeddings(&self) -> Result<bool> {
+    Ok(!self.lock().records.is_empty())
+  }
+
+  /// Delete an insight's embedding
+  async fn delete_embedding(&self, topic: &str, name: &str) -> Result<()> {
+    let mut state = self.lock();
+    let key = (topic.to_string(), name.to_string());
+    if state.records.contains_key(&key) {
+      self.append(&[Entry::Delete { topic: key.0.clone(), name: key.1.clone() }])?;
+      state.records.remove(&key);
+    }
+    Ok(())
+  }
+
+  /// Update an insight's embedding
+  async fn update_embedding(&self, insight: &insight::Insight) -> Result<()> {
+    self.store_embedding(insight).await
+  }
+
+  /// Every stored embedding, unscored
+  async fn get_all_embeddings(&self) -> Result<Vec<VectorSearchResult>> {
+    Ok(self.lock().records.values().map(|record| search_result(record, 0.0)).collect())
+  }
+
+  /// Clear all embeddings
+  async fn clear_all_embeddings(&self) -> Result<()> {
+    let mut state = self.lock();
+    let cleared = State { shard_config: state.shard_config.clone(), records: BTreeMap::new() };
+    self.compact(&cleared)?;
+    *state = cleared;
+    Ok(())
+  }
+
+  /// Start over; vectors of any dimension are accepted, so this only clears
+  async fn reshape_database(&self, _embedding_dimension: usize) -> Result<()> {
+    self.clear_all_embeddings().await
+  }
+
+  /// Row counts for every shard that holds embeddings
+  async fn shard_stats(&self) -> Result<Vec<ShardStats>> {
+    let mut rows: BTreeMap<&str, usize> = BTreeMap::new();
+    let state = self.lock();
+    for record in state.records.values() {
+      *rows.entry(&record.table).or_default() += 1;
+    }
+    Ok(
+      rows.into_iter().map(|(table, rows)| ShardStats { table: table.to_string(), rows }).collect(),
+    )
+  }
+
+  /// Stored embeddings for one topic, keyed by lowercased insight name
+  async fn topic_embeddings(&self, topic: &str) -> Result<HashMap<String, Vec<f32>>> {
+    Ok(
+      self
+        .lock()
+        .records
+        .values()
+        .filter(|record| record.topic.eq_ignore_ascii_case(topic))
+        .map(|record| (record.name.to_lowercase(), record.embedding.clone()))
+        .collect(),
+    )
+  }
+
+  /// Refile every embedding under the new layout
+  async fn rebalance(&self, config: ShardConfig) -> Result<usize> {
+    let mut state = self.lock();
+    let mut records = state.records.clone();
+    let mut moved = 0;
+    for record in records.values_mut() {
+      let target = config.table_for(&record.topic, &record.name);
+      if target != record.table {
+        record.table = target;
+        moved += 1;
+      }
+    }
+
+    let rebalanced = State { shard_config: config, records };
+    self.compact(&rebalanced)?;
+    *state = rebalanced;
+    bentley::info!(&format!("Rebalanced embeddings index, {moved} rows moved"));
+    Ok(moved)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::server::models::sharding::ShardStrategy;
+
+  fn insight(topic: &str, name: &str, tags: &[&str], embedding: Vec<f32>) -> insight::Insight {
+    let mut insight = insight::Insight::new(
+      topic.to_string(),
+      name.to_string(),
+      format!("{name} overview"),
+      format!("{name} details"),
+    );
+    insight.tags = tags.iter().map(|t| t.to_string()).collect();
+    insight.embedding = Some(embedding);
+    insight
+  }
+
+  #[tokio::test]
+  async fn test_search_ranks_filters_and_limits() {
+    let db = MemoryVectorDatabase::in_memory(ShardConfig::default());
+    db.store_embedding(&insight("rust", "exact", &["async"], vec![1.0, 0.0])).await.unwrap();
+    db.store_embedding(&insight("rust", "close", &[], vec![0.8, 0.6])).await.unwrap();
+    db.store_embedding(&insight("rust", "opposite", &["async"], vec![-1.0, 0.0])).await.unwrap();
+    db.store_embedding(&insight("rust", "other-model", &[], vec![1.0, 0.0, 0.0])).await.unwrap();
+
+    let results = db.search_similar(&[1.0, 0.0], 10, None, &[]).await.unwrap();
+    let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["exact", "close", "opposite"]);
+    assert!((results[0].similarity - 1.0).abs() < 1e-6);
+    assert!((results[1].similarity - 0.8).abs() < 1e-6);
+    assert_eq!(results[2].similarity, 0.0);
+
+    let results = db.search_similar(&[1.0, 0.0], 10, Some(0.5), &[]).await.unwrap();
+    assert_eq!(results.len(), 2);
+    let results = db.search_similar(&[1.0, 0.0], 1, None, &[]).await.unwrap();
+    assert_eq!(results[0].id, "rust:exact");
+    let results = db.search_similar(&[0.0, 1.0], 10, None, &["ASYNC".to_string()]).await.unwrap();
+    assert_eq!(results.len(), 2);
+  }
+
+  #[tokio::test]
+  async fn test_journal_survives_reopening() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_path_buf();
+
+    let db = MemoryVectorDatabase::open(path.clone(), ShardConfig::default()).unwrap();
+    db.store_embedding(&insight("rust", "kept", &[], vec![1.0, 0.0])).await.unwrap();
+    db.store_embedding(&insight("rust", "deleted", &[], vec![0.0, 1.0])).await.unwrap();
+    db.update_embedding(&insight("rust", "kept", &[], vec![0.6, 0.8])).await.unwrap();
+    db.delete_embedding("rust", "deleted").await.unwrap();
+    drop(db);
+
+    // A crash mid-write leaves at most a partial last line
+    let journal = path.join(JOURNAL_FILE);
+    let mut file = OpenOptions::new().append(true).open(&journal).unwrap();
+    file.write_all(b"{\"op\":\"put\",\"topic\":").unwrap();
+
+    let db = MemoryVectorDatabase::open(path, ShardConfig::default()).unwrap();
+    let embeddings = db.topic_embeddings("RUST").await.unwrap();
+    assert_eq!(embeddings.len(), 1);
+    assert_eq!(embeddings["kept"], vec![0.6, 0.8]);
+    assert_eq!(fs::read_to_string(&journal).unwrap().lines().count(), 1);
+
+    db.clear_all_embeddings().await.unwrap();
+    assert!(!db.has_embeddings().await.unwrap());
+    assert!(fs::read_to_string(&journal).unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_rebalance_refiles_rows() {
+    let db = MemoryVectorDatabase::in_memory(ShardConfig::default());
+    db.store_embedding(&insight("rust", "a", &[], vec![1.0])).await.unwrap();
+    db.store_embedding(&insight("go", "b", &[], vec![1.0])).await.unwrap();
+    assert_eq!(db.shard_stats().await.unwrap().len(), 1);
+
+    let by_topic = ShardConfig { strategy: ShardStrategy::Topic, ..Default::default() };
+    assert_eq!(db.rebalance(by_topic.clone()).await.unwrap(), 2);
+    assert_eq!(db.rebalance(by_topic).await.unwrap(), 0);
+
+    let stats = db.shard_stats().await.unwrap();
+    assert_eq!(stats.len(), 2);
+    assert!(stats.iter().all(|s| s.rows == 1));
+  }
+}
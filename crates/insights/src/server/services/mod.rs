@@ -1,9 +1,37 @@
+pub mod ask;
+pub mod auth;
+pub mod backup;
+pub mod batch;
+pub mod bootstrap;
+pub mod dedupe;
+pub mod events;
+pub mod export;
+pub mod fulltext;
+pub mod fuzzy;
+pub mod history;
+pub mod import;
+pub mod indexing;
+pub mod lint;
+pub mod markdown;
+pub mod reindex;
+pub mod related;
+pub mod retention;
 pub mod search;
+pub mod sensitive;
+pub mod sharding;
 pub mod similarity;
+pub mod slack;
+pub mod summary;
+pub mod sync;
+pub mod watcher;
+pub mod webhooks;
+pub mod workspace;
 
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 pub mod embeddings;
 #[cfg(feature = "ml-features")]
 pub mod lancedb;
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
+pub mod memory_db;
+#[cfg(feature = "semantic")]
 pub mod vector_database;
@@ -0,0 +1,208 @@
+//! Progress of re-indexing jobs
+//!
+//! Re-indexing embeds every insight, a batch of texts per model run with a few
+//! batches in flight at once, and can take minutes on a large knowledge base.
+//! The job's progress is kept here so `GET /insights/index/status` can report
+//! how far it got and how long it should take, both while it runs and after it
+//! has finished. Only one re-index runs at a time.
+
+use crate::settings;
+use bentley::config;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Most batches that may be in flight at once
+pub const MAX_CONCURRENCY: usize = 16;
+
+const DEFAULT_BATCH_SIZE: usize = 8;
+const DEFAULT_CONCURRENCY: usize = 2;
+
+/// Get the configured number of insights embedded per model run
+/// Default: 8
+/// Setting: insights.index.batch_size (INSIGHTS_EMBED_BATCH_SIZE)
+pub fn get_batch_size() -> usize {
+  config::get(&settings::EMBED_BATCH_SIZE).filter(|n| *n > 0).unwrap_or(DEFAULT_BATCH_SIZE)
+}
+
+/// Get the configured number of batches in flight while embedding in the background
+/// Default: 2
+/// Setting: insights.index.concurrency (INSIGHTS_INDEX_CONCURRENCY)
+pub fn get_concurrency() -> usize {
+  config::get(&settings::INDEX_CONCURRENCY)
+    .filter(|n| (1..=MAX_CONCURRENCY).contains(n))
+    .unwrap_or(DEFAULT_CONCURRENCY)
+}
+
+/// Where a re-index is at
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobState {
+  /// No re-index has run since the server started
+  Idle,
+  Running,
+  Completed,
+  Failed,
+}
+
+/// Counts for one embedding pass
+#[derive(Debug, Clone)]
+pub struct Progress {
+  pub total: usize,
+  pub embedded: usize,
+  pub errors: usize,
+  pub started_at: DateTime<Utc>,
+}
+
+impl Progress {
+  pub fn new(total: usize) -> Self {
+    Self { total, embedded: 0, errors: 0, started_at: Utc::now() }
+  }
+
+  /// Insights handled so far, whether or not they could be embedded
+  pub fn done(&self) -> usize {
+    self.embedded + self.errors
+  }
+
+  /// Estimated time left at the pace kept so far
+  pub fn eta(&self, now: DateTime<Utc>) -> Option<Duration> {
+    let elapsed = (now - self.started_at).to_std().unwrap_or_default();
+    eta(elapsed, self.done(), self.total)
+  }
+}
+
+/// Time left for `total` items once `done` of them took `elapsed`
+pub fn eta(elapsed: Duration, done: usize, total: usize) -> Option<Duration> {
+  if done == 0 {
+    return None;
+  }
+  let remaining = total.saturating_sub(done);
+  Some(elapsed.mul_f64(remaining as f64 / done as f64))
+}
+
+/// Short human form of a duration (`45s`, `3m 05s`, `1h 02m`)
+pub fn format_duration(duration: Duration) -> String {
+  let secs = duration.as_secs();
+  match secs {
+    0..60 => format!("{secs}s"),
+    60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+    _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+  }
+}
+
+// Current Job
+// ===========
+
+/// The current or last re-index
+#[derive(Debug, Clone)]
+pub struct Job {
+  pub state: JobState,
+  pub progress: Progress,
+  pub batch_size: usize,
+  pub concurrency: usize,
+  pub finished_at: Option<DateTime<Utc>>,
+  pub error: Option<String>,
+}
+
+static JOB: Lazy<Mutex<Option<Job>>> = Lazy::new(|| Mutex::new(None));
+
+/// Register a new re-index, unless one is already running
+pub fn start(batch_size: usize, concurrency: usize) -> Result<Job, String> {
+  let mut job = JOB.lock().unwrap_or_else(|e| e.into_inner());
+  if job.as_ref().is_some_and(|j| j.state == JobState::Running) {
+    return Err("A re-index is already running".to_string());
+  }
+
+  let started = Job {
+    state: JobState::Running,
+    progress: Progress::new(0),
+    batch_size,
+    concurrency,
+    finished_at: None,
+    error: None,
+  };
+  *job = Some(started.clone());
+  Ok(started)
+}
+
+/// Record how far the running re-index got
+pub fn record(progress: &Progress) {
+  let mut job = JOB.lock().unwrap_or_else(|e| e.into_inner());
+  if let Some(job) = job.as_mut().filter(|j| j.state == JobState::Running) {
+    job.progress = progress.clone();
+  }
+}
+
+/// Mark the running re-index as finished, with the error that stopped it if any
+pub fn finish(error: Option<String>) {
+  let mut job = JOB.lock().unwrap_or_else(|e| e.into_inner());
+  if let Some(job) = job.as_mut().filter(|j| j.state == JobState::Running) {
+    job.state = if error.is_some() { JobState::Failed } else { JobState::Completed };
+    job.finished_at = Some(Utc::now());
+    job.error = error;
+  }
+}
+
+/// The current or last re-index, if there has been one
+pub fn current() -> Option<Job> {
+  JOB.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_eta_follows_the_pace_so_far() {
+    assert_eq!(eta(Duration::from_secs(10), 0, 100), None);
+    assert_eq!(eta(Duration::from_secs(10), 25, 100), Some(Duration::from_secs(30)));
+    assert_eq!(eta(Duration::from_secs(10), 100, 100), Some(Duration::ZERO));
+  }
+
+  #[test]
+  fn test_progress_eta() {
+    let mut progress = Progress::new(40);
+    assert_eq!(progress.eta(Utc::now()), None);
+
+    progress.embedded = 9;
+    progress.errors = 1;
+    let now = progress.started_at + chrono::Duration::seconds(20);
+    assert_eq!(progress.done(), 10);
+    assert_eq!(progress.eta(now), Some(Duration::from_secs(60)));
+  }
+
+  #[test]
+  fn test_format_duration() {
+    assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+    assert_eq!(format_duration(Duration::from_secs(185)), "3m 05s");
+    assert_eq!(format_duration(Duration::from_secs(3720)), "1h 02m");
+  }
+
+  #[test]
+  fn test_job_lifecycle() {
+    let job = start(8, 2).unwrap();
+    assert_eq!(job.state, JobState::Running);
+    assert!(start(8, 2).is_err());
+
+    let mut progress = Progress::new(3);
+    progress.embedded = 2;
+    record(&progress);
+    assert_eq!(current().unwrap().progress.embedded, 2);
+
+    finish(Some("disk full".to_string()));
+    let job = current().unwrap();
+    assert_eq!(job.state, JobState::Failed);
+    assert_eq!(job.error.as_deref(), Some("disk full"));
+    assert!(job.finished_at.is_some());
+
+    // A finished job no longer takes updates, and a new one may start
+    record(&Progress::new(99));
+    assert_eq!(current().unwrap().progress.total, 3);
+    assert!(start(8, 2).is_ok());
+    finish(None);
+    assert_eq!(current().unwrap().state, JobState::Completed);
+  }
+}
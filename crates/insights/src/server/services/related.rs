@@ -0,0 +1,72 @@
+//! Related insights
+//!
+//! An insight's related insights are its nearest neighbours in embedding space.
+//! The insight itself is always its own nearest neighbour, so it is dropped from
+//! the results, as is anything less similar than the configured threshold.
+
+use crate::server::types::RelatedInsightData;
+use crate::settings;
+use bentley::config;
+
+/// Related insights returned when the caller does not ask for a number
+pub const DEFAULT_LIMIT: usize = 5;
+
+/// Minimum similarity (0.0-1.0) for an insight to count as related
+const DEFAULT_THRESHOLD: f32 = 0.5;
+
+/// Get the configured similarity threshold
+/// Default: 0.5
+/// Setting: insights.related.threshold (INSIGHTS_RELATED_THRESHOLD, 0.0-1.0)
+pub fn get_threshold() -> f32 {
+  config::get(&settings::RELATED_THRESHOLD)
+    .filter(|threshold: &f32| (0.0..=1.0).contains(threshold))
+    .unwrap_or(DEFAULT_THRESHOLD)
+}
+
+/// Most similar candidates first, without the insight they were found for
+pub fn nearest(
+  mut candidates: Vec<RelatedInsightData>,
+  topic: &str,
+  name: &str,
+  limit: usize,
+) -> Vec<RelatedInsightData> {
+  candidates
+    .retain(|c| !(c.topic.eq_ignore_ascii_case(topic) && c.name.eq_ignore_ascii_case(name)));
+  candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+  candidates.truncate(limit);
+  candidates
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn candidate(topic: &str, name: &str, similarity: f32) -> RelatedInsightData {
+    RelatedInsightData {
+      topic: topic.to_string(),
+      name: name.to_string(),
+      overview: String::new(),
+      similarity,
+    }
+  }
+
+  #[test]
+  fn test_nearest_drops_the_insight_itself() {
+    let candidates = vec![
+      candidate("rust", "Tokio", 1.0),
+      candidate("rust", "async-std", 0.7),
+      candidate("go", "tokio", 0.6),
+    ];
+    let related = nearest(candidates, "Rust", "tokio", 5);
+    let names: Vec<_> = related.iter().map(|r| format!("{}/{}", r.topic, r.name)).collect();
+    assert_eq!(names, vec!["rust/async-std", "go/tokio"]);
+  }
+
+  #[test]
+  fn test_nearest_orders_and_limits() {
+    let candidates =
+      vec![candidate("a", "low", 0.55), candidate("a", "high", 0.9), candidate("a", "mid", 0.7)];
+    let related = nearest(candidates, "rust", "tokio", 2);
+    assert_eq!(related.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), vec!["high", "mid"]);
+  }
+}
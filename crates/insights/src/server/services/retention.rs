@@ -0,0 +1,130 @@
+//! Scheduled expiry of insights governed by per-topic retention policies
+
+use anyhow::Result;
+use bentley::config;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+use crate::server::middleware::{server_info, server_warn};
+use crate::server::models::{insight, retention, webhook::WebhookEvent};
+use crate::server::services::{events, fulltext, sync, webhooks};
+use crate::settings;
+
+/// Default interval between retention sweeps (one hour)
+const DEFAULT_SWEEP_INTERVAL_SECS: u64 = 3600;
+
+/// Outcome of a single retention sweep
+#[derive(Debug, Default)]
+pub struct SweepReport {
+  /// `topic/name` of every insight moved to the archive
+  pub archived: Vec<String>,
+  /// Failures encountered while archiving
+  pub errors: Vec<String>,
+}
+
+/// Find all insights that have expired as of `now`
+pub fn find_expired(now: DateTime<Utc>) -> Result<Vec<insight::Insight>> {
+  let policies = retention::load_policies()?;
+  if policies.is_empty() {
+    return Ok(Vec::new());
+  }
+
+  let expired = insight::get_insights(None)?
+    .into_iter()
+    .filter(|insight| retention::is_expired(&policies, insight, now))
+    .collect();
+
+  Ok(expired)
+}
+
+/// Archive every expired insight and drop its embedding
+pub async fn sweep(now: DateTime<Utc>) -> Result<SweepReport> {
+  let mut report = SweepReport::default();
+
+  for expired in find_expired(now)? {
+    let id = format!("{}/{}", expired.topic, expired.name);
+    match retention::archive(&expired) {
+      Ok(_) => {
+        if let Err(e) = fulltext::remove_insight(&expired.topic, &expired.name) {
+          report.errors.push(format!("{id}: archived but not removed from full-text index: {e}"));
+        }
+        remove_embedding(&expired).await;
+        events::publish(WebhookEvent::Deleted, &expired);
+        webhooks::notify(WebhookEvent::Deleted, &expired).await;
+        report.archived.push(id);
+      }
+      Err(e) => report.errors.push(format!("{id}: {e}")),
+    }
+  }
+
+  if !report.archived.is_empty() {
+    sync::auto_commit(&format!("Archive {} expired insights", report.archived.len())).await;
+  }
+  Ok(report)
+}
+
+#[cfg(feature = "semantic")]
+async fn remove_embedding(expired: &insight::Insight) {
+  use crate::server::services::vector_database::VectorDatabase;
+
+  let workspace = crate::server::services::workspace::current();
+  let result = match crate::server::middleware::workspace_vector_db(&workspace).await {
+    Ok(vector_db) => vector_db.delete_embedding(&expired.topic, &expired.name).await,
+    Err(e) => Err(e),
+  };
+  if let Err(e) = result {
+    server_warn(
+      &format!("Archived {}/{} but failed to drop its embedding: {e}", expired.topic, expired.name),
+      "insights-retention",
+    )
+    .await;
+  }
+}
+
+#[cfg(not(feature = "semantic"))]
+async fn remove_embedding(_expired: &insight::Insight) {
+  // No-op: embeddings not available without the semantic feature
+}
+
+/// Get the configured interval between sweeps
+/// Default: 3600 seconds
+/// Setting: insights.retention.interval_secs (INSIGHTS_RETENTION_INTERVAL_SECS)
+pub fn get_sweep_interval() -> Duration {
+  let secs = config::get(&settings::RETENTION_INTERVAL_SECS)
+    .filter(|secs| *secs > 0)
+    .unwrap_or(DEFAULT_SWEEP_INTERVAL_SECS);
+  Duration::from_secs(secs)
+}
+
+/// Spawn the background job that periodically archives expired insights
+#[cfg(not(tarpaulin_include))] // Skip coverage - long-running background task
+pub fn spawn_scheduler(interval: Duration) -> tokio::task::JoinHandle<()> {
+  tokio::spawn(async move {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+      ticker.tick().await;
+      match sweep(Utc::now()).await {
+        Ok(report) => log_sweep(&report).await,
+        Err(e) => server_warn(&format!("Retention sweep failed: {e}"), "insights-retention").await,
+      }
+    }
+  })
+}
+
+async fn log_sweep(report: &SweepReport) {
+  if !report.archived.is_empty() {
+    server_info(
+      &format!(
+        "Archived {} expired insights: {}",
+        report.archived.len(),
+        report.archived.join(", ")
+      ),
+      "insights-retention",
+    )
+    .await;
+  }
+
+  for error in &report.errors {
+    server_warn(&format!("Failed to archive expired insight {error}"), "insights-retention").await;
+  }
+}
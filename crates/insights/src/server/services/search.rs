@@ -1,11 +1,21 @@
+pub mod query;
+
 use anyhow::Result;
+use bentley::config;
 use clap::Args;
 use colored::*;
 
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::server::{models::insight, services::similarity};
+use crate::server::{
+  models::{insight, ranking::RankingConfig, stats, tag, topic},
+  services::similarity,
+  types::{MatchPath, ScoreBreakdown, SearchExplanation, SearchResultData, TermMatch},
+};
+use crate::settings;
 
 // Semantic similarity threshold for meaningful results
 const SEMANTIC_SIMILARITY_THRESHOLD: f32 = 0.2;
@@ -13,21 +23,58 @@ const SEMANTIC_SIMILARITY_THRESHOLD: f32 = 0.2;
 // Default terminal width for text wrapping
 const DEFAULT_TERMINAL_WIDTH: usize = 80;
 
+// Reciprocal-rank fusion damping; 60 is the value from the original RRF paper
+const RRF_K: f32 = 60.0;
+
+// Term matches listed per explained hit
+const MAX_EXPLAINED_MATCHES: usize = 20;
+
+// Characters shown either side of a match in its snippet
+const SNIPPET_CONTEXT: usize = 30;
+
+/// Which backends answer a search
+#[derive(
+  Debug,
+  Default,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  clap::ValueEnum,
+  serde::Serialize,
+  serde::Deserialize,
+  schemars::JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchMode {
+  /// Keyword matches from the full-text index only
+  FullText,
+  /// Embedding similarity only
+  Semantic,
+  /// Keyword matches merged with embedding similarity
+  #[default]
+  Hybrid,
+}
+
 #[derive(Debug)]
 pub struct SearchResult {
   pub topic: String,
   pub name: String,
   pub overview: String,
   pub details: String,
+  pub tags: Vec<String>,
   pub score: f32, // number of matching terms
 }
 
 /// Search configuration options
 #[derive(Args)]
 pub struct SearchCommandOptions {
-  /// Optional topic to restrict search to
+  /// Optional topic to restrict search to, including the topics nested under it
   #[arg(short, long)]
   pub topic: Option<String>,
+  /// Only match insights carrying this tag (repeatable or comma-separated; all must match)
+  #[arg(long = "tag", value_delimiter = ',')]
+  pub tags: Vec<String>,
   /// Case-sensitive search
   #[arg(short, long)]
   pub case_sensitive: bool,
@@ -40,10 +87,84 @@ pub struct SearchCommandOptions {
   /// Use semantic search (term matching + jaccard similarity, no embedding)
   #[arg(short, long)]
   pub semantic: bool,
+  /// Search backend: full-text index, embeddings, or both
+  #[arg(short, long, value_enum, default_value_t = SearchMode::Hybrid)]
+  pub mode: SearchMode,
+  /// Weight of embedding similarity in hybrid ranking, 0.0-1.0 (default: rank fusion)
+  #[arg(long, value_parser = parse_hybrid_weight)]
+  pub hybrid_weight: Option<f32>,
+  /// Boost frequently read insights by up to this share of their score, e.g. 0.5
+  #[arg(long, value_parser = parse_popularity_weight)]
+  pub popularity_weight: Option<f32>,
+  /// Boost recently updated insights by up to this share of their score, e.g. 0.2
+  #[arg(long, value_parser = parse_popularity_weight)]
+  pub recency_weight: Option<f32>,
+  /// Days for the recency boost to halve (default: ranking.yaml, or 30)
+  #[arg(long, value_parser = parse_half_life)]
+  pub recency_half_life: Option<f32>,
+  /// Multiply scores in a topic and its subtopics, e.g. rust=1.5 (repeatable)
+  #[arg(long = "topic-boost", value_parser = parse_topic_boost)]
+  pub topic_boosts: Vec<(String, f32)>,
+  /// Show why each hit matched: retrieval path, scores before fusion, matched terms
+  #[arg(long)]
+  pub explain: bool,
+}
+
+impl SearchCommandOptions {
+  /// Topic boosts given on the command line, the last one winning for a topic
+  pub fn topic_boost_map(&self) -> BTreeMap<String, f32> {
+    self.topic_boosts.iter().cloned().collect()
+  }
+}
+
+/// Accept hybrid weights between 0 and 1
+pub fn parse_hybrid_weight(value: &str) -> std::result::Result<f32, String> {
+  let weight: f32 = value.parse().map_err(|_| format!("'{value}' is not a number"))?;
+  if (0.0..=1.0).contains(&weight) {
+    Ok(weight)
+  } else {
+    Err(format!("{weight} is not between 0.0 and 1.0"))
+  }
+}
+
+/// Accept any non-negative popularity weight
+pub fn parse_popularity_weight(value: &str) -> std::result::Result<f32, String> {
+  let weight: f32 = value.parse().map_err(|_| format!("'{value}' is not a number"))?;
+  if weight.is_finite() && weight >= 0.0 {
+    Ok(weight)
+  } else {
+    Err(format!("{weight} is not a non-negative number"))
+  }
+}
+
+/// Accept a positive number of days
+pub fn parse_half_life(value: &str) -> std::result::Result<f32, String> {
+  let days: f32 = value.parse().map_err(|_| format!("'{value}' is not a number"))?;
+  if days.is_finite() && days > 0.0 {
+    Ok(days)
+  } else {
+    Err(format!("{days} is not a positive number of days"))
+  }
+}
+
+/// Accept `topic=factor` with a positive factor
+pub fn parse_topic_boost(value: &str) -> std::result::Result<(String, f32), String> {
+  let (topic, boost) =
+    value.rsplit_once('=').ok_or_else(|| format!("'{value}' is not in the form topic=factor"))?;
+  if let Some(problem) = topic::invalid(topic) {
+    return Err(problem);
+  }
+  let boost: f32 = boost.parse().map_err(|_| format!("'{boost}' is not a number"))?;
+  if boost.is_finite() && boost > 0.0 {
+    Ok((topic.to_string(), boost))
+  } else {
+    Err(format!("{boost} is not a positive boost"))
+  }
 }
 
 pub struct SearchOptions {
   pub topic: Option<String>,
+  pub tags: Vec<String>,
   pub case_sensitive: bool,
   pub overview_only: bool,
   pub exact: bool,
@@ -54,6 +175,7 @@ impl SearchOptions {
   pub fn from(options: &SearchCommandOptions) -> Self {
     Self {
       topic: options.topic.clone(),
+      tags: options.tags.clone(),
       case_sensitive: options.case_sensitive,
       overview_only: options.overview_only,
       exact: options.exact,
@@ -120,6 +242,9 @@ fn search_topic(
 
       if insight::is_insight_file(&path) {
         let insight = insight::load_from_path(&path)?;
+        if !tag::has_all(&insight.tags, &options.tags) {
+          continue;
+        }
         if let Ok(Some(result)) =
           search_insight(&insight, search_strategy, terms, threshold, options)
         {
@@ -146,6 +271,7 @@ fn search_insight(
       name: insight.name.to_string(),
       overview: insight.overview.to_string(),
       details: insight.details.to_string(),
+      tags: insight.tags.clone(),
       score,
     }))
   } else {
@@ -187,6 +313,221 @@ fn get_semantic_match(
   similarity::semantic(&normalized_terms.into_iter().collect(), &normalized_content)
 }
 
+/// Merge keyword and semantic rankings into one, best match first
+///
+/// Without a weight, reciprocal-rank fusion rewards insights ranked highly by either
+/// path. With a weight, each path's scores are scaled to 0-1 and blended, `weight`
+/// being the share given to semantic similarity.
+pub fn fuse(
+  keyword: Vec<SearchResultData>,
+  semantic: Vec<SearchResultData>,
+  weight: Option<f32>,
+) -> Vec<SearchResultData> {
+  let keyword_scores = match weight {
+    Some(_) => normalized_scores(&keyword),
+    None => reciprocal_ranks(&keyword),
+  };
+  let semantic_scores = match weight {
+    Some(_) => normalized_scores(&semantic),
+    None => reciprocal_ranks(&semantic),
+  };
+  let (keyword_weight, semantic_weight) = weight.map_or((1.0, 1.0), |w| (1.0 - w, w));
+
+  let mut fused: Vec<SearchResultData> = Vec::new();
+  let mut positions: HashMap<(String, String), usize> = HashMap::new();
+  let weighted =
+    keyword.into_iter().zip(keyword_scores.into_iter().map(|score| score * keyword_weight)).chain(
+      semantic.into_iter().zip(semantic_scores.into_iter().map(|score| score * semantic_weight)),
+    );
+
+  for (result, score) in weighted {
+    let key = (result.topic.clone(), result.name.clone());
+    match positions.get(&key) {
+      Some(&index) => {
+        fused[index].score += score;
+        if let (Some(existing), Some(other)) = (&mut fused[index].explanation, result.explanation) {
+          existing.merge(other);
+        }
+      }
+      None => {
+        positions.insert(key, fused.len());
+        fused.push(SearchResultData { score, ..result });
+      }
+    }
+  }
+
+  sort_by_score(&mut fused);
+  fused
+}
+
+/// RRF contribution of each position in an already ranked list
+fn reciprocal_ranks(results: &[SearchResultData]) -> Vec<f32> {
+  ranked_positions(results).into_iter().map(|rank| 1.0 / (RRF_K + rank as f32 + 1.0)).collect()
+}
+
+/// Scores scaled so the best result is 1 and the worst 0 (all 1 if they are equal)
+fn normalized_scores(results: &[SearchResultData]) -> Vec<f32> {
+  let max = results.iter().map(|r| r.score).fold(f32::NEG_INFINITY, f32::max);
+  let min = results.iter().map(|r| r.score).fold(f32::INFINITY, f32::min);
+  let range = max - min;
+  results.iter().map(|r| if range > f32::EPSILON { (r.score - min) / range } else { 1.0 }).collect()
+}
+
+/// Zero-based rank of each result by descending score, keeping input order for ties
+fn ranked_positions(results: &[SearchResultData]) -> Vec<usize> {
+  let mut order: Vec<usize> = (0..results.len()).collect();
+  order.sort_by(|&a, &b| {
+    results[b].score.partial_cmp(&results[a].score).unwrap_or(std::cmp::Ordering::Equal)
+  });
+  let mut ranks = vec![0; results.len()];
+  for (rank, index) in order.into_iter().enumerate() {
+    ranks[index] = rank;
+  }
+  ranks
+}
+
+/// Get the configured weight of the popularity boost
+/// Default: 0.0 (ranking ignores access stats)
+/// Setting: insights.search.popularity_weight (INSIGHTS_POPULARITY_WEIGHT)
+pub fn get_popularity_weight() -> f32 {
+  config::get(&settings::POPULARITY_WEIGHT)
+    .filter(|weight: &f32| weight.is_finite() && *weight >= 0.0)
+    .unwrap_or(0.0)
+}
+
+/// Apply recency decay and topic boosts, recording each factor, and re-rank, best first
+///
+/// `last_updated` looks up when an insight last changed; insights it can't date get
+/// no recency bonus.
+pub fn apply_ranking(
+  results: &mut [SearchResultData],
+  config: &RankingConfig,
+  last_updated: impl Fn(&str, &str) -> Option<DateTime<Utc>>,
+  now: DateTime<Utc>,
+) {
+  if config.is_neutral() {
+    return;
+  }
+
+  for result in results.iter_mut() {
+    let recency = last_updated(&result.topic, &result.name).map_or(1.0, |updated| {
+      let age_days = now.signed_duration_since(updated).num_seconds() as f32 / 86_400.0;
+      similarity::recency_factor(age_days, config.recency_half_life_days, config.recency_weight)
+    });
+    let topic_boost = config.topic_boost(&result.topic);
+
+    let breakdown = breakdown(result);
+    breakdown.recency = recency;
+    breakdown.topic_boost = topic_boost;
+    result.score = similarity::combine(result.score, recency, topic_boost);
+  }
+  sort_by_score(results);
+}
+
+/// The result's score breakdown, started from its current score if it has none
+fn breakdown(result: &mut SearchResultData) -> &mut ScoreBreakdown {
+  let score = result.score;
+  result.ranking.get_or_insert_with(|| ScoreBreakdown::new(score))
+}
+
+/// Best score first, then by topic and name
+fn sort_by_score(results: &mut [SearchResultData]) {
+  results.sort_by(|a, b| {
+    b.score
+      .partial_cmp(&a.score)
+      .unwrap_or(std::cmp::Ordering::Equal)
+      .then_with(|| a.topic.cmp(&b.topic).then_with(|| a.name.cmp(&b.name)))
+  });
+}
+
+/// Raise scores of frequently read insights and re-rank, best first
+///
+/// Each score is multiplied by `1 + weight * popularity`, so the most read insight
+/// gains `weight` times its score and unread insights keep theirs. Scaling rather
+/// than adding keeps the boost meaningful whatever backend produced the scores.
+pub fn boost_by_popularity(results: &mut [SearchResultData], log: &stats::AccessLog, weight: f32) {
+  if weight <= 0.0 {
+    return;
+  }
+
+  for result in results.iter_mut() {
+    let popularity = 1.0 + weight * stats::popularity(log, &result.topic, &result.name);
+    breakdown(result).popularity = popularity;
+    result.score *= popularity;
+  }
+  sort_by_score(results);
+}
+
+/// Record which retrieval path found each result, with the score it gave
+pub fn mark_path(results: &mut [SearchResultData], path: MatchPath) {
+  for result in results.iter_mut() {
+    result.explanation = Some(SearchExplanation::found(path, result.score));
+  }
+}
+
+/// Where the terms occur in a result's fields, up to a limit, in field order
+pub fn term_matches(
+  result: &SearchResultData,
+  terms: &[String],
+  case_sensitive: bool,
+  overview_only: bool,
+) -> Vec<TermMatch> {
+  let mut fields = vec![
+    ("topic", result.topic.as_str()),
+    ("name", result.name.as_str()),
+    ("overview", result.overview.as_str()),
+  ];
+  if !overview_only {
+    fields.push(("details", result.details.as_str()));
+  }
+
+  let fold = |c: char| if case_sensitive { c } else { c.to_lowercase().next().unwrap_or(c) };
+  let mut matches = Vec::new();
+  for (field, text) in fields {
+    let chars: Vec<char> = text.chars().collect();
+    let folded: Vec<char> = chars.iter().copied().map(fold).collect();
+    let mut found: Vec<TermMatch> = Vec::new();
+    for term in terms.iter().filter(|term| !term.is_empty()) {
+      let needle: Vec<char> = term.chars().map(fold).collect();
+      let mut start = 0;
+      while let Some(offset) = find_chars(&folded[start..], &needle) {
+        let (from, to) = (start + offset, start + offset + needle.len());
+        found.push(TermMatch {
+          term: term.clone(),
+          field: field.to_string(),
+          start: from,
+          end: to,
+          snippet: snippet(&chars, from, to),
+        });
+        start = to;
+      }
+    }
+    found.sort_by_key(|found| found.start);
+    matches.extend(found);
+  }
+  matches.truncate(MAX_EXPLAINED_MATCHES);
+  matches
+}
+
+/// Position of `needle` in `haystack`, both as characters
+fn find_chars(haystack: &[char], needle: &[char]) -> Option<usize> {
+  if needle.is_empty() || needle.len() > haystack.len() {
+    return None;
+  }
+  haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// The characters from `start` to `end` with some context, on one line
+fn snippet(chars: &[char], start: usize, end: usize) -> String {
+  let from = start.saturating_sub(SNIPPET_CONTEXT);
+  let to = (end + SNIPPET_CONTEXT).min(chars.len());
+  let text: String = chars[from..to].iter().collect();
+  let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+  let before = if from > 0 { "…" } else { "" };
+  let after = if to < chars.len() { "…" } else { "" };
+  format!("{before}{text}{after}")
+}
+
 /// Highlight search terms
 fn highlight_keywords(text: &str, terms: &[String]) -> String {
   let mut result = text.to_string();
@@ -225,10 +566,10 @@ fn highlight_keywords(text: &str, terms: &[String]) -> String {
   result
 }
 
-/// Build search paths based on topic filter
+/// Build search paths based on topic filter, which also covers nested topics
 fn get_search_paths(insights_root: &Path, topic_filter: Option<&str>) -> Result<Vec<PathBuf>> {
-  if let Some(topic) = topic_filter {
-    Ok(vec![insights_root.join(topic)])
+  if let Some(topic_prefix) = topic_filter {
+    Ok(topic::dirs_within(insights_root, topic_prefix))
   } else {
     Ok(insight::get_topics()?.into_iter().map(|topic| insights_root.join(topic)).collect())
   }
@@ -322,15 +663,24 @@ mod tests {
   fn test_search_options_from_command_options() {
     let cmd_options = SearchCommandOptions {
       topic: Some("test_topic".to_string()),
+      tags: vec!["testing".to_string()],
       case_sensitive: true,
       overview_only: true,
       exact: false,
       semantic: true,
+      mode: SearchMode::Hybrid,
+      hybrid_weight: None,
+      popularity_weight: None,
+      recency_weight: None,
+      recency_half_life: None,
+      topic_boosts: Vec::new(),
+      explain: false,
     };
 
     let options = SearchOptions::from(&cmd_options);
 
     assert_eq!(options.topic, Some("test_topic".to_string()));
+    assert_eq!(options.tags, vec!["testing"]);
     assert!(options.case_sensitive);
     assert!(options.overview_only);
     assert!(!options.exact);
@@ -342,6 +692,7 @@ mod tests {
     let insight = create_test_insight();
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: false,
       overview_only: true,
       exact: false,
@@ -357,6 +708,7 @@ mod tests {
     let insight = create_test_insight();
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: false,
       overview_only: false,
       exact: false,
@@ -373,6 +725,7 @@ mod tests {
     let terms = vec!["Test".to_string(), "CONTENT".to_string()];
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: true,
       overview_only: false,
       exact: false,
@@ -388,6 +741,7 @@ mod tests {
     let terms = vec!["Test".to_string(), "CONTENT".to_string()];
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: false,
       overview_only: false,
       exact: false,
@@ -404,6 +758,7 @@ mod tests {
     let terms = vec!["test".to_string()];
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: false,
       overview_only: false,
       exact: true,
@@ -421,6 +776,7 @@ mod tests {
     let terms = vec!["test".to_string(), "content".to_string()];
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: false,
       overview_only: false,
       exact: true,
@@ -438,6 +794,7 @@ mod tests {
     let terms = vec!["Test".to_string()]; // Capital T
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: true,
       overview_only: false,
       exact: true,
@@ -451,6 +808,7 @@ mod tests {
       &["test".to_string()],
       &SearchOptions {
         topic: None,
+        tags: Vec::new(),
         case_sensitive: false,
         overview_only: false,
         exact: true,
@@ -467,6 +825,7 @@ mod tests {
     let terms = vec!["nonexistent".to_string()];
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: false,
       overview_only: false,
       exact: true,
@@ -483,6 +842,7 @@ mod tests {
     let terms = vec!["test".to_string()];
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: false,
       overview_only: false,
       exact: true,
@@ -506,6 +866,7 @@ mod tests {
     let terms = vec!["nonexistent".to_string()];
     let options = SearchOptions {
       topic: None,
+      tags: Vec::new(),
       case_sensitive: false,
       overview_only: false,
       exact: true,
@@ -606,6 +967,7 @@ mod tests {
       name: "test_insight".to_string(),
       overview: "Test overview".to_string(),
       details: "Test details".to_string(),
+      tags: Vec::new(),
       score: 2.5,
     };
 
@@ -634,6 +996,7 @@ mod tests {
         name: "insight1".to_string(),
         overview: "Overview 1".to_string(),
         details: "Details 1".to_string(),
+        tags: Vec::new(),
         score: 1.0,
       },
       SearchResult {
@@ -641,6 +1004,7 @@ mod tests {
         name: "insight2".to_string(),
         overview: "Overview 2".to_string(),
         details: "Details 2".to_string(),
+        tags: Vec::new(),
         score: 2.0,
       },
     ];
@@ -650,4 +1014,152 @@ mod tests {
     // Should not panic when displaying results
     display_results(&results, &terms, false);
   }
+
+  fn ranked(names: &[(&str, f32)]) -> Vec<SearchResultData> {
+    names
+      .iter()
+      .map(|(name, score)| SearchResultData {
+        topic: "t".to_string(),
+        name: name.to_string(),
+        overview: String::new(),
+        details: String::new(),
+        tags: Vec::new(),
+        score: *score,
+        ranking: None,
+        explanation: None,
+      })
+      .collect()
+  }
+
+  fn order(results: &[SearchResultData]) -> Vec<&str> {
+    results.iter().map(|r| r.name.as_str()).collect()
+  }
+
+  #[test]
+  fn test_fuse_rank_fusion_favors_results_found_by_both() {
+    let keyword = ranked(&[("exact-id", 12.0), ("both", 7.5), ("keyword-only", 3.0)]);
+    let semantic = ranked(&[("paraphrase", 0.92), ("both", 0.81)]);
+
+    let fused = fuse(keyword, semantic, None);
+
+    assert_eq!(order(&fused), vec!["both", "exact-id", "paraphrase", "keyword-only"]);
+  }
+
+  #[test]
+  fn test_fuse_weight_blends_normalized_scores() {
+    let keyword = || ranked(&[("a", 10.0), ("b", 5.0), ("c", 0.0)]);
+    let semantic = || ranked(&[("c", 0.9), ("b", 0.5), ("a", 0.1)]);
+
+    assert_eq!(order(&fuse(keyword(), semantic(), Some(0.0))), vec!["a", "b", "c"]);
+    assert_eq!(order(&fuse(keyword(), semantic(), Some(1.0))), vec!["c", "b", "a"]);
+
+    let blended = fuse(keyword(), semantic(), Some(0.6));
+    assert_eq!(order(&blended), vec!["c", "b", "a"]);
+    assert!((blended[0].score - 0.6).abs() < 1e-6);
+  }
+
+  #[test]
+  fn test_boost_by_popularity_reranks_read_insights() {
+    let now = chrono::Utc::now();
+    let mut log = stats::AccessLog::new();
+    for (name, reads) in [("popular", 99), ("known", 9)] {
+      let entry = stats::AccessStats { reads, search_hits: 0, last_accessed: now };
+      log.insert(stats::key("t", name), entry);
+    }
+
+    let mut results = ranked(&[("best-match", 1.0), ("known", 0.9), ("popular", 0.6)]);
+    boost_by_popularity(&mut results, &log, 0.0);
+    assert_eq!(order(&results), vec!["best-match", "known", "popular"]);
+
+    boost_by_popularity(&mut results, &log, 1.0);
+    assert_eq!(order(&results), vec!["known", "popular", "best-match"]);
+    assert!((results[1].score - 1.2).abs() < 1e-6);
+    assert!((results[0].score - 1.35).abs() < 1e-6);
+  }
+
+  #[test]
+  fn test_apply_ranking_favors_recent_and_boosted_topics() {
+    let now = chrono::Utc::now();
+    let mut results = ranked(&[("old", 1.0), ("fresh", 0.9), ("undated", 0.85)]);
+    results[2].topic = "rust/async".to_string();
+
+    apply_ranking(&mut results, &RankingConfig::default(), |_, _| None, now);
+    assert!(results.iter().all(|result| result.ranking.is_none()));
+
+    let boosts = BTreeMap::from([("rust".to_string(), 1.5)]);
+    let config = RankingConfig::default().with_overrides(Some(0.2), Some(30.0), &boosts);
+    let updated = |_: &str, name: &str| match name {
+      "old" => Some(now - chrono::Duration::days(365)),
+      "fresh" => Some(now),
+      _ => None,
+    };
+    apply_ranking(&mut results, &config, updated, now);
+
+    assert_eq!(order(&results), vec!["undated", "fresh", "old"]);
+    let fresh = results[1].ranking.unwrap();
+    assert_eq!((fresh.similarity, fresh.recency, fresh.topic_boost), (0.9, 1.2, 1.0));
+    assert!((results[1].score - 1.08).abs() < 1e-6);
+    assert_eq!(results[0].ranking.unwrap().topic_boost, 1.5);
+    assert_eq!(results[0].ranking.unwrap().recency, 1.0);
+  }
+
+  #[test]
+  fn test_fuse_merges_explanations_from_both_paths() {
+    let mut keyword = ranked(&[("both", 7.5), ("keyword-only", 3.0)]);
+    let mut semantic = ranked(&[("both", 0.81)]);
+    mark_path(&mut keyword, MatchPath::Keyword);
+    mark_path(&mut semantic, MatchPath::Semantic);
+
+    let fused = fuse(keyword, semantic, None);
+    let both = fused[0].explanation.as_ref().unwrap();
+    assert_eq!(both.matched_by, MatchPath::Both);
+    assert_eq!((both.keyword_score, both.semantic_score), (Some(7.5), Some(0.81)));
+    let keyword_only = fused[1].explanation.as_ref().unwrap();
+    assert_eq!(keyword_only.matched_by, MatchPath::Keyword);
+    assert_eq!(keyword_only.semantic_score, None);
+  }
+
+  #[test]
+  fn test_term_matches_report_character_offsets() {
+    let mut result = ranked(&[("Ünïcode tips", 1.0)]).remove(0);
+    result.overview = "Use spawn_blocking, not SPAWN".to_string();
+    result.details = format!("{} then spawn again", "x ".repeat(40));
+
+    let terms = vec!["spawn".to_string(), "tips".to_string()];
+    let matches = term_matches(&result, &terms, false, false);
+    let found: Vec<(&str, &str, usize, usize)> =
+      matches.iter().map(|m| (m.field.as_str(), m.term.as_str(), m.start, m.end)).collect();
+    assert_eq!(
+      found,
+      [
+        ("name", "tips", 8, 12),
+        ("overview", "spawn", 4, 9),
+        ("overview", "spawn", 24, 29),
+        ("details", "spawn", 86, 91),
+      ]
+    );
+    assert_eq!(matches[2].snippet, "Use spawn_blocking, not SPAWN");
+    assert!(matches[3].snippet.starts_with('…') && matches[3].snippet.ends_with("again"));
+
+    assert_eq!(term_matches(&result, &terms, true, true).len(), 2);
+  }
+
+  #[test]
+  fn test_parse_topic_boost() {
+    assert_eq!(parse_topic_boost("rust/async=1.5"), Ok(("rust/async".to_string(), 1.5)));
+    assert!(parse_topic_boost("rust").is_err());
+    assert!(parse_topic_boost("rust=0").is_err());
+    assert!(parse_topic_boost("=2").is_err());
+    assert_eq!(parse_half_life("14"), Ok(14.0));
+    assert!(parse_half_life("0").is_err());
+  }
+
+  #[test]
+  fn test_parse_hybrid_weight() {
+    assert_eq!(parse_hybrid_weight("0.6"), Ok(0.6));
+    assert!(parse_hybrid_weight("1.5").is_err());
+    assert!(parse_hybrid_weight("high").is_err());
+    assert_eq!(parse_popularity_weight("2"), Ok(2.0));
+    assert!(parse_popularity_weight("-0.5").is_err());
+  }
 }
@@ -0,0 +1,398 @@
+//! Structured search queries
+//!
+//! A query such as `topic:rust tag:async "exact phrase" -deprecated before:2024-06`
+//! parses into plain words, which drive keyword and embedding retrieval exactly as
+//! before, and a list of [`Clause`]s that every result must then satisfy:
+//!
+//! - `topic:<topic>` - filed under the topic or one nested below it
+//! - `tag:<tag>` - carries the tag
+//! - `"some words"` - contains the words in this order (they are also searched for)
+//! - `before:<date>` / `after:<date>` - last updated before the date starts or after
+//!   it ends, where a date is `YYYY`, `YYYY-MM` or `YYYY-MM-DD`
+//! - `-<anything>` - negates a clause; `-word` drops results that mention the word
+//!
+//! Matching ignores case. Anything that looks like `key:value` with an unknown key is
+//! kept as a plain word, so URLs and `std::fs` still search as typed.
+
+use chrono::{Datelike, Months, NaiveDate};
+
+use crate::server::models::insight::Insight;
+use crate::server::models::{tag, topic};
+
+/// A condition a search result has to meet
+#[derive(Debug, Clone, PartialEq)]
+pub enum Clause {
+  /// Mentions this word (only written negated, `-word`; bare words rank instead)
+  Word(String),
+  /// Contains these words in this order
+  Phrase(String),
+  /// Filed under this topic or one nested below it
+  Topic(String),
+  /// Carries this tag
+  Tag(String),
+  /// Last updated before this day
+  Before(NaiveDate),
+  /// Last updated on or after this day
+  After(NaiveDate),
+  /// The inner clause does not hold
+  Not(Box<Clause>),
+}
+
+/// A parsed search query
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+  /// Words handed to retrieval, including those of quoted phrases
+  pub terms: Vec<String>,
+  /// Filters applied to whatever retrieval returns; all of them must hold
+  pub clauses: Vec<Clause>,
+}
+
+impl Query {
+  /// Parse query syntax, rejecting unbalanced quotes, empty filters and bad dates
+  pub fn parse(input: &str) -> Result<Self, String> {
+    let mut query = Query::default();
+    let mut chars = input.chars().peekable();
+
+    loop {
+      while chars.next_if(|c| c.is_whitespace()).is_some() {}
+      let Some(&first) = chars.peek() else { break };
+
+      let negated = first == '-';
+      if negated {
+        chars.next();
+      }
+
+      let token = read_token(&mut chars)?;
+      let clause = match token {
+        Token::Phrase(phrase) => {
+          if !negated {
+            query.terms.extend(phrase.split_whitespace().map(str::to_string));
+          }
+          Clause::Phrase(phrase)
+        }
+        Token::Field(key, value) => field_clause(&key, value)?,
+        Token::Word(word) if negated => Clause::Word(word),
+        Token::Word(word) => {
+          query.terms.push(word);
+          continue;
+        }
+        Token::Empty => continue,
+      };
+
+      query.clauses.push(if negated { Clause::Not(Box::new(clause)) } else { clause });
+    }
+
+    Ok(query)
+  }
+
+  /// Whether the query narrows results beyond ranking them
+  pub fn has_filters(&self) -> bool {
+    !self.clauses.is_empty()
+  }
+
+  /// Whether an insight passes every clause
+  pub fn matches(&self, insight: &Insight) -> bool {
+    let content = Content::of(insight);
+    self.clauses.iter().all(|clause| clause.matches(insight, &content))
+  }
+}
+
+impl Clause {
+  fn matches(&self, insight: &Insight, content: &Content) -> bool {
+    match self {
+      Clause::Word(word) => content.has_word(word),
+      Clause::Phrase(phrase) => content.has_phrase(phrase),
+      Clause::Topic(prefix) => topic::within(&insight.topic, prefix),
+      Clause::Tag(wanted) => tag::has_all(&insight.tags, std::slice::from_ref(wanted)),
+      Clause::Before(day) => insight.last_updated.date_naive() < *day,
+      Clause::After(day) => insight.last_updated.date_naive() >= *day,
+      Clause::Not(inner) => !inner.matches(insight, content),
+    }
+  }
+}
+
+/// Re-quote command-line arguments the shell already unquoted
+///
+/// `insights search "exact phrase"` arrives as one argument with a space in it,
+/// which is only a phrase again once it is wrapped in quotes.
+pub fn quote_args(args: &[String]) -> Vec<String> {
+  args
+    .iter()
+    .map(|arg| {
+      if arg.contains(char::is_whitespace) && !arg.contains('"') {
+        match arg.split_once(':').filter(|(key, _)| is_field(key)) {
+          Some((key, value)) => format!("{key}:\"{value}\""),
+          None => format!("\"{arg}\""),
+        }
+      } else {
+        arg.clone()
+      }
+    })
+    .collect()
+}
+
+enum Token {
+  Word(String),
+  Phrase(String),
+  Field(String, String),
+  Empty,
+}
+
+const FIELDS: [&str; 4] = ["topic", "tag", "before", "after"];
+
+fn is_field(key: &str) -> bool {
+  FIELDS.contains(&key.to_lowercase().as_str())
+}
+
+fn read_token(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Token, String> {
+  if chars.next_if_eq(&'"').is_some() {
+    let phrase = read_quoted(chars)?;
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    return Ok(if phrase.is_empty() { Token::Empty } else { Token::Phrase(phrase) });
+  }
+
+  let mut word = String::new();
+  while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+    if c == ':' && is_field(&word) {
+      let value = if chars.next_if_eq(&'"').is_some() {
+        read_quoted(chars)?
+      } else {
+        std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace())).collect()
+      };
+      return Ok(Token::Field(word.to_lowercase(), value.trim().to_string()));
+    }
+    word.push(c);
+  }
+
+  Ok(if word.is_empty() { Token::Empty } else { Token::Word(word) })
+}
+
+fn read_quoted(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+  let mut quoted = String::new();
+  for c in chars.by_ref() {
+    if c == '"' {
+      return Ok(quoted);
+    }
+    quoted.push(c);
+  }
+  Err(format!("Unclosed quote in search query: \"{quoted}"))
+}
+
+fn field_clause(key: &str, value: String) -> Result<Clause, String> {
+  if value.is_empty() {
+    return Err(format!("'{key}:' needs a value"));
+  }
+
+  match key {
+    "topic" => Ok(Clause::Topic(value)),
+    "tag" => Ok(Clause::Tag(value)),
+    "before" => parse_period(&value).map(|(start, _)| Clause::Before(start)),
+    "after" => parse_period(&value).map(|(_, end)| Clause::After(end)),
+    _ => unreachable!("only known fields are parsed as fields"),
+  }
+}
+
+/// The first day of a `YYYY`, `YYYY-MM` or `YYYY-MM-DD` period and the first day after it
+fn parse_period(value: &str) -> Result<(NaiveDate, NaiveDate), String> {
+  let invalid = || format!("'{value}' is not a date (expected YYYY, YYYY-MM or YYYY-MM-DD)");
+  let parts: Vec<&str> = value.split('-').collect();
+  let numbers: Vec<u32> =
+    parts.iter().map(|part| part.parse().map_err(|_| invalid())).collect::<Result<_, _>>()?;
+
+  let (start, months) = match numbers[..] {
+    [year] => (NaiveDate::from_ymd_opt(year as i32, 1, 1), 12),
+    [year, month] => (NaiveDate::from_ymd_opt(year as i32, month, 1), 1),
+    [year, month, day] => {
+      let start = NaiveDate::from_ymd_opt(year as i32, month, day).ok_or_else(invalid)?;
+      return Ok((start, start.succ_opt().ok_or_else(invalid)?));
+    }
+    _ => return Err(invalid()),
+  };
+
+  let start = start.filter(|start| start.year() > 0).ok_or_else(invalid)?;
+  let end = start.checked_add_months(Months::new(months)).ok_or_else(invalid)?;
+  Ok((start, end))
+}
+
+/// Lowercased text of an insight, for word and phrase matching
+struct Content {
+  text: String,
+}
+
+impl Content {
+  fn of(insight: &Insight) -> Self {
+    let text = [&insight.name, &insight.overview, &insight.details]
+      .iter()
+      .flat_map(|part| part.split_whitespace())
+      .collect::<Vec<_>>()
+      .join(" ")
+      .to_lowercase();
+    Self { text }
+  }
+
+  fn has_word(&self, word: &str) -> bool {
+    let word = word.to_lowercase();
+    self.text.split(|c: char| !c.is_alphanumeric() && c != '_').any(|candidate| candidate == word)
+      || (word.contains(|c: char| !c.is_alphanumeric() && c != '_') && self.text.contains(&word))
+  }
+
+  fn has_phrase(&self, phrase: &str) -> bool {
+    self.text.contains(&phrase.to_lowercase())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::{TimeZone, Utc};
+
+  fn insight(topic: &str, name: &str, details: &str, tags: &[&str], updated: &str) -> Insight {
+    let mut insight = Insight::new(
+      topic.to_string(),
+      name.to_string(),
+      "Overview".to_string(),
+      details.to_string(),
+    );
+    insight.tags = tags.iter().map(|tag| tag.to_string()).collect();
+    let day = NaiveDate::parse_from_str(updated, "%Y-%m-%d").unwrap();
+    insight.last_updated = Utc.from_utc_datetime(&day.and_hms_opt(12, 0, 0).unwrap());
+    insight
+  }
+
+  fn day(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+  }
+
+  #[test]
+  fn test_parse_full_example() {
+    let query =
+      Query::parse(r#"topic:rust tag:async "exact phrase" -deprecated before:2024-06 tokio"#)
+        .unwrap();
+
+    assert_eq!(query.terms, ["exact", "phrase", "tokio"]);
+    assert_eq!(
+      query.clauses,
+      [
+        Clause::Topic("rust".to_string()),
+        Clause::Tag("async".to_string()),
+        Clause::Phrase("exact phrase".to_string()),
+        Clause::Not(Box::new(Clause::Word("deprecated".to_string()))),
+        Clause::Before(day("2024-06-01")),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_plain_words_only_rank() {
+    let query = Query::parse("  spawn_blocking   runtime ").unwrap();
+    assert_eq!(query.terms, ["spawn_blocking", "runtime"]);
+    assert!(!query.has_filters());
+    assert_eq!(Query::parse("").unwrap(), Query::default());
+  }
+
+  #[test]
+  fn test_unknown_keys_stay_words() {
+    let query = Query::parse("https://docs.rs std::fs Topic:Rust").unwrap();
+    assert_eq!(query.terms, ["https://docs.rs", "std::fs"]);
+    assert_eq!(query.clauses, [Clause::Topic("Rust".to_string())]);
+  }
+
+  #[test]
+  fn test_negated_and_quoted_fields() {
+    let query =
+      Query::parse(r#"-tag:legacy -"old api" topic:"ci/github actions" -topic:rust/old"#).unwrap();
+
+    assert!(query.terms.is_empty(), "negated phrases are not searched for");
+    assert_eq!(
+      query.clauses,
+      [
+        Clause::Not(Box::new(Clause::Tag("legacy".to_string()))),
+        Clause::Not(Box::new(Clause::Phrase("old api".to_string()))),
+        Clause::Topic("ci/github actions".to_string()),
+        Clause::Not(Box::new(Clause::Topic("rust/old".to_string()))),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_phrase_whitespace_is_collapsed_and_empty_parts_skipped() {
+    let query = Query::parse("\"  exact \t phrase \" \"\" - ").unwrap();
+    assert_eq!(query.clauses, [Clause::Phrase("exact phrase".to_string())]);
+    assert_eq!(query.terms, ["exact", "phrase"]);
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    assert!(Query::parse(r#"tokio "unclosed"#).unwrap_err().contains("Unclosed quote"));
+    assert!(Query::parse("tag:").unwrap_err().contains("'tag:' needs a value"));
+    assert!(Query::parse(r#"topic:"""#).is_err());
+    for date in ["2024-13", "2024-02-30", "June", "2024-06-01-01", "0-01", "-2024"] {
+      assert!(Query::parse(&format!("before:{date}")).is_err(), "{date} should be rejected");
+    }
+  }
+
+  #[test]
+  fn test_date_periods() {
+    assert_eq!(parse_period("2024").unwrap(), (day("2024-01-01"), day("2025-01-01")));
+    assert_eq!(parse_period("2024-12").unwrap(), (day("2024-12-01"), day("2025-01-01")));
+    assert_eq!(parse_period("2024-02-29").unwrap(), (day("2024-02-29"), day("2024-03-01")));
+
+    let query = Query::parse("after:2024-06").unwrap();
+    assert_eq!(query.clauses, [Clause::After(day("2024-07-01"))]);
+  }
+
+  #[test]
+  fn test_matches_filters() {
+    let fresh = insight("rust/async", "tokio", "Use spawn_blocking.", &["async"], "2024-07-02");
+    let stale = insight("rust", "futures", "Deprecated: use tokio", &["async"], "2024-05-31");
+    let other = insight("python", "asyncio", "Event loops", &["Async"], "2023-01-01");
+
+    let matching = |query: &str| -> Vec<String> {
+      let query = Query::parse(query).unwrap();
+      [&fresh, &stale, &other]
+        .iter()
+        .filter(|insight| query.matches(insight))
+        .map(|insight| insight.name.clone())
+        .collect()
+    };
+
+    assert_eq!(matching("topic:rust"), ["tokio", "futures"]);
+    assert_eq!(matching("topic:RUST/async"), ["tokio"]);
+    assert_eq!(matching("tag:async"), ["tokio", "futures", "asyncio"]);
+    assert_eq!(matching("-deprecated"), ["tokio", "asyncio"]);
+    assert_eq!(matching("-spawn_blocking"), ["futures", "asyncio"]);
+    assert_eq!(matching("-spawn"), ["tokio", "futures", "asyncio"], "words match whole words");
+    assert_eq!(matching(r#""USE   TOKIO""#), ["futures"]);
+    assert_eq!(matching("before:2024-06"), ["futures", "asyncio"]);
+    assert_eq!(matching("after:2024-06"), ["tokio"]);
+    assert_eq!(matching("after:2023 before:2024-06-01"), ["futures"]);
+    assert_eq!(matching("-topic:rust -tag:missing"), ["asyncio"]);
+    assert_eq!(matching("tokio rust"), ["tokio", "futures", "asyncio"], "plain words never filter");
+  }
+
+  #[test]
+  fn test_quote_args_restores_shell_quoting() {
+    let args: Vec<String> =
+      ["exact phrase", "topic:ci/github actions", "tokio", r#"say "hi" there"#, "-legacy"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+    assert_eq!(
+      quote_args(&args),
+      [
+        r#""exact phrase""#,
+        r#"topic:"ci/github actions""#,
+        "tokio",
+        r#"say "hi" there"#,
+        "-legacy"
+      ]
+    );
+
+    let query = Query::parse(&quote_args(&args[..2]).join(" ")).unwrap();
+    assert_eq!(
+      query.clauses,
+      [Clause::Phrase("exact phrase".to_string()), Clause::Topic("ci/github actions".to_string())]
+    );
+  }
+}
@@ -0,0 +1,154 @@
+//! Detection of secrets and personal data in insight content
+//!
+//! Insights are plain markdown that gets embedded and shared, so obvious
+//! credentials and PII are blocked before they reach disk or the index.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::server::models::insight;
+
+/// A single suspicious match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+  /// What kind of data was detected (e.g. "github token")
+  pub kind: &'static str,
+  /// 1-based line within the scanned text
+  pub line: usize,
+  /// Redacted excerpt that is safe to display
+  pub excerpt: String,
+}
+
+/// Findings for a stored insight
+#[derive(Debug, Clone)]
+pub struct InsightFindings {
+  pub topic: String,
+  pub name: String,
+  pub findings: Vec<Finding>,
+}
+
+struct Pattern {
+  kind: &'static str,
+  regex: Regex,
+  check: fn(&str) -> bool,
+}
+
+fn pattern(kind: &'static str, regex: &str) -> Pattern {
+  Pattern { kind, regex: Regex::new(regex).expect("valid sensitive pattern"), check: |_| true }
+}
+
+static PATTERNS: Lazy<Vec<Pattern>> = Lazy::new(|| {
+  vec![
+    pattern("private key", r"-----BEGIN (?:[A-Z0-9]+ )*PRIVATE KEY-----"),
+    pattern("aws access key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    pattern("github token", r"\b(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{22,})"),
+    pattern("gitlab token", r"\bglpat-[A-Za-z0-9_-]{20,}"),
+    pattern("slack token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+    pattern("api key", r"\bsk-(?:[A-Za-z0-9]+-)*[A-Za-z0-9_]{20,}"),
+    pattern("google api key", r"\bAIza[0-9A-Za-z_-]{35}\b"),
+    pattern("jwt", r"\beyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}"),
+    pattern(
+      "credential assignment",
+      r#"(?i)\b(?:api[_-]?key|secret|password|passwd|access[_-]?token)\s*[:=]\s*["']?[A-Za-z0-9/+_\-]{16,}"#,
+    ),
+    pattern(
+      "email address",
+      r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b",
+    ),
+    pattern("us social security number", r"\b\d{3}-\d{2}-\d{4}\b"),
+    Pattern {
+      kind: "payment card number",
+      regex: Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").expect("valid sensitive pattern"),
+      check: passes_luhn,
+    },
+  ]
+});
+
+/// Scan text for secrets and PII
+pub fn scan(text: &str) -> Vec<Finding> {
+  let mut findings = Vec::new();
+
+  for (index, line) in text.lines().enumerate() {
+    for pattern in PATTERNS.iter() {
+      for found in pattern.regex.find_iter(line) {
+        if (pattern.check)(found.as_str()) {
+          findings.push(Finding {
+            kind: pattern.kind,
+            line: index + 1,
+            excerpt: redact(found.as_str()),
+          });
+        }
+      }
+    }
+  }
+
+  findings
+}
+
+/// Scan the overview and details of an insight
+pub fn scan_content(overview: &str, details: &str) -> Vec<Finding> {
+  let mut findings = scan(overview);
+  findings.extend(scan(details));
+  findings
+}
+
+/// Scan every stored insight, returning only those with findings
+pub fn scan_all() -> anyhow::Result<Vec<InsightFindings>> {
+  let results = insight::get_insights(None)?
+    .into_iter()
+    .filter_map(|insight| {
+      let findings = scan_content(&insight.overview, &insight.details);
+      (!findings.is_empty()).then_some(InsightFindings {
+        topic: insight.topic,
+        name: insight.name,
+        findings,
+      })
+    })
+    .collect();
+
+  Ok(results)
+}
+
+/// One-line summary of findings for error messages
+pub fn describe(findings: &[Finding]) -> String {
+  let mut kinds: Vec<&str> = findings.iter().map(|f| f.kind).collect();
+  kinds.sort_unstable();
+  kinds.dedup();
+  format!(
+    "Content appears to contain sensitive data ({}). Remove it, or pass --allow-sensitive to store it anyway",
+    kinds.join(", ")
+  )
+}
+
+/// Keep just enough of a match to recognise it
+fn redact(matched: &str) -> String {
+  let visible: String = matched.chars().take(4).collect();
+  format!("{visible}…")
+}
+
+fn passes_luhn(candidate: &str) -> bool {
+  let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+  if !(13..=19).contains(&digits.len()) {
+    return false;
+  }
+
+  let sum: u32 = digits
+    .iter()
+    .rev()
+    .enumerate()
+    .map(|(i, &d)| {
+      if i % 2 == 1 {
+        let doubled = d * 2;
+        if doubled > 9 {
+          doubled - 9
+        } else {
+          doubled
+        }
+      } else {
+        d
+      }
+    })
+    .sum();
+
+  sum % 10 == 0
+}
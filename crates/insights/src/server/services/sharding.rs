@@ -0,0 +1,183 @@
+//! Query fan-out and rebalancing for the sharded embeddings index
+//!
+//! Searches run against every shard and the per-shard top-K lists are merged here.
+//! Rebalance planning only looks at row counts; moving rows is left to the vector
+//! database implementation.
+
+use std::collections::BTreeMap;
+
+use crate::server::models::sharding::{ShardConfig, ShardStrategy};
+
+/// Row count of a single shard table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardStats {
+  pub table: String,
+  pub rows: usize,
+}
+
+/// Outcome of comparing the current shard sizes against the layout's limits
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebalancePlan {
+  /// Largest shard relative to the mean shard size (1.0 is perfectly even)
+  pub imbalance: f32,
+  /// Why a rebalance is due; empty when the shards are healthy
+  pub reasons: Vec<String>,
+  /// Layout the rows should be redistributed into
+  pub target: ShardConfig,
+}
+
+impl RebalancePlan {
+  pub fn is_needed(&self) -> bool {
+    !self.reasons.is_empty()
+  }
+}
+
+/// Merge per-shard results into a single top-K list, best score first
+pub fn merge_top_k<T>(shards: Vec<Vec<T>>, limit: usize, score: impl Fn(&T) -> f32) -> Vec<T> {
+  let mut merged: Vec<T> = shards.into_iter().flatten().collect();
+  merged.sort_by(|a, b| score(b).total_cmp(&score(a)));
+  merged.truncate(limit);
+  merged
+}
+
+/// Largest shard relative to the mean, ignoring empty layouts
+pub fn imbalance(stats: &[ShardStats]) -> f32 {
+  let total: usize = stats.iter().map(|s| s.rows).sum();
+  if total == 0 {
+    return 1.0;
+  }
+
+  let largest = stats.iter().map(|s| s.rows).max().unwrap_or(0);
+  let mean = total as f32 / stats.len() as f32;
+  largest as f32 / mean
+}
+
+/// Decide whether the shards need redistributing and into what layout
+///
+/// `topic_rows` counts embeddings per normalized topic; it drives how oversized
+/// topics are split under the topic strategy.
+pub fn plan_rebalance(
+  config: &ShardConfig,
+  stats: &[ShardStats],
+  topic_rows: &BTreeMap<String, usize>,
+) -> RebalancePlan {
+  let imbalance = imbalance(stats);
+  let largest = stats.iter().map(|s| s.rows).max().unwrap_or(0);
+  let total: usize = stats.iter().map(|s| s.rows).sum();
+
+  let mut reasons = Vec::new();
+  let mut target = config.clone();
+
+  if stats.len() > 1 && imbalance > config.max_imbalance {
+    reasons.push(format!(
+      "largest shard holds {imbalance:.1}x the mean (limit {:.1}x)",
+      config.max_imbalance
+    ));
+  }
+  if largest > config.max_shard_rows {
+    reasons.push(format!("largest shard holds {largest} rows (limit {})", config.max_shard_rows));
+  }
+
+  match config.strategy {
+    ShardStrategy::Single => {
+      // A single table cannot be rebalanced, only replaced by a sharded layout
+      reasons.retain(|_| stats.len() > 1);
+    }
+    ShardStrategy::Hash => {
+      // Aim for half-full shards so growth does not immediately trigger another pass
+      let needed = total.div_ceil((config.max_shard_rows / 2).max(1)) as u32;
+      target.buckets = config.buckets.max(needed);
+    }
+    ShardStrategy::Topic => {
+      target.split_topics = split_topics(config, topic_rows);
+    }
+  }
+
+  if reasons.is_empty() && target != *config {
+    reasons.push("shard layout no longer matches topic sizes".to_string());
+  }
+
+  RebalancePlan { imbalance, reasons, target }
+}
+
+/// Bucket counts for topics too large to sit in a single shard
+fn split_topics(
+  config: &ShardConfig,
+  topic_rows: &BTreeMap<String, usize>,
+) -> BTreeMap<String, u32> {
+  if topic_rows.is_empty() {
+    return BTreeMap::new();
+  }
+
+  let total: usize = topic_rows.values().sum();
+  let mean = total.div_ceil(topic_rows.len()).max(1);
+  let limit = ((mean as f32 * config.max_imbalance) as usize).clamp(1, config.max_shard_rows);
+
+  topic_rows
+    .iter()
+    .filter(|(_, &rows)| rows > limit)
+    .map(|(topic, &rows)| (topic.clone(), rows.div_ceil(mean.min(limit)) as u32))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stats(rows: &[usize]) -> Vec<ShardStats> {
+    rows
+      .iter()
+      .enumerate()
+      .map(|(i, &rows)| ShardStats { table: format!("shard_{i}"), rows })
+      .collect()
+  }
+
+  #[test]
+  fn test_merge_top_k_orders_across_shards() {
+    let merged = merge_top_k(vec![vec![0.9, 0.2], vec![0.5, 0.95], vec![]], 3, |s| *s);
+    assert_eq!(merged, vec![0.95, 0.9, 0.5]);
+  }
+
+  #[test]
+  fn test_imbalance() {
+    assert_eq!(imbalance(&stats(&[10, 10])), 1.0);
+    assert_eq!(imbalance(&stats(&[30, 10, 20])), 1.5);
+    assert_eq!(imbalance(&stats(&[0, 0])), 1.0);
+  }
+
+  #[test]
+  fn test_hash_plan_grows_buckets_when_shards_overflow() {
+    let config = ShardConfig {
+      strategy: ShardStrategy::Hash,
+      buckets: 2,
+      max_shard_rows: 100,
+      ..Default::default()
+    };
+
+    let plan = plan_rebalance(&config, &stats(&[150, 150]), &BTreeMap::new());
+
+    assert!(plan.is_needed());
+    assert_eq!(plan.target.buckets, 6);
+  }
+
+  #[test]
+  fn test_topic_plan_splits_oversized_topics() {
+    let config = ShardConfig { strategy: ShardStrategy::Topic, ..Default::default() };
+    let topic_rows: BTreeMap<String, usize> =
+      [("big".to_string(), 900), ("a".to_string(), 50), ("b".to_string(), 50)].into();
+
+    let plan = plan_rebalance(&config, &stats(&[900, 50, 50]), &topic_rows);
+
+    assert!(plan.is_needed());
+    assert_eq!(plan.target.split_topics.get("big"), Some(&3));
+    assert!(!plan.target.split_topics.contains_key("a"));
+  }
+
+  #[test]
+  fn test_balanced_shards_need_no_rebalance() {
+    let config = ShardConfig { strategy: ShardStrategy::Hash, buckets: 2, ..Default::default() };
+    let plan = plan_rebalance(&config, &stats(&[10, 12]), &BTreeMap::new());
+    assert!(!plan.is_needed());
+    assert_eq!(plan.target, config);
+  }
+}
@@ -44,6 +44,22 @@ pub fn extract_words(text: &str) -> HashSet<String> {
     .collect()
 }
 
+/// Multiplier favouring recently updated insights
+///
+/// A just-updated insight gains `weight` times its score, and the bonus halves every
+/// `half_life_days`, so old insights settle back to their plain similarity.
+pub fn recency_factor(age_days: f32, half_life_days: f32, weight: f32) -> f32 {
+  if weight <= 0.0 || half_life_days <= 0.0 {
+    return 1.0;
+  }
+  1.0 + weight * 0.5f32.powf(age_days.max(0.0) / half_life_days)
+}
+
+/// Final score from retrieval similarity, recency factor and topic boost
+pub fn combine(similarity: f32, recency: f32, topic_boost: f32) -> f32 {
+  similarity * recency * topic_boost
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -160,4 +176,14 @@ mod tests {
     let similarity = semantic(&query_words, content);
     assert!(similarity > 0.6); // Should be high similarity
   }
+
+  #[test]
+  fn test_recency_factor_halves_with_age() {
+    assert_eq!(recency_factor(0.0, 30.0, 0.2), 1.2);
+    assert!((recency_factor(30.0, 30.0, 0.2) - 1.1).abs() < 1e-6);
+    assert!(recency_factor(365.0, 30.0, 0.2) < 1.001);
+    assert_eq!(recency_factor(-5.0, 30.0, 0.2), 1.2);
+    assert_eq!(recency_factor(0.0, 30.0, 0.0), 1.0);
+    assert!((combine(0.5, 1.2, 2.0) - 1.2).abs() < 1e-6);
+  }
 }
@@ -0,0 +1,543 @@
+//! Conversion of Slack channel exports into insights
+//!
+//! A Slack export is a zip (or the directory it unpacks to) holding `channels.json`,
+//! `users.json` and one `<channel>/<YYYY-MM-DD>.json` file of messages per day.
+//! Messages are grouped into threads and every thread becomes one insight named
+//! after the timestamp of its first message, so importing the same export again
+//! finds the insights it created last time instead of duplicating them.
+
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+use flate2::read::DeflateDecoder;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::server::types::ImportEntry;
+
+/// Overviews longer than this are cut at a word boundary
+const OVERVIEW_MAX_CHARS: usize = 280;
+
+/// Channel housekeeping that carries no knowledge
+const SKIPPED_SUBTYPES: &[&str] = &[
+  "channel_join",
+  "channel_leave",
+  "channel_topic",
+  "channel_purpose",
+  "channel_name",
+  "channel_archive",
+  "channel_unarchive",
+  "pinned_item",
+  "tombstone",
+];
+
+/// `<target|label>` or `<target>` references in Slack's message markup
+static REFERENCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<([^<>|]+)(?:\|([^<>]*))?>").unwrap());
+
+/// Where the threads of an import come from and where they go
+#[derive(Debug, Clone, Default)]
+pub struct SlackImportOptions {
+  /// Channel to import, as named in the export
+  pub channel: String,
+  /// Topic for the insights (defaults to the channel name)
+  pub topic: Option<String>,
+  /// Workspace subdomain used to build permalinks, e.g. `acme` for acme.slack.com
+  pub workspace: Option<String>,
+}
+
+/// One message of a channel history file
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlackMessage {
+  pub ts: String,
+  #[serde(default)]
+  pub thread_ts: Option<String>,
+  #[serde(default)]
+  pub user: Option<String>,
+  #[serde(default)]
+  pub username: Option<String>,
+  #[serde(default)]
+  pub user_profile: Option<SlackProfile>,
+  #[serde(default)]
+  pub subtype: Option<String>,
+  #[serde(default)]
+  pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SlackProfile {
+  #[serde(default)]
+  pub display_name: String,
+  #[serde(default)]
+  pub real_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlackUser {
+  id: String,
+  #[serde(default)]
+  name: String,
+  #[serde(default)]
+  profile: SlackProfile,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlackChannel {
+  id: String,
+  name: String,
+}
+
+/// The files of an export, read from a zip or an unpacked directory
+pub struct SlackExport {
+  files: BTreeMap<String, Vec<u8>>,
+}
+
+impl SlackExport {
+  /// Open a Slack export zip, or a directory it was unpacked into
+  pub fn open(path: &Path) -> Result<Self> {
+    let mut files = BTreeMap::new();
+    if path.is_dir() {
+      collect_dir(path, path, &mut files)?;
+    } else {
+      let data =
+        std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+      for (name, content) in read_zip(&data)? {
+        if name.ends_with(".json") {
+          files.insert(name, content);
+        }
+      }
+    }
+    Ok(Self::from_files(files))
+  }
+
+  /// Build an export from `path -> contents`, dropping any folder the export was wrapped in
+  pub fn from_files(files: BTreeMap<String, Vec<u8>>) -> Self {
+    let prefix = files
+      .keys()
+      .filter_map(|name| name.strip_suffix("channels.json"))
+      .filter(|prefix| prefix.is_empty() || prefix.ends_with('/'))
+      .min_by_key(|prefix| prefix.len())
+      .unwrap_or_default()
+      .to_string();
+
+    let files = files
+      .into_iter()
+      .filter_map(|(name, content)| Some((name.strip_prefix(&prefix)?.to_string(), content)))
+      .collect();
+    Self { files }
+  }
+
+  /// Display names keyed by user id
+  fn users(&self) -> Result<HashMap<String, String>> {
+    let users: Vec<SlackUser> = self.parse("users.json")?.unwrap_or_default();
+    Ok(
+      users
+        .into_iter()
+        .map(|user| {
+          let name = [user.profile.display_name, user.profile.real_name, user.name]
+            .into_iter()
+            .find(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| user.id.clone());
+          (user.id, name)
+        })
+        .collect(),
+    )
+  }
+
+  /// Slack's id for a channel, needed for permalinks
+  fn channel_id(&self, channel: &str) -> Result<Option<String>> {
+    let channels: Vec<SlackChannel> = self.parse("channels.json")?.unwrap_or_default();
+    Ok(channels.into_iter().find(|c| c.name == channel).map(|c| c.id))
+  }
+
+  /// Every message posted in a channel, across all of its day files
+  pub fn messages(&self, channel: &str) -> Result<Vec<SlackMessage>> {
+    let folder = format!("{channel}/");
+    let days: Vec<&String> = self.files.keys().filter(|name| name.starts_with(&folder)).collect();
+    if days.is_empty() {
+      let channels: Vec<&str> = self
+        .files
+        .keys()
+        .filter_map(|name| name.split_once('/').map(|(dir, _)| dir))
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+      return Err(anyhow!(
+        "Channel '{}' is not in the export (channels: {})",
+        channel,
+        channels.join(", ")
+      ));
+    }
+
+    let mut messages = Vec::new();
+    for day in days {
+      let parsed: Vec<SlackMessage> = self.parse(day)?.unwrap_or_default();
+      messages.extend(parsed);
+    }
+    Ok(messages)
+  }
+
+  fn parse<T: serde::de::DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+    self
+      .files
+      .get(name)
+      .map(|content| {
+        serde_json::from_slice(content).map_err(|e| anyhow!("Invalid JSON in {name}: {e}"))
+      })
+      .transpose()
+  }
+}
+
+/// Turn every thread of a channel into an import entry
+pub fn to_entries(export: &SlackExport, options: &SlackImportOptions) -> Result<Vec<ImportEntry>> {
+  let users = export.users()?;
+  let channel_id = export.channel_id(&options.channel)?;
+  let topic = options.topic.clone().unwrap_or_else(|| options.channel.clone());
+
+  let entries = threads(export.messages(&options.channel)?)
+    .into_iter()
+    .filter_map(|thread| {
+      let transcript: Vec<(&SlackMessage, String)> = thread
+        .iter()
+        .map(|message| (message, plain_text(&message.text, &users)))
+        .filter(|(_, text)| !text.trim().is_empty())
+        .collect();
+      let (_, first) = transcript.first()?;
+      let root = &thread[0];
+
+      let mut details = transcript
+        .iter()
+        .map(|(message, text)| {
+          format!("**{}** ({}):\n{text}", author(message, &users), when(&message.ts))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+      details.push_str("\n\nSources:\n");
+      match &channel_id {
+        Some(id) => {
+          details.push_str(&format!("- {}", permalink(options.workspace.as_deref(), id, &root.ts)))
+        }
+        None => details.push_str(&format!("- Slack #{} at {}", options.channel, when(&root.ts))),
+      }
+
+      Some(ImportEntry {
+        topic: topic.clone(),
+        name: format!("slack-{}", root.ts.replace('.', "-")),
+        overview: overview(first),
+        details,
+        tags: Vec::new(),
+      })
+    })
+    .collect();
+  Ok(entries)
+}
+
+/// Group messages into threads, oldest first, dropping channel housekeeping and duplicates
+///
+/// Replies broadcast to the channel appear twice in an export; both copies share a timestamp.
+pub fn threads(messages: Vec<SlackMessage>) -> Vec<Vec<SlackMessage>> {
+  let mut threads: BTreeMap<(u64, u64), BTreeMap<(u64, u64), SlackMessage>> = BTreeMap::new();
+  for message in messages {
+    if message.subtype.as_deref().is_some_and(|s| SKIPPED_SUBTYPES.contains(&s)) {
+      continue;
+    }
+    let root = ts_key(message.thread_ts.as_deref().unwrap_or(&message.ts));
+    threads.entry(root).or_default().entry(ts_key(&message.ts)).or_insert(message);
+  }
+  threads.into_values().map(|thread| thread.into_values().collect()).collect()
+}
+
+/// Link to a message in the Slack web client
+pub fn permalink(workspace: Option<&str>, channel_id: &str, ts: &str) -> String {
+  let host = workspace.map_or_else(|| "slack.com".to_string(), |w| format!("{w}.slack.com"));
+  format!("https://{host}/archives/{channel_id}/p{}", ts.replace('.', ""))
+}
+
+/// Replace Slack's mention and link markup with readable text
+pub fn plain_text(text: &str, users: &HashMap<String, String>) -> String {
+  let text = REFERENCE.replace_all(text, |caps: &regex::Captures| {
+    let target = &caps[1];
+    let label = caps.get(2).map(|m| m.as_str()).filter(|l| !l.is_empty());
+    if let Some(user) = target.strip_prefix('@') {
+      let name = label.or_else(|| users.get(user).map(String::as_str)).unwrap_or(user);
+      format!("@{name}")
+    } else if let Some(channel) = target.strip_prefix('#') {
+      format!("#{}", label.unwrap_or(channel))
+    } else if let Some(special) = target.strip_prefix('!') {
+      format!("@{}", label.unwrap_or(special.split('^').next().unwrap_or(special)))
+    } else {
+      match label {
+        Some(label) if label != target => format!("{label} ({target})"),
+        _ => target.to_string(),
+      }
+    }
+  });
+  text.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+fn author(message: &SlackMessage, users: &HashMap<String, String>) -> String {
+  let profile = message.user_profile.as_ref().and_then(|p| {
+    [&p.display_name, &p.real_name].into_iter().find(|n| !n.trim().is_empty()).cloned()
+  });
+  message
+    .user
+    .as_ref()
+    .and_then(|id| users.get(id).cloned())
+    .or(profile)
+    .or_else(|| message.username.clone())
+    .or_else(|| message.user.clone())
+    .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// First message on one line, cut to a readable length
+fn overview(text: &str) -> String {
+  let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+  if line.chars().count() <= OVERVIEW_MAX_CHARS {
+    return line;
+  }
+  let cut: String = line.chars().take(OVERVIEW_MAX_CHARS).collect();
+  let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+  format!("{}...", cut.trim_end())
+}
+
+fn when(ts: &str) -> String {
+  let (secs, _) = ts_key(ts);
+  DateTime::from_timestamp(secs as i64, 0)
+    .map_or_else(|| ts.to_string(), |t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+}
+
+/// Slack timestamps are `<seconds>.<microseconds>`; compare them numerically
+fn ts_key(ts: &str) -> (u64, u64) {
+  let (secs, micros) = ts.split_once('.').unwrap_or((ts, "0"));
+  (secs.parse().unwrap_or(0), micros.parse().unwrap_or(0))
+}
+
+fn collect_dir(root: &Path, dir: &Path, files: &mut BTreeMap<String, Vec<u8>>) -> Result<()> {
+  for entry in std::fs::read_dir(dir)? {
+    let path: PathBuf = entry?.path();
+    if path.is_dir() {
+      collect_dir(root, &path, files)?;
+    } else if path.extension().is_some_and(|ext| ext == "json") {
+      let name = path.strip_prefix(root)?.to_string_lossy().replace('\\', "/");
+      files.insert(name, std::fs::read(&path)?);
+    }
+  }
+  Ok(())
+}
+
+// Zip archives
+// ============
+
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x0201_4b50;
+const LOCAL_FILE_HEADER: u32 = 0x0403_4b50;
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// Read every file of a zip archive
+///
+/// Only what Slack exports use is supported: stored or deflated entries, no ZIP64.
+pub fn read_zip(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+  let not_zip = || anyhow!("Not a zip archive (or it uses ZIP64, which is not supported)");
+
+  // The end record sits at the very end, followed by a comment of up to 64KiB
+  let search_from = data.len().saturating_sub(22 + u16::MAX as usize);
+  let end = (search_from..data.len().saturating_sub(21))
+    .rev()
+    .find(|&at| u32_at(data, at) == Some(END_OF_CENTRAL_DIRECTORY))
+    .ok_or_else(not_zip)?;
+  let count = u16_at(data, end + 10).ok_or_else(not_zip)?;
+  let mut at = u32_at(data, end + 16).ok_or_else(not_zip)? as usize;
+
+  let mut files = Vec::with_capacity(count as usize);
+  for _ in 0..count {
+    if u32_at(data, at) != Some(CENTRAL_DIRECTORY_HEADER) {
+      return Err(anyhow!("Corrupt zip central directory"));
+    }
+    let field = |offset: usize| u16_at(data, at + offset).ok_or_else(not_zip);
+    let method = field(10)?;
+    let compressed_size = u32_at(data, at + 20).ok_or_else(not_zip)? as usize;
+    let (name_len, extra_len, comment_len) =
+      (field(28)? as usize, field(30)? as usize, field(32)? as usize);
+    let local = u32_at(data, at + 42).ok_or_else(not_zip)? as usize;
+    let name = data.get(at + 46..at + 46 + name_len).ok_or_else(not_zip)?;
+    let name = String::from_utf8_lossy(name).into_owned();
+    at += 46 + name_len + extra_len + comment_len;
+
+    if name.ends_with('/') {
+      continue;
+    }
+    if u32_at(data, local) != Some(LOCAL_FILE_HEADER) {
+      return Err(anyhow!("Corrupt zip entry {name}"));
+    }
+    let start = local
+      + 30
+      + u16_at(data, local + 26).ok_or_else(not_zip)? as usize
+      + u16_at(data, local + 28).ok_or_else(not_zip)? as usize;
+    let raw = data.get(start..start + compressed_size).ok_or_else(not_zip)?;
+
+    let content = match method {
+      STORED => raw.to_vec(),
+      DEFLATED => {
+        let mut content = Vec::new();
+        DeflateDecoder::new(raw)
+          .read_to_end(&mut content)
+          .map_err(|e| anyhow!("Failed to inflate {name}: {e}"))?;
+        content
+      }
+      other => return Err(anyhow!("Zip entry {name} uses unsupported compression {other}")),
+    };
+    files.push((name, content));
+  }
+  Ok(files)
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+  Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+  Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use flate2::{write::DeflateEncoder, Compression};
+  use serde_json::json;
+  use std::io::Write;
+
+  /// Minimal zip writer for fixtures; CRCs are left at zero since the reader ignores them
+  fn zip(files: &[(&str, &[u8], bool)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, content, deflate) in files {
+      let (method, data) = if *deflate {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        (DEFLATED, encoder.finish().unwrap())
+      } else {
+        (STORED, content.to_vec())
+      };
+      let offset = out.len() as u32;
+      let sizes = [data.len() as u32, content.len() as u32];
+
+      out.extend(LOCAL_FILE_HEADER.to_le_bytes());
+      out.extend([20, 0, 0, 0]);
+      out.extend(method.to_le_bytes());
+      out.extend([0; 8]);
+      sizes.iter().for_each(|s| out.extend(s.to_le_bytes()));
+      out.extend((name.len() as u16).to_le_bytes());
+      out.extend([0, 0]);
+      out.extend(name.as_bytes());
+      out.extend(&data);
+
+      central.extend(CENTRAL_DIRECTORY_HEADER.to_le_bytes());
+      central.extend([20, 0, 20, 0, 0, 0]);
+      central.extend(method.to_le_bytes());
+      central.extend([0; 8]);
+      sizes.iter().for_each(|s| central.extend(s.to_le_bytes()));
+      central.extend((name.len() as u16).to_le_bytes());
+      central.extend([0; 12]);
+      central.extend(offset.to_le_bytes());
+      central.extend(name.as_bytes());
+    }
+    let central_offset = out.len() as u32;
+    out.extend(&central);
+    out.extend(END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+    out.extend([0; 4]);
+    out.extend((files.len() as u16).to_le_bytes());
+    out.extend((files.len() as u16).to_le_bytes());
+    out.extend((central.len() as u32).to_le_bytes());
+    out.extend(central_offset.to_le_bytes());
+    out.extend([0, 0]);
+    out
+  }
+
+  fn message(ts: &str, thread_ts: Option<&str>, user: &str, text: &str) -> serde_json::Value {
+    json!({ "type": "message", "ts": ts, "thread_ts": thread_ts, "user": user, "text": text })
+  }
+
+  fn fixture() -> SlackExport {
+    let channels = json!([{ "id": "C042", "name": "eng-learnings" }]).to_string();
+    let users = json!([
+      { "id": "U1", "name": "ada", "profile": { "display_name": "Ada", "real_name": "Ada L" } },
+      { "id": "U2", "name": "grace", "profile": { "display_name": "", "real_name": "Grace H" } }
+    ])
+    .to_string();
+    let day_one = json!([
+      message("1690000000.000100", Some("1690000000.000100"), "U1", "Flaky CI is caused by <@U2>'s cache"),
+      { "type": "message", "subtype": "channel_join", "ts": "1690000001.000000", "user": "U2", "text": "joined" },
+      message("1690000100.000200", Some("1690000000.000100"), "U2", "Fixed in <https://ci.example.com/42|build 42> &amp; deployed"),
+      message("1690000500.000000", None, "U2", "Standalone tip"),
+    ])
+    .to_string();
+    let day_two = json!([
+      message("1690090000.000300", Some("1690000000.000100"), "U1", "Confirmed on main"),
+      // A reply broadcast to the channel shows up a second time
+      message("1690090000.000300", Some("1690000000.000100"), "U1", "Confirmed on main"),
+    ])
+    .to_string();
+
+    let archive = zip(&[
+      ("export/", b"", false),
+      ("export/channels.json", channels.as_bytes(), false),
+      ("export/users.json", users.as_bytes(), true),
+      ("export/eng-learnings/2023-07-22.json", day_one.as_bytes(), true),
+      ("export/eng-learnings/2023-07-23.json", day_two.as_bytes(), false),
+    ]);
+    let files = read_zip(&archive).unwrap().into_iter().collect();
+    SlackExport::from_files(files)
+  }
+
+  #[test]
+  fn test_read_zip_handles_stored_and_deflated_entries() {
+    let archive = zip(&[("a.txt", b"stored", false), ("b.txt", b"deflated deflated", true)]);
+    let files = read_zip(&archive).unwrap();
+    assert_eq!(files[0], ("a.txt".to_string(), b"stored".to_vec()));
+    assert_eq!(files[1], ("b.txt".to_string(), b"deflated deflated".to_vec()));
+    assert!(read_zip(b"not a zip").is_err());
+  }
+
+  #[test]
+  fn test_threads_become_entries_with_permalinks() {
+    let options = SlackImportOptions {
+      channel: "eng-learnings".to_string(),
+      workspace: Some("acme".to_string()),
+      ..Default::default()
+    };
+    let entries = to_entries(&fixture(), &options).unwrap();
+    assert_eq!(entries.len(), 2);
+
+    let thread = &entries[0];
+    assert_eq!(thread.topic, "eng-learnings");
+    assert_eq!(thread.name, "slack-1690000000-000100");
+    assert_eq!(thread.overview, "Flaky CI is caused by @Grace H's cache");
+    assert!(thread.details.contains("**Ada** (2023-07-22 04:26 UTC):"));
+    assert!(thread.details.contains("Fixed in build 42 (https://ci.example.com/42) & deployed"));
+    assert_eq!(thread.details.matches("Confirmed on main").count(), 1);
+    assert!(!thread.details.contains("joined"));
+    assert!(thread.details.ends_with("- https://acme.slack.com/archives/C042/p1690000000000100"));
+
+    assert_eq!(entries[1].name, "slack-1690000500-000000");
+    assert_eq!(entries[1].overview, "Standalone tip");
+  }
+
+  #[test]
+  fn test_unknown_channel_lists_available_ones() {
+    let options = SlackImportOptions { channel: "random".to_string(), ..Default::default() };
+    let error = to_entries(&fixture(), &options).unwrap_err().to_string();
+    assert!(error.contains("eng-learnings"), "{error}");
+  }
+
+  #[test]
+  fn test_overview_is_cut_at_a_word_boundary() {
+    let long = "word ".repeat(100);
+    let cut = overview(&long);
+    assert!(cut.ends_with("word..."));
+    assert!(cut.chars().count() <= OVERVIEW_MAX_CHARS + 3);
+    assert_eq!(overview("line one\n\nline   two"), "line one line two");
+  }
+}
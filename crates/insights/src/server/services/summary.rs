@@ -0,0 +1,311 @@
+//! Clustered outlines of large topics
+//!
+//! Insights are grouped by average-linkage clustering over either their stored
+//! embeddings or, when those are missing, TF-IDF term vectors. Each cluster is
+//! labelled with the terms that carry the most weight across its members.
+
+use std::collections::HashMap;
+
+use crate::server::services::similarity::extract_words;
+
+/// Where the vectors used for clustering came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterSource {
+  Embeddings,
+  Terms,
+}
+
+impl std::fmt::Display for ClusterSource {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ClusterSource::Embeddings => write!(f, "embeddings"),
+      ClusterSource::Terms => write!(f, "terms"),
+    }
+  }
+}
+
+/// Tuning for a topic summary
+#[derive(Debug, Clone)]
+pub struct SummaryOptions {
+  /// Clusters keep merging while their average similarity is at least this
+  pub similarity_threshold: f32,
+  /// Clusters keep merging past the threshold until there are at most this many
+  pub max_clusters: usize,
+  /// Number of label terms per cluster
+  pub label_terms: usize,
+}
+
+impl SummaryOptions {
+  /// Defaults suited to the vector source (embeddings are far denser than terms)
+  pub fn for_source(source: ClusterSource) -> Self {
+    let similarity_threshold = match source {
+      ClusterSource::Embeddings => 0.6,
+      ClusterSource::Terms => 0.12,
+    };
+    Self { similarity_threshold, max_clusters: 12, label_terms: 3 }
+  }
+}
+
+/// A group of related insights
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+  /// Most representative terms, strongest first
+  pub terms: Vec<String>,
+  /// Indices into the summarized documents
+  pub members: Vec<usize>,
+}
+
+/// Clusters for a topic, largest first, plus documents that fit nowhere
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicSummary {
+  pub source: ClusterSource,
+  pub clusters: Vec<Cluster>,
+  pub unclustered: Vec<usize>,
+}
+
+/// Summarize documents, clustering by embeddings when every document has one
+pub fn summarize(
+  texts: &[String],
+  embeddings: Option<&[Vec<f32>]>,
+  options: Option<SummaryOptions>,
+) -> TopicSummary {
+  let term_weights = tf_idf(texts);
+  let (source, similarities) = similarities_with(&term_weights, embeddings);
+
+  let options = options.unwrap_or_else(|| SummaryOptions::for_source(source));
+  let groups = average_linkage(similarities, &options);
+
+  let (mut grouped, singletons): (Vec<_>, Vec<_>) =
+    groups.into_iter().partition(|members| members.len() > 1);
+  grouped.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+
+  let clusters = grouped
+    .into_iter()
+    .map(|members| Cluster { terms: label(&members, &term_weights, options.label_terms), members })
+    .collect();
+  let mut unclustered: Vec<usize> = singletons.into_iter().flatten().collect();
+  unclustered.sort_unstable();
+
+  TopicSummary { source, clusters, unclustered }
+}
+
+/// Cosine similarity of every pair of documents, by embeddings when every document has one
+pub(crate) fn pairwise_similarities(
+  texts: &[String],
+  embeddings: Option<&[Vec<f32>]>,
+) -> (ClusterSource, Vec<Vec<f32>>) {
+  similarities_with(&tf_idf(texts), embeddings)
+}
+
+fn similarities_with(
+  term_weights: &[TermWeights],
+  embeddings: Option<&[Vec<f32>]>,
+) -> (ClusterSource, Vec<Vec<f32>>) {
+  match embeddings {
+    Some(vectors) if vectors.len() == term_weights.len() => (
+      ClusterSource::Embeddings,
+      similarity_matrix(vectors.len(), |a, b| dense_cosine(&vectors[a], &vectors[b])),
+    ),
+    _ => (
+      ClusterSource::Terms,
+      similarity_matrix(term_weights.len(), |a, b| {
+        sparse_cosine(&term_weights[a], &term_weights[b])
+      }),
+    ),
+  }
+}
+
+type TermWeights = HashMap<String, f32>;
+
+/// L2-normalized TF-IDF weights for each document
+fn tf_idf(texts: &[String]) -> Vec<TermWeights> {
+  let counts: Vec<HashMap<String, usize>> = texts.iter().map(|text| term_counts(text)).collect();
+
+  let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+  for terms in &counts {
+    for term in terms.keys() {
+      *document_frequency.entry(term).or_default() += 1;
+    }
+  }
+
+  let total = texts.len() as f32;
+  counts
+    .iter()
+    .map(|terms| {
+      let mut weights: TermWeights = terms
+        .iter()
+        .map(|(term, &count)| {
+          let idf = (total / document_frequency[term.as_str()] as f32).ln() + 1.0;
+          (term.clone(), count as f32 * idf)
+        })
+        .collect();
+
+      let norm = weights.values().map(|w| w * w).sum::<f32>().sqrt();
+      if norm > 0.0 {
+        weights.values_mut().for_each(|w| *w /= norm);
+      }
+      weights
+    })
+    .collect()
+}
+
+fn term_counts(text: &str) -> HashMap<String, usize> {
+  let mut counts = HashMap::new();
+  for word in text.split_whitespace() {
+    // Reuse the search tokenizer so labels match what users search for
+    for term in extract_words(word) {
+      if term.len() > 2 && !term.chars().all(|c| c.is_ascii_digit()) {
+        *counts.entry(term).or_default() += 1;
+      }
+    }
+  }
+  counts
+}
+
+fn similarity_matrix(n: usize, similarity: impl Fn(usize, usize) -> f32) -> Vec<Vec<f32>> {
+  let mut matrix = vec![vec![0.0; n]; n];
+  for a in 0..n {
+    for b in (a + 1)..n {
+      let value = similarity(a, b);
+      matrix[a][b] = value;
+      matrix[b][a] = value;
+    }
+  }
+  matrix
+}
+
+fn dense_cosine(a: &[f32], b: &[f32]) -> f32 {
+  let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+  let norm =
+    a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|y| y * y).sum::<f32>().sqrt();
+  if norm > 0.0 {
+    dot / norm
+  } else {
+    0.0
+  }
+}
+
+fn sparse_cosine(a: &TermWeights, b: &TermWeights) -> f32 {
+  // Both vectors are already normalized
+  let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+  small.iter().filter_map(|(term, w)| large.get(term).map(|v| w * v)).sum()
+}
+
+/// Agglomerative clustering with average linkage (Lance-Williams updates)
+fn average_linkage(mut similarity: Vec<Vec<f32>>, options: &SummaryOptions) -> Vec<Vec<usize>> {
+  let mut clusters: Vec<Option<Vec<usize>>> =
+    (0..similarity.len()).map(|i| Some(vec![i])).collect();
+  let mut active = clusters.len();
+
+  loop {
+    let Some((a, b, best)) = most_similar_pair(&similarity, &clusters) else {
+      break;
+    };
+    if best < options.similarity_threshold && active <= options.max_clusters {
+      break;
+    }
+
+    let moved = clusters[b].take().unwrap_or_default();
+    let (size_a, size_b) = (clusters[a].as_ref().map_or(0, Vec::len) as f32, moved.len() as f32);
+
+    for other in 0..similarity.len() {
+      if other == a || clusters[other].is_none() {
+        continue;
+      }
+      let merged =
+        (size_a * similarity[a][other] + size_b * similarity[b][other]) / (size_a + size_b);
+      similarity[a][other] = merged;
+      similarity[other][a] = merged;
+    }
+
+    if let Some(members) = clusters[a].as_mut() {
+      members.extend(moved);
+      members.sort_unstable();
+    }
+    active -= 1;
+  }
+
+  clusters.into_iter().flatten().collect()
+}
+
+fn most_similar_pair(
+  similarity: &[Vec<f32>],
+  clusters: &[Option<Vec<usize>>],
+) -> Option<(usize, usize, f32)> {
+  let live: Vec<usize> = (0..clusters.len()).filter(|&i| clusters[i].is_some()).collect();
+
+  let mut best: Option<(usize, usize, f32)> = None;
+  for (index, &a) in live.iter().enumerate() {
+    for &b in &live[index + 1..] {
+      if best.is_none_or(|(_, _, value)| similarity[a][b] > value) {
+        best = Some((a, b, similarity[a][b]));
+      }
+    }
+  }
+  best
+}
+
+/// Terms with the highest combined weight across a cluster's members
+fn label(members: &[usize], term_weights: &[TermWeights], count: usize) -> Vec<String> {
+  let mut totals: HashMap<&str, f32> = HashMap::new();
+  for &member in members {
+    for (term, weight) in &term_weights[member] {
+      *totals.entry(term).or_default() += weight;
+    }
+  }
+
+  let mut ranked: Vec<(&str, f32)> = totals.into_iter().collect();
+  ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+  ranked.into_iter().take(count).map(|(term, _)| term.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn texts(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+  }
+
+  #[test]
+  fn test_term_clusters_group_related_insights() {
+    let docs = texts(&[
+      "tokio runtime spawning async tasks",
+      "async tasks on the tokio runtime need send bounds",
+      "postgres index tuning for slow queries",
+      "slow postgres queries fixed with an index",
+      "unrelated note about keyboard shortcuts",
+    ]);
+
+    let summary = summarize(&docs, None, None);
+
+    assert_eq!(summary.source, ClusterSource::Terms);
+    assert_eq!(summary.clusters.len(), 2);
+    assert_eq!(summary.clusters[0].members, vec![0, 1]);
+    assert_eq!(summary.clusters[1].members, vec![2, 3]);
+    assert_eq!(summary.unclustered, vec![4]);
+    assert!(summary.clusters[1].terms.contains(&"postgres".to_string()));
+  }
+
+  #[test]
+  fn test_embedding_clusters_are_preferred_when_complete() {
+    let docs = texts(&["a", "b", "c"]);
+    let vectors = vec![vec![1.0, 0.0], vec![0.9, 0.1], vec![0.0, 1.0]];
+
+    let summary = summarize(&docs, Some(&vectors), None);
+
+    assert_eq!(summary.source, ClusterSource::Embeddings);
+    assert_eq!(summary.clusters[0].members, vec![0, 1]);
+    assert_eq!(summary.unclustered, vec![2]);
+  }
+
+  #[test]
+  fn test_max_clusters_forces_merges() {
+    let docs = texts(&["alpha one", "beta two", "gamma three", "delta four"]);
+    let options = SummaryOptions { similarity_threshold: 0.9, max_clusters: 2, label_terms: 2 };
+
+    let summary = summarize(&docs, None, Some(options));
+
+    assert_eq!(summary.clusters.len() + summary.unclustered.len(), 2);
+  }
+}
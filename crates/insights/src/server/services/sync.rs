@@ -0,0 +1,366 @@
+//! Git-backed sync of the knowledge base between machines
+//!
+//! `blizz sync init` turns the insights root into a git repository that tracks only
+//! insight files; history, search indexes and local configuration (webhook secrets
+//! included) stay out of it. Once sync is on, the server commits after every add,
+//! update and delete, and `push`/`pull` exchange those commits with a remote.
+//!
+//! Git merges most pulls on its own. When both machines changed the same insight,
+//! the file is merged field by field instead of line by line, so conflict markers
+//! only ever land in the overview or details and the frontmatter stays parseable.
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::Mutex;
+
+use crate::server::middleware::server_warn;
+use crate::server::models::insight::{self, Insight};
+
+/// Branch every synced machine commits to
+pub const BRANCH: &str = "main";
+
+pub const REMOTE: &str = "origin";
+
+/// Set in the repository's config by `init`, so a hand-made repository is left alone
+const ENABLED_KEY: &str = "blizz.sync";
+
+/// Only insight files are synced
+const GITIGNORE: &str = "\
+# Managed by blizz sync: only insight files are shared between machines
+/*
+!/.gitignore
+!/*/
+/.*/
+/*/*
+!/*/*.insight.md
+";
+
+/// Serializes git operations made by this process
+static GIT_LOCK: Mutex<()> = Mutex::new(());
+
+/// What a pull brought in
+#[derive(Debug, Default, PartialEq)]
+pub struct PullOutcome {
+  /// Files changed by the pull, relative to the insights root
+  pub changed: Vec<String>,
+  /// Insights changed on both machines that now contain conflict markers
+  pub conflicts: Vec<String>,
+}
+
+/// Whether `init` has been run for this insights root
+pub fn is_enabled(root: &Path) -> bool {
+  root.join(".git").exists()
+    && git(root, &["config", "--get", ENABLED_KEY]).is_ok_and(|value| value.trim() == "true")
+}
+
+/// Make the insights root a sync repository, optionally pointing it at a remote
+///
+/// Running it again only updates the remote.
+pub fn init(root: &Path, remote: Option<&str>) -> Result<()> {
+  let _guard = lock();
+  fs::create_dir_all(root)?;
+
+  if !root.join(".git").exists() {
+    git(root, &["init", "--quiet"])?;
+    git(root, &["symbolic-ref", "HEAD", &format!("refs/heads/{BRANCH}")])?;
+  }
+  git(root, &["config", ENABLED_KEY, "true"])?;
+  // Commits need an identity; fall back to one when the machine has none configured
+  if git(root, &["config", "user.email"]).is_err() {
+    git(root, &["config", "user.name", "blizz sync"])?;
+    git(root, &["config", "user.email", "sync@blizz.invalid"])?;
+  }
+  fs::write(root.join(".gitignore"), GITIGNORE)?;
+
+  if let Some(remote) = remote {
+    if git(root, &["remote", "get-url", REMOTE]).is_ok() {
+      git(root, &["remote", "set-url", REMOTE, remote])?;
+    } else {
+      git(root, &["remote", "add", REMOTE, remote])?;
+    }
+  }
+
+  commit_locked(root, "Initialize insight sync")?;
+  Ok(())
+}
+
+/// Commit every pending change to insight files; returns whether anything was committed
+pub fn commit_all(root: &Path, message: &str) -> Result<bool> {
+  let _guard = lock();
+  commit_locked(root, message)
+}
+
+/// Commit after a change made through the server, if sync is on (non-fatal if it fails)
+pub async fn auto_commit(message: &str) {
+  let root = match insight::get_insights_root() {
+    Ok(root) if is_enabled(&root) => root,
+    _ => return,
+  };
+  if let Err(e) = commit_all(&root, message) {
+    server_warn(&format!("Failed to commit insight change for sync: {e}"), "insights-sync").await;
+  }
+}
+
+/// Commit pending changes and push them to the remote
+pub fn push(root: &Path) -> Result<()> {
+  let _guard = lock();
+  ensure_remote(root)?;
+  commit_locked(root, "Sync local changes")?;
+  git(root, &["push", "--quiet", "--set-upstream", REMOTE, BRANCH])?;
+  Ok(())
+}
+
+/// Fetch the remote and merge its insights into the local ones
+pub fn pull(root: &Path) -> Result<PullOutcome> {
+  let _guard = lock();
+  ensure_remote(root)?;
+  commit_locked(root, "Sync local changes")?;
+  git(root, &["fetch", "--quiet", REMOTE])?;
+
+  let upstream = format!("{REMOTE}/{BRANCH}");
+  if git(root, &["rev-parse", "--verify", "--quiet", &upstream]).is_err() {
+    // Nothing has been pushed yet
+    return Ok(PullOutcome::default());
+  }
+
+  let before = git(root, &["rev-parse", "HEAD"])?.trim().to_string();
+  let merge =
+    run_git(root, &["merge", "--quiet", "--no-edit", "--allow-unrelated-histories", &upstream])?;
+
+  let mut outcome = PullOutcome::default();
+  if !merge.status.success() {
+    let unmerged = lines(&git(root, &["diff", "--name-only", "--diff-filter=U"])?);
+    if unmerged.is_empty() {
+      let _ = git(root, &["merge", "--abort"]);
+      return Err(anyhow!("git merge failed: {}", String::from_utf8_lossy(&merge.stderr).trim()));
+    }
+
+    for path in &unmerged {
+      if !insight::is_insight_file(Path::new(path)) {
+        let _ = git(root, &["checkout", "--ours", "--", path]);
+      } else if merge_insight(root, path)? {
+        outcome.conflicts.push(path.clone());
+      }
+      git(root, &["add", "--", path])?;
+    }
+    git(root, &["commit", "--quiet", "--no-edit"])?;
+  }
+
+  outcome.changed = lines(&git(root, &["diff", "--name-only", &before, "HEAD"])?);
+  Ok(outcome)
+}
+
+/// Merge both sides of an insight changed on both machines; returns whether markers were left
+///
+/// When one side deleted the insight and the other edited it, the edit wins.
+fn merge_insight(root: &Path, path: &str) -> Result<bool> {
+  let stage = |number: u8| git(root, &["show", &format!(":{number}:{path}")]).ok();
+  let (Some(ours), Some(theirs)) = (stage(2), stage(3)) else {
+    return Ok(false);
+  };
+  let base = stage(1).unwrap_or_default();
+
+  let (base_meta, base_details) = insight::parse_insight_with_metadata(&base)?;
+  let (our_meta, our_details) = insight::parse_insight_with_metadata(&ours)?;
+  let (their_meta, their_details) = insight::parse_insight_with_metadata(&theirs)?;
+
+  let (overview, overview_conflict) =
+    merge_text(root, &base_meta.overview, &our_meta.overview, &their_meta.overview)?;
+  let (details, details_conflict) = merge_text(root, &base_details, &our_details, &their_details)?;
+
+  let mut merged = Insight::new(our_meta.topic, our_meta.name, overview, details);
+  merged.created_at = our_meta.created_at.min(their_meta.created_at);
+  merged.last_updated = our_meta.last_updated.max(their_meta.last_updated);
+  merged.update_count = our_meta.update_count.max(their_meta.update_count);
+  fs::write(root.join(path), insight::to_markdown(&merged)?)?;
+
+  Ok(overview_conflict || details_conflict)
+}
+
+/// Three-way merge of one text field, with conflict markers where both sides changed a line
+pub fn merge_text(root: &Path, base: &str, ours: &str, theirs: &str) -> Result<(String, bool)> {
+  if ours == theirs || base == theirs {
+    return Ok((ours.to_string(), false));
+  }
+  if base == ours {
+    return Ok((theirs.to_string(), false));
+  }
+
+  let scratch = root.join(".git");
+  let files = [("ours", ours), ("base", base), ("theirs", theirs)]
+    .map(|(side, text)| (scratch.join(format!("sync-merge-{side}")), format!("{text}\n")));
+  for (path, text) in &files {
+    fs::write(path, text)?;
+  }
+
+  let [ours_path, base_path, theirs_path] = files.map(|(path, _)| path);
+  let output = Command::new("git")
+    .args(["merge-file", "-p", "-L", "local", "-L", "base", "-L", "remote"])
+    .args([&ours_path, &base_path, &theirs_path])
+    .output()
+    .context("Failed to run git")?;
+  for path in [ours_path, base_path, theirs_path] {
+    let _ = fs::remove_file(path);
+  }
+
+  // Exit status is the number of conflicts; negative (or >127) means the merge failed
+  match output.status.code() {
+    Some(conflicts @ 0..=127) => {
+      let merged = String::from_utf8_lossy(&output.stdout);
+      Ok((merged.strip_suffix('\n').unwrap_or(&merged).to_string(), conflicts > 0))
+    }
+    _ => Err(anyhow!("git merge-file failed: {}", String::from_utf8_lossy(&output.stderr).trim())),
+  }
+}
+
+fn ensure_remote(root: &Path) -> Result<()> {
+  if !is_enabled(root) {
+    return Err(anyhow!("Sync is not set up; run `blizz sync init --remote <url>` first"));
+  }
+  git(root, &["remote", "get-url", REMOTE])
+    .map(|_| ())
+    .map_err(|_| anyhow!("No remote configured; run `blizz sync init --remote <url>`"))
+}
+
+fn commit_locked(root: &Path, message: &str) -> Result<bool> {
+  git(root, &["add", "--all"])?;
+  if run_git(root, &["diff", "--cached", "--quiet"])?.status.success() {
+    return Ok(false);
+  }
+  git(root, &["commit", "--quiet", "-m", message])?;
+  Ok(true)
+}
+
+fn lock() -> std::sync::MutexGuard<'static, ()> {
+  GIT_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn lines(output: &str) -> Vec<String> {
+  output.lines().filter(|line| !line.is_empty()).map(str::to_string).collect()
+}
+
+fn run_git(root: &Path, args: &[&str]) -> Result<Output> {
+  Command::new("git").arg("-C").arg(root).args(args).output().context("Failed to run git")
+}
+
+fn git(root: &Path, args: &[&str]) -> Result<String> {
+  let output = run_git(root, args)?;
+  if !output.status.success() {
+    return Err(anyhow!(
+      "git {} failed: {}",
+      args.join(" "),
+      String::from_utf8_lossy(&output.stderr).trim()
+    ));
+  }
+  Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::path::PathBuf;
+  use tempfile::TempDir;
+
+  struct Machines {
+    _dir: TempDir,
+    laptop: PathBuf,
+    desktop: PathBuf,
+  }
+
+  /// Two insights roots syncing through a bare remote
+  fn machines() -> Machines {
+    let dir = TempDir::new().unwrap();
+    let remote = dir.path().join("remote.git");
+    Command::new("git").args(["init", "--quiet", "--bare"]).arg(&remote).status().unwrap();
+
+    let remote = remote.to_str().unwrap();
+    let (laptop, desktop) = (dir.path().join("laptop"), dir.path().join("desktop"));
+    init(&laptop, Some(remote)).unwrap();
+    push(&laptop).unwrap();
+    init(&desktop, Some(remote)).unwrap();
+    pull(&desktop).unwrap();
+    Machines { _dir: dir, laptop, desktop }
+  }
+
+  fn write(root: &Path, overview: &str, details: &str) {
+    let insight =
+      Insight::new("rust".to_string(), "errors".to_string(), overview.into(), details.into());
+    fs::create_dir_all(root.join("rust")).unwrap();
+    fs::write(root.join("rust/errors.insight.md"), insight::to_markdown(&insight).unwrap())
+      .unwrap();
+  }
+
+  fn read(root: &Path) -> (String, String) {
+    let content = fs::read_to_string(root.join("rust/errors.insight.md")).unwrap();
+    let (meta, details) = insight::parse_insight_with_metadata(&content).unwrap();
+    (meta.overview, details)
+  }
+
+  #[test]
+  fn test_only_insight_files_are_tracked() {
+    let m = machines();
+    assert!(is_enabled(&m.laptop));
+    write(&m.laptop, "Overview", "Details");
+    fs::write(m.laptop.join("webhooks.json"), "{\"secret\": \"s\"}").unwrap();
+    fs::create_dir_all(m.laptop.join(".history/rust")).unwrap();
+    fs::write(m.laptop.join(".history/rust/v1.insight.md"), "old").unwrap();
+    fs::write(m.laptop.join("rust/notes.txt"), "scratch").unwrap();
+
+    assert!(commit_all(&m.laptop, "Add rust/errors").unwrap());
+    let tracked = git(&m.laptop, &["ls-files"]).unwrap();
+    assert_eq!(lines(&tracked), vec![".gitignore", "rust/errors.insight.md"]);
+    assert!(!commit_all(&m.laptop, "Nothing").unwrap());
+  }
+
+  #[test]
+  fn test_pull_brings_in_remote_insights() {
+    let m = machines();
+    write(&m.laptop, "Overview", "Details");
+    push(&m.laptop).unwrap();
+
+    let outcome = pull(&m.desktop).unwrap();
+    assert_eq!(outcome.changed, vec!["rust/errors.insight.md"]);
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!(read(&m.desktop), ("Overview".to_string(), "Details".to_string()));
+  }
+
+  #[test]
+  fn test_concurrent_edits_merge_per_field() {
+    let m = machines();
+    write(&m.laptop, "Overview", "one\ntwo\nthree\nfour\nfive");
+    push(&m.laptop).unwrap();
+    pull(&m.desktop).unwrap();
+
+    // Different lines on each machine merge cleanly, even though both bumped the metadata
+    write(&m.laptop, "Overview", "ONE\ntwo\nthree\nfour\nfive");
+    push(&m.laptop).unwrap();
+    write(&m.desktop, "Overview", "one\ntwo\nthree\nfour\nFIVE");
+    let outcome = pull(&m.desktop).unwrap();
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!(read(&m.desktop).1, "ONE\ntwo\nthree\nfour\nFIVE");
+    push(&m.desktop).unwrap();
+    pull(&m.laptop).unwrap();
+
+    // The same line changed on both machines leaves markers in the details only
+    write(&m.laptop, "Laptop overview", "ONE\ntwo\nlaptop\nfour\nFIVE");
+    push(&m.laptop).unwrap();
+    write(&m.desktop, "Overview", "ONE\ntwo\ndesktop\nfour\nFIVE");
+    let outcome = pull(&m.desktop).unwrap();
+    assert_eq!(outcome.conflicts, vec!["rust/errors.insight.md"]);
+
+    let (overview, details) = read(&m.desktop);
+    assert_eq!(overview, "Laptop overview");
+    assert!(details.contains("<<<<<<< local\ndesktop\n=======\nlaptop\n>>>>>>> remote"));
+    assert!(git(&m.desktop, &["status", "--porcelain"]).unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_push_requires_sync_and_a_remote() {
+    let dir = TempDir::new().unwrap();
+    assert!(push(dir.path()).unwrap_err().to_string().contains("blizz sync init"));
+    init(dir.path(), None).unwrap();
+    assert!(pull(dir.path()).unwrap_err().to_string().contains("No remote"));
+  }
+}
@@ -4,10 +4,49 @@
 //! allowing different implementations (LanceDB, Qdrant, etc.) to be swapped
 //! without changing the higher-level application code.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use std::collections::HashMap;
 
 use crate::server::models::insight;
+use crate::server::models::sharding::ShardConfig;
+use crate::server::services::sharding::ShardStats;
+
+/// Storage engine behind the vector database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+  /// LanceDB tables on disk
+  #[cfg(feature = "ml-features")]
+  LanceDb,
+  /// Brute-force search in memory, journaled to disk
+  Memory,
+}
+
+impl Backend {
+  pub fn parse(value: &str) -> Result<Self> {
+    match value.trim().to_lowercase().as_str() {
+      #[cfg(feature = "ml-features")]
+      "lancedb" => Ok(Backend::LanceDb),
+      #[cfg(not(feature = "ml-features"))]
+      "lancedb" => Err(anyhow!("The lancedb vector backend needs the ml-features build")),
+      "memory" => Ok(Backend::Memory),
+      other => Err(anyhow!("Unknown vector backend '{other}' (expected lancedb or memory)")),
+    }
+  }
+}
+
+/// Get the configured vector database backend
+/// Default: lancedb in the ml-features build, memory otherwise
+/// Environment: INSIGHTS_VECTOR_BACKEND
+pub fn get_backend() -> Result<Backend> {
+  match std::env::var("INSIGHTS_VECTOR_BACKEND") {
+    Ok(value) if !value.trim().is_empty() => Backend::parse(&value),
+    #[cfg(feature = "ml-features")]
+    _ => Ok(Backend::LanceDb),
+    #[cfg(not(feature = "ml-features"))]
+    _ => Ok(Backend::Memory),
+  }
+}
 
 /// Generic search result from vector similarity operations
 #[derive(Debug, Clone)]
@@ -22,6 +61,8 @@ pub struct VectorSearchResult {
   pub overview: String,
   /// Detail content
   pub details: String,
+  /// Tags of the insight
+  pub tags: Vec<String>,
   /// Similarity score (0.0-1.0, higher is more similar)
   pub similarity: f32,
 }
@@ -32,12 +73,13 @@ pub trait VectorDatabase: Send + Sync {
   /// Store an insight's embedding in the database
   async fn store_embedding(&self, insight: &insight::Insight) -> Result<()>;
 
-  /// Search for similar embeddings
+  /// Search for similar embeddings among insights carrying every one of `tags`
   async fn search_similar(
     &self,
     query_embedding: &[f32],
     limit: usize,
     threshold: Option<f32>,
+    tags: &[String],
   ) -> Result<Vec<VectorSearchResult>>;
 
   /// Check if any embeddings exist in the database
@@ -57,6 +99,17 @@ pub trait VectorDatabase: Send + Sync {
 
   /// Reshape the database with fresh schema (clean slate approach)
   async fn reshape_database(&self, embedding_dimension: usize) -> Result<()>;
+
+  /// Row counts for every shard of the index
+  async fn shard_stats(&self) -> Result<Vec<ShardStats>>;
+
+  /// Stored embeddings for one topic, keyed by lowercased insight name
+  async fn topic_embeddings(&self, topic: &str) -> Result<HashMap<String, Vec<f32>>>;
+
+  /// Adopt a new shard layout, moving rows into their new shards
+  ///
+  /// Returns the number of rows that moved.
+  async fn rebalance(&self, config: ShardConfig) -> Result<usize>;
 }
 
 /// Type-erased wrapper for VectorDatabase implementations
@@ -79,8 +132,9 @@ impl VectorDatabase for BoxedVectorDatabase {
     query_embedding: &[f32],
     limit: usize,
     threshold: Option<f32>,
+    tags: &[String],
   ) -> Result<Vec<VectorSearchResult>> {
-    self.0.search_similar(query_embedding, limit, threshold).await
+    self.0.search_similar(query_embedding, limit, threshold, tags).await
   }
 
   async fn has_embeddings(&self) -> Result<bool> {
@@ -106,4 +160,16 @@ impl VectorDatabase for BoxedVectorDatabase {
   async fn reshape_database(&self, embedding_dimension: usize) -> Result<()> {
     self.0.reshape_database(embedding_dimension).await
   }
+
+  async fn shard_stats(&self) -> Result<Vec<ShardStats>> {
+    self.0.shard_stats().await
+  }
+
+  async fn topic_embeddings(&self, topic: &str) -> Result<HashMap<String, Vec<f32>>> {
+    self.0.topic_embeddings(topic).await
+  }
+
+  async fn rebalance(&self, config: ShardConfig) -> Result<usize> {
+    self.0.rebalance(config).await
+  }
 }
@@ -0,0 +1,362 @@
+//! Watch mode: keep embeddings in step with insight files edited on disk
+//!
+//! The insights root is polled for modification times and sizes. Once edits have
+//! settled for the debounce period, changed insights are re-indexed and re-embedded
+//! and deleted ones dropped from the full-text index and vector database. The files themselves are never rewritten,
+//! so reindexing cannot trigger another round of changes.
+
+use anyhow::Result;
+use bentley::config::{self, Key};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::server::middleware::{server_info, server_warn};
+use crate::server::models::{insight, topic};
+use crate::server::services::{fulltext, indexing};
+use crate::settings;
+
+/// Default interval between scans of the insights root
+const DEFAULT_POLL_INTERVAL_MS: u64 = 1000;
+
+/// Default quiet period before a burst of edits is reindexed
+const DEFAULT_DEBOUNCE_MS: u64 = 2000;
+
+/// Modification time and size of every insight file, keyed by path
+pub type Snapshot = HashMap<PathBuf, (SystemTime, u64)>;
+
+/// Insight files that differ between two snapshots
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+  /// Files that were added or modified
+  pub changed: Vec<PathBuf>,
+  /// Files that no longer exist
+  pub removed: Vec<PathBuf>,
+}
+
+impl Changes {
+  pub fn is_empty(&self) -> bool {
+    self.changed.is_empty() && self.removed.is_empty()
+  }
+}
+
+/// Scan every topic directory for insight files
+pub fn snapshot() -> Result<Snapshot> {
+  let root = insight::get_insights_root()?;
+  let mut snapshot = Snapshot::new();
+
+  for topic in insight::get_topics()? {
+    let Ok(entries) = std::fs::read_dir(root.join(&topic)) else {
+      // The topic was removed between listing and reading it
+      continue;
+    };
+    for entry in entries.flatten() {
+      let path = entry.path();
+      if !insight::is_insight_file(&path) {
+        continue;
+      }
+      if let Ok(metadata) = entry.metadata() {
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        snapshot.insert(path, (modified, metadata.len()));
+      }
+    }
+  }
+
+  Ok(snapshot)
+}
+
+/// Compare two snapshots, listing paths in sorted order
+pub fn diff(old: &Snapshot, new: &Snapshot) -> Changes {
+  let mut changed: Vec<PathBuf> = new
+    .iter()
+    .filter(|(path, stamp)| old.get(*path) != Some(stamp))
+    .map(|(p, _)| p.clone())
+    .collect();
+  let mut removed: Vec<PathBuf> =
+    old.keys().filter(|path| !new.contains_key(*path)).cloned().collect();
+
+  changed.sort();
+  removed.sort();
+  Changes { changed, removed }
+}
+
+/// Topic and insight name for an insight file path
+///
+/// Files under the insights root get their full nested topic; anywhere else
+/// the enclosing directory is taken as the topic.
+pub fn insight_id(path: &Path) -> Option<(String, String)> {
+  let dir = path.parent()?;
+  let topic = match insight::get_insights_root().ok().and_then(|root| topic::from_dir(&root, dir)) {
+    Some(topic) => topic,
+    None => dir.file_name()?.to_str()?.to_string(),
+  };
+  let stem = path.file_stem()?.to_str()?;
+  let name = stem.strip_suffix(".insight")?;
+  Some((topic, name.to_string()))
+}
+
+/// Debounce state between polls
+#[derive(Debug)]
+pub struct WatchState {
+  /// Snapshot the vector database was last brought in line with
+  indexed: Snapshot,
+  /// Most recent snapshot taken
+  seen: Snapshot,
+  /// When `seen` last differed from the poll before it
+  last_change: Option<Instant>,
+}
+
+impl WatchState {
+  pub fn new(initial: Snapshot) -> Self {
+    Self { indexed: initial.clone(), seen: initial, last_change: None }
+  }
+
+  /// Record a poll; returns the changes to reindex once edits have been quiet for `debounce`
+  pub fn observe(
+    &mut self,
+    snapshot: Snapshot,
+    now: Instant,
+    debounce: Duration,
+  ) -> Option<Changes> {
+    if snapshot != self.seen {
+      self.seen = snapshot;
+      self.last_change = Some(now);
+      return None;
+    }
+
+    let settled = self.last_change.is_some_and(|at| now.duration_since(at) >= debounce);
+    if !settled {
+      return None;
+    }
+
+    self.last_change = None;
+    let changes = diff(&self.indexed, &self.seen);
+    self.indexed = self.seen.clone();
+    (!changes.is_empty()).then_some(changes)
+  }
+}
+
+/// Outcome of reindexing one batch of changes
+#[derive(Debug, Default)]
+pub struct ReindexReport {
+  /// `topic/name` of every insight re-embedded
+  pub updated: Vec<String>,
+  /// `topic/name` of every insight whose embedding was dropped
+  pub removed: Vec<String>,
+  /// Failures encountered while reindexing
+  pub errors: Vec<String>,
+}
+
+/// Bring the vector database in line with the changed files
+pub async fn reindex(changes: &Changes) -> ReindexReport {
+  let mut report = ReindexReport::default();
+
+  for path in &changes.changed {
+    let Some((topic, name)) = insight_id(path) else { continue };
+    let id = format!("{topic}/{name}");
+    indexing::wait_for_turn().await;
+    match reindex_insight(&topic, &name).await {
+      Ok(()) => report.updated.push(id),
+      Err(e) => report.errors.push(format!("{id}: {e}")),
+    }
+  }
+
+  for path in &changes.removed {
+    let Some((topic, name)) = insight_id(path) else { continue };
+    let id = format!("{topic}/{name}");
+    match unindex_insight(&topic, &name).await {
+      Ok(()) => report.removed.push(id),
+      Err(e) => report.errors.push(format!("{id}: {e}")),
+    }
+  }
+
+  report
+}
+
+async fn reindex_insight(topic: &str, name: &str) -> Result<()> {
+  let loaded = insight::load(topic, name)?;
+  fulltext::index_insights(std::slice::from_ref(&loaded))?;
+  update_embedding(&loaded).await
+}
+
+async fn unindex_insight(topic: &str, name: &str) -> Result<()> {
+  fulltext::remove_insight(topic, name)?;
+  remove_embedding(topic, name).await
+}
+
+#[cfg(feature = "semantic")]
+async fn update_embedding(loaded: &insight::Insight) -> Result<()> {
+  use crate::server::services::vector_database::VectorDatabase;
+
+  let document_title = format!("{}/{}", loaded.topic, loaded.name);
+  let document_content = format!("{} {}", loaded.overview, loaded.details);
+  let embedding = crate::server::services::embeddings::create_document_embedding(
+    &document_content,
+    Some(&document_title),
+  )
+  .await?;
+
+  let mut with_embedding = loaded.clone();
+  with_embedding.embedding_version = Some(crate::server::services::embeddings::version().await?);
+  with_embedding.embedding = Some(embedding);
+  with_embedding.embedding_text =
+    Some(format!("title: {document_title} | text: {document_content}"));
+  with_embedding.embedding_computed = Some(chrono::Utc::now());
+
+  crate::server::middleware::get_global_vector_db().update_embedding(&with_embedding).await
+}
+
+#[cfg(not(feature = "semantic"))]
+async fn update_embedding(_loaded: &insight::Insight) -> Result<()> {
+  // No-op: embeddings not available without the semantic feature
+  Ok(())
+}
+
+#[cfg(feature = "semantic")]
+async fn remove_embedding(topic: &str, name: &str) -> Result<()> {
+  use crate::server::services::vector_database::VectorDatabase;
+
+  crate::server::middleware::get_global_vector_db().delete_embedding(topic, name).await
+}
+
+#[cfg(not(feature = "semantic"))]
+async fn remove_embedding(_topic: &str, _name: &str) -> Result<()> {
+  // No-op: embeddings not available without the semantic feature
+  Ok(())
+}
+
+/// Get the configured interval between scans
+/// Default: 1000 milliseconds
+/// Setting: insights.watch.poll_ms (INSIGHTS_WATCH_POLL_MS)
+pub fn get_poll_interval() -> Duration {
+  Duration::from_millis(setting_millis(&settings::WATCH_POLL_MS, DEFAULT_POLL_INTERVAL_MS))
+}
+
+/// Get the configured quiet period before reindexing
+/// Default: 2000 milliseconds
+/// Setting: insights.watch.debounce_ms (INSIGHTS_WATCH_DEBOUNCE_MS)
+pub fn get_debounce() -> Duration {
+  Duration::from_millis(setting_millis(&settings::WATCH_DEBOUNCE_MS, DEFAULT_DEBOUNCE_MS))
+}
+
+fn setting_millis(key: &Key, default: u64) -> u64 {
+  config::get(key).filter(|ms| *ms > 0).unwrap_or(default)
+}
+
+/// Spawn the background job that reindexes insight files as they change
+#[cfg(not(tarpaulin_include))] // Skip coverage - long-running background task
+pub fn spawn_watcher(poll_interval: Duration, debounce: Duration) -> tokio::task::JoinHandle<()> {
+  tokio::spawn(async move {
+    let mut state = WatchState::new(snapshot().unwrap_or_default());
+    let mut ticker = tokio::time::interval(poll_interval);
+    loop {
+      ticker.tick().await;
+      let current = match snapshot() {
+        Ok(current) => current,
+        Err(e) => {
+          server_warn(&format!("Failed to scan insights: {e}"), "insights-watch").await;
+          continue;
+        }
+      };
+      if let Some(changes) = state.observe(current, Instant::now(), debounce) {
+        log_reindex(&reindex(&changes).await).await;
+      }
+    }
+  })
+}
+
+async fn log_reindex(report: &ReindexReport) {
+  if !report.updated.is_empty() {
+    server_info(
+      &format!(
+        "Reindexed {} changed insights: {}",
+        report.updated.len(),
+        report.updated.join(", ")
+      ),
+      "insights-watch",
+    )
+    .await;
+  }
+
+  if !report.removed.is_empty() {
+    server_info(
+      &format!("Dropped {} deleted insights: {}", report.removed.len(), report.removed.join(", ")),
+      "insights-watch",
+    )
+    .await;
+  }
+
+  for error in &report.errors {
+    server_warn(&format!("Failed to reindex {error}"), "insights-watch").await;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stamp(secs: u64, len: u64) -> (SystemTime, u64) {
+    (SystemTime::UNIX_EPOCH + Duration::from_secs(secs), len)
+  }
+
+  fn snap(entries: &[(&str, u64)]) -> Snapshot {
+    entries.iter().map(|(path, secs)| (PathBuf::from(path), stamp(*secs, 10))).collect()
+  }
+
+  #[test]
+  fn test_diff_reports_added_modified_and_removed() {
+    let old = snap(&[("/r/rust/a.insight.md", 1), ("/r/rust/b.insight.md", 1)]);
+    let new = snap(&[("/r/rust/a.insight.md", 2), ("/r/rust/c.insight.md", 1)]);
+
+    let changes = diff(&old, &new);
+    assert_eq!(
+      changes.changed,
+      vec![PathBuf::from("/r/rust/a.insight.md"), PathBuf::from("/r/rust/c.insight.md")]
+    );
+    assert_eq!(changes.removed, vec![PathBuf::from("/r/rust/b.insight.md")]);
+  }
+
+  #[test]
+  fn test_insight_id_from_path() {
+    assert_eq!(
+      insight_id(Path::new("/r/rust/tokio-tips.insight.md")),
+      Some(("rust".to_string(), "tokio-tips".to_string()))
+    );
+    assert_eq!(insight_id(Path::new("/r/rust/notes.md")), None);
+  }
+
+  #[test]
+  fn test_observe_waits_for_edits_to_settle() {
+    let debounce = Duration::from_secs(2);
+    let start = Instant::now();
+    let mut state = WatchState::new(snap(&[("/r/t/a.insight.md", 1)]));
+
+    // Nothing changed yet
+    assert_eq!(state.observe(snap(&[("/r/t/a.insight.md", 1)]), start, debounce), None);
+
+    // A burst of edits keeps pushing the deadline back
+    let edited = snap(&[("/r/t/a.insight.md", 2)]);
+    assert_eq!(state.observe(edited.clone(), start, debounce), None);
+    let edited_again = snap(&[("/r/t/a.insight.md", 3)]);
+    let later = start + Duration::from_secs(1);
+    assert_eq!(state.observe(edited_again.clone(), later, debounce), None);
+    assert_eq!(state.observe(edited_again.clone(), later + Duration::from_secs(1), debounce), None);
+
+    // Quiet for the full debounce period: reindex once
+    let settled = state.observe(edited_again.clone(), later + debounce, debounce).unwrap();
+    assert_eq!(settled.changed, vec![PathBuf::from("/r/t/a.insight.md")]);
+    assert_eq!(state.observe(edited_again, later + debounce * 2, debounce), None);
+  }
+
+  #[test]
+  fn test_observe_ignores_edits_that_revert() {
+    let debounce = Duration::from_secs(1);
+    let start = Instant::now();
+    let original = snap(&[("/r/t/a.insight.md", 1)]);
+    let mut state = WatchState::new(original.clone());
+
+    assert_eq!(state.observe(snap(&[("/r/t/a.insight.md", 2)]), start, debounce), None);
+    assert_eq!(state.observe(original.clone(), start, debounce), None);
+    assert_eq!(state.observe(original, start + debounce, debounce), None);
+  }
+}
@@ -0,0 +1,331 @@
+//! Delivery of signed webhook notifications for insight changes
+//!
+//! Every matching webhook gets its own background task, so a slow or failing
+//! endpoint never holds up the request that changed the insight. Payloads are
+//! signed with HMAC-SHA256 over the raw body, sent in the `X-Insights-Signature`
+//! header as `sha256=<hex>`. Failed deliveries are retried with exponential
+//! backoff and the outcome of each one is appended to a delivery log.
+
+use anyhow::Result;
+use bentley::config;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::server::middleware::server_warn;
+use crate::server::models::insight::{self, Insight};
+use crate::server::models::webhook::{self, Webhook, WebhookEvent};
+use crate::settings;
+
+const DELIVERY_LOG_FILE: &str = "webhook-deliveries.jsonl";
+
+/// Oldest entries are dropped once the delivery log grows past this size
+const MAX_LOG_ENTRIES: usize = 1000;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_BACKOFF_MS: u64 = 1000;
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub const SIGNATURE_HEADER: &str = "X-Insights-Signature";
+pub const EVENT_HEADER: &str = "X-Insights-Event";
+pub const DELIVERY_HEADER: &str = "X-Insights-Delivery";
+
+/// Serializes writers of the delivery log
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// JSON body sent to webhooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+  pub delivery_id: String,
+  pub event: WebhookEvent,
+  pub topic: String,
+  pub name: String,
+  pub overview: String,
+  pub details: String,
+  pub occurred_at: DateTime<Utc>,
+}
+
+impl WebhookPayload {
+  pub fn new(event: WebhookEvent, insight: &Insight) -> Self {
+    Self {
+      delivery_id: uuid::Uuid::new_v4().to_string(),
+      event,
+      topic: insight.topic.clone(),
+      name: insight.name.clone(),
+      overview: insight.overview.clone(),
+      details: insight.details.clone(),
+      occurred_at: Utc::now(),
+    }
+  }
+}
+
+/// Outcome of delivering one payload to one webhook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DeliveryRecord {
+  pub delivery_id: String,
+  pub webhook_id: String,
+  pub event: WebhookEvent,
+  /// `topic/name` of the insight the event was about
+  pub insight: String,
+  pub attempts: u32,
+  /// HTTP status of the last attempt, if the endpoint answered
+  pub status: Option<u16>,
+  pub success: bool,
+  /// Why the last attempt failed
+  pub error: Option<String>,
+  pub finished_at: DateTime<Utc>,
+}
+
+/// Retry settings for deliveries
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+  pub max_attempts: u32,
+  pub initial_backoff: Duration,
+}
+
+impl RetryPolicy {
+  /// Delay before retrying after the given (1-based) failed attempt
+  pub fn backoff(&self, attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    self.initial_backoff.saturating_mul(factor).min(MAX_BACKOFF)
+  }
+}
+
+/// Get the configured retry policy
+/// Default: 5 attempts, starting at 1000ms and doubling
+/// Setting: insights.webhooks.max_attempts (INSIGHTS_WEBHOOK_MAX_ATTEMPTS),
+/// insights.webhooks.backoff_ms (INSIGHTS_WEBHOOK_BACKOFF_MS)
+pub fn get_retry_policy() -> RetryPolicy {
+  let max_attempts = config::get(&settings::WEBHOOK_MAX_ATTEMPTS)
+    .filter(|attempts| *attempts > 0)
+    .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+  let backoff_ms = config::get(&settings::WEBHOOK_BACKOFF_MS).unwrap_or(DEFAULT_BACKOFF_MS);
+  RetryPolicy { max_attempts, initial_backoff: Duration::from_millis(backoff_ms) }
+}
+
+/// Hex HMAC-SHA256 of `body` keyed with the webhook secret
+pub fn sign(secret: &str, body: &[u8]) -> String {
+  let mut mac =
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+  mac.update(body);
+  hex::encode(mac.finalize().into_bytes())
+}
+
+/// Notify every webhook subscribed to this change in the background
+pub async fn notify(event: WebhookEvent, insight: &Insight) {
+  let webhooks = match webhook::load_webhooks() {
+    Ok(webhooks) => webhooks,
+    Err(e) => {
+      server_warn(&format!("Failed to load webhooks: {e}"), "insights-webhooks").await;
+      return;
+    }
+  };
+
+  let policy = get_retry_policy();
+  for target in webhooks.into_iter().filter(|w| w.matches(&insight.topic, event)) {
+    let payload = WebhookPayload::new(event, insight);
+    tokio::spawn(async move {
+      let record = deliver(&target, &payload, policy).await;
+      if !record.success {
+        server_warn(
+          &format!(
+            "Webhook {} gave up on {} {} after {} attempt(s): {}",
+            target.id,
+            record.event,
+            record.insight,
+            record.attempts,
+            record.error.as_deref().unwrap_or("unknown error")
+          ),
+          "insights-webhooks",
+        )
+        .await;
+      }
+      if let Err(e) = append_delivery(&record) {
+        server_warn(&format!("Failed to write webhook delivery log: {e}"), "insights-webhooks")
+          .await;
+      }
+    });
+  }
+}
+
+/// Send a payload, retrying transient failures, and report how it went
+pub async fn deliver(
+  target: &Webhook,
+  payload: &WebhookPayload,
+  policy: RetryPolicy,
+) -> DeliveryRecord {
+  let body = serde_json::to_vec(payload).expect("webhook payloads always serialize");
+  let signature = format!("sha256={}", sign(&target.secret, &body));
+  let client = reqwest::Client::builder()
+    .timeout(REQUEST_TIMEOUT)
+    .build()
+    .unwrap_or_else(|_| reqwest::Client::new());
+
+  let mut attempts = 0;
+  let mut status = None;
+  let mut error = None;
+  while attempts < policy.max_attempts {
+    if attempts > 0 {
+      tokio::time::sleep(policy.backoff(attempts)).await;
+    }
+    attempts += 1;
+
+    let response = client
+      .post(&target.url)
+      .header(reqwest::header::CONTENT_TYPE, "application/json")
+      .header(SIGNATURE_HEADER, &signature)
+      .header(EVENT_HEADER, payload.event.to_string())
+      .header(DELIVERY_HEADER, &payload.delivery_id)
+      .body(body.clone())
+      .send()
+      .await;
+
+    match response {
+      Ok(response) if response.status().is_success() => {
+        status = Some(response.status().as_u16());
+        error = None;
+        break;
+      }
+      Ok(response) => {
+        let code = response.status();
+        status = Some(code.as_u16());
+        error = Some(format!("endpoint responded with {code}"));
+        // Other client errors will not go away by sending the same payload again
+        if code.is_client_error() && code != reqwest::StatusCode::TOO_MANY_REQUESTS {
+          break;
+        }
+      }
+      Err(e) => {
+        status = None;
+        error = Some(e.to_string());
+      }
+    }
+  }
+
+  DeliveryRecord {
+    delivery_id: payload.delivery_id.clone(),
+    webhook_id: target.id.clone(),
+    event: payload.event,
+    insight: format!("{}/{}", payload.topic, payload.name),
+    attempts,
+    status,
+    success: error.is_none(),
+    error,
+    finished_at: Utc::now(),
+  }
+}
+
+fn delivery_log_path() -> Result<PathBuf> {
+  Ok(insight::get_insights_root()?.join(DELIVERY_LOG_FILE))
+}
+
+/// Record a finished delivery, trimming the log once it grows too long
+pub fn append_delivery(record: &DeliveryRecord) -> Result<()> {
+  let _guard = LOG_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+  let path = delivery_log_path()?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+  writeln!(file, "{}", serde_json::to_string(record)?)?;
+  drop(file);
+
+  let content = fs::read_to_string(&path)?;
+  let lines: Vec<&str> = content.lines().collect();
+  if lines.len() > MAX_LOG_ENTRIES {
+    let kept = &lines[lines.len() - MAX_LOG_ENTRIES..];
+    fs::write(&path, format!("{}\n", kept.join("\n")))?;
+  }
+  Ok(())
+}
+
+/// Most recent deliveries first, optionally only those for one webhook
+pub fn recent_deliveries(webhook_id: Option<&str>, limit: usize) -> Result<Vec<DeliveryRecord>> {
+  let path = delivery_log_path()?;
+  if !path.exists() {
+    return Ok(Vec::new());
+  }
+
+  let content = fs::read_to_string(path)?;
+  let deliveries = content
+    .lines()
+    .rev()
+    .filter_map(|line| serde_json::from_str::<DeliveryRecord>(line).ok())
+    .filter(|record| webhook_id.is_none_or(|id| record.webhook_id == id))
+    .take(limit)
+    .collect();
+  Ok(deliveries)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn insight(topic: &str) -> Insight {
+    Insight::new(
+      topic.to_string(),
+      "outage".to_string(),
+      "Overview".to_string(),
+      "Details".to_string(),
+    )
+  }
+
+  #[test]
+  fn test_sign_matches_known_hmac() {
+    // RFC 4231 test case 2
+    assert_eq!(
+      sign("Jefe", b"what do ya want for nothing?"),
+      "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+  }
+
+  #[test]
+  fn test_backoff_doubles_up_to_the_cap() {
+    let policy = RetryPolicy { max_attempts: 5, initial_backoff: Duration::from_secs(1) };
+    assert_eq!(policy.backoff(1), Duration::from_secs(1));
+    assert_eq!(policy.backoff(2), Duration::from_secs(2));
+    assert_eq!(policy.backoff(4), Duration::from_secs(8));
+    assert_eq!(policy.backoff(40), MAX_BACKOFF);
+  }
+
+  #[test]
+  fn test_webhook_matches_topics_and_events() {
+    let everything = Webhook::new("http://localhost/hook", "s", &[], &[]);
+    assert!(everything.matches("anything", WebhookEvent::Deleted));
+
+    let incidents = Webhook::new(
+      "http://localhost/hook",
+      "s",
+      &["Incidents".to_string()],
+      &[WebhookEvent::Created],
+    );
+    assert!(incidents.matches("incidents", WebhookEvent::Created));
+    assert!(incidents.matches("INCIDENTS", WebhookEvent::Created));
+    assert!(!incidents.matches("incidents", WebhookEvent::Updated));
+    assert!(!incidents.matches("notes", WebhookEvent::Created));
+  }
+
+  #[tokio::test]
+  async fn test_unreachable_endpoint_retries_then_gives_up() {
+    // Nothing listens on the discard port
+    let target = Webhook::new("http://127.0.0.1:9/hook", "secret", &[], &[]);
+    let payload = WebhookPayload::new(WebhookEvent::Created, &insight("incidents"));
+    let policy = RetryPolicy { max_attempts: 3, initial_backoff: Duration::ZERO };
+
+    let record = deliver(&target, &payload, policy).await;
+    assert!(!record.success);
+    assert_eq!(record.attempts, 3);
+    assert_eq!(record.status, None);
+    assert_eq!(record.insight, "incidents/outage");
+    assert!(record.error.is_some());
+  }
+}
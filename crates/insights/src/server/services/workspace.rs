@@ -0,0 +1,165 @@
+//! Workspaces: separate knowledge bases served by one server
+//!
+//! Every workspace has its own insight files, vector data, access stats and the
+//! rest of the state kept under the insights root. The `default` workspace is the
+//! insights root itself, so existing setups keep working; the others live in
+//! `.workspaces/<name>` inside it, which topic discovery, sync and backups of the
+//! default workspace leave alone like any other dot-directory.
+//!
+//! A request picks its workspace with the `X-Insights-Workspace` header. The
+//! choice is held in a task-local for the duration of the request, so everything
+//! that resolves the insights root follows it without extra arguments. Work that
+//! outlives a request has to be started with [`spawn`] to keep its workspace;
+//! background jobs (the watcher, retention sweeps and scheduled backups) act on
+//! the default workspace.
+
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use crate::server::models::insight;
+
+/// Workspace used when a request names none
+pub const DEFAULT: &str = "default";
+
+/// Request header naming the workspace
+pub const HEADER: &str = "x-insights-workspace";
+
+/// Folder inside the insights root (and the vector data folder) holding the other workspaces
+pub const WORKSPACES_DIR: &str = ".workspaces";
+
+const MAX_NAME_LEN: usize = 64;
+
+tokio::task_local! {
+  static CURRENT: String;
+}
+
+/// Check that a workspace name is safe to use as a directory name
+pub fn validate(name: &str) -> Result<()> {
+  let valid_chars = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+  let starts_well = name.chars().next().is_some_and(|c| c.is_ascii_alphanumeric());
+  if name.len() > MAX_NAME_LEN || !valid_chars || !starts_well {
+    return Err(anyhow!(
+      "Invalid workspace name '{name}': use up to {MAX_NAME_LEN} letters, digits, '-' or '_', \
+       starting with a letter or digit"
+    ));
+  }
+  Ok(())
+}
+
+/// Workspace of the request being handled, or the default outside of one
+pub fn current() -> String {
+  CURRENT.try_with(Clone::clone).unwrap_or_else(|_| DEFAULT.to_string())
+}
+
+/// Run `future` with `name` as the current workspace
+pub async fn scope<F: Future>(name: String, future: F) -> F::Output {
+  CURRENT.scope(name, future).await
+}
+
+/// Spawn a task that stays in the current workspace
+pub fn spawn<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+where
+  F: Future + Send + 'static,
+  F::Output: Send + 'static,
+{
+  tokio::spawn(CURRENT.scope(current(), future))
+}
+
+/// Where a workspace keeps its data, given the folder the default workspace uses
+pub fn dir(base: &Path, name: &str) -> PathBuf {
+  if name == DEFAULT {
+    base.to_path_buf()
+  } else {
+    base.join(WORKSPACES_DIR).join(name)
+  }
+}
+
+/// Whether a workspace has been created (the default always exists)
+pub fn exists(name: &str) -> Result<bool> {
+  Ok(name == DEFAULT || dir(&insight::get_base_root()?, name).is_dir())
+}
+
+/// Every workspace, the default first and the rest by name
+pub fn list() -> Result<Vec<String>> {
+  let mut names = Vec::new();
+  let workspaces = insight::get_base_root()?.join(WORKSPACES_DIR);
+  if workspaces.is_dir() {
+    for entry in fs::read_dir(&workspaces)? {
+      let entry = entry?;
+      let name = entry.file_name().to_string_lossy().to_string();
+      if entry.file_type()?.is_dir() && validate(&name).is_ok() && name != DEFAULT {
+        names.push(name);
+      }
+    }
+  }
+  names.sort();
+  names.insert(0, DEFAULT.to_string());
+  Ok(names)
+}
+
+/// Create an empty workspace, returning its insights root
+pub fn create(name: &str) -> Result<PathBuf> {
+  validate(name)?;
+  if exists(name)? {
+    return Err(anyhow!("Workspace '{name}' already exists"));
+  }
+  let root = dir(&insight::get_base_root()?, name);
+  fs::create_dir_all(&root)?;
+  Ok(root)
+}
+
+/// Delete a workspace with its insights and vector data
+pub fn delete(name: &str, vector_base: &Path) -> Result<()> {
+  validate(name)?;
+  if name == DEFAULT {
+    return Err(anyhow!("The default workspace cannot be deleted"));
+  }
+  if !exists(name)? {
+    return Err(anyhow!("Workspace '{name}' not found"));
+  }
+
+  fs::remove_dir_all(dir(&insight::get_base_root()?, name))?;
+  let vectors = dir(vector_base, name);
+  if vectors.exists() {
+    fs::remove_dir_all(vectors)?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_validate_accepts_directory_safe_names() {
+    for name in ["work", "personal-2", "client_a", "A1"] {
+      assert!(validate(name).is_ok(), "{name} should be valid");
+    }
+    for name in ["", "-work", ".hidden", "a/b", "..", "with space", &"x".repeat(65)] {
+      assert!(validate(name).is_err(), "{name:?} should be rejected");
+    }
+  }
+
+  #[test]
+  fn test_dir_keeps_default_at_the_base() {
+    let base = Path::new("/data/insights");
+    assert_eq!(dir(base, DEFAULT), base);
+    assert_eq!(dir(base, "work"), Path::new("/data/insights/.workspaces/work"));
+  }
+
+  #[tokio::test]
+  async fn test_current_follows_scope_and_spawned_tasks() {
+    assert_eq!(current(), DEFAULT);
+
+    let (inside, spawned) =
+      scope("work".to_string(), async { (current(), spawn(async { current() }).await.unwrap()) })
+        .await;
+    assert_eq!(inside, "work");
+    assert_eq!(spawned, "work");
+
+    assert_eq!(current(), DEFAULT);
+    assert_eq!(tokio::spawn(async { current() }).await.unwrap(), DEFAULT);
+  }
+}
@@ -2,6 +2,7 @@
 
 use anyhow::Result;
 use axum::serve;
+use bentley::config;
 use bentley::daemon_logs::DaemonLogs;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::net::TcpListener;
@@ -10,18 +11,24 @@ use tower_http::{cors::CorsLayer, trace::TraceLayer};
 
 use crate::server::{
   middleware::{self, init_global_logger},
-  routing::create_router,
+  routing::{create_router, create_ui_router},
+  services::{auth, backup, indexing, retention, watcher, workspace},
 };
+use crate::settings;
 
-#[cfg(feature = "ml-features")]
+#[cfg(feature = "semantic")]
 use crate::server::{
-  middleware::init_global_vector_db,
-  services::{lancedb::LanceDbVectorDatabase, vector_database::BoxedVectorDatabase},
+  middleware::{init_global_vector_db, init_vector_db_opener},
+  models::sharding,
+  services::{
+    memory_db::MemoryVectorDatabase,
+    vector_database::{self, Backend, BoxedVectorDatabase},
+  },
 };
 
 /// Start the REST server
 #[cfg(not(tarpaulin_include))] // Skip coverage - server lifecycle and daemon logs initialization
-pub async fn start_server(addr: SocketAddr) -> Result<()> {
+pub async fn start_server(addr: SocketAddr, watch: bool, ui: bool) -> Result<()> {
   // Initialize daemon logs for persistent logging
   let logs_path = get_server_logs_path();
   let daemon_logs = Arc::new(DaemonLogs::new(&logs_path)?);
@@ -30,43 +37,110 @@ pub async fn start_server(addr: SocketAddr) -> Result<()> {
   init_global_logger(daemon_logs.clone())
     .map_err(|_| anyhow::anyhow!("Failed to initialize global logger"))?;
 
-  // Set log level to Info by default (can be overridden via env var)
-  let log_level = std::env::var("INSIGHTS_LOG_LEVEL")
+  // Set log level to Info by default (can be overridden in settings)
+  let log_level = config::var(&settings::LOG_LEVEL)
     .map(|s| middleware::LogLevel::parse(&s))
     .unwrap_or(middleware::LogLevel::Info);
 
   middleware::set_log_level(log_level);
 
-  // Initialize vector database service (only with ml-features)
-  #[cfg(feature = "ml-features")]
-  {
-    let lancedb_path = get_lancedb_data_path();
-    let lancedb_service = LanceDbVectorDatabase::new(lancedb_path, "insights_embeddings")
-      .await
-      .map_err(|e| anyhow::anyhow!("Failed to initialize vector database: {}", e))?;
+  // Keep embedding work from starving the rest of the machine
+  if let Some(nice) = indexing::get_nice() {
+    match indexing::apply_nice(nice) {
+      Ok(()) => {
+        daemon_logs
+          .info(&format!("Lowered server priority to nice {nice}"), "insights-server")
+          .await
+      }
+      Err(e) => {
+        daemon_logs.warn(&format!("Failed to lower server priority: {e}"), "insights-server").await
+      }
+    }
+  }
+  if let Some(threads) = indexing::get_max_threads() {
+    daemon_logs
+      .info(&format!("Embedding computation limited to {threads} threads"), "insights-server")
+      .await;
+  }
 
-    let vector_db_service = Arc::new(BoxedVectorDatabase::new(lancedb_service));
+  // Initialize vector database service (only with the semantic feature)
+  #[cfg(feature = "semantic")]
+  {
+    let backend = vector_database::get_backend()?;
+    let vector_db_service = Arc::new(open_vector_db().await?);
 
     // Initialize global vector database service
     init_global_vector_db(vector_db_service)
       .map_err(|_| anyhow::anyhow!("Failed to initialize global vector database service"))?;
-
-    daemon_logs.info("Vector database service initialized successfully", "insights-server").await;
+    // Other workspaces open theirs the same way when first used
+    let _ = init_vector_db_opener(|| Box::pin(open_vector_db()));
+
+    daemon_logs
+      .info(
+        &format!("Vector database service initialized successfully ({backend:?} backend)"),
+        "insights-server",
+      )
+      .await;
   }
 
-  #[cfg(not(feature = "ml-features"))]
+  #[cfg(not(feature = "semantic"))]
   {
-    daemon_logs.info("Running in lightweight mode (no ML features)", "insights-server").await;
+    daemon_logs.info("Running in lightweight mode (no semantic search)", "insights-server").await;
+  }
+
+  // Archive insights whose topic retention policy has expired them
+  let retention_interval = retention::get_sweep_interval();
+  retention::spawn_scheduler(retention_interval);
+  daemon_logs
+    .info(
+      &format!("Retention sweeps scheduled every {}s", retention_interval.as_secs()),
+      "insights-server",
+    )
+    .await;
+
+  // Snapshot insights and vectors on a schedule
+  match backup::get_backup_interval() {
+    Some(interval) => {
+      backup::spawn_scheduler(interval);
+      daemon_logs
+        .info(&format!("Backups scheduled every {}s", interval.as_secs()), "insights-server")
+        .await;
+    }
+    None => daemon_logs.info("Scheduled backups are disabled", "insights-server").await,
+  }
+
+  // Keep embeddings in step with insight files edited outside the API
+  if watch {
+    let poll_interval = watcher::get_poll_interval();
+    let debounce = watcher::get_debounce();
+    watcher::spawn_watcher(poll_interval, debounce);
+    daemon_logs
+      .info(
+        &format!(
+          "Watching insights for changes every {}ms ({}ms debounce)",
+          poll_interval.as_millis(),
+          debounce.as_millis()
+        ),
+        "insights-server",
+      )
+      .await;
   }
 
   // Log server startup
   daemon_logs.info(&format!("Starting insights REST server on {addr}"), "insights-server").await;
   bentley::info!(&format!("Starting insights REST server on {addr}"));
 
-  // Create the router with additional middleware
-  let app = create_router().layer(
+  // The browser UI only ships when asked for, so headless servers stay lean
+  let router = if ui { create_router().merge(create_ui_router()) } else { create_router() };
+  if ui {
+    daemon_logs.info(&format!("Web UI available at http://{addr}/ui"), "insights-server").await;
+  }
+
+  // Create the router with additional middleware; tokens are checked before anything else runs
+  let app = router.layer(axum::middleware::from_fn(middleware::auth_middleware)).layer(
     ServiceBuilder::new().layer(TraceLayer::new_for_http()).layer(CorsLayer::permissive()), // TODO: Configure CORS properly for production
   );
+  log_auth_mode(&daemon_logs).await;
 
   // Create listener
   let listener = TcpListener::bind(addr).await?;
@@ -85,6 +159,46 @@ pub async fn start_server(addr: SocketAddr) -> Result<()> {
   }
 }
 
+/// Open the current workspace's vector database with the configured backend
+#[cfg(feature = "semantic")]
+async fn open_vector_db() -> Result<BoxedVectorDatabase> {
+  let shard_config = sharding::load_config().unwrap_or_else(|e| {
+    bentley::warn!(&format!("Ignoring invalid shard layout, using a single table: {e}"));
+    Default::default()
+  });
+  Ok(match vector_database::get_backend()? {
+    #[cfg(feature = "ml-features")]
+    Backend::LanceDb => BoxedVectorDatabase::new(
+      crate::server::services::lancedb::LanceDbVectorDatabase::new(
+        get_vector_data_path().join("lancedb"),
+        shard_config,
+      )
+      .await
+      .map_err(|e| anyhow::anyhow!("Failed to initialize vector database: {}", e))?,
+    ),
+    Backend::Memory => BoxedVectorDatabase::new(
+      MemoryVectorDatabase::open(get_vector_data_path().join("memory"), shard_config)
+        .map_err(|e| anyhow::anyhow!("Failed to initialize vector database: {}", e))?,
+    ),
+  })
+}
+
+/// Say whether the API is protected, so an open server is never a surprise
+async fn log_auth_mode(daemon_logs: &DaemonLogs) {
+  let message = match auth::load_table().await {
+    Ok(table) if !table.is_empty() => {
+      format!("API requires bearer tokens ({} minted)", table.entries().len())
+    }
+    _ if auth::get_require_auth() => "API requires bearer tokens".to_string(),
+    Ok(_) => "API is open: no tokens minted (see `insights token mint`)".to_string(),
+    Err(e) if auth::tokens_minted() => {
+      format!("API requires bearer tokens, which cannot be read yet ({e})")
+    }
+    Err(e) => format!("API is open: tokens could not be read ({e})"),
+  };
+  daemon_logs.info(&message, "insights-auth").await;
+}
+
 /// Get the path for server logs
 #[cfg(not(tarpaulin_include))] // Skip coverage - filesystem path operations
 fn get_server_logs_path() -> std::path::PathBuf {
@@ -96,13 +210,17 @@ fn get_server_logs_path() -> std::path::PathBuf {
     .join("server-logs.jsonl")
 }
 
-/// Get the path for LanceDB data storage
-#[cfg(all(feature = "ml-features", not(tarpaulin_include)))] // Skip coverage - filesystem path operations
-fn get_lancedb_data_path() -> std::path::PathBuf {
+/// Get the directory vector database backends keep the current workspace's data in
+pub fn get_vector_data_path() -> std::path::PathBuf {
+  workspace::dir(&get_vector_base_path(), &workspace::current())
+}
+
+/// Get the directory holding the default workspace's vector data and the other workspaces'
+#[cfg(not(tarpaulin_include))] // Skip coverage - filesystem path operations
+pub fn get_vector_base_path() -> std::path::PathBuf {
   dirs::home_dir()
     .unwrap_or_else(|| std::path::Path::new("/tmp").to_path_buf())
     .join(".blizz")
     .join("volatile")
     .join("insights")
-    .join("lancedb")
 }
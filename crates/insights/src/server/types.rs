@@ -3,8 +3,15 @@
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+use crate::server::models::sharding::{ShardConfig, ShardStrategy};
+use crate::server::models::webhook::WebhookEvent;
+use crate::server::services::reindex::JobState;
+use crate::server::services::search::SearchMode;
+use crate::server::services::webhooks::DeliveryRecord;
+
 // Base Response Structure
 // ======================
 
@@ -55,6 +62,71 @@ pub struct ApiError {
   /// Additional error context
   #[serde(default)]
   pub context: serde_json::Value,
+
+  /// Stable, machine-readable error category
+  #[serde(default)]
+  pub code: ErrorCode,
+}
+
+/// Stable error categories shared by the REST API and the CLI exit status
+///
+/// Codes are part of the public contract: never rename or renumber them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+  /// The requested insight, topic, or policy does not exist
+  NotFound,
+  /// An insight with the same topic and name already exists
+  AlreadyExists,
+  /// The insight changed since the client last read it
+  RevisionConflict,
+  /// The request was malformed or violated a constraint
+  ValidationFailed,
+  /// The search index or embedding store is not available
+  IndexUnavailable,
+  /// The content appears to contain secrets or personal data
+  SensitiveContent,
+  /// The caller is not allowed to perform the request
+  Unauthorized,
+  /// Any other server-side failure
+  #[default]
+  Internal,
+}
+
+impl ErrorCode {
+  /// Machine-readable name, as it appears in JSON bodies
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ErrorCode::NotFound => "NOT_FOUND",
+      ErrorCode::AlreadyExists => "ALREADY_EXISTS",
+      ErrorCode::RevisionConflict => "REVISION_CONFLICT",
+      ErrorCode::ValidationFailed => "VALIDATION_FAILED",
+      ErrorCode::IndexUnavailable => "INDEX_UNAVAILABLE",
+      ErrorCode::SensitiveContent => "SENSITIVE_CONTENT",
+      ErrorCode::Unauthorized => "UNAUTHORIZED",
+      ErrorCode::Internal => "INTERNAL",
+    }
+  }
+
+  /// Process exit status used by the CLI when a request fails with this code
+  pub fn exit_code(self) -> i32 {
+    match self {
+      ErrorCode::Internal => 1,
+      ErrorCode::NotFound => 3,
+      ErrorCode::AlreadyExists => 4,
+      ErrorCode::RevisionConflict => 5,
+      ErrorCode::ValidationFailed => 6,
+      ErrorCode::IndexUnavailable => 7,
+      ErrorCode::Unauthorized => 8,
+      ErrorCode::SensitiveContent => 9,
+    }
+  }
+}
+
+impl std::fmt::Display for ErrorCode {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.as_str())
+  }
 }
 
 // Status/Version Endpoints
@@ -133,6 +205,322 @@ pub struct AddInsightRequest {
 
   /// Detailed content
   pub details: String,
+
+  /// Labels to file the insight under, across topics
+  #[serde(default)]
+  pub tags: Vec<String>,
+
+  /// Store the insight even if it appears to contain secrets or PII
+  #[serde(default)]
+  pub allow_sensitive: bool,
+
+  /// Reject the insight if it fails lint checks instead of warning
+  #[serde(default)]
+  pub strict: bool,
+}
+
+/// A single insight in a bulk import
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ImportEntry {
+  /// Topic category
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+
+  /// Brief overview
+  pub overview: String,
+
+  /// Detailed content
+  #[serde(default)]
+  pub details: String,
+
+  /// Labels to file the insight under
+  #[serde(default)]
+  pub tags: Vec<String>,
+}
+
+/// What to do when an imported insight already exists
+#[derive(
+  Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+  /// Keep the existing insight and skip the imported one
+  #[default]
+  Skip,
+  /// Replace the existing overview and details with the imported ones
+  Merge,
+  /// Abort the whole import before anything is written
+  Fail,
+}
+
+/// Request for /insights/import endpoint
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImportInsightsRequest {
+  /// Insights to import
+  pub insights: Vec<ImportEntry>,
+
+  /// How to handle insights that already exist
+  #[serde(default)]
+  pub on_conflict: ConflictPolicy,
+
+  /// Import insights even if they appear to contain secrets or PII
+  #[serde(default)]
+  pub allow_sensitive: bool,
+}
+
+/// Response for /insights/import endpoint
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImportInsightsResponse {
+  /// `topic/name` of every newly created insight
+  pub added: Vec<String>,
+
+  /// `topic/name` of every existing insight that was overwritten
+  pub merged: Vec<String>,
+
+  /// `topic/name` of every existing insight that was left alone
+  pub skipped: Vec<String>,
+
+  /// Number of insights queued for embedding once the import finished
+  pub embeddings_queued: usize,
+}
+
+/// One write in a batch, tagged by `op`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BatchOperation {
+  /// Add an insight that doesn't exist yet
+  Create {
+    topic: String,
+    name: String,
+    overview: String,
+    #[serde(default)]
+    details: String,
+    #[serde(default)]
+    tags: Vec<String>,
+  },
+  /// Change an existing insight; fields left out are kept
+  Update {
+    topic: String,
+    name: String,
+    #[serde(default)]
+    overview: Option<String>,
+    #[serde(default)]
+    details: Option<String>,
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+  },
+  /// Remove an existing insight
+  Delete { topic: String, name: String },
+}
+
+impl BatchOperation {
+  pub fn action(&self) -> BatchAction {
+    match self {
+      BatchOperation::Create { .. } => BatchAction::Create,
+      BatchOperation::Update { .. } => BatchAction::Update,
+      BatchOperation::Delete { .. } => BatchAction::Delete,
+    }
+  }
+
+  pub fn topic(&self) -> &str {
+    match self {
+      BatchOperation::Create { topic, .. }
+      | BatchOperation::Update { topic, .. }
+      | BatchOperation::Delete { topic, .. } => topic,
+    }
+  }
+
+  pub fn name(&self) -> &str {
+    match self {
+      BatchOperation::Create { name, .. }
+      | BatchOperation::Update { name, .. }
+      | BatchOperation::Delete { name, .. } => name,
+    }
+  }
+}
+
+/// Kind of a batch operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchAction {
+  Create,
+  Update,
+  Delete,
+}
+
+impl std::fmt::Display for BatchAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let action = match self {
+      BatchAction::Create => "create",
+      BatchAction::Update => "update",
+      BatchAction::Delete => "delete",
+    };
+    write!(f, "{action}")
+  }
+}
+
+/// Request for /insights/batch endpoint
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BatchRequest {
+  /// Operations to apply in order, all or none
+  pub operations: Vec<BatchOperation>,
+
+  /// Write content even if it appears to contain secrets or PII
+  #[serde(default)]
+  pub allow_sensitive: bool,
+
+  /// Reject the batch if any written insight fails lint checks instead of warning
+  #[serde(default)]
+  pub strict: bool,
+}
+
+/// What happened to one operation of a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+  Created,
+  Updated,
+  Deleted,
+  /// The operation is why the batch was rejected
+  Failed,
+  /// The operation was fine, but another one failed so nothing was applied
+  NotApplied,
+}
+
+/// Result of one operation, in request order
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BatchResultData {
+  /// Position of the operation in the request, from 0
+  pub index: usize,
+
+  pub op: BatchAction,
+
+  pub topic: String,
+
+  pub name: String,
+
+  pub status: BatchStatus,
+
+  /// Why the operation failed
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+
+  /// Error category of a failed operation
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub code: Option<ErrorCode>,
+
+  /// Lint findings for the written insight
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub warnings: Vec<LintFindingData>,
+}
+
+/// Response for /insights/batch endpoint
+///
+/// A rejected batch answers with an error instead, whose context carries the same
+/// `results` with the failed operations marked.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BatchResponse {
+  /// One result per operation, in request order
+  pub results: Vec<BatchResultData>,
+
+  /// Number of insights queued for embedding once the batch was applied
+  pub embeddings_queued: usize,
+}
+
+/// Request for /insights/export endpoint
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ExportInsightsRequest {
+  /// Only export this topic
+  #[serde(default)]
+  pub topic: Option<String>,
+
+  /// Include stored embeddings alongside each insight
+  #[serde(default)]
+  pub include_embeddings: bool,
+}
+
+/// A single insight as it appears in an export archive
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedInsight {
+  /// Topic category
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+
+  /// Brief overview
+  pub overview: String,
+
+  /// Detailed content
+  pub details: String,
+
+  /// Labels the insight is filed under
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
+
+  /// Creation timestamp
+  pub created_at: DateTime<Utc>,
+
+  /// Last modified timestamp
+  pub last_updated: DateTime<Utc>,
+
+  /// Number of times the insight was updated
+  pub update_count: u32,
+
+  /// Stored embedding, when requested and available
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub embedding: Option<Vec<f32>>,
+}
+
+/// Response for /insights/export endpoint, written to disk as the archive
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct InsightsArchive {
+  /// Archive layout version
+  pub format_version: u32,
+
+  /// When the export was taken
+  pub exported_at: DateTime<Utc>,
+
+  /// Topics included in the archive
+  pub topics: Vec<String>,
+
+  /// Every exported insight
+  pub insights: Vec<ExportedInsight>,
+}
+
+/// Request for /insights/bootstrap endpoint
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BootstrapRequest {
+  /// Seed archive URL (http, https or file) or local path
+  pub source: String,
+
+  /// Pinned SHA-256 of the seed; when omitted, `<source>.sig` must verify against
+  /// the configured seed signing key
+  #[serde(default)]
+  pub sha256: Option<String>,
+
+  /// Restore into a knowledge base that already has insights, replacing any
+  /// that the seed also contains
+  #[serde(default)]
+  pub force: bool,
+}
+
+/// Response for /insights/bootstrap endpoint
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BootstrapResponse {
+  /// `topic/name` of every restored insight
+  pub restored: Vec<String>,
+
+  /// `topic/name` of every existing insight that was left alone
+  pub skipped: Vec<String>,
+
+  /// Number of embeddings restored from the seed
+  pub embeddings_restored: usize,
+
+  /// Number of insights queued for embedding because the seed had none for them
+  pub embeddings_queued: usize,
 }
 
 /// Request for /insights/update endpoint
@@ -149,6 +537,18 @@ pub struct UpdateInsightRequest {
 
   /// New details (optional)
   pub details: Option<String>,
+
+  /// Replacement tags (optional; an empty list removes every tag)
+  #[serde(default)]
+  pub tags: Option<Vec<String>>,
+
+  /// Store the update even if it appears to contain secrets or PII
+  #[serde(default)]
+  pub allow_sensitive: bool,
+
+  /// Reject the update if it fails lint checks instead of warning
+  #[serde(default)]
+  pub strict: bool,
 }
 
 /// Request for /insights/remove endpoint
@@ -197,6 +597,10 @@ pub struct InsightData {
   /// Detailed content
   pub details: String,
 
+  /// Labels the insight is filed under
+  #[serde(default)]
+  pub tags: Vec<String>,
+
   /// Embedding version (if computed)
   pub embedding_version: Option<String>,
 
@@ -234,6 +638,18 @@ pub enum FilterComparison {
   // Room for expansion: Contains, StartsWith, etc.
 }
 
+/// Query for GET /insights/list/insights
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListInsightsQuery {
+  /// Only list this topic and the topics nested under it
+  #[serde(default)]
+  pub topic: Option<String>,
+
+  /// Comma-separated tags every listed insight must carry
+  #[serde(default)]
+  pub tags: Option<String>,
+}
+
 /// Response for /insights/list/insights endpoint
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListInsightsResponse {
@@ -247,12 +663,17 @@ pub struct ListInsightsResponse {
 /// Search request data
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SearchRequest {
-  /// Search terms (space-separated)
+  /// Search terms (space-separated), optionally with filters such as `topic:rust`,
+  /// `tag:async`, `"exact phrase"`, `-excluded` and `before:2024-06`
   pub terms: Vec<String>,
 
   /// Optional topic to restrict search to
   pub topic: Option<String>,
 
+  /// Only match insights carrying every one of these tags
+  #[serde(default)]
+  pub tags: Vec<String>,
+
   /// Case-sensitive search
   #[serde(default)]
   pub case_sensitive: bool,
@@ -268,6 +689,35 @@ pub struct SearchRequest {
   /// Use semantic search (term matching + jaccard similarity, no embedding)
   #[serde(default)]
   pub semantic: bool,
+
+  /// Search backend: full-text index, embeddings, or both
+  #[serde(default)]
+  pub mode: SearchMode,
+
+  /// Weight of embedding similarity in hybrid ranking, 0.0-1.0 (default: rank fusion)
+  #[serde(default)]
+  pub hybrid_weight: Option<f32>,
+
+  /// Boost frequently read insights by up to this share of their score
+  /// (default: INSIGHTS_POPULARITY_WEIGHT, or no boost)
+  #[serde(default)]
+  pub popularity_weight: Option<f32>,
+
+  /// Share of its score a just-updated insight gains (default: ranking.yaml, or none)
+  #[serde(default)]
+  pub recency_weight: Option<f32>,
+
+  /// Days for the recency bonus to halve (default: ranking.yaml, or 30)
+  #[serde(default)]
+  pub recency_half_life_days: Option<f32>,
+
+  /// Score multipliers per topic, added to those in ranking.yaml
+  #[serde(default)]
+  pub topic_boosts: BTreeMap<String, f32>,
+
+  /// Explain each hit: retrieval path, scores before fusion and matched terms
+  #[serde(default)]
+  pub explain: bool,
 }
 
 /// Search result data
@@ -285,8 +735,125 @@ pub struct SearchResultData {
   /// Detail content
   pub details: String,
 
+  /// Labels the insight is filed under
+  #[serde(default)]
+  pub tags: Vec<String>,
+
   /// Search score
   pub score: f32,
+
+  /// How the score was adjusted after retrieval, when ranking changed it
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub ranking: Option<ScoreBreakdown>,
+
+  /// Why the insight matched, when the search asked for explanations
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub explanation: Option<SearchExplanation>,
+}
+
+/// Retrieval path that found a search hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchPath {
+  /// Full-text index or term scan
+  Keyword,
+  /// Embedding similarity
+  Semantic,
+  /// Both of the above
+  Both,
+}
+
+impl std::fmt::Display for MatchPath {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      MatchPath::Keyword => write!(f, "keyword"),
+      MatchPath::Semantic => write!(f, "semantic"),
+      MatchPath::Both => write!(f, "both"),
+    }
+  }
+}
+
+/// A search term found in one field of an insight
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TermMatch {
+  /// Search term that matched
+  pub term: String,
+
+  /// Field it was found in: topic, name, overview or details
+  pub field: String,
+
+  /// Character offset of the match within the field
+  pub start: usize,
+
+  /// Character offset just past the match
+  pub end: usize,
+
+  /// The match with some surrounding text
+  pub snippet: String,
+}
+
+/// Why an insight matched a search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SearchExplanation {
+  /// Which retrieval path found the insight
+  pub matched_by: MatchPath,
+
+  /// Keyword score before fusion, if keyword retrieval found the insight
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub keyword_score: Option<f32>,
+
+  /// Embedding similarity before fusion, if semantic retrieval found the insight
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub semantic_score: Option<f32>,
+
+  /// Where the search terms occur, in field order
+  #[serde(default)]
+  pub matches: Vec<TermMatch>,
+}
+
+impl SearchExplanation {
+  /// An insight found by one retrieval path with the given score
+  pub fn found(path: MatchPath, score: f32) -> Self {
+    let (keyword_score, semantic_score) = match path {
+      MatchPath::Semantic => (None, Some(score)),
+      _ => (Some(score), None),
+    };
+    Self { matched_by: path, keyword_score, semantic_score, matches: Vec::new() }
+  }
+
+  /// Fold in the same insight as found by another path
+  pub fn merge(&mut self, other: SearchExplanation) {
+    if self.matched_by != other.matched_by {
+      self.matched_by = MatchPath::Both;
+    }
+    self.keyword_score = self.keyword_score.or(other.keyword_score);
+    self.semantic_score = self.semantic_score.or(other.semantic_score);
+  }
+}
+
+/// Factors a search score was multiplied by after retrieval
+///
+/// `score = similarity * recency * topic_boost * popularity`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScoreBreakdown {
+  /// Score from retrieval (keyword, semantic or fused) before any adjustment
+  pub similarity: f32,
+
+  /// Bonus for recently updated insights
+  pub recency: f32,
+
+  /// Boost configured for the insight's topic
+  pub topic_boost: f32,
+
+  /// Bonus for frequently read insights
+  pub popularity: f32,
+}
+
+impl ScoreBreakdown {
+  /// A breakdown with no adjustments yet
+  pub fn new(similarity: f32) -> Self {
+    Self { similarity, recency: 1.0, topic_boost: 1.0, popularity: 1.0 }
+  }
 }
 
 /// Search response data
@@ -299,6 +866,62 @@ pub struct SearchResponse {
   pub count: usize,
 }
 
+/// Request for /ask endpoint
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AskRequest {
+  /// Natural-language question
+  pub question: String,
+
+  /// Optional topic to restrict retrieval to
+  #[serde(default)]
+  pub topic: Option<String>,
+
+  /// Number of insights to retrieve (defaults to 5)
+  #[serde(default)]
+  pub limit: Option<usize>,
+}
+
+/// An insight retrieved to answer a question
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AskSourceData {
+  /// Number the answer uses to cite this source, e.g. `[1]`
+  pub index: usize,
+
+  /// Topic name
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+
+  /// Overview and the start of the details
+  pub excerpt: String,
+
+  /// Search score
+  pub score: f32,
+
+  /// Whether the synthesized answer cites this source
+  pub cited: bool,
+}
+
+/// Response for /ask endpoint
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AskResponse {
+  /// Synthesized answer with `[n]` citations, if an LLM is configured
+  #[serde(default)]
+  pub answer: Option<String>,
+
+  /// Model that wrote the answer
+  #[serde(default)]
+  pub model: Option<String>,
+
+  /// Why no answer was synthesized
+  #[serde(default)]
+  pub note: Option<String>,
+
+  /// Retrieved insights, most relevant first
+  pub sources: Vec<AskSourceData>,
+}
+
 /// Response for /insights/list/topics endpoint
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListTopicsResponse {
@@ -306,6 +929,23 @@ pub struct ListTopicsResponse {
   pub topics: Vec<String>,
 }
 
+/// A tag and how many insights carry it
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TagCountData {
+  /// Tag name
+  pub tag: String,
+
+  /// Number of insights carrying the tag
+  pub count: usize,
+}
+
+/// Response for /insights/list/tags endpoint
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListTagsResponse {
+  /// Every tag in use, most used first
+  pub tags: Vec<TagCountData>,
+}
+
 /// Summary information about an insight
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct InsightSummary {
@@ -318,14 +958,826 @@ pub struct InsightSummary {
   /// Brief overview
   pub overview: String,
 
+  /// Labels the insight is filed under
+  #[serde(default)]
+  pub tags: Vec<String>,
+
   /// Creation timestamp
   pub created_at: DateTime<Utc>,
 
   /// Last modified timestamp
   pub updated_at: DateTime<Utc>,
+
+  /// When the topic's retention policy expires this insight (if any)
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
-// Helper Functions
+// Retention Endpoints
+// ===================
+
+/// Retention policy for a topic
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RetentionPolicyData {
+  /// Topic the policy applies to
+  pub topic: String,
+
+  /// Expire insights this many days after their last update
+  #[serde(default)]
+  pub expire_after_days: Option<u32>,
+
+  /// Expire every insight in the topic on this date
+  #[serde(default)]
+  pub expire_on: Option<DateTime<Utc>>,
+}
+
+/// Request for PUT /insights/retention
+pub type SetRetentionRequest = RetentionPolicyData;
+
+/// Request for DELETE /insights/retention
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RemoveRetentionRequest {
+  /// Topic whose policy should be removed
+  pub topic: String,
+}
+
+/// Response for GET /insights/retention
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListRetentionResponse {
+  /// All configured retention policies
+  pub policies: Vec<RetentionPolicyData>,
+}
+
+/// Response for POST /insights/retention/sweep
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RetentionSweepResponse {
+  /// `topic/name` of every insight that was archived
+  pub archived: Vec<String>,
+
+  /// Failures encountered while archiving
+  pub errors: Vec<String>,
+}
+
+// Backup Endpoints
+// ================
+
+/// A snapshot archive of the insights directory and vector database
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BackupData {
+  /// Identifier used to restore the backup, the UTC time it was taken (`20261016T093000Z`)
+  pub id: String,
+
+  /// When the backup was taken
+  pub created_at: DateTime<Utc>,
+
+  /// Size of the archive
+  pub size_bytes: u64,
+}
+
+/// Response for POST /insights/backups
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateBackupResponse {
+  /// The backup that was just taken
+  pub backup: BackupData,
+
+  /// Ids of older backups removed by the retention policy
+  pub pruned: Vec<String>,
+}
+
+/// Response for GET /insights/backups
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListBackupsResponse {
+  /// Folder the archives are kept in
+  pub directory: String,
+
+  /// Every backup, newest first
+  pub backups: Vec<BackupData>,
+}
+
+/// Request for POST /insights/backups/restore
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RestoreBackupRequest {
+  /// Backup to restore
+  pub id: String,
+}
+
+/// Response for POST /insights/backups/restore
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RestoreBackupResponse {
+  /// Backup that was restored
+  pub id: String,
+
+  /// Insights in the restored knowledge base
+  pub restored: usize,
+
+  /// Backup of the state that was replaced, to undo the restore
+  pub safety_backup: String,
+}
+
+// Workspace Endpoints
+// ===================
+
+/// A separate knowledge base served by the same server
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceData {
+  /// Name to pass in the `X-Insights-Workspace` header
+  pub name: String,
+
+  /// Folder the workspace's insights are stored in
+  pub insights_root: String,
+
+  /// Insights in the workspace
+  pub insights: usize,
+}
+
+/// Response for GET /workspaces
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListWorkspacesResponse {
+  /// Every workspace, the default first
+  pub workspaces: Vec<WorkspaceData>,
+}
+
+/// Request for POST /workspaces
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateWorkspaceRequest {
+  /// Letters, digits, '-' and '_', starting with a letter or digit
+  pub name: String,
+}
+
+/// Request for DELETE /workspaces
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DeleteWorkspaceRequest {
+  /// Workspace to delete with all of its insights and embeddings
+  pub name: String,
+}
+
+// Webhook Endpoints
+// =================
+
+/// A registered webhook (the signing secret is never returned)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebhookData {
+  /// Identifier used to remove the webhook or filter its deliveries
+  pub id: String,
+
+  /// Endpoint that receives the signed payloads
+  pub url: String,
+
+  /// Topics the webhook is notified about (empty means every topic)
+  pub topics: Vec<String>,
+
+  /// Events the webhook is notified about (empty means every event)
+  pub events: Vec<WebhookEvent>,
+
+  /// When the webhook was registered
+  pub created_at: DateTime<Utc>,
+}
+
+/// Request for POST /insights/webhooks
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AddWebhookRequest {
+  /// Endpoint that receives the signed payloads
+  pub url: String,
+
+  /// Key used to sign every payload
+  pub secret: String,
+
+  /// Only notify about these topics
+  #[serde(default)]
+  pub topics: Vec<String>,
+
+  /// Only notify about these events
+  #[serde(default)]
+  pub events: Vec<WebhookEvent>,
+}
+
+/// Request for DELETE /insights/webhooks
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RemoveWebhookRequest {
+  /// Webhook to remove
+  pub id: String,
+}
+
+/// Response for GET /insights/webhooks
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListWebhooksResponse {
+  /// All registered webhooks
+  pub webhooks: Vec<WebhookData>,
+}
+
+/// Query for GET /insights/webhooks/deliveries
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DeliveriesQuery {
+  /// Only show deliveries to this webhook
+  #[serde(default)]
+  pub webhook: Option<String>,
+
+  /// Maximum number of deliveries to return
+  #[serde(default)]
+  pub limit: Option<usize>,
+}
+
+/// Response for GET /insights/webhooks/deliveries
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListDeliveriesResponse {
+  /// Most recent deliveries first
+  pub deliveries: Vec<DeliveryRecord>,
+}
+
+// Stats Endpoints
+// ===============
+
+/// Query for GET /insights/stats
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct StatsQuery {
+  /// Number of insights to return (defaults to 20)
+  #[serde(default)]
+  pub top: Option<usize>,
+}
+
+/// How often one insight has been used
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct InsightStatsData {
+  /// Topic name
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+
+  /// Times the insight was fetched directly
+  pub reads: u64,
+
+  /// Times the insight was returned by a search
+  pub search_hits: u64,
+
+  /// Most recent read or search hit
+  pub last_accessed: DateTime<Utc>,
+}
+
+/// Response for GET /insights/stats
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct StatsResponse {
+  /// Most read insights first
+  pub insights: Vec<InsightStatsData>,
+
+  /// Number of insights with any recorded access
+  pub tracked: usize,
+}
+
+// Change Feed
+// ===========
+
+/// Query for GET /ws/events
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct EventsQuery {
+  /// Only send changes to insights in this topic
+  #[serde(default)]
+  pub topic: Option<String>,
+}
+
+// Indexing Endpoints
+// ==================
+
+/// Response for GET /insights/indexing, /insights/indexing/pause and /insights/indexing/resume
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct IndexingStatusResponse {
+  /// Whether background indexing has been paused
+  pub paused: bool,
+
+  /// Why background indexing is currently waiting, if it is
+  pub waiting_on: Option<String>,
+
+  /// Cap on the threads used to compute embeddings
+  pub max_threads: Option<usize>,
+
+  /// Niceness the server lowered itself to
+  pub nice: Option<i32>,
+
+  /// Background indexing only runs on AC power
+  pub require_ac_power: bool,
+
+  /// Background indexing only runs while the machine is idle
+  pub require_idle: bool,
+
+  /// Load average per core under which the machine counts as idle
+  pub idle_load: f64,
+}
+
+/// Query for DELETE /insights/index
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ReindexQuery {
+  /// Batches of insights embedded at once (defaults to INSIGHTS_INDEX_CONCURRENCY)
+  #[serde(default)]
+  pub concurrency: Option<usize>,
+}
+
+/// Response for DELETE /insights/index and GET /insights/index/status
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReindexStatusResponse {
+  /// Where the current or last re-index is at
+  pub state: JobState,
+
+  /// Insights to embed (0 until they have been loaded)
+  pub total: usize,
+
+  /// Insights embedded so far
+  pub embedded: usize,
+
+  /// Insights that could not be embedded
+  pub failed: usize,
+
+  /// Insights embedded per model run
+  pub batch_size: usize,
+
+  /// Batches in flight at once
+  pub concurrency: usize,
+
+  pub started_at: Option<DateTime<Utc>>,
+  pub finished_at: Option<DateTime<Utc>>,
+
+  /// Estimated seconds left while the job runs
+  pub eta_secs: Option<u64>,
+
+  /// What stopped a failed re-index
+  pub error: Option<String>,
+}
+
+// Sensitive Content Endpoints
+// ===========================
+
+/// A suspected secret or piece of personal data within an insight
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SensitiveFindingData {
+  /// Topic of the insight containing the match
+  pub topic: String,
+
+  /// Name of the insight containing the match
+  pub name: String,
+
+  /// Kind of data detected (e.g. "github token")
+  pub kind: String,
+
+  /// 1-based line within the overview or details
+  pub line: usize,
+
+  /// Redacted excerpt of the match
+  pub excerpt: String,
+}
+
+/// Response for GET /insights/scan
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScanResponse {
+  /// Every finding across the knowledge base
+  pub findings: Vec<SensitiveFindingData>,
+}
+
+// Lint Endpoints
+// ==============
+
+/// A lint rule an insight fails
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LintFindingData {
+  /// Topic of the insight
+  pub topic: String,
+
+  /// Name of the insight
+  pub name: String,
+
+  /// Rule that failed (e.g. "empty-overview")
+  pub rule: String,
+
+  /// What is wrong with the insight
+  pub message: String,
+}
+
+/// Response for POST /insights/add and PUT /insights/update
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WriteInsightResponse {
+  /// Lint findings for the written insight
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub warnings: Vec<LintFindingData>,
+}
+
+/// Query for GET /insights/lint
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LintQuery {
+  /// Only lint this topic
+  #[serde(default)]
+  pub topic: Option<String>,
+}
+
+/// Response for GET /insights/lint
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LintResponse {
+  /// Every finding across the knowledge base
+  pub findings: Vec<LintFindingData>,
+}
+
+// Validation Endpoints
+// ====================
+
+/// A frontmatter field that does not fit the insight schema
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaProblemData {
+  /// Field at fault (e.g. "tags")
+  pub field: String,
+
+  /// What is wrong with the field
+  pub message: String,
+
+  /// Whether `fix` can repair it
+  pub fixable: bool,
+}
+
+/// A stored insight whose frontmatter does not fit the schema
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidationResultData {
+  /// Topic of the insight, from its location
+  pub topic: String,
+
+  /// Name of the insight, from its file name
+  pub name: String,
+
+  /// Every problem found in the file
+  pub problems: Vec<SchemaProblemData>,
+
+  /// Whether the file was repaired
+  pub fixed: bool,
+}
+
+/// Request for POST /insights/validate
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ValidateRequest {
+  /// Only check this topic and those nested under it
+  #[serde(default)]
+  pub topic: Option<String>,
+
+  /// Rewrite files whose problems can all be fixed
+  #[serde(default)]
+  pub fix: bool,
+}
+
+/// Response for POST /insights/validate
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ValidateResponse {
+  /// Every insight with problems, fixed or not
+  pub results: Vec<ValidationResultData>,
+}
+
+// Sharding Endpoints
+// ==================
+
+/// Row count of one shard table
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ShardStatsData {
+  /// Name of the vector table
+  pub table: String,
+
+  /// Number of embeddings stored in the table
+  pub rows: usize,
+}
+
+/// Response for GET /insights/shards
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ShardsResponse {
+  /// Current shard layout
+  pub config: ShardConfig,
+
+  /// Every shard table with its size
+  pub shards: Vec<ShardStatsData>,
+
+  /// Largest shard relative to the mean shard size
+  pub imbalance: f32,
+
+  /// Why a rebalance is recommended; empty when the shards are healthy
+  #[serde(default)]
+  pub rebalance_reasons: Vec<String>,
+}
+
+/// Request for PUT /insights/shards
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ConfigureShardsRequest {
+  /// How embeddings are spread across tables
+  pub strategy: ShardStrategy,
+
+  /// Number of tables for the hash strategy
+  #[serde(default)]
+  pub buckets: Option<u32>,
+
+  /// Largest-to-mean shard ratio that triggers a rebalance
+  #[serde(default)]
+  pub max_imbalance: Option<f32>,
+
+  /// Shard size that triggers a rebalance
+  #[serde(default)]
+  pub max_shard_rows: Option<usize>,
+}
+
+/// Request for POST /insights/shards/rebalance
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct RebalanceShardsRequest {
+  /// Redistribute rows even if the shards are within their limits
+  #[serde(default)]
+  pub force: bool,
+}
+
+/// Response for PUT /insights/shards and POST /insights/shards/rebalance
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RebalanceShardsResponse {
+  /// Whether any rows were redistributed
+  pub rebalanced: bool,
+
+  /// Number of embeddings that moved to a different table
+  pub moved: usize,
+
+  /// Layout in effect after the operation
+  pub config: ShardConfig,
+
+  /// Why the rebalance was performed
+  #[serde(default)]
+  pub reasons: Vec<String>,
+}
+
+// Topic Summary Endpoints
+// =======================
+
+/// Request for POST /insights/summary
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SummarizeTopicRequest {
+  /// Topic to summarize
+  pub topic: String,
+
+  /// Upper bound on the number of clusters in the outline
+  #[serde(default)]
+  pub max_clusters: Option<usize>,
+}
+
+/// An insight as it appears inside a cluster
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ClusterInsightData {
+  /// Insight name
+  pub name: String,
+
+  /// Brief overview
+  pub overview: String,
+}
+
+/// A group of closely related insights
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ClusterData {
+  /// Short label built from the representative terms
+  pub label: String,
+
+  /// Most representative terms, strongest first
+  pub terms: Vec<String>,
+
+  /// Insights in the cluster
+  pub insights: Vec<ClusterInsightData>,
+}
+
+/// Response for POST /insights/summary
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TopicSummaryResponse {
+  /// Topic that was summarized
+  pub topic: String,
+
+  /// What the clustering compared: "embeddings" or "terms"
+  pub method: String,
+
+  /// Total number of insights in the topic
+  pub total: usize,
+
+  /// Clusters, largest first
+  pub clusters: Vec<ClusterData>,
+
+  /// Insights that are not similar enough to any other
+  #[serde(default)]
+  pub unclustered: Vec<ClusterInsightData>,
+}
+
+// Related Insights Endpoints
+// ==========================
+
+/// Request for POST /insights/related
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RelatedInsightsRequest {
+  /// Topic category
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+
+  /// Maximum number of related insights (default: 5)
+  #[serde(default)]
+  pub limit: Option<usize>,
+}
+
+/// An insight close to the one asked about
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RelatedInsightData {
+  /// Topic name
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+
+  /// Brief overview
+  pub overview: String,
+
+  /// Embedding similarity (0.0-1.0, higher is more similar)
+  pub similarity: f32,
+}
+
+/// Response for POST /insights/related
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RelatedInsightsResponse {
+  /// Topic of the insight asked about
+  pub topic: String,
+
+  /// Name of the insight asked about
+  pub name: String,
+
+  /// Minimum similarity a related insight had to reach
+  pub threshold: f32,
+
+  /// Related insights, most similar first
+  pub related: Vec<RelatedInsightData>,
+}
+
+// Duplicate Detection Endpoints
+// =============================
+
+/// Request for POST /insights/duplicates
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FindDuplicatesRequest {
+  /// Only compare insights in this topic or nested under it
+  #[serde(default)]
+  pub topic: Option<String>,
+
+  /// Minimum cosine similarity (0.0-1.0) for two insights to count as duplicates
+  #[serde(default)]
+  pub threshold: Option<f32>,
+}
+
+/// An insight inside a cluster of duplicates
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicateInsightData {
+  /// Topic name
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+
+  /// Brief overview
+  pub overview: String,
+
+  /// When the insight was created
+  pub created_at: DateTime<Utc>,
+}
+
+/// Insights that are near-identical to one another
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicateClusterData {
+  /// Similarity of the closest pair in the cluster
+  pub similarity: f32,
+
+  /// Insights in the cluster, oldest first
+  pub insights: Vec<DuplicateInsightData>,
+}
+
+/// Response for POST /insights/duplicates
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindDuplicatesResponse {
+  /// What was compared: "embeddings" or "terms"
+  pub method: String,
+
+  /// Similarity threshold that was applied
+  pub threshold: f32,
+
+  /// Number of insights compared
+  pub scanned: usize,
+
+  /// Clusters of duplicates, closest first
+  pub clusters: Vec<DuplicateClusterData>,
+}
+
+// History Endpoints
+// =================
+
+/// Request for POST /insights/history
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryRequest {
+  /// Topic category
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+}
+
+/// One recorded version of an insight
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct VersionData {
+  /// Version number, starting at 1
+  pub version: u32,
+
+  /// When the insight was last updated as of this version
+  pub recorded_at: DateTime<Utc>,
+
+  /// Overview at this version
+  pub overview: String,
+
+  /// Update count at this version
+  pub update_count: u32,
+}
+
+/// Response for POST /insights/history
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryResponse {
+  pub topic: String,
+  pub name: String,
+
+  /// Versions, oldest first; the last one is the current content
+  pub versions: Vec<VersionData>,
+}
+
+/// Request for POST /insights/history/diff
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiffVersionsRequest {
+  pub topic: String,
+  pub name: String,
+
+  /// Older version (default: the one before `to`)
+  #[serde(default)]
+  pub from: Option<u32>,
+
+  /// Newer version (default: the latest)
+  #[serde(default)]
+  pub to: Option<u32>,
+}
+
+/// Response for POST /insights/history/diff
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiffVersionsResponse {
+  pub from: u32,
+  pub to: u32,
+
+  /// Unified diff of overview and details (empty if identical)
+  pub diff: String,
+}
+
+/// Request for POST /insights/history/rollback
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RollbackRequest {
+  pub topic: String,
+  pub name: String,
+
+  /// Version whose content should be restored
+  pub to: u32,
+}
+
+/// Response for POST /insights/history/rollback
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RollbackResponse {
+  /// Version that was restored
+  pub restored: u32,
+
+  /// Version number the restored content was recorded as
+  pub version: u32,
+}
+
+// Web UI Endpoints
+// ================
+
+/// Query for GET /insights/render
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RenderInsightQuery {
+  /// Topic category
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+}
+
+/// Response for /insights/render endpoint
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RenderInsightResponse {
+  /// Topic category
+  pub topic: String,
+
+  /// Insight name
+  pub name: String,
+
+  /// Labels the insight is filed under
+  pub tags: Vec<String>,
+
+  /// Overview as escaped HTML
+  pub overview_html: String,
+
+  /// Details as escaped HTML
+  pub details_html: String,
+}
+
+// Helper Functions
 // ================
 
 impl<T> BaseResponse<T> {
@@ -368,8 +1820,21 @@ impl ApiError {
       message: message.to_string(),
       stack: Vec::new(),
       context: serde_json::Value::Null,
+      code: ErrorCode::default(),
     }
   }
+
+  /// Set the stable error category
+  pub fn with_code(mut self, code: ErrorCode) -> Self {
+    self.code = code;
+    self
+  }
+
+  /// Attach structured details, e.g. which fields failed validation
+  pub fn with_context(mut self, context: serde_json::Value) -> Self {
+    self.context = context;
+    self
+  }
 }
 
 #[cfg(test)]
@@ -420,6 +1885,39 @@ mod tests {
     assert!(error.context.is_null());
   }
 
+  #[test]
+  fn test_api_error_code_defaults_to_internal() {
+    let error = ApiError::new("key", "message");
+    assert_eq!(error.code, ErrorCode::Internal);
+
+    let error = error.with_code(ErrorCode::NotFound);
+    assert_eq!(error.code, ErrorCode::NotFound);
+  }
+
+  #[test]
+  fn test_error_code_serialization_is_stable() {
+    for code in [
+      ErrorCode::NotFound,
+      ErrorCode::AlreadyExists,
+      ErrorCode::RevisionConflict,
+      ErrorCode::ValidationFailed,
+      ErrorCode::IndexUnavailable,
+      ErrorCode::SensitiveContent,
+      ErrorCode::Unauthorized,
+      ErrorCode::Internal,
+    ] {
+      let json = serde_json::to_string(&code).unwrap();
+      assert_eq!(json, format!("\"{}\"", code.as_str()));
+      assert_eq!(serde_json::from_str::<ErrorCode>(&json).unwrap(), code);
+    }
+  }
+
+  #[test]
+  fn test_api_error_without_code_deserializes_as_internal() {
+    let error: ApiError = serde_json::from_str(r#"{"key":"k","message":"m"}"#).unwrap();
+    assert_eq!(error.code, ErrorCode::Internal);
+  }
+
   #[test]
   fn test_api_error_with_stack_and_context() {
     let mut error = ApiError::new("database_error", "Connection failed");
@@ -451,10 +1949,18 @@ mod tests {
     let request = SearchRequest {
       terms: vec!["test".to_string()],
       topic: None,
+      tags: Vec::new(),
       case_sensitive: false,
       overview_only: false,
       exact: false,
       semantic: false,
+      mode: SearchMode::default(),
+      hybrid_weight: None,
+      popularity_weight: None,
+      recency_weight: None,
+      recency_half_life_days: None,
+      topic_boosts: BTreeMap::new(),
+      explain: false,
     };
 
     // These should all be false by default due to #[serde(default)]
@@ -0,0 +1,135 @@
+// Read-only browser for the insights server. Every request goes through the
+// regular API; rendered markdown comes from /insights/render already escaped.
+
+const TOKEN_KEY = "insights-token";
+
+const $ = (id) => document.getElementById(id);
+
+async function api(path, body) {
+  const headers = { "Content-Type": "application/json" };
+  const token = localStorage.getItem(TOKEN_KEY);
+  if (token) {
+    headers.Authorization = `Bearer ${token}`;
+  }
+
+  const response = await fetch(path, {
+    method: body ? "POST" : "GET",
+    headers,
+    body: body ? JSON.stringify(body) : undefined,
+  });
+  const data = await response.json().catch(() => ({}));
+  if (!response.ok) {
+    const message = data.errors?.[0]?.message ?? `${response.status} ${response.statusText}`;
+    throw new Error(message);
+  }
+  return data;
+}
+
+function showError(error) {
+  const banner = $("error");
+  banner.textContent = error ? error.message : "";
+  banner.hidden = !error;
+}
+
+function item(label, detail, onClick) {
+  const li = document.createElement("li");
+  const button = document.createElement("button");
+  button.type = "button";
+  button.textContent = label;
+  button.addEventListener("click", onClick);
+  li.append(button);
+  if (detail) {
+    const small = document.createElement("small");
+    small.textContent = detail;
+    li.append(small);
+  }
+  return li;
+}
+
+function showInsights(title, insights) {
+  $("list-title").textContent = title;
+  const list = $("insights");
+  list.replaceChildren(
+    ...insights.map((insight) =>
+      item(`${insight.topic}/${insight.name}`, insight.overview, () => preview(insight))
+    )
+  );
+  if (insights.length === 0) {
+    list.append(Object.assign(document.createElement("li"), { textContent: "Nothing found." }));
+  }
+}
+
+async function loadTopics() {
+  try {
+    const { topics } = await api("/insights/list/topics");
+    $("topics").replaceChildren(...topics.map((topic) => item(topic, null, () => browse(topic))));
+    showError(null);
+  } catch (error) {
+    showError(error);
+  }
+}
+
+async function browse(topic) {
+  try {
+    const { insights } = await api(`/insights/list/insights?topic=${encodeURIComponent(topic)}`);
+    showInsights(topic, insights);
+    showError(null);
+  } catch (error) {
+    showError(error);
+  }
+}
+
+async function search(terms) {
+  try {
+    const { results } = await api("/insights/search", { terms: terms.split(/\s+/) });
+    showInsights(`Results for “${terms}”`, results);
+    showError(null);
+  } catch (error) {
+    showError(error);
+  }
+}
+
+async function preview({ topic, name }) {
+  try {
+    const query = `topic=${encodeURIComponent(topic)}&name=${encodeURIComponent(name)}`;
+    const insight = await api(`/insights/render?${query}`);
+    const tags = insight.tags.map((tag) => `#${tag}`).join(" ");
+
+    const article = $("preview");
+    article.replaceChildren();
+    const heading = document.createElement("h2");
+    heading.textContent = `${insight.topic}/${insight.name}`;
+    const meta = Object.assign(document.createElement("p"), { className: "tags", textContent: tags });
+    const overview = Object.assign(document.createElement("div"), { className: "overview" });
+    overview.innerHTML = insight.overview_html;
+    const details = document.createElement("div");
+    details.innerHTML = insight.details_html;
+    article.append(heading, meta, overview, details);
+    showError(null);
+  } catch (error) {
+    showError(error);
+  }
+}
+
+$("search").addEventListener("submit", (event) => {
+  event.preventDefault();
+  const terms = $("terms").value.trim();
+  if (terms) {
+    search(terms);
+  }
+});
+
+$("token").addEventListener("click", () => {
+  const token = prompt("API token (leave empty to clear)", localStorage.getItem(TOKEN_KEY) ?? "");
+  if (token === null) {
+    return;
+  }
+  if (token.trim()) {
+    localStorage.setItem(TOKEN_KEY, token.trim());
+  } else {
+    localStorage.removeItem(TOKEN_KEY);
+  }
+  loadTopics();
+});
+
+loadTopics();
@@ -0,0 +1,219 @@
+//! Settings insights reads from `$BLIZZ_HOME/config.yaml` or the environment
+//!
+//! Each key's environment variable still wins over the file, so existing setups keep
+//! working. Defaults live with the code that reads the setting; `default` here only
+//! describes them for `blizz config list`.
+
+use bentley::config::Key;
+
+// Client
+// ======
+
+pub const SERVER_URL: Key = Key::new(
+  "insights.server_url",
+  "INSIGHTS_SERVER_URL",
+  "http://localhost:3000",
+  "Address of the insights server",
+);
+pub const TIMEOUT_SECS: Key =
+  Key::new("insights.timeout_secs", "INSIGHTS_TIMEOUT_SECS", "30", "Seconds a request may take");
+pub const WORKSPACE: Key = Key::new(
+  "insights.workspace",
+  "INSIGHTS_WORKSPACE",
+  "",
+  "Workspace requests are scoped to; none means the default workspace",
+);
+
+// Server
+// ======
+
+pub const LOG_LEVEL: Key =
+  Key::new("insights.log_level", "INSIGHTS_LOG_LEVEL", "info", "Request log level");
+pub const REQUIRE_AUTH: Key = Key::new(
+  "insights.require_auth",
+  "INSIGHTS_REQUIRE_AUTH",
+  "false",
+  "Refuse requests when no tokens can be checked",
+);
+
+pub const STORE: Key =
+  Key::new("insights.store", "INSIGHTS_STORE", "files", "Where insights are kept: files or sqlite");
+pub const SEED_PUBLIC_KEY: Key = Key::new(
+  "insights.bootstrap.public_key",
+  "INSIGHTS_SEED_PUBLIC_KEY",
+  "",
+  "Hex ed25519 key seed archives must be signed with; none requires a pinned sha256",
+);
+
+// Search
+// ======
+
+pub const POPULARITY_WEIGHT: Key = Key::new(
+  "insights.search.popularity_weight",
+  "INSIGHTS_POPULARITY_WEIGHT",
+  "0.0",
+  "How much access stats boost search results",
+);
+pub const RELATED_THRESHOLD: Key = Key::new(
+  "insights.related.threshold",
+  "INSIGHTS_RELATED_THRESHOLD",
+  "0.5",
+  "Similarity (0.0-1.0) for an insight to count as related",
+);
+pub const RERANK_INITIAL_LIMIT: Key = Key::new(
+  "insights.rerank.initial_limit",
+  "INSIGHTS_RERANK_INITIAL_LIMIT",
+  "128",
+  "Candidates retrieved before reranking",
+);
+pub const RERANK_INITIAL_THRESHOLD: Key = Key::new(
+  "insights.rerank.initial_threshold",
+  "INSIGHTS_RERANK_INITIAL_THRESHOLD",
+  "0.3",
+  "Similarity a candidate needs before reranking",
+);
+pub const RERANK_FINAL_LIMIT: Key = Key::new(
+  "insights.rerank.final_limit",
+  "INSIGHTS_RERANK_FINAL_LIMIT",
+  "8",
+  "Results kept after reranking",
+);
+
+// Indexing
+// ========
+
+pub const EMBED_BATCH_SIZE: Key = Key::new(
+  "insights.index.batch_size",
+  "INSIGHTS_EMBED_BATCH_SIZE",
+  "8",
+  "Insights embedded per model run",
+);
+pub const INDEX_CONCURRENCY: Key = Key::new(
+  "insights.index.concurrency",
+  "INSIGHTS_INDEX_CONCURRENCY",
+  "2",
+  "Batches in flight while embedding in the background",
+);
+pub const INDEX_THREADS: Key = Key::new(
+  "insights.index.threads",
+  "INSIGHTS_INDEX_THREADS",
+  "",
+  "Cap on embedding threads; none uses every core",
+);
+pub const INDEX_NICE: Key = Key::new(
+  "insights.index.nice",
+  "INSIGHTS_INDEX_NICE",
+  "",
+  "Niceness for the server process; none keeps its priority",
+);
+pub const INDEX_SCHEDULE: Key = Key::new(
+  "insights.index.schedule",
+  "INSIGHTS_INDEX_SCHEDULE",
+  "always",
+  "When background indexing runs: comma-separated ac-power, idle",
+);
+pub const INDEX_IDLE_LOAD: Key = Key::new(
+  "insights.index.idle_load",
+  "INSIGHTS_INDEX_IDLE_LOAD",
+  "0.5",
+  "Load average per core below which the machine counts as idle",
+);
+pub const WATCH_POLL_MS: Key = Key::new(
+  "insights.watch.poll_ms",
+  "INSIGHTS_WATCH_POLL_MS",
+  "1000",
+  "Milliseconds between checks for changed insight files",
+);
+pub const WATCH_DEBOUNCE_MS: Key = Key::new(
+  "insights.watch.debounce_ms",
+  "INSIGHTS_WATCH_DEBOUNCE_MS",
+  "2000",
+  "Quiet period in milliseconds before reindexing changed files",
+);
+
+// Maintenance
+// ===========
+
+pub const BACKUP_DIR: Key = Key::new(
+  "insights.backup.dir",
+  "INSIGHTS_BACKUP_DIR",
+  ".backups in the insights root",
+  "Folder backups are kept in",
+);
+pub const BACKUP_KEEP: Key =
+  Key::new("insights.backup.keep", "INSIGHTS_BACKUP_KEEP", "7", "Backups always kept");
+pub const BACKUP_KEEP_DAYS: Key = Key::new(
+  "insights.backup.keep_days",
+  "INSIGHTS_BACKUP_KEEP_DAYS",
+  "",
+  "Also keep every backup younger than this many days",
+);
+pub const BACKUP_INTERVAL_SECS: Key = Key::new(
+  "insights.backup.interval_secs",
+  "INSIGHTS_BACKUP_INTERVAL_SECS",
+  "86400",
+  "Seconds between scheduled backups; 0 disables them",
+);
+pub const RETENTION_INTERVAL_SECS: Key = Key::new(
+  "insights.retention.interval_secs",
+  "INSIGHTS_RETENTION_INTERVAL_SECS",
+  "3600",
+  "Seconds between retention sweeps",
+);
+pub const WEBHOOK_MAX_ATTEMPTS: Key = Key::new(
+  "insights.webhooks.max_attempts",
+  "INSIGHTS_WEBHOOK_MAX_ATTEMPTS",
+  "5",
+  "Deliveries tried before a webhook event is dropped",
+);
+pub const WEBHOOK_BACKOFF_MS: Key = Key::new(
+  "insights.webhooks.backoff_ms",
+  "INSIGHTS_WEBHOOK_BACKOFF_MS",
+  "1000",
+  "First retry delay in milliseconds, doubling after each attempt",
+);
+pub const LINT_SOURCE_TOPICS: Key = Key::new(
+  "insights.lint.source_topics",
+  "INSIGHTS_LINT_SOURCE_TOPICS",
+  "",
+  "Comma-separated topics whose insights must cite a source",
+);
+pub const LINT_MIN_DETAILS: Key = Key::new(
+  "insights.lint.min_details",
+  "INSIGHTS_LINT_MIN_DETAILS",
+  "40",
+  "Characters an insight's details need",
+);
+
+/// Every setting insights reads, in the order `blizz config list` shows them
+pub const KEYS: &[Key] = &[
+  SERVER_URL,
+  TIMEOUT_SECS,
+  WORKSPACE,
+  LOG_LEVEL,
+  REQUIRE_AUTH,
+  STORE,
+  SEED_PUBLIC_KEY,
+  POPULARITY_WEIGHT,
+  RELATED_THRESHOLD,
+  RERANK_INITIAL_LIMIT,
+  RERANK_INITIAL_THRESHOLD,
+  RERANK_FINAL_LIMIT,
+  EMBED_BATCH_SIZE,
+  INDEX_CONCURRENCY,
+  INDEX_THREADS,
+  INDEX_NICE,
+  INDEX_SCHEDULE,
+  INDEX_IDLE_LOAD,
+  WATCH_POLL_MS,
+  WATCH_DEBOUNCE_MS,
+  BACKUP_DIR,
+  BACKUP_KEEP,
+  BACKUP_KEEP_DAYS,
+  BACKUP_INTERVAL_SECS,
+  RETENTION_INTERVAL_SECS,
+  WEBHOOK_MAX_ATTEMPTS,
+  WEBHOOK_BACKOFF_MS,
+  LINT_SOURCE_TOPICS,
+  LINT_MIN_DETAILS,
+];
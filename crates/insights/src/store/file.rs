@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+
+use super::InsightStore;
+use crate::server::models::insight::{
+  check_insight_exists, check_insight_is_new, cleanup_empty_dir, collect_topics,
+  ensure_parent_dir_exists, extract_insight_name, file_path, get_insights_root, is_insight_file,
+  make_insight_path, parse_insight_from_content, write_to_file, Insight,
+};
+use crate::server::models::topic;
+
+/// Markdown files with YAML frontmatter, one directory per topic under [`get_insights_root`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileStore;
+
+impl InsightStore for FileStore {
+  fn save(&self, insight: &Insight) -> Result<()> {
+    let file_path = file_path(insight)?;
+    ensure_parent_dir_exists(&file_path)?;
+    check_insight_is_new(&file_path, &insight.topic, &insight.name)?;
+    write_to_file(insight, &file_path)
+  }
+
+  fn save_existing(&self, insight: &Insight) -> Result<()> {
+    let file_path = file_path(insight)?;
+    write_to_file(insight, &file_path)
+  }
+
+  fn replace(&self, insight: &Insight) -> Result<()> {
+    let existing_file_path = make_insight_path(&insight.topic, &insight.name)?;
+    if !existing_file_path.exists() {
+      return Err(anyhow!("Insight {}/{} not found", insight.topic, insight.name));
+    }
+
+    let new_file_path = file_path(insight)?;
+
+    // Delete the existing file FIRST to ensure cross-platform compatibility.
+    // Prevents issues on case-insensitive filesystems
+    fs::remove_file(&existing_file_path)?;
+
+    // Clean up empty directories from old location
+    let _ = cleanup_empty_dir(&existing_file_path);
+
+    // Now save to the normalized path
+    write_to_file(insight, &new_file_path)
+  }
+
+  fn load(&self, topic: &str, name: &str) -> Result<Insight> {
+    let file_path = make_insight_path(topic, name)?;
+
+    if !file_path.exists() {
+      return Err(anyhow!("Insight {}/{} not found", topic, name));
+    }
+
+    let content = fs::read_to_string(&file_path)?;
+    parse_insight_from_content(topic, name, &content)
+  }
+
+  fn delete(&self, insight: &Insight) -> Result<()> {
+    let file_path = file_path(insight)?;
+    check_insight_exists(&file_path, &insight.topic, &insight.name)?;
+    fs::remove_file(&file_path)?;
+    cleanup_empty_dir(&file_path)?;
+    Ok(())
+  }
+
+  fn topics(&self) -> Result<Vec<String>> {
+    let insights_root = get_insights_root()?;
+
+    if !insights_root.exists() {
+      return Ok(vec![]);
+    }
+
+    let mut topics = Vec::new();
+    collect_topics(&insights_root, &insights_root, &mut topics)?;
+    topics.sort();
+    Ok(topics)
+  }
+
+  fn insights(&self, topic_filter: Option<&str>) -> Result<Vec<Insight>> {
+    let insights_root = get_insights_root()?;
+    let mut all_insights = Vec::new();
+
+    for topic_name in self.topics()? {
+      if topic_filter.is_some_and(|filter| !topic::within(&topic_name, filter)) {
+        continue;
+      }
+      let topic_path = insights_root.join(&topic_name);
+      let mut topic_insights = self.collect_topic(&topic_name, &topic_path)?;
+      all_insights.append(&mut topic_insights);
+    }
+
+    all_insights.sort_by_key(|insight| insight.name.clone());
+    Ok(all_insights)
+  }
+}
+
+impl FileStore {
+  fn collect_topic(&self, topic_name: &str, topic_path: &std::path::Path) -> Result<Vec<Insight>> {
+    if !topic_path.exists() {
+      return Ok(Vec::new());
+    }
+
+    let mut insights = Vec::new();
+
+    for entry in fs::read_dir(topic_path)? {
+      let path = entry?.path();
+
+      if !is_insight_file(&path) {
+        continue;
+      }
+
+      if let Some(insight_name) = extract_insight_name(&path) {
+        insights.push(self.load(topic_name, &insight_name)?);
+      }
+    }
+
+    Ok(insights)
+  }
+}
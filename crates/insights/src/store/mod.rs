@@ -0,0 +1,128 @@
+//! Storage backends for insights
+//!
+//! Every backend implements [`InsightStore`]. The free functions in
+//! [`crate::server::models::insight`] (`save`, `load`, `get_insights`, ...) go
+//! through the store picked by the `insights.store` setting, so callers never
+//! depend on a particular backend. [`migrate`] copies a knowledge base from one
+//! store to another.
+
+use anyhow::{anyhow, Result};
+use bentley::config;
+
+use crate::server::models::insight::Insight;
+use crate::settings;
+
+mod file;
+mod sqlite;
+
+pub use file::FileStore;
+pub use sqlite::SqliteStore;
+
+/// Where insights are kept
+pub trait InsightStore: Send + Sync {
+  /// Add a new insight, failing if it already exists
+  fn save(&self, insight: &Insight) -> Result<()>;
+
+  /// Write an insight whether or not it already exists
+  fn save_existing(&self, insight: &Insight) -> Result<()>;
+
+  /// Overwrite an existing insight, failing if it does not exist
+  fn replace(&self, insight: &Insight) -> Result<()>;
+
+  fn load(&self, topic: &str, name: &str) -> Result<Insight>;
+
+  fn delete(&self, insight: &Insight) -> Result<()>;
+
+  /// Every topic holding insights, nested ones included, sorted
+  fn topics(&self) -> Result<Vec<String>>;
+
+  /// Insights in a topic and every topic nested under it, or in all topics, sorted by name
+  fn insights(&self, topic_filter: Option<&str>) -> Result<Vec<Insight>>;
+}
+
+/// Storage engine behind insights
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreBackend {
+  /// One markdown file per insight under the insights root
+  Files,
+  /// A single SQLite database in the insights root
+  Sqlite,
+}
+
+impl StoreBackend {
+  pub fn parse(value: &str) -> Result<Self> {
+    match value.trim().to_lowercase().as_str() {
+      "files" => Ok(StoreBackend::Files),
+      "sqlite" => Ok(StoreBackend::Sqlite),
+      other => Err(anyhow!("Unknown insight store '{other}' (expected files or sqlite)")),
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      StoreBackend::Files => "files",
+      StoreBackend::Sqlite => "sqlite",
+    }
+  }
+}
+
+impl std::fmt::Display for StoreBackend {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.name())
+  }
+}
+
+/// Get the configured insight store backend
+/// Default: files
+/// Setting: insights.store (INSIGHTS_STORE)
+pub fn get_store_backend() -> Result<StoreBackend> {
+  match config::var(&settings::STORE) {
+    Some(value) if !value.trim().is_empty() => StoreBackend::parse(&value),
+    _ => Ok(StoreBackend::Files),
+  }
+}
+
+/// The configured insight store
+pub fn store() -> Result<Box<dyn InsightStore>> {
+  open(get_store_backend()?)
+}
+
+/// A store of the given kind for the current workspace
+pub fn open(backend: StoreBackend) -> Result<Box<dyn InsightStore>> {
+  match backend {
+    StoreBackend::Files => Ok(Box::new(FileStore)),
+    StoreBackend::Sqlite => Ok(Box::new(SqliteStore::open_default()?)),
+  }
+}
+
+/// What a migration did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+  /// Insights written to the target store
+  pub copied: usize,
+  /// Insights the target already held, left untouched
+  pub skipped: Vec<String>,
+}
+
+/// Copy every insight from one store into another
+///
+/// Insights the target already holds are skipped unless `overwrite` is set. The
+/// source is never modified, so a migration can be rerun or rolled back by
+/// switching `insights.store` back.
+pub fn migrate(
+  from: &dyn InsightStore,
+  to: &dyn InsightStore,
+  overwrite: bool,
+) -> Result<MigrationReport> {
+  let mut report = MigrationReport::default();
+  for insight in from.insights(None)? {
+    let exists = to.load(&insight.topic, &insight.name).is_ok();
+    if exists && !overwrite {
+      report.skipped.push(format!("{}/{}", insight.topic, insight.name));
+      continue;
+    }
+    to.save_existing(&insight)?;
+    report.copied += 1;
+  }
+  Ok(report)
+}
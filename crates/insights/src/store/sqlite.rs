@@ -0,0 +1,278 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::InsightStore;
+use crate::server::models::insight::{get_insights_root, Insight};
+use crate::server::models::topic;
+
+/// Name of the database file inside the insights root
+pub const DATABASE_FILE: &str = "insights.db";
+
+const SCHEMA: &str = "
+  PRAGMA foreign_keys = ON;
+
+  CREATE TABLE IF NOT EXISTS insights (
+    topic_key TEXT NOT NULL,
+    name_key TEXT NOT NULL,
+    topic TEXT NOT NULL,
+    name TEXT NOT NULL,
+    overview TEXT NOT NULL,
+    details TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    last_updated TEXT NOT NULL,
+    update_count INTEGER NOT NULL,
+    PRIMARY KEY (topic_key, name_key)
+  );
+
+  CREATE TABLE IF NOT EXISTS tags (
+    topic_key TEXT NOT NULL,
+    name_key TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (topic_key, name_key, position),
+    FOREIGN KEY (topic_key, name_key) REFERENCES insights (topic_key, name_key)
+      ON DELETE CASCADE ON UPDATE CASCADE
+  );
+
+  CREATE INDEX IF NOT EXISTS tags_by_tag ON tags (tag);
+  CREATE INDEX IF NOT EXISTS insights_by_last_updated ON insights (last_updated);
+";
+
+const SELECT_INSIGHT: &str = "SELECT topic, name, overview, details, created_at, last_updated,
+  update_count, topic_key, name_key FROM insights";
+
+/// Insights, their tags and timestamps in one SQLite database
+///
+/// Topics and names are matched ignoring case, like the file store, while the
+/// original case is kept for display. Embeddings are not stored here; they live
+/// in the vector database, as they do with the file store.
+pub struct SqliteStore {
+  path: PathBuf,
+  connection: Mutex<Connection>,
+}
+
+impl SqliteStore {
+  /// The database in the current workspace's insights root
+  pub fn open_default() -> Result<Self> {
+    Self::open(&get_insights_root()?.join(DATABASE_FILE))
+  }
+
+  /// The database at `path`, created along with its tables if missing
+  pub fn open(path: &Path) -> Result<Self> {
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    let connection = Connection::open(path)
+      .with_context(|| format!("Failed to open insight database: {}", path.display()))?;
+    connection.execute_batch(SCHEMA)?;
+    Ok(Self { path: path.to_path_buf(), connection: Mutex::new(connection) })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+    self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+
+  fn write(&self, insight: &Insight, mode: WriteMode) -> Result<()> {
+    if let Some(problem) = topic::invalid(&insight.topic) {
+      return Err(anyhow!("Invalid insight {}/{}: {problem}", insight.topic, insight.name));
+    }
+    let (topic_key, name_key) = keys(&insight.topic, &insight.name);
+
+    let mut connection = self.connection();
+    let tx = connection.transaction()?;
+    let exists = tx
+      .query_row(
+        "SELECT 1 FROM insights WHERE topic_key = ?1 AND name_key = ?2",
+        params![topic_key, name_key],
+        |_| Ok(()),
+      )
+      .optional()?
+      .is_some();
+    match mode {
+      WriteMode::New if exists => {
+        return Err(anyhow!("Insight {}/{} already exists", insight.topic, insight.name));
+      }
+      WriteMode::Existing if !exists => {
+        return Err(anyhow!("Insight {}/{} not found", insight.topic, insight.name));
+      }
+      _ => {}
+    }
+
+    tx.execute(
+      "INSERT INTO insights (topic_key, name_key, topic, name, overview, details, created_at,
+         last_updated, update_count)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+       ON CONFLICT (topic_key, name_key) DO UPDATE SET
+         topic = excluded.topic, name = excluded.name, overview = excluded.overview,
+         details = excluded.details, created_at = excluded.created_at,
+         last_updated = excluded.last_updated, update_count = excluded.update_count",
+      params![
+        topic_key,
+        name_key,
+        insight.topic,
+        insight.name,
+        insight.overview,
+        insight.details,
+        insight.created_at.to_rfc3339(),
+        insight.last_updated.to_rfc3339(),
+        insight.update_count,
+      ],
+    )?;
+    tx.execute(
+      "DELETE FROM tags WHERE topic_key = ?1 AND name_key = ?2",
+      params![topic_key, name_key],
+    )?;
+    for (position, tag) in insight.tags.iter().enumerate() {
+      tx.execute(
+        "INSERT INTO tags (topic_key, name_key, position, tag) VALUES (?1, ?2, ?3, ?4)",
+        params![topic_key, name_key, position as i64, tag],
+      )?;
+    }
+    tx.commit()?;
+    Ok(())
+  }
+
+  fn tags(connection: &Connection, topic_key: &str, name_key: &str) -> Result<Vec<String>> {
+    let mut statement = connection.prepare_cached(
+      "SELECT tag FROM tags WHERE topic_key = ?1 AND name_key = ?2 ORDER BY position",
+    )?;
+    let tags = statement
+      .query_map(params![topic_key, name_key], |row| row.get(0))?
+      .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(tags)
+  }
+}
+
+/// What a write expects to find
+#[derive(Clone, Copy)]
+enum WriteMode {
+  New,
+  Existing,
+  Any,
+}
+
+impl InsightStore for SqliteStore {
+  fn save(&self, insight: &Insight) -> Result<()> {
+    self.write(insight, WriteMode::New)
+  }
+
+  fn save_existing(&self, insight: &Insight) -> Result<()> {
+    self.write(insight, WriteMode::Any)
+  }
+
+  fn replace(&self, insight: &Insight) -> Result<()> {
+    self.write(insight, WriteMode::Existing)
+  }
+
+  fn load(&self, topic: &str, name: &str) -> Result<Insight> {
+    let (topic_key, name_key) = keys(topic, name);
+    let connection = self.connection();
+    let row = connection
+      .query_row(
+        &format!("{SELECT_INSIGHT} WHERE topic_key = ?1 AND name_key = ?2"),
+        params![topic_key, name_key],
+        StoredRow::read,
+      )
+      .optional()?
+      .ok_or_else(|| anyhow!("Insight {}/{} not found", topic, name))?;
+    row.into_insight(&connection)
+  }
+
+  fn delete(&self, insight: &Insight) -> Result<()> {
+    let (topic_key, name_key) = keys(&insight.topic, &insight.name);
+    let removed = self.connection().execute(
+      "DELETE FROM insights WHERE topic_key = ?1 AND name_key = ?2",
+      params![topic_key, name_key],
+    )?;
+    if removed == 0 {
+      return Err(anyhow!("Insight {}/{} not found", insight.topic, insight.name));
+    }
+    Ok(())
+  }
+
+  fn topics(&self) -> Result<Vec<String>> {
+    let connection = self.connection();
+    let mut statement =
+      connection.prepare("SELECT DISTINCT topic_key FROM insights ORDER BY topic_key")?;
+    let topics = statement.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+    Ok(topics)
+  }
+
+  fn insights(&self, topic_filter: Option<&str>) -> Result<Vec<Insight>> {
+    let connection = self.connection();
+    let mut statement = connection.prepare(&format!("{SELECT_INSIGHT} ORDER BY name"))?;
+    let rows = statement.query_map([], StoredRow::read)?.collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut insights = Vec::new();
+    for row in rows {
+      if topic_filter.is_some_and(|filter| !topic::within(&row.topic_key, filter)) {
+        continue;
+      }
+      insights.push(row.into_insight(&connection)?);
+    }
+    Ok(insights)
+  }
+}
+
+/// An `insights` row before its tags are attached
+struct StoredRow {
+  topic: String,
+  name: String,
+  overview: String,
+  details: String,
+  created_at: String,
+  last_updated: String,
+  update_count: u32,
+  topic_key: String,
+  name_key: String,
+}
+
+impl StoredRow {
+  fn read(row: &Row<'_>) -> rusqlite::Result<Self> {
+    Ok(Self {
+      topic: row.get(0)?,
+      name: row.get(1)?,
+      overview: row.get(2)?,
+      details: row.get(3)?,
+      created_at: row.get(4)?,
+      last_updated: row.get(5)?,
+      update_count: row.get(6)?,
+      topic_key: row.get(7)?,
+      name_key: row.get(8)?,
+    })
+  }
+
+  fn into_insight(self, connection: &Connection) -> Result<Insight> {
+    let tags = SqliteStore::tags(connection, &self.topic_key, &self.name_key)?;
+    Ok(Insight {
+      topic: self.topic,
+      name: self.name,
+      overview: self.overview,
+      details: self.details,
+      tags,
+      created_at: parse_time(&self.created_at)?,
+      last_updated: parse_time(&self.last_updated)?,
+      update_count: self.update_count,
+      embedding_version: None,
+      embedding: None,
+      embedding_text: None,
+      embedding_computed: None,
+    })
+  }
+}
+
+/// Case-insensitive lookup keys, matching the file store's lowercased paths
+fn keys(topic: &str, name: &str) -> (String, String) {
+  (topic.to_lowercase(), name.to_lowercase())
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+  Ok(DateTime::parse_from_rfc3339(value)?.with_timezone(&Utc))
+}
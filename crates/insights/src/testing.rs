@@ -0,0 +1,332 @@
+//! Ephemeral insights server for integration tests
+//!
+//! Starts the real REST router in-process on a random local port, backed by a
+//! temporary insights root that is deleted when the server is dropped. The root
+//! can be seeded from fixture insights or copied from a snapshot directory (an
+//! insights root checked into the calling crate). Embeddings are switched off, so
+//! no model is downloaded and semantic search falls back to keyword matching.
+//!
+//! The insights root is process-wide (`INSIGHTS_ROOT`), so only one test server
+//! runs at a time; starting a second one waits until the first is dropped.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use insights::testing::TestServer;
+//!
+//! let server = TestServer::builder()
+//!   .insight("rust", "tokio-tips", "Runtime notes", "Prefer spawn_blocking for CPU work")
+//!   .start()
+//!   .await?;
+//!
+//! let client = server.client();
+//! let found = client.get_insight("rust", "tokio-tips", false).await?;
+//! assert_eq!(found.insight.overview, "Runtime notes");
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Result};
+use bentley::daemon_logs::DaemonLogs;
+use once_cell::sync::Lazy;
+use std::ffi::OsString;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tokio::task::JoinHandle;
+
+use crate::cli::client::{ClientConfig, InsightsClient};
+use crate::server::middleware;
+use crate::server::models::insight::{self, Insight};
+use crate::server::routing::{create_router, create_ui_router};
+use crate::server::services::fulltext;
+
+/// Held by the running test server so servers never share `INSIGHTS_ROOT`
+static SERVER_LOCK: Lazy<Arc<Mutex<()>>> = Lazy::new(|| Arc::new(Mutex::new(())));
+
+/// Configures the data a test server starts with
+#[derive(Debug, Default)]
+pub struct TestServerBuilder {
+  snapshots: Vec<PathBuf>,
+  fixtures: Vec<Insight>,
+  ui: bool,
+}
+
+impl TestServerBuilder {
+  /// Seed an insight
+  pub fn insight(self, topic: &str, name: &str, overview: &str, details: &str) -> Self {
+    self.fixture(Insight::new(
+      topic.to_string(),
+      name.to_string(),
+      overview.to_string(),
+      details.to_string(),
+    ))
+  }
+
+  /// Seed a prepared insight, keeping its metadata
+  pub fn fixture(mut self, fixture: Insight) -> Self {
+    self.fixtures.push(fixture);
+    self
+  }
+
+  /// Copy every insight file from an existing insights root
+  ///
+  /// Snapshots are copied before fixtures, so a fixture with the same topic and
+  /// name replaces the snapshot's copy.
+  pub fn snapshot(mut self, root: impl Into<PathBuf>) -> Self {
+    self.snapshots.push(root.into());
+    self
+  }
+
+  /// Serve the web UI as well, as `--ui` does
+  pub fn ui(mut self) -> Self {
+    self.ui = true;
+    self
+  }
+
+  /// Seed a fresh insights root and start serving it
+  pub async fn start(self) -> Result<TestServer> {
+    let guard = SERVER_LOCK.clone().lock_owned().await;
+    let root = TempDir::new()?;
+    let previous_root = std::env::var_os("INSIGHTS_ROOT");
+    std::env::set_var("INSIGHTS_ROOT", root.path());
+    // From here on dropping the server restores the environment
+    let mut server = TestServer {
+      root,
+      addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+      handle: None,
+      previous_root,
+      _guard: guard,
+    };
+
+    for snapshot in &self.snapshots {
+      copy_snapshot(snapshot, server.root())?;
+    }
+    for fixture in &self.fixtures {
+      let path = insight::file_path(fixture)?;
+      if let Some(topic_dir) = path.parent() {
+        fs::create_dir_all(topic_dir)?;
+      }
+      fs::write(path, insight::to_markdown(fixture)?)?;
+    }
+    fulltext::rebuild()?;
+
+    init_backends()?;
+    let listener = TcpListener::bind(server.addr).await?;
+    server.addr = listener.local_addr()?;
+    let router = if self.ui { create_router().merge(create_ui_router()) } else { create_router() };
+    server.handle = Some(tokio::spawn(async move {
+      let _ = axum::serve(listener, router).await;
+    }));
+
+    Ok(server)
+  }
+}
+
+/// An insights server running in the current test
+///
+/// Dropping it stops the server, deletes its data and restores `INSIGHTS_ROOT`.
+pub struct TestServer {
+  root: TempDir,
+  addr: SocketAddr,
+  handle: Option<JoinHandle<()>>,
+  previous_root: Option<OsString>,
+  _guard: OwnedMutexGuard<()>,
+}
+
+impl TestServer {
+  /// Builder for a server seeded with snapshots or fixtures
+  pub fn builder() -> TestServerBuilder {
+    TestServerBuilder::default()
+  }
+
+  /// Start a server with an empty insights root
+  pub async fn start() -> Result<Self> {
+    Self::builder().start().await
+  }
+
+  /// Address the server is listening on
+  pub fn addr(&self) -> SocketAddr {
+    self.addr
+  }
+
+  /// Base URL for raw HTTP requests, e.g. `http://127.0.0.1:41234`
+  pub fn url(&self) -> String {
+    format!("http://{}", self.addr)
+  }
+
+  /// The temporary insights root the server reads and writes
+  pub fn root(&self) -> &Path {
+    self.root.path()
+  }
+
+  /// API client pointed at this server
+  pub fn client(&self) -> InsightsClient {
+    InsightsClient::with_config(ClientConfig { base_url: self.url(), ..ClientConfig::default() })
+  }
+
+  /// API client pointed at this server, working in `workspace`
+  pub fn workspace_client(&self, workspace: &str) -> InsightsClient {
+    InsightsClient::with_config(ClientConfig {
+      base_url: self.url(),
+      workspace: Some(workspace.to_string()),
+      ..ClientConfig::default()
+    })
+  }
+
+  /// Read an insight straight from disk, bypassing the API
+  pub fn insight(&self, topic: &str, name: &str) -> Result<Insight> {
+    insight::load(topic, name)
+  }
+}
+
+impl Drop for TestServer {
+  fn drop(&mut self) {
+    if let Some(handle) = self.handle.take() {
+      handle.abort();
+    }
+    match self.previous_root.take() {
+      Some(previous) => std::env::set_var("INSIGHTS_ROOT", previous),
+      None => std::env::remove_var("INSIGHTS_ROOT"),
+    }
+  }
+}
+
+/// Copy the insight files of every topic under `from`, nested ones included, into `to`
+fn copy_snapshot(from: &Path, to: &Path) -> Result<()> {
+  if !from.is_dir() {
+    return Err(anyhow!("Snapshot {} is not a directory", from.display()));
+  }
+
+  for topic in fs::read_dir(from)?.flatten() {
+    let topic_name = topic.file_name();
+    // Skip history, indexes and other bookkeeping directories
+    if !topic.path().is_dir() || topic_name.to_string_lossy().starts_with('.') {
+      continue;
+    }
+    copy_topic(&topic.path(), &to.join(&topic_name))?;
+  }
+
+  Ok(())
+}
+
+fn copy_topic(from: &Path, to: &Path) -> Result<()> {
+  for entry in fs::read_dir(from)?.flatten() {
+    let path = entry.path();
+    if path.is_dir() && !entry.file_name().to_string_lossy().starts_with('.') {
+      copy_topic(&path, &to.join(entry.file_name()))?;
+    } else if insight::is_insight_file(&path) {
+      fs::create_dir_all(to)?;
+      fs::copy(&path, to.join(entry.file_name()))?;
+    }
+  }
+  Ok(())
+}
+
+/// Logging and (with the semantic feature) the vector database, set up once per process
+fn init_backends() -> Result<()> {
+  static INIT: std::sync::OnceLock<std::result::Result<(), String>> = std::sync::OnceLock::new();
+
+  INIT
+    .get_or_init(|| {
+      let logs_path = std::env::temp_dir()
+        .join(format!("insights-testing-{}", std::process::id()))
+        .join("server-logs.jsonl");
+      let logs = DaemonLogs::new_with_silent(&logs_path, true).map_err(|e| e.to_string())?;
+      // Already set if the process started a real server; keep that logger
+      let _ = middleware::init_global_logger(Arc::new(logs));
+      init_vector_db();
+      Ok(())
+    })
+    .clone()
+    .map_err(|e| anyhow!("Failed to initialize test server logging: {e}"))
+}
+
+#[cfg(feature = "semantic")]
+fn init_vector_db() {
+  use crate::server::services::{embeddings, vector_database::BoxedVectorDatabase};
+
+  embeddings::disable();
+  let _ = middleware::init_global_vector_db(Arc::new(BoxedVectorDatabase::new(
+    no_embeddings::NoEmbeddings,
+  )));
+  let _ = middleware::init_vector_db_opener(|| {
+    Box::pin(async { Ok(BoxedVectorDatabase::new(no_embeddings::NoEmbeddings)) })
+  });
+}
+
+#[cfg(not(feature = "semantic"))]
+fn init_vector_db() {
+  // No-op: there is no vector database without the semantic feature
+}
+
+#[cfg(feature = "semantic")]
+mod no_embeddings {
+  use anyhow::Result;
+  use async_trait::async_trait;
+  use std::collections::HashMap;
+
+  use crate::server::models::insight::Insight;
+  use crate::server::models::sharding::ShardConfig;
+  use crate::server::services::sharding::ShardStats;
+  use crate::server::services::vector_database::{VectorDatabase, VectorSearchResult};
+
+  /// Vector database that stores nothing and never has embeddings to search
+  pub struct NoEmbeddings;
+
+  #[async_trait]
+  impl VectorDatabase for NoEmbeddings {
+    async fn store_embedding(&self, _insight: &Insight) -> Result<()> {
+      Ok(())
+    }
+
+    async fn search_similar(
+      &self,
+      _query_embedding: &[f32],
+      _limit: usize,
+      _threshold: Option<f32>,
+      _tags: &[String],
+    ) -> Result<Vec<VectorSearchResult>> {
+      Ok(Vec::new())
+    }
+
+    async fn has_embeddings(&self) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn delete_embedding(&self, _topic: &str, _name: &str) -> Result<()> {
+      Ok(())
+    }
+
+    async fn update_embedding(&self, _insight: &Insight) -> Result<()> {
+      Ok(())
+    }
+
+    async fn get_all_embeddings(&self) -> Result<Vec<VectorSearchResult>> {
+      Ok(Vec::new())
+    }
+
+    async fn clear_all_embeddings(&self) -> Result<()> {
+      Ok(())
+    }
+
+    async fn reshape_database(&self, _embedding_dimension: usize) -> Result<()> {
+      Ok(())
+    }
+
+    async fn shard_stats(&self) -> Result<Vec<ShardStats>> {
+      Ok(Vec::new())
+    }
+
+    async fn topic_embeddings(&self, _topic: &str) -> Result<HashMap<String, Vec<f32>>> {
+      Ok(HashMap::new())
+    }
+
+    async fn rebalance(&self, _config: ShardConfig) -> Result<usize> {
+      Ok(0)
+    }
+  }
+}
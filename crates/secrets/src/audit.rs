@@ -0,0 +1,47 @@
+//! Append-only audit log kept beside the vault
+//!
+//! Each line is `<unix seconds> [<tag>] <message>`. Never pass secret values here.
+
+use anyhow::Result;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Audit log for the vault at `cred_path`
+pub fn log_path(cred_path: &Path) -> PathBuf {
+  cred_path.parent().unwrap_or_else(|| Path::new(".")).join("audit.log")
+}
+
+/// Append a tagged entry to the vault's audit log
+pub fn record(cred_path: &Path, tag: &str, message: &str) -> Result<()> {
+  let mut file = OpenOptions::new().create(true).append(true).open(log_path(cred_path))?;
+  writeln!(file, "{} [{tag}] {message}", now())?;
+  Ok(())
+}
+
+/// Seconds since the unix epoch
+pub fn now() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_record_appends_tagged_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("credentials.enc");
+
+    record(&vault, "lockout", "first").unwrap();
+    record(&vault, "unlock", "second").unwrap();
+
+    let content = std::fs::read_to_string(log_path(&vault)).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("[lockout] first"));
+    assert!(lines[1].ends_with("[unlock] second"));
+  }
+}
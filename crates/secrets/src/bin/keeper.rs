@@ -1,12 +1,185 @@
+//! Keeper daemon: holds the master password and serves it over a Unix socket
+//!
+//! Each connection sends one request line and gets one reply line, unless the
+//! request is malformed or the peer is refused:
+//!
+//! - `GET` - the master password, or an empty line while locked
+//! - `LOCK` - forget the password (`OK`)
+//! - `UNLOCK <password>` - verify the password against the vault and keep it (`OK` or `ERR <reason>`)
+//! - `STATUS` - `LOCKED`, `UNLOCKED`, or `UNLOCKED <seconds until auto-lock>`
+//! - `GET_SECRET`, `STORE_SECRET`, `DELETE_SECRET` - work on a single secret so
+//!   the client never sees the password, see [`secrets::keeper_secrets`]
+//!
+//! When started by systemd socket activation, or with neither a terminal to prompt
+//! on nor `SECRETS_AUTH`, the keeper starts locked and waits for an `UNLOCK`.
+//!
+//! With `SECRETS_IDLE_TIMEOUT_SECS` set, the password is zeroized once it has not
+//! been used for that long and clients have to unlock the keeper again.
+//!
+//! Only processes of the keeper's own user may connect, optionally narrowed to the
+//! programs listed in `keeper.json`, see [`secrets::peer`]. Denied connections are
+//! logged to `keeper-logs.jsonl` beside the vault.
+
 use anyhow::anyhow;
 use anyhow::Result;
-
+use secrets::keeper_secrets::{
+  SecretReply, SecretRequest, DELETE_SECRET, GET_SECRET, STORE_SECRET,
+};
+use secrets::peer::{Peer, PeerPolicy};
+use secrets::settings;
+use secrets::{CryptoProvider, PasswordBasedCryptoManager};
+
+use std::io::IsTerminal;
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use std::{env, fs};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixListener;
 use tokio::signal;
 use tokio::task::JoinHandle;
+use zeroize::Zeroizing;
+
+/// How long shutdown waits for in-flight client requests to complete
+const CLIENT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the idle timeout is checked
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Held while the vault is read or written, so concurrent stores keep each other's changes
+static VAULT: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Persistent log of denied connections, once the daemon has opened it
+static DAEMON_LOGS: OnceLock<bentley::DaemonLogs> = OnceLock::new();
+
+/// Master password held in memory, forgotten when locked or idle for too long
+struct KeeperState {
+  cred_path: PathBuf,
+  password: Option<Zeroizing<String>>,
+  last_used: Instant,
+  idle_timeout: Option<Duration>,
+  peers: PeerPolicy,
+}
+
+type SharedState = Arc<Mutex<KeeperState>>;
+
+impl KeeperState {
+  fn unlocked(cred_path: PathBuf, password: String, idle_timeout: Option<Duration>) -> Self {
+    Self { password: Some(Zeroizing::new(password)), ..Self::locked(cred_path, idle_timeout) }
+  }
+
+  fn locked(cred_path: PathBuf, idle_timeout: Option<Duration>) -> Self {
+    Self {
+      cred_path,
+      password: None,
+      last_used: Instant::now(),
+      idle_timeout,
+      peers: PeerPolicy::default(),
+    }
+  }
+
+  fn with_peers(mut self, peers: PeerPolicy) -> Self {
+    self.peers = peers;
+    self
+  }
+
+  fn lock(&mut self) {
+    // Dropping the Zeroizing wrapper wipes the password
+    self.password = None;
+  }
+
+  fn unlock(&mut self, password: String, now: Instant) {
+    self.password = Some(Zeroizing::new(password));
+    self.last_used = now;
+  }
+
+  /// Lock if the password has gone unused for longer than the idle timeout
+  fn expire_if_idle(&mut self, now: Instant) -> bool {
+    let idle =
+      self.idle_timeout.is_some_and(|timeout| now.duration_since(self.last_used) >= timeout);
+    if idle && self.password.is_some() {
+      self.lock();
+      return true;
+    }
+    false
+  }
+
+  /// The password, counting this as a use
+  fn take_use(&mut self, now: Instant) -> Option<Zeroizing<String>> {
+    self.expire_if_idle(now);
+    let password = self.password.clone()?;
+    self.last_used = now;
+    Some(password)
+  }
+
+  fn status(&mut self, now: Instant) -> String {
+    self.expire_if_idle(now);
+    if self.password.is_none() {
+      return "LOCKED".to_string();
+    }
+    match self.idle_timeout {
+      Some(timeout) => {
+        let remaining = timeout.saturating_sub(now.duration_since(self.last_used));
+        format!("UNLOCKED {}", remaining.as_secs())
+      }
+      None => "UNLOCKED".to_string(),
+    }
+  }
+}
+
+/// A parsed protocol request
+#[derive(Debug, PartialEq, Eq)]
+enum Request {
+  Get,
+  Lock,
+  Unlock(String),
+  Status,
+  Secret(SecretOp, SecretRequest),
+}
+
+/// What a per-secret request does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SecretOp {
+  Get,
+  Store,
+  Delete,
+}
+
+fn parse_request(line: &str) -> Option<Request> {
+  let line = line.trim_end_matches(['\r', '\n']);
+  match line.trim() {
+    "GET" => return Some(Request::Get),
+    "LOCK" => return Some(Request::Lock),
+    "STATUS" => return Some(Request::Status),
+    _ => {}
+  }
+  if let Some(password) = line.strip_prefix("UNLOCK ") {
+    let password = password.trim();
+    return (!password.is_empty()).then(|| Request::Unlock(password.to_string()));
+  }
+
+  let (verb, body) = line.split_once(' ')?;
+  let op = match verb {
+    GET_SECRET => SecretOp::Get,
+    STORE_SECRET => SecretOp::Store,
+    DELETE_SECRET => SecretOp::Delete,
+    _ => return None,
+  };
+  let request: SecretRequest = serde_json::from_str(body).ok()?;
+  let has_value = request.value.as_deref().is_some_and(|value| !value.trim().is_empty());
+  (has_value == (op == SecretOp::Store)).then_some(Request::Secret(op, request))
+}
+
+/// Get the configured idle timeout
+/// Default: none (the password is kept until the keeper stops)
+/// Setting: secrets.idle_timeout_secs (SECRETS_IDLE_TIMEOUT_SECS)
+fn get_idle_timeout() -> Option<Duration> {
+  bentley::config::var(&settings::IDLE_TIMEOUT_SECS)
+    .and_then(|s| s.trim().parse().ok())
+    .filter(|secs| *secs > 0)
+    .map(Duration::from_secs)
+}
 
 // Test constants
 #[cfg(test)]
@@ -24,39 +197,120 @@ const ERROR_PASSWORD_EMPTY: &str = "master password cannot be empty";
 #[cfg(test)]
 const ERROR_PASSWORDS_DONT_MATCH: &str = "passwords do not match";
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
+  // Claim the systemd-owned socket while the process is still single-threaded
+  let activated = secrets::systemd::take_activated_listener()?;
+  tokio::runtime::Runtime::new()?.block_on(run(activated))
+}
+
+async fn run(activated: Option<std::os::unix::net::UnixListener>) -> Result<()> {
   let keeper_path = get_base()?;
 
   // Ensure directory exists
   fs::create_dir_all(&keeper_path)?;
   let cred_path = keeper_path.join("credentials.enc");
-  let master_password = if !cred_path.exists() {
-    secrets::encryption::EncryptionManager::create_new_vault(&cred_path)?
-  } else {
-    secrets::encryption::EncryptionManager::get_master_password(&cred_path)?
+  let master_password = startup_password(&cred_path, activated.is_some())?;
+
+  let logs = bentley::DaemonLogs::new(keeper_path.join("keeper-logs.jsonl"))?;
+  let _ = DAEMON_LOGS.set(logs);
+
+  let peers = PeerPolicy::load(&cred_path)?;
+  if !peers.allowed_executables().is_empty() {
+    bentley::info!(&format!("serving {} allowed executable(s)", peers.allowed_executables().len()));
+  }
+
+  let idle_timeout = get_idle_timeout();
+  let state = match master_password {
+    Some(password) => KeeperState::unlocked(cred_path, password, idle_timeout),
+    None => {
+      bentley::info!("starting locked - run `blizz secrets agent unlock` to unlock");
+      KeeperState::locked(cred_path, idle_timeout)
+    }
   };
+  let state = state.with_peers(peers);
+  let state = Arc::new(Mutex::new(state));
+  let idle_handle = idle_timeout.map(|timeout| {
+    bentley::info!(&format!("locking after {}s without use", timeout.as_secs()));
+    spawn_idle_lock(state.clone())
+  });
 
-  let socket_path = create_socket(&keeper_path)?;
-  bentley::info!("daemon started - press ctrl+c to exit");
+  let in_flight = Arc::new(());
+  let (socket_path, ipc_handle) = match activated {
+    Some(listener) => {
+      bentley::info!("using socket passed by systemd");
+      let listener = UnixListener::from_std(listener)?;
+      (None, spawn_listener(listener, state, in_flight.clone()))
+    }
+    None => {
+      let socket_path = create_socket(&keeper_path)?;
+      let handle = spawn_handler(&socket_path, state, in_flight.clone());
+      (Some(socket_path), handle)
+    }
+  };
 
-  let ipc_handle = spawn_handler(&socket_path, master_password);
+  let _ = secrets::systemd::notify("READY=1");
+  bentley::info!("daemon started - press ctrl+c to exit");
 
-  // Wait for shutdown signal
-  signal::ctrl_c().await?;
+  wait_for_shutdown().await?;
   bentley::info!("\nshutting down daemon");
+  let _ = secrets::systemd::notify("STOPPING=1");
+
+  // Stop accepting new clients, then let in-flight requests finish
+  ipc_handle.abort();
+  if let Some(handle) = idle_handle {
+    handle.abort();
+  }
+  drain_clients(&in_flight, CLIENT_DRAIN_TIMEOUT).await;
 
-  // Clean up socket file
-  let _ = fs::remove_file(&socket_path);
+  // When systemd owns the socket it keeps queueing connections across restarts,
+  // so only a socket we bound ourselves is removed
+  if let Some(socket_path) = socket_path {
+    let _ = fs::remove_file(&socket_path);
+  }
 
   // Clean up PID file
   let pid_file = keeper_path.join("keeper.pid");
   let _ = fs::remove_file(&pid_file);
 
-  ipc_handle.abort();
   Ok(())
 }
 
+fn startup_password(cred_path: &Path, activated: bool) -> Result<Option<String>> {
+  let can_prompt = !activated && std::io::stdin().is_terminal();
+  if !cred_path.exists() {
+    if !can_prompt {
+      return Err(anyhow!(
+        "no vault found - run `blizz secrets agent start` in a terminal to create one"
+      ));
+    }
+    return secrets::encryption::EncryptionManager::create_new_vault(cred_path).map(Some);
+  }
+  if env::var_os("SECRETS_AUTH").is_none() && !can_prompt {
+    return Ok(None);
+  }
+  secrets::encryption::EncryptionManager::get_master_password(cred_path).map(Some)
+}
+
+async fn wait_for_shutdown() -> Result<()> {
+  let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+  tokio::select! {
+    result = signal::ctrl_c() => result?,
+    _ = terminate.recv() => {}
+  }
+  Ok(())
+}
+
+async fn drain_clients(in_flight: &Arc<()>, timeout: Duration) {
+  let deadline = tokio::time::Instant::now() + timeout;
+  while Arc::strong_count(in_flight) > 1 {
+    if tokio::time::Instant::now() >= deadline {
+      bentley::warn!("timed out waiting for clients to finish");
+      return;
+    }
+    tokio::time::sleep(Duration::from_millis(10)).await;
+  }
+}
+
 fn get_base() -> Result<PathBuf> {
   let base = if let Ok(dir) = env::var("BLIZZ_HOME") {
     PathBuf::from(dir)
@@ -75,7 +329,25 @@ fn create_socket(keeper_path: &Path) -> Result<PathBuf> {
   Ok(socket)
 }
 
-fn spawn_handler(socket: &PathBuf, pwd: String) -> JoinHandle<()> {
+/// Periodically forget the password once it has been idle for too long
+fn spawn_idle_lock(state: SharedState) -> JoinHandle<()> {
+  tokio::spawn(async move {
+    let mut ticker = tokio::time::interval(IDLE_CHECK_INTERVAL);
+    loop {
+      ticker.tick().await;
+      let locked = lock_state(&state).expire_if_idle(Instant::now());
+      if locked {
+        bentley::info!("idle timeout reached, keeper locked");
+      }
+    }
+  })
+}
+
+fn lock_state(state: &SharedState) -> std::sync::MutexGuard<'_, KeeperState> {
+  state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn spawn_handler(socket: &PathBuf, state: SharedState, in_flight: Arc<()>) -> JoinHandle<()> {
   let listener = match UnixListener::bind(socket) {
     Ok(listener) => listener,
     Err(e) => {
@@ -84,15 +356,30 @@ fn spawn_handler(socket: &PathBuf, pwd: String) -> JoinHandle<()> {
     }
   };
 
+  // Peer checks do the real gatekeeping; this keeps other users from even connecting
+  if let Err(e) = fs::set_permissions(socket, fs::Permissions::from_mode(0o600)) {
+    bentley::warn!(&format!("failed to restrict socket permissions: {e}"));
+  }
+
   bentley::info!(&format!("listening on socket: {}", socket.display()));
 
-  let handler = tokio::spawn(async move {
+  spawn_listener(listener, state, in_flight)
+}
+
+fn spawn_listener(
+  listener: UnixListener,
+  state: SharedState,
+  in_flight: Arc<()>,
+) -> JoinHandle<()> {
+  tokio::spawn(async move {
     loop {
       match listener.accept().await {
         Ok((stream, _)) => {
-          let pwd_clone = pwd.clone();
+          let state = state.clone();
+          let guard = in_flight.clone();
           tokio::spawn(async move {
-            handle_client(stream, pwd_clone).await;
+            handle_client(stream, state).await;
+            drop(guard);
           });
         }
         Err(e) => {
@@ -100,34 +387,143 @@ fn spawn_handler(socket: &PathBuf, pwd: String) -> JoinHandle<()> {
         }
       }
     }
-  });
+  })
+}
 
-  handler
+/// Whether the process on the other end of `stream` may use the keeper
+async fn admit(stream: &tokio::net::UnixStream, state: &SharedState) -> bool {
+  let verdict = match Peer::of(stream) {
+    Ok(peer) => lock_state(state).peers.check(&peer),
+    Err(e) => Err(format!("denied connection: peer credentials unavailable: {e}")),
+  };
+  let Err(reason) = verdict else {
+    return true;
+  };
+  match DAEMON_LOGS.get() {
+    Some(logs) => logs.warn(&reason, "keeper").await,
+    None => bentley::warn!(&reason),
+  }
+  false
 }
 
-async fn handle_client(stream: tokio::net::UnixStream, password: String) {
-  let mut reader = BufReader::new(stream);
-  let mut line = String::new();
+async fn handle_client(stream: tokio::net::UnixStream, state: SharedState) {
+  if !admit(&stream, &state).await {
+    return;
+  }
 
-  match reader.read_line(&mut line).await {
-    Ok(_) if line.trim() == "GET" => {
-      let mut stream = reader.into_inner();
-      if let Err(e) = stream.write_all(password.as_bytes()).await {
-        bentley::warn!(&format!("failed to send password: {e}"));
+  let mut reader = BufReader::new(stream);
+  let mut line = Zeroizing::new(String::new());
+
+  let request = match reader.read_line(&mut line).await {
+    Ok(_) => match parse_request(&line) {
+      Some(request) => request,
+      None => {
+        // Never echo the line back: it may be a mistyped UNLOCK carrying a password
+        bentley::warn!("invalid request");
         return;
       }
-      if let Err(e) = stream.write_all(b"\n").await {
-        bentley::warn!(&format!("failed to send newline: {e}"));
-        return;
+    },
+    Err(e) => {
+      bentley::warn!(&format!("failed to read request: {e}"));
+      return;
+    }
+  };
+
+  let reply = respond(request, &state).await;
+  let mut stream = reader.into_inner();
+  if let Err(e) = stream.write_all(reply.as_bytes()).await {
+    bentley::warn!(&format!("failed to send reply: {e}"));
+    return;
+  }
+  if let Err(e) = stream.write_all(b"\n").await {
+    bentley::warn!(&format!("failed to send newline: {e}"));
+  }
+}
+
+/// Carry out a request, returning the reply line without its newline
+async fn respond(request: Request, state: &SharedState) -> Zeroizing<String> {
+  match request {
+    Request::Get => {
+      let password = lock_state(state).take_use(Instant::now());
+      match &password {
+        Some(_) => bentley::verbose!("password sent to client"),
+        None => bentley::verbose!("password requested while locked"),
       }
-      bentley::verbose!("password sent to client");
+      // An empty line tells a locked keeper apart from one that died mid-request
+      password.unwrap_or_default()
     }
-    Ok(_) => {
-      bentley::warn!(&format!("invalid request: {}", line.trim()));
+    Request::Lock => {
+      lock_state(state).lock();
+      bentley::info!("keeper locked");
+      Zeroizing::new("OK".to_string())
     }
-    Err(e) => {
-      bentley::warn!(&format!("failed to read request: {e}"));
+    Request::Unlock(password) => {
+      let password = Zeroizing::new(password);
+      let cred_path = lock_state(state).cred_path.clone();
+      let candidate = password.clone();
+      // Key derivation is slow, keep it off the async workers and outside the state lock.
+      // verify_password holds the lockout lock itself, so parallel UNLOCKs queue there.
+      let verified = tokio::task::spawn_blocking(move || {
+        secrets::encryption::EncryptionManager::verify_password(&cred_path, &candidate)
+      })
+      .await
+      .map_err(|e| anyhow!("verification task failed: {e}"))
+      .and_then(|result| result);
+
+      let reply = match verified {
+        Ok(()) => {
+          lock_state(state).unlock(password.to_string(), Instant::now());
+          bentley::info!("keeper unlocked");
+          "OK".to_string()
+        }
+        Err(e) => format!("ERR {e}"),
+      };
+      Zeroizing::new(reply)
     }
+    Request::Status => Zeroizing::new(lock_state(state).status(Instant::now())),
+    Request::Secret(op, request) => {
+      let reply = serve_secret(op, request, state).await;
+      let reply = serde_json::to_string(&reply).expect("secret replies always serialize");
+      Zeroizing::new(reply)
+    }
+  }
+}
+
+/// Read, store or delete one secret with the held password
+async fn serve_secret(op: SecretOp, request: SecretRequest, state: &SharedState) -> SecretReply {
+  let (password, cred_path) = {
+    let mut state = lock_state(state);
+    (state.take_use(Instant::now()), state.cred_path.clone())
+  };
+  let Some(password) = password else {
+    return SecretReply::failed("keeper is locked");
+  };
+
+  let _vault = VAULT.lock().await;
+  let (group, name) = (request.group.clone(), request.name.clone());
+  // Key derivation is slow, keep it off the async workers
+  let result = tokio::task::spawn_blocking(move || {
+    let vault = PasswordBasedCryptoManager::at(cred_path);
+    let SecretRequest { group, name, value } = request;
+    match op {
+      SecretOp::Get => vault.get_secret(&group, &name, &password).map(Some),
+      SecretOp::Store => {
+        let value = Zeroizing::new(value.unwrap_or_default());
+        vault.store_secret(&group, &name, value.trim(), &password).map(|()| None)
+      }
+      SecretOp::Delete => vault.delete_secret(&group, &name, &password).map(|()| None),
+    }
+  })
+  .await
+  .map_err(|e| anyhow!("vault task failed: {e}"))
+  .and_then(|result| result);
+
+  match result {
+    Ok(value) => {
+      bentley::verbose!(&format!("{op:?} {group}/{name} for client"));
+      SecretReply { value, error: None }
+    }
+    Err(e) => SecretReply::failed(e),
   }
 }
 
@@ -151,6 +547,11 @@ mod tests {
   use std::process::Command as StdCommand;
   use tempfile::TempDir;
 
+  fn unlocked(password: &str) -> SharedState {
+    let state = KeeperState::unlocked(PathBuf::from("credentials.enc"), password.to_string(), None);
+    Arc::new(Mutex::new(state))
+  }
+
   fn with_temp_env<F, R>(f: F) -> R
   where
     F: FnOnce(&TempDir) -> R,
@@ -544,7 +945,7 @@ mod tests {
 
     // Handle the server side
     let server_task = tokio::spawn(async move {
-      handle_client(server_stream, test_password.to_string()).await;
+      handle_client(server_stream, unlocked(test_password)).await;
     });
 
     // Wait for client to get response
@@ -593,7 +994,7 @@ mod tests {
     });
 
     let server_task = tokio::spawn(async move {
-      handle_client(server_stream, test_password.to_string()).await;
+      handle_client(server_stream, unlocked(test_password)).await;
     });
 
     let result = client_task.await.expect("Client task failed");
@@ -622,7 +1023,7 @@ mod tests {
 
     // This should handle the error gracefully and not panic
     let server_task = tokio::spawn(async move {
-      handle_client(server_stream, test_password.to_string()).await;
+      handle_client(server_stream, unlocked(test_password)).await;
     });
 
     // Should complete without panicking
@@ -666,7 +1067,7 @@ mod tests {
     let test_password = "spawn_test_password_123";
 
     // Test successful socket binding and handler spawn
-    let handle = spawn_handler(&socket_path, test_password.to_string());
+    let handle = spawn_handler(&socket_path, unlocked(test_password), Arc::new(()));
 
     // Give it a moment to start
     tokio::time::sleep(Duration::from_millis(50)).await;
@@ -735,7 +1136,7 @@ mod tests {
     let test_password = "connection_test_789";
 
     // Start the handler
-    let handle = spawn_handler(&socket_path, test_password.to_string());
+    let handle = spawn_handler(&socket_path, unlocked(test_password), Arc::new(()));
 
     // Give it time to start
     tokio::time::sleep(Duration::from_millis(100)).await;
@@ -925,7 +1326,7 @@ mod tests {
     assert!(socket_path.ends_with("keeper.sock"));
 
     // 3. Handler spawning (line 45) - test briefly then abort
-    let handle = spawn_handler(&socket_path, test_password.to_string());
+    let handle = spawn_handler(&socket_path, unlocked(test_password), Arc::new(()));
 
     // Give it a brief moment to start
     tokio::time::sleep(Duration::from_millis(50)).await;
@@ -1165,4 +1566,285 @@ mod tests {
     assert!(matches!(permission_denied.kind(), io::ErrorKind::PermissionDenied));
     assert!(matches!(not_found.kind(), io::ErrorKind::NotFound));
   }
+
+  #[tokio::test]
+  async fn test_drain_clients_returns_when_idle() {
+    let in_flight = Arc::new(());
+    let start = tokio::time::Instant::now();
+    drain_clients(&in_flight, Duration::from_secs(5)).await;
+    assert!(start.elapsed() < Duration::from_secs(1));
+  }
+
+  #[tokio::test]
+  async fn test_drain_clients_waits_for_in_flight_requests() {
+    let in_flight = Arc::new(());
+    let guard = in_flight.clone();
+
+    let holder = tokio::spawn(async move {
+      tokio::time::sleep(Duration::from_millis(50)).await;
+      drop(guard);
+    });
+
+    drain_clients(&in_flight, Duration::from_secs(5)).await;
+    assert_eq!(Arc::strong_count(&in_flight), 1);
+    holder.await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_drain_clients_gives_up_after_timeout() {
+    let in_flight = Arc::new(());
+    let _stuck = in_flight.clone();
+
+    drain_clients(&in_flight, Duration::from_millis(20)).await;
+    assert_eq!(Arc::strong_count(&in_flight), 2);
+  }
+
+  #[tokio::test]
+  async fn test_spawn_listener_serves_preexisting_listener() {
+    use tokio::io::AsyncReadExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("activated.sock");
+
+    // Simulate the socket systemd would hand over
+    let std_listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+    std_listener.set_nonblocking(true).unwrap();
+    let listener = UnixListener::from_std(std_listener).unwrap();
+
+    let handle = spawn_listener(listener, unlocked("activated_password"), Arc::new(()));
+
+    let mut stream = tokio::net::UnixStream::connect(&socket_path).await.unwrap();
+    stream.write_all(b"GET\n").await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    assert_eq!(response.trim(), "activated_password");
+    handle.abort();
+  }
+
+  #[test]
+  fn test_socket_activated_keeper_starts_locked_without_a_terminal() {
+    use std::io::{BufRead, Write};
+    use std::os::fd::AsRawFd;
+    use std::os::unix::net::{UnixDatagram, UnixStream};
+    use std::os::unix::process::CommandExt;
+    use std::process::Stdio;
+
+    let temp_dir = TempDir::new().unwrap();
+    let keeper_dir = temp_dir.path().join("persistent").join("keeper");
+    fs::create_dir_all(&keeper_dir).unwrap();
+    let password = "activated_password";
+    secrets::PasswordBasedCredentialStore::new(&std::collections::HashMap::new(), password)
+      .unwrap()
+      .save_to_file(&keeper_dir.join("credentials.enc"))
+      .unwrap();
+
+    let socket_path = temp_dir.path().join("activated.sock");
+    let listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+    let notify_path = temp_dir.path().join("notify.sock");
+    let notify = UnixDatagram::bind(&notify_path).unwrap();
+    notify.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+
+    // The shell sets LISTEN_PID to its own pid, which exec hands to the keeper
+    let mut cmd = StdCommand::new("sh");
+    cmd
+      .args(["-c", "LISTEN_PID=$$ exec \"$0\""])
+      .arg(assert_cmd::cargo::cargo_bin("keeper"))
+      .env("BLIZZ_HOME", temp_dir.path())
+      .env("LISTEN_FDS", "1")
+      .env("NOTIFY_SOCKET", &notify_path)
+      .env_remove("SECRETS_AUTH")
+      .stdin(Stdio::null())
+      .stdout(Stdio::null())
+      .stderr(Stdio::null());
+    let fd = listener.as_raw_fd();
+    // SAFETY: only async-signal-safe calls between fork and exec
+    unsafe {
+      cmd.pre_exec(move || {
+        let result = if fd == secrets::systemd::LISTEN_FDS_START {
+          libc::fcntl(fd, libc::F_SETFD, 0)
+        } else {
+          libc::dup2(fd, secrets::systemd::LISTEN_FDS_START)
+        };
+        if result < 0 {
+          return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+      });
+    }
+    let mut child = cmd.spawn().unwrap();
+
+    let ask = |line: &str| {
+      let mut stream = UnixStream::connect(&socket_path).unwrap();
+      stream.write_all(format!("{line}\n").as_bytes()).unwrap();
+      let mut reply = String::new();
+      std::io::BufReader::new(stream).read_line(&mut reply).unwrap();
+      reply.trim_end().to_string()
+    };
+    let mut buf = [0u8; 32];
+    let ready = notify.recv(&mut buf).map(|len| buf[..len].to_vec());
+    let replies = ready.is_ok().then(|| {
+      [ask("STATUS"), ask(&format!("UNLOCK {password}")), ask("GET")]
+    });
+    let _ = child.kill();
+    let _ = child.wait();
+
+    assert_eq!(ready.unwrap(), b"READY=1");
+    assert_eq!(replies.unwrap(), ["LOCKED", "OK", password]);
+  }
+
+  async fn request(state: &SharedState, line: &str) -> String {
+    raw_request(state, line).await.trim_end().to_string()
+  }
+
+  /// Everything the keeper sent back, newline included
+  async fn raw_request(state: &SharedState, line: &str) -> String {
+    use tokio::io::AsyncReadExt;
+    use tokio::net::UnixStream;
+
+    let (mut client, server) = UnixStream::pair().expect("Failed to create socket pair");
+    let server_task = tokio::spawn(handle_client(server, state.clone()));
+    client.write_all(format!("{line}\n").as_bytes()).await.expect("Failed to send request");
+    let mut response = String::new();
+    client.read_to_string(&mut response).await.expect("Failed to read response");
+    server_task.await.expect("Server task failed");
+    response
+  }
+
+  #[tokio::test]
+  async fn test_other_users_get_no_reply() {
+    let own = secrets::peer::current_uid();
+    let state = KeeperState::unlocked(PathBuf::from("credentials.enc"), "pw".to_string(), None);
+    let state = Arc::new(Mutex::new(state.with_peers(PeerPolicy::for_uid(own.wrapping_add(1)))));
+
+    // The connection is dropped before the request is even read
+    let (mut client, server) = tokio::net::UnixStream::pair().unwrap();
+    handle_client(server, state.clone()).await;
+    let mut response = String::new();
+    tokio::io::AsyncReadExt::read_to_string(&mut client, &mut response).await.unwrap();
+    assert_eq!(response, "");
+
+    lock_state(&state).peers = PeerPolicy::for_uid(own);
+    assert_eq!(request(&state, "GET").await, "pw");
+  }
+
+  #[test]
+  fn test_parse_request() {
+    assert_eq!(parse_request("GET\n"), Some(Request::Get));
+    assert_eq!(parse_request("LOCK\r\n"), Some(Request::Lock));
+    assert_eq!(parse_request("STATUS\n"), Some(Request::Status));
+    assert_eq!(parse_request("UNLOCK pass word\n"), Some(Request::Unlock("pass word".to_string())));
+    assert_eq!(parse_request("UNLOCK \n"), None);
+    assert_eq!(parse_request("UNLOCK"), None);
+    assert_eq!(parse_request("get\n"), None);
+  }
+
+  #[test]
+  fn test_state_expires_after_idle_timeout() {
+    let start = Instant::now();
+    let mut state = KeeperState::unlocked(
+      PathBuf::from("credentials.enc"),
+      "pw".to_string(),
+      Some(Duration::from_secs(60)),
+    );
+    state.last_used = start;
+
+    assert_eq!(state.status(start + Duration::from_secs(20)), "UNLOCKED 40");
+    // Using the password resets the idle clock
+    assert!(state.take_use(start + Duration::from_secs(30)).is_some());
+    assert!(!state.expire_if_idle(start + Duration::from_secs(80)));
+    assert!(state.expire_if_idle(start + Duration::from_secs(90)));
+    assert_eq!(state.status(start + Duration::from_secs(90)), "LOCKED");
+    assert!(state.take_use(start + Duration::from_secs(91)).is_none());
+  }
+
+  #[test]
+  fn test_state_without_timeout_stays_unlocked() {
+    let mut state = KeeperState::unlocked(PathBuf::from("credentials.enc"), "pw".to_string(), None);
+    let later = Instant::now() + Duration::from_secs(365 * 24 * 3600);
+    assert!(!state.expire_if_idle(later));
+    assert_eq!(state.status(later), "UNLOCKED");
+  }
+
+  #[tokio::test]
+  async fn test_lock_then_get_returns_an_empty_line() {
+    let state = unlocked("lock_test_password");
+    assert_eq!(request(&state, "STATUS").await, "UNLOCKED");
+    assert_eq!(request(&state, "LOCK").await, "OK");
+    assert_eq!(request(&state, "STATUS").await, "LOCKED");
+    assert_eq!(raw_request(&state, "GET").await, "\n");
+  }
+
+  #[tokio::test]
+  async fn test_unlock_verifies_password_against_vault() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let cred_path = temp_dir.path().join("credentials.enc");
+    let password = "unlock_test_password";
+    let store =
+      secrets::PasswordBasedCredentialStore::new(&std::collections::HashMap::new(), password)
+        .expect("Failed to create vault");
+    store.save_to_file(&cred_path).expect("Failed to save vault");
+
+    let mut locked = KeeperState::unlocked(cred_path, password.to_string(), None);
+    locked.lock();
+    let state = Arc::new(Mutex::new(locked));
+
+    let reply = request(&state, "UNLOCK wrong_password").await;
+    assert!(reply.starts_with("ERR "), "unexpected reply: {reply}");
+    assert_eq!(request(&state, "GET").await, "");
+
+    assert_eq!(request(&state, &format!("UNLOCK {password}")).await, "OK");
+    assert_eq!(request(&state, "GET").await, password);
+  }
+
+  #[test]
+  fn test_parse_secret_requests() {
+    let token =
+      SecretRequest { group: "github".to_string(), name: "token".to_string(), value: None };
+    assert_eq!(
+      parse_request("GET_SECRET {\"group\":\"github\",\"name\":\"token\"}\n"),
+      Some(Request::Secret(SecretOp::Get, token.clone()))
+    );
+    assert_eq!(
+      parse_request("DELETE_SECRET {\"group\":\"github\",\"name\":\"token\"}"),
+      Some(Request::Secret(SecretOp::Delete, token))
+    );
+    assert!(matches!(
+      parse_request("STORE_SECRET {\"group\":\"g\",\"name\":\"n\",\"value\":\"a b\"}"),
+      Some(Request::Secret(SecretOp::Store, SecretRequest { value: Some(_), .. }))
+    ));
+
+    // A store needs a value and nothing else may carry one
+    assert_eq!(parse_request("STORE_SECRET {\"group\":\"g\",\"name\":\"n\"}"), None);
+    assert_eq!(
+      parse_request("STORE_SECRET {\"group\":\"g\",\"name\":\"n\",\"value\":\" \"}"),
+      None
+    );
+    assert_eq!(parse_request("GET_SECRET {\"group\":\"g\",\"name\":\"n\",\"value\":\"v\"}"), None);
+    assert_eq!(parse_request("GET_SECRET github token"), None);
+  }
+
+  #[tokio::test]
+  async fn test_secrets_are_served_without_the_password() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let cred_path = temp_dir.path().join("credentials.enc");
+    let password = "secret_request_password";
+    secrets::PasswordBasedCredentialStore::new(&std::collections::HashMap::new(), password)
+      .and_then(|store| store.save_to_file(&cred_path))
+      .expect("Failed to create vault");
+    let state = Arc::new(Mutex::new(KeeperState::unlocked(cred_path, password.to_string(), None)));
+
+    let store = "STORE_SECRET {\"group\":\"github\",\"name\":\"token\",\"value\":\"ghp_1\"}";
+    assert_eq!(request(&state, store).await, "{}");
+    let get = "GET_SECRET {\"group\":\"github\",\"name\":\"token\"}";
+    assert_eq!(request(&state, get).await, "{\"value\":\"ghp_1\"}");
+
+    let delete = "DELETE_SECRET {\"group\":\"github\",\"name\":\"token\"}";
+    assert_eq!(request(&state, delete).await, "{}");
+    let missing: SecretReply = serde_json::from_str(&request(&state, get).await).unwrap();
+    assert!(missing.error.unwrap().contains("not found"));
+
+    lock_state(&state).lock();
+    assert_eq!(request(&state, get).await, "{\"error\":\"keeper is locked\"}");
+  }
 }
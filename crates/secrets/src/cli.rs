@@ -1,10 +1,15 @@
-use crate::commands;
+use crate::commands::{self, MutationOptions, StoreOptions};
+use crate::envfile::EnvFormat;
+use crate::expiry::ExpiryOptions;
 use crate::keeper_client;
+use crate::settings;
 use crate::Secrets;
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use std::env;
 
+pub use bentley::OutputFormat;
+
 #[derive(Parser)]
 #[command(name = "secrets")]
 #[command(
@@ -18,6 +23,9 @@ pub struct Cli {
   /// Suppress banners and flourishes (useful when called from other tools)
   #[arg(long, global = true)]
   pub quiet: bool,
+  /// Output format for list, read and verify
+  #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+  pub output: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -30,6 +38,43 @@ pub enum AgentAction {
   Stop,
   /// Restart daemon
   Restart,
+  /// Forget the password in the daemon until it is unlocked again
+  Lock,
+  /// Give a locked daemon the password again
+  Unlock,
+  /// Generate user-level systemd units so systemd owns the socket and starts keeper on demand
+  InstallService {
+    /// Directory to write the units to (defaults to ~/.config/systemd/user)
+    #[arg(long)]
+    unit_dir: Option<std::path::PathBuf>,
+  },
+}
+
+#[derive(Subcommand)]
+pub enum SpecAction {
+  /// Register (or replace) the spec for a service
+  Add {
+    /// Service name; its secrets are stored in the group of the same name
+    name: String,
+    /// Secret the service requires (repeatable)
+    #[arg(long = "require", value_name = "KEY")]
+    required: Vec<String>,
+    /// Secret the service can use but does not require (repeatable)
+    #[arg(long = "optional", value_name = "KEY")]
+    optional: Vec<String>,
+    /// Short description shown by setup
+    #[arg(short, long)]
+    description: Option<String>,
+  },
+  /// List registered and built-in specs
+  #[command(visible_alias = "ls")]
+  List,
+  /// Remove a registered spec
+  #[command(visible_alias = "rm")]
+  Remove {
+    /// Service name
+    name: String,
+  },
 }
 
 #[derive(Subcommand)]
@@ -43,6 +88,9 @@ pub enum Commands {
     /// Show secret keys (default: just group names)
     #[arg(long)]
     keys: bool,
+    /// With --keys, show how often and how recently each secret was read
+    #[arg(short, long, requires = "keys")]
+    verbose: bool,
   },
   /// Retrieve/read a secret entry
   Read {
@@ -51,6 +99,9 @@ pub enum Commands {
     /// Group/namespace for the secret (defaults to 'general')
     #[arg(short, long)]
     group: Option<String>,
+    /// Print only the secret value, with no trailing newline
+    #[arg(long)]
+    raw: bool,
   },
   /// Store a secret entry
   Store {
@@ -61,9 +112,12 @@ pub enum Commands {
     /// Group/namespace for the secret (defaults to 'general')
     #[arg(short, long)]
     group: Option<String>,
-    /// Force overwrite existing secret
-    #[arg(short, long)]
-    force: bool,
+    #[command(flatten)]
+    options: StoreOptions,
+    #[command(flatten)]
+    expiry: ExpiryOptions,
+    #[command(flatten)]
+    mutation: MutationOptions,
   },
   /// Delete secret entries
   Delete {
@@ -75,14 +129,194 @@ pub enum Commands {
     /// Skip confirmation prompt
     #[arg(long)]
     force: bool,
+    #[command(flatten)]
+    mutation: MutationOptions,
+  },
+  /// Delete secrets that have not been read for a while, asking about each one
+  Prune {
+    /// Minimum time since last use, e.g. 180d, 12w, 1y
+    #[arg(long, value_parser = crate::usage::parse_period)]
+    unused_for: u64,
+    /// Delete every stale secret without asking
+    #[arg(short, long)]
+    yes: bool,
+    #[command(flatten)]
+    mutation: MutationOptions,
+  },
+  /// Show secrets that have expired or are about to
+  CheckExpiry {
+    /// How far ahead to look, e.g. 14d, 4w
+    #[arg(long, value_parser = crate::usage::parse_period, default_value = "14d")]
+    within: u64,
+  },
+  /// Store every entry of a dotenv or JSON file under one group
+  ImportEnv {
+    /// File to import (.env or .json)
+    file: std::path::PathBuf,
+    /// Group to store the entries in
+    #[arg(short, long)]
+    group: String,
+    /// File format (detected from the extension by default)
+    #[arg(long, value_enum)]
+    format: Option<EnvFormat>,
+    /// Overwrite secrets that already exist in the group
+    #[arg(short, long)]
+    force: bool,
+    /// Skip confirmation prompt
+    #[arg(short, long)]
+    yes: bool,
+    #[command(flatten)]
+    mutation: MutationOptions,
+  },
+  /// Print every secret in a group as dotenv or JSON
+  ExportEnv {
+    /// Group to export
+    #[arg(short, long)]
+    group: String,
+    /// Output format
+    #[arg(long, value_enum, default_value = "dotenv")]
+    format: EnvFormat,
+  },
+  /// Run a command with a group's secrets as environment variables, for its lifetime only
+  Exec {
+    /// Group whose secrets are leased to the command
+    #[arg(short, long)]
+    group: String,
+    /// Command and arguments to run, after `--`
+    #[arg(last = true, required = true)]
+    command: Vec<String>,
+  },
+  /// Write a group's secrets to a short-lived, owner-only env file, or shred one
+  Env {
+    /// Group to snapshot
+    #[arg(short, long, required_unless_present = "revoke")]
+    group: Option<String>,
+    /// Snapshot format
+    #[arg(long, value_enum, default_value = "dotenv")]
+    format: EnvFormat,
+    /// Snapshot file to write, or to shred with --revoke
+    #[arg(long)]
+    file: std::path::PathBuf,
+    /// How long the snapshot lives before it is shredded, e.g. 10m, 2h
+    #[arg(long, value_parser = crate::keys::parse_ttl, default_value = "15m")]
+    ttl: u64,
+    /// Overwrite the file if it exists
+    #[arg(short, long)]
+    force: bool,
+    /// Shred the snapshot at --file now instead of writing one
+    #[arg(long, conflicts_with = "group")]
+    revoke: bool,
+  },
+  /// Print the current one-time code from a TOTP seed stored in a group
+  Totp {
+    /// Group holding the seed
+    group: String,
+    /// Seed to use when the group holds more than one
+    #[arg(short, long)]
+    name: Option<String>,
+    /// Print only the code, with no trailing newline
+    #[arg(long)]
+    raw: bool,
+  },
+  /// Write the whole vault to a file encrypted with a transfer passphrase
+  Export {
+    /// File to write, e.g. vault.kexport
+    #[arg(short, long)]
+    output: std::path::PathBuf,
+    /// Overwrite the output file if it exists
+    #[arg(short, long)]
+    force: bool,
+  },
+  /// Merge a vault exported with `secrets export` into this machine's vault
+  Import {
+    /// Export file to read
+    file: std::path::PathBuf,
+    /// Overwrite secrets that already exist
+    #[arg(short, long)]
+    force: bool,
+    /// Skip confirmation prompt
+    #[arg(short, long)]
+    yes: bool,
+    #[command(flatten)]
+    mutation: MutationOptions,
+  },
+  /// Store an SSH private key or certificate file in the vault
+  StoreKey {
+    /// Name to store the key under
+    name: String,
+    /// Key file to read, e.g. ~/.ssh/id_ed25519
+    file: std::path::PathBuf,
+    /// Overwrite a key stored under the same name
+    #[arg(short, long)]
+    force: bool,
+    #[command(flatten)]
+    mutation: MutationOptions,
+  },
+  /// Write a stored key to a 0600 file and/or ssh-agent
+  WriteKey {
+    /// Name the key was stored under
+    name: String,
+    /// File to write the key to
+    #[arg(long)]
+    to: Option<std::path::PathBuf>,
+    /// Remove the file (and the agent identity) after this long, e.g. 10m, 2h
+    #[arg(long, value_parser = crate::keys::parse_ttl)]
+    ttl: Option<u64>,
+    /// Add the key to the running ssh-agent
+    #[arg(long)]
+    agent: bool,
+    /// Overwrite the file if it exists
+    #[arg(short, long)]
+    force: bool,
+  },
+  /// Delete a stored key
+  RemoveKey {
+    /// Name the key was stored under
+    name: String,
+    #[command(flatten)]
+    mutation: MutationOptions,
+  },
+  /// Shred a key file or env snapshot written with a TTL once it expires
+  #[command(hide = true)]
+  ExpireKey {
+    path: std::path::PathBuf,
+    /// Seconds to wait before removing the file
+    #[arg(long)]
+    after: u64,
+    /// Identity of the written file; a replaced file is left alone
+    #[arg(long)]
+    id: u64,
+  },
+  /// Register the secrets a service needs, for verify and setup
+  Spec {
+    #[command(subcommand)]
+    action: SpecAction,
+  },
+  /// Check that every secret a service requires is stored
+  Verify {
+    /// Service (group) to check
+    service: String,
+  },
+  /// Prompt for every secret a service needs that is not stored yet
+  Setup {
+    /// Service (group) to set up
+    service: String,
+    /// Prompt for secrets that are already stored too
+    #[arg(short, long)]
+    force: bool,
+    #[command(flatten)]
+    mutation: MutationOptions,
   },
   /// Clear all secrets from the vault
   Clear {
     /// Skip confirmation prompt
     #[arg(long)]
     force: bool,
+    #[command(flatten)]
+    mutation: MutationOptions,
   },
   /// Daemon management commands
+  #[command(visible_alias = "keeper")]
   Agent {
     #[command(subcommand)]
     action: AgentAction,
@@ -92,40 +326,101 @@ pub enum Commands {
     /// Skip confirmation prompt
     #[arg(long)]
     force: bool,
+    #[command(flatten)]
+    mutation: MutationOptions,
   },
 }
 
 /// Handle a secrets command
-pub async fn handle_command(command: Commands) -> Result<()> {
-  // Auto-detect quiet mode if called as subprocess or if SECRETS_QUIET is set
-  let quiet_mode = env::var("SECRETS_QUIET").is_ok() || is_subprocess();
+pub async fn handle_command(command: Commands, output: OutputFormat, quiet: bool) -> Result<()> {
+  if output == OutputFormat::Html {
+    return Err(anyhow::anyhow!("secrets supports --output text or json, not html"));
+  }
+
+  // Auto-detect quiet mode if called as subprocess or if the quiet setting is on
+  let quiet_mode = quiet || quiet_setting() || is_subprocess();
 
   let secrets = Secrets::new();
 
   match command {
-    Commands::Store { name, value, group, force } => {
+    Commands::Store { name, value, group, options, expiry, mutation } => {
       let group = group.unwrap_or_else(|| "general".to_string());
-      commands::store(&secrets, &group, &name, value, force).await?;
+      commands::store(&secrets, &group, &name, value, options, expiry, mutation).await?;
     }
-    Commands::Read { name, group } => {
+    Commands::Read { name, group, raw } => {
       let group = group.unwrap_or_else(|| "general".to_string());
-      commands::read(&secrets, &group, &name).await?;
+      commands::read(&secrets, &group, &name, output, raw).await?;
     }
-    Commands::Delete { name, group, force } => {
+    Commands::Delete { name, group, force, mutation } => {
       let group = group.unwrap_or_else(|| "general".to_string());
-      commands::delete(&secrets, &group, Some(name), force).await?;
+      commands::delete(&secrets, &group, Some(name), force, mutation).await?;
+    }
+    Commands::ImportEnv { file, group, format, force, yes, mutation } => {
+      commands::import_env(&secrets, &file, &group, format, force, yes, mutation).await?;
     }
-    Commands::List { group, keys } => {
-      commands::list(&secrets, group, keys, quiet_mode).await?;
+    Commands::ExportEnv { group, format } => {
+      commands::export_env(&secrets, &group, format).await?;
     }
-    Commands::Clear { force } => {
-      commands::clear(&secrets, force, quiet_mode).await?;
+    Commands::Exec { group, command } => {
+      commands::exec(&secrets, &group, &command).await?;
+    }
+    Commands::Env { group, format, file, ttl, force, revoke } => match group {
+      Some(group) if !revoke => {
+        commands::env_snapshot(&secrets, &group, format, &file, ttl, force).await?;
+      }
+      _ => commands::revoke_env_snapshot(&file)?,
+    },
+    Commands::Totp { group, name, raw } => {
+      commands::totp(&secrets, &group, name.as_deref(), output, raw).await?;
+    }
+    Commands::List { group, keys, verbose } => {
+      commands::list(&secrets, group, keys, verbose, quiet_mode, output).await?;
+    }
+    Commands::CheckExpiry { within } => {
+      commands::check_expiry(&secrets, within).await?;
+    }
+    Commands::Prune { unused_for, yes, mutation } => {
+      commands::prune(&secrets, unused_for, yes, mutation).await?;
+    }
+    Commands::Export { output, force } => {
+      commands::export_vault(&secrets, &output, force).await?;
+    }
+    Commands::Import { file, force, yes, mutation } => {
+      commands::import_vault(&secrets, &file, force, yes, mutation).await?;
+    }
+    Commands::StoreKey { name, file, force, mutation } => {
+      commands::store_key(&secrets, &name, &file, force, mutation).await?;
+    }
+    Commands::WriteKey { name, to, ttl, agent, force } => {
+      commands::write_key(&secrets, &name, to.as_deref(), ttl, agent, force).await?;
+    }
+    Commands::RemoveKey { name, mutation } => {
+      commands::remove_key(&secrets, &name, mutation).await?;
+    }
+    Commands::ExpireKey { path, after, id } => {
+      crate::keys::remove_after(&path, after, id)?;
+    }
+    Commands::Spec { action } => match action {
+      SpecAction::Add { name, required, optional, description } => {
+        commands::spec_add(&name, description.as_deref(), &required, &optional)?;
+      }
+      SpecAction::List => commands::spec_list()?,
+      SpecAction::Remove { name } => commands::spec_remove(&name)?,
+    },
+    Commands::Verify { service } => {
+      commands::verify(&secrets, &service, output).await?;
+    }
+    Commands::Setup { service, force, mutation } => {
+      commands::setup(&secrets, &service, force, mutation).await?;
+    }
+    Commands::Clear { force, mutation } => {
+      commands::clear(&secrets, force, quiet_mode, mutation).await?;
     }
     Commands::Agent { action } => {
       handle_agent(action).await?;
     }
-    Commands::ResetPassword { force } => {
-      commands::reset_password(&secrets, force).await?;
+    Commands::ResetPassword { force, mutation } => {
+      commands::reset_password(&secrets, force, mutation).await?;
     }
   }
 
@@ -163,11 +458,38 @@ async fn handle_agent(action: AgentAction) -> Result<()> {
     AgentAction::Restart => {
       keeper_client::restart(&socket_path, &pid_file, &keeper_path).await?;
     }
+
+    AgentAction::Lock => {
+      keeper_client::lock(&socket_path).await?;
+    }
+
+    AgentAction::Unlock => {
+      let password = match env::var("SECRETS_AUTH") {
+        Ok(password) => password,
+        Err(_) => {
+          crate::encryption::EncryptionManager::prompt_for_password("enter master password:")?
+        }
+      };
+      keeper_client::unlock(&socket_path, &password).await?;
+      bentley::success!("keeper unlocked");
+    }
+
+    AgentAction::InstallService { unit_dir } => {
+      keeper_client::install_service(&socket_path, &base, unit_dir).await?;
+    }
   }
 
   Ok(())
 }
 
+/// Whether quiet mode is on: SECRETS_QUIET set to anything, or `secrets.quiet` true
+fn quiet_setting() -> bool {
+  match env::var(settings::QUIET.env) {
+    Ok(_) => true,
+    Err(_) => bentley::config::get(&settings::QUIET).unwrap_or(false),
+  }
+}
+
 /// Detect if we're running as a subprocess
 fn is_subprocess() -> bool {
   // Check if parent process is not a shell-like process
@@ -1,49 +1,161 @@
+use crate::cli::OutputFormat;
 use crate::Secrets;
 use anyhow::Result;
-use std::path::PathBuf;
+use serde::Serialize;
 
+use crate::envfile::{self, EnvFormat};
+use crate::exec::{self, Lease};
+use crate::expiry::{self, ExpiryOptions, SecretKind};
 use crate::keeper_client;
+use crate::totp::Totp;
+use crate::vaultfile::VaultLock;
+use crate::{audit, keys, snapshot, specs, usage, vault_path};
+use std::collections::{BTreeMap, BTreeSet};
 use std::io::Write;
 use std::path::Path;
+use zeroize::Zeroize;
+
+type Credentials = std::collections::HashMap<String, std::collections::HashMap<String, String>>;
+
+/// Flags shared by every command that modifies the vault
+#[derive(clap::Args, Debug, Clone, Copy, Default)]
+pub struct MutationOptions {
+  /// Show what would change (group/key names only) without touching the vault
+  #[arg(long)]
+  pub dry_run: bool,
+  /// Log each vault step (unlock, decrypt, mutate, encrypt, write)
+  #[arg(long)]
+  pub trace: bool,
+}
+
+impl MutationOptions {
+  /// Log a vault step when tracing is enabled. Never pass secret values here.
+  fn step(&self, step: &str, detail: &str) {
+    if self.trace {
+      bentley::debug!(&format!("{step}: {detail}"));
+    }
+  }
+
+  /// Report a planned change in dry-run mode
+  fn plan(&self, change: &str) {
+    bentley::info!(&format!("dry run: would {change}"));
+  }
+}
+
+/// Encrypt credentials and write them to the vault file, tracing each step
+fn write_vault(
+  credentials: &Credentials,
+  master_password: &str,
+  credentials_path: &Path,
+  opts: MutationOptions,
+) -> Result<()> {
+  use crate::PasswordBasedCredentialStore;
+
+  opts.step("encrypt", &format!("encrypting {} group(s)", credentials.len()));
+  let store = PasswordBasedCredentialStore::new(credentials, master_password)?;
+
+  opts.step("write", &format!("writing {}", credentials_path.display()));
+  store.save_to_file(credentials_path)?;
+  Ok(())
+}
+
+/// Lock the vault for a load-modify-save cycle. Dry runs never write, so they don't lock.
+///
+/// Take this after the master password: the keeper locks the vault too when serving secrets.
+fn lock_vault(credentials_path: &Path, opts: MutationOptions) -> Result<Option<VaultLock>> {
+  if opts.dry_run {
+    return Ok(None);
+  }
+  opts.step("lock", &format!("locking {}", credentials_path.display()));
+  crate::PasswordBasedCredentialStore::lock(credentials_path).map(Some)
+}
+
+/// Count secrets across all groups
+fn secret_count(credentials: &Credentials) -> usize {
+  usage::secret_groups(credentials).map(|(_, group)| group.len()).sum()
+}
+
+/// Count retrievals and save them; a failure here never fails the read itself
+fn record_reads(
+  credentials: &mut Credentials,
+  group: &str,
+  names: &[&str],
+  master_password: &str,
+  credentials_path: &Path,
+) {
+  let now = audit::now();
+  for name in names {
+    usage::record_use(credentials, group, name, now);
+  }
+  if let Err(e) = write_vault(credentials, master_password, credentials_path, Default::default()) {
+    bentley::warn!(&format!("failed to record secret usage: {e}"));
+  }
+}
+
+/// Flags for how `store` treats the value
+#[derive(clap::Args, Debug, Clone, Copy, Default)]
+pub struct StoreOptions {
+  /// Force overwrite existing secret
+  #[arg(short, long)]
+  pub force: bool,
+  /// The value is a TOTP seed (base32 or otpauth:// URI) for `secrets totp`
+  #[arg(long)]
+  pub totp: bool,
+}
+
+impl StoreOptions {
+  fn kind(&self) -> SecretKind {
+    if self.totp {
+      SecretKind::Totp
+    } else {
+      SecretKind::Plain
+    }
+  }
+}
 
 pub async fn store(
   _secrets: &Secrets,
   group: &str,
   name: &str,
   value: Option<String>,
-  force: bool,
+  store_options: StoreOptions,
+  expiry_options: ExpiryOptions,
+  opts: MutationOptions,
 ) -> Result<()> {
-  let secret_value = if let Some(val) = value {
-    val
-  } else {
-    let prompt = format!("Enter value for {group}/{name}: ");
-    crate::encryption::EncryptionManager::prompt_for_password(&prompt)?
+  usage::check_group(group)?;
+  let (force, kind) = (store_options.force, store_options.kind());
+
+  // A dry run never needs the value, so don't prompt for one
+  let secret_value = match value {
+    Some(val) => val,
+    None if opts.dry_run => String::new(),
+    None => {
+      let prompt = format!("Enter value for {group}/{name}: ");
+      crate::encryption::EncryptionManager::prompt_for_password(&prompt)?
+    }
   };
 
-  if secret_value.trim().is_empty() {
+  if !opts.dry_run && secret_value.trim().is_empty() {
     bentley::error!("Cannot store empty secret value");
     return Ok(());
   }
+  if kind == SecretKind::Totp && !secret_value.is_empty() {
+    Totp::parse(&secret_value)?;
+  }
 
   // Get master password once
+  opts.step("unlock", "retrieving master password");
   let master_password = get_master_password(_secrets).await?;
 
   // Load existing credentials or create new ones
-  let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
-    PathBuf::from(blizz_dir)
-  } else {
-    dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
-  };
-
-  let mut credentials_path = base_path;
-  credentials_path.push("persistent");
-  credentials_path.push("keeper");
-  credentials_path.push("credentials.enc");
+  let credentials_path = vault_path();
 
   // Load existing credentials or start with empty
+  let _lock = lock_vault(&credentials_path, opts)?;
   let mut all_credentials = if credentials_path.exists() {
     use crate::PasswordBasedCredentialStore;
     if let Some(store) = PasswordBasedCredentialStore::load_from_file(&credentials_path)? {
+      opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
       match store.decrypt_credentials(&master_password) {
         Ok(creds) => creds,
         Err(_) => {
@@ -55,48 +167,79 @@ pub async fn store(
       std::collections::HashMap::new()
     }
   } else {
+    opts.step("decrypt", "no vault file yet, starting empty");
     std::collections::HashMap::new()
   };
 
   // Check if secret already exists (now that we have the credentials loaded)
-  if !force {
-    if let Some(group_secrets) = all_credentials.get(group) {
-      if group_secrets.contains_key(name) {
-        bentley::warn!(&format!("Secret {group}/{name} already exists"));
-        bentley::info!("Use --force to overwrite existing secret");
-        return Ok(());
-      }
-    }
+  let exists = all_credentials.get(group).is_some_and(|secrets| secrets.contains_key(name));
+  if exists && !force {
+    bentley::warn!(&format!("Secret {group}/{name} already exists"));
+    bentley::info!("Use --force to overwrite existing secret");
+    return Ok(());
+  }
+
+  let operation = if exists { "overwrite" } else { "create" };
+  if opts.dry_run {
+    opts.plan(&format!("{operation} secret {group}/{name}"));
+    return Ok(());
   }
 
   // Add/update the secret
-  all_credentials
-    .entry(group.to_string())
-    .or_default()
-    .insert(name.to_string(), secret_value.trim().to_string());
+  opts.step("mutate", &format!("{operation} {group}/{name}"));
+  let now = audit::now();
+  put_secret(&mut all_credentials, group, name, &secret_value, now, &expiry_options, kind);
 
   // Save back to file
-  use crate::PasswordBasedCredentialStore;
-  let store = PasswordBasedCredentialStore::new(&all_credentials, &master_password)?;
-  store.save_to_file(&credentials_path)?;
+  write_vault(&all_credentials, &master_password, &credentials_path, opts)?;
 
   bentley::success!(&format!("Stored secret: {group}/{name}"));
+  if let Some(expires_at) = expiry::get(&all_credentials, group, name).and_then(|m| m.expires_at) {
+    bentley::info!(&format!("{group}/{name} {}", expiry::describe_expiry(expires_at, now)));
+  }
   Ok(())
 }
 
-/// Read a secret from the vault
-pub async fn read(secrets: &Secrets, group: &str, name: &str) -> Result<()> {
-  // Get the credentials file path
-  let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
-    PathBuf::from(blizz_dir)
-  } else {
-    dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
-  };
+/// Insert or replace a secret and record its store time, expiry and kind
+///
+/// The kind always follows the new value, so a plain overwrite of a TOTP seed clears it.
+fn put_secret(
+  credentials: &mut Credentials,
+  group: &str,
+  name: &str,
+  value: &str,
+  now: u64,
+  expiry_options: &ExpiryOptions,
+  kind: SecretKind,
+) {
+  credentials
+    .entry(group.to_string())
+    .or_default()
+    .insert(name.to_string(), value.trim().to_string());
+  usage::record_store(credentials, group, name, now);
+  expiry::record_store(credentials, group, name, now, expiry_options);
+  expiry::set_kind(credentials, group, name, kind);
+}
 
-  let mut credentials_path = base_path.clone();
-  credentials_path.push("persistent");
-  credentials_path.push("keeper");
-  credentials_path.push("credentials.enc");
+/// A secret as printed by `read --output json`
+#[derive(Debug, Serialize)]
+struct SecretJson<'a> {
+  group: &'a str,
+  name: &'a str,
+  value: &'a str,
+}
+
+/// Read a secret from the vault
+///
+/// `raw` prints the bare value with no trailing newline, for `$(secrets read ...)` and pipes.
+pub async fn read(
+  secrets: &Secrets,
+  group: &str,
+  name: &str,
+  output: OutputFormat,
+  raw: bool,
+) -> Result<()> {
+  let credentials_path = vault_path();
 
   // Check if credentials file exists
   if !credentials_path.exists() {
@@ -104,7 +247,11 @@ pub async fn read(secrets: &Secrets, group: &str, name: &str) -> Result<()> {
     std::process::exit(1);
   }
 
-  // Load the encrypted store from file
+  // Get master password using daemon integration
+  let master_password = get_master_password(secrets).await?;
+
+  // Load the encrypted store from file; reads are counted, so hold the lock until saved
+  let _lock = lock_vault(&credentials_path, Default::default())?;
   use crate::PasswordBasedCredentialStore;
   let store = match PasswordBasedCredentialStore::load_from_file(&credentials_path)? {
     Some(store) => store,
@@ -114,11 +261,8 @@ pub async fn read(secrets: &Secrets, group: &str, name: &str) -> Result<()> {
     }
   };
 
-  // Get master password using daemon integration
-  let master_password = get_master_password(secrets).await?;
-
   // Decrypt all credentials
-  let all_credentials = match store.decrypt_credentials(&master_password) {
+  let mut all_credentials = match store.decrypt_credentials(&master_password) {
     Ok(creds) => creds,
     Err(_) => {
       bentley::error!("invalid master password or corrupted data");
@@ -127,9 +271,16 @@ pub async fn read(secrets: &Secrets, group: &str, name: &str) -> Result<()> {
   };
 
   // Look for the specific secret
-  match all_credentials.get(group).and_then(|group_secrets| group_secrets.get(name)) {
+  let found = if usage::is_reserved(group) {
+    None
+  } else {
+    all_credentials.get(group).and_then(|group_secrets| group_secrets.get(name)).cloned()
+  };
+  match found {
     Some(value) => {
-      println!("{value}");
+      print!("{}", format_secret(group, name, &value, output, raw)?);
+      std::io::stdout().flush()?;
+      record_reads(&mut all_credentials, group, &[name], &master_password, &credentials_path);
     }
     None => {
       bentley::warn!(&format!("secret not found: {group}/{name}"));
@@ -140,23 +291,32 @@ pub async fn read(secrets: &Secrets, group: &str, name: &str) -> Result<()> {
   Ok(())
 }
 
+fn format_secret(
+  group: &str,
+  name: &str,
+  value: &str,
+  output: OutputFormat,
+  raw: bool,
+) -> Result<String> {
+  Ok(match (raw, output) {
+    (true, _) => value.to_string(),
+    (false, OutputFormat::Json) => {
+      format!("{}\n", serde_json::to_string(&SecretJson { group, name, value })?)
+    }
+    (false, _) => format!("{value}\n"),
+  })
+}
+
 pub async fn delete(
   secrets: &Secrets,
   group: &str,
   name: Option<String>,
   force: bool,
+  opts: MutationOptions,
 ) -> Result<()> {
-  // Get the credentials file path
-  let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
-    PathBuf::from(blizz_dir)
-  } else {
-    dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
-  };
+  usage::check_group(group)?;
 
-  let mut credentials_path = base_path.clone();
-  credentials_path.push("persistent");
-  credentials_path.push("keeper");
-  credentials_path.push("credentials.enc");
+  let credentials_path = vault_path();
 
   // Check if credentials file exists
   if !credentials_path.exists() {
@@ -165,9 +325,11 @@ pub async fn delete(
   }
 
   // Get master password using daemon integration
+  opts.step("unlock", "retrieving master password");
   let master_password = get_master_password(secrets).await?;
 
   // Load the encrypted store from file
+  let _lock = lock_vault(&credentials_path, opts)?;
   use crate::PasswordBasedCredentialStore;
   let store = match PasswordBasedCredentialStore::load_from_file(&credentials_path)? {
     Some(store) => store,
@@ -178,6 +340,7 @@ pub async fn delete(
   };
 
   // Decrypt all credentials
+  opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
   let mut all_credentials = match store.decrypt_credentials(&master_password) {
     Ok(creds) => creds,
     Err(_) => {
@@ -196,6 +359,11 @@ pub async fn delete(
       return Ok(());
     }
 
+    if opts.dry_run {
+      opts.plan(&format!("delete secret {group}/{name}"));
+      return Ok(());
+    }
+
     if !force {
       bentley::warn!(&format!("This will delete the secret: {group}/{name}"));
       let confirm =
@@ -207,6 +375,7 @@ pub async fn delete(
     }
 
     // Remove the secret
+    opts.step("mutate", &format!("delete {group}/{name}"));
     if let Some(group_secrets) = all_credentials.get_mut(group) {
       group_secrets.remove(&name);
 
@@ -215,10 +384,10 @@ pub async fn delete(
         all_credentials.remove(group);
       }
     }
+    usage::prune_orphans(&mut all_credentials);
 
     // Save updated credentials back to file
-    let updated_store = PasswordBasedCredentialStore::new(&all_credentials, &master_password)?;
-    updated_store.save_to_file(&credentials_path)?;
+    write_vault(&all_credentials, &master_password, &credentials_path, opts)?;
 
     bentley::success!(&format!("Deleted secret: {group}/{name}"));
   } else {
@@ -228,6 +397,12 @@ pub async fn delete(
       return Ok(());
     }
 
+    if opts.dry_run {
+      let keys = all_credentials[group].keys().map(|key| format!("{group}/{key}"));
+      opts.plan(&format!("delete group {group}: {}", keys.collect::<Vec<_>>().join(", ")));
+      return Ok(());
+    }
+
     if !force {
       bentley::warn!(&format!("This will delete ALL secrets for group: {group}"));
       let confirm =
@@ -242,11 +417,12 @@ pub async fn delete(
     let secret_count = all_credentials.get(group).map_or(0, |secrets| secrets.len());
 
     // Remove the entire group
+    opts.step("mutate", &format!("delete group {group}"));
     all_credentials.remove(group);
+    usage::prune_orphans(&mut all_credentials);
 
     // Save updated credentials back to file
-    let updated_store = PasswordBasedCredentialStore::new(&all_credentials, &master_password)?;
-    updated_store.save_to_file(&credentials_path)?;
+    write_vault(&all_credentials, &master_password, &credentials_path, opts)?;
 
     bentley::success!(&format!("Deleted {secret_count} secrets for group: {group}"));
   }
@@ -254,26 +430,82 @@ pub async fn delete(
   Ok(())
 }
 
+/// Vault contents as printed by `list --output json`
+#[derive(Debug, Default, Serialize)]
+struct ListJson {
+  groups: Vec<GroupJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct GroupJson {
+  group: String,
+  count: usize,
+  /// Only with --keys
+  #[serde(skip_serializing_if = "Option::is_none")]
+  keys: Option<Vec<KeyJson>>,
+}
+
+#[derive(Debug, Serialize)]
+struct KeyJson {
+  name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  expires_at: Option<u64>,
+  /// Only with --verbose
+  #[serde(skip_serializing_if = "Option::is_none")]
+  usage: Option<usage::Usage>,
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+  println!("{}", serde_json::to_string_pretty(value)?);
+  Ok(())
+}
+
+/// Build the JSON listing, sorted by group and key so output is stable for scripts
+fn list_json(
+  all_credentials: &Credentials,
+  credentials_to_show: &Credentials,
+  show_keys: bool,
+  verbose: bool,
+) -> ListJson {
+  let mut groups: Vec<GroupJson> = credentials_to_show
+    .iter()
+    .map(|(group, secrets_map)| {
+      let keys = show_keys.then(|| {
+        let mut names: Vec<&String> = secrets_map.keys().collect();
+        names.sort();
+        names
+          .into_iter()
+          .map(|key| KeyJson {
+            name: key.clone(),
+            expires_at: expiry::get(all_credentials, group, key).and_then(|meta| meta.expires_at),
+            usage: verbose.then(|| usage::get(all_credentials, group, key)).flatten(),
+          })
+          .collect()
+      });
+      GroupJson { group: group.clone(), count: secrets_map.len(), keys }
+    })
+    .collect();
+  groups.sort_by(|a, b| a.group.cmp(&b.group));
+  ListJson { groups }
+}
+
 pub async fn list(
   secrets: &Secrets,
   group_filter: Option<String>,
   show_keys: bool,
+  verbose: bool,
   quiet: bool,
+  output: OutputFormat,
 ) -> Result<()> {
-  // Get the credentials file path (same logic as PasswordBasedCryptoManager::new)
-  let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
-    PathBuf::from(blizz_dir)
-  } else {
-    dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
-  };
+  let json = output == OutputFormat::Json;
 
-  let mut credentials_path = base_path.clone();
-  credentials_path.push("persistent");
-  credentials_path.push("keeper");
-  credentials_path.push("credentials.enc");
+  let credentials_path = vault_path();
 
   // Check if credentials file exists
   if !credentials_path.exists() {
+    if json {
+      return print_json(&ListJson::default());
+    }
     bentley::info!("no secrets stored yet");
     return Ok(());
   }
@@ -283,6 +515,9 @@ pub async fn list(
   let store = match PasswordBasedCredentialStore::load_from_file(&credentials_path)? {
     Some(store) => store,
     None => {
+      if json {
+        return print_json(&ListJson::default());
+      }
       bentley::info!("no secrets found");
       return Ok(());
     }
@@ -294,26 +529,30 @@ pub async fn list(
   // Decrypt all credentials
   let all_credentials = match store.decrypt_credentials(&master_password) {
     Ok(creds) => creds,
+    Err(_) if json => return Err(anyhow::anyhow!("invalid master password or corrupted data")),
     Err(_) => {
       bentley::error!("invalid master password or corrupted data");
       return Ok(());
     }
   };
 
+  // Filter by group if specified
+  let filter_group = group_filter.clone();
+  let credentials_to_show: Credentials = usage::secret_groups(&all_credentials)
+    .filter(|(group, _)| group_filter.as_ref().is_none_or(|filter| *group == filter))
+    .map(|(group, secrets)| (group.clone(), secrets.clone()))
+    .collect();
+
+  if json {
+    return print_json(&list_json(&all_credentials, &credentials_to_show, show_keys, verbose));
+  }
+
   // Display the contents
-  if all_credentials.is_empty() {
+  if secret_count(&all_credentials) == 0 {
     bentley::info!("vault is empty");
     return Ok(());
   }
 
-  // Filter by group if specified
-  let filter_group = group_filter.clone();
-  let credentials_to_show = if let Some(filter) = group_filter {
-    all_credentials.into_iter().filter(|(group, _)| group == &filter).collect()
-  } else {
-    all_credentials
-  };
-
   if credentials_to_show.is_empty() {
     if let Some(filter) = filter_group {
       bentley::info!(&format!("no secrets found for group: {filter}"));
@@ -323,13 +562,29 @@ pub async fn list(
     return Ok(());
   }
 
+  let now = audit::now();
+  let expiring: Vec<_> = expiry::expiring(&all_credentials, now, expiry::WARNING_PERIOD)
+    .into_iter()
+    .filter(|entry| credentials_to_show.contains_key(&entry.group))
+    .collect();
+
   // Display format depends on show_keys flag
   if show_keys {
     // Show detailed view with group/key pairs
     for (group, secrets_map) in credentials_to_show {
       bentley::info!(&format!("\n{group}/"));
       for key in secrets_map.keys() {
-        bentley::info!(&format!("   {group}/{key}"));
+        let mut line = format!("   {group}/{key}");
+        if verbose {
+          let stats = describe_usage(usage::get(&all_credentials, &group, key), now);
+          line.push_str(&format!("  ({stats})"));
+        }
+        match expiring.iter().find(|e| e.group == group && e.name == *key) {
+          Some(entry) => {
+            bentley::warn!(&format!("{line}  {}", expiry::describe_expiry(entry.expires_at, now)))
+          }
+          None => bentley::info!(&line),
+        }
       }
     }
   } else {
@@ -340,6 +595,12 @@ pub async fn list(
       bentley::info!(&format!("{group}: {count} {plural}"));
     }
 
+    if !expiring.is_empty() {
+      bentley::warn!(&format!(
+        "{} secret(s) expired or expiring soon; run 'secrets check-expiry'",
+        expiring.len()
+      ));
+    }
     if !quiet {
       bentley::info!("\nuse --keys to see individual secret names");
     }
@@ -348,12 +609,54 @@ pub async fn list(
   Ok(())
 }
 
-pub async fn clear(secrets: &Secrets, force: bool, quiet: bool) -> Result<()> {
-  bentley::warn!("this will DELETE ALL SECRETS from the vault");
-  bentley::warn!("this action cannot be undone!");
+/// Report secrets that have expired or will within `within` seconds
+///
+/// Fails when any secret has already expired, so it can run from cron or CI.
+pub async fn check_expiry(secrets: &Secrets, within: u64) -> Result<()> {
+  let credentials_path = vault_path();
+  if !credentials_path.exists() {
+    bentley::info!("no secrets stored yet");
+    return Ok(());
+  }
+
+  let master_password = get_master_password(secrets).await?;
+  let all_credentials = load_vault(&credentials_path, &master_password)?;
+
+  let now = audit::now();
+  let expiring = expiry::expiring(&all_credentials, now, within);
+  if expiring.is_empty() {
+    bentley::success!("no secrets expire within the requested period");
+    return Ok(());
+  }
+
+  for entry in &expiring {
+    let note = entry.note.as_ref().map(|note| format!(" - {note}")).unwrap_or_default();
+    let status = expiry::describe_expiry(entry.expires_at, now);
+    bentley::warn!(&format!("   {}/{} {status}{note}", entry.group, entry.name));
+  }
+
+  let expired = expiring.iter().filter(|entry| entry.is_expired(now)).count();
+  if expired > 0 {
+    return Err(anyhow::anyhow!(
+      "{expired} secret(s) have expired; rotate them with 'secrets store --force'"
+    ));
+  }
+  Ok(())
+}
+
+pub async fn clear(
+  secrets: &Secrets,
+  force: bool,
+  quiet: bool,
+  opts: MutationOptions,
+) -> Result<()> {
+  if !opts.dry_run {
+    bentley::warn!("this will DELETE ALL SECRETS from the vault");
+    bentley::warn!("this action cannot be undone!");
+  }
 
   // If not forced, ask for confirmation
-  if !force {
+  if !force && !opts.dry_run {
     bentley::info!("type 'yes' to confirm vault clearing:");
     print!("> ");
     std::io::stdout().flush()?;
@@ -366,27 +669,21 @@ pub async fn clear(secrets: &Secrets, force: bool, quiet: bool) -> Result<()> {
   }
 
   // Get master password using daemon integration for verification
+  opts.step("unlock", "retrieving master password");
   let master_password = get_master_password(secrets).await?;
 
-  // Try to verify the password by attempting to decrypt existing secrets
-  let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
-    PathBuf::from(blizz_dir)
-  } else {
-    dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
-  };
-
-  let mut credentials_path = base_path;
-  credentials_path.push("persistent");
-  credentials_path.push("keeper");
-  credentials_path.push("credentials.enc");
+  // Get the credentials file path (same logic as PasswordBasedCryptoManager::new)
+  let credentials_path = vault_path();
 
+  // Verify the password by decrypting existing secrets
+  let _lock = lock_vault(&credentials_path, opts)?;
+  let mut existing = Credentials::new();
   if credentials_path.exists() {
     use crate::PasswordBasedCredentialStore;
     if let Some(store) = PasswordBasedCredentialStore::load_from_file(&credentials_path)? {
+      opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
       match store.decrypt_credentials(&master_password) {
-        Ok(_) => {
-          // Password verified successfully
-        }
+        Ok(creds) => existing = creds,
         Err(_) => {
           bentley::error!("invalid master password - vault contents preserved");
           return Ok(());
@@ -395,48 +692,878 @@ pub async fn clear(secrets: &Secrets, force: bool, quiet: bool) -> Result<()> {
     }
   }
 
+  if opts.dry_run {
+    let mut groups: Vec<&String> = usage::secret_groups(&existing).map(|(g, _)| g).collect();
+    groups.sort();
+    opts.plan(&format!(
+      "delete {} secret(s) across {} group(s): {}",
+      secret_count(&existing),
+      groups.len(),
+      groups.iter().map(|g| g.as_str()).collect::<Vec<_>>().join(", ")
+    ));
+    return Ok(());
+  }
+
   bentley::verbose!("clearing vault...");
 
-  // Get the credentials file path (same logic as PasswordBasedCryptoManager::new)
-  let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
-    PathBuf::from(blizz_dir)
+  if credentials_path.exists() {
+    // Replace the vault with an empty encrypted store
+    opts.step("mutate", &format!("remove all {} secret(s)", secret_count(&existing)));
+    write_vault(&Credentials::new(), &master_password, &credentials_path, opts)?;
   } else {
-    dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
+    bentley::info!("no action taken - nothing to clear");
+  }
+
+  if !quiet {
+    bentley::success!("vault cleared");
+  }
+
+  Ok(())
+}
+
+/// Interactively delete secrets that have not been read within `unused_for` seconds
+pub async fn prune(
+  secrets: &Secrets,
+  unused_for: u64,
+  yes: bool,
+  opts: MutationOptions,
+) -> Result<()> {
+  let credentials_path = vault_path();
+  if !credentials_path.exists() {
+    bentley::info!("no secrets stored yet");
+    return Ok(());
+  }
+
+  opts.step("unlock", "retrieving master password");
+  let master_password = get_master_password(secrets).await?;
+  let _lock = lock_vault(&credentials_path, opts)?;
+  opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+
+  let now = audit::now();
+  let stale = usage::stale(&all_credentials, now, unused_for);
+  if stale.is_empty() {
+    bentley::info!("no stale secrets found");
+    return Ok(());
+  }
+
+  bentley::info!(&format!("{} secret(s) unused for the requested period:", stale.len()));
+  let mut to_delete = Vec::new();
+  for entry in &stale {
+    let label = format!("{}/{} ({})", entry.group, entry.name, describe_usage(entry.usage, now));
+    if opts.dry_run || yes {
+      bentley::info!(&format!("   {label}"));
+      to_delete.push(entry);
+      continue;
+    }
+    let answer =
+      crate::encryption::EncryptionManager::prompt_confirmation(&format!("Delete {label}? (y/N)"))?;
+    if matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+      to_delete.push(entry);
+    }
+  }
+
+  if to_delete.is_empty() {
+    bentley::info!("nothing deleted");
+    return Ok(());
+  }
+
+  if opts.dry_run {
+    opts.plan(&format!("delete {} stale secret(s)", to_delete.len()));
+    return Ok(());
+  }
+
+  opts.step("mutate", &format!("delete {} stale secret(s)", to_delete.len()));
+  for entry in &to_delete {
+    if let Some(group_secrets) = all_credentials.get_mut(&entry.group) {
+      group_secrets.remove(&entry.name);
+      if group_secrets.is_empty() {
+        all_credentials.remove(&entry.group);
+      }
+    }
+  }
+  usage::prune_orphans(&mut all_credentials);
+
+  write_vault(&all_credentials, &master_password, &credentials_path, opts)?;
+
+  let names: Vec<String> = to_delete.iter().map(|e| format!("{}/{}", e.group, e.name)).collect();
+  if let Err(e) = audit::record(&credentials_path, "prune", &names.join(", ")) {
+    bentley::warn!(&format!("failed to write audit log: {e}"));
+  }
+
+  bentley::success!(&format!("Deleted {} stale secret(s)", to_delete.len()));
+  Ok(())
+}
+
+/// One-line usage summary for `list --keys --verbose`
+fn describe_usage(record: Option<usage::Usage>, now: u64) -> String {
+  let Some(record) = record else {
+    return "not tracked yet".to_string();
   };
+  match record.last_used {
+    Some(at) => {
+      let times = if record.count == 1 { "time" } else { "times" };
+      format!("read {} {times}, last {}", record.count, usage::describe_age(at, now))
+    }
+    None => "never read".to_string(),
+  }
+}
+
+/// Decrypt the vault, treating a missing vault as empty
+fn load_vault(credentials_path: &Path, master_password: &str) -> Result<Credentials> {
+  use crate::PasswordBasedCredentialStore;
+
+  match PasswordBasedCredentialStore::load_from_file(credentials_path)? {
+    Some(store) => store
+      .decrypt_credentials(master_password)
+      .map_err(|_| anyhow::anyhow!("invalid master password or corrupted data")),
+    None => Ok(Credentials::new()),
+  }
+}
+
+/// Store every entry of a dotenv or JSON file under one group
+pub async fn import_env(
+  secrets: &Secrets,
+  file: &Path,
+  group: &str,
+  format: Option<EnvFormat>,
+  force: bool,
+  yes: bool,
+  opts: MutationOptions,
+) -> Result<()> {
+  usage::check_group(group)?;
+  let format = format.unwrap_or_else(|| EnvFormat::detect(file));
+  let content = std::fs::read_to_string(file)
+    .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", file.display()))?;
+  let entries = envfile::parse(&content, format)
+    .map_err(|e| anyhow::anyhow!("failed to parse {}: {e}", file.display()))?;
+
+  if entries.is_empty() {
+    bentley::info!(&format!("no entries found in {}", file.display()));
+    return Ok(());
+  }
+
+  opts.step("unlock", "retrieving master password");
+  let master_password = get_master_password(secrets).await?;
+
+  let credentials_path = vault_path();
+  let _lock = lock_vault(&credentials_path, opts)?;
+  opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+
+  // Preview names only, never values
+  let existing = all_credentials.get(group);
+  let mut to_store = Vec::new();
+  bentley::info!(&format!("importing {} entries into group {group}:", entries.len()));
+  for key in entries.keys() {
+    let exists = existing.is_some_and(|secrets| secrets.contains_key(key));
+    let action = match (exists, force) {
+      (false, _) => "new",
+      (true, true) => "overwrite",
+      (true, false) => "exists, skipped (use --force to overwrite)",
+    };
+    bentley::info!(&format!("   {group}/{key} ({action})"));
+    if !exists || force {
+      to_store.push(key);
+    }
+  }
+
+  if to_store.is_empty() {
+    bentley::info!("nothing to import");
+    return Ok(());
+  }
+
+  if opts.dry_run {
+    opts.plan(&format!("store {} secret(s) in group {group}", to_store.len()));
+    return Ok(());
+  }
+
+  if !yes {
+    let prompt = format!("Store {} secret(s) in {group}? Type 'yes' to confirm: ", to_store.len());
+    let confirm = crate::encryption::EncryptionManager::prompt_confirmation(&prompt)?;
+    if confirm.trim().to_lowercase() != "yes" {
+      bentley::info!("Cancelled");
+      return Ok(());
+    }
+  }
+
+  opts.step("mutate", &format!("store {} secret(s) in {group}", to_store.len()));
+  let group_secrets = all_credentials.entry(group.to_string()).or_default();
+  for key in &to_store {
+    group_secrets.insert(key.to_string(), entries[*key].clone());
+  }
+  let now = audit::now();
+  for key in &to_store {
+    usage::record_store(&mut all_credentials, group, key, now);
+    expiry::record_store(&mut all_credentials, group, key, now, &ExpiryOptions::default());
+  }
+
+  write_vault(&all_credentials, &master_password, &credentials_path, opts)?;
+
+  bentley::success!(&format!("Imported {} secret(s) into group: {group}", to_store.len()));
+  Ok(())
+}
+
+/// Write every secret in a group to stdout as dotenv or JSON
+pub async fn export_env(secrets: &Secrets, group: &str, format: EnvFormat) -> Result<()> {
+  let credentials_path = vault_path();
+  if !credentials_path.exists() {
+    return Err(anyhow::anyhow!("No secrets stored yet"));
+  }
+
+  usage::check_group(group)?;
+
+  let master_password = get_master_password(secrets).await?;
+  let _lock = lock_vault(&credentials_path, Default::default())?;
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
 
-  let mut credentials_path = base_path;
-  credentials_path.push("persistent");
-  credentials_path.push("keeper");
-  credentials_path.push("credentials.enc");
+  let group_secrets = all_credentials
+    .get(group)
+    .ok_or_else(|| anyhow::anyhow!("No secrets found for group: {group}"))?;
 
+  let entries: BTreeMap<String, String> =
+    group_secrets.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+  print!("{}", envfile::render(&entries, format)?);
+  std::io::stdout().flush()?;
+
+  let names: Vec<&str> = entries.keys().map(String::as_str).collect();
+  record_reads(&mut all_credentials, group, &names, &master_password, &credentials_path);
+
+  Ok(())
+}
+
+/// Write a group's secrets to an owner-only env file that is shredded after `ttl` seconds
+///
+/// Like a lease, a snapshot that cannot be audited is not written.
+pub async fn env_snapshot(
+  secrets: &Secrets,
+  group: &str,
+  format: EnvFormat,
+  output: &Path,
+  ttl: u64,
+  force: bool,
+) -> Result<()> {
+  let credentials_path = vault_path();
+  if !credentials_path.exists() {
+    return Err(anyhow::anyhow!("No secrets stored yet"));
+  }
+
+  usage::check_group(group)?;
+
+  let master_password = get_master_password(secrets).await?;
+  let _lock = lock_vault(&credentials_path, Default::default())?;
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+
+  let group_secrets = all_credentials
+    .get(group)
+    .ok_or_else(|| anyhow::anyhow!("No secrets found for group: {group}"))?;
+  let expires_at = audit::now() + ttl;
+  let mut content = snapshot::render(group, group_secrets, format, expires_at)?;
+  let names = snapshot::names(group, group_secrets);
+  let keys: Vec<String> = group_secrets.keys().cloned().collect();
+
+  let detail = format!("{group} -> {}: {}, for {ttl}s", output.display(), names.join(", "));
+  audit::record(&credentials_path, "snapshot", &detail)
+    .map_err(|e| anyhow::anyhow!("refusing to write snapshot: failed to write audit log: {e}"))?;
+  let written = keys::write(output, &content, force);
+  content.zeroize();
+  written?;
+  keys::schedule_removal(output, ttl)?;
+
+  let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+  record_reads(&mut all_credentials, group, &keys, &master_password, &credentials_path);
+
+  bentley::success!(&format!(
+    "Wrote {} secret(s) from {group} to {}, shredded in {ttl}s",
+    names.len(),
+    output.display()
+  ));
+  Ok(())
+}
+
+/// Shred an env snapshot before it expires
+pub fn revoke_env_snapshot(output: &Path) -> Result<()> {
+  snapshot::revoke(output)?;
+  let credentials_path = vault_path();
+  let detail = format!("revoked {}", output.display());
   if credentials_path.exists() {
-    // Create empty credentials structure
-    let empty_credentials = std::collections::HashMap::new();
+    if let Err(e) = audit::record(&credentials_path, "snapshot", &detail) {
+      bentley::warn!(&format!("failed to write audit log: {e}"));
+    }
+  }
+  bentley::success!(&format!("Shredded {}", output.display()));
+  Ok(())
+}
 
-    // Create a new encrypted store with empty credentials
-    use crate::PasswordBasedCredentialStore;
-    let empty_store = PasswordBasedCredentialStore::new(&empty_credentials, &master_password)?;
-    empty_store.save_to_file(&credentials_path)?;
+/// Lease a group's secrets to one command as environment variables
+///
+/// The vault is unlocked again before the command starts, so it can use `secrets` itself.
+/// A lease that cannot be audited is not granted. Exits with the command's status.
+pub async fn exec(secrets: &Secrets, group: &str, command: &[String]) -> Result<()> {
+  let (program, args) = exec::split_command(command)?;
+
+  let credentials_path = vault_path();
+  if !credentials_path.exists() {
+    return Err(anyhow::anyhow!("No secrets stored yet"));
+  }
+
+  usage::check_group(group)?;
+
+  let master_password = get_master_password(secrets).await?;
+  let lease = {
+    let _lock = lock_vault(&credentials_path, Default::default())?;
+    let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+
+    let group_secrets = all_credentials
+      .get(group)
+      .ok_or_else(|| anyhow::anyhow!("No secrets found for group: {group}"))?;
+    let lease = Lease::new(group, group_secrets);
+
+    let names: Vec<String> = group_secrets.keys().cloned().collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    record_reads(&mut all_credentials, group, &names, &master_password, &credentials_path);
+    lease
+  };
+
+  audit::record(&credentials_path, "lease", &lease.granted(program))
+    .map_err(|e| anyhow::anyhow!("refusing to lease secrets: failed to write audit log: {e}"))?;
+
+  let started = std::time::Instant::now();
+  let status = lease.run(program, args).await?;
+
+  if let Err(e) =
+    audit::record(&credentials_path, "lease", &lease.released(program, started, &status))
+  {
+    bentley::warn!(&format!("failed to write audit log: {e}"));
+  }
+  drop(lease);
+
+  if !status.success() {
+    std::process::exit(status.code().unwrap_or(1));
+  }
+  Ok(())
+}
+
+/// A one-time code as printed by `totp --output json`
+#[derive(Debug, Serialize)]
+struct CodeJson<'a> {
+  group: &'a str,
+  name: &'a str,
+  code: &'a str,
+  /// Seconds the code stays valid
+  remaining: u64,
+}
+
+/// Print the current one-time code from a TOTP seed in `group`
+///
+/// Without a name, the group must hold exactly one secret stored with `--totp`.
+pub async fn totp(
+  secrets: &Secrets,
+  group: &str,
+  name: Option<&str>,
+  output: OutputFormat,
+  raw: bool,
+) -> Result<()> {
+  let credentials_path = vault_path();
+  if !credentials_path.exists() {
+    return Err(anyhow::anyhow!("No secrets stored yet"));
+  }
+  usage::check_group(group)?;
+
+  let master_password = get_master_password(secrets).await?;
+  let _lock = lock_vault(&credentials_path, Default::default())?;
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+
+  let name = match name {
+    Some(name) => name.to_string(),
+    None => match expiry::of_kind(&all_credentials, group, SecretKind::Totp).as_slice() {
+      [name] => name.to_string(),
+      [] => {
+        return Err(anyhow::anyhow!(
+          "no TOTP seeds in group {group}; store one with `secrets store <name> --group {group} --totp`"
+        ))
+      }
+      names => {
+        return Err(anyhow::anyhow!(
+          "group {group} has several TOTP seeds ({}); pick one with --name",
+          names.join(", ")
+        ))
+      }
+    },
+  };
+  let seed = all_credentials
+    .get(group)
+    .and_then(|secrets| secrets.get(&name))
+    .ok_or_else(|| anyhow::anyhow!("secret not found: {group}/{name}"))?;
+  let totp = Totp::parse(seed).map_err(|e| anyhow::anyhow!("{group}/{name}: {e}"))?;
+
+  let now = audit::now();
+  let code = totp.code_at(now);
+  let remaining = totp.remaining(now);
+  let printed = match (raw, output) {
+    (true, _) => code.clone(),
+    (false, OutputFormat::Json) => {
+      let json = CodeJson { group, name: &name, code: &code, remaining };
+      format!("{}\n", serde_json::to_string(&json)?)
+    }
+    (false, _) => format!("{code} (valid for {remaining}s)\n"),
+  };
+  print!("{printed}");
+  std::io::stdout().flush()?;
+
+  record_reads(&mut all_credentials, group, &[&name], &master_password, &credentials_path);
+  Ok(())
+}
+
+/// Groups that move with an export: everything but this machine's usage counters
+///
+/// Expiry metadata describes the secrets themselves, so it travels with them.
+fn transferable(
+  credentials: &Credentials,
+) -> impl Iterator<Item = (&String, &std::collections::HashMap<String, String>)> {
+  credentials.iter().filter(|(group, _)| *group != usage::USAGE_GROUP)
+}
+
+/// Transfer passphrase from SECRETS_TRANSFER_PASSPHRASE, or prompted for
+fn transfer_passphrase(confirm: bool) -> Result<String> {
+  use crate::encryption::EncryptionManager;
+
+  if let Ok(passphrase) = std::env::var("SECRETS_TRANSFER_PASSPHRASE") {
+    return Ok(passphrase.trim().to_string());
+  }
+
+  let passphrase = EncryptionManager::prompt_for_password("enter transfer passphrase:")?;
+  if passphrase.is_empty() {
+    return Err(anyhow::anyhow!("transfer passphrase cannot be empty"));
+  }
+  if confirm
+    && EncryptionManager::prompt_for_password("confirm transfer passphrase:")? != passphrase
+  {
+    return Err(anyhow::anyhow!("passphrases do not match"));
+  }
+  Ok(passphrase)
+}
+
+/// Re-encrypt the whole vault under a transfer passphrase for another machine
+pub async fn export_vault(secrets: &Secrets, output: &Path, force: bool) -> Result<()> {
+  use crate::transfer::TransferFile;
+
+  let credentials_path = vault_path();
+  if !credentials_path.exists() {
+    return Err(anyhow::anyhow!("No secrets stored yet"));
+  }
+  if output.exists() && !force {
+    return Err(anyhow::anyhow!("{} already exists (use --force to overwrite)", output.display()));
+  }
+
+  let master_password = get_master_password(secrets).await?;
+  let credentials = load_vault(&credentials_path, &master_password)?;
+
+  // Usage counters describe this machine, so only secrets and keys travel
+  let exported: Credentials =
+    transferable(&credentials).map(|(name, group)| (name.clone(), group.clone())).collect();
+
+  let passphrase = transfer_passphrase(true)?;
+  TransferFile::seal(&exported, &passphrase, audit::now())?.save(output)?;
+
+  let secret_groups: Vec<_> =
+    transferable(&exported).filter(|(name, _)| *name != expiry::META_GROUP).collect();
+  bentley::success!(&format!(
+    "Exported {} secret(s) across {} group(s) to {}",
+    secret_groups.iter().map(|(_, group)| group.len()).sum::<usize>(),
+    secret_groups.len(),
+    output.display()
+  ));
+  bentley::info!("the export is only as strong as its passphrase; delete it once imported");
+  Ok(())
+}
+
+/// Merge an export made with `secrets export` into this machine's vault
+pub async fn import_vault(
+  secrets: &Secrets,
+  file: &Path,
+  force: bool,
+  yes: bool,
+  opts: MutationOptions,
+) -> Result<()> {
+  use crate::transfer::TransferFile;
+
+  let export = TransferFile::load(file)?;
+  let passphrase = transfer_passphrase(false)?;
+  opts.step("verify", &format!("verifying and decrypting {}", file.display()));
+  let imported = export.open(&passphrase)?;
+
+  opts.step("unlock", "retrieving master password");
+  let master_password = get_master_password(secrets).await?;
+
+  let credentials_path = vault_path();
+  let _lock = lock_vault(&credentials_path, opts)?;
+  opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+
+  // Preview names only, never values
+  let mut to_store: Vec<(&String, &String)> = Vec::new();
+  let sorted: BTreeMap<&String, BTreeSet<&String>> = transferable(&imported)
+    .filter(|(group, _)| *group != expiry::META_GROUP)
+    .map(|(group, entries)| (group, entries.keys().collect()))
+    .collect();
+  let total: usize = sorted.values().map(BTreeSet::len).sum();
+  bentley::info!(&format!("importing {total} secret(s):"));
+  for (group, keys) in &sorted {
+    let existing = all_credentials.get(*group);
+    for key in keys {
+      let exists = existing.is_some_and(|secrets| secrets.contains_key(*key));
+      let action = match (exists, force) {
+        (false, _) => "new",
+        (true, true) => "overwrite",
+        (true, false) => "exists, skipped (use --force to overwrite)",
+      };
+      bentley::info!(&format!("   {group}/{key} ({action})"));
+      if !exists || force {
+        to_store.push((group, key));
+      }
+    }
+  }
+
+  if to_store.is_empty() {
+    bentley::info!("nothing to import");
+    return Ok(());
+  }
+
+  if opts.dry_run {
+    opts.plan(&format!("store {} secret(s)", to_store.len()));
+    return Ok(());
+  }
+
+  if !yes {
+    let prompt = format!("Store {} secret(s)? Type 'yes' to confirm: ", to_store.len());
+    let confirm = crate::encryption::EncryptionManager::prompt_confirmation(&prompt)?;
+    if confirm.trim().to_lowercase() != "yes" {
+      bentley::info!("Cancelled");
+      return Ok(());
+    }
+  }
+
+  opts.step("mutate", &format!("store {} secret(s)", to_store.len()));
+  let now = audit::now();
+  for (group, key) in &to_store {
+    let value = imported[*group][*key].clone();
+    all_credentials.entry(group.to_string()).or_default().insert(key.to_string(), value);
+    usage::record_store(&mut all_credentials, group, key, now);
+    expiry::copy(&imported, &mut all_credentials, group, key);
+  }
+
+  write_vault(&all_credentials, &master_password, &credentials_path, opts)?;
+
+  bentley::success!(&format!("Imported {} secret(s) from {}", to_store.len(), file.display()));
+  Ok(())
+}
+
+/// Store a private key or certificate file in the vault
+pub async fn store_key(
+  secrets: &Secrets,
+  name: &str,
+  file: &Path,
+  force: bool,
+  opts: MutationOptions,
+) -> Result<()> {
+  keys::check_name(name)?;
+  let material = std::fs::read_to_string(file)
+    .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", file.display()))?;
+  keys::validate(&material)?;
+
+  opts.step("unlock", "retrieving master password");
+  let master_password = get_master_password(secrets).await?;
+
+  let credentials_path = vault_path();
+  let _lock = lock_vault(&credentials_path, opts)?;
+  opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+
+  let exists =
+    all_credentials.get(keys::KEYS_GROUP).is_some_and(|stored| stored.contains_key(name));
+  if exists && !force {
+    bentley::warn!(&format!("Key {name} already exists"));
+    bentley::info!("Use --force to overwrite existing key");
+    return Ok(());
+  }
+
+  let operation = if exists { "overwrite" } else { "create" };
+  if opts.dry_run {
+    opts.plan(&format!("{operation} key {name}"));
+    return Ok(());
+  }
+
+  opts.step("mutate", &format!("{operation} key {name}"));
+  all_credentials
+    .entry(keys::KEYS_GROUP.to_string())
+    .or_default()
+    .insert(name.to_string(), material);
+  usage::record_store(&mut all_credentials, keys::KEYS_GROUP, name, audit::now());
+
+  write_vault(&all_credentials, &master_password, &credentials_path, opts)?;
+
+  bentley::success!(&format!("Stored key: {name}"));
+  Ok(())
+}
+
+/// Write a stored key to a file and/or ssh-agent, optionally for a limited time
+pub async fn write_key(
+  secrets: &Secrets,
+  name: &str,
+  to: Option<&Path>,
+  ttl: Option<u64>,
+  agent: bool,
+  force: bool,
+) -> Result<()> {
+  if to.is_none() && !agent {
+    return Err(anyhow::anyhow!("nothing to do: pass --to <file>, --agent, or both"));
+  }
+  if agent && !keys::agent_available() {
+    return Err(anyhow::anyhow!("no ssh-agent available (SSH_AUTH_SOCK is not set)"));
+  }
+
+  let credentials_path = vault_path();
+  if !credentials_path.exists() {
+    return Err(anyhow::anyhow!("No secrets stored yet"));
+  }
+
+  let master_password = get_master_password(secrets).await?;
+  let _lock = lock_vault(&credentials_path, Default::default())?;
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+  let material = all_credentials
+    .get(keys::KEYS_GROUP)
+    .and_then(|stored| stored.get(name))
+    .cloned()
+    .ok_or_else(|| anyhow::anyhow!("no stored key named {name}"))?;
+
+  if let Some(path) = to {
+    keys::write(path, &material, force)?;
+    if let Some(ttl) = ttl {
+      keys::schedule_removal(path, ttl)?;
+    }
+    if agent {
+      keys::add_to_agent(path, ttl)?;
+    }
   } else {
-    bentley::info!("no action taken - nothing to clear");
+    // ssh-add only reads files, so hand it a private copy and remove it straight away
+    let path = std::env::temp_dir().join(format!("secrets-key-{:016x}", rand::random::<u64>()));
+    keys::write(&path, &material, false)?;
+    let added = keys::add_to_agent(&path, ttl);
+    std::fs::remove_file(&path)?;
+    added?;
   }
 
-  if !quiet {
-    bentley::success!("vault cleared");
+  record_reads(
+    &mut all_credentials,
+    keys::KEYS_GROUP,
+    &[name],
+    &master_password,
+    &credentials_path,
+  );
+
+  let lifetime = ttl.map_or_else(String::new, |ttl| format!(" for {ttl}s"));
+  if let Some(path) = to {
+    bentley::success!(&format!("Wrote key {name} to {}{lifetime}", path.display()));
+  }
+  if agent {
+    bentley::success!(&format!("Added key {name} to ssh-agent{lifetime}"));
+  }
+  Ok(())
+}
+
+/// Delete a stored key
+pub async fn remove_key(secrets: &Secrets, name: &str, opts: MutationOptions) -> Result<()> {
+  let credentials_path = vault_path();
+  if !credentials_path.exists() {
+    return Err(anyhow::anyhow!("No secrets stored yet"));
   }
 
+  opts.step("unlock", "retrieving master password");
+  let master_password = get_master_password(secrets).await?;
+  let _lock = lock_vault(&credentials_path, opts)?;
+  opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+
+  let exists =
+    all_credentials.get(keys::KEYS_GROUP).is_some_and(|stored| stored.contains_key(name));
+  if !exists {
+    return Err(anyhow::anyhow!("no stored key named {name}"));
+  }
+  if opts.dry_run {
+    opts.plan(&format!("delete key {name}"));
+    return Ok(());
+  }
+
+  opts.step("mutate", &format!("delete key {name}"));
+  if let Some(stored) = all_credentials.get_mut(keys::KEYS_GROUP) {
+    stored.remove(name);
+  }
+  usage::prune_orphans(&mut all_credentials);
+  write_vault(&all_credentials, &master_password, &credentials_path, opts)?;
+
+  bentley::success!(&format!("Deleted key: {name}"));
   Ok(())
 }
 
-/// Helper function to get master password, first trying daemon, then fallback to direct prompt
-async fn get_master_password(_secrets: &Secrets) -> Result<String> {
-  // Check if credentials file exists
-  let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
-    PathBuf::from(blizz_dir)
+/// Register the secrets a service needs
+pub fn spec_add(
+  name: &str,
+  description: Option<&str>,
+  required: &[String],
+  optional: &[String],
+) -> Result<()> {
+  let spec = specs::build(name, description, required, optional)?;
+  let count = spec.required_credentials.len();
+  let replaced = specs::add(&vault_path(), spec)?;
+  let verb = if replaced { "Updated" } else { "Registered" };
+  bentley::success!(&format!("{verb} spec for {name} ({count} secret(s))"));
+  Ok(())
+}
+
+/// Show registered and built-in service specs
+pub fn spec_list() -> Result<()> {
+  let registered = specs::load(&vault_path())?;
+  let builtins = ["github", "notion"].into_iter().filter(|name| !registered.contains_key(*name));
+  let all = registered
+    .values()
+    .cloned()
+    .map(|spec| (spec, ""))
+    .chain(builtins.filter_map(specs::builtin).map(|spec| (spec, " (built-in)")));
+
+  for (spec, origin) in all {
+    bentley::info!(&format!("{}{origin}: {}", spec.name, spec.description));
+    for cred in &spec.required_credentials {
+      let kind = if cred.is_required { "required" } else { "optional" };
+      bentley::info!(&format!("   {} ({kind})", cred.key));
+    }
+  }
+  Ok(())
+}
+
+/// Forget a registered service spec
+pub fn spec_remove(name: &str) -> Result<()> {
+  specs::remove(&vault_path(), name)?;
+  bentley::success!(&format!("Removed spec for {name}"));
+  Ok(())
+}
+
+/// Result of `verify --output json`
+#[derive(Debug, Serialize)]
+struct VerifyJson<'a> {
+  service: &'a str,
+  ok: bool,
+  missing: &'a [String],
+}
+
+/// Check that every secret a service requires is in the vault
+pub async fn verify(secrets: &Secrets, service: &str, output: OutputFormat) -> Result<()> {
+  let credentials_path = vault_path();
+  let spec = specs::resolve(&credentials_path, service)?;
+
+  let all_credentials = if credentials_path.exists() {
+    let master_password = get_master_password(secrets).await?;
+    load_vault(&credentials_path, &master_password)?
   } else {
-    dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
+    Credentials::new()
   };
 
+  let missing = specs::missing(&spec, &all_credentials);
+  match output {
+    OutputFormat::Json => {
+      print_json(&VerifyJson { service, ok: missing.is_empty(), missing: &missing })?
+    }
+    _ if missing.is_empty() => {
+      bentley::success!(&format!("{service}: all required secrets present"))
+    }
+    _ => {
+      for key in &missing {
+        bentley::warn!(&format!("   {service}/{key} missing"));
+      }
+    }
+  }
+
+  if missing.is_empty() {
+    return Ok(());
+  }
+  Err(anyhow::anyhow!(
+    "{service} is missing {} required secret(s); run 'secrets setup {service}'",
+    missing.len()
+  ))
+}
+
+/// Prompt for each secret a service needs and store them in one write
+pub async fn setup(
+  secrets: &Secrets,
+  service: &str,
+  force: bool,
+  opts: MutationOptions,
+) -> Result<()> {
+  let credentials_path = vault_path();
+  let spec = specs::resolve(&credentials_path, service)?;
+
+  opts.step("unlock", "retrieving master password");
+  let master_password = get_master_password(secrets).await?;
+  let _lock = lock_vault(&credentials_path, opts)?;
+  opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
+  let mut all_credentials = load_vault(&credentials_path, &master_password)?;
+
+  let existing = all_credentials.get(service);
+  let pending: Vec<_> = spec
+    .required_credentials
+    .iter()
+    .filter(|cred| force || !existing.is_some_and(|secrets| secrets.contains_key(&cred.key)))
+    .collect();
+
+  if pending.is_empty() {
+    bentley::info!(&format!("{service} is already set up (use --force to re-enter secrets)"));
+    return Ok(());
+  }
+
+  if opts.dry_run {
+    for cred in &pending {
+      bentley::info!(&format!("   {service}/{}", cred.key));
+    }
+    opts.plan(&format!("prompt for and store {} secret(s) in group {service}", pending.len()));
+    return Ok(());
+  }
+
+  bentley::info!(&format!("Setting up {}", spec.description));
+  let mut values = Vec::new();
+  for cred in pending {
+    let optional = if cred.is_required { "" } else { ", optional" };
+    let prompt = format!("{service}/{} ({}{optional}): ", cred.key, cred.description);
+    let value = crate::encryption::EncryptionManager::prompt_for_password(&prompt)?;
+    match value.trim() {
+      "" if cred.is_required => return Err(anyhow::anyhow!("{} cannot be empty", cred.key)),
+      "" => continue,
+      value => values.push((cred.key.clone(), value.to_string())),
+    }
+  }
+
+  if values.is_empty() {
+    bentley::info!("nothing to store");
+    return Ok(());
+  }
+
+  opts.step("mutate", &format!("store {} secret(s) in {service}", values.len()));
+  let now = audit::now();
+  for (key, value) in &values {
+    all_credentials.entry(service.to_string()).or_default().insert(key.clone(), value.clone());
+    usage::record_store(&mut all_credentials, service, key, now);
+    expiry::record_store(&mut all_credentials, service, key, now, &ExpiryOptions::default());
+  }
+
+  write_vault(&all_credentials, &master_password, &credentials_path, opts)?;
+
+  bentley::success!(&format!("Stored {} secret(s) for {service}", values.len()));
+  Ok(())
+}
+
+/// Helper function to get master password, first trying daemon, then fallback to direct prompt
+async fn get_master_password(_secrets: &Secrets) -> Result<String> {
+  let base_path = crate::base_path();
+
   // Existing vault - try to get password from daemon first
   match keeper_client::get(&base_path).await {
     Ok(password) => {
@@ -457,8 +1584,16 @@ async fn get_master_password(_secrets: &Secrets) -> Result<String> {
         Err(_) => {
           // Last resort - prompt directly
           bentley::verbose!("daemon unavailable, prompting directly");
-          let cred_path = base_path.join("persistent").join("keeper").join("credentials.enc");
+          let cred_path = vault_path();
           let password = crate::encryption::EncryptionManager::get_master_password(&cred_path)?;
+
+          // A running but locked keeper gets the password back so later calls skip the prompt
+          let socket_path = base_path.join("persistent").join("keeper").join("keeper.sock");
+          if socket_path.exists() {
+            if let Err(e) = keeper_client::unlock(&socket_path, &password).await {
+              bentley::verbose!(&format!("could not unlock daemon: {e}"));
+            }
+          }
           Ok(password)
         }
       }
@@ -484,29 +1619,22 @@ async fn start_daemon_if_needed(base_path: &Path) -> Result<()> {
 }
 
 /// Reset the master password for the vault
-pub async fn reset_password(secrets: &Secrets, force: bool) -> Result<()> {
+pub async fn reset_password(secrets: &Secrets, force: bool, opts: MutationOptions) -> Result<()> {
   bentley::verbose!("resetting master password...");
 
   // Get the current master password from the daemon
+  opts.step("unlock", "retrieving current master password");
   let current_password = get_master_password(secrets).await?;
 
   // Load current credentials store
-  let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
-    PathBuf::from(blizz_dir)
-  } else {
-    dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
-  };
-
-  let mut credentials_path = base_path;
-  credentials_path.push("persistent");
-  credentials_path.push("keeper");
-  credentials_path.push("credentials.enc");
+  let credentials_path = vault_path();
 
   if !credentials_path.exists() {
     return Err(anyhow::anyhow!("No vault exists to reset password for"));
   }
 
   // Load existing credentials with current password
+  let _lock = lock_vault(&credentials_path, opts)?;
   use crate::PasswordBasedCredentialStore;
   let existing_store = match PasswordBasedCredentialStore::load_from_file(&credentials_path)? {
     Some(store) => store,
@@ -516,6 +1644,7 @@ pub async fn reset_password(secrets: &Secrets, force: bool) -> Result<()> {
   };
 
   // Decrypt all credentials with current password
+  opts.step("decrypt", &format!("decrypting {}", credentials_path.display()));
   let credentials = match existing_store.decrypt_credentials(&current_password) {
     Ok(creds) => creds,
     Err(_) => {
@@ -523,9 +1652,18 @@ pub async fn reset_password(secrets: &Secrets, force: bool) -> Result<()> {
     }
   };
 
+  if opts.dry_run {
+    opts.plan(&format!(
+      "re-encrypt {} secret(s) across {} group(s) with a new master password",
+      secret_count(&credentials),
+      usage::secret_groups(&credentials).count()
+    ));
+    return Ok(());
+  }
+
   if !force {
     eprintln!("This will re-encrypt all secrets with a new master password.");
-    eprintln!("You currently have {} secret(s) stored.", credentials.len());
+    eprintln!("You currently have {} secret(s) stored.", secret_count(&credentials));
     eprint!("Are you sure you want to continue? (y/N): ");
     std::io::stdout().flush()?;
 
@@ -556,8 +1694,8 @@ pub async fn reset_password(secrets: &Secrets, force: bool) -> Result<()> {
   }
 
   // Create new encrypted store with new password
-  let new_store = PasswordBasedCredentialStore::new(&credentials, &new_password)?;
-  new_store.save_to_file(&credentials_path)?;
+  opts.step("mutate", "switching to the new master password");
+  write_vault(&credentials, &new_password, &credentials_path, opts)?;
 
   bentley::success!("master password reset successfully");
   bentley::info!("please restart the daemon for the new password to take effect");
@@ -569,6 +1707,7 @@ pub async fn reset_password(secrets: &Secrets, force: bool) -> Result<()> {
 mod tests {
   use super::*;
   use crate::Secrets;
+  use std::path::PathBuf;
   use tempfile::TempDir;
 
   // Mock keeper_client module for tests
@@ -712,17 +1851,83 @@ mod tests {
 
     // Test the early return path for empty values (line 23-26 in store function)
     // This should return Ok(()) without calling get_master_password
-    let result = store(&secrets, "test", "test", Some("   ".to_string()), false).await;
+    let result = store(
+      &secrets,
+      "test",
+      "test",
+      Some("   ".to_string()),
+      StoreOptions::default(),
+      ExpiryOptions::default(),
+      MutationOptions::default(),
+    )
+    .await;
     assert!(result.is_ok(), "Empty values should be handled gracefully");
   }
 
+  #[tokio::test]
+  async fn test_store_rejects_invalid_totp_seed() {
+    let temp_dir = setup_test_env();
+    let secrets = Secrets::new();
+
+    let result = store(
+      &secrets,
+      "github",
+      "otp",
+      Some("not base32!".to_string()),
+      StoreOptions { force: false, totp: true },
+      ExpiryOptions::default(),
+      MutationOptions::default(),
+    )
+    .await;
+    assert!(result.unwrap_err().to_string().contains("not base32"));
+    assert!(!temp_dir.path().join("persistent/keeper/credentials.enc").exists());
+  }
+
+  #[test]
+  fn test_put_secret_plain_over_totp_clears_kind() {
+    let mut credentials = Credentials::new();
+    let options = ExpiryOptions::default();
+    put_secret(
+      &mut credentials,
+      "github",
+      "otp",
+      "JBSWY3DPEHPK3PXP",
+      10,
+      &options,
+      SecretKind::Totp,
+    );
+    assert_eq!(expiry::kind(&credentials, "github", "otp"), SecretKind::Totp);
+
+    put_secret(
+      &mut credentials,
+      "github",
+      "otp",
+      "plain-password",
+      20,
+      &options,
+      SecretKind::Plain,
+    );
+    assert_eq!(credentials["github"]["otp"], "plain-password");
+    assert_eq!(expiry::kind(&credentials, "github", "otp"), SecretKind::Plain);
+    assert!(expiry::of_kind(&credentials, "github", SecretKind::Totp).is_empty());
+  }
+
   #[tokio::test]
   async fn test_store_whitespace_value_rejection() {
     let _temp_dir = setup_test_env();
     let secrets = Secrets::new();
 
     // Test the early return path for whitespace-only values
-    let result = store(&secrets, "test", "test", Some("\t\n\r ".to_string()), false).await;
+    let result = store(
+      &secrets,
+      "test",
+      "test",
+      Some("\t\n\r ".to_string()),
+      StoreOptions::default(),
+      ExpiryOptions::default(),
+      MutationOptions::default(),
+    )
+    .await;
     assert!(result.is_ok(), "Whitespace-only values should be handled gracefully");
   }
 
@@ -732,10 +1937,97 @@ mod tests {
     let secrets = Secrets::new();
 
     // Test mixed whitespace and special characters
-    let result = store(&secrets, "test", "test", Some("  \n\t  \r  ".to_string()), false).await;
+    let result = store(
+      &secrets,
+      "test",
+      "test",
+      Some("  \n\t  \r  ".to_string()),
+      StoreOptions::default(),
+      ExpiryOptions::default(),
+      MutationOptions::default(),
+    )
+    .await;
     assert!(result.is_ok(), "Mixed whitespace values should be handled gracefully");
   }
 
+  #[test]
+  fn test_write_vault_round_trips() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("credentials.enc");
+    let mut credentials = Credentials::new();
+    credentials.entry("github".to_string()).or_default().insert("token".into(), "abc".into());
+
+    let opts = MutationOptions { dry_run: false, trace: true };
+    write_vault(&credentials, "test_password_123", &path, opts).unwrap();
+
+    let store = crate::PasswordBasedCredentialStore::load_from_file(&path).unwrap().unwrap();
+    let decrypted = store.decrypt_credentials("test_password_123").unwrap();
+    assert_eq!(decrypted["github"]["token"], "abc");
+  }
+
+  #[test]
+  fn test_describe_usage() {
+    let day = 24 * 60 * 60;
+    let now = 10 * day;
+    assert_eq!(describe_usage(None, now), "not tracked yet");
+
+    let stored = usage::Usage { count: 0, last_used: None, stored_at: Some(day) };
+    assert_eq!(describe_usage(Some(stored), now), "never read");
+
+    let read = usage::Usage { count: 3, last_used: Some(7 * day), stored_at: Some(day) };
+    assert_eq!(describe_usage(Some(read), now), "read 3 times, last 3d ago");
+  }
+
+  #[test]
+  fn test_secret_count_spans_groups() {
+    let mut credentials = Credentials::new();
+    credentials.entry("a".to_string()).or_default().insert("one".into(), "1".into());
+    credentials.entry("a".to_string()).or_default().insert("two".into(), "2".into());
+    credentials.entry("b".to_string()).or_default().insert("three".into(), "3".into());
+
+    assert_eq!(secret_count(&credentials), 3);
+    assert_eq!(secret_count(&Credentials::new()), 0);
+
+    // Usage records are not secrets
+    usage::record_use(&mut credentials, "a", "one", 1);
+    assert_eq!(secret_count(&credentials), 3);
+  }
+
+  #[test]
+  fn test_format_secret() {
+    let text = format_secret("github", "token", "abc", OutputFormat::Text, false).unwrap();
+    assert_eq!(text, "abc\n");
+
+    let raw = format_secret("github", "token", "abc", OutputFormat::Json, true).unwrap();
+    assert_eq!(raw, "abc");
+
+    let json = format_secret("github", "token", "a\"b", OutputFormat::Json, false).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, serde_json::json!({"group": "github", "name": "token", "value": "a\"b"}));
+  }
+
+  #[test]
+  fn test_list_json_shows_keys_and_usage_only_when_asked() {
+    let mut credentials = Credentials::new();
+    credentials.entry("b".to_string()).or_default().insert("two".into(), "2".into());
+    credentials.entry("a".to_string()).or_default().insert("one".into(), "1".into());
+    usage::record_use(&mut credentials, "a", "one", 5);
+    let shown: Credentials = usage::secret_groups(&credentials)
+      .map(|(group, secrets)| (group.clone(), secrets.clone()))
+      .collect();
+
+    let summary = serde_json::to_value(list_json(&credentials, &shown, false, false)).unwrap();
+    assert_eq!(
+      summary,
+      serde_json::json!({"groups": [{"group": "a", "count": 1}, {"group": "b", "count": 1}]})
+    );
+
+    let detailed = serde_json::to_value(list_json(&credentials, &shown, true, true)).unwrap();
+    assert_eq!(detailed["groups"][0]["keys"][0]["name"], "one");
+    assert_eq!(detailed["groups"][0]["keys"][0]["usage"]["count"], 1);
+    assert!(detailed["groups"][1]["keys"][0].get("usage").is_none());
+  }
+
   #[tokio::test]
   async fn test_get_master_password_mock_daemon_starts_successfully() {
     let _temp_dir = setup_test_env();
@@ -18,6 +18,8 @@ use std::fs;
 use std::path::Path;
 use uuid::Uuid;
 
+use crate::lockout;
+
 /// Encrypted credential blob stored on disk
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EncryptedBlob {
@@ -357,7 +359,7 @@ impl EncryptionManager {
     Ok(master_password)
   }
 
-  /// Verify password against stored credentials
+  /// Verify password against stored credentials, enforcing the failed-attempt lockout
   pub fn verify_password(cred_path: &Path, master_password: &str) -> Result<()> {
     let data = fs::read_to_string(cred_path)?;
     let store_json: Value = serde_json::from_str(data.trim())?;
@@ -366,11 +368,11 @@ impl EncryptionManager {
       .ok_or_else(|| anyhow!("invalid vault format: missing 'encrypted_data'"))?;
     let blob: EncryptedBlob = serde_json::from_value(blob_val.clone())?;
 
-    if let Err(e) = Self::decrypt_credentials(&blob, master_password.trim()) {
-      return Err(anyhow!("incorrect password: {e}"));
-    }
-
-    Ok(())
+    lockout::attempt(cred_path, || {
+      Self::decrypt_credentials(&blob, master_password.trim())
+        .map(drop)
+        .map_err(|e| anyhow!("incorrect password: {e}"))
+    })
   }
 
   /// Create new vault with password confirmation
@@ -393,7 +395,7 @@ impl EncryptionManager {
     if let Some(parent) = cred_path.parent() {
       fs::create_dir_all(parent)?;
     }
-    store.save_to_file(&cred_path.to_path_buf())?;
+    store.save_to_file(cred_path)?;
 
     bentley::success!("vault created successfully");
     Ok(password1.trim().to_string())
@@ -493,6 +495,31 @@ mod tests {
     });
   }
 
+  #[test]
+  fn test_verify_password_locks_out_after_repeated_failures() {
+    use crate::PasswordBasedCredentialStore;
+    use std::collections::HashMap;
+
+    with_temp_dir(|temp_dir| {
+      let vault_path = temp_dir.path().join("test_vault.enc");
+      let correct_password = "correct_password_456";
+
+      let store = PasswordBasedCredentialStore::new(&HashMap::new(), correct_password).unwrap();
+      store.save_to_file(&vault_path).unwrap();
+
+      for _ in 0..lockout::FREE_ATTEMPTS {
+        let result = EncryptionManager::verify_password(&vault_path, "wrong_password");
+        assert!(result.unwrap_err().to_string().contains("incorrect password"));
+      }
+
+      // Even the right password is refused until the delay has passed
+      let result = EncryptionManager::verify_password(&vault_path, correct_password);
+      let error_msg = result.unwrap_err().to_string();
+      assert!(error_msg.contains("too many failed attempts"), "got: {error_msg}");
+      assert_eq!(lockout::load(&vault_path).unwrap().failures, lockout::FREE_ATTEMPTS);
+    });
+  }
+
   #[test]
   fn test_verify_password_invalid_vault_format() {
     with_temp_dir(|temp_dir| {
@@ -0,0 +1,229 @@
+//! Reading and writing key/value files for batch import and export
+//!
+//! Supports dotenv (`KEY=value`, optional `export`, quotes and comments) and flat
+//! JSON objects. Entries are kept in key order so previews and exports are stable.
+
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Key/value file formats understood by import and export
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvFormat {
+  Dotenv,
+  Json,
+}
+
+impl EnvFormat {
+  /// Guess the format from a file extension, defaulting to dotenv
+  pub fn detect(path: &Path) -> Self {
+    match path.extension().and_then(|ext| ext.to_str()) {
+      Some(ext) if ext.eq_ignore_ascii_case("json") => EnvFormat::Json,
+      _ => EnvFormat::Dotenv,
+    }
+  }
+}
+
+/// Parse file content in the given format
+pub fn parse(content: &str, format: EnvFormat) -> Result<BTreeMap<String, String>> {
+  match format {
+    EnvFormat::Dotenv => parse_dotenv(content),
+    EnvFormat::Json => parse_json(content),
+  }
+}
+
+/// Render entries in the given format
+pub fn render(entries: &BTreeMap<String, String>, format: EnvFormat) -> Result<String> {
+  match format {
+    EnvFormat::Dotenv => Ok(render_dotenv(entries)),
+    EnvFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(entries)?)),
+  }
+}
+
+fn parse_dotenv(content: &str) -> Result<BTreeMap<String, String>> {
+  let mut entries = BTreeMap::new();
+
+  for (index, raw) in content.lines().enumerate() {
+    let line = raw.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (key, value) =
+      line.split_once('=').ok_or_else(|| anyhow!("line {}: expected KEY=value", index + 1))?;
+
+    let key = key.trim();
+    if !is_valid_key(key) {
+      return Err(anyhow!("line {}: invalid key '{key}'", index + 1));
+    }
+
+    let value = parse_dotenv_value(value.trim()).map_err(|e| anyhow!("line {}: {e}", index + 1))?;
+    entries.insert(key.to_string(), value);
+  }
+
+  Ok(entries)
+}
+
+fn parse_dotenv_value(value: &str) -> Result<String> {
+  if let Some(rest) = value.strip_prefix('"') {
+    let end = closing_quote(rest).ok_or_else(|| anyhow!("unterminated double quote"))?;
+    return Ok(unescape(&rest[..end]));
+  }
+
+  if let Some(rest) = value.strip_prefix('\'') {
+    let end = rest.find('\'').ok_or_else(|| anyhow!("unterminated single quote"))?;
+    return Ok(rest[..end].to_string());
+  }
+
+  // Unquoted values end at an inline comment
+  let value = match value.find(" #") {
+    Some(index) => &value[..index],
+    None => value,
+  };
+  Ok(value.trim().to_string())
+}
+
+/// Index of the first unescaped double quote
+fn closing_quote(text: &str) -> Option<usize> {
+  let mut escaped = false;
+  for (index, c) in text.char_indices() {
+    match c {
+      '\\' if !escaped => escaped = true,
+      '"' if !escaped => return Some(index),
+      _ => escaped = false,
+    }
+  }
+  None
+}
+
+fn unescape(text: &str) -> String {
+  let mut result = String::with_capacity(text.len());
+  let mut chars = text.chars();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      result.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('n') => result.push('\n'),
+      Some('t') => result.push('\t'),
+      Some(other) => result.push(other),
+      None => result.push('\\'),
+    }
+  }
+  result
+}
+
+fn parse_json(content: &str) -> Result<BTreeMap<String, String>> {
+  let value: serde_json::Value = serde_json::from_str(content)?;
+  let object =
+    value.as_object().ok_or_else(|| anyhow!("expected a JSON object of key/value pairs"))?;
+
+  let mut entries = BTreeMap::new();
+  for (key, value) in object {
+    let value = match value {
+      serde_json::Value::String(s) => s.clone(),
+      serde_json::Value::Number(n) => n.to_string(),
+      serde_json::Value::Bool(b) => b.to_string(),
+      _ => return Err(anyhow!("value for '{key}' must be a string, number or boolean")),
+    };
+    entries.insert(key.clone(), value);
+  }
+
+  Ok(entries)
+}
+
+fn render_dotenv(entries: &BTreeMap<String, String>) -> String {
+  entries.iter().map(|(key, value)| format!("{key}={}\n", quote_dotenv(value))).collect()
+}
+
+fn quote_dotenv(value: &str) -> String {
+  let plain =
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "_-./:@+,".contains(c));
+  if plain {
+    return value.to_string();
+  }
+
+  let escaped =
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t");
+  format!("\"{escaped}\"")
+}
+
+fn is_valid_key(key: &str) -> bool {
+  let mut chars = key.chars();
+  matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+    && chars.all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_dotenv_basics() {
+    let content = r#"
+# comment
+API_KEY=abc123
+export REGION = us-east-1
+QUOTED="hello world" # trailing
+SINGLE='literal $HOME'
+ESCAPED="line1\nline2 \"q\""
+INLINE=value # comment
+"#;
+    let entries = parse(content, EnvFormat::Dotenv).unwrap();
+
+    assert_eq!(entries["API_KEY"], "abc123");
+    assert_eq!(entries["REGION"], "us-east-1");
+    assert_eq!(entries["QUOTED"], "hello world");
+    assert_eq!(entries["SINGLE"], "literal $HOME");
+    assert_eq!(entries["ESCAPED"], "line1\nline2 \"q\"");
+    assert_eq!(entries["INLINE"], "value");
+  }
+
+  #[test]
+  fn test_parse_dotenv_rejects_malformed_lines() {
+    assert!(parse("NOT A PAIR", EnvFormat::Dotenv).is_err());
+    assert!(parse("1BAD=x", EnvFormat::Dotenv).is_err());
+    assert!(parse("OPEN=\"never closed", EnvFormat::Dotenv).is_err());
+  }
+
+  #[test]
+  fn test_parse_json() {
+    let entries =
+      parse(r#"{"token": "abc", "port": 8080, "debug": true}"#, EnvFormat::Json).unwrap();
+    assert_eq!(entries["token"], "abc");
+    assert_eq!(entries["port"], "8080");
+    assert_eq!(entries["debug"], "true");
+
+    assert!(parse(r#"{"nested": {"a": 1}}"#, EnvFormat::Json).is_err());
+    assert!(parse(r#"["a"]"#, EnvFormat::Json).is_err());
+  }
+
+  #[test]
+  fn test_dotenv_round_trip() {
+    let mut entries = BTreeMap::new();
+    entries.insert("PLAIN".to_string(), "abc-123".to_string());
+    entries.insert("SPACES".to_string(), "hello world".to_string());
+    entries.insert("TRICKY".to_string(), "quote \" slash \\ newline \n".to_string());
+    entries.insert("EMPTY".to_string(), String::new());
+
+    let rendered = render(&entries, EnvFormat::Dotenv).unwrap();
+    assert_eq!(parse(&rendered, EnvFormat::Dotenv).unwrap(), entries);
+  }
+
+  #[test]
+  fn test_json_round_trip() {
+    let mut entries = BTreeMap::new();
+    entries.insert("a".to_string(), "1".to_string());
+    let rendered = render(&entries, EnvFormat::Json).unwrap();
+    assert_eq!(parse(&rendered, EnvFormat::Json).unwrap(), entries);
+  }
+
+  #[test]
+  fn test_detect_format() {
+    assert_eq!(EnvFormat::detect(Path::new("creds.json")), EnvFormat::Json);
+    assert_eq!(EnvFormat::detect(Path::new(".env")), EnvFormat::Dotenv);
+    assert_eq!(EnvFormat::detect(Path::new("service.env")), EnvFormat::Dotenv);
+  }
+}
@@ -0,0 +1,122 @@
+//! Time-limited leases of a group's secrets to a child process
+//!
+//! `secrets exec --group github -- cmd args...` runs one command with the group's
+//! secrets as environment variables. The values go from the decrypted vault straight
+//! into the child's environment: they are never written to disk or set in this
+//! process's own environment, and our copies are wiped once the child exits. The
+//! lease lasts exactly as long as the child, and both ends of it are audited.
+
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::process::ExitStatus;
+use std::time::Instant;
+use zeroize::Zeroize;
+
+/// Environment variable a secret is exposed as, e.g. `GITHUB_TOKEN` for github/token
+pub fn env_name(group: &str, key: &str) -> String {
+  format!("{group}_{key}")
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+    .collect()
+}
+
+/// A group's secrets, held only for the lifetime of one child process
+pub struct Lease {
+  group: String,
+  vars: BTreeMap<String, String>,
+}
+
+impl Lease {
+  /// Lease every secret of `group`
+  pub fn new(group: &str, secrets: &HashMap<String, String>) -> Self {
+    let vars = secrets.iter().map(|(key, value)| (env_name(group, key), value.clone())).collect();
+    Self { group: group.to_string(), vars }
+  }
+
+  /// Names of the variables the child receives, in order
+  pub fn names(&self) -> Vec<&str> {
+    self.vars.keys().map(String::as_str).collect()
+  }
+
+  /// Run `command` with the leased variables and wait for it to exit
+  ///
+  /// Ctrl-C reaches the child on its own; this process waits for the child instead of
+  /// dying first, so the end of the lease is always recorded.
+  pub async fn run(&self, command: &str, args: &[String]) -> Result<ExitStatus> {
+    let mut child = tokio::process::Command::new(command)
+      .args(args)
+      .envs(&self.vars)
+      .spawn()
+      .with_context(|| format!("failed to start '{command}'"))?;
+
+    loop {
+      tokio::select! {
+        status = child.wait() => return Ok(status?),
+        _ = tokio::signal::ctrl_c() => continue,
+      }
+    }
+  }
+
+  /// Audit log line for the start of the lease; names only, never values
+  pub fn granted(&self, command: &str) -> String {
+    format!("{} -> {command}: {}", self.group, self.names().join(", "))
+  }
+
+  /// Audit log line for the end of the lease
+  pub fn released(&self, command: &str, started: Instant, status: &ExitStatus) -> String {
+    let outcome = match status.code() {
+      Some(code) => format!("exit code {code}"),
+      None => "killed by a signal".to_string(),
+    };
+    format!(
+      "{} -> {command}: released after {}s, {outcome}",
+      self.group,
+      started.elapsed().as_secs()
+    )
+  }
+}
+
+impl Drop for Lease {
+  fn drop(&mut self) {
+    for value in self.vars.values_mut() {
+      value.zeroize();
+    }
+  }
+}
+
+/// Split `cmd args...` after `--` into the program and its arguments
+pub fn split_command(command: &[String]) -> Result<(&str, &[String])> {
+  match command.split_first() {
+    Some((program, args)) => Ok((program.as_str(), args)),
+    None => {
+      Err(anyhow!("no command given; usage: secrets exec --group <group> -- <cmd> [args...]"))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_env_name_is_a_valid_variable() {
+    assert_eq!(env_name("github", "token"), "GITHUB_TOKEN");
+    assert_eq!(env_name("my-api", "client.secret"), "MY_API_CLIENT_SECRET");
+  }
+
+  #[tokio::test]
+  async fn test_lease_reaches_the_child_only() {
+    let secrets = HashMap::from([("token".to_string(), "s3cret".to_string())]);
+    let lease = Lease::new("lease-test", &secrets);
+    assert_eq!(lease.names(), ["LEASE_TEST_TOKEN"]);
+    assert!(!lease.granted("sh").contains("s3cret"));
+
+    let script = "test \"$LEASE_TEST_TOKEN\" = s3cret".to_string();
+    let status = lease.run("sh", &["-c".to_string(), script]).await.unwrap();
+    assert!(status.success());
+    assert!(std::env::var("LEASE_TEST_TOKEN").is_err());
+
+    let status = lease.run("sh", &["-c".to_string(), "exit 3".to_string()]).await.unwrap();
+    assert!(lease.released("sh", Instant::now(), &status).ends_with("exit code 3"));
+  }
+}
@@ -0,0 +1,289 @@
+//! Expiry metadata for secrets, kept inside the encrypted vault
+//!
+//! Like usage records, metadata lives in a reserved group of the decrypted
+//! credentials map keyed `<group>/<name>`, so it is encrypted with the secrets
+//! and travels with them in exports. Vaults written before metadata existed need
+//! no migration: a secret without a record takes its creation time from its
+//! usage record, and gets a full record the next time it is stored.
+//!
+//! Storing a secret again is treated as rotating it: the creation time resets,
+//! and the previous lifetime and note carry over unless new ones are given. The
+//! record also says what kind of secret the value is, e.g. a TOTP seed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::usage;
+
+/// Group holding metadata records (never a real secret group)
+pub const META_GROUP: &str = ".meta";
+
+/// How far ahead `list` warns about expiring secrets
+pub const WARNING_PERIOD: u64 = 14 * 24 * 60 * 60;
+
+type Credentials = HashMap<String, HashMap<String, String>>;
+
+/// What a stored value is, for commands that treat some secrets specially
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretKind {
+  /// Used as is
+  #[default]
+  Plain,
+  /// Seed that `secrets totp` generates one-time codes from
+  Totp,
+}
+
+impl SecretKind {
+  fn is_plain(&self) -> bool {
+    *self == SecretKind::Plain
+  }
+}
+
+/// Metadata for one secret (timestamps are unix seconds)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+  /// When the current value was stored
+  pub created_at: Option<u64>,
+  /// When the value stops working and needs rotating
+  pub expires_at: Option<u64>,
+  /// Free-form reminder, e.g. where to rotate the secret
+  pub note: Option<String>,
+  /// Missing from records written before kinds existed, which are all plain
+  #[serde(default, skip_serializing_if = "SecretKind::is_plain")]
+  pub kind: SecretKind,
+}
+
+/// Expiry flags for commands that store a secret
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct ExpiryOptions {
+  /// Time until the secret expires, e.g. 90d, 12w, 1y (kept when rotating)
+  #[arg(long, value_parser = usage::parse_period)]
+  pub expires: Option<u64>,
+  /// Note kept with the secret, shown when it is about to expire
+  #[arg(long)]
+  pub note: Option<String>,
+}
+
+fn key(group: &str, name: &str) -> String {
+  format!("{group}/{name}")
+}
+
+fn record(credentials: &Credentials, group: &str, name: &str) -> Option<Metadata> {
+  let record = credentials.get(META_GROUP)?.get(&key(group, name))?;
+  serde_json::from_str(record).ok()
+}
+
+fn set(credentials: &mut Credentials, group: &str, name: &str, metadata: &Metadata) {
+  let record = serde_json::to_string(metadata).expect("metadata records always serialize");
+  credentials.entry(META_GROUP.to_string()).or_default().insert(key(group, name), record);
+}
+
+/// Metadata for a secret, falling back to its usage record for older vaults
+pub fn get(credentials: &Credentials, group: &str, name: &str) -> Option<Metadata> {
+  record(credentials, group, name).or_else(|| {
+    let stored_at = usage::get(credentials, group, name)?.stored_at;
+    Some(Metadata { created_at: stored_at, ..Default::default() })
+  })
+}
+
+/// Kind of a stored secret; plain unless recorded otherwise
+pub fn kind(credentials: &Credentials, group: &str, name: &str) -> SecretKind {
+  record(credentials, group, name).map(|metadata| metadata.kind).unwrap_or_default()
+}
+
+/// Mark a stored secret as being of `kind`
+pub fn set_kind(credentials: &mut Credentials, group: &str, name: &str, kind: SecretKind) {
+  let metadata = Metadata { kind, ..get(credentials, group, name).unwrap_or_default() };
+  set(credentials, group, name, &metadata);
+}
+
+/// Secrets of `group` recorded as `kind`, sorted by name
+pub fn of_kind<'a>(credentials: &'a Credentials, group: &str, kind: SecretKind) -> Vec<&'a str> {
+  let mut names: Vec<&str> = credentials
+    .get(group)
+    .into_iter()
+    .flat_map(|secrets| secrets.keys())
+    .filter(|name| self::kind(credentials, group, name) == kind)
+    .map(String::as_str)
+    .collect();
+  names.sort();
+  names
+}
+
+/// Note that the secret was (re)stored, applying any new expiry or note
+pub fn record_store(
+  credentials: &mut Credentials,
+  group: &str,
+  name: &str,
+  now: u64,
+  options: &ExpiryOptions,
+) {
+  let previous = record(credentials, group, name).unwrap_or_default();
+  let lifetime = match (previous.created_at, previous.expires_at) {
+    (Some(created_at), Some(expires_at)) => Some(expires_at.saturating_sub(created_at)),
+    _ => None,
+  };
+
+  let metadata = Metadata {
+    created_at: Some(now),
+    expires_at: options.expires.or(lifetime).map(|lifetime| now + lifetime),
+    note: options.note.clone().or(previous.note),
+    kind: previous.kind,
+  };
+  set(credentials, group, name, &metadata);
+}
+
+/// Copy a secret's record from another vault, e.g. one being imported
+pub fn copy(from: &Credentials, to: &mut Credentials, group: &str, name: &str) {
+  if let Some(metadata) = record(from, group, name) {
+    set(to, group, name, &metadata);
+  }
+}
+
+/// A secret that has expired or will within the requested period
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiringSecret {
+  pub group: String,
+  pub name: String,
+  pub expires_at: u64,
+  pub note: Option<String>,
+}
+
+impl ExpiringSecret {
+  pub fn is_expired(&self, now: u64) -> bool {
+    self.expires_at <= now
+  }
+}
+
+/// Secrets expiring within `within` seconds (or already expired), soonest first
+pub fn expiring(credentials: &Credentials, now: u64, within: u64) -> Vec<ExpiringSecret> {
+  let cutoff = now.saturating_add(within);
+  let mut expiring: Vec<ExpiringSecret> = usage::secret_groups(credentials)
+    .flat_map(|(group, secrets)| secrets.keys().map(move |name| (group, name)))
+    .filter_map(|(group, name)| {
+      let metadata = record(credentials, group, name)?;
+      let expires_at = metadata.expires_at.filter(|&at| at <= cutoff)?;
+      Some(ExpiringSecret {
+        group: group.clone(),
+        name: name.clone(),
+        expires_at,
+        note: metadata.note,
+      })
+    })
+    .collect();
+  expiring
+    .sort_by(|a, b| (a.expires_at, &a.group, &a.name).cmp(&(b.expires_at, &b.group, &b.name)));
+  expiring
+}
+
+/// Human readable expiry, e.g. `expires in 5d` or `expired 2d ago`
+pub fn describe_expiry(expires_at: u64, now: u64) -> String {
+  const HOUR: u64 = 60 * 60;
+  const DAY: u64 = 24 * HOUR;
+
+  let span = |secs: u64| match secs {
+    s if s < HOUR => "<1h".to_string(),
+    s if s < DAY => format!("{}h", s / HOUR),
+    s => format!("{}d", s / DAY),
+  };
+  if expires_at <= now {
+    format!("expired {} ago", span(now - expires_at))
+  } else {
+    format!("expires in {}", span(expires_at - now))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const DAY: u64 = 24 * 60 * 60;
+
+  fn vault(entries: &[(&str, &str)]) -> Credentials {
+    let mut credentials = Credentials::new();
+    for (group, name) in entries {
+      credentials.entry(group.to_string()).or_default().insert(name.to_string(), "v".to_string());
+    }
+    credentials
+  }
+
+  fn expires(days: u64, note: Option<&str>) -> ExpiryOptions {
+    ExpiryOptions { expires: Some(days * DAY), note: note.map(str::to_string) }
+  }
+
+  #[test]
+  fn test_rotation_keeps_lifetime_and_note() {
+    let mut credentials = vault(&[("github", "token")]);
+    record_store(
+      &mut credentials,
+      "github",
+      "token",
+      10 * DAY,
+      &expires(90, Some("rotate in settings")),
+    );
+
+    record_store(&mut credentials, "github", "token", 50 * DAY, &ExpiryOptions::default());
+    let metadata = get(&credentials, "github", "token").unwrap();
+    assert_eq!(metadata.created_at, Some(50 * DAY));
+    assert_eq!(metadata.expires_at, Some(140 * DAY));
+    assert_eq!(metadata.note.as_deref(), Some("rotate in settings"));
+
+    record_store(&mut credentials, "github", "token", 60 * DAY, &expires(30, None));
+    assert_eq!(get(&credentials, "github", "token").unwrap().expires_at, Some(90 * DAY));
+  }
+
+  #[test]
+  fn test_kind_survives_rotation() {
+    let mut credentials = vault(&[("github", "otp"), ("github", "token"), ("legacy", "otp")]);
+    usage::record_store(&mut credentials, "legacy", "otp", 42);
+    set_kind(&mut credentials, "legacy", "otp", SecretKind::Totp);
+    assert_eq!(get(&credentials, "legacy", "otp").unwrap().created_at, Some(42));
+
+    record_store(&mut credentials, "github", "otp", DAY, &ExpiryOptions::default());
+    set_kind(&mut credentials, "github", "otp", SecretKind::Totp);
+    record_store(&mut credentials, "github", "otp", 2 * DAY, &expires(30, None));
+    assert_eq!(kind(&credentials, "github", "otp"), SecretKind::Totp);
+    assert_eq!(kind(&credentials, "github", "token"), SecretKind::Plain);
+    assert_eq!(of_kind(&credentials, "github", SecretKind::Totp), ["otp"]);
+
+    let plain = serde_json::to_string(&get(&credentials, "legacy", "none").unwrap_or_default());
+    assert!(!plain.unwrap().contains("kind"));
+  }
+
+  #[test]
+  fn test_older_vaults_fall_back_to_usage() {
+    let mut credentials = vault(&[("github", "token"), ("legacy", "token")]);
+    usage::record_store(&mut credentials, "github", "token", 42);
+
+    assert_eq!(get(&credentials, "github", "token").unwrap().created_at, Some(42));
+    assert_eq!(get(&credentials, "legacy", "token"), None);
+    assert!(expiring(&credentials, 100, 100 * DAY).is_empty());
+    assert!(usage::is_reserved(META_GROUP));
+  }
+
+  #[test]
+  fn test_expiring_is_soonest_first_within_window() {
+    let now = 100 * DAY;
+    let mut credentials = vault(&[("a", "later"), ("b", "soon"), ("c", "expired"), ("d", "none")]);
+    record_store(&mut credentials, "a", "later", now, &expires(60, None));
+    record_store(&mut credentials, "b", "soon", now, &expires(5, Some("ask ops")));
+    record_store(&mut credentials, "c", "expired", now - 10 * DAY, &expires(7, None));
+    record_store(&mut credentials, "d", "none", now, &ExpiryOptions::default());
+
+    let expiring = expiring(&credentials, now, 14 * DAY);
+    let names: Vec<&str> = expiring.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["expired", "soon"]);
+    assert!(expiring[0].is_expired(now));
+    assert_eq!(expiring[1].note.as_deref(), Some("ask ops"));
+  }
+
+  #[test]
+  fn test_describe_expiry() {
+    let now = 100 * DAY;
+    assert_eq!(describe_expiry(now + 5 * DAY, now), "expires in 5d");
+    assert_eq!(describe_expiry(now + 3 * 60 * 60, now), "expires in 3h");
+    assert_eq!(describe_expiry(now - 2 * DAY, now), "expired 2d ago");
+    assert_eq!(describe_expiry(now, now), "expired <1h ago");
+  }
+}
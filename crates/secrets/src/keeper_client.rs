@@ -75,38 +75,122 @@ pub async fn start(
   Ok(())
 }
 
-/// Check the status of the agent
-pub async fn status(socket_path: &std::path::Path) -> Result<()> {
+/// What a keeper answers on its socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeeperState {
+  /// There is no socket
+  NotRunning,
+  /// The socket exists but nothing usable answers on it
+  Unreachable(String),
+  /// Running without the master password
+  Locked,
+  /// Running with the master password, auto-locking after this many idle seconds if set
+  Unlocked(Option<u64>),
+  /// Answered `STATUS` with something unexpected
+  Unknown(String),
+}
+
+/// Ask the keeper behind `socket_path` how it is doing, without changing anything
+pub async fn probe(socket_path: &Path) -> KeeperState {
   if !socket_path.exists() {
-    bentley::info!("agent is not running");
-    bentley::info!("use 'secrets agent start' to start the daemon");
-    return Ok(());
+    return KeeperState::NotRunning;
   }
 
-  match UnixStream::connect(&socket_path).await {
-    Ok(mut stream) => {
-      use tokio::io::{AsyncReadExt, AsyncWriteExt};
-      if (stream.write_all(b"GET\n").await).is_err() {
-        bentley::warn!("socket exists but failed to communicate");
-        return Ok(());
-      }
+  let response = match request(socket_path, "STATUS").await {
+    Ok(response) => response,
+    Err(e) => return KeeperState::Unreachable(e.to_string()),
+  };
 
-      let mut response = String::new();
-      if stream.read_to_string(&mut response).await.is_ok() && !response.trim().is_empty() {
-        bentley::success!("keeper is running and responsive");
-      } else {
-        bentley::error!("keeper is running but not responding correctly");
-      }
+  let mut fields = response.split_whitespace();
+  match (fields.next(), fields.next().and_then(|secs| secs.parse::<u64>().ok())) {
+    (Some("LOCKED"), _) => KeeperState::Locked,
+    (Some("UNLOCKED"), secs) => KeeperState::Unlocked(secs),
+    _ => KeeperState::Unknown(response),
+  }
+}
+
+/// Check the status of the agent
+pub async fn status(socket_path: &std::path::Path) -> Result<()> {
+  match probe(socket_path).await {
+    KeeperState::NotRunning => {
+      bentley::info!("agent is not running");
+      bentley::info!("use 'secrets agent start' to start the daemon");
     }
-    Err(_) => {
+    KeeperState::Unreachable(_) => {
       bentley::error!("socket file exists but connection failed");
       bentley::error!("agent may be starting up or in bad state");
     }
+    KeeperState::Locked => {
+      bentley::warn!("keeper is running but locked");
+      bentley::info!("use 'secrets agent unlock' to unlock it");
+    }
+    KeeperState::Unlocked(Some(secs)) => {
+      bentley::success!(&format!("keeper is running and unlocked (auto-locks in {secs}s)"));
+    }
+    KeeperState::Unlocked(None) => bentley::success!("keeper is running and unlocked"),
+    KeeperState::Unknown(_) => bentley::error!("keeper is running but not responding correctly"),
   }
 
   Ok(())
 }
 
+/// Make the keeper forget the master password until it is unlocked again
+pub async fn lock(socket_path: &Path) -> Result<()> {
+  if !socket_path.exists() {
+    bentley::info!("agent is not running");
+    return Ok(());
+  }
+
+  match request(socket_path, "LOCK").await?.as_str() {
+    "OK" => bentley::success!("keeper locked"),
+    other => return Err(anyhow!("unexpected reply from daemon: {other}")),
+  }
+  Ok(())
+}
+
+/// Hand the master password back to a locked keeper
+pub async fn unlock(socket_path: &Path, password: &str) -> Result<()> {
+  if !socket_path.exists() {
+    return Err(anyhow!("daemon socket not found"));
+  }
+
+  let reply = request(socket_path, &format!("UNLOCK {}", password.trim())).await?;
+  match reply.strip_prefix("ERR ") {
+    None if reply == "OK" => Ok(()),
+    Some(reason) => Err(anyhow!("daemon refused to unlock: {reason}")),
+    None => Err(anyhow!("unexpected reply from daemon: {reply}")),
+  }
+}
+
+/// Send one request line to the daemon and return its (trimmed) reply
+async fn request(socket_path: &Path, line: &str) -> Result<String> {
+  Ok(request_reply(socket_path, line).await?.unwrap_or_default())
+}
+
+/// Send one request and read its reply line, or `None` if the daemon closed the
+/// connection without answering
+async fn request_reply(socket_path: &Path, line: &str) -> Result<Option<String>> {
+  use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+  let mut stream = UnixStream::connect(socket_path)
+    .await
+    .map_err(|e| anyhow!("failed to connect to daemon: {}", e))?;
+
+  // Requests are newline-terminated for protocol compatibility
+  stream
+    .write_all(format!("{line}\n").as_bytes())
+    .await
+    .map_err(|e| anyhow!("failed to send request to daemon: {}", e))?;
+
+  let mut response = String::new();
+  stream
+    .read_to_string(&mut response)
+    .await
+    .map_err(|e| anyhow!("failed to read response from daemon: {}", e))?;
+
+  Ok((!response.is_empty()).then(|| response.trim().to_string()))
+}
+
 /// Stop the agent
 pub async fn stop(socket_path: &std::path::Path, pid_file: &std::path::Path) -> Result<()> {
   use std::{fs, process::Command};
@@ -177,6 +261,51 @@ pub async fn restart(
   Ok(())
 }
 
+/// Install user-level systemd units for socket-activated keeper startup
+pub async fn install_service(
+  socket_path: &Path,
+  blizz_home: &Path,
+  unit_dir: Option<std::path::PathBuf>,
+) -> Result<()> {
+  let unit_dir = match unit_dir {
+    Some(dir) => dir,
+    None => crate::systemd::user_unit_dir()?,
+  };
+
+  let keeper_bin = locate_keeper_binary()?;
+  let written =
+    crate::systemd::install_user_units(&unit_dir, socket_path, &keeper_bin, blizz_home)?;
+
+  for path in &written {
+    bentley::success!(&format!("wrote {}", path.display()));
+  }
+
+  let unit = crate::systemd::UNIT_NAME;
+  bentley::info!("enable socket activation with:");
+  bentley::info!("  systemctl --user daemon-reload");
+  bentley::info!(&format!("  systemctl --user enable --now {unit}.socket"));
+  bentley::info!("without a terminal, keeper reads the master password from SECRETS_AUTH");
+
+  Ok(())
+}
+
+/// Find the keeper binary, preferring the one installed next to this executable
+fn locate_keeper_binary() -> Result<std::path::PathBuf> {
+  if let Ok(current) = env::current_exe() {
+    if let Some(sibling) = current.parent().map(|dir| dir.join("keeper")) {
+      if sibling.exists() {
+        return Ok(sibling);
+      }
+    }
+  }
+
+  let path = env::var_os("PATH").ok_or_else(|| anyhow!("PATH is not set"))?;
+  env::split_paths(&path)
+    .map(|dir| dir.join("keeper"))
+    .find(|candidate| candidate.exists())
+    .ok_or_else(|| anyhow!("could not find the 'keeper' binary in PATH"))
+}
+
 /// Try to get password from running daemon
 pub async fn get(base_path: &Path) -> Result<String> {
   let socket_path = base_path.join("persistent").join("keeper").join("keeper.sock");
@@ -185,31 +314,32 @@ pub async fn get(base_path: &Path) -> Result<String> {
     return Err(anyhow!("daemon socket not found"));
   }
 
-  let mut stream = UnixStream::connect(&socket_path)
-    .await
-    .map_err(|e| anyhow!("failed to connect to daemon: {}", e))?;
-
-  use tokio::io::{AsyncReadExt, AsyncWriteExt};
-
-  // Send GET request to daemon (with newline for protocol compatibility)
-  stream
-    .write_all(b"GET\n")
-    .await
-    .map_err(|e| anyhow!("failed to send request to daemon: {}", e))?;
-
-  // Read password response
-  let mut password = String::new();
-  stream
-    .read_to_string(&mut password)
-    .await
-    .map_err(|e| anyhow!("failed to read response from daemon: {}", e))?;
-
-  let password = password.trim();
-  if password.is_empty() {
-    return Err(anyhow!("daemon returned empty password"));
+  // A locked keeper answers with an empty line
+  match request_reply(&socket_path, "GET").await? {
+    None => Err(anyhow!("daemon closed the connection without replying")),
+    Some(password) if password.is_empty() => Err(anyhow!("the keeper is locked")),
+    Some(password) => Ok(password),
   }
+}
 
-  Ok(password.to_string())
+/// Read every secret in a group through a running daemon, never prompting
+///
+/// Meant for other services (such as the insights server) that run without a
+/// terminal. Missing vaults and groups read as empty.
+pub async fn read_group(
+  base_path: &Path,
+  group: &str,
+) -> Result<std::collections::HashMap<String, String>> {
+  let vault = base_path.join("persistent").join("keeper").join("credentials.enc");
+  let Some(store) = crate::PasswordBasedCredentialStore::load_from_file(&vault)? else {
+    return Ok(Default::default());
+  };
+
+  let password = get(base_path).await?;
+  let mut credentials = store
+    .decrypt_credentials(&password)
+    .map_err(|_| anyhow!("invalid master password or corrupted data"))?;
+  Ok(credentials.remove(group).unwrap_or_default())
 }
 
 #[cfg(test)]
@@ -330,6 +460,32 @@ mod tests {
     assert!(result.is_ok(), "Should handle empty response from daemon");
   }
 
+  #[tokio::test]
+  async fn test_probe_reports_keeper_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("test.sock");
+    assert_eq!(probe(&socket_path).await, KeeperState::NotRunning);
+
+    fs::write(&socket_path, "").unwrap();
+    assert!(matches!(probe(&socket_path).await, KeeperState::Unreachable(_)));
+    fs::remove_file(&socket_path).unwrap();
+
+    let listener = UnixListener::bind(&socket_path).unwrap();
+    let _handle = tokio::spawn(async move {
+      for reply in ["LOCKED", "UNLOCKED 90", "UNLOCKED", "HUH"] {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = [0; 7];
+        let _ = stream.read_exact(&mut buffer).await;
+        let _ = stream.write_all(reply.as_bytes()).await;
+      }
+    });
+
+    assert_eq!(probe(&socket_path).await, KeeperState::Locked);
+    assert_eq!(probe(&socket_path).await, KeeperState::Unlocked(Some(90)));
+    assert_eq!(probe(&socket_path).await, KeeperState::Unlocked(None));
+    assert_eq!(probe(&socket_path).await, KeeperState::Unknown("HUH".to_string()));
+  }
+
   // Tests for stop() function branches
   #[tokio::test]
   async fn test_stop_agent_not_running() {
@@ -526,6 +682,41 @@ mod tests {
   }
 
   // Tests for get() function branches
+  #[tokio::test]
+  async fn test_read_group_decrypts_through_daemon() {
+    let temp_dir = TempDir::new().unwrap();
+    let base_path = temp_dir.path();
+    let keeper_dir = base_path.join("persistent").join("keeper");
+    fs::create_dir_all(&keeper_dir).unwrap();
+
+    let credentials = std::collections::HashMap::from([(
+      "insights".to_string(),
+      std::collections::HashMap::from([("llm_model".to_string(), "local".to_string())]),
+    )]);
+    let store =
+      crate::PasswordBasedCredentialStore::new(&credentials, "test_password_123").unwrap();
+    store.save_to_file(&keeper_dir.join("credentials.enc")).unwrap();
+
+    let listener = UnixListener::bind(keeper_dir.join("keeper.sock")).unwrap();
+    let _handle = tokio::spawn(async move {
+      if let Ok((mut stream, _)) = listener.accept().await {
+        let mut buffer = [0; 4];
+        let _ = stream.read_exact(&mut buffer).await;
+        let _ = stream.write_all(b"test_password_123").await;
+      }
+    });
+
+    let group = read_group(base_path, "insights").await.unwrap();
+    assert_eq!(group.get("llm_model").map(String::as_str), Some("local"));
+  }
+
+  #[tokio::test]
+  async fn test_read_group_without_vault_needs_no_daemon() {
+    let temp_dir = TempDir::new().unwrap();
+    let group = read_group(temp_dir.path(), "insights").await.unwrap();
+    assert!(group.is_empty());
+  }
+
   #[tokio::test]
   async fn test_get_socket_does_not_exist() {
     let temp_dir = TempDir::new().unwrap();
@@ -581,7 +772,7 @@ mod tests {
   }
 
   #[tokio::test]
-  async fn test_get_empty_password_response() {
+  async fn test_get_tells_a_locked_keeper_from_no_reply() {
     let temp_dir = TempDir::new().unwrap();
     let base_path = temp_dir.path();
     let socket_dir = base_path.join("persistent").join("keeper");
@@ -589,22 +780,23 @@ mod tests {
 
     fs::create_dir_all(&socket_dir).unwrap();
 
-    // Create a mock daemon that responds with empty password
+    // A locked keeper sends an empty line; a failing one closes without a word
     let listener = UnixListener::bind(&socket_path).unwrap();
-
     let _handle = tokio::spawn(async move {
-      if let Ok((mut stream, _)) = listener.accept().await {
+      for reply in [&b"\n"[..], b""] {
+        let (mut stream, _) = listener.accept().await.unwrap();
         let mut buffer = [0; 4];
         let _ = stream.read_exact(&mut buffer).await;
-        let _ = stream.write_all(b"").await; // Empty response
+        let _ = stream.write_all(reply).await;
       }
     });
 
     tokio::time::sleep(Duration::from_millis(10)).await;
 
-    let result = get(base_path).await;
-    assert!(result.is_err(), "Should fail when daemon returns empty password");
-    assert!(result.unwrap_err().to_string().contains("daemon returned empty password"));
+    let locked = get(base_path).await.unwrap_err();
+    assert!(locked.to_string().contains("the keeper is locked"));
+    let silent = get(base_path).await.unwrap_err();
+    assert!(silent.to_string().contains("without replying"));
   }
 
   #[tokio::test]
@@ -631,7 +823,50 @@ mod tests {
 
     let result = get(base_path).await;
     assert!(result.is_err(), "Should fail when daemon returns whitespace-only password");
-    assert!(result.unwrap_err().to_string().contains("daemon returned empty password"));
+    assert!(result.unwrap_err().to_string().contains("the keeper is locked"));
+  }
+
+  /// Mock daemon that answers a single request line with `reply`, handing back the request
+  fn mock_daemon(socket_path: &Path, reply: &'static str) -> tokio::task::JoinHandle<String> {
+    let listener = UnixListener::bind(socket_path).unwrap();
+    tokio::spawn(async move {
+      let (stream, _) = listener.accept().await.unwrap();
+      let mut reader = tokio::io::BufReader::new(stream);
+      let mut line = String::new();
+      tokio::io::AsyncBufReadExt::read_line(&mut reader, &mut line).await.unwrap();
+      reader.into_inner().write_all(reply.as_bytes()).await.unwrap();
+      line
+    })
+  }
+
+  #[tokio::test]
+  async fn test_unlock_sends_password_and_accepts_ok() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("test.sock");
+    let daemon = mock_daemon(&socket_path, "OK\n");
+
+    unlock(&socket_path, "  hunter2\n").await.unwrap();
+    assert_eq!(daemon.await.unwrap(), "UNLOCK hunter2\n");
+  }
+
+  #[tokio::test]
+  async fn test_unlock_reports_refusal() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("test.sock");
+    let _daemon = mock_daemon(&socket_path, "ERR incorrect password\n");
+
+    let err = unlock(&socket_path, "wrong").await.unwrap_err();
+    assert!(err.to_string().contains("incorrect password"));
+  }
+
+  #[tokio::test]
+  async fn test_lock_sends_lock_request() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("test.sock");
+    let daemon = mock_daemon(&socket_path, "OK\n");
+
+    lock(&socket_path).await.unwrap();
+    assert_eq!(daemon.await.unwrap(), "LOCK\n");
   }
 
   #[tokio::test]
@@ -0,0 +1,214 @@
+//! Per-secret requests served by the keeper daemon
+//!
+//! Instead of handing out the master password, the keeper can read, store and
+//! delete single secrets itself, so clients never decrypt the vault and never
+//! see the password. Each request is one line, a verb followed by a JSON
+//! [`SecretRequest`], and is answered with one JSON [`SecretReply`] line:
+//!
+//! - `GET_SECRET {"group":"github","name":"token"}` - `{"value":"..."}`
+//! - `STORE_SECRET {"group":"github","name":"token","value":"..."}` - `{}`
+//! - `DELETE_SECRET {"group":"github","name":"token"}` - `{}`
+//!
+//! Failures, including a locked keeper, reply `{"error":"<reason>"}`.
+//!
+//! `SECRETS_BACKEND=keeper` selects [`KeeperCryptoProvider`] for
+//! [`crate::Secrets::new`].
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use crate::{usage, CryptoProvider};
+
+pub const GET_SECRET: &str = "GET_SECRET";
+pub const STORE_SECRET: &str = "STORE_SECRET";
+pub const DELETE_SECRET: &str = "DELETE_SECRET";
+
+/// The secret a request is about, and the value to store for `STORE_SECRET`
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretRequest {
+  pub group: String,
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub value: Option<String>,
+}
+
+impl std::fmt::Debug for SecretRequest {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    // Never let a value end up in a log line
+    f.debug_struct("SecretRequest")
+      .field("group", &self.group)
+      .field("name", &self.name)
+      .field("value", &self.value.as_ref().map(|_| "<redacted>"))
+      .finish()
+  }
+}
+
+/// Answer to a secret request
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretReply {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub value: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+impl SecretReply {
+  pub fn value(value: String) -> Self {
+    Self { value: Some(value), error: None }
+  }
+
+  pub fn failed(error: impl std::fmt::Display) -> Self {
+    Self { value: None, error: Some(error.to_string()) }
+  }
+}
+
+/// Crypto provider that leaves decryption to a running keeper
+pub struct KeeperCryptoProvider {
+  base_path: PathBuf,
+}
+
+impl KeeperCryptoProvider {
+  pub fn new() -> Self {
+    Self::at(crate::base_path())
+  }
+
+  /// Provider for the keeper of the blizz directory at `base_path`
+  pub fn at(base_path: PathBuf) -> Self {
+    Self { base_path }
+  }
+
+  fn keeper_path(&self) -> PathBuf {
+    self.base_path.join("persistent").join("keeper")
+  }
+
+  /// Send one request and return the value of a successful reply
+  fn request(&self, verb: &str, request: &SecretRequest) -> Result<Option<String>> {
+    let socket_path = self.keeper_path().join("keeper.sock");
+    let mut stream = UnixStream::connect(&socket_path)
+      .with_context(|| format!("failed to connect to keeper at {}", socket_path.display()))?;
+
+    let line = format!("{verb} {}\n", serde_json::to_string(request)?);
+    stream.write_all(line.as_bytes()).context("failed to send request to keeper")?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).context("failed to read reply from keeper")?;
+    if reply.trim().is_empty() {
+      return Err(anyhow!("keeper closed the connection; it may not support {verb}"));
+    }
+
+    let reply: SecretReply =
+      serde_json::from_str(reply.trim()).context("unexpected reply from keeper")?;
+    match reply.error {
+      Some(error) => Err(anyhow!(error)),
+      None => Ok(reply.value),
+    }
+  }
+}
+
+impl Default for KeeperCryptoProvider {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl CryptoProvider for KeeperCryptoProvider {
+  fn credentials_exist(&self) -> bool {
+    self.keeper_path().join("credentials.enc").exists()
+  }
+
+  fn get_master_password(&self) -> Result<String> {
+    // The keeper holds the password; clients never need it
+    Ok(String::new())
+  }
+
+  fn prompt_for_new_master_password(&self) -> Result<String> {
+    Err(anyhow!("no vault yet; create one with 'secrets agent start'"))
+  }
+
+  fn store_secret(
+    &self,
+    group: &str,
+    name: &str,
+    value: &str,
+    _master_password: &str,
+  ) -> Result<()> {
+    usage::check_group(group)?;
+    let request = SecretRequest {
+      group: group.to_string(),
+      name: name.to_string(),
+      value: Some(value.to_string()),
+    };
+    self.request(STORE_SECRET, &request)?;
+    Ok(())
+  }
+
+  fn get_secret(&self, group: &str, name: &str, _master_password: &str) -> Result<String> {
+    usage::check_group(group)?;
+    let request = SecretRequest { group: group.to_string(), name: name.to_string(), value: None };
+    self.request(GET_SECRET, &request)?.ok_or_else(|| anyhow!("keeper sent no value"))
+  }
+
+  fn delete_secret(&self, group: &str, name: &str, _master_password: &str) -> Result<()> {
+    let request = SecretRequest { group: group.to_string(), name: name.to_string(), value: None };
+    self.request(DELETE_SECRET, &request)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::os::unix::net::UnixListener;
+  use tempfile::TempDir;
+
+  /// A keeper that answers one request with `reply` and hands back the request line
+  fn fake_keeper(base: &std::path::Path, reply: &'static str) -> std::thread::JoinHandle<String> {
+    let keeper_path = base.join("persistent").join("keeper");
+    std::fs::create_dir_all(&keeper_path).unwrap();
+    let listener = UnixListener::bind(keeper_path.join("keeper.sock")).unwrap();
+
+    std::thread::spawn(move || {
+      let (stream, _) = listener.accept().unwrap();
+      let mut line = String::new();
+      BufReader::new(&stream).read_line(&mut line).unwrap();
+      (&stream).write_all(reply.as_bytes()).unwrap();
+      line
+    })
+  }
+
+  #[test]
+  fn test_get_secret_asks_the_keeper() {
+    let temp_dir = TempDir::new().unwrap();
+    let keeper = fake_keeper(temp_dir.path(), "{\"value\":\"s3cret\"}\n");
+    let provider = KeeperCryptoProvider::at(temp_dir.path().to_path_buf());
+
+    assert_eq!(provider.get_secret("github", "token", "").unwrap(), "s3cret");
+    assert_eq!(keeper.join().unwrap(), "GET_SECRET {\"group\":\"github\",\"name\":\"token\"}\n");
+  }
+
+  #[test]
+  fn test_keeper_errors_are_returned() {
+    let temp_dir = TempDir::new().unwrap();
+    let keeper = fake_keeper(temp_dir.path(), "{\"error\":\"keeper is locked\"}\n");
+    let provider = KeeperCryptoProvider::at(temp_dir.path().to_path_buf());
+
+    let error = provider.store_secret("github", "token", "value", "").unwrap_err();
+    assert_eq!(error.to_string(), "keeper is locked");
+    assert!(keeper.join().unwrap().starts_with("STORE_SECRET "));
+
+    assert!(provider.get_secret(usage::USAGE_GROUP, "x", "").is_err());
+  }
+
+  #[test]
+  fn test_request_debug_hides_the_value() {
+    let request = SecretRequest {
+      group: "github".to_string(),
+      name: "token".to_string(),
+      value: Some("s3cret".to_string()),
+    };
+    assert!(!format!("{request:?}").contains("s3cret"));
+  }
+}
@@ -0,0 +1,218 @@
+//! OS keychain backend
+//!
+//! Keeps each secret as its own entry in the platform keychain instead of the
+//! password-encrypted vault file, through the `keyring` crate: the login keychain
+//! on macOS, Credential Manager on Windows, and the Secret Service (GNOME Keyring,
+//! KWallet) on Linux and the BSDs. The keychain is unlocked with the user's
+//! session, so there is no master password, and usage tracking stays a feature of
+//! the vault file.
+//!
+//! Entries are filed under the service `blizz/<group>` with the secret's name as
+//! the account, so blizz secrets are easy to find in the platform's own tools.
+//!
+//! `SECRETS_BACKEND=keychain` selects it for [`crate::Secrets::new`]. The
+//! `secrets` CLI and the keeper keep working on the vault file.
+
+use anyhow::{anyhow, Result};
+use keyring::credential::CredentialBuilder;
+use keyring::Entry;
+
+use crate::{usage, CryptoProvider};
+
+/// Service every entry is filed under, so blizz secrets are easy to find
+pub const SERVICE: &str = "blizz";
+
+/// Whether `keyring` has a persistent store on this platform; elsewhere it would
+/// quietly fall back to an in-memory mock
+const SUPPORTED: bool = cfg!(any(
+  target_os = "macos",
+  target_os = "windows",
+  target_os = "linux",
+  target_os = "freebsd",
+  target_os = "openbsd"
+));
+
+/// Crypto provider backed by the OS keychain
+pub struct KeychainCryptoProvider {
+  /// Store entries are built in; `None` is the platform keychain
+  builder: Option<Box<CredentialBuilder>>,
+}
+
+impl KeychainCryptoProvider {
+  pub fn new() -> Self {
+    Self { builder: None }
+  }
+
+  /// Keep entries in another credential store, such as an in-memory one in tests
+  pub fn with_builder(builder: Box<CredentialBuilder>) -> Self {
+    Self { builder: Some(builder) }
+  }
+
+  /// The keychain entry holding a secret
+  fn entry(&self, group: &str, name: &str) -> Result<Entry> {
+    let service = entry_service(group);
+    match &self.builder {
+      Some(builder) => Ok(Entry::new_with_credential(
+        builder.build(None, &service, name).map_err(keychain_failed)?,
+      )),
+      None if SUPPORTED => Entry::new(&service, name).map_err(keychain_failed),
+      None => Err(unsupported()),
+    }
+  }
+}
+
+impl Default for KeychainCryptoProvider {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl CryptoProvider for KeychainCryptoProvider {
+  fn credentials_exist(&self) -> bool {
+    // The keychain is always there; there is no vault to set up first
+    true
+  }
+
+  fn get_master_password(&self) -> Result<String> {
+    // The session unlocks the keychain
+    Ok(String::new())
+  }
+
+  fn prompt_for_new_master_password(&self) -> Result<String> {
+    Ok(String::new())
+  }
+
+  fn store_secret(
+    &self,
+    group: &str,
+    name: &str,
+    value: &str,
+    _master_password: &str,
+  ) -> Result<()> {
+    usage::check_group(group)?;
+    self.entry(group, name)?.set_password(value).map_err(keychain_failed)
+  }
+
+  fn get_secret(&self, group: &str, name: &str, _master_password: &str) -> Result<String> {
+    usage::check_group(group)?;
+    match self.entry(group, name)?.get_password() {
+      Err(keyring::Error::NoEntry) => Err(not_found(group, name)),
+      result => result.map_err(keychain_failed),
+    }
+  }
+
+  fn delete_secret(&self, group: &str, name: &str, _master_password: &str) -> Result<()> {
+    match self.entry(group, name)?.delete_credential() {
+      Err(keyring::Error::NoEntry) => Err(not_found(group, name)),
+      result => result.map_err(keychain_failed),
+    }
+  }
+}
+
+/// Service a group's entries are filed under
+pub fn entry_service(group: &str) -> String {
+  format!("{SERVICE}/{group}")
+}
+
+fn not_found(group: &str, name: &str) -> anyhow::Error {
+  anyhow!("Secret not found for {}/{}", group, name)
+}
+
+fn keychain_failed(error: keyring::Error) -> anyhow::Error {
+  anyhow!("keychain access failed: {error}")
+}
+
+fn unsupported() -> anyhow::Error {
+  anyhow!("the keychain backend needs macOS, Windows or a Secret Service; unset SECRETS_BACKEND to use the vault file")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use keyring::credential::{CredentialApi, CredentialBuilderApi};
+  use std::any::Any;
+  use std::collections::HashMap;
+  use std::sync::{Arc, Mutex};
+
+  type Store = Arc<Mutex<HashMap<(String, String), Vec<u8>>>>;
+
+  /// Credential store shared by every entry built from it, unlike keyring's mock
+  #[derive(Default)]
+  struct MemoryBuilder(Store);
+
+  struct MemoryCredential {
+    store: Store,
+    key: (String, String),
+  }
+
+  impl CredentialBuilderApi for MemoryBuilder {
+    fn build(
+      &self,
+      _target: Option<&str>,
+      service: &str,
+      user: &str,
+    ) -> keyring::Result<Box<keyring::credential::Credential>> {
+      let key = (service.to_string(), user.to_string());
+      Ok(Box::new(MemoryCredential { store: self.0.clone(), key }))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+      self
+    }
+  }
+
+  impl CredentialApi for MemoryCredential {
+    fn set_secret(&self, secret: &[u8]) -> keyring::Result<()> {
+      self.store.lock().unwrap().insert(self.key.clone(), secret.to_vec());
+      Ok(())
+    }
+
+    fn get_secret(&self) -> keyring::Result<Vec<u8>> {
+      self.store.lock().unwrap().get(&self.key).cloned().ok_or(keyring::Error::NoEntry)
+    }
+
+    fn delete_credential(&self) -> keyring::Result<()> {
+      self.store.lock().unwrap().remove(&self.key).map(drop).ok_or(keyring::Error::NoEntry)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+      self
+    }
+  }
+
+  fn provider() -> (KeychainCryptoProvider, Store) {
+    let builder = MemoryBuilder::default();
+    let store = builder.0.clone();
+    (KeychainCryptoProvider::with_builder(Box::new(builder)), store)
+  }
+
+  #[test]
+  fn test_entries_are_filed_by_group_and_name() {
+    let (provider, store) = provider();
+    provider.store_secret("github", "my \"token\"", "line1\nline2", "").unwrap();
+
+    let key = ("blizz/github".to_string(), "my \"token\"".to_string());
+    assert_eq!(store.lock().unwrap().get(&key).unwrap(), b"line1\nline2");
+    assert_eq!(provider.get_secret("github", "my \"token\"", "").unwrap(), "line1\nline2");
+  }
+
+  #[test]
+  fn test_store_replaces_and_delete_removes() {
+    let (provider, _) = provider();
+    provider.store_secret("github", "token", "old", "").unwrap();
+    provider.store_secret("github", "token", "new", "").unwrap();
+    assert_eq!(provider.get_secret("github", "token", "").unwrap(), "new");
+
+    provider.delete_secret("github", "token", "").unwrap();
+    let missing = provider.get_secret("github", "token", "").unwrap_err();
+    assert!(missing.to_string().contains("Secret not found for github/token"));
+    assert!(provider.delete_secret("github", "token", "").is_err());
+  }
+
+  #[test]
+  fn test_reserved_groups_are_refused() {
+    let (provider, store) = provider();
+    assert!(provider.store_secret(usage::USAGE_GROUP, "x", "y", "").is_err());
+    assert!(store.lock().unwrap().is_empty());
+  }
+}
@@ -4,14 +4,46 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+pub mod audit;
 pub mod cli;
 pub mod commands;
 pub mod encryption;
+pub mod envfile;
+pub mod exec;
+pub mod expiry;
 pub mod keeper_client;
+pub mod keeper_secrets;
+pub mod keychain;
+pub mod keys;
+pub mod lockout;
+pub mod peer;
+pub mod settings;
+pub mod snapshot;
+pub mod specs;
+pub mod systemd;
+pub mod totp;
+pub mod transfer;
+pub mod usage;
+pub mod vaultfile;
 
 use encryption::{EncryptedBlob, EncryptionManager};
+use keeper_secrets::KeeperCryptoProvider;
+use keychain::KeychainCryptoProvider;
+
+/// Directory the vault and keeper live in: `$BLIZZ_DIR`, or `~/.blizz`
+pub fn base_path() -> PathBuf {
+  match std::env::var("BLIZZ_DIR") {
+    Ok(blizz_dir) => PathBuf::from(blizz_dir),
+    Err(_) => dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz"),
+  }
+}
+
+/// Path of the encrypted vault file under [`base_path`]
+pub fn vault_path() -> PathBuf {
+  base_path().join("persistent").join("keeper").join("credentials.enc")
+}
 
 // Helper function for password input using dialoguer
 fn read_password() -> Result<String> {
@@ -102,33 +134,41 @@ impl PasswordBasedCredentialStore {
     EncryptionManager::decrypt_credentials(&self.encrypted_data, master_password)
   }
 
-  pub fn load_from_file(path: &PathBuf) -> Result<Option<Self>> {
-    if path.exists() {
-      let content = fs::read_to_string(path)?;
-      let store: PasswordBasedCredentialStore = serde_json::from_str(content.trim())?;
-      Ok(Some(store))
-    } else {
-      Ok(None)
+  /// Load the vault, falling back to its backup if the vault itself is unreadable
+  pub fn load_from_file(path: &Path) -> Result<Option<Self>> {
+    if !path.exists() {
+      return Ok(None);
     }
-  }
 
-  pub fn save_to_file(&self, path: &PathBuf) -> Result<()> {
-    if let Some(parent) = path.parent() {
-      fs::create_dir_all(parent)?;
+    match Self::read(path) {
+      Ok(store) => Ok(Some(store)),
+      Err(e) => {
+        let backup = vaultfile::backup_path(path);
+        let store = Self::read(&backup).map_err(|_| e)?;
+        bentley::warn!(&format!(
+          "{} is damaged, using the backup at {}",
+          path.display(),
+          backup.display()
+        ));
+        Ok(Some(store))
+      }
     }
+  }
+
+  fn read(path: &Path) -> Result<Self> {
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(content.trim())?)
+  }
+
+  /// Atomically replace the vault, keeping the previous one as a backup
+  pub fn save_to_file(&self, path: &Path) -> Result<()> {
     let content = serde_json::to_string_pretty(self)?;
-    fs::write(path, content)?;
-
-    // Set restrictive permissions on credential file
-    #[cfg(unix)]
-    {
-      use std::os::unix::fs::PermissionsExt;
-      let mut perms = fs::metadata(path)?.permissions();
-      perms.set_mode(0o600); // Owner read/write only
-      fs::set_permissions(path, perms)?;
-    }
+    vaultfile::write_atomic(path, &content)
+  }
 
-    Ok(())
+  /// Hold this while loading, changing and saving the vault at `path`
+  pub fn lock(path: &Path) -> Result<vaultfile::VaultLock> {
+    vaultfile::VaultLock::acquire(path)
   }
 }
 
@@ -144,7 +184,9 @@ pub trait CryptoProvider {
 }
 
 /// Password-based crypto manager using Argon2 key derivation
-struct PasswordBasedCryptoManager {
+///
+/// Also what the keeper runs when it serves single secrets to clients.
+pub struct PasswordBasedCryptoManager {
   credentials_path: PathBuf,
 }
 
@@ -206,25 +248,40 @@ impl CryptoProvider for PasswordBasedCryptoManager {
     value: &str,
     master_password: &str,
   ) -> Result<()> {
+    usage::check_group(group)?;
+    let _lock = PasswordBasedCredentialStore::lock(&self.credentials_path)?;
     let mut credentials = self.load_credentials(master_password).unwrap_or_else(|_| HashMap::new());
 
     credentials.entry(group.to_string()).or_default().insert(name.to_string(), value.to_string());
+    let now = audit::now();
+    usage::record_store(&mut credentials, group, name, now);
+    expiry::record_store(&mut credentials, group, name, now, &Default::default());
 
     self.save_credentials(&credentials, master_password)?;
     Ok(())
   }
 
   fn get_secret(&self, group: &str, name: &str, master_password: &str) -> Result<String> {
-    let credentials = self.load_credentials(master_password)?;
+    usage::check_group(group)?;
+    let _lock = PasswordBasedCredentialStore::lock(&self.credentials_path)?;
+    let mut credentials = self.load_credentials(master_password)?;
 
-    credentials
+    let value = credentials
       .get(group)
       .and_then(|service_creds| service_creds.get(name))
       .cloned()
-      .ok_or_else(|| anyhow!("Secret not found for {}/{}", group, name))
+      .ok_or_else(|| anyhow!("Secret not found for {}/{}", group, name))?;
+
+    // Usage tracking must never make a read fail
+    usage::record_use(&mut credentials, group, name, audit::now());
+    if let Err(e) = self.save_credentials(&credentials, master_password) {
+      bentley::warn!(&format!("failed to record secret usage: {e}"));
+    }
+    Ok(value)
   }
 
   fn delete_secret(&self, group: &str, name: &str, master_password: &str) -> Result<()> {
+    let _lock = PasswordBasedCredentialStore::lock(&self.credentials_path)?;
     let mut credentials = self.load_credentials(master_password)?;
 
     if let Some(service_creds) = credentials.get_mut(group) {
@@ -233,6 +290,7 @@ impl CryptoProvider for PasswordBasedCryptoManager {
         if service_creds.is_empty() {
           credentials.remove(group);
         }
+        usage::prune_orphans(&mut credentials);
         self.save_credentials(&credentials, master_password)?;
         Ok(())
       } else {
@@ -245,21 +303,15 @@ impl CryptoProvider for PasswordBasedCryptoManager {
 }
 
 impl PasswordBasedCryptoManager {
-  fn new() -> Self {
-    let base_path = if let Ok(blizz_dir) = std::env::var("BLIZZ_DIR") {
-      std::path::PathBuf::from(blizz_dir)
-    } else {
-      dirs::home_dir().unwrap_or_else(|| std::env::current_dir().unwrap()).join(".blizz")
-    };
-
-    let mut credentials_path = base_path;
-    credentials_path.push("persistent");
-    credentials_path.push("keeper");
-    credentials_path.push("credentials.enc");
-
+  /// Manager for the vault file at `credentials_path`
+  pub fn at(credentials_path: PathBuf) -> Self {
     Self { credentials_path }
   }
 
+  fn new() -> Self {
+    Self::at(vault_path())
+  }
+
   fn load_credentials(
     &self,
     master_password: &str,
@@ -282,6 +334,43 @@ impl PasswordBasedCryptoManager {
   }
 }
 
+/// Where [`Secrets`] keeps secrets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+  /// Password-encrypted vault file (Argon2)
+  #[default]
+  File,
+  /// The OS keychain, see [`keychain`]
+  Keychain,
+  /// The vault file, decrypted by a running keeper, see [`keeper_secrets`]
+  Keeper,
+}
+
+impl std::str::FromStr for Backend {
+  type Err = anyhow::Error;
+
+  fn from_str(value: &str) -> Result<Self> {
+    match value.trim().to_lowercase().as_str() {
+      "file" | "vault" => Ok(Backend::File),
+      "keychain" => Ok(Backend::Keychain),
+      "keeper" => Ok(Backend::Keeper),
+      other => Err(anyhow!("unknown secrets backend '{other}' (use file, keychain or keeper)")),
+    }
+  }
+}
+
+impl Backend {
+  /// Get the configured backend
+  /// Default: file
+  /// Setting: secrets.backend (SECRETS_BACKEND, file, keychain or keeper)
+  pub fn from_env() -> Result<Self> {
+    match bentley::config::var(&settings::BACKEND) {
+      Some(value) if !value.trim().is_empty() => value.parse(),
+      _ => Ok(Backend::default()),
+    }
+  }
+}
+
 /// Secrets - The watchful guardian of secrets
 ///
 /// Provides secure credential storage using Argon2-based password derivation
@@ -327,9 +416,22 @@ impl SecretProvider for Secrets {
 }
 
 impl Secrets {
-  /// Create a new Secrets instance for the Blizz toolset
+  /// Create a new Secrets instance for the Blizz toolset, using the configured backend
   pub fn new() -> Self {
-    Self::with_crypto_provider(Box::new(PasswordBasedCryptoManager::new()))
+    let backend = Backend::from_env().unwrap_or_else(|e| {
+      bentley::warn!(&format!("{e}; using the vault file"));
+      Backend::File
+    });
+    Self::with_backend(backend)
+  }
+
+  /// Create a Secrets instance on a specific backend
+  pub fn with_backend(backend: Backend) -> Self {
+    match backend {
+      Backend::File => Self::with_crypto_provider(Box::new(PasswordBasedCryptoManager::new())),
+      Backend::Keychain => Self::with_crypto_provider(Box::new(KeychainCryptoProvider::new())),
+      Backend::Keeper => Self::with_crypto_provider(Box::new(KeeperCryptoProvider::new())),
+    }
   }
 
   /// Create a Secrets instance with a custom crypto provider for dependency injection
@@ -429,9 +531,7 @@ impl Secrets {
 
     for key in common_keys {
       if let Ok(value) = self.get_secret(group, &key) {
-        // Convert to environment variable format (uppercase with underscores)
-        let env_key = format!("{}_{}", group.to_uppercase(), key.to_uppercase());
-        env_vars.insert(env_key, value);
+        env_vars.insert(exec::env_name(group, &key), value);
       }
     }
 
@@ -757,6 +857,67 @@ mod tests {
     assert_eq!(retrieved.unwrap(), value);
   }
 
+  #[test]
+  fn test_reads_are_counted_inside_the_vault() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let crypto =
+      PasswordBasedCryptoManager { credentials_path: temp_dir.path().join("credentials.enc") };
+    let password = "test_password_123";
+
+    crypto.store_secret("github", "token", "abc", password).unwrap();
+    crypto.get_secret("github", "token", password).unwrap();
+    crypto.get_secret("github", "token", password).unwrap();
+
+    let credentials = crypto.load_credentials(password).unwrap();
+    let recorded = usage::get(&credentials, "github", "token").unwrap();
+    assert_eq!(recorded.count, 2);
+    assert!(recorded.last_used.is_some() && recorded.stored_at.is_some());
+    assert!(crypto.get_secret(usage::USAGE_GROUP, "github/token", password).is_err());
+
+    crypto.delete_secret("github", "token", password).unwrap();
+    assert!(crypto.load_credentials(password).unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_concurrent_stores_keep_every_secret() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let credentials_path = temp_dir.path().join("credentials.enc");
+    let password = "test_password_123";
+
+    let writers: Vec<_> = (0..4)
+      .map(|i| {
+        let crypto = PasswordBasedCryptoManager::at(credentials_path.clone());
+        std::thread::spawn(move || {
+          crypto.store_secret("ci", &format!("token{i}"), "value", password).unwrap();
+        })
+      })
+      .collect();
+    for writer in writers {
+      writer.join().unwrap();
+    }
+
+    let credentials = PasswordBasedCryptoManager::at(credentials_path).load_credentials(password);
+    assert_eq!(credentials.unwrap()["ci"].len(), 4);
+  }
+
+  #[test]
+  fn test_truncated_vault_falls_back_to_backup() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let credentials_path = temp_dir.path().join("credentials.enc");
+    let crypto = PasswordBasedCryptoManager::at(credentials_path.clone());
+    let password = "test_password_123";
+
+    crypto.store_secret("github", "token", "abc", password).unwrap();
+    crypto.store_secret("github", "user", "octocat", password).unwrap();
+
+    let content = fs::read_to_string(&credentials_path).unwrap();
+    fs::write(&credentials_path, &content[..content.len() / 2]).unwrap();
+
+    // The backup predates the last store, but is far better than nothing
+    assert_eq!(crypto.get_secret("github", "token", password).unwrap(), "abc");
+    assert!(crypto.get_secret("github", "user", password).is_err());
+  }
+
   #[test]
   fn test_credential_retrieval_nonexistent() {
     let secrets = create_test_secrets();
@@ -979,6 +1140,21 @@ mod tests {
     assert_eq!(retrieved, value);
   }
 
+  #[test]
+  fn test_backend_selection() {
+    assert_eq!("Keychain".parse::<Backend>().unwrap(), Backend::Keychain);
+    assert_eq!("vault".parse::<Backend>().unwrap(), Backend::File);
+    assert_eq!("keeper".parse::<Backend>().unwrap(), Backend::Keeper);
+    assert!("cloud".parse::<Backend>().is_err());
+
+    temp_env::with_var("SECRETS_BACKEND", Some("keychain"), || {
+      assert_eq!(Backend::from_env().unwrap(), Backend::Keychain);
+    });
+    temp_env::with_var("SECRETS_BACKEND", None::<&str>, || {
+      assert_eq!(Backend::from_env().unwrap(), Backend::File);
+    });
+  }
+
   #[test]
   fn test_delete_nonexistent_credential() {
     let password = "test_password_123";
@@ -0,0 +1,233 @@
+//! Brute-force protection for master password unlocks
+//!
+//! Consecutive failures are persisted next to the vault so restarting the keeper
+//! does not reset them. After a few free attempts every further failure doubles the
+//! wait before the next attempt is accepted, and each one is written to the audit log.
+//!
+//! [`attempt`] holds a lock from the check to the recorded outcome, so parallel
+//! attempts (several keeper connections or processes) are verified one at a time and
+//! each one sees the failures recorded before it.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::audit;
+use crate::vaultfile::{self, VaultLock};
+
+/// Failures allowed before any delay applies (typos happen)
+pub const FREE_ATTEMPTS: u32 = 3;
+
+/// Longest wait between attempts
+pub const MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Consecutive failed unlocks for one vault
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockoutState {
+  pub failures: u32,
+  /// Unix seconds of the most recent failure
+  pub last_failure: u64,
+}
+
+impl LockoutState {
+  /// Wait required after the most recent failure before another attempt
+  pub fn delay(&self) -> Duration {
+    if self.failures < FREE_ATTEMPTS {
+      return Duration::ZERO;
+    }
+    let exponent = (self.failures - FREE_ATTEMPTS).min(31);
+    Duration::from_secs(1u64 << exponent).min(MAX_DELAY)
+  }
+
+  /// Time left before another attempt is accepted
+  pub fn remaining(&self, now: u64) -> Duration {
+    let ready_at = self.last_failure.saturating_add(self.delay().as_secs());
+    Duration::from_secs(ready_at.saturating_sub(now))
+  }
+}
+
+/// Lockout state file for the vault at `cred_path`
+pub fn state_path(cred_path: &Path) -> PathBuf {
+  cred_path.with_extension("lockout")
+}
+
+/// Load the persisted state, treating a missing file as a clean slate
+pub fn load(cred_path: &Path) -> Result<LockoutState> {
+  let path = state_path(cred_path);
+  if !path.exists() {
+    return Ok(LockoutState::default());
+  }
+  let content = fs::read_to_string(&path)?;
+  serde_json::from_str(&content).map_err(|e| anyhow!("invalid lockout state: {e}"))
+}
+
+fn save(cred_path: &Path, state: &LockoutState) -> Result<()> {
+  vaultfile::write_atomic(&state_path(cred_path), &serde_json::to_string(state)?)
+}
+
+/// Run one unlock attempt, refusing it while the vault is cooling down
+///
+/// `verify` failing counts as a failed attempt; succeeding clears the count. Problems
+/// recording the outcome are only warned about, so they never decide the attempt.
+pub fn attempt(cred_path: &Path, verify: impl FnOnce() -> Result<()>) -> Result<()> {
+  let _lock = VaultLock::acquire(&state_path(cred_path))?;
+  check(cred_path)?;
+
+  if let Err(e) = verify() {
+    if let Err(record_err) = record_failure(cred_path) {
+      bentley::warn!(&format!("failed to record unlock attempt: {record_err}"));
+    }
+    return Err(e);
+  }
+
+  if let Err(e) = record_success(cred_path) {
+    bentley::warn!(&format!("failed to reset lockout state: {e}"));
+  }
+  Ok(())
+}
+
+/// Refuse an attempt while the vault is still cooling down
+fn check(cred_path: &Path) -> Result<()> {
+  let state = load(cred_path)?;
+  let remaining = state.remaining(audit::now());
+  if remaining.is_zero() {
+    return Ok(());
+  }
+
+  let message = format!(
+    "unlock refused: {} failed attempts, next attempt allowed in {}s",
+    state.failures,
+    remaining.as_secs()
+  );
+  audit_warn(cred_path, &message);
+  Err(anyhow!("too many failed attempts, try again in {}s", remaining.as_secs()))
+}
+
+/// Count a failed attempt and start the next delay
+fn record_failure(cred_path: &Path) -> Result<LockoutState> {
+  let mut state = load(cred_path)?;
+  state.failures = state.failures.saturating_add(1);
+  state.last_failure = audit::now();
+  save(cred_path, &state)?;
+
+  let message = format!(
+    "failed unlock attempt {} (next attempt delayed {}s)",
+    state.failures,
+    state.delay().as_secs()
+  );
+  audit_warn(cred_path, &message);
+  Ok(state)
+}
+
+/// Clear the failure count after a successful unlock
+fn record_success(cred_path: &Path) -> Result<()> {
+  let state = load(cred_path)?;
+  if state.failures == 0 {
+    return Ok(());
+  }
+
+  let path = state_path(cred_path);
+  fs::remove_file(&path)?;
+  let _ = fs::remove_file(vaultfile::backup_path(&path));
+  let message = format!("unlocked after {} failed attempts", state.failures);
+  if let Err(e) = audit::record(cred_path, "unlock", &message) {
+    bentley::warn!(&format!("failed to write audit log: {e}"));
+  }
+  Ok(())
+}
+
+/// Warn on the console and in the audit log; a broken log must not block unlocks
+fn audit_warn(cred_path: &Path, message: &str) {
+  bentley::warn!(message);
+  if let Err(e) = audit::record(cred_path, "lockout", message) {
+    bentley::warn!(&format!("failed to write audit log: {e}"));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn state(failures: u32, last_failure: u64) -> LockoutState {
+    LockoutState { failures, last_failure }
+  }
+
+  #[test]
+  fn test_delay_grows_exponentially_after_free_attempts() {
+    assert_eq!(state(0, 0).delay(), Duration::ZERO);
+    assert_eq!(state(FREE_ATTEMPTS - 1, 0).delay(), Duration::ZERO);
+    assert_eq!(state(FREE_ATTEMPTS, 0).delay(), Duration::from_secs(1));
+    assert_eq!(state(FREE_ATTEMPTS + 3, 0).delay(), Duration::from_secs(8));
+    assert_eq!(state(u32::MAX, 0).delay(), MAX_DELAY);
+  }
+
+  #[test]
+  fn test_remaining_counts_down_from_last_failure() {
+    let locked = state(FREE_ATTEMPTS + 2, 100);
+    assert_eq!(locked.remaining(100), Duration::from_secs(4));
+    assert_eq!(locked.remaining(103), Duration::from_secs(1));
+    assert_eq!(locked.remaining(200), Duration::ZERO);
+  }
+
+  #[test]
+  fn test_failures_persist_and_reset_on_success() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("credentials.enc");
+
+    for _ in 0..FREE_ATTEMPTS {
+      assert!(attempt(&vault, || Err(anyhow!("incorrect password"))).is_err());
+    }
+
+    assert_eq!(load(&vault).unwrap().failures, FREE_ATTEMPTS);
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      let mode = fs::metadata(state_path(&vault)).unwrap().permissions().mode();
+      assert_eq!(mode & 0o777, 0o600);
+    }
+    let refused = attempt(&vault, || Ok(())).unwrap_err().to_string();
+    assert!(refused.contains("too many failed attempts"), "got: {refused}");
+
+    // Wait out the delay so the correct password is checked
+    let mut state = load(&vault).unwrap();
+    state.last_failure -= state.delay().as_secs();
+    save(&vault, &state).unwrap();
+    attempt(&vault, || Ok(())).unwrap();
+    assert_eq!(load(&vault).unwrap(), LockoutState::default());
+    assert!(!state_path(&vault).exists());
+
+    let log = fs::read_to_string(audit::log_path(&vault)).unwrap();
+    assert!(log.contains("[lockout] failed unlock attempt 1"));
+    assert!(log.contains("[lockout] unlock refused"));
+    assert!(log.contains("[unlock] unlocked after 3 failed attempts"));
+  }
+
+  #[test]
+  fn test_parallel_attempts_are_counted_one_at_a_time() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("credentials.enc");
+
+    let attempts: Vec<_> = (0..FREE_ATTEMPTS + 3)
+      .map(|_| {
+        let vault = vault.clone();
+        std::thread::spawn(move || {
+          attempt(&vault, || {
+            std::thread::sleep(Duration::from_millis(50));
+            Err(anyhow!("incorrect password"))
+          })
+          .unwrap_err()
+          .to_string()
+        })
+      })
+      .collect();
+    let errors: Vec<String> = attempts.into_iter().map(|t| t.join().unwrap()).collect();
+
+    // Only the free attempts reach verification; the rest see their failures first
+    let refused = errors.iter().filter(|e| e.contains("too many failed attempts")).count();
+    assert_eq!(refused, 3, "got: {errors:?}");
+    assert_eq!(load(&vault).unwrap().failures, FREE_ATTEMPTS);
+  }
+}
@@ -5,5 +5,5 @@ use secrets::cli::{handle_command, Cli};
 #[tokio::main]
 async fn main() -> Result<()> {
   let cli = Cli::parse();
-  handle_command(cli.command).await
+  handle_command(cli.command, cli.output, cli.quiet).await
 }
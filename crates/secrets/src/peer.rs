@@ -0,0 +1,193 @@
+//! Who may talk to the keeper
+//!
+//! The keeper hands out the master password, so each connection is checked against
+//! the credentials the kernel reports for the other end of the socket. The peer must
+//! run as the keeper's own user and, when `keeper.json` beside the vault lists
+//! `allowed_executables`, be one of those programs. Anyone else is turned away before
+//! their request is read.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Keeper settings read from `keeper.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeeperConfig {
+  /// Programs allowed to connect; any program of the keeper's user when empty
+  #[serde(default)]
+  pub allowed_executables: Vec<PathBuf>,
+}
+
+/// Keeper settings for the vault at `cred_path`
+pub fn config_path(cred_path: &Path) -> PathBuf {
+  cred_path.parent().unwrap_or_else(|| Path::new(".")).join("keeper.json")
+}
+
+/// The process on the other end of a keeper connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+  pub uid: u32,
+  pub pid: Option<i32>,
+  /// Program the peer is running, when the platform can tell
+  pub executable: Option<PathBuf>,
+}
+
+impl Peer {
+  /// Credentials of the process connected to `stream`
+  pub fn of(stream: &tokio::net::UnixStream) -> std::io::Result<Self> {
+    let cred = stream.peer_cred()?;
+    let pid = cred.pid();
+    Ok(Self { uid: cred.uid(), pid, executable: pid.and_then(executable) })
+  }
+
+  /// Short description for logs, e.g. `pid 4242 (/usr/bin/secrets), uid 1000`
+  pub fn describe(&self) -> String {
+    let pid = self.pid.map_or_else(|| "unknown pid".to_string(), |pid| format!("pid {pid}"));
+    match &self.executable {
+      Some(exe) => format!("{pid} ({}), uid {}", exe.display(), self.uid),
+      None => format!("{pid}, uid {}", self.uid),
+    }
+  }
+}
+
+/// Which peers the keeper serves
+#[derive(Debug, Clone)]
+pub struct PeerPolicy {
+  uid: u32,
+  allowed_executables: Vec<PathBuf>,
+}
+
+impl Default for PeerPolicy {
+  fn default() -> Self {
+    Self::for_uid(current_uid())
+  }
+}
+
+impl PeerPolicy {
+  /// Serve any program running as `uid`
+  pub fn for_uid(uid: u32) -> Self {
+    Self { uid, allowed_executables: Vec::new() }
+  }
+
+  /// Policy for the current user with the allowlist from `keeper.json`, if there is one
+  pub fn load(cred_path: &Path) -> Result<Self> {
+    let path = config_path(cred_path);
+    let config = if path.exists() {
+      let content = fs::read_to_string(&path)?;
+      serde_json::from_str::<KeeperConfig>(&content)
+        .map_err(|e| anyhow!("invalid keeper config {}: {e}", path.display()))?
+    } else {
+      KeeperConfig::default()
+    };
+    Ok(Self::default().allowing(config.allowed_executables))
+  }
+
+  /// Only serve these programs; symlinks are resolved so they match what the kernel reports
+  pub fn allowing(mut self, executables: Vec<PathBuf>) -> Self {
+    self.allowed_executables =
+      executables.into_iter().map(|exe| fs::canonicalize(&exe).unwrap_or(exe)).collect();
+    self
+  }
+
+  pub fn allowed_executables(&self) -> &[PathBuf] {
+    &self.allowed_executables
+  }
+
+  /// Why `peer` may not connect, if it may not
+  pub fn check(&self, peer: &Peer) -> std::result::Result<(), String> {
+    if peer.uid != self.uid {
+      return Err(format!("denied {}: not the keeper's user (uid {})", peer.describe(), self.uid));
+    }
+    if self.allowed_executables.is_empty() {
+      return Ok(());
+    }
+    match &peer.executable {
+      Some(exe) if self.allowed_executables.contains(exe) => Ok(()),
+      Some(_) => Err(format!("denied {}: executable not in allowed_executables", peer.describe())),
+      None => Err(format!("denied {}: executable could not be determined", peer.describe())),
+    }
+  }
+}
+
+/// User id of this process
+pub fn current_uid() -> u32 {
+  // SAFETY: getuid has no preconditions and cannot fail
+  unsafe { libc::getuid() }
+}
+
+/// Program a process is running
+#[cfg(target_os = "linux")]
+fn executable(pid: i32) -> Option<PathBuf> {
+  fs::read_link(format!("/proc/{pid}/exe")).ok()
+}
+
+/// Program a process is running
+#[cfg(target_os = "macos")]
+fn executable(pid: i32) -> Option<PathBuf> {
+  use std::os::unix::ffi::OsStrExt;
+
+  let mut buf = vec![0u8; libc::PROC_PIDPATHINFO_MAXSIZE as usize];
+  // SAFETY: the buffer is valid for writes of the length passed
+  let len = unsafe { libc::proc_pidpath(pid, buf.as_mut_ptr().cast(), buf.len() as u32) };
+  if len <= 0 {
+    return None;
+  }
+  buf.truncate(len as usize);
+  Some(PathBuf::from(std::ffi::OsStr::from_bytes(&buf)))
+}
+
+/// Program a process is running
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn executable(_pid: i32) -> Option<PathBuf> {
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn peer(uid: u32, executable: Option<&str>) -> Peer {
+    Peer { uid, pid: Some(4242), executable: executable.map(PathBuf::from) }
+  }
+
+  #[test]
+  fn test_check_requires_same_user_and_allowed_executable() {
+    let policy = PeerPolicy::for_uid(1000);
+    assert!(policy.check(&peer(1000, None)).is_ok());
+    let other = policy.check(&peer(0, Some("/usr/bin/secrets"))).unwrap_err();
+    assert!(other.contains("pid 4242 (/usr/bin/secrets), uid 0"), "{other}");
+
+    let policy = policy.allowing(vec![PathBuf::from("/nonexistent/secrets")]);
+    assert!(policy.check(&peer(1000, Some("/nonexistent/secrets"))).is_ok());
+    assert!(policy.check(&peer(1000, Some("/usr/bin/python3"))).is_err());
+    assert!(policy.check(&peer(1000, None)).unwrap_err().contains("could not be determined"));
+  }
+
+  #[test]
+  fn test_load_reads_allowlist_beside_the_vault() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("credentials.enc");
+    assert!(PeerPolicy::load(&vault).unwrap().allowed_executables().is_empty());
+
+    fs::write(config_path(&vault), r#"{"allowed_executables": ["/nonexistent/blizz"]}"#).unwrap();
+    let policy = PeerPolicy::load(&vault).unwrap();
+    assert_eq!(policy.allowed_executables(), [PathBuf::from("/nonexistent/blizz")]);
+
+    fs::write(config_path(&vault), "{").unwrap();
+    assert!(PeerPolicy::load(&vault).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_peer_of_socket_is_this_process() {
+    let (ours, theirs) = tokio::net::UnixStream::pair().unwrap();
+    let peer = Peer::of(&ours).unwrap();
+    drop(theirs);
+
+    assert_eq!(peer.uid, current_uid());
+    assert!(PeerPolicy::default().check(&peer).is_ok());
+    #[cfg(target_os = "linux")]
+    assert_eq!(peer.executable, std::env::current_exe().ok());
+  }
+}
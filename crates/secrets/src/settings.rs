@@ -0,0 +1,24 @@
+//! Settings secrets reads from `$BLIZZ_HOME/config.yaml` or the environment
+//!
+//! Passwords are deliberately not settings: they only ever come from the environment
+//! or a prompt, never from a file.
+
+use bentley::config::Key;
+
+pub const BACKEND: Key = Key::new(
+  "secrets.backend",
+  "SECRETS_BACKEND",
+  "file",
+  "Where the vault key lives: file, keychain or keeper",
+);
+pub const IDLE_TIMEOUT_SECS: Key = Key::new(
+  "secrets.idle_timeout_secs",
+  "SECRETS_IDLE_TIMEOUT_SECS",
+  "",
+  "Seconds the keeper holds the password unused; none keeps it until the keeper stops",
+);
+pub const QUIET: Key =
+  Key::new("secrets.quiet", "SECRETS_QUIET", "false", "Skip banners and progress output");
+
+/// Every setting secrets reads, in the order `blizz config list` shows them
+pub const KEYS: &[Key] = &[BACKEND, IDLE_TIMEOUT_SECS, QUIET];
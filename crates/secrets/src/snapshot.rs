@@ -0,0 +1,99 @@
+//! Short-lived env files for tools that can only read environment files
+//!
+//! `secrets env --group github --file .env.agent` writes a group's secrets, named
+//! as `secrets exec` would pass them, to a file only the owner can read. The file
+//! carries its own expiry in `SECRETS_SNAPSHOT_EXPIRES_AT`, and a detached `secrets`
+//! process shreds it once the TTL runs out. `secrets env --revoke` shreds it sooner.
+
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use crate::envfile::{self, EnvFormat};
+use crate::exec::env_name;
+use crate::keys;
+
+/// Variable holding the unix time a snapshot expires at
+pub const EXPIRES_VAR: &str = "SECRETS_SNAPSHOT_EXPIRES_AT";
+
+/// Snapshot content for `group`, expiring at `expires_at` (unix seconds)
+pub fn render(
+  group: &str,
+  secrets: &HashMap<String, String>,
+  format: EnvFormat,
+  expires_at: u64,
+) -> Result<String> {
+  let mut entries: BTreeMap<String, String> =
+    secrets.iter().map(|(key, value)| (env_name(group, key), value.clone())).collect();
+  if entries.contains_key(EXPIRES_VAR) {
+    return Err(anyhow!("group {group} has a secret that would be named {EXPIRES_VAR}"));
+  }
+  entries.insert(EXPIRES_VAR.to_string(), expires_at.to_string());
+
+  let content = envfile::render(&entries, format)?;
+  Ok(match format {
+    EnvFormat::Dotenv => format!(
+      "# Snapshot of the {group} secrets, shredded at unix time {expires_at}\n\
+       # Revoke sooner with: secrets env --revoke --file <this file>\n{content}"
+    ),
+    EnvFormat::Json => content,
+  })
+}
+
+/// Names of the variables in a snapshot of `secrets`, in order
+pub fn names(group: &str, secrets: &HashMap<String, String>) -> Vec<String> {
+  let mut names: Vec<String> = secrets.keys().map(|key| env_name(group, key)).collect();
+  names.sort();
+  names
+}
+
+/// Shred the snapshot at `path`, refusing files that aren't snapshots
+pub fn revoke(path: &Path) -> Result<()> {
+  let content =
+    fs::read_to_string(path).map_err(|e| anyhow!("no snapshot at {}: {e}", path.display()))?;
+  if !content.contains(EXPIRES_VAR) {
+    return Err(anyhow!("{} is not a secrets snapshot; refusing to shred it", path.display()));
+  }
+  keys::shred(path)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn secrets() -> HashMap<String, String> {
+    HashMap::from([("token".to_string(), "s3cret value".to_string())])
+  }
+
+  #[test]
+  fn test_render_embeds_expiry() {
+    let dotenv = render("github", &secrets(), EnvFormat::Dotenv, 1700000000).unwrap();
+    assert!(dotenv.starts_with("# Snapshot of the github secrets"));
+    let entries = envfile::parse(&dotenv, EnvFormat::Dotenv).unwrap();
+    assert_eq!(entries["GITHUB_TOKEN"], "s3cret value");
+    assert_eq!(entries[EXPIRES_VAR], "1700000000");
+
+    let json = render("github", &secrets(), EnvFormat::Json, 1700000000).unwrap();
+    let entries = envfile::parse(&json, EnvFormat::Json).unwrap();
+    assert_eq!(entries.keys().collect::<Vec<_>>(), ["GITHUB_TOKEN", EXPIRES_VAR]);
+    assert_eq!(names("github", &secrets()), ["GITHUB_TOKEN"]);
+  }
+
+  #[test]
+  fn test_revoke_only_shreds_snapshots() {
+    let temp_dir = TempDir::new().unwrap();
+    let snapshot = temp_dir.path().join(".env.agent");
+    let content = render("github", &secrets(), EnvFormat::Dotenv, 1).unwrap();
+    keys::write(&snapshot, &content, false).unwrap();
+    let other = temp_dir.path().join(".env");
+    fs::write(&other, "TOKEN=mine\n").unwrap();
+
+    assert!(revoke(&other).is_err());
+    assert!(other.exists());
+    revoke(&snapshot).unwrap();
+    assert!(!snapshot.exists());
+    assert!(revoke(&snapshot).is_err());
+  }
+}
@@ -0,0 +1,185 @@
+//! Service specs registered by the user, kept beside the vault
+//!
+//! A spec names a group and the secrets it needs, so `secrets verify` and
+//! `secrets setup` work for any service, not just the built-in ones. Specs only
+//! hold key names and descriptions, never values, so they are stored unencrypted.
+//! A registered spec takes precedence over a built-in one with the same name.
+
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{services, usage, CredentialSpec, ServiceConfig};
+
+type Credentials = HashMap<String, HashMap<String, String>>;
+
+/// Registered specs for the vault at `cred_path`
+pub fn specs_path(cred_path: &Path) -> PathBuf {
+  cred_path.parent().unwrap_or_else(|| Path::new(".")).join("services.json")
+}
+
+/// Load every registered spec, keyed by service name
+pub fn load(cred_path: &Path) -> Result<BTreeMap<String, ServiceConfig>> {
+  let path = specs_path(cred_path);
+  if !path.exists() {
+    return Ok(BTreeMap::new());
+  }
+  let content = fs::read_to_string(&path)?;
+  serde_json::from_str(&content).map_err(|e| anyhow!("invalid service specs: {e}"))
+}
+
+fn save(cred_path: &Path, specs: &BTreeMap<String, ServiceConfig>) -> Result<()> {
+  let path = specs_path(cred_path);
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(path, serde_json::to_string_pretty(specs)?)?;
+  Ok(())
+}
+
+/// Build a spec from the keys a service requires and the ones it can use
+pub fn build(
+  name: &str,
+  description: Option<&str>,
+  required: &[String],
+  optional: &[String],
+) -> Result<ServiceConfig> {
+  usage::check_group(name)?;
+  if name.trim().is_empty() {
+    return Err(anyhow!("service name cannot be empty"));
+  }
+  if required.is_empty() && optional.is_empty() {
+    return Err(anyhow!("a spec needs at least one --require or --optional key"));
+  }
+
+  let mut credentials: Vec<CredentialSpec> = Vec::new();
+  let keys = required.iter().map(|k| (k, true)).chain(optional.iter().map(|k| (k, false)));
+  for (key, is_required) in keys {
+    let key = key.trim();
+    if key.is_empty() {
+      return Err(anyhow!("secret names cannot be empty"));
+    }
+    if credentials.iter().any(|spec| spec.key == key) {
+      return Err(anyhow!("'{key}' is listed more than once"));
+    }
+    credentials.push(CredentialSpec {
+      key: key.to_string(),
+      description: key.replace(['_', '-'], " "),
+      example: None,
+      is_required,
+    });
+  }
+
+  Ok(ServiceConfig {
+    name: name.to_string(),
+    description: description.map_or_else(|| format!("{name} credentials"), str::to_string),
+    required_credentials: credentials,
+  })
+}
+
+/// Register a spec, replacing any earlier spec for the same service
+pub fn add(cred_path: &Path, spec: ServiceConfig) -> Result<bool> {
+  let mut specs = load(cred_path)?;
+  let replaced = specs.insert(spec.name.clone(), spec).is_some();
+  save(cred_path, &specs)?;
+  Ok(replaced)
+}
+
+/// Remove a registered spec
+pub fn remove(cred_path: &Path, name: &str) -> Result<()> {
+  let mut specs = load(cred_path)?;
+  if specs.remove(name).is_none() {
+    return Err(anyhow!("no registered spec for '{name}'"));
+  }
+  save(cred_path, &specs)
+}
+
+/// Specs that ship with secrets
+pub fn builtin(name: &str) -> Option<ServiceConfig> {
+  match name.to_lowercase().as_str() {
+    "github" => Some(services::github()),
+    "notion" => Some(services::notion()),
+    _ => None,
+  }
+}
+
+/// Find the spec for a service, preferring a registered one
+pub fn resolve(cred_path: &Path, name: &str) -> Result<ServiceConfig> {
+  if let Some(spec) = load(cred_path)?.remove(name) {
+    return Ok(spec);
+  }
+  builtin(name).ok_or_else(|| {
+    anyhow!("no spec for '{name}'; register one with 'secrets spec add {name} --require <key>'")
+  })
+}
+
+/// Required secrets of `spec` missing from the vault
+pub fn missing(spec: &ServiceConfig, credentials: &Credentials) -> Vec<String> {
+  let stored = credentials.get(&spec.name);
+  spec
+    .required_credentials
+    .iter()
+    .filter(|cred| cred.is_required)
+    .filter(|cred| !stored.is_some_and(|group| group.contains_key(&cred.key)))
+    .map(|cred| cred.key.clone())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn keys(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+  }
+
+  #[test]
+  fn test_build_rejects_bad_specs() {
+    assert!(build("myservice", None, &[], &[]).is_err());
+    assert!(build("myservice", None, &keys(&["token", "token"]), &[]).is_err());
+    assert!(build("myservice", None, &keys(&["token"]), &keys(&["token"])).is_err());
+    assert!(build(".usage", None, &keys(&["token"]), &[]).is_err());
+
+    let spec = build("myservice", None, &keys(&["api_token", "url"]), &keys(&["region"])).unwrap();
+    let required: Vec<(&str, bool)> =
+      spec.required_credentials.iter().map(|c| (c.key.as_str(), c.is_required)).collect();
+    assert_eq!(required, [("api_token", true), ("url", true), ("region", false)]);
+    assert_eq!(spec.required_credentials[0].description, "api token");
+  }
+
+  #[test]
+  fn test_registered_specs_round_trip_and_override_builtins() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("credentials.enc");
+
+    assert_eq!(resolve(&vault, "github").unwrap().required_credentials[0].key, "token");
+    assert!(resolve(&vault, "myservice").is_err());
+
+    let spec = build("myservice", Some("My service"), &keys(&["token", "url"]), &[]).unwrap();
+    assert!(!add(&vault, spec).unwrap());
+    let github = build("github", None, &keys(&["app_id"]), &[]).unwrap();
+    assert!(!add(&vault, github).unwrap());
+
+    assert_eq!(resolve(&vault, "myservice").unwrap().description, "My service");
+    assert_eq!(resolve(&vault, "github").unwrap().required_credentials[0].key, "app_id");
+
+    remove(&vault, "github").unwrap();
+    assert_eq!(resolve(&vault, "github").unwrap().required_credentials[0].key, "token");
+    assert!(remove(&vault, "github").is_err());
+  }
+
+  #[test]
+  fn test_missing_lists_only_absent_required_keys() {
+    let spec = build("myservice", None, &keys(&["token", "url"]), &keys(&["region"])).unwrap();
+    let mut credentials = Credentials::new();
+    assert_eq!(missing(&spec, &credentials), keys(&["token", "url"]));
+
+    credentials
+      .entry("myservice".to_string())
+      .or_default()
+      .insert("url".to_string(), "https://example.com".to_string());
+    assert_eq!(missing(&spec, &credentials), keys(&["token"]));
+  }
+}
@@ -0,0 +1,238 @@
+//! systemd integration for the keeper daemon
+//!
+//! Covers the three pieces systemd needs from us: picking up a socket passed
+//! via socket activation (`LISTEN_FDS`), reporting readiness over
+//! `NOTIFY_SOCKET`, and generating user-level unit files so systemd can own
+//! the keeper socket and start the daemon on first access.
+
+use anyhow::{anyhow, Result};
+use std::env;
+use std::fs;
+use std::os::unix::net::{UnixDatagram, UnixListener};
+use std::path::{Path, PathBuf};
+
+/// First file descriptor passed by systemd (see sd_listen_fds(3))
+pub const LISTEN_FDS_START: i32 = 3;
+
+/// Name shared by the generated `.socket` and `.service` units
+pub const UNIT_NAME: &str = "blizz-keeper";
+
+/// Work out how many sockets systemd passed to this process, if any.
+///
+/// The fds only belong to us when `LISTEN_PID` matches our own pid; otherwise
+/// they were meant for a parent and must be ignored.
+pub fn parse_listen_fds(listen_pid: Option<&str>, listen_fds: Option<&str>, own_pid: u32) -> usize {
+  let pid_matches = listen_pid.and_then(|pid| pid.trim().parse::<u32>().ok()) == Some(own_pid);
+  if !pid_matches {
+    return 0;
+  }
+
+  listen_fds.and_then(|fds| fds.trim().parse::<usize>().ok()).unwrap_or(0)
+}
+
+/// Take ownership of the listening socket handed over by systemd, if any.
+///
+/// The activation variables are cleared afterwards so child processes don't
+/// try to claim the same descriptors. Changing the environment races with any
+/// other thread reading it, so call this before starting a runtime or spawning
+/// threads.
+pub fn take_activated_listener() -> Result<Option<UnixListener>> {
+  let count = parse_listen_fds(
+    env::var("LISTEN_PID").ok().as_deref(),
+    env::var("LISTEN_FDS").ok().as_deref(),
+    std::process::id(),
+  );
+
+  env::remove_var("LISTEN_PID");
+  env::remove_var("LISTEN_FDS");
+  env::remove_var("LISTEN_FDNAMES");
+
+  if count == 0 {
+    return Ok(None);
+  }
+
+  if count > 1 {
+    bentley::warn!(&format!("systemd passed {count} sockets, only the first will be used"));
+  }
+
+  use std::os::fd::FromRawFd;
+  // SAFETY: systemd guarantees fd 3 is an open listening socket owned by this
+  // process when LISTEN_PID matches, and nothing else in the process has claimed it.
+  let listener = unsafe { UnixListener::from_raw_fd(LISTEN_FDS_START) };
+  listener.set_nonblocking(true)?;
+
+  Ok(Some(listener))
+}
+
+/// Send a state update (e.g. `READY=1`) to the service manager.
+///
+/// Returns `Ok(false)` when not running under systemd so callers can notify
+/// unconditionally.
+pub fn notify(state: &str) -> Result<bool> {
+  let Ok(target) = env::var("NOTIFY_SOCKET") else {
+    return Ok(false);
+  };
+
+  let socket = UnixDatagram::unbound()?;
+
+  if let Some(name) = target.strip_prefix('@') {
+    send_abstract(&socket, name, state)?;
+  } else {
+    socket.send_to(state.as_bytes(), &target)?;
+  }
+
+  Ok(true)
+}
+
+#[cfg(target_os = "linux")]
+fn send_abstract(socket: &UnixDatagram, name: &str, state: &str) -> Result<()> {
+  use std::os::linux::net::SocketAddrExt;
+  let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+  socket.send_to_addr(state.as_bytes(), &addr)?;
+  Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn send_abstract(_socket: &UnixDatagram, _name: &str, _state: &str) -> Result<()> {
+  Err(anyhow!("abstract notify sockets are only supported on linux"))
+}
+
+/// Render the `.socket` unit that lets systemd own the keeper socket
+pub fn socket_unit(socket_path: &Path) -> String {
+  format!(
+    "[Unit]\n\
+     Description=Blizz keeper socket\n\
+     \n\
+     [Socket]\n\
+     ListenStream={}\n\
+     SocketMode=0600\n\
+     \n\
+     [Install]\n\
+     WantedBy=sockets.target\n",
+    socket_path.display()
+  )
+}
+
+/// Render the `.service` unit started on first access to the keeper socket
+pub fn service_unit(keeper_bin: &Path, blizz_home: &Path) -> String {
+  format!(
+    "[Unit]\n\
+     Description=Blizz keeper daemon\n\
+     Requires={UNIT_NAME}.socket\n\
+     After={UNIT_NAME}.socket\n\
+     \n\
+     [Service]\n\
+     Type=notify\n\
+     NotifyAccess=main\n\
+     ExecStart={}\n\
+     Environment=BLIZZ_HOME={}\n\
+     Restart=on-failure\n\
+     \n\
+     [Install]\n\
+     Also={UNIT_NAME}.socket\n",
+    keeper_bin.display(),
+    blizz_home.display()
+  )
+}
+
+/// Default location for user-level units (`$XDG_CONFIG_HOME/systemd/user`)
+pub fn user_unit_dir() -> Result<PathBuf> {
+  let config = dirs::config_dir().ok_or_else(|| anyhow!("failed to determine config directory"))?;
+  Ok(config.join("systemd").join("user"))
+}
+
+/// Write the socket and service units into `unit_dir`, returning the written paths
+pub fn install_user_units(
+  unit_dir: &Path,
+  socket_path: &Path,
+  keeper_bin: &Path,
+  blizz_home: &Path,
+) -> Result<Vec<PathBuf>> {
+  fs::create_dir_all(unit_dir)?;
+
+  let socket_file = unit_dir.join(format!("{UNIT_NAME}.socket"));
+  let service_file = unit_dir.join(format!("{UNIT_NAME}.service"));
+
+  fs::write(&socket_file, socket_unit(socket_path))?;
+  fs::write(&service_file, service_unit(keeper_bin, blizz_home))?;
+
+  Ok(vec![socket_file, service_file])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_parse_listen_fds_matching_pid() {
+    assert_eq!(parse_listen_fds(Some("42"), Some("1"), 42), 1);
+    assert_eq!(parse_listen_fds(Some("42"), Some("3"), 42), 3);
+  }
+
+  #[test]
+  fn test_parse_listen_fds_ignores_other_pids() {
+    assert_eq!(parse_listen_fds(Some("41"), Some("1"), 42), 0);
+    assert_eq!(parse_listen_fds(None, Some("1"), 42), 0);
+  }
+
+  #[test]
+  fn test_parse_listen_fds_invalid_values() {
+    assert_eq!(parse_listen_fds(Some("abc"), Some("1"), 42), 0);
+    assert_eq!(parse_listen_fds(Some("42"), Some("many"), 42), 0);
+    assert_eq!(parse_listen_fds(Some("42"), None, 42), 0);
+  }
+
+  #[test]
+  fn test_notify_without_notify_socket() {
+    temp_env::with_var("NOTIFY_SOCKET", None::<&str>, || {
+      assert!(!notify("READY=1").unwrap());
+    });
+  }
+
+  #[test]
+  fn test_notify_sends_state_to_socket() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("notify.sock");
+    let receiver = UnixDatagram::bind(&path).unwrap();
+
+    temp_env::with_var("NOTIFY_SOCKET", Some(path.to_str().unwrap()), || {
+      assert!(notify("READY=1").unwrap());
+    });
+
+    let mut buf = [0u8; 32];
+    let len = receiver.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"READY=1");
+  }
+
+  #[test]
+  fn test_units_reference_paths() {
+    let socket = socket_unit(Path::new("/home/me/.blizz/persistent/keeper/keeper.sock"));
+    assert!(socket.contains("ListenStream=/home/me/.blizz/persistent/keeper/keeper.sock"));
+    assert!(socket.contains("SocketMode=0600"));
+
+    let service = service_unit(Path::new("/usr/local/bin/keeper"), Path::new("/home/me/.blizz"));
+    assert!(service.contains("Type=notify"));
+    assert!(service.contains("ExecStart=/usr/local/bin/keeper"));
+    assert!(service.contains("Environment=BLIZZ_HOME=/home/me/.blizz"));
+    assert!(service.contains(&format!("Requires={UNIT_NAME}.socket")));
+  }
+
+  #[test]
+  fn test_install_user_units_writes_both_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let unit_dir = temp_dir.path().join("systemd").join("user");
+
+    let written = install_user_units(
+      &unit_dir,
+      Path::new("/tmp/keeper.sock"),
+      Path::new("/usr/bin/keeper"),
+      Path::new("/tmp"),
+    )
+    .unwrap();
+
+    assert_eq!(written.len(), 2);
+    assert!(unit_dir.join(format!("{UNIT_NAME}.socket")).exists());
+    assert!(unit_dir.join(format!("{UNIT_NAME}.service")).exists());
+  }
+}
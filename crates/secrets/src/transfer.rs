@@ -0,0 +1,194 @@
+//! Portable vault exports for moving secrets between machines
+//!
+//! The vault key is bound to the machine it was created on, so an exported vault
+//! is re-encrypted under a transfer passphrase instead: Argon2id over the
+//! passphrase alone, then AES-256-GCM with the file header as associated data.
+//! A SHA-256 digest of the encrypted payload is stored alongside it so a damaged
+//! file can be told apart from a wrong passphrase.
+
+use aes_gcm::{
+  aead::{Aead, AeadCore, KeyInit, OsRng as AeadOsRng, Payload},
+  Aes256Gcm, Key, Nonce,
+};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::encryption::EncryptionManager;
+
+type Credentials = HashMap<String, HashMap<String, String>>;
+
+/// Identifies a secrets export, checked before anything is decrypted
+pub const FORMAT: &str = "secrets-export";
+
+/// Current export layout
+pub const VERSION: u32 = 1;
+
+/// Stands in for the machine key so the derived key only depends on the passphrase
+const TRANSFER_CONTEXT: &[u8] = b"secrets-transfer-v1";
+
+/// An exported vault as written to disk; binary fields are base64
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferFile {
+  pub format: String,
+  pub version: u32,
+  /// Unix seconds when the export was made
+  pub created_at: u64,
+  pub salt: String,
+  pub nonce: String,
+  pub data: String,
+  /// Hex SHA-256 of the encrypted data
+  pub checksum: String,
+}
+
+impl TransferFile {
+  /// Encrypt credentials under a transfer passphrase
+  pub fn seal(credentials: &Credentials, passphrase: &str, created_at: u64) -> Result<Self> {
+    let mut salt = vec![0u8; 16];
+    rand::rng().fill_bytes(&mut salt);
+    let nonce = Aes256Gcm::generate_nonce(&mut AeadOsRng);
+
+    let mut file = Self {
+      format: FORMAT.to_string(),
+      version: VERSION,
+      created_at,
+      salt: STANDARD.encode(&salt),
+      nonce: STANDARD.encode(nonce),
+      data: String::new(),
+      checksum: String::new(),
+    };
+
+    let plaintext = serde_json::to_vec(credentials)?;
+    let cipher = cipher(passphrase, &salt)?;
+    let aad = file.header();
+    let data = cipher
+      .encrypt(&nonce, Payload { msg: &plaintext, aad: aad.as_bytes() })
+      .map_err(|e| anyhow!("encryption failed: {e}"))?;
+
+    file.checksum = checksum(&data);
+    file.data = STANDARD.encode(data);
+    Ok(file)
+  }
+
+  /// Verify and decrypt an export
+  pub fn open(&self, passphrase: &str) -> Result<Credentials> {
+    if self.format != FORMAT {
+      return Err(anyhow!("not a secrets export"));
+    }
+    if self.version != VERSION {
+      return Err(anyhow!("unsupported export version {} (expected {VERSION})", self.version));
+    }
+
+    let corrupted = |_| anyhow!("export file is corrupted");
+    let salt = STANDARD.decode(&self.salt).map_err(corrupted)?;
+    let nonce = STANDARD.decode(&self.nonce).map_err(corrupted)?;
+    let data = STANDARD.decode(&self.data).map_err(corrupted)?;
+    if nonce.len() != 12 || checksum(&data) != self.checksum {
+      return Err(anyhow!("export file is corrupted"));
+    }
+
+    let aad = self.header();
+    let plaintext = cipher(passphrase, &salt)?
+      .decrypt(Nonce::from_slice(&nonce), Payload { msg: &data, aad: aad.as_bytes() })
+      .map_err(|_| anyhow!("incorrect transfer passphrase or tampered export"))?;
+
+    serde_json::from_slice(&plaintext).map_err(|e| anyhow!("export holds invalid data: {e}"))
+  }
+
+  pub fn load(path: &Path) -> Result<Self> {
+    let content =
+      fs::read_to_string(path).map_err(|e| anyhow!("failed to read {}: {e}", path.display()))?;
+    serde_json::from_str(content.trim())
+      .map_err(|e| anyhow!("{} is not a secrets export: {e}", path.display()))
+  }
+
+  /// Write the export, readable by the owner only
+  pub fn save(&self, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+  }
+
+  /// Fields bound to the ciphertext, so none can be altered without detection
+  fn header(&self) -> String {
+    format!("{}:{}:{}:{}", self.format, self.version, self.created_at, self.salt)
+  }
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+  let key = EncryptionManager::derive_key(passphrase, TRANSFER_CONTEXT, salt)?;
+  Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+fn checksum(data: &[u8]) -> String {
+  Sha256::digest(data).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn credentials() -> Credentials {
+    let mut credentials = Credentials::new();
+    credentials
+      .entry("github".to_string())
+      .or_default()
+      .insert("token".to_string(), "ghp_example".to_string());
+    credentials
+  }
+
+  #[test]
+  fn test_round_trip_through_a_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("vault.kexport");
+
+    TransferFile::seal(&credentials(), "correct horse", 1_700_000_000)
+      .unwrap()
+      .save(&path)
+      .unwrap();
+    let opened = TransferFile::load(&path).unwrap().open("correct horse").unwrap();
+    assert_eq!(opened, credentials());
+  }
+
+  #[test]
+  fn test_wrong_passphrase_and_corruption_are_distinguished() {
+    let file = TransferFile::seal(&credentials(), "correct horse", 1_700_000_000).unwrap();
+
+    let wrong = file.open("battery staple").unwrap_err();
+    assert!(wrong.to_string().contains("incorrect transfer passphrase"));
+
+    let mut damaged = file.clone();
+    let mut data = STANDARD.decode(&damaged.data).unwrap();
+    data[0] ^= 0xff;
+    damaged.data = STANDARD.encode(data);
+    assert!(damaged.open("correct horse").unwrap_err().to_string().contains("corrupted"));
+  }
+
+  #[test]
+  fn test_header_is_authenticated() {
+    let file = TransferFile::seal(&credentials(), "correct horse", 1_700_000_000).unwrap();
+
+    let mut backdated = file.clone();
+    backdated.created_at -= 1;
+    assert!(backdated.open("correct horse").is_err());
+
+    let mut future = file;
+    future.version = VERSION + 1;
+    assert!(future.open("correct horse").unwrap_err().to_string().contains("unsupported"));
+  }
+}
@@ -0,0 +1,218 @@
+//! Per-secret access counters kept inside the encrypted vault
+//!
+//! Usage lives in a reserved group of the decrypted credentials map, so it is
+//! encrypted with the secrets and survives every command that rewrites the vault
+//! without them having to know about it. Entries are keyed `<group>/<name>` and
+//! hold a small JSON record; secrets stored before tracking existed have none.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Group holding usage records (never a real secret group)
+pub const USAGE_GROUP: &str = ".usage";
+
+type Credentials = HashMap<String, HashMap<String, String>>;
+
+/// Access record for one secret (timestamps are unix seconds)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+  /// Times the secret has been retrieved
+  pub count: u64,
+  /// Most recent retrieval
+  pub last_used: Option<u64>,
+  /// When the secret was last stored
+  pub stored_at: Option<u64>,
+}
+
+impl Usage {
+  /// Most recent time the secret was touched, if known
+  pub fn last_activity(&self) -> Option<u64> {
+    self.last_used.max(self.stored_at)
+  }
+}
+
+/// Whether a group name is reserved for vault bookkeeping
+pub fn is_reserved(group: &str) -> bool {
+  group == USAGE_GROUP || group == crate::expiry::META_GROUP || group == crate::keys::KEYS_GROUP
+}
+
+/// Refuse to store secrets in a reserved group
+pub fn check_group(group: &str) -> Result<()> {
+  if is_reserved(group) {
+    return Err(anyhow!("group name '{group}' is reserved"));
+  }
+  Ok(())
+}
+
+fn key(group: &str, name: &str) -> String {
+  format!("{group}/{name}")
+}
+
+/// Usage record for a secret, if it has been tracked
+pub fn get(credentials: &Credentials, group: &str, name: &str) -> Option<Usage> {
+  let record = credentials.get(USAGE_GROUP)?.get(&key(group, name))?;
+  serde_json::from_str(record).ok()
+}
+
+fn set(credentials: &mut Credentials, group: &str, name: &str, usage: Usage) {
+  let record = serde_json::to_string(&usage).expect("usage records always serialize");
+  credentials.entry(USAGE_GROUP.to_string()).or_default().insert(key(group, name), record);
+}
+
+/// Count a retrieval of the secret
+pub fn record_use(credentials: &mut Credentials, group: &str, name: &str, now: u64) {
+  let mut usage = get(credentials, group, name).unwrap_or_default();
+  usage.count += 1;
+  usage.last_used = Some(now);
+  set(credentials, group, name, usage);
+}
+
+/// Note that the secret was (re)stored, keeping its access count
+pub fn record_store(credentials: &mut Credentials, group: &str, name: &str, now: u64) {
+  let mut usage = get(credentials, group, name).unwrap_or_default();
+  usage.stored_at = Some(now);
+  set(credentials, group, name, usage);
+}
+
+/// Drop the usage and metadata records of secrets that no longer exist
+pub fn prune_orphans(credentials: &mut Credentials) {
+  for records_group in [USAGE_GROUP, crate::expiry::META_GROUP] {
+    let Some(mut records) = credentials.remove(records_group) else { continue };
+    records.retain(|record_key, _| {
+      record_key
+        .split_once('/')
+        .is_some_and(|(group, name)| credentials.get(group).is_some_and(|g| g.contains_key(name)))
+    });
+    if !records.is_empty() {
+      credentials.insert(records_group.to_string(), records);
+    }
+  }
+}
+
+/// Secret groups without the usage records
+pub fn secret_groups(
+  credentials: &Credentials,
+) -> impl Iterator<Item = (&String, &HashMap<String, String>)> {
+  credentials.iter().filter(|(group, _)| !is_reserved(group))
+}
+
+/// A secret that has not been used within the requested period
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleSecret {
+  pub group: String,
+  pub name: String,
+  pub usage: Option<Usage>,
+}
+
+/// Secrets whose last use (or store) is older than `max_age` seconds, sorted by name
+///
+/// Secrets with no usage record predate tracking and are always included.
+pub fn stale(credentials: &Credentials, now: u64, max_age: u64) -> Vec<StaleSecret> {
+  let cutoff = now.saturating_sub(max_age);
+  let mut stale: Vec<StaleSecret> = secret_groups(credentials)
+    .flat_map(|(group, secrets)| secrets.keys().map(move |name| (group, name)))
+    .filter_map(|(group, name)| {
+      let usage = get(credentials, group, name);
+      let fresh = usage.and_then(|u| u.last_activity()).is_some_and(|at| at >= cutoff);
+      (!fresh).then(|| StaleSecret { group: group.clone(), name: name.clone(), usage })
+    })
+    .collect();
+  stale.sort_by(|a, b| (&a.group, &a.name).cmp(&(&b.group, &b.name)));
+  stale
+}
+
+/// Parse a period such as `180d`, `6w`, `12h` or `1y` into seconds
+pub fn parse_period(value: &str) -> std::result::Result<u64, String> {
+  let value = value.trim();
+  let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+  let (digits, unit) = value.split_at(split);
+  let amount: u64 = digits.parse().map_err(|_| format!("'{value}' is not a period like 180d"))?;
+  let unit_secs = match unit {
+    "h" => 60 * 60,
+    "d" => 24 * 60 * 60,
+    "w" => 7 * 24 * 60 * 60,
+    "y" => 365 * 24 * 60 * 60,
+    _ => return Err(format!("unknown unit '{unit}' in '{value}' (use h, d, w or y)")),
+  };
+  Ok(amount * unit_secs)
+}
+
+/// Human readable age of a timestamp, e.g. `3d ago`
+pub fn describe_age(at: u64, now: u64) -> String {
+  let secs = now.saturating_sub(at);
+  match secs {
+    s if s < 60 * 60 => "just now".to_string(),
+    s if s < 24 * 60 * 60 => format!("{}h ago", s / (60 * 60)),
+    s => format!("{}d ago", s / (24 * 60 * 60)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const DAY: u64 = 24 * 60 * 60;
+
+  fn vault(entries: &[(&str, &str)]) -> Credentials {
+    let mut credentials = Credentials::new();
+    for (group, name) in entries {
+      credentials.entry(group.to_string()).or_default().insert(name.to_string(), "v".to_string());
+    }
+    credentials
+  }
+
+  #[test]
+  fn test_record_use_counts_and_timestamps() {
+    let mut credentials = vault(&[("github", "token")]);
+    assert_eq!(get(&credentials, "github", "token"), None);
+
+    record_store(&mut credentials, "github", "token", 10);
+    record_use(&mut credentials, "github", "token", 20);
+    record_use(&mut credentials, "github", "token", 30);
+
+    let usage = get(&credentials, "github", "token").unwrap();
+    assert_eq!(usage, Usage { count: 2, last_used: Some(30), stored_at: Some(10) });
+    assert_eq!(secret_groups(&credentials).count(), 1);
+  }
+
+  #[test]
+  fn test_stale_uses_last_activity_and_includes_untracked() {
+    let now = 400 * DAY;
+    let mut credentials = vault(&[("old", "token"), ("new", "token"), ("legacy", "token")]);
+    record_use(&mut credentials, "old", "token", now - 200 * DAY);
+    record_store(&mut credentials, "new", "token", now - 200 * DAY);
+    record_use(&mut credentials, "new", "token", now - DAY);
+
+    let stale = stale(&credentials, now, 180 * DAY);
+    let names: Vec<String> = stale.iter().map(|s| format!("{}/{}", s.group, s.name)).collect();
+    assert_eq!(names, vec!["legacy/token", "old/token"]);
+    assert_eq!(stale[0].usage, None);
+  }
+
+  #[test]
+  fn test_prune_orphans_drops_deleted_secrets() {
+    let mut credentials = vault(&[("github", "token")]);
+    record_use(&mut credentials, "github", "token", 1);
+    record_use(&mut credentials, "gone", "token", 1);
+
+    prune_orphans(&mut credentials);
+    assert!(get(&credentials, "github", "token").is_some());
+    assert!(get(&credentials, "gone", "token").is_none());
+
+    credentials.remove("github");
+    prune_orphans(&mut credentials);
+    assert!(!credentials.contains_key(USAGE_GROUP));
+  }
+
+  #[test]
+  fn test_parse_period() {
+    assert_eq!(parse_period("180d"), Ok(180 * DAY));
+    assert_eq!(parse_period("2w"), Ok(14 * DAY));
+    assert_eq!(parse_period("12h"), Ok(12 * 60 * 60));
+    assert_eq!(parse_period("1y"), Ok(365 * DAY));
+    assert!(parse_period("soon").is_err());
+    assert!(parse_period("5m").is_err());
+    assert!(parse_period("30").is_err());
+  }
+}
@@ -0,0 +1,158 @@
+//! Crash- and race-safe access to the vault file
+//!
+//! Writers take an advisory lock on a `.lock` file next to the vault for the
+//! whole load-modify-save cycle, so two processes storing secrets at once can't
+//! drop each other's changes. Saves go to a temp file that is renamed over the
+//! vault, and the previous vault is kept as a `.bak` to fall back on if the
+//! vault is ever found truncated.
+
+use anyhow::{anyhow, Result};
+use fs4::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Exclusive lock on a vault, released when dropped
+#[derive(Debug)]
+pub struct VaultLock {
+  file: File,
+}
+
+impl VaultLock {
+  /// Block until no other process holds the lock for `vault`
+  pub fn acquire(vault: &Path) -> Result<Self> {
+    if let Some(parent) = vault.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let lock_path = sibling(vault, "lock");
+    let file = OpenOptions::new()
+      .create(true)
+      .truncate(false)
+      .write(true)
+      .open(&lock_path)
+      .map_err(|e| anyhow!("failed to open {}: {e}", lock_path.display()))?;
+    file.lock_exclusive().map_err(|e| anyhow!("failed to lock {}: {e}", vault.display()))?;
+    Ok(Self { file })
+  }
+}
+
+impl Drop for VaultLock {
+  fn drop(&mut self) {
+    let _ = FileExt::unlock(&self.file);
+  }
+}
+
+/// Where the previous version of `vault` is kept
+pub fn backup_path(vault: &Path) -> PathBuf {
+  sibling(vault, "bak")
+}
+
+/// Replace `vault` with `content` without ever leaving a partial file behind
+///
+/// The current vault becomes the backup before the new one is renamed into place.
+pub fn write_atomic(vault: &Path, content: &str) -> Result<()> {
+  if let Some(parent) = vault.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  let temp_path = sibling(vault, "tmp");
+  let mut temp = create_private(&temp_path)?;
+  temp.write_all(content.as_bytes())?;
+  temp.sync_all()?;
+  drop(temp);
+
+  if vault.exists() {
+    let backup = backup_path(vault);
+    fs::copy(vault, &backup)?;
+    restrict_permissions(&backup)?;
+  }
+
+  fs::rename(&temp_path, vault)?;
+  restrict_permissions(vault)
+}
+
+/// `credentials.enc` -> `credentials.enc.<extension>`
+fn sibling(vault: &Path, extension: &str) -> PathBuf {
+  let mut name = vault.file_name().map(|name| name.to_os_string()).unwrap_or_default();
+  name.push(format!(".{extension}"));
+  vault.with_file_name(name)
+}
+
+fn create_private(path: &Path) -> Result<File> {
+  let mut options = OpenOptions::new();
+  options.create(true).truncate(true).write(true);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+    options.mode(0o600);
+  }
+  Ok(options.open(path)?)
+}
+
+fn restrict_permissions(path: &Path) -> Result<()> {
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = fs::metadata(path)?.permissions();
+    perms.set_mode(0o600); // Owner read/write only
+    fs::set_permissions(path, perms)?;
+  }
+  #[cfg(not(unix))]
+  let _ = path;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::{Arc, Barrier};
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_write_atomic_keeps_previous_version_as_backup() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("keeper").join("credentials.enc");
+
+    write_atomic(&vault, "first").unwrap();
+    assert!(!backup_path(&vault).exists());
+
+    write_atomic(&vault, "second").unwrap();
+    assert_eq!(fs::read_to_string(&vault).unwrap(), "second");
+    assert_eq!(fs::read_to_string(backup_path(&vault)).unwrap(), "first");
+    assert!(!sibling(&vault, "tmp").exists());
+
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      let mode = fs::metadata(backup_path(&vault)).unwrap().permissions().mode();
+      assert_eq!(mode & 0o777, 0o600);
+    }
+  }
+
+  #[test]
+  fn test_lock_serializes_read_modify_write() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = Arc::new(temp_dir.path().join("credentials.enc"));
+    write_atomic(&vault, "0").unwrap();
+
+    let barrier = Arc::new(Barrier::new(4));
+    let writers: Vec<_> = (0..4)
+      .map(|_| {
+        let (vault, barrier) = (Arc::clone(&vault), Arc::clone(&barrier));
+        std::thread::spawn(move || {
+          barrier.wait();
+          for _ in 0..25 {
+            let _lock = VaultLock::acquire(&vault).unwrap();
+            let count: u32 = fs::read_to_string(&*vault).unwrap().parse().unwrap();
+            write_atomic(&vault, &(count + 1).to_string()).unwrap();
+          }
+        })
+      })
+      .collect();
+    for writer in writers {
+      writer.join().unwrap();
+    }
+
+    assert_eq!(fs::read_to_string(&*vault).unwrap(), "100");
+  }
+}
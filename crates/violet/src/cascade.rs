@@ -0,0 +1,403 @@
+//! Per-directory configuration
+//!
+//! Besides the project config in the working directory, any directory below it
+//! may hold its own `violet.yaml` or `violet.json`. A file is analyzed with the
+//! project config overlaid by every config between the project root and the
+//! file's directory, nearest last, so a legacy area can relax its thresholds or
+//! a generated folder can add ignores without touching the rest of the repo.
+//!
+//! Nested configs merge the same way the project config merges over the
+//! defaults: thresholds and penalties they set win, ignore lists combine. Ignore
+//! globs containing a `/` are relative to the directory of the config that
+//! declares them.
+
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::config::{self, VioletConfig};
+
+/// Where a setting came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+  Defaults,
+  File(PathBuf),
+}
+
+impl fmt::Display for Source {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Source::Defaults => write!(f, "built-in defaults"),
+      Source::File(path) => write!(f, "{}", path.display()),
+    }
+  }
+}
+
+/// A config file, with its ignore globs rebased onto the project root
+#[derive(Debug)]
+struct Layer {
+  source: PathBuf,
+  config: VioletConfig,
+}
+
+impl Layer {
+  fn load(path: PathBuf, relative_dir: &Path) -> Result<Self> {
+    let mut config = config::load_config_file(&path)?;
+    if !relative_dir.as_os_str().is_empty() {
+      let prefix = relative_dir.to_string_lossy().replace('\\', "/");
+      for pattern in config.ignore_files.iter_mut().filter(|pattern| pattern.contains('/')) {
+        *pattern = format!("{prefix}/{pattern}");
+      }
+    }
+    Ok(Self { source: path, config })
+  }
+}
+
+/// A value in the effective config and the config that set it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+  pub value: f64,
+  pub source: Source,
+}
+
+/// How the effective config for one path was put together
+#[derive(Debug)]
+pub struct Explanation {
+  /// Config files that apply, outermost first
+  pub sources: Vec<PathBuf>,
+  pub config: VioletConfig,
+  /// Threshold for the path, by extension when it is a file
+  pub threshold: Setting,
+  pub penalties: Vec<(&'static str, Setting)>,
+  /// Ignore globs added by config files, rebased onto the project root
+  pub ignore_files: Vec<(String, Source)>,
+  /// The pattern that ignores the path, and the config declaring it
+  pub ignored_by: Option<(String, Source)>,
+}
+
+/// Effective configuration for every directory under a project root
+pub struct ConfigTree {
+  /// Canonical project root; None when nested configs are not looked up
+  root: Option<PathBuf>,
+  defaults: VioletConfig,
+  project: Option<Rc<Layer>>,
+  base: Rc<VioletConfig>,
+  layers: RefCell<HashMap<PathBuf, Option<Rc<Layer>>>>,
+  resolved: RefCell<HashMap<PathBuf, Rc<VioletConfig>>>,
+}
+
+impl ConfigTree {
+  /// Configuration for the project in the working directory
+  pub fn load() -> Result<Self> {
+    let root = std::env::current_dir().context("Failed to get current working directory")?;
+    Self::at(&root)
+  }
+
+  /// Configuration for the project rooted at `root`
+  pub fn at(root: &Path) -> Result<Self> {
+    let root = fs::canonicalize(root)
+      .with_context(|| format!("Failed to resolve project root {}", root.display()))?;
+    let project = match config::find_config_file(&root)? {
+      Some(path) => Some(Rc::new(
+        Layer::load(path.clone(), Path::new(""))
+          .with_context(|| format!("Failed to load project config from {}", path.display()))?,
+      )),
+      None => None,
+    };
+
+    let defaults = config::default_global_config();
+    let base = config::merge(defaults.clone(), project.as_ref().map(|layer| layer.config.clone()));
+    Ok(Self {
+      root: Some(root),
+      defaults,
+      project,
+      base: Rc::new(base),
+      layers: RefCell::default(),
+      resolved: RefCell::default(),
+    })
+  }
+
+  /// A single config for every path, ignoring any config files on disk
+  pub fn flat(config: VioletConfig) -> Self {
+    Self {
+      root: None,
+      defaults: config.clone(),
+      project: None,
+      base: Rc::new(config),
+      layers: RefCell::default(),
+      resolved: RefCell::default(),
+    }
+  }
+
+  /// The project config, which applies to the root directory
+  pub fn project(&self) -> &VioletConfig {
+    &self.base
+  }
+
+  /// Canonical project root, when nested configs are looked up
+  pub fn root(&self) -> Option<&Path> {
+    self.root.as_deref()
+  }
+
+  /// Effective config for the entries of `dir`
+  pub fn for_dir(&self, dir: &Path) -> Result<Rc<VioletConfig>> {
+    match self.nested(dir) {
+      Some(canonical) => self.resolve(&canonical),
+      None => Ok(self.base.clone()),
+    }
+  }
+
+  /// Effective config for a file
+  pub fn for_file(&self, path: &Path) -> Result<Rc<VioletConfig>> {
+    self.for_dir(parent_dir(path))
+  }
+
+  /// Whether `config` ignores `path`, as given or relative to the project root
+  pub fn ignores(&self, config: &VioletConfig, path: &Path) -> bool {
+    self.matching_ignore(&config.ignore_files, path).is_some()
+  }
+
+  /// Effective config for `path` and where each part of it came from
+  pub fn explain(&self, path: &Path) -> Result<Explanation> {
+    let dir = if path.is_dir() { path } else { parent_dir(path) };
+    let mut layers: Vec<Rc<Layer>> = self.project.iter().cloned().collect();
+    if let Some(canonical) = self.nested(dir) {
+      self.collect_layers(&canonical, &mut layers)?;
+    }
+
+    let mut config = self.defaults.clone();
+    let mut threshold =
+      Setting { value: config::get_threshold(&config, path), source: Source::Defaults };
+    let mut penalties = penalty_settings(&config, &Source::Defaults);
+    let mut ignored_by = self
+      .matching_ignore(&config.ignore_files, path)
+      .map(|pattern| (pattern.clone(), Source::Defaults));
+
+    let mut ignore_files = Vec::new();
+    for layer in &layers {
+      let source = Source::File(layer.source.clone());
+      ignore_files
+        .extend(layer.config.ignore_files.iter().map(|pattern| (pattern.clone(), source.clone())));
+      config = config::merge(config, Some(layer.config.clone()));
+
+      let value = config::get_threshold(&config, path);
+      if value != threshold.value {
+        threshold = Setting { value, source: source.clone() };
+      }
+      for (current, (_, next)) in penalties.iter_mut().zip(penalty_settings(&config, &source)) {
+        if next.value != current.1.value {
+          current.1 = next;
+        }
+      }
+      if ignored_by.is_none() {
+        ignored_by = self
+          .matching_ignore(&layer.config.ignore_files, path)
+          .map(|pattern| (pattern.clone(), source));
+      }
+    }
+
+    Ok(Explanation {
+      sources: layers.iter().map(|layer| layer.source.clone()).collect(),
+      config,
+      threshold,
+      penalties,
+      ignore_files,
+      ignored_by,
+    })
+  }
+
+  fn matching_ignore<'a>(&self, patterns: &'a [String], path: &Path) -> Option<&'a String> {
+    config::matching_ignore(patterns, path).or_else(|| {
+      let root = self.root.as_ref()?;
+      let canonical = fs::canonicalize(path).ok()?;
+      config::matching_ignore(patterns, canonical.strip_prefix(root).ok()?)
+    })
+  }
+
+  /// The canonical form of `dir` when it is below the project root
+  fn nested(&self, dir: &Path) -> Option<PathBuf> {
+    let root = self.root.as_ref()?;
+    let canonical = fs::canonicalize(dir).ok()?;
+    canonical.starts_with(root).then_some(canonical)
+  }
+
+  fn resolve(&self, dir: &Path) -> Result<Rc<VioletConfig>> {
+    if self.root.as_deref() == Some(dir) {
+      return Ok(self.base.clone());
+    }
+    if let Some(config) = self.resolved.borrow().get(dir) {
+      return Ok(config.clone());
+    }
+
+    let parent = self.resolve(dir.parent().expect("nested directories have a parent"))?;
+    let config = match self.layer(dir)? {
+      Some(layer) => Rc::new(config::merge((*parent).clone(), Some(layer.config.clone()))),
+      None => parent,
+    };
+    self.resolved.borrow_mut().insert(dir.to_path_buf(), config.clone());
+    Ok(config)
+  }
+
+  /// Nested config files from just below the root down to `dir`
+  fn collect_layers(&self, dir: &Path, layers: &mut Vec<Rc<Layer>>) -> Result<()> {
+    if self.root.as_deref() == Some(dir) {
+      return Ok(());
+    }
+    self.collect_layers(dir.parent().expect("nested directories have a parent"), layers)?;
+    layers.extend(self.layer(dir)?);
+    Ok(())
+  }
+
+  fn layer(&self, dir: &Path) -> Result<Option<Rc<Layer>>> {
+    if let Some(layer) = self.layers.borrow().get(dir) {
+      return Ok(layer.clone());
+    }
+
+    let layer = match config::find_config_file(dir)? {
+      Some(path) => {
+        let root = self.root.as_deref().expect("only nested directories have layers");
+        let relative_dir = dir.strip_prefix(root).unwrap_or(dir);
+        let layer = Layer::load(path.clone(), relative_dir)
+          .with_context(|| format!("Failed to load config from {}", path.display()))?;
+        Some(Rc::new(layer))
+      }
+      None => None,
+    };
+    self.layers.borrow_mut().insert(dir.to_path_buf(), layer.clone());
+    Ok(layer)
+  }
+}
+
+fn parent_dir(path: &Path) -> &Path {
+  match path.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  }
+}
+
+fn penalty_settings(config: &VioletConfig, source: &Source) -> Vec<(&'static str, Setting)> {
+  let penalties = &config.complexity.penalties;
+  [
+    ("depth", penalties.depth),
+    ("verbosity", penalties.verbosity),
+    ("syntactics", penalties.syntactics),
+  ]
+  .into_iter()
+  .map(|(name, value)| (name, Setting { value, source: source.clone() }))
+  .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn write(path: &Path, content: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+  }
+
+  fn project() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    write(
+      &root.join("violet.yaml"),
+      "complexity:\n  thresholds:\n    default: 6.0\n    .rs: 7.0\n",
+    );
+    write(
+      &root.join("legacy/violet.yaml"),
+      "complexity:\n  thresholds:\n    default: 10.0\n  penalties:\n    depth: 2.0\nignore_files:\n  - \"generated/**\"\n",
+    );
+    write(
+      &root.join("legacy/billing/violet.json"),
+      r#"{"complexity": {"thresholds": {".rs": 12.0}}}"#,
+    );
+    write(&root.join("src/main.rs"), "fn main() {}\n");
+    write(&root.join("legacy/billing/invoice.rs"), "fn main() {}\n");
+    temp
+  }
+
+  #[test]
+  fn test_nearest_config_wins() {
+    let temp = project();
+    let root = temp.path();
+    let tree = ConfigTree::at(root).unwrap();
+
+    let top = tree.for_file(&root.join("src/main.rs")).unwrap();
+    assert_eq!(config::get_threshold(&top, "main.rs"), 7.0);
+    assert_eq!(config::get_threshold(&top, "main.py"), 6.0);
+
+    let legacy = tree.for_dir(&root.join("legacy")).unwrap();
+    assert_eq!(config::get_threshold(&legacy, "old.py"), 10.0);
+    assert_eq!(config::get_threshold(&legacy, "old.rs"), 7.0);
+    assert_eq!(legacy.complexity.penalties.depth, 2.0);
+
+    let billing = tree.for_file(&root.join("legacy/billing/invoice.rs")).unwrap();
+    assert_eq!(config::get_threshold(&billing, "invoice.rs"), 12.0);
+    assert_eq!(config::get_threshold(&billing, "invoice.py"), 10.0);
+    assert_eq!(billing.complexity.penalties.depth, 2.0);
+  }
+
+  #[test]
+  fn test_nested_ignores_are_relative_to_their_config() {
+    let temp = project();
+    let root = temp.path();
+    let tree = ConfigTree::at(root).unwrap();
+
+    let legacy = tree.for_dir(&root.join("legacy")).unwrap();
+    assert!(config::should_ignore_file(&legacy, "legacy/generated/api.rs"));
+    assert!(!config::should_ignore_file(&legacy, "generated/api.rs"));
+    assert!(config::should_ignore_file(&legacy, "legacy/notes.md"));
+
+    let top = tree.for_dir(root).unwrap();
+    assert!(!config::should_ignore_file(&top, "legacy/generated/api.rs"));
+  }
+
+  #[test]
+  fn test_explain_names_the_config_behind_each_setting() {
+    let temp = project();
+    let root = temp.path();
+    let tree = ConfigTree::at(root).unwrap();
+
+    let explanation = tree.explain(&root.join("legacy/billing/invoice.rs")).unwrap();
+    let canonical = fs::canonicalize(root).unwrap();
+    assert_eq!(
+      explanation.sources,
+      [
+        canonical.join("violet.yaml"),
+        canonical.join("legacy/violet.yaml"),
+        canonical.join("legacy/billing/violet.json"),
+      ]
+    );
+    assert_eq!(explanation.threshold.value, 12.0);
+    assert_eq!(
+      explanation.threshold.source,
+      Source::File(canonical.join("legacy/billing/violet.json"))
+    );
+    assert_eq!(
+      explanation.penalties[0].1.source,
+      Source::File(canonical.join("legacy/violet.yaml"))
+    );
+    assert_eq!(explanation.penalties[1].1.source, Source::Defaults);
+    assert_eq!(explanation.ignored_by, None);
+
+    let readme = tree.explain(&root.join("legacy/README.md")).unwrap();
+    assert_eq!(readme.ignored_by, Some(("*.md".to_string(), Source::Defaults)));
+  }
+
+  #[test]
+  fn test_conflicting_config_files_are_rejected() {
+    let temp = project();
+    let root = temp.path();
+    write(&root.join("src/violet.yaml"), "ignore_files: []\n");
+    write(&root.join("src/violet.json"), "{}");
+
+    let tree = ConfigTree::at(root).unwrap();
+    let error = tree.for_file(&root.join("src/main.rs")).unwrap_err();
+    assert!(error.to_string().contains("keep only one"));
+  }
+}
@@ -0,0 +1,362 @@
+//! Continuous integration support
+//!
+//! With `--ci`, the provider is detected from its environment variables, analysis is
+//! limited to the files changed since the base commit, and violations are reported in
+//! the provider's own format: workflow commands on GitHub Actions, a Code Quality
+//! report on GitLab CI.
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Code Quality report GitLab picks up as an artifact (`artifacts:reports:codequality`)
+pub const GITLAB_REPORT_FILE: &str = "gl-code-quality-report.json";
+
+/// Git's placeholder for "no previous commit", e.g. the first push of a branch
+const NULL_SHA: &str = "0000000000000000000000000000000000000000";
+
+/// CI services violet knows how to talk to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+  GitHubActions,
+  GitLabCi,
+}
+
+impl fmt::Display for Provider {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Provider::GitHubActions => write!(f, "GitHub Actions"),
+      Provider::GitLabCi => write!(f, "GitLab CI"),
+    }
+  }
+}
+
+/// How violations are reported and whether they fail the build
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Severity {
+  /// Report violations as errors and exit non-zero
+  #[default]
+  Error,
+  /// Report violations as warnings without failing the build
+  Warning,
+}
+
+/// What was learned about the running CI job
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiContext {
+  pub provider: Provider,
+  /// Commit or ref to diff against; None analyzes everything
+  pub base: Option<String>,
+}
+
+/// Detect the CI provider from the process environment
+pub fn detect() -> Option<CiContext> {
+  detect_from(&|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+}
+
+/// Detect the CI provider from a variable lookup
+pub fn detect_from(var: &dyn Fn(&str) -> Option<String>) -> Option<CiContext> {
+  if var("GITHUB_ACTIONS").as_deref() == Some("true") {
+    let base = github_event_base(var).or_else(|| var("GITHUB_BASE_REF").map(remote_branch));
+    return Some(CiContext { provider: Provider::GitHubActions, base });
+  }
+
+  if var("GITLAB_CI").as_deref() == Some("true") {
+    let base = var("CI_MERGE_REQUEST_DIFF_BASE_SHA")
+      .or_else(|| var("CI_MERGE_REQUEST_TARGET_BRANCH_NAME").map(remote_branch))
+      .or_else(|| var("CI_COMMIT_BEFORE_SHA"))
+      .filter(|sha| sha != NULL_SHA);
+    return Some(CiContext { provider: Provider::GitLabCi, base });
+  }
+
+  None
+}
+
+fn remote_branch(branch: String) -> String {
+  format!("origin/{branch}")
+}
+
+/// Base commit from the GitHub event payload: the PR base, or the commit before a push
+fn github_event_base(var: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+  let content = std::fs::read_to_string(var("GITHUB_EVENT_PATH")?).ok()?;
+  let event: serde_json::Value = serde_json::from_str(&content).ok()?;
+  event["pull_request"]["base"]["sha"]
+    .as_str()
+    .or_else(|| event["before"].as_str())
+    .filter(|sha| *sha != NULL_SHA)
+    .map(str::to_string)
+}
+
+/// Top of the git work tree containing the current directory
+pub fn repo_root() -> Result<PathBuf> {
+  let root = git(&["rev-parse", "--show-toplevel"])?;
+  Ok(std::fs::canonicalize(root.trim())?)
+}
+
+/// Path as CI providers expect it: relative to the repository root
+pub fn repo_relative(path: &Path, root: &Path) -> String {
+  let relative = std::fs::canonicalize(path)
+    .ok()
+    .and_then(|canonical| canonical.strip_prefix(root).ok().map(Path::to_path_buf))
+    .unwrap_or_else(|| path.to_path_buf());
+  let display = relative.display().to_string();
+  display.strip_prefix("./").map(str::to_string).unwrap_or(display)
+}
+
+/// Canonical paths of files added, copied, modified or renamed since `base`
+pub fn changed_files(root: &Path, base: &str) -> Result<HashSet<PathBuf>> {
+  let range = format!("{base}...HEAD");
+  let diff =
+    git(&["diff", "--name-only", "--diff-filter=ACMR", "-z", &range]).with_context(|| {
+      format!("Failed to diff against {base} (shallow clones need the base commit fetched)")
+    })?;
+
+  Ok(
+    diff
+      .split('\0')
+      .filter(|name| !name.is_empty())
+      .filter_map(|name| std::fs::canonicalize(root.join(name)).ok())
+      .collect(),
+  )
+}
+
+fn git(args: &[&str]) -> Result<String> {
+  let output = Command::new("git").args(args).output().context("Failed to run git")?;
+  if !output.status.success() {
+    return Err(anyhow!(
+      "git {} failed: {}",
+      args.join(" "),
+      String::from_utf8_lossy(&output.stderr).trim()
+    ));
+  }
+  Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// One chunk over its threshold, ready to be reported
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+  pub path: String,
+  pub start_line: usize,
+  pub end_line: usize,
+  pub score: f64,
+  pub threshold: f64,
+}
+
+impl Annotation {
+  fn message(&self) -> String {
+    format!("Chunk complexity {:.2} exceeds threshold {:.2}", self.score, self.threshold)
+  }
+}
+
+/// GitHub Actions workflow commands, one per annotation
+pub fn github_annotations(annotations: &[Annotation], severity: Severity) -> Vec<String> {
+  let level = match severity {
+    Severity::Error => "error",
+    Severity::Warning => "warning",
+  };
+  annotations
+    .iter()
+    .map(|a| {
+      format!(
+        "::{level} file={},line={},endLine={},title=violet::{}",
+        escape_property(&a.path),
+        a.start_line,
+        a.end_line,
+        a.message()
+      )
+    })
+    .collect()
+}
+
+/// Workflow command properties may not contain the command's own delimiters
+fn escape_property(value: &str) -> String {
+  value
+    .replace('%', "%25")
+    .replace('\r', "%0D")
+    .replace('\n', "%0A")
+    .replace(':', "%3A")
+    .replace(',', "%2C")
+}
+
+#[derive(Debug, Serialize)]
+struct CodeQualityIssue {
+  description: String,
+  check_name: &'static str,
+  fingerprint: String,
+  severity: &'static str,
+  location: CodeQualityLocation,
+}
+
+#[derive(Debug, Serialize)]
+struct CodeQualityLocation {
+  path: String,
+  lines: CodeQualityLines,
+}
+
+#[derive(Debug, Serialize)]
+struct CodeQualityLines {
+  begin: usize,
+  end: usize,
+}
+
+/// GitLab Code Quality report (a Code Climate issue list)
+pub fn gitlab_report(annotations: &[Annotation], severity: Severity) -> Result<String> {
+  let level = match severity {
+    Severity::Error => "major",
+    Severity::Warning => "minor",
+  };
+  let issues: Vec<CodeQualityIssue> = annotations
+    .iter()
+    .map(|a| CodeQualityIssue {
+      description: a.message(),
+      check_name: "violet-complexity",
+      fingerprint: fingerprint(&format!("{}:{}-{}", a.path, a.start_line, a.end_line)),
+      severity: level,
+      location: CodeQualityLocation {
+        path: a.path.clone(),
+        lines: CodeQualityLines { begin: a.start_line, end: a.end_line },
+      },
+    })
+    .collect();
+  Ok(serde_json::to_string_pretty(&issues)?)
+}
+
+/// Stable across runs and toolchains, so GitLab can match issues between pipelines
+fn fingerprint(key: &str) -> String {
+  let hash = key
+    .bytes()
+    .fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3));
+  format!("{hash:016x}")
+}
+
+/// Emit annotations in the provider's native format
+pub fn report(provider: Provider, annotations: &[Annotation], severity: Severity) -> Result<()> {
+  match provider {
+    Provider::GitHubActions => {
+      for line in github_annotations(annotations, severity) {
+        println!("{line}");
+      }
+    }
+    Provider::GitLabCi => {
+      let path = Path::new(GITLAB_REPORT_FILE);
+      std::fs::write(path, gitlab_report(annotations, severity)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+      eprintln!("Wrote {} issues to {}", annotations.len(), path.display());
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashMap;
+  use tempfile::TempDir;
+
+  fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> =
+      vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    move |name| vars.get(name).cloned()
+  }
+
+  fn annotation() -> Annotation {
+    Annotation {
+      path: "src/lib.rs".to_string(),
+      start_line: 10,
+      end_line: 24,
+      score: 9.5,
+      threshold: 8.0,
+    }
+  }
+
+  #[test]
+  fn test_detect_nothing_outside_ci() {
+    assert_eq!(detect_from(&lookup(&[("CI", "true")])), None);
+  }
+
+  #[test]
+  fn test_detect_github_pull_request_base_from_event() {
+    let temp_dir = TempDir::new().unwrap();
+    let event = temp_dir.path().join("event.json");
+    std::fs::write(&event, r#"{"pull_request": {"base": {"sha": "abc123"}}}"#).unwrap();
+
+    let context = detect_from(&lookup(&[
+      ("GITHUB_ACTIONS", "true"),
+      ("GITHUB_EVENT_PATH", event.to_str().unwrap()),
+      ("GITHUB_BASE_REF", "main"),
+    ]))
+    .unwrap();
+
+    assert_eq!(context.provider, Provider::GitHubActions);
+    assert_eq!(context.base.as_deref(), Some("abc123"));
+  }
+
+  #[test]
+  fn test_detect_github_falls_back_to_base_ref() {
+    let context =
+      detect_from(&lookup(&[("GITHUB_ACTIONS", "true"), ("GITHUB_BASE_REF", "main")])).unwrap();
+    assert_eq!(context.base.as_deref(), Some("origin/main"));
+  }
+
+  #[test]
+  fn test_detect_github_new_branch_push_has_no_base() {
+    let temp_dir = TempDir::new().unwrap();
+    let event = temp_dir.path().join("event.json");
+    std::fs::write(&event, format!(r#"{{"before": "{NULL_SHA}"}}"#)).unwrap();
+
+    let context = detect_from(&lookup(&[
+      ("GITHUB_ACTIONS", "true"),
+      ("GITHUB_EVENT_PATH", event.to_str().unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(context.base, None);
+  }
+
+  #[test]
+  fn test_detect_gitlab_prefers_merge_request_base() {
+    let context = detect_from(&lookup(&[
+      ("GITLAB_CI", "true"),
+      ("CI_MERGE_REQUEST_DIFF_BASE_SHA", "def456"),
+      ("CI_COMMIT_BEFORE_SHA", "old"),
+    ]))
+    .unwrap();
+    assert_eq!(context.provider, Provider::GitLabCi);
+    assert_eq!(context.base.as_deref(), Some("def456"));
+
+    let push =
+      detect_from(&lookup(&[("GITLAB_CI", "true"), ("CI_COMMIT_BEFORE_SHA", NULL_SHA)])).unwrap();
+    assert_eq!(push.base, None);
+  }
+
+  #[test]
+  fn test_github_annotations_follow_severity() {
+    let lines = github_annotations(&[annotation()], Severity::Warning);
+    assert_eq!(
+      lines,
+      vec![
+        "::warning file=src/lib.rs,line=10,endLine=24,title=violet::Chunk complexity 9.50 exceeds threshold 8.00"
+      ]
+    );
+    assert!(github_annotations(&[annotation()], Severity::Error)[0].starts_with("::error "));
+  }
+
+  #[test]
+  fn test_escape_property() {
+    assert_eq!(escape_property("a,b:c%"), "a%2Cb%3Ac%25");
+  }
+
+  #[test]
+  fn test_gitlab_report_is_code_quality_json() {
+    let report = gitlab_report(&[annotation()], Severity::Error).unwrap();
+    let issues: serde_json::Value = serde_json::from_str(&report).unwrap();
+    let issue = &issues[0];
+    assert_eq!(issue["severity"], "major");
+    assert_eq!(issue["location"]["path"], "src/lib.rs");
+    assert_eq!(issue["location"]["lines"]["begin"], 10);
+    assert_eq!(issue["fingerprint"].as_str().unwrap().len(), 16);
+    assert_eq!(issue["fingerprint"], fingerprint("src/lib.rs:10-24"));
+  }
+}
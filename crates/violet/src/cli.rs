@@ -0,0 +1,1453 @@
+//! Command-line front end shared by the `violet` binary and `blizz lint`
+//!
+//! Analysis results are collected first and rendered afterwards, so the same run
+//! can be printed as the familiar table, a JSON report or a standalone HTML page.
+
+use anyhow::{anyhow, Context, Result};
+use bentley::layout::{self, Align, Column, Table};
+use colored::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub use bentley::OutputFormat;
+
+use crate::cascade::{ConfigTree, Explanation, Source};
+use crate::ci;
+use crate::config;
+use crate::html;
+use crate::ratchet::{self, Ratchet};
+use crate::scoring;
+use crate::simplicity;
+use crate::traversal;
+
+const TOTAL_WIDTH: usize = 80;
+const PADDING: usize = 2;
+
+/// Arguments for a lint run
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct LintArgs {
+  #[arg(value_name = "PATH")]
+  pub paths: Vec<PathBuf>,
+
+  /// Report directories skipped during traversal
+  #[arg(short, long)]
+  pub verbose: bool,
+
+  /// Descend into git submodules (skipped by default)
+  #[arg(long)]
+  pub include_submodules: bool,
+
+  /// Detect the CI provider, lint only changed files and emit its native annotations
+  #[arg(long)]
+  pub ci: bool,
+
+  /// How CI annotations are reported; only `error` fails the run
+  #[arg(long, value_enum, default_value_t = ci::Severity::Error, requires = "ci")]
+  pub ci_severity: ci::Severity,
+
+  /// Write the JSON or HTML report to this file instead of stdout
+  #[arg(long, value_name = "FILE")]
+  pub report_file: Option<PathBuf>,
+}
+
+/// Arguments for recording or tightening a ratchet
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct RatchetArgs {
+  /// Paths to record (defaults to the current directory)
+  #[arg(value_name = "PATH")]
+  pub paths: Vec<PathBuf>,
+
+  /// Descend into git submodules (skipped by default)
+  #[arg(long)]
+  pub include_submodules: bool,
+
+  /// Lower every recorded ceiling by this much instead of recording, e.g. 5%
+  #[arg(long, value_name = "PERCENT", value_parser = ratchet::parse_percent)]
+  pub tighten: Option<f64>,
+
+  /// Discard the existing ratchet and record every failing file as it is now
+  #[arg(long, conflicts_with = "tighten")]
+  pub reset: bool,
+}
+
+/// Arguments for showing the effective configuration
+#[derive(clap::Args, Debug, Clone)]
+pub struct ConfigArgs {
+  /// File or directory whose config to show, with the config file behind each value
+  #[arg(long, value_name = "PATH", default_value = ".")]
+  pub explain: PathBuf,
+}
+
+/// Arguments for explaining how a file's chunks were scored
+#[derive(clap::Args, Debug, Clone)]
+pub struct ExplainArgs {
+  /// File to explain
+  #[arg(value_name = "FILE")]
+  pub file: PathBuf,
+
+  /// Only chunks overlapping these lines, as lint output numbers them, e.g. 40-80
+  #[arg(long, value_name = "START-END", value_parser = parse_line_range)]
+  pub lines: Option<(usize, usize)>,
+}
+
+/// How results are presented, shared by violet and `blizz lint`
+#[derive(clap::Args, Debug, Clone, Copy, Default)]
+pub struct LintOptions {
+  /// Only show files with violations
+  #[arg(short, long)]
+  pub quiet: bool,
+
+  /// Output format
+  #[arg(long, value_enum, default_value = "text")]
+  pub output: OutputFormat,
+}
+
+/// A file that was analyzed, with the threshold it was held to
+struct AnalyzedFile {
+  analysis: simplicity::FileAnalysis,
+  threshold: f64,
+}
+
+impl AnalyzedFile {
+  fn violations(&self) -> impl Iterator<Item = &scoring::ComplexityRegion> {
+    self.analysis.issues.iter().filter(|region| region.score > self.threshold)
+  }
+}
+
+/// JSON report of a lint run
+#[derive(Debug, Serialize)]
+pub struct LintReport {
+  pub violations: usize,
+  pub files: Vec<FileReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+  pub path: String,
+  pub ignored: bool,
+  pub threshold: f64,
+  pub chunks: Vec<ChunkReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChunkReport {
+  pub start_line: usize,
+  pub end_line: usize,
+  pub score: f64,
+}
+
+/// The CI job being linted for
+struct CiRun {
+  provider: ci::Provider,
+  repo_root: PathBuf,
+  /// Files changed since the base commit; None when there is no base to diff against
+  changed: Option<HashSet<PathBuf>>,
+}
+
+/// Analyze the given paths and print the results
+///
+/// Returns the number of chunks that should fail the run; callers decide how that
+/// maps onto an exit code.
+pub fn run(args: &LintArgs, options: LintOptions) -> Result<usize> {
+  let mut args = args.clone();
+  if args.paths.is_empty() {
+    if !args.ci {
+      return Err(anyhow!("No paths specified"));
+    }
+    args.paths.push(PathBuf::from("."));
+  }
+
+  if args.report_file.is_some() && options.output == OutputFormat::Text {
+    return Err(anyhow!("--report-file needs --output json or --output html"));
+  }
+
+  let tree = ConfigTree::load().context("Failed to load configuration")?;
+  let ci_run = if args.ci { Some(prepare_ci()?) } else { None };
+  let changed = ci_run.as_ref().and_then(|run| run.changed.as_ref());
+  let mut files = analyze_paths(&args, &tree, changed)?;
+  apply_ratchet(&mut files)?;
+  let violations = files.iter().map(|file| file.violations().count()).sum();
+
+  match options.output {
+    OutputFormat::Text => print_text(&files, tree.project(), options.quiet),
+    OutputFormat::Json => {
+      write_report(&json_report(&files, violations, options.quiet)?, args.report_file.as_deref())?
+    }
+    OutputFormat::Html => {
+      write_report(&html_report(&files, violations, options.quiet), args.report_file.as_deref())?
+    }
+  }
+
+  match ci_run {
+    Some(ci_run) => {
+      ci::report(ci_run.provider, &annotations(&files, &ci_run.repo_root), args.ci_severity)?;
+      Ok(if args.ci_severity == ci::Severity::Error { violations } else { 0 })
+    }
+    None => Ok(violations),
+  }
+}
+
+/// Hold files covered by the ratchet to their recorded ceiling instead of the threshold
+fn apply_ratchet(files: &mut [AnalyzedFile]) -> Result<()> {
+  let root = std::env::current_dir().context("Failed to get current working directory")?;
+  let Some(ratchet) = Ratchet::load(&root.join(ratchet::RATCHET_FILE))? else {
+    return Ok(());
+  };
+
+  for file in files.iter_mut() {
+    if let Some(ceiling) = ratchet.ceiling(&ratchet::key(&file.analysis.file_path, &root)) {
+      file.threshold = file.threshold.max(ceiling);
+    }
+  }
+  Ok(())
+}
+
+/// Record the worst score of every failing file, or tighten the recorded ceilings
+pub fn run_ratchet(args: &RatchetArgs) -> Result<()> {
+  let tree = ConfigTree::load().context("Failed to load configuration")?;
+  let root = std::env::current_dir().context("Failed to get current working directory")?;
+  let path = root.join(ratchet::RATCHET_FILE);
+  let existing = if args.reset { None } else { Ratchet::load(&path)? };
+
+  if let Some(percent) = args.tighten {
+    let mut ratchet = existing.ok_or_else(|| {
+      anyhow!("No {} to tighten; run `violet ratchet` first", ratchet::RATCHET_FILE)
+    })?;
+    let thresholds = ratchet
+      .files
+      .keys()
+      .map(|key| {
+        let file = root.join(key);
+        Ok((key.clone(), config::get_threshold(&*tree.for_file(&file)?, &file)))
+      })
+      .collect::<Result<HashMap<_, _>>>()?;
+    let cleared = ratchet.tighten(percent, |key| thresholds[key]);
+    ratchet.save(&path)?;
+    println!("Tightened {} ceilings by {percent}%", ratchet.files.len());
+    report_cleared(&cleared);
+    return Ok(());
+  }
+
+  let lint_args = LintArgs {
+    paths: if args.paths.is_empty() { vec![PathBuf::from(".")] } else { args.paths.clone() },
+    include_submodules: args.include_submodules,
+    ..Default::default()
+  };
+  let observed: Vec<ratchet::Observation> = analyze_paths(&lint_args, &tree, None)?
+    .iter()
+    .map(|file| ratchet::Observation {
+      key: ratchet::key(&file.analysis.file_path, &root),
+      worst: file.violations().map(|chunk| chunk.score).reduce(f64::max),
+    })
+    .collect();
+
+  let fresh = existing.is_none();
+  let mut ratchet = existing.unwrap_or_default();
+  let outcome = ratchet.record(&observed, fresh);
+  let mut cleared = outcome.cleared;
+  cleared.extend(ratchet.forget_missing(&root));
+  ratchet.save(&path)?;
+
+  if fresh {
+    println!(
+      "Recorded {} files over their threshold in {}",
+      ratchet.files.len(),
+      ratchet::RATCHET_FILE
+    );
+  } else {
+    println!("Lowered {} ceilings in {}", outcome.lowered.len(), ratchet::RATCHET_FILE);
+  }
+  report_cleared(&cleared);
+  for key in &outcome.unrecorded {
+    eprintln!(
+      "{} {key} is over its threshold and not in the ratchet; fix it or record again with --reset",
+      "warning:".yellow()
+    );
+  }
+  Ok(())
+}
+
+/// Print the effective config for a path and the config file behind each value
+pub fn run_config(args: &ConfigArgs) -> Result<()> {
+  let tree = ConfigTree::load().context("Failed to load configuration")?;
+  let explanation = tree.explain(&args.explain)?;
+  let root = tree.root().unwrap_or(Path::new("."));
+  print!("{}", format_explanation(&args.explain, &explanation, root));
+  Ok(())
+}
+
+fn format_explanation(path: &Path, explanation: &Explanation, root: &Path) -> String {
+  let source = |source: &Source| source_name(source, root);
+  let mut output = format!("Effective config for {}\n\n", path.display());
+
+  output.push_str("config files\n");
+  if explanation.sources.is_empty() {
+    output.push_str("  (none, using built-in defaults)\n");
+  }
+  for file in &explanation.sources {
+    output.push_str(&format!("  {}\n", source(&Source::File(file.clone()))));
+  }
+  output.push('\n');
+
+  output.push_str(&settings_table(explanation, root).to_string());
+
+  if path.is_dir() && !explanation.config.complexity.thresholds.extensions.is_empty() {
+    output.push('\n');
+    output.push_str(&threshold_table(&explanation.config).to_string());
+  }
+
+  if !explanation.ignore_files.is_empty() {
+    output.push_str("\nignore_files\n");
+    for (pattern, from) in &explanation.ignore_files {
+      output.push_str(&format!("  {pattern}  ({})\n", source(from)));
+    }
+  }
+  if !explanation.config.ignore_patterns.is_empty() {
+    output.push_str("\nignore_patterns\n");
+    for pattern in &explanation.config.ignore_patterns {
+      output.push_str(&format!("  {pattern}\n"));
+    }
+  }
+  if !explanation.config.penalty_patterns.is_empty() {
+    output.push_str("\npenalty_patterns\n");
+    output.push_str(&format_penalty_patterns(&explanation.config.penalty_patterns, path));
+  }
+
+  match &explanation.ignored_by {
+    Some((pattern, from)) => {
+      output.push_str(&format!("\nignored: yes, by \"{pattern}\" from {}\n", source(from)))
+    }
+    None => output.push_str("\nignored: no\n"),
+  }
+  output
+}
+
+/// One line per penalty pattern, noting ones that don't apply to `path`
+fn format_penalty_patterns(patterns: &[config::PenaltyPattern], path: &Path) -> String {
+  let mut output = String::new();
+  for pattern in patterns {
+    output.push_str(&format!("  {}  weight {}", pattern.pattern, pattern.weight));
+    if !pattern.extensions.is_empty() {
+      output.push_str(&format!("  ({})", pattern.extensions.join(", ")));
+    }
+    if path.is_file() && !pattern.applies_to(path) {
+      output.push_str("  not applied");
+    }
+    output.push('\n');
+  }
+  output
+}
+
+fn source_name(source: &Source, root: &Path) -> String {
+  match source {
+    Source::File(file) => file.strip_prefix(root).unwrap_or(file).display().to_string(),
+    Source::Defaults => source.to_string(),
+  }
+}
+
+/// Threshold and penalties in effect, with the config behind each
+fn settings_table(explanation: &Explanation, root: &Path) -> Table {
+  let mut settings = Table::new()
+    .column(Column::new("setting").min_width(23))
+    .column(Column::new("value").align(Align::Right))
+    .column(Column::new("from"))
+    .rule('=')
+    .row([
+      "threshold".to_string(),
+      format!("{:.2}", explanation.threshold.value),
+      source_name(&explanation.threshold.source, root),
+    ]);
+  for (name, setting) in &explanation.penalties {
+    settings.push_row([
+      format!("penalties.{name}"),
+      format!("{:.2}", setting.value),
+      source_name(&setting.source, root),
+    ]);
+  }
+  settings
+}
+
+/// Print how the chunks of a file were scored, line by line
+pub fn run_explain(args: &ExplainArgs) -> Result<()> {
+  if !args.file.is_file() {
+    return Err(anyhow!("{} is not a file", args.file.display()));
+  }
+  let tree = ConfigTree::load().context("Failed to load configuration")?;
+  let config = tree.for_file(&args.file)?;
+  let explanation = tree.explain(&args.file)?;
+  let chunks = simplicity::explain_file(&args.file, &config, args.lines)
+    .map_err(|e| anyhow!("Failed to analyze {}: {e}", args.file.display()))?;
+  let root = tree.root().unwrap_or(Path::new("."));
+  print!("{}", format_score_explanation(args, &explanation, chunks.as_deref(), root));
+  Ok(())
+}
+
+/// Parse an inclusive line range such as `40-80`, or a single line
+fn parse_line_range(value: &str) -> Result<(usize, usize), String> {
+  let (start, end) = value.split_once('-').unwrap_or((value, value));
+  let parse = |number: &str| {
+    number
+      .trim()
+      .parse::<usize>()
+      .ok()
+      .filter(|line| *line > 0)
+      .ok_or_else(|| format!("'{value}' is not a line range like 40-80"))
+  };
+  let (start, end) = (parse(start)?, parse(end)?);
+  if start > end {
+    return Err(format!("line range '{value}' ends before it starts"));
+  }
+  Ok((start, end))
+}
+
+fn format_score_explanation(
+  args: &ExplainArgs,
+  explanation: &Explanation,
+  chunks: Option<&[simplicity::ChunkExplanation]>,
+  root: &Path,
+) -> String {
+  let mut output = format!("Score for {}", args.file.display());
+  if let Some((start, end)) = args.lines {
+    output.push_str(&format!(", lines {start}-{end}"));
+  }
+  output.push_str("\n\n");
+  output.push_str(&settings_table(explanation, root).to_string());
+  if !explanation.config.penalty_patterns.is_empty() {
+    output.push_str("\npenalty_patterns\n");
+    output.push_str(&format_penalty_patterns(&explanation.config.penalty_patterns, &args.file));
+  }
+  if let Some((pattern, from)) = &explanation.ignored_by {
+    output
+      .push_str(&format!("\nignored: yes, by \"{pattern}\" from {}\n", source_name(from, root)));
+  }
+
+  let Some(chunks) = chunks else {
+    output.push_str("\nThe file is ignored by a violet directive\n");
+    return output;
+  };
+  if chunks.is_empty() {
+    output.push_str("\nNo code in these lines\n");
+  }
+  for chunk in chunks {
+    output.push('\n');
+    output.push_str(&format_chunk_explanation(chunk, explanation.threshold.value));
+  }
+  output
+}
+
+fn format_chunk_explanation(chunk: &simplicity::ChunkExplanation, threshold: f64) -> String {
+  let heading = format!("lines {}-{}", chunk.start_line, chunk.end_line);
+  if chunk.ignored {
+    return format!("{heading}: skipped, matches an ignore pattern\n");
+  }
+  let verdict = if chunk.score > threshold { "over" } else { "within" };
+  let mut output = format!("{heading}: {:.2} ({verdict} threshold {threshold:.2})\n", chunk.score);
+
+  let term = |off: bool, value: f64| if off { "-".to_string() } else { format!("{value:.2}") };
+  let mut table = Table::new()
+    .column(Column::new("line").align(Align::Right))
+    .column(Column::new("depth").align(Align::Right))
+    .column(Column::new("verbosity").align(Align::Right))
+    .column(Column::new("syntactics").align(Align::Right))
+    .column(Column::new("depth term").align(Align::Right))
+    .column(Column::new("verbosity term").align(Align::Right))
+    .column(Column::new("syntactics term").align(Align::Right))
+    .column(Column::new("code").max_width(40))
+    .rule('-');
+  for (i, (line, text)) in chunk.lines.iter().zip(&chunk.text).enumerate() {
+    table.push_row([
+      (chunk.start_line + i).to_string(),
+      format!("{}", line.depth),
+      format!("{}", line.verbosity),
+      if line.pattern_weight > 0.0 {
+        format!("{} + {}", line.syntactics - line.pattern_weight, line.pattern_weight)
+      } else {
+        format!("{}", line.syntactics)
+      },
+      term(line.scope.depth, line.depth_term),
+      term(line.scope.verbosity, line.verbosity_term),
+      term(line.scope.syntactics, line.syntactic_term),
+      text.trim().to_string(),
+    ]);
+  }
+  let (depth, verbosity, syntactics) = scoring::term_totals(&chunk.lines);
+  table.push_row([
+    "total".to_string(),
+    String::new(),
+    String::new(),
+    String::new(),
+    format!("{depth:.2}"),
+    format!("{verbosity:.2}"),
+    format!("{syntactics:.2}"),
+    String::new(),
+  ]);
+  output.push_str(&table.to_string());
+
+  let sum = depth + verbosity + syntactics;
+  output.push_str(&format!(
+    "score = ln({depth:.2} + {verbosity:.2} + {syntactics:.2}) = ln({sum:.2}) = {:.2}\n",
+    chunk.score
+  ));
+  output
+}
+
+fn report_cleared(cleared: &[String]) {
+  for key in cleared {
+    println!("  {} {key}", "cleared".green());
+  }
+}
+
+fn prepare_ci() -> Result<CiRun> {
+  let context = ci::detect()
+    .ok_or_else(|| anyhow!("--ci needs a supported CI provider (GitHub Actions or GitLab CI)"))?;
+  let repo_root = ci::repo_root()?;
+
+  let changed = match &context.base {
+    Some(base) => {
+      let changed = ci::changed_files(&repo_root, base)?;
+      eprintln!("{}: linting {} files changed since {base}", context.provider, changed.len());
+      Some(changed)
+    }
+    None => {
+      eprintln!("{}: no base commit to diff against, linting everything", context.provider);
+      None
+    }
+  };
+
+  Ok(CiRun { provider: context.provider, repo_root, changed })
+}
+
+fn annotations(files: &[AnalyzedFile], repo_root: &Path) -> Vec<ci::Annotation> {
+  files
+    .iter()
+    .flat_map(|file| {
+      let path = ci::repo_relative(&file.analysis.file_path, repo_root);
+      file.violations().map(move |chunk| ci::Annotation {
+        path: path.clone(),
+        start_line: chunk.start_line,
+        end_line: chunk.end_line,
+        score: chunk.score,
+        threshold: file.threshold,
+      })
+    })
+    .collect()
+}
+
+/// Map file extensions to human-readable language names
+fn extension_to_language(ext: &str) -> &str {
+  get_language_map().get(ext).unwrap_or(&ext)
+}
+
+fn display_threshold_config(config: &config::VioletConfig) {
+  let thresholds = &config.complexity.thresholds.extensions;
+  if thresholds.is_empty() {
+    display_simple_threshold(config.complexity.thresholds.default);
+  } else {
+    display_threshold_table(config);
+  }
+  println!();
+}
+
+fn display_simple_threshold(threshold: f64) {
+  println!("threshold: {threshold:.2}");
+}
+
+fn display_threshold_table(config: &config::VioletConfig) {
+  print!("{}", threshold_table(config));
+}
+
+fn threshold_table(config: &config::VioletConfig) -> Table {
+  let mut table = Table::new()
+    .column(Column::new("language").min_width(23))
+    .column(Column::new("threshold").align(Align::Right))
+    .rule('=')
+    .row(["default".to_string(), format!("{:.2}", config.complexity.thresholds.default)]);
+
+  let mut sorted_thresholds: Vec<_> = config.complexity.thresholds.extensions.iter().collect();
+  sorted_thresholds.sort_by_key(|(ext, _)| ext.as_str());
+
+  for (extension, threshold) in sorted_thresholds {
+    table.push_row([extension_to_language(extension).to_string(), format!("{threshold:.2}")]);
+  }
+  table
+}
+
+fn analyze_paths(
+  args: &LintArgs,
+  tree: &ConfigTree,
+  changed: Option<&HashSet<PathBuf>>,
+) -> Result<Vec<AnalyzedFile>> {
+  let mut files = Vec::new();
+
+  for path in &args.paths {
+    if path.is_file() {
+      if is_changed(path, changed) {
+        files.extend(analyze_single_file(path, tree)?);
+      }
+    } else if path.is_dir() {
+      files.extend(analyze_directory(path, tree, args, changed)?);
+    } else {
+      eprintln!("Warning: {} is not a file or directory", path.display());
+    }
+  }
+
+  Ok(files)
+}
+
+fn analyze_single_file(path: &Path, tree: &ConfigTree) -> Result<Option<AnalyzedFile>> {
+  let config = tree.for_file(path)?;
+  if tree.ignores(&config, path) {
+    return Ok(None);
+  }
+
+  match simplicity::analyze_file(path, &config) {
+    Ok(analysis) => {
+      Ok(Some(AnalyzedFile { analysis, threshold: config::get_threshold(&config, path) }))
+    }
+    Err(e) => {
+      eprintln!("Error analyzing {}: {}", path.display(), e);
+      Ok(None)
+    }
+  }
+}
+
+fn analyze_directory(
+  path: &Path,
+  tree: &ConfigTree,
+  args: &LintArgs,
+  changed: Option<&HashSet<PathBuf>>,
+) -> Result<Vec<AnalyzedFile>> {
+  let options = traversal::TraversalOptions { include_submodules: args.include_submodules };
+  let traversal = traversal::collect_files(path, tree, options)?;
+  if args.verbose {
+    report_skipped_roots(&traversal.skipped);
+  }
+
+  let mut files = Vec::new();
+  for file_path in traversal.files.iter().filter(|file_path| is_changed(file_path, changed)) {
+    files.extend(analyze_single_file(file_path, tree)?);
+  }
+  Ok(files)
+}
+
+/// Whether a file is in the changed set, when analysis is limited to one
+fn is_changed(path: &Path, changed: Option<&HashSet<PathBuf>>) -> bool {
+  changed.is_none_or(|changed| {
+    std::fs::canonicalize(path).is_ok_and(|canonical| changed.contains(&canonical))
+  })
+}
+
+fn print_text(files: &[AnalyzedFile], config: &config::VioletConfig, quiet: bool) {
+  let violation_output: Vec<String> = files
+    .iter()
+    .filter_map(|file| process_file_analysis(&file.analysis, quiet, file.threshold))
+    .collect();
+
+  print_results(violation_output, config);
+}
+
+/// Files worth reporting: those with violations, plus ignored files unless quiet
+fn reported(files: &[AnalyzedFile], quiet: bool) -> impl Iterator<Item = &AnalyzedFile> {
+  files
+    .iter()
+    .filter(move |file| file.violations().next().is_some() || (file.analysis.ignored && !quiet))
+}
+
+fn json_report(files: &[AnalyzedFile], violations: usize, quiet: bool) -> Result<String> {
+  let files = reported(files, quiet)
+    .map(|file| FileReport {
+      path: file.analysis.file_path.display().to_string(),
+      ignored: file.analysis.ignored,
+      threshold: file.threshold,
+      chunks: file
+        .violations()
+        .map(|chunk| ChunkReport {
+          start_line: chunk.start_line,
+          end_line: chunk.end_line,
+          score: chunk.score,
+        })
+        .collect(),
+    })
+    .collect();
+
+  Ok(format!("{}\n", serde_json::to_string_pretty(&LintReport { violations, files })?))
+}
+
+fn html_report(files: &[AnalyzedFile], violations: usize, quiet: bool) -> String {
+  let files: Vec<html::ReportFile> = reported(files, quiet)
+    .map(|file| html::ReportFile {
+      path: file.analysis.file_path.display().to_string(),
+      ignored: file.analysis.ignored,
+      threshold: file.threshold,
+      chunks: file.violations().collect(),
+    })
+    .collect();
+  html::render(&files, violations)
+}
+
+/// Print a rendered report, or write it to `path` when one was given
+fn write_report(report: &str, path: Option<&Path>) -> Result<()> {
+  match path {
+    Some(path) => std::fs::write(path, report)
+      .with_context(|| format!("Failed to write report to {}", path.display())),
+    None => {
+      print!("{report}");
+      Ok(())
+    }
+  }
+}
+
+fn print_results(violation_output: Vec<String>, config: &config::VioletConfig) {
+  print_tool_announcement();
+
+  if !violation_output.is_empty() {
+    display_threshold_config(config);
+    print_violations_table(&violation_output);
+  } else {
+    print_success_message();
+  }
+}
+
+fn print_tool_announcement() {
+  println!(
+    "{}",
+    "Violet - A Versatile, Intuitive, and Objective Legibility Evaluation Tool".purple().bold()
+  );
+  println!();
+}
+
+fn print_violations_table(violation_output: &[String]) {
+  let score_width = "score".len();
+  let chunk_width = TOTAL_WIDTH - score_width - PADDING;
+
+  println!("{:<width$} score", "chunk", width = chunk_width);
+  println!("{}", "=".repeat(TOTAL_WIDTH));
+
+  for output in violation_output {
+    print!("{output}");
+  }
+}
+
+fn print_success_message() {
+  println!("No issues found. What beautiful code you have!");
+}
+
+fn report_skipped_roots(skipped: &[traversal::SkippedRoot]) {
+  for root in skipped {
+    eprintln!("{} {} ({})", "skipped".dimmed(), root.path.display(), root.reason);
+  }
+}
+
+fn format_chunk_preview(chunk: &scoring::ComplexityRegion) -> String {
+  let mut output = String::new();
+  let preview_lines: Vec<&str> = chunk.preview.lines().collect();
+
+  for line in preview_lines.iter() {
+    if layout::display_width(line) > 70 {
+      let truncated = layout::truncate(line, 70);
+      output.push_str(&format!("    {}\n", truncated.dimmed()));
+    } else {
+      output.push_str(&format!("    {line}\n"));
+    }
+  }
+
+  output
+}
+
+/// Logarithmic scaling for component display
+fn scale_component_score(score: f64) -> f64 {
+  (1.0_f64 + score).ln()
+}
+
+fn report_subscore(name: &str, scaled_score: f64, percent: f64) -> String {
+  format!("    {name}: {scaled_score:.2} ({percent:.0}%)\n")
+}
+
+fn format_complexity_breakdown(breakdown: &scoring::ComplexityBreakdown) -> String {
+  let mut output = String::new();
+
+  let depth_scaled = scale_component_score(breakdown.depth_score);
+  let verbosity_scaled = scale_component_score(breakdown.verbosity_score);
+  let syntactic_scaled = scale_component_score(breakdown.syntactic_score);
+
+  output.push_str(&report_subscore("depth", depth_scaled, breakdown.depth_percent));
+  output.push_str(&report_subscore("verbosity", verbosity_scaled, breakdown.verbosity_percent));
+  output.push_str(&report_subscore("syntactics", syntactic_scaled, breakdown.syntactic_percent));
+
+  output
+}
+
+fn format_violating_chunk(chunk: &scoring::ComplexityRegion) -> String {
+  let mut output = String::new();
+
+  let chunk_display = format!("- lines {}-{}", chunk.start_line, chunk.end_line);
+  let score_str = format!("{:.2}", chunk.score);
+  output.push_str(&format_aligned_row(&chunk_display, &score_str, true, false));
+
+  output.push_str(&format_chunk_preview(chunk));
+  output.push_str(&format_complexity_breakdown(&chunk.breakdown));
+
+  output
+}
+
+fn handle_ignored_file(analysis: &simplicity::FileAnalysis, quiet: bool) -> Option<String> {
+  if !quiet {
+    let mut output = String::new();
+    output.push_str(&format_aligned_row(
+      &analysis.file_path.display().to_string(),
+      "(ignored)",
+      false,
+      true,
+    ));
+    Some(output)
+  } else {
+    None
+  }
+}
+
+fn process_file_analysis(
+  analysis: &simplicity::FileAnalysis,
+  quiet: bool,
+  threshold: f64,
+) -> Option<String> {
+  if analysis.ignored {
+    return handle_ignored_file(analysis, quiet);
+  }
+
+  let complex_chunks: Vec<&scoring::ComplexityRegion> =
+    analysis.issues.iter().filter(|chunk| chunk.score > threshold).collect();
+
+  if complex_chunks.is_empty() {
+    return None;
+  }
+
+  let mut output = String::new();
+  output.push_str(&format_file_header(&analysis.file_path.display().to_string()));
+
+  for chunk in complex_chunks {
+    output.push_str(&format_violating_chunk(chunk));
+  }
+
+  Some(output)
+}
+
+fn format_file_header(file_path: &str) -> String {
+  let formatted_file = format_file_path(file_path, TOTAL_WIDTH - 2);
+  format!("{}\n", formatted_file.bold())
+}
+
+fn format_aligned_row(
+  file_or_chunk: &str,
+  score_text: &str,
+  is_error: bool,
+  is_file: bool,
+) -> String {
+  let avg_column_width = score_text.len();
+  let file_column_width = TOTAL_WIDTH - avg_column_width - PADDING;
+
+  let formatted_file = format_file_path(file_or_chunk, file_column_width);
+
+  let colored_score = if is_error {
+    score_text.red().to_string()
+  } else if score_text == "(ignored)" {
+    score_text.dimmed().to_string()
+  } else {
+    score_text.green().to_string()
+  };
+
+  let padding_needed = file_column_width.saturating_sub(layout::display_width(&formatted_file));
+  if is_file {
+    let dashes = "-".repeat(padding_needed);
+    format!("{formatted_file}{dashes} {colored_score}\n")
+  } else {
+    let dots = ".".repeat(padding_needed);
+    format!("{formatted_file}{dots} {colored_score}\n")
+  }
+}
+
+fn format_file_path(path: &str, max_width: usize) -> String {
+  layout::truncate_start(path, max_width)
+}
+
+// violet ignore chunk
+/// Get the static language mapping table
+fn get_language_map() -> &'static HashMap<&'static str, &'static str> {
+  static LANGUAGE_MAP: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+  LANGUAGE_MAP.get_or_init(|| {
+    let mut map = HashMap::new();
+
+    // JavaScript family
+    map.insert(".js", "javascript");
+    map.insert(".mjs", "modules javascript");
+    map.insert(".cjs", "commonjs javascript");
+    map.insert(".jsx", "react javascript");
+    map.insert(".ts", "typescript");
+    map.insert(".tsx", "react typescript");
+
+    // Python family
+    map.insert(".py", "python");
+    map.insert(".pyw", "windows python");
+    map.insert(".pyc", "compiled python");
+
+    // Systems languages
+    map.insert(".rs", "rust");
+    map.insert(".go", "go");
+    map.insert(".c", "C");
+    map.insert(".h", "C headers");
+    map.insert(".cpp", "C++");
+    map.insert(".cc", "C++");
+    map.insert(".cxx", "C++");
+    map.insert(".c++", "C++");
+    map.insert(".hpp", "C++ headers");
+    map.insert(".hxx", "C++ headers");
+
+    // JVM languages
+    map.insert(".java", "java");
+    map.insert(".kt", "kotlin");
+    map.insert(".kts", "kotlin script");
+    map.insert(".scala", "scala");
+    map.insert(".groovy", "groovy");
+    map.insert(".gvy", "groovy");
+    map.insert(".gy", "groovy");
+    map.insert(".gsh", "groovy shell");
+
+    // Other languages
+    map.insert(".cs", "C#");
+    map.insert(".php", "php");
+    map.insert(".rb", "ruby");
+    map.insert(".swift", "swift");
+    map.insert(".hs", "haskell");
+    map.insert(".ex", "elixir");
+    map.insert(".exs", "elixir (script)");
+    map.insert(".pl", "perl");
+    map.insert(".pm", "perl (module)");
+    map.insert(".lua", "lua");
+    map.insert(".dart", "dart");
+    map.insert(".r", "R");
+    map.insert(".R", "R (alt)");
+    map.insert(".m", "matlab");
+    map.insert(".vb", "visual basic");
+    map.insert(".gd", "gdscript");
+    map.insert(".asm", "assembly");
+    map.insert(".s", "assembly");
+
+    // Shell scripts
+    map.insert(".sh", "shell scripts");
+    map.insert(".bash", "bash");
+    map.insert(".zsh", "zsh");
+    map.insert(".fish", "fish");
+    map.insert(".ps1", "powershell");
+
+    // Web technologies
+    map.insert(".html", "html");
+    map.insert(".htm", "html (alt)");
+    map.insert(".css", "css");
+    map.insert(".scss", "sass (scss)");
+    map.insert(".sass", "sass");
+    map.insert(".less", "less");
+    map.insert(".vue", "vue");
+
+    // Data formats
+    map.insert(".json", "json");
+    map.insert(".xml", "xml");
+    map.insert(".yaml", "yaml");
+    map.insert(".yml", "yml");
+    map.insert(".toml", "toml");
+    map.insert(".sql", "sql");
+    map.insert(".md", "markdown");
+
+    // Infrastructure
+    map.insert(".dockerfile", "dockerfile");
+    map.insert(".tf", "terraform");
+    map.insert(".hcl", "hcl");
+
+    map
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::scoring::{ComplexityBreakdown, ComplexityRegion};
+  use std::collections::HashMap;
+  use std::fs;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_format_file_path_no_truncation() {
+    let path = "src/main.rs";
+    let result = format_file_path(path, 20);
+    assert_eq!(result, "src/main.rs");
+  }
+
+  #[test]
+  fn test_report_file_needs_a_report_format() {
+    let args = LintArgs {
+      paths: vec![PathBuf::from(".")],
+      report_file: Some(PathBuf::from("report.html")),
+      ..Default::default()
+    };
+    let error = run(&args, LintOptions::default()).unwrap_err();
+    assert!(error.to_string().contains("--output json or --output html"));
+  }
+
+  #[test]
+  fn test_write_report_to_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("report.html");
+    write_report("<html></html>", Some(&path)).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "<html></html>");
+
+    let missing = temp_dir.path().join("missing").join("report.html");
+    assert!(write_report("", Some(&missing)).is_err());
+  }
+
+  #[test]
+  fn test_format_file_path_with_truncation() {
+    let path = "very/long/path/to/some/file.rs";
+    let result = format_file_path(path, 15);
+    assert_eq!(result, "...some/file.rs");
+    assert_eq!(result.len(), 15);
+  }
+
+  #[test]
+  fn test_format_file_path_exact_length() {
+    let path = "exact_length";
+    let result = format_file_path(path, 12);
+    assert_eq!(result, "exact_length");
+  }
+
+  #[test]
+  fn test_threshold_table_lists_default_then_languages() {
+    let mut config = config::VioletConfig::default();
+    config.complexity.thresholds.default = 7.0;
+    config.complexity.thresholds.extensions.insert(".rs".to_string(), 6.5);
+
+    let lines = threshold_table(&config).render();
+    assert_eq!(lines[0], "language                threshold");
+    assert_eq!(lines[1], "=".repeat(33));
+    assert_eq!(lines[2], format!("default{}7.00", " ".repeat(22)));
+    assert_eq!(lines[3], format!("rust{}6.50", " ".repeat(25)));
+  }
+
+  #[test]
+  fn test_format_explanation_names_sources() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = fs::canonicalize(temp_dir.path()).unwrap();
+    fs::create_dir_all(root.join("legacy")).unwrap();
+    fs::write(
+      root.join("legacy/violet.json"),
+      r#"{"complexity": {"thresholds": {"default": 11.0}}, "ignore_files": ["gen/**"]}"#,
+    )
+    .unwrap();
+    let file = root.join("legacy/gen/api.rs");
+    fs::create_dir_all(file.parent().unwrap()).unwrap();
+    fs::write(&file, "fn main() {}\n").unwrap();
+
+    let tree = ConfigTree::at(&root).unwrap();
+    let output = format_explanation(&file, &tree.explain(&file).unwrap(), &root);
+
+    assert!(output.contains("config files\n  legacy/violet.json\n"));
+    assert!(output.contains("threshold"));
+    assert!(output.contains("11.00 legacy/violet.json"));
+    assert!(output.contains("  legacy/gen/**  (legacy/violet.json)\n"));
+    assert!(output.ends_with("ignored: yes, by \"legacy/gen/**\" from legacy/violet.json\n"));
+  }
+
+  #[test]
+  fn test_parse_line_range() {
+    assert_eq!(parse_line_range("40-80"), Ok((40, 80)));
+    assert_eq!(parse_line_range("12"), Ok((12, 12)));
+    assert!(parse_line_range("80-40").unwrap_err().contains("ends before it starts"));
+    assert!(parse_line_range("0-3").is_err());
+    assert!(parse_line_range("a-b").is_err());
+  }
+
+  #[test]
+  fn test_format_score_explanation() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap();
+    fs::write(root.join("violet.yaml"), "complexity:\n  penalties:\n    depth: 3.0\n").unwrap();
+    let file = root.join("deep.rs");
+    fs::write(
+      &file,
+      "fn one() {\n  1\n}\n\nfn deep() {\n  // violet ignore line depth\n  if a {\n    b\n  }\n}\n",
+    )
+    .unwrap();
+
+    let tree = ConfigTree::at(&root).unwrap();
+    let config = tree.for_file(&file).unwrap();
+    let args = ExplainArgs { file: file.clone(), lines: Some((6, 7)) };
+    let chunks = simplicity::explain_file(&file, &config, args.lines).unwrap();
+    let output =
+      format_score_explanation(&args, &tree.explain(&file).unwrap(), chunks.as_deref(), &root);
+
+    assert!(output.contains(", lines 6-7\n"));
+    assert!(output.contains("3.00 violet.yaml"));
+    assert!(!output.contains("fn one()"));
+    assert!(output.contains("lines 5-10: "));
+    assert!(output.contains("if a {"));
+    assert!(output.contains("score = ln("));
+    let ignored_row = output.lines().find(|line| line.ends_with("if a {")).unwrap();
+    assert!(ignored_row.contains(" - "));
+  }
+
+  #[test]
+  fn test_format_file_header() {
+    let result = format_file_header("src/test.rs");
+    assert!(result.contains("src/test.rs"));
+    assert!(result.ends_with('\n'));
+  }
+
+  #[test]
+  fn test_format_aligned_row_chunk() {
+    let result = format_aligned_row("- lines 10-20", "7.5", true, false);
+    assert!(result.contains("- lines 10-20"));
+    assert!(result.contains("7.5"));
+    assert!(result.contains('.'));
+    assert!(result.ends_with('\n'));
+  }
+
+  #[test]
+  fn test_format_aligned_row_file() {
+    let result = format_aligned_row("src/main.rs", "6.2", false, true);
+    assert!(result.contains("src/main.rs"));
+    assert!(result.contains("6.2"));
+    assert!(result.contains('-'));
+    assert!(result.ends_with('\n'));
+  }
+
+  #[test]
+  fn test_format_aligned_row_ignored() {
+    let result = format_aligned_row("src/ignored.rs", "(ignored)", false, true);
+    assert!(result.contains("src/ignored.rs"));
+    assert!(result.contains("(ignored)"));
+  }
+
+  #[test]
+  fn test_collect_files_recursively_empty_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = config::VioletConfig {
+      complexity: config::ComplexityConfig {
+        thresholds: config::ThresholdConfig { default: 6.0, extensions: HashMap::new() },
+        penalties: config::PenaltyConfig::default(),
+      },
+      ..Default::default()
+    };
+
+    let file1_path = temp_dir.path().join("test1.rs");
+    fs::write(&file1_path, "fn main() {}").unwrap();
+
+    let subdir = temp_dir.path().join("subdir");
+    fs::create_dir(&subdir).unwrap();
+    let file2_path = subdir.join("test2.rs");
+    fs::write(&file2_path, "fn test() {}").unwrap();
+
+    let files =
+      traversal::collect_files(temp_dir.path(), &ConfigTree::flat(config), Default::default())
+        .unwrap()
+        .files;
+
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().any(|f| f.file_name().unwrap() == "test1.rs"));
+    assert!(files.iter().any(|f| f.file_name().unwrap() == "test2.rs"));
+  }
+
+  #[test]
+  fn test_collect_files_recursively_with_ignore_patterns() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = config::VioletConfig {
+      complexity: config::ComplexityConfig {
+        thresholds: config::ThresholdConfig { default: 6.0, extensions: HashMap::new() },
+        penalties: config::PenaltyConfig::default(),
+      },
+      ignore_files: vec!["*.ignored".to_string(), "temp*".to_string()],
+      ..Default::default()
+    };
+
+    let included_file = temp_dir.path().join("included.rs");
+    fs::write(&included_file, "fn main() {}").unwrap();
+
+    let ignored_file1 = temp_dir.path().join("test.ignored");
+    fs::write(&ignored_file1, "should be ignored").unwrap();
+
+    let ignored_file2 = temp_dir.path().join("temp_file.rs");
+    fs::write(&ignored_file2, "should be ignored").unwrap();
+
+    let files =
+      traversal::collect_files(temp_dir.path(), &ConfigTree::flat(config), Default::default())
+        .unwrap()
+        .files;
+
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].file_name().unwrap(), "included.rs");
+  }
+
+  #[test]
+  fn test_format_chunk_preview_simple() {
+    let chunk_score = ComplexityRegion {
+      score: 5.0,
+      start_line: 1,
+      end_line: 3,
+      preview: "fn simple() {\n    return 42;\n}".to_string(),
+      breakdown: ComplexityBreakdown {
+        depth_score: 2.0,
+        depth_percent: 40.0,
+        verbosity_score: 2.0,
+        verbosity_percent: 40.0,
+        syntactic_score: 1.0,
+        syntactic_percent: 20.0,
+      },
+    };
+
+    let preview = format_chunk_preview(&chunk_score);
+
+    assert!(preview.contains("fn simple() {"));
+    assert!(preview.contains("return 42;"));
+    assert!(preview.contains("}"));
+    assert!(preview.contains("    fn simple() {"));
+    assert!(preview.contains("        return 42;"));
+    assert!(preview.contains("    }"));
+  }
+
+  #[test]
+  fn test_format_chunk_preview_long_lines() {
+    let long_line = "a".repeat(100);
+    let chunk_score = ComplexityRegion {
+      score: 5.0,
+      start_line: 1,
+      end_line: 1,
+      preview: long_line,
+      breakdown: ComplexityBreakdown {
+        depth_score: 1.0,
+        depth_percent: 100.0,
+        verbosity_score: 0.0,
+        verbosity_percent: 0.0,
+        syntactic_score: 0.0,
+        syntactic_percent: 0.0,
+      },
+    };
+
+    let preview = format_chunk_preview(&chunk_score);
+
+    assert!(preview.contains("..."));
+    assert!(preview.len() < 100);
+  }
+
+  #[test]
+  fn test_format_chunk_preview_many_lines() {
+    let many_lines = (1..10).map(|i| format!("line {i}")).collect::<Vec<_>>().join("\n");
+    let chunk_score = ComplexityRegion {
+      score: 5.0,
+      start_line: 1,
+      end_line: 5,
+      preview: many_lines,
+      breakdown: ComplexityBreakdown {
+        depth_score: 2.0,
+        depth_percent: 50.0,
+        verbosity_score: 2.0,
+        verbosity_percent: 50.0,
+        syntactic_score: 0.0,
+        syntactic_percent: 0.0,
+      },
+    };
+
+    let preview = format_chunk_preview(&chunk_score);
+
+    assert!(preview.contains("line 1"));
+    assert!(preview.contains("line 5"));
+    assert!(preview.contains("line 9"));
+    assert!(!preview.contains("..."));
+  }
+
+  #[test]
+  fn test_format_chunk_preview_unicode_characters() {
+    let prefix = "a".repeat(66);
+    let line_with_unicode = format!("{prefix}←rest of the line");
+    let chunk_score = ComplexityRegion {
+      score: 5.0,
+      start_line: 1,
+      end_line: 1,
+      preview: line_with_unicode,
+      breakdown: ComplexityBreakdown {
+        depth_score: 1.0,
+        depth_percent: 100.0,
+        verbosity_score: 0.0,
+        verbosity_percent: 0.0,
+        syntactic_score: 0.0,
+        syntactic_percent: 0.0,
+      },
+    };
+
+    let preview = format_chunk_preview(&chunk_score);
+    assert!(preview.contains("..."));
+    assert!(preview.contains("aaa"));
+    assert!(!preview.is_empty());
+  }
+
+  #[test]
+  fn test_scale_component_score() {
+    assert_eq!(scale_component_score(0.0), (1.0_f64).ln());
+    assert_eq!(scale_component_score(1.0), (2.0_f64).ln());
+    assert_eq!(scale_component_score(10.0), (11.0_f64).ln());
+
+    let small = scale_component_score(1.0);
+    let medium = scale_component_score(10.0);
+    let large = scale_component_score(100.0);
+
+    assert!(small < medium);
+    assert!(medium < large);
+    assert!(large.is_finite());
+  }
+
+  #[test]
+  fn test_report_subscore() {
+    let result = report_subscore("Depth", 5.5, 33.3);
+
+    assert!(result.contains("Depth"));
+    assert!(result.contains("5.5"));
+    assert!(result.contains("33%"));
+    assert!(result.contains("("));
+    assert!(result.contains(")"));
+  }
+
+  #[test]
+  fn test_format_file_header_line_ending() {
+    let file_path = "src/main.rs";
+    let header = format_file_header(file_path);
+
+    assert!(header.contains("src/main.rs"));
+    assert!(header.ends_with('\n'));
+  }
+
+  #[test]
+  fn test_format_file_header_long_path() {
+    let long_path = "very/long/path/to/some/deeply/nested/file/that/might/exceed/normal/width.rs";
+    let header = format_file_header(long_path);
+
+    assert!(header.contains("file"));
+    assert!(header.contains(".rs"));
+    assert!(header.ends_with('\n'));
+  }
+
+  #[test]
+  fn test_format_violating_chunk() {
+    let chunk_score = ComplexityRegion {
+      score: 8.5,
+      start_line: 10,
+      end_line: 15,
+      preview: "fn complex() {\n    if deeply {\n        nested();\n    }\n}".to_string(),
+      breakdown: ComplexityBreakdown {
+        depth_score: 4.0,
+        depth_percent: 50.0,
+        verbosity_score: 2.0,
+        verbosity_percent: 25.0,
+        syntactic_score: 2.0,
+        syntactic_percent: 25.0,
+      },
+    };
+
+    let formatted = format_violating_chunk(&chunk_score);
+
+    assert!(formatted.contains("8.5"));
+
+    assert!(formatted.contains("10") || formatted.contains("15"));
+
+    assert!(formatted.contains("fn complex()"));
+
+    assert!(formatted.contains("Depth") || formatted.contains("depth"));
+  }
+
+  #[test]
+  fn test_format_file_path_truncation() {
+    let normal_path = "src/main.rs";
+    let formatted_normal = format_file_path(normal_path, 50);
+    assert_eq!(formatted_normal, normal_path);
+
+    let long_path = "very/long/path/to/some/deeply/nested/file.rs";
+    let formatted_long = format_file_path(long_path, 20);
+
+    assert!(formatted_long.len() <= 20);
+    assert!(formatted_long.contains("...") || formatted_long.contains("file.rs"));
+  }
+
+  #[test]
+  fn test_collect_files_recursively_depth() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = config::VioletConfig {
+      complexity: config::ComplexityConfig {
+        thresholds: config::ThresholdConfig { default: 6.0, extensions: HashMap::new() },
+        penalties: config::PenaltyConfig::default(),
+      },
+      ..Default::default()
+    };
+
+    let level1 = temp_dir.path().join("level1");
+    fs::create_dir(&level1).unwrap();
+    let level2 = level1.join("level2");
+    fs::create_dir(&level2).unwrap();
+    let level3 = level2.join("level3");
+    fs::create_dir(&level3).unwrap();
+
+    fs::write(temp_dir.path().join("root.rs"), "root file").unwrap();
+    fs::write(level1.join("level1.rs"), "level1 file").unwrap();
+    fs::write(level2.join("level2.rs"), "level2 file").unwrap();
+    fs::write(level3.join("level3.rs"), "level3 file").unwrap();
+
+    let files =
+      traversal::collect_files(temp_dir.path(), &ConfigTree::flat(config), Default::default())
+        .unwrap()
+        .files;
+
+    assert_eq!(files.len(), 4);
+    let file_names: Vec<_> =
+      files.iter().map(|f| f.file_name().unwrap().to_str().unwrap()).collect();
+    assert!(file_names.contains(&"root.rs"));
+    assert!(file_names.contains(&"level1.rs"));
+    assert!(file_names.contains(&"level2.rs"));
+    assert!(file_names.contains(&"level3.rs"));
+  }
+
+  #[test]
+  fn test_extension_to_language() {
+    assert_eq!(extension_to_language(".rs"), "rust");
+    assert_eq!(extension_to_language(".js"), "javascript");
+    assert_eq!(extension_to_language(".ts"), "typescript");
+    assert_eq!(extension_to_language(".py"), "python");
+    assert_eq!(extension_to_language(".go"), "go");
+    assert_eq!(extension_to_language(".java"), "java");
+
+    assert_eq!(extension_to_language(".cpp"), "C++");
+    assert_eq!(extension_to_language(".cc"), "C++");
+    assert_eq!(extension_to_language(".cxx"), "C++");
+
+    assert_eq!(extension_to_language(".sh"), "shell scripts");
+    assert_eq!(extension_to_language(".bash"), "bash");
+    assert_eq!(extension_to_language(".zsh"), "zsh");
+
+    assert_eq!(extension_to_language(".unknown"), ".unknown");
+    assert_eq!(extension_to_language(".xyz"), ".xyz");
+
+    assert_eq!(extension_to_language(".R"), "R (alt)");
+    assert_eq!(extension_to_language(".r"), "R");
+
+    assert_eq!(extension_to_language(".js"), "javascript");
+    assert_eq!(extension_to_language(".jsx"), "react javascript");
+    assert_eq!(extension_to_language(".ts"), "typescript");
+    assert_eq!(extension_to_language(".tsx"), "react typescript");
+
+    assert_eq!(extension_to_language(".c"), "C");
+    assert_eq!(extension_to_language(".h"), "C headers");
+    assert_eq!(extension_to_language(".cpp"), "C++");
+    assert_eq!(extension_to_language(".hpp"), "C++ headers");
+
+    assert_eq!(extension_to_language(".mjs"), "modules javascript");
+    assert_eq!(extension_to_language(".cjs"), "commonjs javascript");
+
+    assert_eq!(extension_to_language(".py"), "python");
+    assert_eq!(extension_to_language(".pyw"), "windows python");
+    assert_eq!(extension_to_language(".pyc"), "compiled python");
+  }
+}
@@ -1,8 +1,8 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use glob::Pattern;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Configuration file format
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
@@ -13,6 +13,8 @@ pub struct VioletConfig {
   pub ignore_files: Vec<String>,
   #[serde(default)]
   pub ignore_patterns: Vec<String>,
+  #[serde(default)]
+  pub penalty_patterns: Vec<PenaltyPattern>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
@@ -43,6 +45,52 @@ pub struct PenaltyConfig {
   pub syntactics: f64,
 }
 
+/// A regex whose matches count extra toward the syntactic subscore of a line
+///
+/// ```yaml
+/// penalty_patterns:
+///   - pattern: '\.unwrap\(\)'
+///     weight: 3
+///     extensions: [".rs"]
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PenaltyPattern {
+  pub pattern: String,
+  /// Special characters each match counts as
+  pub weight: f64,
+  /// Extensions the pattern applies to, e.g. ".ts"; every file when empty
+  #[serde(default)]
+  pub extensions: Vec<String>,
+}
+
+impl PenaltyPattern {
+  pub fn applies_to<P: AsRef<Path>>(&self, file_path: P) -> bool {
+    if self.extensions.is_empty() {
+      return true;
+    }
+    let extension = file_path.as_ref().extension().and_then(|ext| ext.to_str());
+    extension.is_some_and(|extension| self.extensions.contains(&format!(".{extension}")))
+  }
+
+  fn validate(&self) -> Result<()> {
+    regex::Regex::new(&self.pattern)
+      .with_context(|| format!("Invalid penalty pattern '{}'", self.pattern))?;
+    if !self.weight.is_finite() || self.weight < 0.0 {
+      return Err(anyhow!(
+        "Weight of penalty pattern '{}' must be a non-negative number",
+        self.pattern
+      ));
+    }
+    if let Some(extension) = self.extensions.iter().find(|extension| !extension.starts_with('.')) {
+      return Err(anyhow!(
+        "Extension '{extension}' of penalty pattern '{}' must start with '.'",
+        self.pattern
+      ));
+    }
+    Ok(())
+  }
+}
+
 impl Default for PenaltyConfig {
   fn default() -> Self {
     Self {
@@ -75,7 +123,11 @@ fn default_syntactics_penalty() -> f64 {
   1.15
 }
 
-fn default_global_config() -> VioletConfig {
+/// Config file names, looked up in the project root and every directory below it
+pub const CONFIG_FILES: [&str; 2] = ["violet.yaml", "violet.json"];
+
+/// Built-in configuration that every project config is merged over
+pub fn default_global_config() -> VioletConfig {
   VioletConfig {
     complexity: ComplexityConfig {
       thresholds: ThresholdConfig::default(),
@@ -83,6 +135,7 @@ fn default_global_config() -> VioletConfig {
     },
     ignore_files: get_default_ignored_files(),
     ignore_patterns: vec![],
+    penalty_patterns: vec![],
   }
 }
 
@@ -109,41 +162,64 @@ pub fn get_threshold<P: AsRef<Path>>(config: &VioletConfig, file_path: P) -> f64
 }
 
 pub fn should_ignore_file<P: AsRef<Path>>(config: &VioletConfig, file_path: P) -> bool {
+  matching_ignore(&config.ignore_files, file_path).is_some()
+}
+
+/// The first of `patterns` that ignores the file
+pub fn matching_ignore<P: AsRef<Path>>(patterns: &[String], file_path: P) -> Option<&String> {
   let path_str = file_path.as_ref().to_string_lossy();
 
   // Handle both "./path" and "path" formats
   let normalized_path =
     if let Some(stripped) = path_str.strip_prefix("./") { stripped } else { &path_str };
 
-  for pattern in config.ignore_files.iter() {
-    if matches_glob(&path_str, pattern) || matches_glob(normalized_path, pattern) {
-      return true;
+  patterns
+    .iter()
+    .find(|pattern| matches_glob(&path_str, pattern) || matches_glob(normalized_path, pattern))
+}
+
+/// The config file in `dir`, if it has one
+pub fn find_config_file(dir: &Path) -> Result<Option<PathBuf>> {
+  let mut found = CONFIG_FILES.iter().map(|name| dir.join(name)).filter(|path| path.is_file());
+
+  match (found.next(), found.next()) {
+    (Some(first), Some(second)) => {
+      Err(anyhow!("Both {} and {} exist; keep only one", first.display(), second.display()))
     }
+    (first, _) => Ok(first),
   }
-  false
 }
 
 fn load_project_config() -> Result<Option<VioletConfig>> {
   let current_dir = std::env::current_dir().context("Failed to get current working directory")?;
 
-  let project_config_path = current_dir.join("violet.yaml");
-
-  if project_config_path.exists() {
-    let config = load_config_file(&project_config_path).with_context(|| {
-      format!("Failed to load project config from {}", project_config_path.display())
-    })?;
-    Ok(Some(config))
-  } else {
-    Ok(None)
+  match find_config_file(&current_dir)? {
+    Some(project_config_path) => {
+      let config = load_config_file(&project_config_path).with_context(|| {
+        format!("Failed to load project config from {}", project_config_path.display())
+      })?;
+      Ok(Some(config))
+    }
+    None => Ok(None),
   }
 }
 
-fn load_config_file(path: &Path) -> Result<VioletConfig> {
+pub fn load_config_file(path: &Path) -> Result<VioletConfig> {
   let content = std::fs::read_to_string(path)
     .with_context(|| format!("Failed to read config file: {}", path.display()))?;
 
-  serde_yaml::from_str(&content)
-    .with_context(|| format!("Failed to parse YAML config file: {}", path.display()))
+  let config: VioletConfig = if path.extension().is_some_and(|ext| ext == "json") {
+    serde_json::from_str(&content)
+      .with_context(|| format!("Failed to parse JSON config file: {}", path.display()))?
+  } else {
+    serde_yaml::from_str(&content)
+      .with_context(|| format!("Failed to parse YAML config file: {}", path.display()))?
+  };
+
+  for pattern in &config.penalty_patterns {
+    pattern.validate().with_context(|| format!("Invalid config file: {}", path.display()))?;
+  }
+  Ok(config)
 }
 
 /// Merge ignore patterns, removing duplicates
@@ -163,14 +239,34 @@ fn merge_ignore_patterns(
   result
 }
 
-fn merge(global: VioletConfig, project: Option<VioletConfig>) -> VioletConfig {
+/// Overlay `project` on `global`: values it changes from the defaults win, ignore lists combine
+pub fn merge(global: VioletConfig, project: Option<VioletConfig>) -> VioletConfig {
   let project = project.unwrap_or_default();
 
   let merged_thresholds = merge_threshold_configs(&global, &project);
   let merged_penalties = merge_penalty_configs(&global, &project);
   let merged_ignores = merge_ignore_configs(&global, &project);
+  let penalty_patterns = merge_penalty_patterns(global.penalty_patterns, project.penalty_patterns);
+
+  build_merged_config(merged_thresholds, merged_penalties, merged_ignores, penalty_patterns)
+}
 
-  build_merged_config(merged_thresholds, merged_penalties, merged_ignores)
+/// Combine penalty patterns; a project pattern for the same regex and extensions reweights it
+fn merge_penalty_patterns(
+  global: Vec<PenaltyPattern>,
+  project: Vec<PenaltyPattern>,
+) -> Vec<PenaltyPattern> {
+  let mut result = global;
+  for pattern in project {
+    let existing = result.iter_mut().find(|existing| {
+      existing.pattern == pattern.pattern && existing.extensions == pattern.extensions
+    });
+    match existing {
+      Some(existing) => existing.weight = pattern.weight,
+      None => result.push(pattern),
+    }
+  }
+  result
 }
 
 fn merge_threshold_configs(global: &VioletConfig, project: &VioletConfig) -> ThresholdConfig {
@@ -234,11 +330,13 @@ fn build_merged_config(
   thresholds: ThresholdConfig,
   penalties: PenaltyConfig,
   (ignore_files, ignore_patterns): (Vec<String>, Vec<String>),
+  penalty_patterns: Vec<PenaltyPattern>,
 ) -> VioletConfig {
   VioletConfig {
     complexity: ComplexityConfig { thresholds, penalties },
     ignore_files,
     ignore_patterns,
+    penalty_patterns,
   }
 }
 
@@ -700,6 +798,77 @@ ignore_files:
     assert!(config.ignore_files.contains(&"temp/**".to_string()));
   }
 
+  #[test]
+  fn test_load_config_file_penalty_patterns() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("violet.yaml");
+    std::fs::write(
+      &path,
+      r#"penalty_patterns:
+  - pattern: '\.unwrap\(\)'
+    weight: 3
+    extensions: [".rs"]
+  - pattern: 'TODO'
+    weight: 0.5
+"#,
+    )
+    .unwrap();
+
+    let config = load_config_file(&path).unwrap();
+    assert_eq!(config.penalty_patterns.len(), 2);
+    assert_eq!(config.penalty_patterns[0].pattern, r"\.unwrap\(\)");
+    assert_eq!(config.penalty_patterns[0].weight, 3.0);
+    assert!(config.penalty_patterns[0].applies_to("src/main.rs"));
+    assert!(!config.penalty_patterns[0].applies_to("src/main.ts"));
+    assert!(config.penalty_patterns[1].applies_to("Makefile"));
+
+    let json = dir.path().join("violet.json");
+    std::fs::write(&json, r#"{"penalty_patterns": [{"pattern": ":\\s*any", "weight": 2}]}"#)
+      .unwrap();
+    assert_eq!(load_config_file(&json).unwrap().penalty_patterns[0].pattern, r":\s*any");
+  }
+
+  #[test]
+  fn test_load_config_file_rejects_bad_penalty_patterns() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("violet.yaml");
+    let error = |yaml: &str| {
+      std::fs::write(&path, yaml).unwrap();
+      format!("{:#}", load_config_file(&path).unwrap_err())
+    };
+
+    assert!(error("penalty_patterns:\n  - pattern: 'unwrap('\n    weight: 1\n")
+      .contains("Invalid penalty pattern 'unwrap('"));
+    assert!(error("penalty_patterns:\n  - pattern: 'x'\n    weight: -1\n")
+      .contains("must be a non-negative number"));
+    assert!(error("penalty_patterns:\n  - pattern: 'x'\n    weight: 1\n    extensions: [rs]\n")
+      .contains("must start with '.'"));
+    assert!(error("penalty_patterns:\n  - pattern: 'x'\n").contains("Failed to parse"));
+  }
+
+  #[test]
+  fn test_merge_penalty_patterns() {
+    let pattern = |pattern: &str, weight: f64, extensions: &[&str]| PenaltyPattern {
+      pattern: pattern.to_string(),
+      weight,
+      extensions: extensions.iter().map(|extension| extension.to_string()).collect(),
+    };
+    let global = VioletConfig {
+      penalty_patterns: vec![pattern("unwrap", 1.0, &[".rs"]), pattern("TODO", 1.0, &[])],
+      ..Default::default()
+    };
+    let project = VioletConfig {
+      penalty_patterns: vec![pattern("unwrap", 4.0, &[".rs"]), pattern("any", 2.0, &[".ts"])],
+      ..Default::default()
+    };
+
+    let merged = merge(global, Some(project)).penalty_patterns;
+    assert_eq!(
+      merged,
+      [pattern("unwrap", 4.0, &[".rs"]), pattern("TODO", 1.0, &[]), pattern("any", 2.0, &[".ts"])]
+    );
+  }
+
   #[test]
   fn test_load_config_file_invalid_yaml() {
     use std::io::Write;
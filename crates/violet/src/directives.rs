@@ -1,49 +1,183 @@
 //! Violet directive processing
 //!
-//! Handles parsing and processing of violet directives in source code.
+//! Directives live in comments, in any comment style:
+//!
+//! - `violet ignore file` - skip the whole file
+//! - `violet ignore chunk` - skip the chunk containing the directive
+//! - `violet ignore start` / `violet ignore end` - skip the lines between (ranges nest)
+//! - `violet ignore line` - skip the next line
+//!
+//! Any of them can be scoped to subscores by naming them right after the directive,
+//! e.g. `violet ignore chunk verbosity` or `violet ignore start depth, syntactics`.
+//! Scoped lines are still scored, just without those subscores. Text after the
+//! directive that isn't a subscore name is a free-form comment.
+//!
+//! Every directive is parsed by [`parse`]; the rest of violet works from its result.
 
 use regex::Regex;
+use std::sync::OnceLock;
+
+/// What a directive applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+  File,
+  Chunk,
+  Start,
+  End,
+  Line,
+}
+
+/// Subscores an ignore directive switches off
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Scope {
+  pub depth: bool,
+  pub verbosity: bool,
+  pub syntactics: bool,
+}
+
+impl Scope {
+  /// Every subscore, i.e. ignore outright
+  pub const ALL: Scope = Scope { depth: true, verbosity: true, syntactics: true };
+
+  pub fn is_all(self) -> bool {
+    self == Self::ALL
+  }
+
+  pub fn is_empty(self) -> bool {
+    self == Self::default()
+  }
+
+  pub fn union(self, other: Scope) -> Scope {
+    Scope {
+      depth: self.depth || other.depth,
+      verbosity: self.verbosity || other.verbosity,
+      syntactics: self.syntactics || other.syntactics,
+    }
+  }
+}
+
+/// A parsed `violet ignore` directive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Directive {
+  pub target: Target,
+  pub scope: Scope,
+}
+
+const DIRECTIVE_PATTERN: &str =
+  r"violet\signore\s(file|chunk|start|end|line)\b((?:[\s,]+(?:depth|verbosity|syntactics)\b)*)";
+
+fn directive_regex() -> &'static Regex {
+  static REGEX: OnceLock<Regex> = OnceLock::new();
+  REGEX.get_or_init(|| Regex::new(DIRECTIVE_PATTERN).unwrap())
+}
+
+/// The directive on a line, if there is one
+pub fn parse(line: &str) -> Option<Directive> {
+  let captures = directive_regex().captures(line)?;
+  let target = match &captures[1] {
+    "file" => Target::File,
+    "chunk" => Target::Chunk,
+    "start" => Target::Start,
+    "end" => Target::End,
+    _ => Target::Line,
+  };
+
+  let mut scope = Scope::default();
+  for word in captures[2].split(|c: char| c.is_whitespace() || c == ',') {
+    match word {
+      "depth" => scope.depth = true,
+      "verbosity" => scope.verbosity = true,
+      "syntactics" => scope.syntactics = true,
+      _ => {}
+    }
+  }
+  let scope = if scope.is_empty() { Scope::ALL } else { scope };
+  Some(Directive { target, scope })
+}
+
+/// A file with ignored lines removed, and the subscores switched off for each kept line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preprocessed {
+  pub content: String,
+  /// One entry per line of `content`
+  pub scopes: Vec<Scope>,
+}
 
 /// Strip out violet directives, returning None if entire file should be ignored
 pub fn preprocess_file(content: &str) -> Option<String> {
-  let lines: Vec<&str> = content.lines().collect();
+  preprocess(content).map(|preprocessed| preprocessed.content)
+}
 
-  if is_ignored_file(&lines) {
+/// Apply every directive except chunk ones, returning None if the file is ignored
+///
+/// Chunk directive lines are kept so the chunk they are in can be found later.
+pub fn preprocess(content: &str) -> Option<Preprocessed> {
+  let lines: Vec<&str> = content.lines().collect();
+  let file_scope = file_scope(&lines);
+  if file_scope.is_all() {
     return None;
   }
 
-  let mut result_lines = Vec::new();
-  let mut ignore_depth = 0;
-  let mut skip_next_line = false;
-
-  for line in lines.iter() {
-    if process_line(line, &mut ignore_depth, &mut skip_next_line, &mut result_lines) {
+  let mut kept = Vec::new();
+  let mut scopes = Vec::new();
+  let mut ranges: Vec<Scope> = Vec::new();
+  let mut next_line: Option<Scope> = None;
+
+  for line in lines {
+    let range_scope = ranges.iter().fold(file_scope, |scope, range| scope.union(*range));
+    if let Some(line_scope) = next_line.take() {
+      let scope = range_scope.union(line_scope);
+      if !scope.is_all() {
+        kept.push(line);
+        scopes.push(scope);
+      }
       continue;
     }
 
-    if ignore_depth == 0 {
-      result_lines.push(*line);
+    match parse(line).map(|directive| (directive.target, directive.scope)) {
+      Some((Target::Start, scope)) => ranges.push(scope),
+      Some((Target::End, _)) => {
+        ranges.pop();
+      }
+      Some((Target::Line, scope)) => next_line = Some(scope),
+      Some((Target::File, _)) => {}
+      Some((Target::Chunk, _)) | None if !range_scope.is_all() => {
+        kept.push(line);
+        scopes.push(range_scope);
+      }
+      Some((Target::Chunk, _)) | None => {}
     }
   }
 
-  Some(result_lines.join("\n"))
+  Some(Preprocessed { content: kept.join("\n"), scopes })
 }
 
-const IGNORE_DIRECTIVE_PATTERN: &str = r"violet\signore\s(file|chunk|start|end|line)";
-const IGNORE_CHUNK_PATTERN: &str = r"violet\signore\schunk";
+/// Subscores ignored for the whole file
+fn file_scope(lines: &[&str]) -> Scope {
+  lines
+    .iter()
+    .filter_map(|line| parse(line))
+    .filter(|directive| directive.target == Target::File)
+    .fold(Scope::default(), |scope, directive| scope.union(directive.scope))
+}
 
 /// Check if lines contain a directive to ignore the entire file
 pub fn is_ignored_file(lines: &[&str]) -> bool {
-  let ignore_regex = Regex::new(IGNORE_DIRECTIVE_PATTERN).unwrap();
-  lines.iter().any(|line| {
-    ignore_regex.captures(line).is_some_and(|caps| caps.get(1).unwrap().as_str() == "file")
-  })
+  file_scope(lines).is_all()
+}
+
+/// Subscores ignored by chunk directives in the chunk
+pub fn chunk_scope(chunk_content: &str) -> Scope {
+  chunk_content
+    .lines()
+    .filter_map(parse)
+    .filter(|directive| directive.target == Target::Chunk)
+    .fold(Scope::default(), |scope, directive| scope.union(directive.scope))
 }
 
 /// Check if chunk content contains a directive to ignore the chunk
 pub fn is_ignored_chunk(chunk_content: &str) -> bool {
-  let ignore_regex = Regex::new(IGNORE_CHUNK_PATTERN).unwrap();
-  chunk_content.lines().any(|line| ignore_regex.is_match(line))
+  chunk_scope(chunk_content).is_all()
 }
 
 /// Check if chunk should be ignored based on directives and regex patterns
@@ -63,59 +197,6 @@ pub fn has_ignored_patterns(chunk_content: &str, ignore_patterns: &[String]) ->
   false
 }
 
-/// Process a specific ignore directive and update state
-pub fn process_directive<'a>(
-  directive: &str,
-  ignore_depth: &mut usize,
-  skip_next_line: &mut bool,
-  result_lines: &mut Vec<&'a str>,
-  line: &'a str,
-) {
-  match directive {
-    "start" => {
-      *ignore_depth += 1;
-    }
-    "end" => {
-      if *ignore_depth > 0 {
-        *ignore_depth -= 1;
-      }
-    }
-    "line" => {
-      *skip_next_line = true;
-    }
-    "chunk" => {
-      // Keep directive lines for chunk removal during analysis
-      if *ignore_depth == 0 {
-        result_lines.push(line);
-      }
-    }
-    _ => {}
-  }
-}
-
-/// Process a single line for ignore directives
-pub fn process_line<'a>(
-  line: &'a str,
-  ignore_depth: &mut usize,
-  skip_next_line: &mut bool,
-  result_lines: &mut Vec<&'a str>,
-) -> bool {
-  let ignore_regex = Regex::new(IGNORE_DIRECTIVE_PATTERN).unwrap();
-
-  if *skip_next_line {
-    *skip_next_line = false;
-    return true;
-  }
-
-  if let Some(captures) = ignore_regex.captures(line) {
-    let directive = captures.get(1).unwrap().as_str();
-    process_directive(directive, ignore_depth, skip_next_line, result_lines, line);
-    return true;
-  }
-
-  false
-}
-
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -238,4 +319,44 @@ mod tests {
     let preprocessed = preprocess_file(&content);
     assert_eq!(preprocessed, None);
   }
+
+  #[test]
+  fn test_parse_scopes_and_free_comments() {
+    let directive = |target, scope| Some(Directive { target, scope });
+    let verbosity = Scope { verbosity: true, ..Scope::default() };
+
+    assert_eq!(parse("// violet ignore chunk"), directive(Target::Chunk, Scope::ALL));
+    assert_eq!(parse("# violet ignore line verbosity"), directive(Target::Line, verbosity));
+    assert_eq!(
+      parse("/* violet ignore start depth, syntactics */"),
+      directive(Target::Start, Scope { depth: true, syntactics: true, ..Scope::default() })
+    );
+    // Free-form text is not a scope, and only whole words count
+    assert_eq!(
+      parse("// violet ignore chunk -- verbosity is unavoidable here"),
+      directive(Target::Chunk, Scope::ALL)
+    );
+    assert_eq!(parse("// violet ignore chunky"), None);
+    assert_eq!(parse("// violet ignore file depthy"), directive(Target::File, Scope::ALL));
+    assert_eq!(parse("fn main() {}"), None);
+  }
+
+  #[test]
+  fn test_preprocess_keeps_scoped_lines_with_their_scope() {
+    let content = "a\n// violet ignore start verbosity\nb\n// violet ignore line depth\nc\n// violet ignore end\nd\n// violet ignore line\ne\nf";
+    let preprocessed = preprocess(content).unwrap();
+    assert_eq!(preprocessed.content, "a\nb\nc\nd\nf");
+
+    let verbosity = Scope { verbosity: true, ..Scope::default() };
+    let expected =
+      [Scope::default(), verbosity, verbosity.union(Scope { depth: true, ..Scope::default() })];
+    assert_eq!(preprocessed.scopes[..3], expected);
+    assert_eq!(preprocessed.scopes[3..], [Scope::default(), Scope::default()]);
+
+    let scoped_file = preprocess("// violet ignore file syntactics\nx").unwrap();
+    assert_eq!(scoped_file.content, "x");
+    assert_eq!(scoped_file.scopes, [Scope { syntactics: true, ..Scope::default() }]);
+    assert!(!is_ignored_chunk("// violet ignore chunk depth\nx"));
+    assert!(chunk_scope("// violet ignore chunk depth\nx").depth);
+  }
 }
@@ -0,0 +1,244 @@
+//! Standalone HTML report
+//!
+//! `--output html` renders a lint run as a single page with no external assets, so
+//! it can be uploaded as a CI artifact and opened anywhere. Every table sorts by
+//! clicking its headers, each chunk shows how its score splits into depth,
+//! verbosity and syntactics, and code previews fold away until needed.
+
+use std::fmt::Write;
+
+use crate::scoring::{ComplexityBreakdown, ComplexityRegion};
+
+/// A file as it appears in the report
+pub struct ReportFile<'a> {
+  pub path: String,
+  pub ignored: bool,
+  pub threshold: f64,
+  /// Chunks over the threshold
+  pub chunks: Vec<&'a ComplexityRegion>,
+}
+
+impl ReportFile<'_> {
+  fn worst(&self) -> Option<f64> {
+    self.chunks.iter().map(|chunk| chunk.score).reduce(f64::max)
+  }
+}
+
+const STYLE: &str = r#"
+body { font: 14px/1.5 system-ui, sans-serif; margin: 2rem auto; max-width: 72rem; color: #222; }
+h1 { color: #6b3fa0; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1.5rem; }
+th, td { padding: .35rem .6rem; border-bottom: 1px solid #ddd; text-align: left; vertical-align: top; }
+th { cursor: pointer; user-select: none; background: #f4f0f9; }
+th::after { content: " \2195"; color: #aaa; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.bar { display: flex; width: 16rem; height: .9rem; border-radius: 3px; overflow: hidden; background: #eee; }
+.bar span { display: block; height: 100%; }
+.depth { background: #6b3fa0; } .verbosity { background: #3f8ea0; } .syntactics { background: #d08b2c; }
+.legend span { display: inline-block; width: .8rem; height: .8rem; margin: 0 .3rem 0 1rem; vertical-align: middle; }
+details pre { background: #f7f7f7; padding: .6rem; overflow-x: auto; margin: .4rem 0 0; }
+.ok { color: #2c7a3f; } .ignored { color: #888; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable th").forEach((th, column) => {
+  th.addEventListener("click", () => {
+    const body = th.closest("table").tBodies[0];
+    const ascending = th.dataset.order !== "asc";
+    th.dataset.order = ascending ? "asc" : "desc";
+    const key = row => {
+      const cell = row.cells[column];
+      const value = cell.dataset.value ?? cell.textContent;
+      return isNaN(parseFloat(value)) ? value : parseFloat(value);
+    };
+    [...body.rows]
+      .sort((a, b) => (key(a) < key(b) ? -1 : key(a) > key(b) ? 1 : 0) * (ascending ? 1 : -1))
+      .forEach(row => body.appendChild(row));
+  });
+});
+"#;
+
+/// Render the whole report
+pub fn render(files: &[ReportFile], violations: usize) -> String {
+  let mut html = String::new();
+  html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+  html.push_str("<title>Violet report</title>\n");
+  let _ = writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>");
+  html.push_str("<h1>Violet report</h1>\n");
+
+  let failing: Vec<&ReportFile> = files.iter().filter(|file| !file.chunks.is_empty()).collect();
+  if failing.is_empty() {
+    html.push_str("<p class=\"ok\">No issues found. What beautiful code you have!</p>\n");
+  } else {
+    let _ = writeln!(
+      html,
+      "<p>{violations} chunk(s) over their threshold in {} file(s).</p>",
+      failing.len()
+    );
+    html.push_str(&summary_table(files));
+    html.push_str(&legend());
+    for file in failing {
+      html.push_str(&file_section(file));
+    }
+  }
+
+  let _ = writeln!(html, "<script>{SCRIPT}</script>\n</body>\n</html>");
+  html
+}
+
+fn summary_table(files: &[ReportFile]) -> String {
+  let mut html = String::from(
+    "<h2>Files</h2>\n<table class=\"sortable\">\n<thead><tr><th>file</th><th>chunks</th>\
+     <th>worst score</th><th>threshold</th></tr></thead>\n<tbody>\n",
+  );
+  for file in files {
+    let name = escape(&file.path);
+    if file.ignored {
+      let _ = writeln!(
+        html,
+        "<tr class=\"ignored\"><td>{name}</td><td class=\"num\" data-value=\"-1\">ignored</td>\
+         <td></td><td></td></tr>"
+      );
+      continue;
+    }
+    let worst = file.worst().map(|score| format!("{score:.2}")).unwrap_or_default();
+    let _ = writeln!(
+      html,
+      "<tr><td><a href=\"#{}\">{name}</a></td><td class=\"num\">{}</td>\
+       <td class=\"num\">{worst}</td><td class=\"num\">{:.2}</td></tr>",
+      anchor(&file.path),
+      file.chunks.len(),
+      file.threshold
+    );
+  }
+  html.push_str("</tbody>\n</table>\n");
+  html
+}
+
+fn legend() -> String {
+  String::from(
+    "<p class=\"legend\">score breakdown:<span class=\"depth\"></span>depth\
+     <span class=\"verbosity\"></span>verbosity<span class=\"syntactics\"></span>syntactics</p>\n",
+  )
+}
+
+fn file_section(file: &ReportFile) -> String {
+  let mut html = String::new();
+  let _ = writeln!(
+    html,
+    "<h3 id=\"{}\">{} <small>(threshold {:.2})</small></h3>",
+    anchor(&file.path),
+    escape(&file.path),
+    file.threshold
+  );
+  html.push_str(
+    "<table class=\"sortable\">\n<thead><tr><th>lines</th><th>score</th><th>breakdown</th>\
+     <th>preview</th></tr></thead>\n<tbody>\n",
+  );
+  for chunk in &file.chunks {
+    let _ = writeln!(
+      html,
+      "<tr><td data-value=\"{start}\">{start}-{end}</td><td class=\"num\">{score:.2}</td>\
+       <td>{bar}</td><td><details><summary>show code</summary><pre>{preview}</pre></details></td></tr>",
+      start = chunk.start_line,
+      end = chunk.end_line,
+      score = chunk.score,
+      bar = breakdown_bar(&chunk.breakdown),
+      preview = escape(&chunk.preview),
+    );
+  }
+  html.push_str("</tbody>\n</table>\n");
+  html
+}
+
+/// Stacked bar of each component's share of the score
+fn breakdown_bar(breakdown: &ComplexityBreakdown) -> String {
+  let parts = [
+    ("depth", breakdown.depth_percent),
+    ("verbosity", breakdown.verbosity_percent),
+    ("syntactics", breakdown.syntactic_percent),
+  ];
+  let mut html = String::from("<div class=\"bar\">");
+  for (name, percent) in parts {
+    let _ = write!(
+      html,
+      "<span class=\"{name}\" style=\"width:{percent:.1}%\" title=\"{name} {percent:.0}%\"></span>"
+    );
+  }
+  html.push_str("</div>");
+  html
+}
+
+/// Id for a file's section, safe to use in a fragment
+fn anchor(path: &str) -> String {
+  let slug: String =
+    path.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' }).collect();
+  format!("file-{slug}")
+}
+
+fn escape(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      _ => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn region(start_line: usize, score: f64, preview: &str) -> ComplexityRegion {
+    ComplexityRegion {
+      score,
+      start_line,
+      end_line: start_line + 4,
+      preview: preview.to_string(),
+      breakdown: ComplexityBreakdown {
+        depth_score: 4.0,
+        depth_percent: 50.0,
+        verbosity_score: 2.0,
+        verbosity_percent: 25.0,
+        syntactic_score: 2.0,
+        syntactic_percent: 25.0,
+      },
+    }
+  }
+
+  #[test]
+  fn test_render_escapes_and_links_files() {
+    let chunk = region(10, 9.5, "if a < b && c { <script> }");
+    let files = vec![
+      ReportFile {
+        path: "src/a&b.rs".to_string(),
+        ignored: false,
+        threshold: 6.0,
+        chunks: vec![&chunk],
+      },
+      ReportFile { path: "gen.rs".to_string(), ignored: true, threshold: 6.0, chunks: vec![] },
+    ];
+
+    let html = render(&files, 1);
+    assert!(html.contains("<pre>if a &lt; b &amp;&amp; c { &lt;script&gt; }</pre>"));
+    assert!(html.contains("<a href=\"#file-src-a-b-rs\">src/a&amp;b.rs</a>"));
+    assert!(html.contains("<h3 id=\"file-src-a-b-rs\">"));
+    assert!(html.contains(">10-14</td><td class=\"num\">9.50</td>"));
+    assert!(html.contains("<tr class=\"ignored\"><td>gen.rs</td>"));
+    assert!(html.contains("style=\"width:50.0%\""));
+    assert!(!html.contains("src=\"http"), "report must not load external assets");
+  }
+
+  #[test]
+  fn test_render_clean_run() {
+    let html = render(&[], 0);
+    assert!(html.contains("No issues found"));
+    assert!(!html.contains("<h2>Files</h2>"));
+  }
+}
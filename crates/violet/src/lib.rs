@@ -1,10 +1,16 @@
 //! Language-agnostic code complexity analysis using information theory
 
+pub mod cascade;
 pub mod chunking;
+pub mod ci;
+pub mod cli;
 pub mod config;
 pub mod directives;
+pub mod html;
+pub mod ratchet;
 pub mod scoring;
 pub mod simplicity;
+pub mod traversal;
 
 pub use config::VioletConfig;
 pub use simplicity::{analyze_file, FileAnalysis};
@@ -0,0 +1,227 @@
+//! Per-file score ceilings for adopting violet on an existing codebase
+//!
+//! `violet ratchet` records the worst chunk score of every file that is over its
+//! threshold. While the ratchet file exists, those files are held to their recorded
+//! score instead of the threshold: they may not get worse, and recording again locks
+//! in any improvement. Ceilings only ever move down, either when a file improves or
+//! when `--tighten` lowers all of them by a percentage.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Ratchet file, read from the working directory like `violet.yaml`
+pub const RATCHET_FILE: &str = "violet-ratchet.json";
+
+/// Recorded ceilings keyed by path relative to the ratchet file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ratchet {
+  pub files: BTreeMap<String, f64>,
+}
+
+/// The worst score seen for a file in this run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+  pub key: String,
+  /// Worst chunk score above the file's threshold; None when the file passes
+  pub worst: Option<f64>,
+}
+
+/// What recording a ratchet changed
+#[derive(Debug, Default, PartialEq)]
+pub struct RecordOutcome {
+  /// Files whose ceiling was added or lowered
+  pub lowered: Vec<String>,
+  /// Files that no longer need a ceiling
+  pub cleared: Vec<String>,
+  /// Files over their threshold that an existing ratchet does not cover
+  pub unrecorded: Vec<String>,
+}
+
+impl Ratchet {
+  pub fn load(path: &Path) -> Result<Option<Self>> {
+    if !path.exists() {
+      return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)
+      .with_context(|| format!("Failed to read {}", path.display()))?;
+    let ratchet = serde_json::from_str(&content)
+      .with_context(|| format!("Failed to parse ratchet file {}", path.display()))?;
+    Ok(Some(ratchet))
+  }
+
+  pub fn save(&self, path: &Path) -> Result<()> {
+    let content = serde_json::to_string_pretty(self)?;
+    std::fs::write(path, format!("{content}\n"))
+      .with_context(|| format!("Failed to write {}", path.display()))
+  }
+
+  /// Recorded ceiling for a file, if it has one
+  pub fn ceiling(&self, key: &str) -> Option<f64> {
+    self.files.get(key).copied()
+  }
+
+  /// Record the scores of a run
+  ///
+  /// A fresh ratchet takes every failing file as it is. An existing one only lowers
+  /// ceilings: files it does not cover are reported rather than added, so a ratchet
+  /// can never be used to let new violations in.
+  pub fn record(&mut self, observed: &[Observation], fresh: bool) -> RecordOutcome {
+    let mut outcome = RecordOutcome::default();
+    if fresh {
+      self.files.clear();
+    }
+
+    for observation in observed {
+      let current = self.files.get(&observation.key).copied();
+      match (observation.worst.map(round_up), current) {
+        (None, Some(_)) => {
+          self.files.remove(&observation.key);
+          outcome.cleared.push(observation.key.clone());
+        }
+        (Some(worst), None) if fresh => {
+          self.files.insert(observation.key.clone(), worst);
+          outcome.lowered.push(observation.key.clone());
+        }
+        (Some(_), None) => outcome.unrecorded.push(observation.key.clone()),
+        (Some(worst), Some(ceiling)) if worst < ceiling => {
+          self.files.insert(observation.key.clone(), worst);
+          outcome.lowered.push(observation.key.clone());
+        }
+        _ => {}
+      }
+    }
+    outcome
+  }
+
+  /// Lower every ceiling by `percent`, dropping those that reach the file's threshold
+  pub fn tighten(&mut self, percent: f64, threshold: impl Fn(&str) -> f64) -> Vec<String> {
+    let factor = 1.0 - percent / 100.0;
+    let mut cleared = Vec::new();
+    self.files.retain(|key, ceiling| {
+      *ceiling = round_down(*ceiling * factor);
+      let keep = *ceiling > threshold(key);
+      if !keep {
+        cleared.push(key.clone());
+      }
+      keep
+    });
+    cleared
+  }
+
+  /// Drop ceilings for files that no longer exist under `root`
+  pub fn forget_missing(&mut self, root: &Path) -> Vec<String> {
+    let missing: Vec<String> =
+      self.files.keys().filter(|key| !root.join(key).exists()).cloned().collect();
+    for key in &missing {
+      self.files.remove(key);
+    }
+    missing
+  }
+}
+
+/// Ratchet key of a file: its path relative to `root`, with forward slashes
+pub fn key(path: &Path, root: &Path) -> String {
+  let relative = std::fs::canonicalize(path)
+    .ok()
+    .zip(std::fs::canonicalize(root).ok())
+    .and_then(|(path, root)| path.strip_prefix(root).ok().map(Path::to_path_buf))
+    .unwrap_or_else(|| path.strip_prefix("./").unwrap_or(path).to_path_buf());
+  relative.to_string_lossy().replace('\\', "/")
+}
+
+/// Parse a tightening step such as `5%` or `5`
+pub fn parse_percent(value: &str) -> Result<f64, String> {
+  let number = value.trim().trim_end_matches('%').trim();
+  match number.parse::<f64>() {
+    Ok(percent) if percent > 0.0 && percent < 100.0 => Ok(percent),
+    _ => Err(format!("expected a percentage between 0 and 100, got '{value}'")),
+  }
+}
+
+/// Scores are shown with two decimals; ceilings are rounded so they still hold the score
+fn round_up(score: f64) -> f64 {
+  (score * 100.0).ceil() / 100.0
+}
+
+fn round_down(score: f64) -> f64 {
+  (score * 100.0).floor() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn seen(key: &str, worst: Option<f64>) -> Observation {
+    Observation { key: key.to_string(), worst }
+  }
+
+  fn ceilings(entries: &[(&str, f64)]) -> Ratchet {
+    Ratchet { files: entries.iter().map(|(k, v)| (k.to_string(), *v)).collect() }
+  }
+
+  #[test]
+  fn test_fresh_record_takes_failing_files_as_they_are() {
+    let mut ratchet = Ratchet::default();
+    let outcome = ratchet.record(&[seen("a.rs", Some(9.123)), seen("b.rs", None)], true);
+    assert_eq!(ratchet, ceilings(&[("a.rs", 9.13)]));
+    assert_eq!(outcome.lowered, vec!["a.rs"]);
+  }
+
+  #[test]
+  fn test_existing_record_only_lowers() {
+    let mut ratchet =
+      ceilings(&[("worse.rs", 8.0), ("better.rs", 8.0), ("clean.rs", 8.0), ("other.rs", 8.0)]);
+    let outcome = ratchet.record(
+      &[
+        seen("worse.rs", Some(9.0)),
+        seen("better.rs", Some(7.5)),
+        seen("clean.rs", None),
+        seen("new.rs", Some(7.0)),
+      ],
+      false,
+    );
+
+    assert_eq!(ratchet, ceilings(&[("worse.rs", 8.0), ("better.rs", 7.5), ("other.rs", 8.0)]));
+    assert_eq!(outcome.lowered, vec!["better.rs"]);
+    assert_eq!(outcome.cleared, vec!["clean.rs"]);
+    assert_eq!(outcome.unrecorded, vec!["new.rs"]);
+  }
+
+  #[test]
+  fn test_tighten_lowers_ceilings_down_to_the_threshold() {
+    let mut ratchet = ceilings(&[("a.rs", 10.0), ("b.rs", 6.2)]);
+    let cleared = ratchet.tighten(5.0, |_| 6.0);
+    assert_eq!(ratchet, ceilings(&[("a.rs", 9.5)]));
+    assert_eq!(cleared, vec!["b.rs"]);
+  }
+
+  #[test]
+  fn test_parse_percent() {
+    assert_eq!(parse_percent("5%"), Ok(5.0));
+    assert_eq!(parse_percent("2.5"), Ok(2.5));
+    assert!(parse_percent("0%").is_err());
+    assert!(parse_percent("100").is_err());
+    assert!(parse_percent("lots").is_err());
+  }
+
+  #[test]
+  fn test_save_load_and_keys() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join(RATCHET_FILE);
+    assert_eq!(Ratchet::load(&path).unwrap(), None);
+
+    let src = temp_dir.path().join("src");
+    std::fs::create_dir(&src).unwrap();
+    std::fs::write(src.join("main.rs"), "fn main() {}").unwrap();
+    let key = key(&src.join("main.rs"), temp_dir.path());
+    assert_eq!(key, "src/main.rs");
+
+    let mut ratchet = ceilings(&[(key.as_str(), 7.0), ("gone.rs", 7.0)]);
+    assert_eq!(ratchet.forget_missing(temp_dir.path()), vec!["gone.rs"]);
+    ratchet.save(&path).unwrap();
+    assert_eq!(Ratchet::load(&path).unwrap(), Some(ratchet));
+  }
+}
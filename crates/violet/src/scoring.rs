@@ -2,7 +2,10 @@
 //!
 //! Provides functions for calculating complexity scores and analyzing code chunks.
 
+use crate::config::PenaltyPattern;
+use crate::directives::Scope;
 use regex::Regex;
+use std::path::Path;
 
 /// Breakdown showing which factors contribute to complexity
 #[derive(Debug, Clone)]
@@ -62,6 +65,30 @@ pub fn syntactics(line: &str) -> f64 {
   special_regex.find_iter(line.trim()).count() as f64
 }
 
+/// Configured penalty patterns compiled for one file
+#[derive(Debug, Clone, Default)]
+pub struct PatternPenalties {
+  patterns: Vec<(Regex, f64)>,
+}
+
+impl PatternPenalties {
+  /// The patterns that apply to `file_path`; ones that fail to compile are skipped
+  pub fn new<P: AsRef<Path>>(patterns: &[PenaltyPattern], file_path: P) -> Self {
+    let patterns = patterns
+      .iter()
+      .filter(|pattern| pattern.applies_to(&file_path))
+      .filter_map(|pattern| Regex::new(&pattern.pattern).ok().map(|regex| (regex, pattern.weight)))
+      .collect();
+    Self { patterns }
+  }
+
+  /// Extra syntactics for a line: each match counts as its pattern's weight
+  pub fn weight(&self, line: &str) -> f64 {
+    let line = line.trim();
+    self.patterns.iter().map(|(regex, weight)| regex.find_iter(line).count() as f64 * weight).sum()
+  }
+}
+
 pub fn depth(line: &str) -> f64 {
   get_indents(line) as f64
 }
@@ -78,16 +105,93 @@ pub fn complexity(
   syntactic_penalty: f64,
 ) -> f64 {
   let lines: Vec<&str> = chunk.lines().collect();
-  let mut depth_total = 0.0;
-  let mut verbosity_total = 0.0;
-  let mut syntactic_total = 0.0;
-
-  for line in lines {
-    depth_total += punish(depth(line), depth_penalty);
-    verbosity_total += punish(verbosity(line), verbosity_penalty);
-    syntactic_total += punish(syntactics(line), syntactic_penalty);
-  }
+  let patterns = PatternPenalties::default();
+  scoped_complexity(&lines, &[], &patterns, depth_penalty, verbosity_penalty, syntactic_penalty)
+}
+
+/// Complexity of lines, leaving out the subscores each line's scope switches off
+///
+/// Lines past the end of `scopes` are scored in full.
+pub fn scoped_complexity(
+  lines: &[&str],
+  scopes: &[Scope],
+  patterns: &PatternPenalties,
+  depth_penalty: f64,
+  verbosity_penalty: f64,
+  syntactic_penalty: f64,
+) -> f64 {
+  total_complexity(&line_scores(
+    lines,
+    scopes,
+    patterns,
+    depth_penalty,
+    verbosity_penalty,
+    syntactic_penalty,
+  ))
+}
+
+/// What one line measures and adds to its chunk's score
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineScore {
+  pub depth: f64,
+  pub verbosity: f64,
+  /// Special characters plus `pattern_weight`
+  pub syntactics: f64,
+  /// Extra syntactics from configured penalty patterns
+  pub pattern_weight: f64,
+  /// Subscores switched off for the line by directives
+  pub scope: Scope,
+  /// `penalty ^ measurement` for each subscore, 0 where switched off
+  pub depth_term: f64,
+  pub verbosity_term: f64,
+  pub syntactic_term: f64,
+}
+
+/// Measurements and penalized terms for each line
+pub fn line_scores(
+  lines: &[&str],
+  scopes: &[Scope],
+  patterns: &PatternPenalties,
+  depth_penalty: f64,
+  verbosity_penalty: f64,
+  syntactic_penalty: f64,
+) -> Vec<LineScore> {
+  let term =
+    |off: bool, measured: f64, penalty: f64| if off { 0.0 } else { punish(measured, penalty) };
 
+  lines
+    .iter()
+    .enumerate()
+    .map(|(i, line)| {
+      let scope = scopes.get(i).copied().unwrap_or_default();
+      let pattern_weight = patterns.weight(line);
+      let (depth, verbosity) = (depth(line), verbosity(line));
+      let syntactics = syntactics(line) + pattern_weight;
+      LineScore {
+        depth,
+        verbosity,
+        syntactics,
+        pattern_weight,
+        scope,
+        depth_term: term(scope.depth, depth, depth_penalty),
+        verbosity_term: term(scope.verbosity, verbosity, verbosity_penalty),
+        syntactic_term: term(scope.syntactics, syntactics, syntactic_penalty),
+      }
+    })
+    .collect()
+}
+
+/// Sum of every line's terms, per subscore: depth, verbosity, syntactics
+pub fn term_totals(scores: &[LineScore]) -> (f64, f64, f64) {
+  let depth_total: f64 = scores.iter().map(|score| score.depth_term).sum();
+  let verbosity_total: f64 = scores.iter().map(|score| score.verbosity_term).sum();
+  let syntactic_total: f64 = scores.iter().map(|score| score.syntactic_term).sum();
+  (depth_total, verbosity_total, syntactic_total)
+}
+
+/// Chunk score from its lines' terms
+pub fn total_complexity(scores: &[LineScore]) -> f64 {
+  let (depth_total, verbosity_total, syntactic_total) = term_totals(scores);
   let sum = depth_total + verbosity_total + syntactic_total;
 
   // Natural log for information-theoretic scaling
@@ -105,15 +209,30 @@ pub fn chunk_breakdown(
   _syntactic_penalty: f64,
 ) -> ComplexityBreakdown {
   let lines: Vec<&str> = chunk.lines().collect();
+  scoped_breakdown(&lines, &[], &PatternPenalties::default())
+}
 
+/// Component breakdown of lines, leaving out the subscores each line's scope switches off
+pub fn scoped_breakdown(
+  lines: &[&str],
+  scopes: &[Scope],
+  patterns: &PatternPenalties,
+) -> ComplexityBreakdown {
   let mut total_depth = 0.0;
   let mut total_verbosity = 0.0;
   let mut total_syntactic = 0.0;
 
-  for line in lines {
-    total_depth += depth(line);
-    total_verbosity += verbosity(line);
-    total_syntactic += syntactics(line);
+  for (i, line) in lines.iter().enumerate() {
+    let scope = scopes.get(i).copied().unwrap_or_default();
+    if !scope.depth {
+      total_depth += depth(line);
+    }
+    if !scope.verbosity {
+      total_verbosity += verbosity(line);
+    }
+    if !scope.syntactics {
+      total_syntactic += syntactics(line) + patterns.weight(line);
+    }
   }
 
   breakdown(total_depth, total_verbosity, total_syntactic)
@@ -163,6 +282,52 @@ mod tests {
     assert_eq!(get_indents("\t  partial"), 2);
   }
 
+  #[test]
+  fn test_line_scores_add_up_to_complexity() {
+    let lines = ["fn f() {", "    return g(x);", "}"];
+    let scopes = [Scope::default(), Scope { depth: true, ..Scope::default() }];
+    let patterns = PatternPenalties::default();
+    let scores = line_scores(&lines, &scopes, &patterns, 2.0, 1.1, 1.5);
+
+    assert_eq!(scores[1].depth, 2.0);
+    assert_eq!(scores[1].depth_term, 0.0);
+    assert_eq!(scores[1].syntactics, 3.0);
+    assert_eq!(scores[1].syntactic_term, 1.5_f64.powf(3.0));
+    assert_eq!(scores[2].scope, Scope::default());
+
+    let (depth_total, verbosity_total, syntactic_total) = term_totals(&scores);
+    let expected = (depth_total + verbosity_total + syntactic_total).ln();
+    assert_eq!(total_complexity(&scores), expected);
+    assert_eq!(scoped_complexity(&lines, &scopes, &patterns, 2.0, 1.1, 1.5), expected);
+  }
+
+  #[test]
+  fn test_penalty_patterns_add_to_syntactics() {
+    let unwrap = PenaltyPattern {
+      pattern: r"\.unwrap\(\)".to_string(),
+      weight: 2.5,
+      extensions: vec![".rs".to_string()],
+    };
+    let any = PenaltyPattern {
+      pattern: r":\s*any\b".to_string(),
+      weight: 4.0,
+      extensions: vec![".ts".to_string()],
+    };
+    let patterns = PatternPenalties::new(&[unwrap, any], "src/lib.rs");
+    let line = "    let x = a.unwrap().b.unwrap(); // any: any";
+    assert_eq!(patterns.weight(line), 5.0);
+
+    let plain = line_scores(&[line], &[], &PatternPenalties::default(), 2.0, 1.1, 1.5);
+    let scored = line_scores(&[line], &[], &patterns, 2.0, 1.1, 1.5);
+    assert_eq!(scored[0].pattern_weight, 5.0);
+    assert_eq!(scored[0].syntactics, plain[0].syntactics + 5.0);
+    assert!(scored[0].syntactic_term > plain[0].syntactic_term);
+    assert_eq!(scored[0].depth_term, plain[0].depth_term);
+
+    let off = [Scope { syntactics: true, ..Scope::default() }];
+    assert_eq!(line_scores(&[line], &off, &patterns, 2.0, 1.1, 1.5)[0].syntactic_term, 0.0);
+  }
+
   #[test]
   fn test_create_breakdown() {
     let bd = breakdown(10.0, 20.0, 30.0);
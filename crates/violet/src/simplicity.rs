@@ -2,7 +2,7 @@
 
 use crate::chunking;
 use crate::config;
-use crate::directives;
+use crate::directives::{self, Scope};
 use crate::scoring;
 use std::fs;
 use std::path::Path;
@@ -12,9 +12,12 @@ use std::path::Path;
 #[derive(Debug)]
 struct ChunkAnalysisContext<'a> {
   lines: &'a [&'a str],
+  /// Subscores switched off for each line by scoped directives
+  scopes: &'a [Scope],
   threshold: f64,
   ignore_patterns: &'a [String],
   penalties: &'a config::PenaltyConfig,
+  patterns: &'a scoring::PatternPenalties,
 }
 
 #[derive(Debug, Clone)]
@@ -31,27 +34,41 @@ fn ignored_file_analysis(path: &Path) -> FileAnalysis {
 
 /// Average complexity across all chunks in file
 pub fn average_chunk_complexity(file_content: &str, penalties: &config::PenaltyConfig) -> f64 {
+  average_scoped_complexity(file_content, &[], &scoring::PatternPenalties::default(), penalties)
+}
+
+/// Average complexity across all chunks, leaving out subscores switched off per line
+fn average_scoped_complexity(
+  file_content: &str,
+  scopes: &[Scope],
+  patterns: &scoring::PatternPenalties,
+  penalties: &config::PenaltyConfig,
+) -> f64 {
   let chunks = chunking::find_chunks(file_content);
   if chunks.is_empty() {
     return 0.0;
   }
 
-  let chunk_scores = calculate_chunk_scores(file_content, &chunks, penalties);
+  let chunk_scores = calculate_chunk_scores(file_content, scopes, &chunks, patterns, penalties);
   chunk_scores.iter().sum::<f64>() / chunks.len() as f64
 }
 
 fn calculate_chunk_scores(
   file_content: &str,
+  scopes: &[Scope],
   chunks: &[(usize, usize)],
+  patterns: &scoring::PatternPenalties,
   penalties: &config::PenaltyConfig,
 ) -> Vec<f64> {
   let lines: Vec<&str> = file_content.lines().collect();
   chunks
     .iter()
-    .map(|chunk| {
-      let chunk_content = lines[chunk.0..chunk.1].join("\n");
-      scoring::complexity(
-        &chunk_content,
+    .map(|&(start, end)| {
+      let chunk_scopes = chunk_scopes(scopes, start, end, &lines[start..end].join("\n"));
+      scoring::scoped_complexity(
+        &lines[start..end],
+        &chunk_scopes,
+        patterns,
         penalties.depth,
         penalties.verbosity,
         penalties.syntactics,
@@ -60,6 +77,12 @@ fn calculate_chunk_scores(
     .collect()
 }
 
+/// Scopes of a chunk's lines, widened by any chunk directive inside it
+fn chunk_scopes(scopes: &[Scope], start: usize, end: usize, chunk_content: &str) -> Vec<Scope> {
+  let chunk_scope = directives::chunk_scope(chunk_content);
+  (start..end).map(|i| scopes.get(i).copied().unwrap_or_default().union(chunk_scope)).collect()
+}
+
 /// Analyze file and identify complexity hotspots
 pub fn analyze_file<P: AsRef<Path>>(
   file_path: P,
@@ -68,21 +91,27 @@ pub fn analyze_file<P: AsRef<Path>>(
   let path = file_path.as_ref();
   let content = fs::read_to_string(path)?;
 
-  let preprocessed = match directives::preprocess_file(&content) {
+  let preprocessed = match directives::preprocess(&content) {
     Some(processed) => processed,
     None => return Ok(ignored_file_analysis(path)),
   };
 
-  if preprocessed.trim().is_empty() {
+  if preprocessed.content.trim().is_empty() {
     return Ok(empty_file_analysis(path));
   }
 
   let threshold = config::get_threshold(config, path);
-  let chunks = chunking::find_chunks(&preprocessed);
-  let lines: Vec<&str> = preprocessed.lines().collect();
-
-  let issues = find_issues(chunks, &lines, threshold, config);
-  let file_average_score = average_chunk_complexity(&preprocessed, &config.complexity.penalties);
+  let chunks = chunking::find_chunks(&preprocessed.content);
+  let lines: Vec<&str> = preprocessed.content.lines().collect();
+  let patterns = scoring::PatternPenalties::new(&config.penalty_patterns, path);
+
+  let issues = find_issues(chunks, &lines, &preprocessed.scopes, &patterns, threshold, config);
+  let file_average_score = average_scoped_complexity(
+    &preprocessed.content,
+    &preprocessed.scopes,
+    &patterns,
+    &config.complexity.penalties,
+  );
 
   Ok(FileAnalysis {
     file_path: path.to_path_buf(),
@@ -92,6 +121,69 @@ pub fn analyze_file<P: AsRef<Path>>(
   })
 }
 
+/// How one chunk's score was computed
+#[derive(Debug, Clone)]
+pub struct ChunkExplanation {
+  /// Line numbers as lint output reports them
+  pub start_line: usize,
+  pub end_line: usize,
+  /// Rounded as it is for the threshold comparison
+  pub score: f64,
+  /// Skipped because it matches a configured ignore pattern
+  pub ignored: bool,
+  pub lines: Vec<scoring::LineScore>,
+  /// The text of each line, after directives are applied
+  pub text: Vec<String>,
+}
+
+/// The chunks of a file overlapping a line range, and how each was scored
+///
+/// `range` is inclusive and uses lint output's line numbers; `None` covers the whole
+/// file. Returns `None` when a directive ignores the entire file.
+pub fn explain_file<P: AsRef<Path>>(
+  file_path: P,
+  config: &config::VioletConfig,
+  range: Option<(usize, usize)>,
+) -> Result<Option<Vec<ChunkExplanation>>, Box<dyn std::error::Error>> {
+  let path = file_path.as_ref();
+  let content = fs::read_to_string(path)?;
+  let Some(preprocessed) = directives::preprocess(&content) else {
+    return Ok(None);
+  };
+
+  let lines: Vec<&str> = preprocessed.content.lines().collect();
+  let (first, last) = range.unwrap_or((1, usize::MAX));
+  let penalties = &config.complexity.penalties;
+  let patterns = scoring::PatternPenalties::new(&config.penalty_patterns, path);
+
+  let explanations = chunking::find_chunks(&preprocessed.content)
+    .into_iter()
+    .filter(|&(start, end)| end > start && start < last && end >= first)
+    .map(|(start, end)| {
+      let chunk_content = lines[start..end].join("\n");
+      let scopes = chunk_scopes(&preprocessed.scopes, start, end, &chunk_content);
+      let scores = scoring::line_scores(
+        &lines[start..end],
+        &scopes,
+        &patterns,
+        penalties.depth,
+        penalties.verbosity,
+        penalties.syntactics,
+      );
+      ChunkExplanation {
+        start_line: start + 1,
+        end_line: end + 1,
+        score: (scoring::total_complexity(&scores) * 100.0).round() / 100.0,
+        ignored: directives::has_ignored_patterns(&chunk_content, &config.ignore_patterns),
+        lines: scores,
+        text: lines[start..end].iter().map(|line| line.to_string()).collect(),
+      }
+    })
+    .collect();
+
+  Ok(Some(explanations))
+}
+
 fn empty_file_analysis(path: &Path) -> FileAnalysis {
   FileAnalysis { file_path: path.to_path_buf(), average_score: 0.0, issues: vec![], ignored: false }
 }
@@ -99,14 +191,18 @@ fn empty_file_analysis(path: &Path) -> FileAnalysis {
 fn find_issues(
   chunks: Vec<(usize, usize)>,
   lines: &[&str],
+  scopes: &[Scope],
+  patterns: &scoring::PatternPenalties,
   threshold: f64,
   config: &config::VioletConfig,
 ) -> Vec<scoring::ComplexityRegion> {
   let context = ChunkAnalysisContext {
     lines,
+    scopes,
     threshold,
     ignore_patterns: &config.ignore_patterns,
     penalties: &config.complexity.penalties,
+    patterns,
   };
 
   chunks.into_iter().filter_map(|(start, end)| analyze_chunk(start, end, &context)).collect()
@@ -127,8 +223,11 @@ fn analyze_chunk(
     return None;
   }
 
-  let raw_score = scoring::complexity(
-    &chunk_content,
+  let scopes = chunk_scopes(context.scopes, start, end, &chunk_content);
+  let raw_score = scoring::scoped_complexity(
+    &context.lines[start..end],
+    &scopes,
+    context.patterns,
     context.penalties.depth,
     context.penalties.verbosity,
     context.penalties.syntactics,
@@ -142,9 +241,9 @@ fn analyze_chunk(
       start,
       end,
       score,
-      &chunk_content,
       &context.lines[start..end],
-      context.penalties,
+      &scopes,
+      context.patterns,
     ))
   } else {
     None
@@ -155,28 +254,16 @@ fn create_complexity_region(
   start: usize,
   end: usize,
   score: f64,
-  chunk_content: &str,
   lines: &[&str],
-  penalties: &config::PenaltyConfig,
+  scopes: &[Scope],
+  patterns: &scoring::PatternPenalties,
 ) -> scoring::ComplexityRegion {
-  let breakdown = calculate_chunk_breakdown(chunk_content, penalties);
+  let breakdown = scoring::scoped_breakdown(lines, scopes, patterns);
   let preview = create_chunk_preview(lines);
 
   build_complexity_region(start, end, score, breakdown, preview)
 }
 
-fn calculate_chunk_breakdown(
-  chunk_content: &str,
-  penalties: &config::PenaltyConfig,
-) -> scoring::ComplexityBreakdown {
-  scoring::chunk_breakdown(
-    chunk_content,
-    penalties.depth,
-    penalties.verbosity,
-    penalties.syntactics,
-  )
-}
-
 fn build_complexity_region(
   start: usize,
   end: usize,
@@ -248,6 +335,76 @@ mod tests {
     assert!(total_score < 1000.0);
   }
 
+  #[test]
+  fn test_scoped_chunk_directive_drops_only_its_subscores() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let body = "fn complex() {\n    if deeply {\n        if nested {\n            return compute(a, b);\n        }\n    }\n}";
+    let plain = dir.path().join("plain.rs");
+    let scoped = dir.path().join("scoped.rs");
+    std::fs::write(&plain, body).unwrap();
+    std::fs::write(&scoped, format!("// violet ignore chunk depth\n{body}")).unwrap();
+
+    let config = config::VioletConfig::default();
+    let plain = analyze_file(&plain, &config).unwrap();
+    let scoped = analyze_file(&scoped, &config).unwrap();
+    assert!(!scoped.ignored);
+    assert!(scoped.average_score > 0.0);
+    assert!(scoped.average_score < plain.average_score);
+  }
+
+  #[test]
+  fn test_explain_file_matches_lint_scores() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("nested.rs");
+    let nested =
+      "fn nested() {\n    if a {\n        if b {\n            return c(d, e);\n        }\n    }\n}";
+    std::fs::write(&path, format!("fn one() {{\n    1\n}}\n\n{nested}\n")).unwrap();
+
+    let mut config = config::VioletConfig::default();
+    config.complexity.thresholds.default = 0.0;
+    let issues = analyze_file(&path, &config).unwrap().issues;
+
+    let all = explain_file(&path, &config, None).unwrap().unwrap();
+    assert_eq!(all.len(), issues.len());
+    for (explained, issue) in all.iter().zip(&issues) {
+      assert_eq!((explained.start_line, explained.end_line), (issue.start_line, issue.end_line));
+      assert_eq!(explained.score, issue.score);
+    }
+
+    let second = explain_file(&path, &config, Some((6, 7))).unwrap().unwrap();
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].start_line, 5);
+    assert_eq!(second[0].lines[3].depth, 6.0);
+    assert_eq!(second[0].text[0], "fn nested() {");
+  }
+
+  #[test]
+  fn test_penalty_patterns_raise_scores_for_matching_extensions() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let body = "fn load() {\n    let a = read().unwrap();\n    let b = parse(a).unwrap();\n}";
+    let rust = dir.path().join("load.rs");
+    let typescript = dir.path().join("load.ts");
+    std::fs::write(&rust, body).unwrap();
+    std::fs::write(&typescript, body).unwrap();
+
+    let plain = config::VioletConfig::default();
+    let penalized = config::VioletConfig {
+      penalty_patterns: vec![config::PenaltyPattern {
+        pattern: r"\.unwrap\(\)".to_string(),
+        weight: 5.0,
+        extensions: vec![".rs".to_string()],
+      }],
+      ..Default::default()
+    };
+
+    let before = analyze_file(&rust, &plain).unwrap().average_score;
+    assert!(analyze_file(&rust, &penalized).unwrap().average_score > before);
+    assert_eq!(analyze_file(&typescript, &penalized).unwrap().average_score, before);
+
+    let explained = explain_file(&rust, &penalized, None).unwrap().unwrap();
+    assert_eq!(explained[0].lines[1].pattern_weight, 5.0);
+  }
+
   #[test]
   fn test_complexity_comparison() {
     let simple_content = "fn simple() {\n    return 42;\n}";
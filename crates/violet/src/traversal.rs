@@ -0,0 +1,295 @@
+//! Directory traversal
+//!
+//! Walks analysis roots while skipping git submodules (as listed in `.gitmodules`)
+//! and following symlinks without looping or counting the same directory twice.
+//! Each directory's entries are filtered with the config that applies there.
+
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cascade::ConfigTree;
+use crate::config::VioletConfig;
+
+/// Controls for how directories are walked
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraversalOptions {
+  /// Descend into git submodules instead of skipping them
+  pub include_submodules: bool,
+}
+
+/// Why a directory was not descended into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+  /// Listed as a submodule in a `.gitmodules` file
+  Submodule,
+  /// A symlink leading back to one of its own parent directories
+  SymlinkCycle,
+  /// Already walked through another path (usually a symlink)
+  AlreadyVisited,
+}
+
+impl std::fmt::Display for SkipReason {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SkipReason::Submodule => write!(f, "git submodule"),
+      SkipReason::SymlinkCycle => write!(f, "symlink cycle"),
+      SkipReason::AlreadyVisited => write!(f, "already visited"),
+    }
+  }
+}
+
+/// A directory that was left out of the walk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRoot {
+  pub path: PathBuf,
+  pub reason: SkipReason,
+}
+
+/// Files found under a root, plus the directories that were skipped
+#[derive(Debug, Default)]
+pub struct Traversal {
+  pub files: Vec<PathBuf>,
+  pub skipped: Vec<SkippedRoot>,
+}
+
+/// Mutable state threaded through the walk
+struct Walker<'a> {
+  tree: &'a ConfigTree,
+  options: TraversalOptions,
+  submodules: HashSet<PathBuf>,
+  visited_dirs: HashSet<PathBuf>,
+  visited_files: HashSet<PathBuf>,
+  ancestors: Vec<PathBuf>,
+  result: Traversal,
+}
+
+/// Recursively collect files under `root`, respecting ignore patterns
+pub fn collect_files(
+  root: &Path,
+  tree: &ConfigTree,
+  options: TraversalOptions,
+) -> Result<Traversal> {
+  let mut walker = Walker {
+    tree,
+    options,
+    submodules: HashSet::new(),
+    visited_dirs: HashSet::new(),
+    visited_files: HashSet::new(),
+    ancestors: Vec::new(),
+    result: Traversal::default(),
+  };
+
+  if !options.include_submodules {
+    if let Some(repo_root) = find_repo_root(root) {
+      walker.submodules.extend(submodule_paths(&repo_root));
+    }
+  }
+
+  walker.walk(root)?;
+  Ok(walker.result)
+}
+
+impl Walker<'_> {
+  fn walk(&mut self, dir: &Path) -> Result<()> {
+    let Ok(canonical) = fs::canonicalize(dir) else {
+      return Ok(());
+    };
+    let config = self.tree.for_dir(&canonical)?;
+    self.visited_dirs.insert(canonical.clone());
+    self.ancestors.push(canonical.clone());
+
+    if !self.options.include_submodules {
+      self.submodules.extend(submodule_paths(&canonical));
+    }
+
+    if let Ok(entries) = fs::read_dir(dir) {
+      let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+      paths.sort();
+      for path in paths {
+        self.visit(path, &config)?;
+      }
+    }
+
+    self.ancestors.pop();
+    Ok(())
+  }
+
+  fn visit(&mut self, path: PathBuf, config: &VioletConfig) -> Result<()> {
+    if self.tree.ignores(config, &path) {
+      return Ok(());
+    }
+
+    let Ok(canonical) = fs::canonicalize(&path) else {
+      return Ok(());
+    };
+
+    if path.is_file() {
+      if self.visited_files.insert(canonical) {
+        self.result.files.push(path);
+      }
+    } else if path.is_dir() {
+      match self.skip_reason(&canonical) {
+        Some(reason) => self.result.skipped.push(SkippedRoot { path, reason }),
+        None => self.walk(&path)?,
+      }
+    }
+    Ok(())
+  }
+
+  fn skip_reason(&self, canonical: &Path) -> Option<SkipReason> {
+    if self.ancestors.iter().any(|ancestor| ancestor == canonical) {
+      Some(SkipReason::SymlinkCycle)
+    } else if self.visited_dirs.contains(canonical) {
+      Some(SkipReason::AlreadyVisited)
+    } else if self.submodules.contains(canonical) {
+      Some(SkipReason::Submodule)
+    } else {
+      None
+    }
+  }
+}
+
+/// Closest directory at or above `path` that looks like a git checkout
+fn find_repo_root(path: &Path) -> Option<PathBuf> {
+  let canonical = fs::canonicalize(path).ok()?;
+  canonical
+    .ancestors()
+    .find(|dir| dir.join(".git").exists() || dir.join(".gitmodules").is_file())
+    .map(Path::to_path_buf)
+}
+
+/// Canonical submodule directories declared in `<dir>/.gitmodules`
+pub fn submodule_paths(dir: &Path) -> Vec<PathBuf> {
+  let Ok(content) = fs::read_to_string(dir.join(".gitmodules")) else {
+    return Vec::new();
+  };
+
+  parse_gitmodules(&content)
+    .into_iter()
+    .filter_map(|relative| fs::canonicalize(dir.join(relative)).ok())
+    .collect()
+}
+
+/// Extract the `path = ...` entries from a `.gitmodules` file
+pub fn parse_gitmodules(content: &str) -> Vec<String> {
+  content
+    .lines()
+    .filter_map(|line| {
+      let (key, value) = line.trim().split_once('=')?;
+      (key.trim() == "path").then(|| value.trim().to_string())
+    })
+    .filter(|path| !path.is_empty())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn touch(path: &Path) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, "fn main() {}\n").unwrap();
+  }
+
+  fn flat() -> ConfigTree {
+    ConfigTree::flat(VioletConfig::default())
+  }
+
+  fn names(traversal: &Traversal, root: &Path) -> Vec<String> {
+    let mut names: Vec<String> = traversal
+      .files
+      .iter()
+      .map(|f| f.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+      .collect();
+    names.sort();
+    names
+  }
+
+  #[test]
+  fn test_parse_gitmodules() {
+    let content = r#"
+[submodule "vendor/lib"]
+	path = vendor/lib
+	url = https://example.com/lib.git
+[submodule "tools"]
+	path=tools
+"#;
+    assert_eq!(parse_gitmodules(content), vec!["vendor/lib", "tools"]);
+  }
+
+  #[test]
+  fn test_submodules_skipped_by_default() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    fs::write(root.join(".gitmodules"), "[submodule \"v\"]\n\tpath = vendor/lib\n").unwrap();
+    touch(&root.join("src/main.rs"));
+    touch(&root.join("vendor/lib/lib.rs"));
+
+    let traversal = collect_files(root, &flat(), TraversalOptions::default()).unwrap();
+
+    assert!(!names(&traversal, root).contains(&"vendor/lib/lib.rs".to_string()));
+    assert_eq!(traversal.skipped.len(), 1);
+    assert_eq!(traversal.skipped[0].reason, SkipReason::Submodule);
+  }
+
+  #[test]
+  fn test_submodules_included_on_request() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    fs::write(root.join(".gitmodules"), "[submodule \"v\"]\n\tpath = vendor/lib\n").unwrap();
+    touch(&root.join("vendor/lib/lib.rs"));
+
+    let options = TraversalOptions { include_submodules: true };
+    let traversal = collect_files(root, &flat(), options).unwrap();
+
+    assert!(names(&traversal, root).contains(&"vendor/lib/lib.rs".to_string()));
+    assert!(traversal.skipped.is_empty());
+  }
+
+  #[test]
+  fn test_nested_config_ignores_apply_below_it() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    fs::write(root.join("violet.yaml"), "ignore_files: []\n").unwrap();
+    touch(&root.join("generated/keep.rs"));
+    touch(&root.join("legacy/generated/api.rs"));
+    touch(&root.join("legacy/lib.rs"));
+    fs::write(root.join("legacy/violet.yaml"), "ignore_files:\n  - \"generated/**\"\n").unwrap();
+
+    let tree = ConfigTree::at(root).unwrap();
+    let traversal = collect_files(root, &tree, TraversalOptions::default()).unwrap();
+
+    assert_eq!(names(&traversal, root), vec!["generated/keep.rs", "legacy/lib.rs"]);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_symlink_cycle_is_broken() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    touch(&root.join("src/main.rs"));
+    std::os::unix::fs::symlink(root.join("src"), root.join("src/loop")).unwrap();
+
+    let traversal = collect_files(root, &flat(), TraversalOptions::default()).unwrap();
+
+    assert_eq!(names(&traversal, root), vec!["src/main.rs"]);
+    assert_eq!(traversal.skipped[0].reason, SkipReason::SymlinkCycle);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_symlinked_directory_counted_once() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    touch(&root.join("real/lib.rs"));
+    std::os::unix::fs::symlink(root.join("real"), root.join("alias")).unwrap();
+
+    let traversal = collect_files(root, &flat(), TraversalOptions::default()).unwrap();
+
+    assert_eq!(traversal.files.len(), 1);
+    assert_eq!(traversal.skipped[0].reason, SkipReason::AlreadyVisited);
+  }
+}